@@ -0,0 +1,290 @@
+//! Accessibility audit of exported pages.
+//!
+//! With `--audit-accessibility`, each page's storage content is checked for
+//! problems that survive the conversion and make the mirrored docs harder to
+//! use with assistive technology: images without alt text, tables without a
+//! header row, headings that skip levels, and preserved text colors with too
+//! little contrast against their background. Findings are reported per page
+//! and counted in the end-of-run summary.
+
+use std::fmt;
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+use serde::Serialize;
+
+use crate::markdown::html_entities::preprocess_html_entities;
+use crate::markdown::utils::{get_attribute, get_element_text, matches_tag, wrap_with_namespaces};
+
+/// Minimum contrast ratio for normal text (WCAG 2 level AA).
+const MIN_CONTRAST_RATIO: f64 = 4.5;
+
+/// Background assumed for colored text without an explicit background.
+const DEFAULT_BACKGROUND: Rgb = Rgb(255, 255, 255);
+
+/// Longest text excerpt quoted in a finding.
+const MAX_EXCERPT_CHARS: usize = 40;
+
+/// Category of an accessibility finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+  /// An image has no alternative text.
+  MissingAltText,
+  /// A table has no header cells.
+  TableWithoutHeaders,
+  /// A heading is more than one level deeper than the previous heading.
+  HeadingLevelJump,
+  /// Colored text does not contrast enough with its background.
+  LowContrast,
+}
+
+/// A single accessibility finding in a page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessibilityIssue {
+  /// Category of the finding.
+  pub kind: IssueKind,
+  /// Human-readable description naming the offending element.
+  pub message: String,
+}
+
+impl fmt::Display for AccessibilityIssue {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+/// Audit a page's storage content.
+///
+/// # Arguments
+/// * `storage_content` - Confluence storage format of the page.
+///
+/// # Returns
+/// The findings in document order.
+///
+/// # Errors
+/// Returns an error when the storage content cannot be parsed.
+pub fn audit_storage(storage_content: &str) -> Result<Vec<AccessibilityIssue>> {
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document =
+    Document::parse(&wrapped).context("Failed to parse Confluence storage content for the accessibility audit")?;
+
+  let mut issues = Vec::new();
+  let mut previous_heading = None;
+  let mut table_number = 0;
+
+  for node in document.descendants().filter(Node::is_element) {
+    if matches_tag(node, "ac:image") {
+      check_confluence_image(node, &mut issues);
+    } else if matches_tag(node, "img") {
+      if get_attribute(node, "alt").is_none_or(|alt| alt.trim().is_empty()) {
+        let source = get_attribute(node, "src").unwrap_or_default();
+        issues.push(issue(
+          IssueKind::MissingAltText,
+          format!("Image {source} has no alt text"),
+        ));
+      }
+    } else if matches_tag(node, "table") {
+      table_number += 1;
+      if !node.descendants().any(|cell| matches_tag(cell, "th")) {
+        issues.push(issue(
+          IssueKind::TableWithoutHeaders,
+          format!("Table {table_number} has no header row"),
+        ));
+      }
+    } else if let Some(level) = heading_level(node) {
+      if let Some(previous) = previous_heading
+        && level > previous + 1
+      {
+        issues.push(issue(
+          IssueKind::HeadingLevelJump,
+          format!(
+            "Heading \"{}\" jumps from h{previous} to h{level}",
+            excerpt(&get_element_text(node))
+          ),
+        ));
+      }
+      previous_heading = Some(level);
+    } else if let Some(color) = get_attribute(node, "style").and_then(|style| style_property(&style, "color")) {
+      check_contrast(node, &color, &mut issues);
+    }
+  }
+
+  Ok(issues)
+}
+
+fn issue(kind: IssueKind, message: String) -> AccessibilityIssue {
+  AccessibilityIssue { kind, message }
+}
+
+/// Flag an `ac:image` without an `ac:alt` attribute.
+fn check_confluence_image(node: Node, issues: &mut Vec<AccessibilityIssue>) {
+  if get_attribute(node, "ac:alt").is_some_and(|alt| !alt.trim().is_empty()) {
+    return;
+  }
+
+  let source = node
+    .descendants()
+    .find_map(|child| get_attribute(child, "ri:filename").or_else(|| get_attribute(child, "ri:value")))
+    .unwrap_or_else(|| "(unnamed)".to_string());
+  issues.push(issue(
+    IssueKind::MissingAltText,
+    format!("Image {source} has no alt text"),
+  ));
+}
+
+/// Flag colored text whose contrast with its background is below the minimum.
+fn check_contrast(node: Node, color: &str, issues: &mut Vec<AccessibilityIssue>) {
+  let Some(foreground) = parse_color(color) else {
+    return;
+  };
+  // The closest explicit background, on the span itself or an ancestor
+  let background = node
+    .ancestors()
+    .find_map(|ancestor| get_attribute(ancestor, "style").and_then(|style| style_property(&style, "background-color")))
+    .and_then(|value| parse_color(&value))
+    .unwrap_or(DEFAULT_BACKGROUND);
+
+  let ratio = contrast_ratio(foreground, background);
+  if ratio < MIN_CONTRAST_RATIO {
+    issues.push(issue(
+      IssueKind::LowContrast,
+      format!(
+        "Text \"{}\" has contrast {ratio:.1}:1 ({foreground} on {background}), below {MIN_CONTRAST_RATIO}:1",
+        excerpt(&get_element_text(node))
+      ),
+    ));
+  }
+}
+
+/// Level of an `h1`-`h6` element.
+fn heading_level(node: Node) -> Option<u8> {
+  match node.tag_name().name().as_bytes() {
+    [b'h', digit @ b'1'..=b'6'] => Some(digit - b'0'),
+    _ => None,
+  }
+}
+
+/// Value of a property in an inline `style` attribute.
+fn style_property(style: &str, property: &str) -> Option<String> {
+  style.split(';').find_map(|declaration| {
+    let (name, value) = declaration.split_once(':')?;
+    name
+      .trim()
+      .eq_ignore_ascii_case(property)
+      .then(|| value.trim().trim_end_matches("!important").trim().to_string())
+  })
+}
+
+/// Shorten text for quoting in a finding.
+fn excerpt(text: &str) -> String {
+  let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+  if text.chars().count() <= MAX_EXCERPT_CHARS {
+    return text;
+  }
+  let shortened: String = text.chars().take(MAX_EXCERPT_CHARS - 1).collect();
+  format!("{}…", shortened.trim_end())
+}
+
+/// An sRGB color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rgb(u8, u8, u8);
+
+impl fmt::Display for Rgb {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+  }
+}
+
+/// Parse a CSS color in `#rgb`, `#rrggbb`, or `rgb(r, g, b)` notation.
+///
+/// Named colors and other notations are not checked.
+fn parse_color(value: &str) -> Option<Rgb> {
+  let value = value.trim().to_ascii_lowercase();
+  if let Some(hex) = value.strip_prefix('#').filter(|hex| hex.is_ascii()) {
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    return match hex.len() {
+      3 => {
+        let mut channels = hex.chars().map(|c| channel(&c.to_string().repeat(2)));
+        Some(Rgb(channels.next()??, channels.next()??, channels.next()??))
+      }
+      6 => Some(Rgb(channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+      _ => None,
+    };
+  }
+
+  let arguments = value
+    .strip_prefix("rgb(")
+    .or_else(|| value.strip_prefix("rgba("))?
+    .strip_suffix(')')?;
+  let mut channels = arguments.split(',').map(|part| part.trim().parse::<u8>().ok());
+  Some(Rgb(channels.next()??, channels.next()??, channels.next()??))
+}
+
+/// WCAG relative luminance of a color.
+fn relative_luminance(Rgb(r, g, b): Rgb) -> f64 {
+  let linear = |channel: u8| {
+    let value = f64::from(channel) / 255.0;
+    if value <= 0.03928 {
+      value / 12.92
+    } else {
+      ((value + 0.055) / 1.055).powf(2.4)
+    }
+  };
+  0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// WCAG contrast ratio between two colors, from 1 to 21.
+fn contrast_ratio(first: Rgb, second: Rgb) -> f64 {
+  let (first, second) = (relative_luminance(first), relative_luminance(second));
+  (first.max(second) + 0.05) / (first.min(second) + 0.05)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_audit_storage_flags_issues() {
+    let storage = r#"
+      <h1>Guide</h1>
+      <h3>Details</h3>
+      <ac:image><ri:attachment ri:filename="diagram.png" /></ac:image>
+      <ac:image ac:alt="Architecture"><ri:attachment ri:filename="arch.png" /></ac:image>
+      <table><tbody><tr><td>a</td><td>b</td></tr></tbody></table>
+      <table><tbody><tr><th>Name</th></tr><tr><td>x</td></tr></tbody></table>
+      <p><span style="color: #ffcc00;">Caution</span> and <span style="color: rgb(23, 43, 77);">fine</span></p>
+      <p><span style="background-color: #000000;"><span style="color: #333333;">Hidden</span></span></p>
+    "#;
+
+    let messages: Vec<_> = audit_storage(storage)
+      .unwrap()
+      .into_iter()
+      .map(|issue| issue.message)
+      .collect();
+
+    insta::assert_snapshot!(messages.join("\n"), @r#"
+    Heading "Details" jumps from h1 to h3
+    Image diagram.png has no alt text
+    Table 1 has no header row
+    Text "Caution" has contrast 1.5:1 (#ffcc00 on #ffffff), below 4.5:1
+    Text "Hidden" has contrast 1.7:1 (#333333 on #000000), below 4.5:1
+    "#);
+  }
+
+  #[test]
+  fn test_parse_color() {
+    assert_eq!(parse_color("#fff"), Some(Rgb(255, 255, 255)));
+    assert_eq!(parse_color("#1A2b3C"), Some(Rgb(0x1a, 0x2b, 0x3c)));
+    assert_eq!(parse_color("rgb(97, 189, 109)"), Some(Rgb(97, 189, 109)));
+    assert_eq!(parse_color("red"), None);
+  }
+
+  #[test]
+  fn test_contrast_ratio() {
+    let ratio = contrast_ratio(Rgb(0, 0, 0), Rgb(255, 255, 255));
+    assert!((ratio - 21.0).abs() < 0.01);
+    assert!((contrast_ratio(Rgb(119, 119, 119), DEFAULT_BACKGROUND) - 4.48).abs() < 0.01);
+  }
+}
@@ -6,9 +6,12 @@
 use roxmltree::Node;
 use tracing::debug;
 
-use crate::asciidoc::AsciiDocOptions;
+use crate::asciidoc::macros::{convert_macro_to_asciidoc, convert_task_list_to_asciidoc};
+use crate::asciidoc::{AsciiDocOptions, utils};
 use crate::markdown::html_entities::decode_html_entities;
-use crate::markdown::utils::{get_attribute, get_element_text, matches_tag, qualified_tag_name};
+use crate::markdown::utils::{
+  get_attribute, get_element_text, get_table_caption, get_table_summary, matches_tag, qualified_tag_name,
+};
 
 /// Converts an element and its children to AsciiDoc recursively.
 ///
@@ -102,29 +105,13 @@ fn convert_element_node(child: Node, options: &AsciiDocOptions) -> String {
 
   match local_name {
     // Headings - AsciiDoc uses = for headings
-    "h1" => {
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+      let level = usize::from(local_name.as_bytes()[1] - b'0');
       let content = convert_node_to_asciidoc(child, options);
-      result.push_str(&format!("\n= {}\n\n", content.trim()));
-    }
-    "h2" => {
-      let content = convert_node_to_asciidoc(child, options);
-      result.push_str(&format!("\n== {}\n\n", content.trim()));
-    }
-    "h3" => {
-      let content = convert_node_to_asciidoc(child, options);
-      result.push_str(&format!("\n=== {}\n\n", content.trim()));
-    }
-    "h4" => {
-      let content = convert_node_to_asciidoc(child, options);
-      result.push_str(&format!("\n==== {}\n\n", content.trim()));
-    }
-    "h5" => {
-      let content = convert_node_to_asciidoc(child, options);
-      result.push_str(&format!("\n===== {}\n\n", content.trim()));
-    }
-    "h6" => {
-      let content = convert_node_to_asciidoc(child, options);
-      result.push_str(&format!("\n====== {}\n\n", content.trim()));
+      match options.heading_overflow.apply(level, options.max_heading_level) {
+        Some(level) => result.push_str(&format!("\n{} {}\n\n", "=".repeat(level), content.trim())),
+        None => result.push_str(&format!("\n*{}*\n\n", content.trim())),
+      }
     }
 
     // Paragraphs
@@ -228,13 +215,23 @@ fn convert_element_node(child: Node, options: &AsciiDocOptions) -> String {
     }
 
     // Confluence-specific elements
+    "structured-macro" if matches_tag(child, "ac:structured-macro") => {
+      let convert = |node: Node| convert_node_to_asciidoc(node, options);
+      result.push_str(&convert_macro_to_asciidoc(child, &convert, options));
+    }
+    "task-list" if matches_tag(child, "ac:task-list") => {
+      result.push_str(&convert_task_list_to_asciidoc(child));
+    }
     "link" if matches_tag(child, "ac:link") => {
       result.push_str(&convert_confluence_link(child));
     }
 
     // Images
     "image" if matches_tag(child, "ac:image") => {
-      result.push_str(&convert_image_to_asciidoc(child));
+      let image = convert_image_to_asciidoc(child);
+      if !image.is_empty() {
+        result.push_str(&format!("\n{image}\n\n"));
+      }
     }
 
     // Layout elements - pass through content
@@ -337,14 +334,37 @@ fn convert_list_items(node: Node, options: &AsciiDocOptions, marker: &str, depth
 }
 
 /// Convert Confluence link to AsciiDoc.
+///
+/// External URLs become `url[text]`, attachment links `link:file[text]`, and
+/// page links fall back to the link body or the page title.
 fn convert_confluence_link(node: Node) -> String {
   // Try to find the link text
   let link_text = node
     .children()
     .find(|child| matches_tag(*child, "ac:link-body") || matches_tag(*child, "ac:plain-text-link-body"))
     .map(get_element_text)
+    .map(|text| text.trim().to_string())
     .unwrap_or_default();
 
+  if let Some(filename) = node
+    .children()
+    .find(|child| matches_tag(*child, "ri:attachment"))
+    .and_then(|attachment| get_attribute(attachment, "ri:filename"))
+    .filter(|filename| !filename.is_empty())
+  {
+    let text = if link_text.is_empty() { &filename } else { &link_text };
+    return format!("link:{filename}[{}]", escape_macro_text(text));
+  }
+
+  if link_text.is_empty()
+    && let Some(title) = node
+      .children()
+      .find(|child| matches_tag(*child, "ri:page"))
+      .and_then(|page| get_attribute(page, "ri:content-title"))
+  {
+    return title;
+  }
+
   // Try to find the URL
   let url = node
     .children()
@@ -369,21 +389,38 @@ fn convert_image_to_asciidoc(node: Node) -> String {
   if let Some(url_node) = node.children().find(|child| matches_tag(*child, "ri:url"))
     && let Some(src) = get_attribute(url_node, "ri:value")
   {
-    return format!("image::{src}[{alt}]");
+    return format!("image::{src}[{}]", image_attributes(&alt));
   }
 
   // Try ri:attachment
   if let Some(attachment_node) = node.children().find(|child| matches_tag(*child, "ri:attachment"))
     && let Some(filename) = get_attribute(attachment_node, "ri:filename")
   {
-    return format!("image::{filename}[{alt}]");
+    return format!("image::{filename}[{}]", image_attributes(&alt));
   }
 
   // Fallback - return empty if no source found
   String::new()
 }
 
+/// Quote alt text when it would otherwise be split into several attributes.
+fn image_attributes(alt: &str) -> String {
+  if alt.contains([',', '"', ']', '=']) {
+    format!("\"{}\"", alt.replace('"', "\\\"").replace(']', "\\]"))
+  } else {
+    alt.to_string()
+  }
+}
+
+/// Escape the characters that would end a macro's text early.
+fn escape_macro_text(text: &str) -> String {
+  text.replace(']', "\\]")
+}
+
 /// Convert HTML table to AsciiDoc format.
+///
+/// A `<caption>` becomes the table's block title and a `summary` attribute is
+/// kept as a line comment above it.
 fn convert_table_to_asciidoc(node: Node, options: &AsciiDocOptions) -> String {
   let mut rows: Vec<Vec<String>> = Vec::new();
   let mut has_header = false;
@@ -415,6 +452,15 @@ fn convert_table_to_asciidoc(node: Node, options: &AsciiDocOptions) -> String {
       for tr in container.children().filter(|n| matches_tag(*n, "tr")) {
         let row = extract_table_row(tr, options);
         if !row.is_empty() {
+          // A leading row of header cells inside tbody is the header too
+          if rows.is_empty()
+            && tr
+              .children()
+              .filter(|n| n.is_element())
+              .all(|cell| matches_tag(cell, "th"))
+          {
+            has_header = true;
+          }
           rows.push(row);
         }
       }
@@ -426,15 +472,32 @@ fn convert_table_to_asciidoc(node: Node, options: &AsciiDocOptions) -> String {
   }
 
   // Build AsciiDoc table
-  let mut result = String::new();
-  result.push_str("\n|===\n");
+  let mut result = String::from("\n");
+  if let Some(summary) = get_table_summary(node) {
+    result.push_str(&format!("// Table summary: {summary}\n"));
+  }
+  if let Some(caption) = get_table_caption(node) {
+    // Block titles are rendered as the table caption by Asciidoctor
+    result.push_str(&format!(".{caption}\n"));
+  }
+  result.push_str("|===\n");
 
   for (i, row) in rows.iter().enumerate() {
-    // Output cells
-    for cell in row {
-      result.push_str(&format!("| {cell} "));
+    // Output cells; block content needs an AsciiDoc (`a`) cell on its own lines
+    if row.iter().any(|cell| cell.contains('\n')) {
+      for cell in row {
+        if cell.contains('\n') {
+          result.push_str(&format!("a| {cell}\n"));
+        } else {
+          result.push_str(&format!("| {cell}\n"));
+        }
+      }
+    } else {
+      for cell in row {
+        result.push_str(&format!("| {cell} "));
+      }
+      result.push('\n');
     }
-    result.push('\n');
 
     // Add blank line after header row
     if i == 0 && has_header {
@@ -452,8 +515,18 @@ fn extract_table_row(tr: Node, options: &AsciiDocOptions) -> Vec<String> {
     .filter(|n| matches_tag(*n, "td") || matches_tag(*n, "th"))
     .map(|cell| {
       let content = convert_node_to_asciidoc(cell, options);
-      // Clean up cell content - remove newlines and extra whitespace
-      content.trim().replace('\n', " ")
+      let content = content.trim().replace('|', "\\|");
+      // Keep line structure only for block content such as lists and listings
+      let has_blocks = cell.descendants().any(|node| {
+        ["ul", "ol", "pre", "ac:structured-macro", "ac:task-list"]
+          .iter()
+          .any(|tag| matches_tag(node, tag))
+      });
+      if has_blocks {
+        utils::clean_asciidoc(&content).trim_end().to_string()
+      } else {
+        content.replace('\n', " ")
+      }
     })
     .collect()
 }
@@ -599,4 +672,18 @@ mod tests {
     assert!(output.contains("| Header 1"));
     assert!(output.contains("| Cell 1"));
   }
+
+  #[test]
+  fn test_convert_table_caption_and_summary() {
+    let input = r#"
+      <table summary="Totals per region">
+        <caption>Regional sales</caption>
+        <tr><th>Region</th><th>Total</th></tr>
+        <tr><td>North</td><td>42</td></tr>
+      </table>
+    "#;
+    let output = convert_to_asciidoc(input);
+    assert!(output.contains("// Table summary: Totals per region\n.Regional sales\n|===\n"));
+    assert!(!output.contains("| Regional sales"));
+  }
 }
@@ -0,0 +1,323 @@
+//! Confluence macro conversion to AsciiDoc.
+//!
+//! Mirrors the Markdown macro handlers, but targets the block types
+//! Asciidoctor provides natively: admonition blocks, `[source]` listings,
+//! sidebars, collapsible example blocks, checklists, and `[plantuml]`
+//! diagram blocks.
+
+use roxmltree::Node;
+use tracing::debug;
+
+use crate::asciidoc::AsciiDocOptions;
+use crate::markdown::utils::{
+  append_annotation, describe_macro, find_child_by_tag, find_child_by_tag_and_attr, get_attribute,
+  get_diagram_reference, get_element_text, matches_tag,
+};
+use crate::tasks::InlineTask;
+
+/// Converts a Confluence structured macro to AsciiDoc.
+///
+/// Unknown macros fall back to returning their text content. With
+/// `annotate_macros` set, a comment naming the macro and its parameters
+/// follows the output.
+///
+/// # Arguments
+/// * `element` - The `<ac:structured-macro>` node being processed.
+/// * `convert_node` - Callback used to render nested rich text into AsciiDoc.
+/// * `options` - Conversion behaviour flags that influence macro rendering.
+///
+/// # Returns
+/// An AsciiDoc fragment representing the macro.
+pub fn convert_macro_to_asciidoc(
+  element: Node,
+  convert_node: &dyn Fn(Node) -> String,
+  options: &AsciiDocOptions,
+) -> String {
+  let rendered = render_macro(element, convert_node, options);
+  if !options.annotate_macros {
+    return rendered;
+  }
+
+  // Line comments only work at the start of a line, so inline macros get an
+  // HTML comment passed through to the rendered page instead
+  let description = describe_macro(element);
+  let comment = if rendered.ends_with('\n') {
+    format!("// {description}")
+  } else {
+    format!(
+      "pass:[<!-- {} -->]",
+      description.replace("--", "- -").replace(']', "\\]")
+    )
+  };
+  append_annotation(rendered, &comment)
+}
+
+/// Dispatches a structured macro to its renderer.
+fn render_macro(element: Node, convert_node: &dyn Fn(Node) -> String, options: &AsciiDocOptions) -> String {
+  let macro_name = get_attribute(element, "ac:name").unwrap_or_default();
+
+  match macro_name.as_str() {
+    "code" | "code-block" => render_code_block(element),
+    "note" | "info" | "warning" | "tip" => render_admonition(&macro_name, element, convert_node),
+    "panel" => render_delimited_block(parameter(element, "title"), None, "****", &body(element, convert_node)),
+    "expand" => render_delimited_block(
+      Some(parameter(element, "title").unwrap_or_else(|| "Details".to_string())),
+      Some("%collapsible"),
+      "====",
+      &body(element, convert_node),
+    ),
+    "excerpt" => format!("\n{}\n\n", body(element, convert_node).trim()),
+    "toc" => "\ntoc::[]\n\n".to_string(),
+    "drawio" | "gliffy" => render_diagram(element),
+    "plantuml" => render_plantuml(element),
+    "status" => format!("`[{}]`", parameter(element, "title").unwrap_or_default()),
+    "anchor" => match parameter(element, "anchor") {
+      Some(anchor) if options.preserve_anchors => format!("[[{anchor}]]"),
+      _ => String::new(),
+    },
+    _ => {
+      debug!("Unhandled AsciiDoc macro: {macro_name}");
+      get_element_text(element)
+    }
+  }
+}
+
+/// Converts a Confluence task list to an AsciiDoc checklist.
+///
+/// # Arguments
+/// * `element` - The `<ac:task-list>` node to convert.
+///
+/// # Returns
+/// One `* [x]` or `* [ ]` item per task with its assignee and due date,
+/// surrounded by blank lines.
+pub fn convert_task_list_to_asciidoc(element: Node) -> String {
+  let mut result = String::from("\n");
+
+  for task in element.children().filter(|child| matches_tag(*child, "ac:task")) {
+    let task = InlineTask::from_node(task);
+    let checkbox = if task.complete { "[x]" } else { "[ ]" };
+    result.push_str(&format!("* {checkbox} {}\n", task.label()));
+  }
+
+  result.push('\n');
+  result
+}
+
+/// Renders a code macro as a `[source]` listing block, keeping the language
+/// and title parameters.
+fn render_code_block(element: Node) -> String {
+  let language = parameter(element, "language");
+  let code = find_child_by_tag(element, "ac:plain-text-body")
+    .map(get_element_text)
+    .or_else(|| find_child_by_tag(element, "ac:rich-text-body").map(get_element_text))
+    .unwrap_or_else(|| get_element_text(element));
+  let code = code.trim_matches(|c| matches!(c, '\n' | '\r'));
+
+  let mut result = String::from("\n");
+  if let Some(title) = parameter(element, "title") {
+    result.push_str(&format!(".{title}\n"));
+  }
+  match language {
+    Some(language) => result.push_str(&format!("[source,{language}]\n")),
+    None => result.push_str("[source]\n"),
+  }
+
+  result.push_str(&listing_block(code));
+  result
+}
+
+/// Wraps text in a `----` listing block.
+fn listing_block(code: &str) -> String {
+  // A line matching the delimiter would end the block early, so lengthen it
+  let longest = code
+    .lines()
+    .filter(|line| !line.is_empty() && line.chars().all(|c| c == '-'))
+    .map(str::len)
+    .max()
+    .unwrap_or(0);
+  let delimiter = "-".repeat(longest.max(3) + 1);
+  format!("{delimiter}\n{code}\n{delimiter}\n\n")
+}
+
+/// Renders a `drawio` or `gliffy` macro as the diagram's PNG export followed
+/// by a link to its source attachment.
+fn render_diagram(element: Node) -> String {
+  match get_diagram_reference(element) {
+    Some(diagram) => format!(
+      "\nimage::{preview}[{name}]\n\nlink:{name}[{name} ({tool} source)]\n\n",
+      name = diagram.name,
+      preview = diagram.preview_filename(),
+      tool = diagram.tool,
+    ),
+    None => get_element_text(element),
+  }
+}
+
+/// Renders a `plantuml` macro as a `[plantuml]` listing block for Asciidoctor Diagram.
+fn render_plantuml(element: Node) -> String {
+  let source = find_child_by_tag(element, "ac:plain-text-body")
+    .map(get_element_text)
+    .unwrap_or_default();
+  let source = source.trim_matches(|c| matches!(c, '\n' | '\r'));
+  if source.trim().is_empty() {
+    return String::new();
+  }
+
+  format!("\n[plantuml]\n{}", listing_block(source))
+}
+
+/// Renders a note/info/warning/tip macro as an admonition block.
+fn render_admonition(macro_name: &str, element: Node, convert_node: &dyn Fn(Node) -> String) -> String {
+  let label = match macro_name {
+    "tip" => "TIP",
+    "warning" => "WARNING",
+    "note" => "IMPORTANT",
+    _ => "NOTE",
+  };
+
+  let mut result = format!("\n[{label}]\n");
+  if let Some(title) = parameter(element, "title") {
+    result.push_str(&format!(".{title}\n"));
+  }
+  result.push_str(&format!("====\n{}\n====\n\n", body(element, convert_node).trim()));
+  result
+}
+
+/// Renders content inside a delimited block with an optional title and style.
+fn render_delimited_block(title: Option<String>, style: Option<&str>, delimiter: &str, content: &str) -> String {
+  let mut result = String::from("\n");
+  if let Some(title) = title {
+    result.push_str(&format!(".{title}\n"));
+  }
+  if let Some(style) = style {
+    result.push_str(&format!("[{style}]\n"));
+  }
+  result.push_str(&format!("{delimiter}\n{}\n{delimiter}\n\n", content.trim()));
+  result
+}
+
+/// Converts the macro's rich text body, falling back to its text content.
+fn body(element: Node, convert_node: &dyn Fn(Node) -> String) -> String {
+  find_child_by_tag(element, "ac:rich-text-body")
+    .map(convert_node)
+    .unwrap_or_else(|| get_element_text(element))
+}
+
+/// Reads a non-empty macro parameter.
+fn parameter(element: Node, name: &str) -> Option<String> {
+  find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", name)
+    .map(get_element_text)
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::asciidoc::{AsciiDocOptions, storage_to_asciidoc_with_options};
+
+  fn render(input: &str) -> String {
+    storage_to_asciidoc_with_options(input, &AsciiDocOptions::default()).unwrap()
+  }
+
+  #[test]
+  fn test_code_macro_keeps_language_and_title() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="code">"#,
+      r#"<ac:parameter ac:name="language">rust</ac:parameter>"#,
+      r#"<ac:parameter ac:name="title">main.rs</ac:parameter>"#,
+      "<ac:plain-text-body><![CDATA[fn main() {}\n----\n]]></ac:plain-text-body>",
+      "</ac:structured-macro>"
+    );
+
+    insta::assert_snapshot!(render(input), @r"
+    .main.rs
+    [source,rust]
+    -----
+    fn main() {}
+    ----
+    -----
+    ");
+  }
+
+  #[test]
+  fn test_admonition_and_expand_blocks() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="warning">"#,
+      r#"<ac:parameter ac:name="title">Careful</ac:parameter>"#,
+      "<ac:rich-text-body><p>Back up <strong>first</strong>.</p></ac:rich-text-body>",
+      "</ac:structured-macro>",
+      r#"<ac:structured-macro ac:name="expand">"#,
+      "<ac:rich-text-body><p>Hidden</p></ac:rich-text-body>",
+      "</ac:structured-macro>"
+    );
+
+    insta::assert_snapshot!(render(input), @r"
+    [WARNING]
+    .Careful
+    ====
+    Back up *first*.
+    ====
+
+    .Details
+    [%collapsible]
+    ====
+    Hidden
+    ====
+    ");
+  }
+
+  #[test]
+  fn test_annotate_macros() {
+    let input = concat!(
+      "<p>State: ",
+      r#"<ac:structured-macro ac:name="status">"#,
+      r#"<ac:parameter ac:name="title">Done</ac:parameter>"#,
+      "</ac:structured-macro>",
+      "</p>",
+      r#"<ac:structured-macro ac:name="info">"#,
+      r#"<ac:parameter ac:name="title">Heads up</ac:parameter>"#,
+      "<ac:rich-text-body><p>Read this.</p></ac:rich-text-body>",
+      "</ac:structured-macro>"
+    );
+    let options = AsciiDocOptions {
+      annotate_macros: true,
+      ..Default::default()
+    };
+
+    insta::assert_snapshot!(storage_to_asciidoc_with_options(input, &options).unwrap(), @r"
+    State: `[Done]`pass:[<!-- confluence-macro: status title=Done -->]
+
+    [NOTE]
+    .Heads up
+    ====
+    Read this.
+    ====
+    // confluence-macro: info title=Heads up
+    ");
+  }
+
+  #[test]
+  fn test_diagram_macros() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="drawio">"#,
+      r#"<ac:parameter ac:name="diagramName">Architecture</ac:parameter>"#,
+      "</ac:structured-macro>",
+      r#"<ac:structured-macro ac:name="plantuml">"#,
+      "<ac:plain-text-body><![CDATA[@startuml\nAlice -> Bob: Hello\n@enduml]]></ac:plain-text-body>",
+      "</ac:structured-macro>"
+    );
+
+    insta::assert_snapshot!(render(input), @r"
+    image::Architecture.png[Architecture]
+
+    link:Architecture[Architecture (draw.io source)]
+
+    [plantuml]
+    ----
+    @startuml
+    Alice -> Bob: Hello
+    @enduml
+    ----
+    ");
+  }
+}
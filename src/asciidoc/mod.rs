@@ -12,6 +12,7 @@
 //!
 //! AsciiDoc-specific conversion is handled by:
 //! - [`elements`] - AsciiDoc element converters
+//! - [`macros`] - Confluence macro converters (admonitions, code, expand, tasks)
 //! - [`utils`] - AsciiDoc-specific cleanup utilities
 
 use std::time::Instant;
@@ -20,7 +21,10 @@ use anyhow::Result;
 use roxmltree::Document;
 use tracing::{debug, error, trace};
 
+use crate::format::HeadingOverflow;
+
 mod elements;
+mod macros;
 mod utils;
 
 pub use elements::convert_node_to_asciidoc;
@@ -32,6 +36,12 @@ pub struct AsciiDocOptions {
   pub preserve_anchors: bool,
   /// Render tables in compact form without column width specs.
   pub compact_tables: bool,
+  /// Deepest heading level to emit; deeper headings follow `heading_overflow`.
+  pub max_heading_level: Option<u8>,
+  /// How headings deeper than `max_heading_level` are rendered.
+  pub heading_overflow: HeadingOverflow,
+  /// Follow each converted macro with a comment recording its name and parameters.
+  pub annotate_macros: bool,
 }
 
 /// Convert Confluence storage format to AsciiDoc using the provided options.
@@ -104,6 +114,30 @@ mod tests {
     assert!(output.contains("== Subtitle"));
   }
 
+  #[test]
+  fn test_max_heading_level() {
+    let input = "<h2>Setup</h2><h5>Linux</h5>";
+    let clamped = AsciiDocOptions {
+      max_heading_level: Some(3),
+      ..Default::default()
+    };
+    let bold = AsciiDocOptions {
+      heading_overflow: HeadingOverflow::Bold,
+      ..clamped
+    };
+
+    assert!(
+      storage_to_asciidoc_with_options(input, &clamped)
+        .unwrap()
+        .contains("=== Linux")
+    );
+    assert!(
+      storage_to_asciidoc_with_options(input, &bold)
+        .unwrap()
+        .contains("\n*Linux*\n")
+    );
+  }
+
   #[test]
   fn test_convert_formatting() {
     let input = "<p><strong>bold</strong> <em>italic</em> <s>strike</s></p>";
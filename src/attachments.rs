@@ -7,15 +7,119 @@ use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use clap::ValueEnum;
+use futures::{StreamExt, TryStreamExt, stream};
 use tokio::fs;
 use tracing::warn;
 
 use crate::confluence::{Attachment, ConfluenceApi};
+use crate::error::Error;
+use crate::paths;
 
 /// Default directory name where attachments are stored relative to the page
 /// output directory.
 pub const ATTACHMENTS_DIR: &str = "attachments";
 
+/// Maximum number of images or attachments of one page downloaded at once.
+///
+/// Requests still pass through the client's rate limiter, which is shared by
+/// all pages of an export.
+pub const MAX_CONCURRENT_ASSET_DOWNLOADS: usize = 8;
+
+/// How downloaded attachments are arranged below [`ATTACHMENTS_DIR`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum AttachmentLayout {
+  /// All attachments in one directory (default)
+  #[default]
+  Flat,
+  /// One subdirectory per content type: `pdf/`, `images/`, `archives/`, ...
+  ByType,
+  /// One subdirectory per page, named after the page title
+  PerPage,
+}
+
+impl AttachmentLayout {
+  /// Directory, relative to the page output directory, for an attachment.
+  ///
+  /// # Arguments
+  /// * `attachment` - The attachment being placed.
+  /// * `page_dir` - Sanitized title of the page the attachment belongs to.
+  pub fn directory(&self, attachment: &Attachment, page_dir: &str) -> PathBuf {
+    let base = PathBuf::from(ATTACHMENTS_DIR);
+    match self {
+      AttachmentLayout::Flat => base,
+      AttachmentLayout::ByType => base.join(content_category(attachment)),
+      AttachmentLayout::PerPage => base.join(page_dir),
+    }
+  }
+}
+
+/// Subdirectory name grouping an attachment by its content type.
+///
+/// The media type reported by Confluence decides when it is specific;
+/// otherwise the file extension does.
+pub fn content_category(attachment: &Attachment) -> &'static str {
+  let media_type = attachment
+    .media_type
+    .as_deref()
+    .unwrap_or_default()
+    .to_ascii_lowercase();
+  let extension = attachment
+    .title
+    .rsplit_once('.')
+    .map(|(_, extension)| extension.to_ascii_lowercase())
+    .unwrap_or_default();
+
+  if media_type == "application/pdf" || extension == "pdf" {
+    "pdf"
+  } else if media_type.starts_with("image/")
+    || matches!(
+      extension.as_str(),
+      "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp" | "tif" | "tiff"
+    )
+  {
+    "images"
+  } else if media_type.starts_with("video/")
+    || media_type.starts_with("audio/")
+    || matches!(
+      extension.as_str(),
+      "mp4" | "mov" | "webm" | "avi" | "mp3" | "wav" | "ogg"
+    )
+  {
+    "media"
+  } else if [
+    "zip",
+    "x-tar",
+    "gzip",
+    "x-gzip",
+    "x-7z-compressed",
+    "x-rar-compressed",
+    "vnd.rar",
+  ]
+  .iter()
+  .any(|subtype| media_type == format!("application/{subtype}"))
+    || matches!(
+      extension.as_str(),
+      "zip" | "tar" | "gz" | "tgz" | "7z" | "rar" | "bz2" | "xz"
+    )
+  {
+    "archives"
+  } else if media_type.starts_with("text/")
+    || media_type.contains("officedocument")
+    || media_type.contains("msword")
+    || media_type.contains("ms-excel")
+    || media_type.contains("ms-powerpoint")
+    || matches!(
+      extension.as_str(),
+      "doc" | "docx" | "xls" | "xlsx" | "ppt" | "pptx" | "odt" | "ods" | "odp" | "txt" | "csv" | "md" | "rtf"
+    )
+  {
+    "documents"
+  } else {
+    "other"
+  }
+}
+
 /// Represents an attachment downloaded from Confluence.
 #[derive(Debug, Clone)]
 pub struct DownloadedAttachment {
@@ -34,12 +138,28 @@ pub struct DownloadedAttachment {
 /// * `overwrite` - When `true`, existing files are replaced.
 /// * `skip_titles` - Optional set of attachment titles that should be skipped (typically image filenames already
 ///   handled separately).
+///
+/// # Errors
+/// Returns the [`Error`] of the first attachment that cannot be listed,
+/// downloaded, or written, classified by its cause.
 pub async fn download_attachments(
   client: &dyn ConfluenceApi,
   page_id: &str,
   output_dir: &Path,
   overwrite: bool,
   skip_titles: Option<&HashSet<String>>,
+) -> crate::Result<Vec<DownloadedAttachment>> {
+  download_all(client, page_id, output_dir, overwrite, skip_titles)
+    .await
+    .map_err(Error::from)
+}
+
+async fn download_all(
+  client: &dyn ConfluenceApi,
+  page_id: &str,
+  output_dir: &Path,
+  overwrite: bool,
+  skip_titles: Option<&HashSet<String>>,
 ) -> Result<Vec<DownloadedAttachment>> {
   let attachments = client
     .get_attachments(page_id)
@@ -55,67 +175,55 @@ pub async fn download_attachments(
     .await
     .with_context(|| format!("Failed to create attachments directory {}", attachments_dir.display()))?;
 
+  // Filenames are chosen sequentially so duplicates get stable suffixes
   let mut downloaded = Vec::new();
+  let mut pending = Vec::new();
   let mut used_filenames = HashSet::new();
 
-  'attachment_loop: for attachment in attachments {
+  for attachment in attachments {
     if should_skip(&attachment, skip_titles) {
       continue;
     }
 
-    let download_url = match attachment.links.as_ref().and_then(|links| links.download.as_ref()) {
-      Some(url) => url,
-      None => {
-        warn!(
-          "Skipping attachment '{}' because no download link was provided",
-          attachment.title
-        );
-        continue;
-      }
+    let Some(download_url) = attachment.links.as_ref().and_then(|links| links.download.clone()) else {
+      warn!(
+        "Skipping attachment '{}' because no download link was provided",
+        attachment.title
+      );
+      continue;
     };
 
-    let attachment_title = attachment.title.clone();
-
-    let sanitized = sanitize_filename(&attachment_title);
+    let sanitized = sanitize_filename(&attachment.title);
     let (base, ext) = split_name_and_extension(&sanitized);
     let mut filename = sanitized.clone();
     let mut counter = 1;
-
-    loop {
-      let output_path = attachments_dir.join(&filename);
-
-      if used_filenames.contains(&filename) {
-        filename = next_candidate(&base, &ext, counter);
-        counter += 1;
-        continue;
-      }
-
-      if output_path.exists() && !overwrite {
-        used_filenames.insert(filename.clone());
-        downloaded.push(DownloadedAttachment {
-          original_name: attachment_title.clone(),
-          relative_path: PathBuf::from(ATTACHMENTS_DIR).join(&filename),
-        });
-        continue 'attachment_loop;
-      }
-
-      used_filenames.insert(filename.clone());
-      break;
+    while used_filenames.contains(&filename) {
+      filename = next_candidate(&base, &ext, counter);
+      counter += 1;
     }
+    used_filenames.insert(filename.clone());
 
     let output_path = attachments_dir.join(&filename);
-
-    client
-      .download_attachment(download_url, &output_path)
-      .await
-      .with_context(|| format!("Failed to download attachment {}", attachment_title.clone()))?;
-
+    if overwrite || !output_path.exists() {
+      pending.push((attachment.title.clone(), download_url, output_path));
+    }
     downloaded.push(DownloadedAttachment {
-      original_name: attachment_title,
+      original_name: attachment.title,
       relative_path: PathBuf::from(ATTACHMENTS_DIR).join(filename),
     });
   }
 
+  stream::iter(pending)
+    .map(|(title, url, output_path)| async move {
+      client
+        .download_attachment(&url, &output_path)
+        .await
+        .with_context(|| format!("Failed to download attachment {title}"))
+    })
+    .buffer_unordered(MAX_CONCURRENT_ASSET_DOWNLOADS)
+    .try_collect::<Vec<()>>()
+    .await?;
+
   Ok(downloaded)
 }
 
@@ -139,6 +247,26 @@ pub fn update_markdown_attachment_links(markdown: &str, attachments: &[Downloade
   result
 }
 
+/// Update AsciiDoc `link:` macros that reference attachment filenames to point
+/// at the downloaded files.
+pub fn update_asciidoc_attachment_links(asciidoc: &str, attachments: &[DownloadedAttachment]) -> String {
+  let mut result = asciidoc.to_string();
+
+  for attachment in attachments {
+    let local_path = attachment
+      .relative_path
+      .to_str()
+      .map(|s| s.replace('\\', "/"))
+      .unwrap_or_default();
+
+    let search = format!("link:{}[", attachment.original_name);
+    let replacement = format!("link:{local_path}[");
+    result = result.replace(&search, &replacement);
+  }
+
+  result
+}
+
 fn should_skip(attachment: &Attachment, skip_titles: Option<&HashSet<String>>) -> bool {
   if let Some(skip) = skip_titles {
     skip.contains(&attachment.title)
@@ -164,19 +292,60 @@ fn split_name_and_extension(name: &str) -> (String, String) {
 }
 
 fn sanitize_filename(filename: &str) -> String {
-  filename
-    .chars()
-    .map(|c| match c {
-      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-      c => c,
-    })
-    .collect()
+  paths::sanitize_asset_filename(filename)
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  fn attachment(title: &str, media_type: Option<&str>) -> Attachment {
+    Attachment {
+      id: "1".to_string(),
+      title: title.to_string(),
+      attachment_type: "attachment".to_string(),
+      media_type: media_type.map(str::to_string),
+      file_size: None,
+      links: None,
+    }
+  }
+
+  #[test]
+  fn test_content_category() {
+    assert_eq!(
+      content_category(&attachment("spec.pdf", Some("application/pdf"))),
+      "pdf"
+    );
+    assert_eq!(content_category(&attachment("diagram", Some("image/png"))), "images");
+    assert_eq!(
+      content_category(&attachment("logs.tar.gz", Some("application/octet-stream"))),
+      "archives"
+    );
+    assert_eq!(content_category(&attachment("budget.xlsx", None)), "documents");
+    assert_eq!(
+      content_category(&attachment("demo.mov", Some("video/quicktime"))),
+      "media"
+    );
+    assert_eq!(content_category(&attachment("blob.bin", None)), "other");
+  }
+
+  #[test]
+  fn test_attachment_layout_directory() {
+    let spec = attachment("spec.pdf", Some("application/pdf"));
+    assert_eq!(
+      AttachmentLayout::Flat.directory(&spec, "Design"),
+      PathBuf::from("attachments")
+    );
+    assert_eq!(
+      AttachmentLayout::ByType.directory(&spec, "Design"),
+      PathBuf::from("attachments/pdf")
+    );
+    assert_eq!(
+      AttachmentLayout::PerPage.directory(&spec, "Design"),
+      PathBuf::from("attachments/Design")
+    );
+  }
+
   #[test]
   fn test_split_name_and_extension_with_extension() {
     let (base, ext) = split_name_and_extension("report.pdf");
@@ -0,0 +1,155 @@
+//! Blueprint usage across a page tree.
+//!
+//! Confluence does not expose which template or blueprint a page was created
+//! from as a field of its own, but every built-in blueprint tags the pages it
+//! creates with a fixed label (`meeting-notes`, `decision`, ...). This module
+//! maps those labels back to their blueprints and aggregates a page tree into
+//! per-blueprint usage, including who created each page (from the `history`
+//! expansion), so documentation teams can find stale boilerplate pages to
+//! clean up.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::confluence::{Page, PageTree};
+
+/// Labels added by the built-in Confluence blueprints, with the blueprint name.
+pub const BLUEPRINT_LABELS: &[(&str, &str)] = &[
+  ("meeting-notes", "Meeting notes"),
+  ("decision", "Decision"),
+  ("kb-how-to-article", "How-to article"),
+  ("kb-troubleshooting-article", "Troubleshooting article"),
+  ("requirements", "Product requirements"),
+  ("retrospective", "Retrospective"),
+  ("file-list", "File list"),
+];
+
+/// Name of the blueprint a page was created from, detected from its labels.
+///
+/// Returns `None` when labels were not expanded or none of them belong to a
+/// known blueprint.
+pub fn blueprint_of(page: &Page) -> Option<&'static str> {
+  page.labels().iter().find_map(|label| {
+    BLUEPRINT_LABELS
+      .iter()
+      .find(|(blueprint_label, _)| blueprint_label.eq_ignore_ascii_case(label))
+      .map(|(_, name)| *name)
+  })
+}
+
+/// A page created from a blueprint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlueprintPage {
+  /// Confluence page ID.
+  pub id: String,
+  /// Page title.
+  pub title: String,
+  /// Display name of the page's creator, when known.
+  pub created_by: Option<String>,
+}
+
+/// Pages of a tree created from one blueprint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlueprintUsage {
+  /// Blueprint name, e.g. `"Meeting notes"`.
+  pub blueprint: String,
+  /// Pages created from the blueprint, in tree order.
+  pub pages: Vec<BlueprintPage>,
+}
+
+/// Group the pages of a tree by the blueprint they were created from.
+///
+/// # Arguments
+/// * `tree` - Page tree fetched with labels (and optionally history) expanded.
+///
+/// # Returns
+/// One entry per blueprint in use, most used first (ties sorted by name).
+/// Pages not created from a known blueprint are left out.
+pub fn blueprint_usage(tree: &PageTree) -> Vec<BlueprintUsage> {
+  let mut groups: BTreeMap<&'static str, Vec<BlueprintPage>> = BTreeMap::new();
+  collect_blueprint_pages(tree, &mut groups);
+
+  let mut usage: Vec<BlueprintUsage> = groups
+    .into_iter()
+    .map(|(blueprint, pages)| BlueprintUsage {
+      blueprint: blueprint.to_string(),
+      pages,
+    })
+    .collect();
+  usage.sort_by(|a, b| {
+    b.pages
+      .len()
+      .cmp(&a.pages.len())
+      .then_with(|| a.blueprint.cmp(&b.blueprint))
+  });
+  usage
+}
+
+fn collect_blueprint_pages(tree: &PageTree, groups: &mut BTreeMap<&'static str, Vec<BlueprintPage>>) {
+  if let Some(blueprint) = blueprint_of(&tree.page) {
+    groups.entry(blueprint).or_default().push(BlueprintPage {
+      id: tree.page.id.clone(),
+      title: tree.page.title.clone(),
+      created_by: tree.page.created_by().map(str::to_string),
+    });
+  }
+  for child in &tree.children {
+    collect_blueprint_pages(child, groups);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::{page, tree};
+
+  fn labeled_page(id: &str, labels: &[&str], creator: Option<&str>) -> Page {
+    Page {
+      metadata: serde_json::from_value(serde_json::json!({
+        "labels": {"results": labels.iter().map(|name| serde_json::json!({"name": name})).collect::<Vec<_>>()}
+      }))
+      .unwrap(),
+      history: serde_json::from_value(serde_json::json!({
+        "createdBy": creator.map(|name| serde_json::json!({"displayName": name}))
+      }))
+      .unwrap(),
+      ..page(id, &format!("Page {id}"))
+    }
+  }
+
+  #[test]
+  fn test_blueprint_of_matches_known_labels() {
+    assert_eq!(
+      blueprint_of(&labeled_page("1", &["team", "meeting-notes"], None)),
+      Some("Meeting notes")
+    );
+    assert_eq!(blueprint_of(&labeled_page("2", &["draft"], None)), None);
+  }
+
+  #[test]
+  fn test_blueprint_usage_groups_and_sorts() {
+    let root = tree(
+      labeled_page("1", &[], None),
+      vec![
+        tree(labeled_page("2", &["decision"], Some("Ana")), Vec::new()),
+        tree(
+          labeled_page("3", &["meeting-notes"], Some("Ben")),
+          vec![tree(labeled_page("4", &["meeting-notes"], None), Vec::new())],
+        ),
+      ],
+    );
+
+    let usage = blueprint_usage(&root);
+
+    assert_eq!(usage.len(), 2);
+    assert_eq!(usage[0].blueprint, "Meeting notes");
+    assert_eq!(
+      usage[0].pages.iter().map(|page| page.id.as_str()).collect::<Vec<_>>(),
+      ["3", "4"]
+    );
+    assert_eq!(usage[0].pages[0].created_by.as_deref(), Some("Ben"));
+    assert_eq!(usage[1].blueprint, "Decision");
+    assert_eq!(usage[1].pages[0].created_by.as_deref(), Some("Ana"));
+  }
+}
@@ -0,0 +1,400 @@
+//! Digest of what changed between two syncs of an export.
+//!
+//! Re-running an export into the same directory shows up in a mirror's
+//! history as raw diffs of converted files. With `--changelog`, every run
+//! records the sections and attachments of each exported page in
+//! [`SNAPSHOT_FILE`] and writes a `CHANGES` document in the output format
+//! comparing them with the
+//! record of the previous run: pages added, removed, moved, or renamed,
+//! sections added or removed by heading, and attachments added or removed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::confluence::Page;
+use crate::format::OutputFormat;
+use crate::integrity::sha256_hex;
+use crate::processed_page::ProcessedPage;
+
+/// File stem of the generated digest, at the export root; the extension
+/// follows the output format.
+pub const CHANGES_STEM: &str = "CHANGES";
+
+/// File name of the record compared against on the next run, at the export
+/// root.
+pub const SNAPSHOT_FILE: &str = ".confluence-dl-changes.json";
+
+/// What the digest compares of an exported page.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageState {
+  /// Page title.
+  pub title: String,
+  /// Path of the page file relative to the export root.
+  pub path: String,
+  /// Section headings in document order.
+  pub headings: Vec<String>,
+  /// Names of the images and attachments downloaded with the page.
+  pub attachments: BTreeSet<String>,
+  /// Hash of the converted content.
+  pub hash: String,
+}
+
+/// Pages of one run, keyed by page ID.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+  exported_at: Option<String>,
+  pages: BTreeMap<String, PageState>,
+}
+
+/// Pages recorded during an export.
+#[derive(Debug, Default)]
+pub struct ChangeLog {
+  pages: Mutex<BTreeMap<String, PageState>>,
+}
+
+impl ChangeLog {
+  /// Record an exported page.
+  ///
+  /// # Arguments
+  /// * `page` - The exported page.
+  /// * `processed` - Its converted content and downloaded files.
+  /// * `path` - Path of the page file relative to the export root.
+  /// * `format` - Format the page was exported in.
+  pub fn record(&self, page: &Page, processed: &ProcessedPage, path: &str, format: OutputFormat) {
+    let state = PageState {
+      title: page.title.clone(),
+      path: path.replace('\\', "/"),
+      headings: section_headings(&processed.content, format),
+      attachments: processed
+        .link_map
+        .images
+        .keys()
+        .chain(processed.link_map.attachments.keys())
+        .cloned()
+        .collect(),
+      hash: sha256_hex(processed.content.as_bytes()),
+    };
+    self
+      .pages
+      .lock()
+      .expect("changelog lock poisoned")
+      .insert(page.id.clone(), state);
+  }
+
+  /// Write the digest comparing the recorded pages with the previous run,
+  /// then replace the previous run's record with them.
+  ///
+  /// # Arguments
+  /// * `root` - The export root.
+  /// * `format` - Format of the exported pages; the digest is written in it.
+  /// * `extension` - Extension of the digest file.
+  ///
+  /// # Returns
+  /// The path of the written digest.
+  ///
+  /// # Errors
+  /// Returns an error when the previous record cannot be parsed or the files
+  /// cannot be written.
+  pub fn write(&self, root: &Path, format: OutputFormat, extension: &str) -> Result<PathBuf> {
+    let snapshot_path = root.join(SNAPSHOT_FILE);
+    let previous: Snapshot = match fs::read_to_string(&snapshot_path) {
+      Ok(json) => {
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", snapshot_path.display()))?
+      }
+      Err(err) if err.kind() == ErrorKind::NotFound => Snapshot::default(),
+      Err(err) => return Err(err).with_context(|| format!("Failed to read {}", snapshot_path.display())),
+    };
+
+    let pages = self.pages.lock().expect("changelog lock poisoned");
+    let changes_path = root.join(format!("{CHANGES_STEM}.{extension}"));
+    fs::write(&changes_path, render_changes(&previous, &pages, format))
+      .with_context(|| format!("Failed to write {}", changes_path.display()))?;
+
+    let snapshot = Snapshot {
+      exported_at: Some(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)),
+      pages: pages.clone(),
+    };
+    let json = serde_json::to_string_pretty(&snapshot).context("Failed to serialize changelog snapshot")?;
+    fs::write(&snapshot_path, format!("{json}\n"))
+      .with_context(|| format!("Failed to write {}", snapshot_path.display()))?;
+    Ok(changes_path)
+  }
+}
+
+/// Render the digest of the differences between two runs.
+fn render_changes(previous: &Snapshot, current: &BTreeMap<String, PageState>, format: OutputFormat) -> String {
+  let mut result = format!("{}\n\n", format.heading(1, "Changes"));
+  match &previous.exported_at {
+    Some(exported_at) => result.push_str(&format!("Compared with the export of {exported_at}.\n")),
+    None => result.push_str("No previous export to compare with; every page is new.\n"),
+  }
+
+  let added: Vec<&PageState> = current
+    .iter()
+    .filter(|(id, _)| !previous.pages.contains_key(*id))
+    .map(|(_, state)| state)
+    .collect();
+  let removed: Vec<&PageState> = previous
+    .pages
+    .iter()
+    .filter(|(id, _)| !current.contains_key(*id))
+    .map(|(_, state)| state)
+    .collect();
+  let changed: Vec<(&PageState, Vec<String>)> = current
+    .iter()
+    .filter_map(|(id, state)| {
+      let notes = page_changes(previous.pages.get(id)?, state);
+      (!notes.is_empty()).then_some((state, notes))
+    })
+    .collect();
+
+  if added.is_empty() && removed.is_empty() && changed.is_empty() {
+    result.push_str("\nNo changes since the previous export.\n");
+    return result;
+  }
+  if !added.is_empty() {
+    result.push_str(&format!("\n{}\n\n", format.heading(2, "Added pages")));
+    for state in added {
+      result.push_str(&format!("{} {}\n", format.bullet(), page_link(state, format)));
+    }
+  }
+  if !removed.is_empty() {
+    result.push_str(&format!("\n{}\n\n", format.heading(2, "Removed pages")));
+    for state in removed {
+      result.push_str(&format!("{} {} (`{}`)\n", format.bullet(), state.title, state.path));
+    }
+  }
+  if !changed.is_empty() {
+    result.push_str(&format!("\n{}\n", format.heading(2, "Changed pages")));
+    for (state, notes) in changed {
+      result.push_str(&format!("\n{}\n\n", format.heading(3, &page_link(state, format))));
+      for note in notes {
+        result.push_str(&format!("{} {note}\n", format.bullet()));
+      }
+    }
+  }
+  result
+}
+
+/// Describe how a page changed between two runs.
+fn page_changes(before: &PageState, after: &PageState) -> Vec<String> {
+  let mut notes = Vec::new();
+  if before.title != after.title {
+    notes.push(format!("Renamed from \"{}\"", before.title));
+  } else if before.path != after.path {
+    notes.push(format!("Moved from `{}`", before.path));
+  }
+
+  let listed = |label: &str, items: Vec<&String>| {
+    let items: Vec<&str> = items.into_iter().map(String::as_str).collect();
+    (!items.is_empty()).then(|| format!("{label}: {}", items.join(", ")))
+  };
+  notes.extend(listed(
+    "Added sections",
+    multiset_difference(&after.headings, &before.headings),
+  ));
+  notes.extend(listed(
+    "Removed sections",
+    multiset_difference(&before.headings, &after.headings),
+  ));
+  notes.extend(listed(
+    "Added attachments",
+    after.attachments.difference(&before.attachments).collect(),
+  ));
+  notes.extend(listed(
+    "Removed attachments",
+    before.attachments.difference(&after.attachments).collect(),
+  ));
+
+  if notes.is_empty() && before.hash != after.hash {
+    notes.push("Text changed".to_string());
+  }
+  notes
+}
+
+/// Items of `items` left after removing one occurrence of each item of
+/// `removed`, in order.
+fn multiset_difference<'a>(items: &'a [String], removed: &[String]) -> Vec<&'a String> {
+  let mut remaining: Vec<&String> = removed.iter().collect();
+  items
+    .iter()
+    .filter(|item| match remaining.iter().position(|other| other == item) {
+      Some(index) => {
+        remaining.swap_remove(index);
+        false
+      }
+      None => true,
+    })
+    .collect()
+}
+
+fn page_link(state: &PageState, format: OutputFormat) -> String {
+  format.link(&state.title, &state.path.replace(' ', "%20"))
+}
+
+/// Heading text of the sections of converted content, skipping fenced code
+/// blocks.
+fn section_headings(content: &str, format: OutputFormat) -> Vec<String> {
+  let marker = match format.content_format() {
+    OutputFormat::AsciiDoc => '=',
+    _ => '#',
+  };
+  let mut headings = Vec::new();
+  let mut in_fence = false;
+  for line in content.lines() {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("```") || trimmed.starts_with("~~~") || trimmed == "----" {
+      in_fence = !in_fence;
+      continue;
+    }
+    if in_fence {
+      continue;
+    }
+    let level = line.chars().take_while(|&c| c == marker).count();
+    if (1..=6).contains(&level)
+      && let Some(text) = line[level..].strip_prefix(' ')
+    {
+      headings.push(text.trim().trim_end_matches(marker).trim().to_string());
+    }
+  }
+  headings
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn state(title: &str, path: &str, headings: &[&str], attachments: &[&str], hash: &str) -> PageState {
+    PageState {
+      title: title.to_string(),
+      path: path.to_string(),
+      headings: headings.iter().map(|heading| heading.to_string()).collect(),
+      attachments: attachments.iter().map(|name| name.to_string()).collect(),
+      hash: hash.to_string(),
+    }
+  }
+
+  #[test]
+  fn test_render_changes() {
+    let previous = Snapshot {
+      exported_at: Some("2024-05-01T10:00:00Z".to_string()),
+      pages: BTreeMap::from([
+        (
+          "1".to_string(),
+          state("Guide", "Guide.md", &["Install", "Legacy"], &["old.pdf"], "a"),
+        ),
+        ("2".to_string(), state("FAQ", "Guide/FAQ.md", &[], &[], "b")),
+        ("3".to_string(), state("Retired", "Guide/Retired.md", &[], &[], "c")),
+      ]),
+    };
+    let current = BTreeMap::from([
+      (
+        "1".to_string(),
+        state("Guide", "Guide.md", &["Install", "Upgrade"], &["diagram.png"], "d"),
+      ),
+      ("2".to_string(), state("FAQ", "Guide/FAQ.md", &[], &[], "e")),
+      (
+        "4".to_string(),
+        state("Release Notes", "Guide/Release Notes.md", &[], &[], "f"),
+      ),
+    ]);
+
+    insta::assert_snapshot!(render_changes(&previous, &current, OutputFormat::Markdown), @r"
+    # Changes
+
+    Compared with the export of 2024-05-01T10:00:00Z.
+
+    ## Added pages
+
+    - [Release Notes](Guide/Release%20Notes.md)
+
+    ## Removed pages
+
+    - Retired (`Guide/Retired.md`)
+
+    ## Changed pages
+
+    ### [Guide](Guide.md)
+
+    - Added sections: Upgrade
+    - Removed sections: Legacy
+    - Added attachments: diagram.png
+    - Removed attachments: old.pdf
+
+    ### [FAQ](Guide/FAQ.md)
+
+    - Text changed
+    ");
+  }
+
+  #[test]
+  fn test_render_changes_asciidoc() {
+    let previous = Snapshot {
+      exported_at: Some("2024-05-01T10:00:00Z".to_string()),
+      pages: BTreeMap::from([
+        ("1".to_string(), state("Guide", "Guide.adoc", &["Install"], &[], "a")),
+        ("3".to_string(), state("Retired", "Guide/Retired.adoc", &[], &[], "c")),
+      ]),
+    };
+    let current = BTreeMap::from([
+      (
+        "1".to_string(),
+        state("Guide", "Guide.adoc", &["Install", "Upgrade"], &[], "d"),
+      ),
+      (
+        "4".to_string(),
+        state("Release Notes", "Guide/Release Notes.adoc", &[], &[], "f"),
+      ),
+    ]);
+
+    insta::assert_snapshot!(render_changes(&previous, &current, OutputFormat::AsciiDoc), @r"
+    = Changes
+
+    Compared with the export of 2024-05-01T10:00:00Z.
+
+    == Added pages
+
+    * link:Guide/Release%20Notes.adoc[Release Notes]
+
+    == Removed pages
+
+    * Retired (`Guide/Retired.adoc`)
+
+    == Changed pages
+
+    === link:Guide.adoc[Guide]
+
+    * Added sections: Upgrade
+    ");
+  }
+
+  #[test]
+  fn test_render_changes_without_changes() {
+    let pages = BTreeMap::from([("1".to_string(), state("Guide", "Guide.md", &[], &[], "a"))]);
+    let previous = Snapshot {
+      exported_at: Some("2024-05-01T10:00:00Z".to_string()),
+      pages: pages.clone(),
+    };
+
+    assert!(
+      render_changes(&previous, &pages, OutputFormat::Markdown).ends_with("\nNo changes since the previous export.\n")
+    );
+  }
+
+  #[test]
+  fn test_section_headings() {
+    let markdown = "# Guide\n\n## Install ##\n\n```\n# not a heading\n```\n\n#hashtag\n";
+    assert_eq!(section_headings(markdown, OutputFormat::Markdown), ["Guide", "Install"]);
+    assert_eq!(
+      section_headings("= Guide\n\n== Install\n", OutputFormat::AsciiDoc),
+      ["Guide", "Install"]
+    );
+  }
+}
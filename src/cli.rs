@@ -5,17 +5,38 @@
 
 use std::process;
 
-use clap::{Parser, Subcommand, ValueEnum, ValueHint};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum, ValueHint};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::filter::LevelFilter;
 use url::Url;
 
-use crate::color::ColorScheme;
+use crate::attachments::AttachmentLayout;
+use crate::color::{ColorScheme, Theme};
 use crate::commands::auth::{AuthCommand, handle_auth_command};
+use crate::commands::diff::handle_diff_command;
+use crate::commands::diff_page::handle_diff_page_command;
 use crate::commands::ls::handle_ls_command;
 use crate::commands::page::handle_page_download;
+use crate::commands::stats::handle_stats_command;
 use crate::commands::version::handle_version_command;
-use crate::format::OutputFormat;
+use crate::confluence::PageOrder;
+use crate::credentials::CredentialSource;
+use crate::events::LogFormat;
+use crate::format::{
+  BulletMarker, ContentStateDisplay, EmphasisMarker, ExportProfile, FenceStyle, HeadingOverflow, HeadingStyle,
+  ListingFormat, MarkdownFlavor, OutputFormat, TableCaptionPosition, TableFormat, TableSpanMode,
+};
+use crate::image_formats::CommandConverter;
+use crate::images::{ImageNaming, ImageQuality};
+use crate::integrity::Signer;
+use crate::links::UnexportedLinkStyle;
+use crate::manifest::PruneMode;
+use crate::markdown::{AdmonitionStyle, EmojiStyle, ImageStyle};
+use crate::notify::NotifyFormat;
+use crate::processed_page::{ConflictPolicy, FilenameStyle, OutputLayout};
+use crate::size_limits::{SizeLimit, parse_size, parse_size_limit};
 
 /// confluence-dl - Export Confluence pages to Markdown
 #[derive(Debug, Parser)]
@@ -73,6 +94,73 @@ pub enum Command {
     /// Maximum depth when traversing children (0 lists only the root page)
     #[arg(long, value_name = "N")]
     max_depth: Option<usize>,
+
+    /// Estimate page count and depth distribution by sampling at most REQUESTS API calls
+    #[arg(
+      long,
+      value_name = "REQUESTS",
+      num_args = 0..=1,
+      default_missing_value = "50",
+      conflicts_with = "max_depth"
+    )]
+    estimate: Option<usize>,
+
+    /// How to print the page tree
+    #[arg(long, default_value = "tree", value_name = "FORMAT", conflicts_with = "estimate")]
+    format: ListingFormat,
+  },
+
+  /// Summarize a page tree: page count and the blueprints pages were created from
+  Stats {
+    /// Page URL or numeric page ID whose descendants should be summarized
+    #[arg(value_name = "PAGE_URL_OR_ID", value_hint = ValueHint::Url)]
+    target: String,
+
+    /// Maximum depth when traversing children (0 covers only the root page)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Output in JSON format
+    #[arg(long)]
+    json: bool,
+  },
+
+  /// Show what changed in a page between two versions
+  DiffPage {
+    /// Page URL or numeric page ID
+    #[arg(value_name = "PAGE_URL_OR_ID", value_hint = ValueHint::Url)]
+    target: String,
+
+    /// Version to compare from
+    #[arg(long, value_name = "VERSION")]
+    from: u32,
+
+    /// Version to compare to (defaults to the current version)
+    #[arg(long, value_name = "VERSION")]
+    to: Option<u32>,
+
+    /// Write an HTML diff to FILE instead of printing a unified diff
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    html: Option<String>,
+  },
+
+  /// Compare live Confluence content with an earlier export: pages added, removed, and modified since
+  Diff {
+    /// Page URL or numeric page ID the export was made from
+    #[arg(value_name = "PAGE_URL_OR_ID", value_hint = ValueHint::Url)]
+    target: String,
+
+    /// Maximum depth when traversing children (0 covers only the root page)
+    #[arg(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Directory of the earlier export, holding its manifest.json [default: the --output directory]
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    dir: Option<String>,
+
+    /// Print a unified diff of each modified page against its exported file
+    #[arg(long)]
+    patch: bool,
   },
 
   /// Authentication testing and inspection
@@ -118,6 +206,45 @@ fn normalize_url(url: &str) -> Result<String, String> {
   Ok(url_str)
 }
 
+/// Parse a file extension, accepting it with or without a leading dot.
+fn parse_extension(value: &str) -> Result<String, String> {
+  let extension = value.trim().trim_start_matches('.');
+  if extension.is_empty() {
+    return Err("extension must not be empty".to_string());
+  }
+  if extension.contains(['/', '\\']) {
+    return Err("extension must not contain path separators".to_string());
+  }
+  Ok(extension.to_string())
+}
+
+/// Parse the start of a modified-date window: a date (`2024-01-01`, from
+/// midnight UTC) or an RFC 3339 timestamp.
+fn parse_modified_since(value: &str) -> Result<DateTime<Utc>, String> {
+  parse_date_bound(value, NaiveTime::MIN)
+}
+
+/// Parse the end of a modified-date window: a date (`2024-12-31`, through
+/// the end of the day UTC) or an RFC 3339 timestamp.
+fn parse_modified_until(value: &str) -> Result<DateTime<Utc>, String> {
+  let end_of_day = NaiveTime::from_hms_nano_opt(23, 59, 59, 999_999_999).expect("valid time");
+  parse_date_bound(value, end_of_day)
+}
+
+fn parse_date_bound(value: &str, time_of_day: NaiveTime) -> Result<DateTime<Utc>, String> {
+  let value = value.trim();
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    return Ok(date.and_time(time_of_day).and_utc());
+  }
+  DateTime::parse_from_rfc3339(value)
+    .map(|timestamp| timestamp.with_timezone(&Utc))
+    .map_err(|_| "expected a date (YYYY-MM-DD) or an RFC 3339 timestamp".to_string())
+}
+
+fn parse_image_converter(value: &str) -> Result<CommandConverter, String> {
+  CommandConverter::parse(value).map_err(|err| err.to_string())
+}
+
 /// Authentication options
 #[derive(Debug, Parser)]
 pub struct AuthOptions {
@@ -132,6 +259,16 @@ pub struct AuthOptions {
   /// Confluence API token
   #[arg(long, env = "CONFLUENCE_TOKEN", value_name = "TOKEN")]
   pub token: Option<String>,
+
+  /// Where to look up credentials not given by --user/--token
+  #[arg(
+    long,
+    env = "CONFLUENCE_CREDENTIAL_SOURCE",
+    value_enum,
+    default_value_t,
+    value_name = "SOURCE"
+  )]
+  pub credential_source: CredentialSource,
 }
 
 /// Output options
@@ -145,17 +282,236 @@ pub struct OutputOptions {
   #[arg(long)]
   pub overwrite: bool,
 
+  /// Write a single converted page to stdout instead of files (images and attachments are not downloaded)
+  #[arg(
+    long,
+    conflicts_with_all = [
+      "save_raw",
+      "extract_tables",
+      "split_table_rows",
+      "split_table_columns",
+      "include_graph",
+      "changelog",
+      "tasks_report",
+      "sign"
+    ]
+  )]
+  pub stdout: bool,
+
+  /// Detect local edits when re-exporting (records a content hash in front matter)
+  #[arg(long, value_enum, value_name = "POLICY")]
+  pub conflict: Option<ConflictPolicy>,
+
   /// Save raw Confluence storage format alongside converted output
   #[arg(long)]
   pub save_raw: bool,
 
+  /// Also write each table on a page to `tables/<page>-<n>.<format>` next to the page
+  #[arg(long, value_enum, value_name = "FORMAT")]
+  pub extract_tables: Option<TableFormat>,
+
+  /// Show only the first N rows of tables with more data rows, linking to the full table in `tables/<page>-<n>.csv`
+  #[arg(long, value_name = "N")]
+  pub split_table_rows: Option<usize>,
+
+  /// Split tables with more than N columns the same way, keeping the first N columns in the preview
+  #[arg(long, value_name = "N")]
+  pub split_table_columns: Option<usize>,
+
+  /// Follow each converted macro with a comment naming it and its parameters, for auditing conversions
+  #[arg(long)]
+  pub annotate_macros: bool,
+
   /// Render tables without padding columns for alignment
   #[arg(long)]
   pub compact_tables: bool,
 
+  /// Where to place table captions relative to the table
+  #[arg(long, value_enum, default_value = "above", value_name = "POSITION")]
+  pub table_captions: TableCaptionPosition,
+
+  /// How Markdown tables render cells with `colspan` or `rowspan`
+  #[arg(long, value_enum, default_value = "html", value_name = "MODE")]
+  pub table_spans: TableSpanMode,
+
   /// Output format
   #[arg(long, short = 'F', default_value = "markdown", value_name = "FORMAT")]
   pub format: OutputFormat,
+
+  /// Escape Markdown so it compiles as MDX (e.g. for Docusaurus)
+  #[arg(long)]
+  pub mdx_safe: bool,
+
+  /// File extension for exported pages, overriding the format default (e.g. `mdx`)
+  #[arg(long, value_name = "EXT", value_parser = parse_extension)]
+  pub extension: Option<String>,
+
+  /// How page files are named; sibling pages that would share a name get their page ID appended
+  #[arg(long, value_enum, default_value = "title", value_name = "STYLE")]
+  pub filename_style: FilenameStyle,
+
+  /// Directories the export is placed in: flat, or by space key and/or the titles of the pages above the exported page
+  #[arg(long, value_enum, default_value = "flat", value_name = "LAYOUT")]
+  pub layout: OutputLayout,
+
+  /// Transliterate accented letters in page filenames to ASCII and replace other non-ASCII characters
+  #[arg(long)]
+  pub ascii_filenames: bool,
+
+  /// Shorten page filenames so paths stay within N characters [default: 260 on Windows, unlimited elsewhere]
+  #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+  pub max_path_length: Option<usize>,
+
+  /// Tailor output for a documentation site generator
+  #[arg(long, value_enum, value_name = "PROFILE")]
+  pub profile: Option<ExportProfile>,
+
+  /// Write a graph of include/excerpt-include dependencies between pages (`.json` for JSON, DOT otherwise)
+  #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+  pub include_graph: Option<String>,
+
+  /// Write CHANGES.md (CHANGES.adoc for AsciiDoc) summarizing pages, sections, and attachments added or removed since
+  /// the previous run
+  #[arg(long)]
+  pub changelog: bool,
+
+  /// Write TASKS.md (TASKS.adoc for AsciiDoc) listing the open inline tasks of every exported page with their
+  /// assignees and due dates
+  #[arg(long)]
+  pub tasks_report: bool,
+
+  /// Write manifest.json listing every exported page and asset with its path and checksum
+  #[arg(
+    long,
+    default_value_t = true,
+    default_missing_value = "true",
+    action = clap::ArgAction::Set,
+    num_args = 0..=1
+  )]
+  pub manifest: bool,
+
+  /// List files of the previous export whose pages were deleted or moved out of the tree in Confluence; `delete`
+  /// removes them. Skipped when pages fail or filters leave pages out
+  #[arg(
+    long,
+    value_enum,
+    value_name = "MODE",
+    default_missing_value = "list",
+    num_args = 0..=1,
+    require_equals = true
+  )]
+  pub prune: Option<PruneMode>,
+
+  /// Re-hash images and attachments listed in manifest.json and download corrupted or truncated ones again
+  #[arg(long)]
+  pub verify: bool,
+
+  /// Add front matter fields to pages matching rules in this JSON file (by space, label, or title glob)
+  #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+  pub front_matter_rules: Option<String>,
+
+  /// Wrap each page's content in this template file; placeholders: {{title}}, {{body}}, {{url}}, {{labels}}, {{id}},
+  /// {{space}}, {{version}}, {{last_modified}}
+  #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+  pub template: Option<String>,
+
+  /// Warn when a page file exceeds this size: `github` (1 MiB), `gitlab` (50 MiB), a size like `512K`, or `off`
+  #[arg(long, default_value = "github", value_name = "LIMIT", value_parser = parse_size_limit)]
+  pub size_limit: SizeLimit,
+
+  /// Report images without alt text, tables without headers, skipped heading levels, and low-contrast text colors
+  #[arg(long)]
+  pub audit_accessibility: bool,
+
+  /// Write a SHA256SUMS file covering every exported file, signed when --sign-key is given
+  #[arg(long)]
+  pub sign: bool,
+
+  /// Secret key used to sign SHA256SUMS (minisign key or SSH private key)
+  #[arg(long, value_name = "KEY", requires = "sign", value_hint = ValueHint::FilePath)]
+  pub sign_key: Option<String>,
+
+  /// Tool used to sign SHA256SUMS
+  #[arg(long, value_enum, default_value = "minisign", value_name = "TOOL")]
+  pub signer: Signer,
+
+  /// Syntax for level 1 and 2 Markdown headings
+  #[arg(long, value_enum, default_value = "atx", value_name = "STYLE")]
+  pub heading_style: HeadingStyle,
+
+  /// Deepest heading level to emit (1-6); deeper headings are clamped or turned bold
+  #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=6))]
+  pub max_heading_level: Option<u8>,
+
+  /// How to render headings deeper than --max-heading-level
+  #[arg(
+    long,
+    value_enum,
+    default_value = "clamp",
+    value_name = "MODE",
+    requires = "max_heading_level"
+  )]
+  pub heading_overflow: HeadingOverflow,
+
+  /// Marker for unordered Markdown list items
+  #[arg(long, value_enum, default_value = "dash", value_name = "MARKER")]
+  pub bullet_marker: BulletMarker,
+
+  /// Delimiter for Markdown emphasis
+  #[arg(long, value_enum, default_value = "underscore", value_name = "MARKER")]
+  pub emphasis_marker: EmphasisMarker,
+
+  /// Delimiter for Markdown strong emphasis
+  #[arg(long, value_enum, default_value = "asterisk", value_name = "MARKER")]
+  pub strong_marker: EmphasisMarker,
+
+  /// Fence for Markdown code blocks
+  #[arg(long, value_enum, default_value = "backtick", value_name = "STYLE")]
+  pub code_fence: FenceStyle,
+
+  /// Markdown variant for admonitions, task lists, and wiki links: gfm, commonmark, pandoc, or obsidian
+  #[arg(
+    long,
+    visible_alias = "flavor",
+    value_enum,
+    value_name = "FLAVOR",
+    conflicts_with = "profile"
+  )]
+  pub markdown_flavor: Option<MarkdownFlavor>,
+
+  /// Syntax for note, info, tip, and warning macros; overrides the style chosen by --markdown-flavor or --profile
+  #[arg(long, value_enum, value_name = "STYLE")]
+  pub admonition_style: Option<AdmonitionStyle>,
+
+  /// How emoji are written: unicode characters, :shortcode: text, or stripped
+  #[arg(long, value_enum, default_value = "unicode", value_name = "STYLE")]
+  pub emoji_style: EmojiStyle,
+
+  /// Fix Markdown output to pass common markdownlint rules (spacing, list indentation, trailing whitespace)
+  #[arg(long)]
+  pub lint_fix: bool,
+
+  /// Render Confluence table of contents macros as links to the page's headings
+  #[arg(long)]
+  pub generate_toc: bool,
+
+  /// Redact the parameters of macros with this name from converted and raw output (repeatable)
+  #[arg(long, value_name = "MACRO")]
+  pub redact_macro: Vec<String>,
+
+  /// Show the page's content state (status banner) in front matter, as a badge under the title, or both
+  #[arg(long, value_enum, default_value = "off", value_name = "MODE")]
+  pub content_state: ContentStateDisplay,
+
+  /// Add `created_by` and `last_modified_by` fields with the page's author and last editor to front matter
+  #[arg(long)]
+  pub author_metadata: bool,
+}
+
+impl Default for OutputOptions {
+  fn default() -> Self {
+    Self::parse_from(["confluence-dl"])
+  }
 }
 
 /// Behavior options
@@ -173,9 +529,36 @@ pub struct BehaviorOptions {
   #[arg(short, long, conflicts_with = "verbose")]
   pub quiet: bool,
 
-  /// Colorize output
+  /// Colorize output (`auto` honors NO_COLOR, CLICOLOR, and CLICOLOR_FORCE)
   #[arg(long, value_enum, default_value = "auto", value_name = "WHEN")]
   pub color: ColorOption,
+
+  /// Color palette for terminal output
+  #[arg(long, value_enum, default_value = "dark", value_name = "THEME")]
+  pub theme: Theme,
+
+  /// Report progress and results as text or as line-delimited JSON events on stdout
+  #[arg(long, value_enum, default_value = "text", value_name = "FORMAT")]
+  pub log_format: LogFormat,
+
+  /// Post the run summary (pages, failures, duration) to this webhook when the export finishes or fails
+  #[arg(long, value_name = "URL", value_hint = ValueHint::Url)]
+  pub notify_webhook: Option<String>,
+
+  /// Record pages that fail to export in errors.json and continue with the rest of the tree, exiting with code 6
+  /// when any page failed
+  #[arg(long)]
+  pub keep_going: bool,
+
+  /// Payload format of the webhook notification
+  #[arg(
+    long,
+    value_enum,
+    default_value = "json",
+    value_name = "FORMAT",
+    requires = "notify_webhook"
+  )]
+  pub notify_format: NotifyFormat,
 }
 
 /// Color output options
@@ -197,9 +580,98 @@ pub struct PageOptions {
   #[arg(long, value_name = "N", requires = "children")]
   pub max_depth: Option<usize>,
 
+  /// Also export the pages linked from the exported pages, and the pages they link to, up to --link-depth links away
+  #[arg(long)]
+  pub follow_links: bool,
+
+  /// How many links away from the exported pages --follow-links goes
+  #[arg(
+    long,
+    default_value = "1",
+    value_name = "N",
+    requires = "follow_links",
+    value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+  )]
+  pub link_depth: usize,
+
   /// Download page attachments
   #[arg(long)]
   pub attachments: bool,
+
+  /// How downloaded attachments are arranged in subdirectories
+  #[arg(
+    long,
+    value_enum,
+    default_value = "flat",
+    value_name = "LAYOUT",
+    requires = "attachments"
+  )]
+  pub attachments_layout: AttachmentLayout,
+
+  /// Store each unique image and attachment once in a shared `assets/`
+  /// directory at the export root, named by content hash
+  #[arg(long, requires = "children")]
+  pub dedupe_assets: bool,
+
+  /// Only export child pages carrying this label (repeatable)
+  #[arg(long, value_name = "LABEL", requires = "children")]
+  pub include_label: Vec<String>,
+
+  /// Skip child pages carrying this label, along with their descendants
+  /// (repeatable)
+  #[arg(long, value_name = "LABEL", requires = "children")]
+  pub exclude_label: Vec<String>,
+
+  /// Only export child pages whose title matches this glob (repeatable;
+  /// `*` and `?` wildcards, case-insensitive)
+  #[arg(long, value_name = "GLOB", requires = "children")]
+  pub include_title: Vec<String>,
+
+  /// Skip child pages whose title matches this glob, along with their
+  /// descendants (repeatable)
+  #[arg(long, value_name = "GLOB", requires = "children")]
+  pub exclude_title: Vec<String>,
+
+  /// Skip the child page with this ID, along with its descendants; the pruned
+  /// branch is never fetched (repeatable)
+  #[arg(long, visible_alias = "exclude-subtree", value_name = "ID", requires = "children")]
+  pub exclude_id: Vec<String>,
+
+  /// Only export child pages created or last modified by this user, given
+  /// as email address, account ID, or display name (repeatable)
+  #[arg(long, value_name = "USER", requires = "children")]
+  pub author: Vec<String>,
+
+  /// Only export child pages last modified on or after this date
+  /// (`YYYY-MM-DD` or an RFC 3339 timestamp)
+  #[arg(long, value_name = "DATE", value_parser = parse_modified_since, requires = "children")]
+  pub modified_since: Option<DateTime<Utc>>,
+
+  /// Only export child pages last modified on or before this date
+  /// (`YYYY-MM-DD` or an RFC 3339 timestamp)
+  #[arg(long, value_name = "DATE", value_parser = parse_modified_until, requires = "children")]
+  pub modified_until: Option<DateTime<Utc>>,
+
+  /// Order of sibling pages in the exported tree
+  #[arg(
+    long,
+    value_enum,
+    default_value = "position",
+    value_name = "ORDER",
+    requires = "children"
+  )]
+  pub order: PageOrder,
+
+  /// Write a README.md (README.adoc for AsciiDoc) at the export root describing the space: name, description, logo,
+  /// home page, and page count
+  #[arg(long, requires = "children")]
+  pub space_readme: bool,
+}
+
+impl Default for PageOptions {
+  fn default() -> Self {
+    Self::parse_from(["confluence-dl"])
+  }
 }
 
 /// Image and link options
@@ -219,14 +691,87 @@ pub struct ImagesLinksOptions {
   #[arg(long, default_value = "images", value_name = "DIR", value_hint = ValueHint::DirPath)]
   pub images_dir: String,
 
+  /// How downloaded images are named, so pages sharing an images directory do not collide
+  #[arg(long, value_enum, default_value = "name", value_name = "POLICY")]
+  pub image_naming: ImageNaming,
+
+  /// Download images at full resolution or as the thumbnails Confluence generates, for quick previews
+  #[arg(long, value_enum, default_value = "original", value_name = "QUALITY")]
+  pub image_quality: ImageQuality,
+
+  /// How images are written: Markdown, or HTML keeping size, alignment, and captions (Markdown only)
+  #[arg(long, value_enum, default_value = "markdown", value_name = "STYLE")]
+  pub image_style: ImageStyle,
+
+  /// Convert images browsers cannot render (EMF, WMF, TIFF, EPS) to PNG with this command, e.g. "magick {input}
+  /// {output}"
+  #[arg(long, value_name = "COMMAND", value_parser = parse_image_converter)]
+  pub image_converter: Option<CommandConverter>,
+
+  /// Also download images embedded by URL from other sites, instead of linking to them
+  #[arg(long)]
+  pub download_external_images: bool,
+
+  /// Largest external image downloaded; larger images stay linked to their URL
+  #[arg(long, default_value = "10M", value_name = "SIZE", value_parser = parse_size, requires = "download_external_images")]
+  pub external_image_max_size: u64,
+
+  /// Timeout in seconds for downloading each external image
+  #[arg(
+    long,
+    default_value = "30",
+    value_name = "SECONDS",
+    requires = "download_external_images"
+  )]
+  pub external_image_timeout: u64,
+
   /// Keep Confluence anchor IDs
   #[arg(long)]
   pub preserve_anchors: bool,
+
+  /// How to render links to pages that are not part of the export
+  #[arg(long, value_enum, default_value = "keep", value_name = "STYLE")]
+  pub unexported_links: UnexportedLinkStyle,
+
+  /// Replace the text of bare external links with the target page title (Markdown only)
+  #[arg(long)]
+  pub fetch_link_titles: bool,
+
+  /// Max link title requests per second
+  #[arg(long, default_value = "2", value_name = "N")]
+  pub link_title_rate_limit: usize,
+
+  /// Resolve @mentions to user display names (one extra API call per mentioned user)
+  #[arg(long)]
+  pub resolve_users: bool,
+
+  /// Inline content of include, excerpt-include, and multiexcerpt-include macros (one extra API call per included
+  /// page; Markdown only)
+  #[arg(long)]
+  pub resolve_includes: bool,
+
+  /// Link bare Jira issue keys (e.g. ABC-123) in prose to this Jira site (Markdown only)
+  #[arg(long, value_name = "URL", value_parser = normalize_url, value_hint = ValueHint::Url)]
+  pub jira_url: Option<String>,
+
+  /// Only link issue keys of these Jira projects (comma-separated)
+  #[arg(long, value_name = "KEYS", value_delimiter = ',', requires = "jira_url")]
+  pub jira_projects: Vec<String>,
+}
+
+impl Default for ImagesLinksOptions {
+  fn default() -> Self {
+    Self::parse_from(["confluence-dl"])
+  }
 }
 
 /// Performance options
 #[derive(Debug, Parser)]
 pub struct PerformanceOptions {
+  /// Apply a bundle of parallelism, rate limit, retry, and page size settings
+  #[arg(long, value_enum, value_name = "PRESET")]
+  pub preset: Option<PerformancePreset>,
+
   /// Number of parallel downloads (`-1` uses available cores)
   #[arg(long, default_value = "4", value_name = "N", allow_negative_numbers = true)]
   pub parallel: isize,
@@ -238,6 +783,88 @@ pub struct PerformanceOptions {
   /// Request timeout in seconds
   #[arg(long, default_value = "30", value_name = "SECONDS")]
   pub timeout: u64,
+
+  /// Retries for throttled (429) or unavailable (5xx) API responses
+  #[arg(long, default_value = "3", value_name = "N")]
+  pub retries: u32,
+
+  /// Timeout in seconds for each image or attachment download
+  #[arg(long, default_value = "600", value_name = "SECONDS")]
+  pub asset_timeout: u64,
+
+  /// Retries for image and attachment downloads that time out or fail (defaults to --retries)
+  #[arg(long, value_name = "N")]
+  pub asset_retries: Option<u32>,
+
+  /// Bytes of each attachment download held in memory before being written to disk
+  #[arg(long, default_value = "8M", value_name = "SIZE", value_parser = parse_size)]
+  pub download_buffer: u64,
+
+  /// Results requested per page when listing child pages and attachments
+  #[arg(long, default_value = "25", value_name = "N")]
+  pub page_size: usize,
+
+  /// Record every API response to a cassette file for offline replay
+  #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with = "replay")]
+  pub record: Option<String>,
+
+  /// Answer API requests from a recorded cassette instead of Confluence (no credentials needed)
+  #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+  pub replay: Option<String>,
+
+  /// Cache converted pages in this directory and reuse them when content and options are unchanged
+  #[arg(long, env = "CONFLUENCE_DL_CACHE_DIR", value_name = "DIR", value_hint = ValueHint::DirPath)]
+  pub cache_dir: Option<String>,
+}
+
+impl Default for PerformanceOptions {
+  fn default() -> Self {
+    Self::parse_from(["confluence-dl"])
+  }
+}
+
+/// Named bundles of performance settings for tenants of different sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PerformancePreset {
+  /// Sequential, 2 requests/s, 5 retries: for small or heavily throttled tenants
+  Gentle,
+  /// The standard settings: 4 parallel, 10 requests/s, 3 retries
+  Default,
+  /// 16 parallel, 50 requests/s, 100 results per page: for large tenants with headroom
+  Aggressive,
+}
+
+/// Settings applied by a [`PerformancePreset`].
+struct PresetValues {
+  parallel: isize,
+  rate_limit: usize,
+  retries: u32,
+  page_size: usize,
+}
+
+impl PerformancePreset {
+  fn values(self) -> PresetValues {
+    match self {
+      PerformancePreset::Gentle => PresetValues {
+        parallel: 1,
+        rate_limit: 2,
+        retries: 5,
+        page_size: 25,
+      },
+      PerformancePreset::Default => PresetValues {
+        parallel: 4,
+        rate_limit: 10,
+        retries: 3,
+        page_size: 25,
+      },
+      PerformancePreset::Aggressive => PresetValues {
+        parallel: 16,
+        rate_limit: 50,
+        retries: 2,
+        page_size: 100,
+      },
+    }
+  }
 }
 
 impl PerformanceOptions {
@@ -250,6 +877,37 @@ impl PerformanceOptions {
     }
   }
 
+  /// Apply the selected preset to every setting not given explicitly, on
+  /// the command line or through an environment variable.
+  ///
+  /// # Arguments
+  /// * `matches` - Parsed arguments, used to tell explicit flags from defaults.
+  pub fn apply_preset(&mut self, matches: &ArgMatches) {
+    let Some(preset) = self.preset else {
+      return;
+    };
+    let values = preset.values();
+    let is_default = |id: &str| {
+      !matches!(
+        matches.value_source(id),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+      )
+    };
+
+    if is_default("parallel") {
+      self.parallel = values.parallel;
+    }
+    if is_default("rate_limit") {
+      self.rate_limit = values.rate_limit;
+    }
+    if is_default("retries") {
+      self.retries = values.retries;
+    }
+    if is_default("page_size") {
+      self.page_size = values.page_size;
+    }
+  }
+
   /// Human-readable label describing the parallel setting.
   pub fn parallel_label(&self) -> String {
     match self.parallel {
@@ -266,16 +924,26 @@ impl PerformanceOptions {
 impl Cli {
   /// Parse CLI arguments from the environment
   pub fn parse_args() -> Self {
-    let mut cli = Self::parse();
+    let matches = Self::command().get_matches();
+    let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    cli.performance.apply_preset(&matches);
+    cli.normalize();
+    cli
+  }
 
+  /// Apply shorthands and normalize values after parsing.
+  fn normalize(&mut self) {
     // Normalize URL: add https:// if no scheme is present
-    if let Some(url) = &cli.auth.url
+    if let Some(url) = &self.auth.url
       && !url.contains("://")
     {
-      cli.auth.url = Some(format!("https://{url}"));
+      self.auth.url = Some(format!("https://{url}"));
     }
 
-    cli
+    // `-o -` is the conventional spelling of `--stdout`
+    if self.output.output == "-" {
+      self.output.stdout = true;
+    }
   }
 
   /// Validate CLI arguments
@@ -299,6 +967,93 @@ impl Cli {
     if self.page.max_depth.is_some() && !self.page.children {
       return Err("--max-depth requires --children".to_string());
     }
+    if self.output.layout != OutputLayout::Flat
+      && matches!(
+        self.output.profile,
+        Some(ExportProfile::Docusaurus | ExportProfile::MkDocs)
+      )
+    {
+      return Err(
+        "--layout cannot be combined with --profile docusaurus or mkdocs, which lay out pages themselves".to_string(),
+      );
+    }
+    if self.behavior.keep_going && !(self.page.children || self.page.follow_links) {
+      return Err("--keep-going requires --children or --follow-links".to_string());
+    }
+    if self.output.prune.is_some() && !self.page.children {
+      return Err("--prune requires --children".to_string());
+    }
+    if self.output.prune.is_some() && !self.output.manifest {
+      return Err("--prune requires --manifest, which records the files of each export".to_string());
+    }
+    if self.output.sign && !self.output.manifest {
+      return Err("--sign requires --manifest, which records the files of each export".to_string());
+    }
+
+    if self.output.stdout && self.behavior.log_format == LogFormat::Json {
+      return Err("--stdout writes the page to stdout and cannot be combined with --log-format json".to_string());
+    }
+
+    if self.output.stdout
+      && (self.output.save_raw
+        || self.output.extract_tables.is_some()
+        || self.output.include_graph.is_some()
+        || self.output.changelog
+        || self.output.tasks_report
+        || self.output.sign)
+    {
+      return Err(
+        "--stdout cannot be combined with --save-raw, --extract-tables, --include-graph, --changelog, --tasks-report, \
+         or --sign"
+          .to_string(),
+      );
+    }
+
+    if self.output.stdout && (self.page.children || self.page.attachments || self.page.follow_links) {
+      return Err(
+        "--stdout exports a single page and cannot be combined with --children, --attachments, or --follow-links"
+          .to_string(),
+      );
+    }
+
+    if self.output.conflict.is_some() && self.output.format == OutputFormat::Json {
+      return Err("--conflict tracks edits in front matter and cannot be combined with --format json".to_string());
+    }
+
+    if self.output.profile.is_some() && self.output.format != OutputFormat::Markdown {
+      return Err("--profile requires --format markdown".to_string());
+    }
+
+    if self.output.markdown_flavor.is_some() && self.output.format.content_format() != OutputFormat::Markdown {
+      return Err("--markdown-flavor requires --format markdown".to_string());
+    }
+
+    if self.output.admonition_style.is_some() && self.output.format.content_format() != OutputFormat::Markdown {
+      return Err("--admonition-style requires --format markdown".to_string());
+    }
+
+    if self.output.emoji_style != EmojiStyle::Unicode && self.output.format.content_format() != OutputFormat::Markdown {
+      return Err("--emoji-style requires --format markdown".to_string());
+    }
+
+    if self.images_links.image_style != ImageStyle::Markdown
+      && self.output.format.content_format() != OutputFormat::Markdown
+    {
+      return Err("--image-style requires --format markdown".to_string());
+    }
+
+    let split_tables = self.output.split_table_rows.is_some() || self.output.split_table_columns.is_some();
+    if split_tables && self.output.format.content_format() != OutputFormat::Markdown {
+      return Err("--split-table-rows and --split-table-columns require --format markdown".to_string());
+    }
+
+    if self.output.split_table_rows == Some(0) || self.output.split_table_columns == Some(0) {
+      return Err("--split-table-rows and --split-table-columns must be at least 1".to_string());
+    }
+
+    if self.images_links.resolve_includes && self.output.format.content_format() != OutputFormat::Markdown {
+      return Err("--resolve-includes requires --format markdown".to_string());
+    }
 
     if self.performance.parallel == 0 || self.performance.parallel < -1 {
       return Err("--parallel must be at least 1 or -1 to use available cores".to_string());
@@ -308,6 +1063,14 @@ impl Cli {
       return Err("--rate-limit must be at least 1 request per second".to_string());
     }
 
+    if self.performance.page_size == 0 {
+      return Err("--page-size must be at least 1".to_string());
+    }
+
+    if self.images_links.link_title_rate_limit == 0 {
+      return Err("--link-title-rate-limit must be at least 1 request per second".to_string());
+    }
+
     Ok(())
   }
 }
@@ -317,10 +1080,10 @@ impl Cli {
 pub async fn run() {
   let cli = Cli::parse_args();
 
-  init_tracing(&cli.behavior);
-
   // Create color scheme based on user preference
-  let colors = ColorScheme::new(cli.behavior.color);
+  let colors = ColorScheme::with_theme(cli.behavior.color, cli.behavior.theme);
+
+  init_tracing(&cli.behavior, colors.is_enabled());
 
   // Validate CLI arguments
   if let Err(e) = cli.validate() {
@@ -331,8 +1094,31 @@ pub async fn run() {
   // Handle subcommands
   if let Some(ref command) = cli.command {
     match command {
-      Command::Ls { target, max_depth } => {
-        handle_ls_command(target, *max_depth, &cli, &colors).await;
+      Command::Ls {
+        target,
+        max_depth,
+        estimate,
+        format,
+      } => {
+        handle_ls_command(target, *max_depth, *estimate, *format, &cli, &colors).await;
+      }
+      Command::Stats {
+        target,
+        max_depth,
+        json,
+      } => {
+        handle_stats_command(target, *max_depth, *json, &cli, &colors).await;
+      }
+      Command::DiffPage { target, from, to, html } => {
+        handle_diff_page_command(target, *from, *to, html.as_deref(), &cli, &colors).await;
+      }
+      Command::Diff {
+        target,
+        max_depth,
+        dir,
+        patch,
+      } => {
+        handle_diff_command(target, *max_depth, dir.as_deref(), *patch, &cli, &colors).await;
       }
       Command::Auth { subcommand } => {
         handle_auth_command(subcommand, &cli, &colors).await;
@@ -350,7 +1136,7 @@ pub async fn run() {
   }
 }
 
-fn init_tracing(behavior: &BehaviorOptions) {
+fn init_tracing(behavior: &BehaviorOptions, ansi: bool) {
   let level = if behavior.quiet {
     LevelFilter::ERROR
   } else {
@@ -369,6 +1155,7 @@ fn init_tracing(behavior: &BehaviorOptions) {
   let _ = tracing_subscriber::fmt()
     .with_env_filter(env_filter)
     .with_target(false)
+    .with_ansi(ansi)
     .with_writer(std::io::stderr)
     .try_init();
 }
@@ -400,6 +1187,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -407,27 +1195,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -449,6 +1246,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -456,27 +1254,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -498,6 +1305,7 @@ mod tests {
         url: Some("https://example.com".to_string()),
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -505,27 +1313,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: Some(3),
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -543,6 +1360,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -550,27 +1368,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 0,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -588,6 +1415,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -595,27 +1423,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: -1,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -631,6 +1468,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -638,27 +1476,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: -2,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -667,6 +1514,351 @@ mod tests {
     assert!(result.unwrap_err().contains("--parallel must be at least 1 or -1"));
   }
 
+  #[test]
+  fn test_cli_extension_parse() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--extension",
+      ".mdx",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.output.extension.as_deref(), Some("mdx"));
+
+    assert!(
+      Cli::try_parse_from([
+        "confluence-dl",
+        "--extension",
+        "a/b",
+        "https://example.com/wiki/pages/123"
+      ])
+      .is_err()
+    );
+  }
+
+  #[test]
+  fn test_cli_prune_mode() {
+    use clap::Parser;
+
+    let url = "https://example.com/wiki/pages/123";
+    let cli = Cli::try_parse_from(["confluence-dl", "--children", "--prune", url]).unwrap();
+    assert_eq!(cli.output.prune, Some(PruneMode::List));
+    assert!(cli.validate().is_ok());
+    let cli = Cli::try_parse_from(["confluence-dl", "--children", "--prune=delete", url]).unwrap();
+    assert_eq!(cli.output.prune, Some(PruneMode::Delete));
+
+    let cli = Cli::try_parse_from(["confluence-dl", "--prune", url]).unwrap();
+    assert!(cli.validate().unwrap_err().contains("--prune requires --children"));
+    let cli = Cli::try_parse_from(["confluence-dl", "--children", "--prune", "--manifest", "false", url]).unwrap();
+    assert!(cli.validate().unwrap_err().contains("--prune requires --manifest"));
+  }
+
+  #[test]
+  fn test_cli_sign_requires_manifest() {
+    use clap::Parser;
+
+    let url = "https://example.com/wiki/pages/123";
+    let cli = Cli::try_parse_from(["confluence-dl", "--sign", url]).unwrap();
+    assert!(cli.validate().is_ok());
+    let err = Cli::try_parse_from(["confluence-dl", "--sign-key", "key", url]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+
+    let cli = Cli::try_parse_from(["confluence-dl", "--sign", "--sign-key", "key", url]).unwrap();
+    assert!(cli.validate().is_ok());
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--sign",
+      "--sign-key",
+      "key",
+      "--manifest",
+      "false",
+      url,
+    ])
+    .unwrap();
+    assert!(cli.validate().unwrap_err().contains("--sign requires --manifest"));
+  }
+
+  #[test]
+  fn test_cli_keep_going_requires_tree_export() {
+    let url = "https://example.com/wiki/pages/123";
+    let cli = Cli::try_parse_from(["confluence-dl", "--keep-going", url]).unwrap();
+    assert!(cli.validate().unwrap_err().contains("--keep-going requires"));
+    let cli = Cli::try_parse_from(["confluence-dl", "--children", "--keep-going", url]).unwrap();
+    assert!(cli.behavior.keep_going);
+    assert!(cli.validate().is_ok());
+  }
+
+  #[test]
+  fn test_cli_stdout_is_single_page_only() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["confluence-dl", "--stdout", "https://example.com/wiki/pages/123"]).unwrap();
+    assert!(cli.output.stdout);
+    assert!(cli.validate().is_ok());
+
+    for conflicting in ["--children", "--attachments", "--follow-links"] {
+      let cli = Cli::try_parse_from([
+        "confluence-dl",
+        "--stdout",
+        conflicting,
+        "https://example.com/wiki/pages/123",
+      ])
+      .unwrap();
+      assert!(cli.validate().unwrap_err().contains("--stdout"));
+    }
+    assert!(
+      Cli::try_parse_from([
+        "confluence-dl",
+        "--stdout",
+        "--save-raw",
+        "https://example.com/wiki/pages/123"
+      ])
+      .is_err()
+    );
+
+    let mut cli = Cli::try_parse_from(["confluence-dl", "-o", "-", "https://example.com/wiki/pages/123"]).unwrap();
+    cli.normalize();
+    assert!(cli.output.stdout);
+    assert!(cli.validate().is_ok());
+
+    let mut cli = Cli::try_parse_from([
+      "confluence-dl",
+      "-o",
+      "-",
+      "--sign",
+      "--sign-key",
+      "key",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    cli.normalize();
+    assert!(cli.validate().unwrap_err().contains("--sign"));
+  }
+  #[test]
+  fn test_cli_credential_source() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--url",
+      "https://example.atlassian.net",
+      "auth",
+      "login",
+    ])
+    .unwrap();
+    assert!(matches!(
+      cli.command,
+      Some(Command::Auth {
+        subcommand: AuthCommand::Login
+      })
+    ));
+
+    let cli = Cli::try_parse_from(["confluence-dl", "--credential-source", "keyring", "123"]).unwrap();
+    assert_eq!(cli.auth.credential_source, CredentialSource::Keyring);
+    assert!(Cli::try_parse_from(["confluence-dl", "--credential-source", "vault", "123"]).is_err());
+  }
+
+  #[test]
+  fn test_cli_profile_requires_markdown() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--profile",
+      "docusaurus",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.output.profile, Some(ExportProfile::Docusaurus));
+    assert!(cli.validate().is_ok());
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--profile",
+      "mkdocs",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.output.profile, Some(ExportProfile::MkDocs));
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--profile",
+      "docusaurus",
+      "--format",
+      "adoc",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert!(
+      cli
+        .validate()
+        .unwrap_err()
+        .contains("--profile requires --format markdown")
+    );
+  }
+
+  #[test]
+  fn test_cli_json_format() {
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--format",
+      "json",
+      "--markdown-flavor",
+      "gfm",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.output.format, OutputFormat::Json);
+    assert!(cli.validate().is_ok());
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--format",
+      "json",
+      "--conflict",
+      "skip",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert!(cli.validate().unwrap_err().contains("--conflict"));
+  }
+
+  #[test]
+  fn test_cli_exclude_subtree() {
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--children",
+      "--exclude-subtree",
+      "456",
+      "--exclude-id",
+      "789",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.page.exclude_id, vec!["456", "789"]);
+
+    let result = Cli::try_parse_from(["confluence-dl", "--exclude-subtree", "456", "123"]);
+    assert!(result.is_err());
+  }
+  #[test]
+  fn test_cli_modified_window() {
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--children",
+      "--modified-since",
+      "2023-07-01",
+      "--modified-until",
+      "2024-06-30",
+      "123",
+    ])
+    .unwrap();
+    assert_eq!(
+      cli.page.modified_since.unwrap().to_rfc3339(),
+      "2023-07-01T00:00:00+00:00"
+    );
+    assert_eq!(
+      cli.page.modified_until.unwrap().to_rfc3339(),
+      "2024-06-30T23:59:59.999999999+00:00"
+    );
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--children",
+      "--modified-since",
+      "2024-01-01T09:00:00+02:00",
+      "123",
+    ])
+    .unwrap();
+    assert_eq!(
+      cli.page.modified_since.unwrap().to_rfc3339(),
+      "2024-01-01T07:00:00+00:00"
+    );
+
+    assert!(Cli::try_parse_from(["confluence-dl", "--children", "--modified-since", "last year", "123"]).is_err());
+    assert!(Cli::try_parse_from(["confluence-dl", "--modified-since", "2024-01-01", "123"]).is_err());
+  }
+
+  #[test]
+  fn test_cli_markdown_flavor() {
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--markdown-flavor",
+      "commonmark",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.output.markdown_flavor, Some(MarkdownFlavor::CommonMark));
+    assert!(cli.validate().is_ok());
+
+    let cli = Cli::try_parse_from([
+      "confluence-dl",
+      "--admonition-style",
+      "github",
+      "https://example.com/wiki/pages/123",
+    ])
+    .unwrap();
+    assert_eq!(cli.output.admonition_style, Some(AdmonitionStyle::GitHub));
+
+    let result = Cli::try_parse_from([
+      "confluence-dl",
+      "--markdown-flavor",
+      "gfm",
+      "--profile",
+      "docusaurus",
+      "https://example.com/wiki/pages/123",
+    ]);
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_cli_preset_respects_explicit_flags() {
+    use clap::{CommandFactory, FromArgMatches};
+
+    let matches = Cli::command().get_matches_from([
+      "confluence-dl",
+      "--preset",
+      "gentle",
+      "--rate-limit",
+      "7",
+      "https://example.com/wiki/pages/123",
+    ]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.performance.apply_preset(&matches);
+
+    assert_eq!(cli.performance.parallel, 1);
+    assert_eq!(cli.performance.rate_limit, 7);
+    assert_eq!(cli.performance.retries, 5);
+    assert_eq!(cli.performance.page_size, 25);
+  }
+
+  #[test]
+  fn test_cli_preset_respects_environment_values() {
+    use clap::{CommandFactory, FromArgMatches};
+
+    unsafe {
+      std::env::set_var("CONFLUENCE_DL_TEST_RETRIES", "9");
+    }
+    let matches = Cli::command()
+      .mut_arg("retries", |arg| arg.env("CONFLUENCE_DL_TEST_RETRIES"))
+      .get_matches_from([
+        "confluence-dl",
+        "--preset",
+        "gentle",
+        "https://example.com/wiki/pages/123",
+      ]);
+    unsafe {
+      std::env::remove_var("CONFLUENCE_DL_TEST_RETRIES");
+    }
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.performance.apply_preset(&matches);
+
+    assert_eq!(cli.performance.retries, 9);
+    assert_eq!(cli.performance.parallel, 1);
+  }
+
   #[test]
   fn test_cli_parallel_auto_parse() {
     use clap::Parser;
@@ -691,6 +1883,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -698,27 +1891,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -738,6 +1940,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -745,27 +1948,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -782,6 +1994,7 @@ mod tests {
         url: Some("https://example.com".to_string()),
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -789,27 +2002,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: false,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
@@ -826,6 +2048,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: "./output".to_string(),
@@ -833,27 +2056,36 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: false,
         color: ColorOption::Auto,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: true,
         max_depth: Some(3),
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: true,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 4,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
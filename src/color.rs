@@ -1,151 +1,192 @@
 //! Color utilities for terminal output
 //!
 //! This module provides consistent color handling across the application,
-//! respecting user preferences and terminal capabilities.
+//! respecting user preferences and terminal capabilities. With `--color auto`
+//! the `NO_COLOR`, `CLICOLOR`, and `CLICOLOR_FORCE` conventions are honored,
+//! and `--theme` picks a palette suited to the terminal background.
 
-use owo_colors::OwoColorize;
+use std::ffi::OsString;
+
+use clap::ValueEnum;
+use owo_colors::{OwoColorize, Style};
 
 use crate::cli::ColorOption;
 
+/// Palette used for colored output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Theme {
+  /// Bright colors for dark terminal backgrounds (default)
+  #[default]
+  Dark,
+  /// Darker colors that stay readable on light backgrounds
+  Light,
+  /// No colors; bold, underline, and dim text only
+  Plain,
+}
+
 /// Color scheme for the application
 ///
 /// This provides semantic color names that make the code more readable
 /// and ensure consistent visual design across the application.
 pub struct ColorScheme {
   enabled: bool,
+  theme: Theme,
 }
 
 impl ColorScheme {
   /// Create a new color scheme based on user preference and terminal
-  /// capabilities
+  /// capabilities, using the default theme
   pub fn new(color_option: ColorOption) -> Self {
-    let enabled = match color_option {
-      ColorOption::Always => true,
-      ColorOption::Never => false,
-      ColorOption::Auto => {
-        // Check if stdout is a TTY
-        use std::io::IsTerminal;
-        std::io::stdout().is_terminal()
-      }
-    };
+    Self::with_theme(color_option, Theme::default())
+  }
 
-    Self { enabled }
+  /// Create a color scheme with the given theme.
+  ///
+  /// # Arguments
+  /// * `color_option` - The `--color` setting.
+  /// * `theme` - Palette used when colors are enabled.
+  pub fn with_theme(color_option: ColorOption, theme: Theme) -> Self {
+    use std::io::IsTerminal;
+    let enabled = colors_enabled(
+      color_option,
+      |name| std::env::var_os(name),
+      std::io::stdout().is_terminal(),
+    );
+    Self { enabled, theme }
   }
 
   /// Check if colors are enabled
-  #[allow(dead_code)]
   pub fn is_enabled(&self) -> bool {
     self.enabled
   }
 
+  /// Apply the style of the active theme, or nothing when colors are off.
+  fn paint<T: std::fmt::Display>(&self, text: T, dark: Style, light: Style, plain: Style) -> String {
+    if !self.enabled {
+      return text.to_string();
+    }
+    let style = match self.theme {
+      Theme::Dark => dark,
+      Theme::Light => light,
+      Theme::Plain => plain,
+    };
+    format!("{}", text.style(style))
+  }
+
   // Semantic color methods for different message types
 
   /// Style for success messages (green)
   pub fn success<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.green())
-    } else {
-      text.to_string()
-    }
+    self.paint(text, Style::new().green(), Style::new().green(), Style::new())
   }
 
   /// Style for error messages (bright red)
   pub fn error<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.bright_red().bold())
-    } else {
-      text.to_string()
-    }
+    self.paint(
+      text,
+      Style::new().bright_red().bold(),
+      Style::new().red().bold(),
+      Style::new().bold(),
+    )
   }
 
   /// Style for warning messages (yellow)
   pub fn warning<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.yellow())
-    } else {
-      text.to_string()
-    }
+    self.paint(text, Style::new().yellow(), Style::new().yellow().bold(), Style::new())
   }
 
   /// Style for info messages (cyan)
   pub fn info<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.cyan())
-    } else {
-      text.to_string()
-    }
+    self.paint(text, Style::new().cyan(), Style::new().blue(), Style::new())
   }
 
   /// Style for debug messages (bright black/gray)
   #[allow(dead_code)]
   pub fn debug<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.bright_black())
-    } else {
-      text.to_string()
-    }
+    self.paint(
+      text,
+      Style::new().bright_black(),
+      Style::new().bright_black(),
+      Style::new(),
+    )
   }
 
   /// Style for emphasis/important text (bright white, bold)
   pub fn emphasis<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.bright_white().bold())
-    } else {
-      text.to_string()
-    }
+    self.paint(
+      text,
+      Style::new().bright_white().bold(),
+      Style::new().bold(),
+      Style::new().bold(),
+    )
   }
 
   /// Style for URLs and links (blue, underlined)
   pub fn link<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.blue().underline())
-    } else {
-      text.to_string()
-    }
+    self.paint(
+      text,
+      Style::new().blue().underline(),
+      Style::new().blue().underline(),
+      Style::new().underline(),
+    )
   }
 
   /// Style for file paths (magenta)
   pub fn path<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.magenta())
-    } else {
-      text.to_string()
-    }
+    self.paint(text, Style::new().magenta(), Style::new().magenta(), Style::new())
   }
 
   /// Style for numbers and metrics (bright blue)
   pub fn number<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.bright_blue())
-    } else {
-      text.to_string()
-    }
+    self.paint(text, Style::new().bright_blue(), Style::new().blue(), Style::new())
   }
 
   /// Style for commands and code (bright green, monospace feel via styling)
   pub fn code<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.bright_green())
-    } else {
-      text.to_string()
-    }
+    self.paint(text, Style::new().bright_green(), Style::new().green(), Style::new())
   }
 
   /// Style for dimmed/secondary text (gray)
   pub fn dimmed<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.dimmed())
-    } else {
-      text.to_string()
-    }
+    self.paint(
+      text,
+      Style::new().dimmed(),
+      Style::new().dimmed(),
+      Style::new().dimmed(),
+    )
   }
 
   /// Style for progress indicators (bright cyan)
   pub fn progress<T: std::fmt::Display>(&self, text: T) -> String {
-    if self.enabled {
-      format!("{}", text.bright_cyan())
-    } else {
-      text.to_string()
+    self.paint(text, Style::new().bright_cyan(), Style::new().cyan(), Style::new())
+  }
+}
+
+/// Decide whether output is styled.
+///
+/// `--color always` and `--color never` win. With `auto`, a non-empty
+/// `NO_COLOR` disables styling, a `CLICOLOR_FORCE` other than `0` enables it
+/// even when not writing to a terminal, and `CLICOLOR=0` disables it;
+/// otherwise output is styled only on a terminal.
+///
+/// # Arguments
+/// * `color_option` - The `--color` setting.
+/// * `env` - Looks up an environment variable.
+/// * `is_terminal` - Whether stdout is a terminal.
+fn colors_enabled(color_option: ColorOption, env: impl Fn(&str) -> Option<OsString>, is_terminal: bool) -> bool {
+  match color_option {
+    ColorOption::Always => true,
+    ColorOption::Never => false,
+    ColorOption::Auto => {
+      if env("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        false
+      } else if env("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+        true
+      } else if env("CLICOLOR").is_some_and(|value| value == "0") {
+        false
+      } else {
+        is_terminal
+      }
     }
   }
 }
@@ -164,9 +205,10 @@ impl ColorScheme {
 //    - Ensure sufficient contrast between text and background
 //
 // 4. **Respect User Preferences**: Always honor the --color flag:
-//    - auto: Detect terminal capabilities
+//    - auto: Detect terminal capabilities and honor NO_COLOR / CLICOLOR / CLICOLOR_FORCE
 //    - always: Force colors (for piping to files that will be viewed later)
 //    - never: No colors (for CI/CD, logs, accessibility)
+//    and print through a ColorScheme so --theme applies everywhere.
 //
 // 5. **Progressive Enhancement**: The application should work perfectly without colors. Colors are a visual
 //    enhancement, not a requirement.
@@ -234,4 +276,50 @@ mod tests {
     assert!(!scheme.dimmed(text).is_empty());
     assert!(!scheme.progress(text).is_empty());
   }
+
+  #[test]
+  fn test_color_environment_variables() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+      move |name: &str| {
+        vars
+          .iter()
+          .find(|(key, _)| *key == name)
+          .map(|(_, value)| OsString::from(value))
+      }
+    };
+
+    assert!(colors_enabled(ColorOption::Auto, env(&[]), true));
+    assert!(!colors_enabled(ColorOption::Auto, env(&[]), false));
+    assert!(!colors_enabled(ColorOption::Auto, env(&[("NO_COLOR", "1")]), true));
+    assert!(colors_enabled(ColorOption::Auto, env(&[("NO_COLOR", "")]), true));
+    assert!(!colors_enabled(ColorOption::Auto, env(&[("CLICOLOR", "0")]), true));
+    assert!(colors_enabled(
+      ColorOption::Auto,
+      env(&[("CLICOLOR_FORCE", "1")]),
+      false
+    ));
+    assert!(!colors_enabled(
+      ColorOption::Auto,
+      env(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]),
+      true
+    ));
+    assert!(colors_enabled(ColorOption::Always, env(&[("NO_COLOR", "1")]), false));
+    assert!(!colors_enabled(
+      ColorOption::Never,
+      env(&[("CLICOLOR_FORCE", "1")]),
+      true
+    ));
+  }
+
+  #[test]
+  fn test_themes() {
+    let light = ColorScheme::with_theme(ColorOption::Always, Theme::Light);
+    assert_eq!(light.emphasis("test"), "\u{1b}[1mtest\u{1b}[0m");
+    assert_eq!(light.number("7"), "\u{1b}[34m7\u{1b}[0m");
+
+    let plain = ColorScheme::with_theme(ColorOption::Always, Theme::Plain);
+    assert_eq!(plain.success("test"), "test");
+    assert_eq!(plain.error("test"), "\u{1b}[1mtest\u{1b}[0m");
+    assert_eq!(plain.link("test"), "\u{1b}[4mtest\u{1b}[0m");
+  }
 }
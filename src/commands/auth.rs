@@ -1,19 +1,25 @@
 //! Authentication subcommand handlers.
 //!
-//! Covers both `confluence-dl auth test`, which performs a live API call, and
+//! Covers `confluence-dl auth test`, which performs a live API call,
 //! `confluence-dl auth show`, which prints the currently detected credential
-//! sources.
+//! sources, and `confluence-dl auth login`/`logout`, which manage credentials
+//! stored in the OS keyring.
 
+use std::io::{self, BufRead, Write};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::process;
+use std::sync::Arc;
+use std::time::Duration;
 
 use clap::Subcommand;
 
 use crate::cli::Cli;
 use crate::color::ColorScheme;
-use crate::confluence::{self, ConfluenceApi};
-use crate::credentials::{CredentialsProvider, NetrcProvider};
+use crate::confluence::{self, Cassette, ConfluenceApi};
+use crate::credentials::{
+  Credential, CredentialError, CredentialSource, CredentialsProvider, KeyringProvider, NetrcProvider,
+};
 
 /// Authentication subcommands exposed under `confluence-dl auth`.
 #[derive(Debug, Subcommand)]
@@ -23,13 +29,20 @@ pub enum AuthCommand {
 
   /// Display current authentication configuration (without sensitive data).
   Show,
+
+  /// Verify credentials and store them in the OS keyring.
+  Login,
+
+  /// Remove credentials stored in the OS keyring.
+  Logout,
 }
 
 /// Dispatch the authentication subcommands defined under `confluence-dl auth`.
 ///
 /// `auth test` validates that the provided credentials work against the
-/// Confluence API, while `auth show` prints a human-readable summary of the
-/// resolved credential sources.
+/// Confluence API, `auth show` prints a human-readable summary of the
+/// resolved credential sources, and `auth login`/`auth logout` store or
+/// remove credentials in the OS keyring.
 ///
 /// # Arguments
 /// * `subcommand` - Auth-specific variant to execute.
@@ -53,7 +66,9 @@ pub async fn handle_auth_command(subcommand: &AuthCommand, cli: &Cli, colors: &C
       println!("{} {}", colors.info("→"), colors.info("Testing authentication"));
       println!("  {}: {}", colors.emphasis("URL"), colors.link(base_url));
 
-      warn_if_insecure_netrc(colors);
+      if cli.auth.credential_source == CredentialSource::Netrc {
+        warn_if_insecure_netrc(colors);
+      }
 
       // Load credentials
       let (username, token) = match load_credentials(base_url, cli) {
@@ -70,6 +85,7 @@ pub async fn handle_auth_command(subcommand: &AuthCommand, cli: &Cli, colors: &C
           eprintln!("     • CLI flags: --user and --token");
           eprintln!("     • Environment variables: CONFLUENCE_USER and CONFLUENCE_TOKEN");
           eprintln!("     • ~/.netrc file");
+          eprintln!("     • OS keyring: confluence-dl auth login, then --credential-source keyring");
           process::exit(2);
         }
       };
@@ -83,6 +99,8 @@ pub async fn handle_auth_command(subcommand: &AuthCommand, cli: &Cli, colors: &C
         &token,
         cli.performance.timeout,
         cli.performance.rate_limit,
+        cli.performance.retries,
+        cli.performance.page_size,
       ) {
         Ok(c) => c,
         Err(e) => {
@@ -142,9 +160,112 @@ pub async fn handle_auth_command(subcommand: &AuthCommand, cli: &Cli, colors: &C
     AuthCommand::Show => {
       show_auth_config(cli, colors);
     }
+    AuthCommand::Login => {
+      if let Err(e) = login(cli, colors).await {
+        eprintln!("\n{} {}", colors.error("✗"), colors.error("Login failed"));
+        eprintln!("  {e:#}");
+        process::exit(2);
+      }
+    }
+    AuthCommand::Logout => {
+      let Some(host) = cli.auth.url.as_deref().and_then(extract_host) else {
+        eprintln!("{} {}", colors.error("✗"), colors.error("Base URL not provided"));
+        eprintln!("  confluence-dl --url https://your-instance.atlassian.net auth logout");
+        process::exit(1);
+      };
+      match KeyringProvider::new().delete(&host) {
+        Ok(true) => println!(
+          "{} Removed stored credentials for {}",
+          colors.success("✓"),
+          colors.emphasis(&host)
+        ),
+        Ok(false) => println!(
+          "{} No stored credentials for {}",
+          colors.info("ℹ"),
+          colors.emphasis(&host)
+        ),
+        Err(e) => {
+          eprintln!("{} {}", colors.error("✗"), colors.error("Failed to remove credentials"));
+          eprintln!("  {e}");
+          process::exit(1);
+        }
+      }
+    }
   }
 }
 
+/// Verify credentials against the API and store them in the OS keyring.
+///
+/// The username and token come from `--user`/`--token` (or their environment
+/// variables) when given and are prompted for otherwise; the token prompt
+/// does not echo.
+///
+/// # Errors
+/// Returns an error when no base URL is configured, the prompts cannot be
+/// read, the credentials are rejected by Confluence, or the keyring refuses
+/// the entry.
+async fn login(cli: &Cli, colors: &ColorScheme) -> anyhow::Result<()> {
+  let base_url = cli.auth.url.as_deref().ok_or_else(|| {
+    anyhow::anyhow!("Base URL not provided; pass --url https://your-instance.atlassian.net or set CONFLUENCE_URL")
+  })?;
+  let host = extract_host(base_url).ok_or_else(|| anyhow::anyhow!("Invalid base URL"))?;
+
+  println!("{} {}", colors.info("→"), colors.info("Logging in"));
+  println!("  {}: {}", colors.emphasis("URL"), colors.link(base_url));
+
+  let username = match &cli.auth.user {
+    Some(user) => user.clone(),
+    None => prompt_line("  Email: ")?,
+  };
+  let token = match &cli.auth.token {
+    Some(token) => token.clone(),
+    None => rpassword::prompt_password("  API token: ")?.trim().to_string(),
+  };
+  if username.is_empty() || token.is_empty() {
+    anyhow::bail!("Both an email and an API token are required");
+  }
+
+  let client = confluence::ConfluenceClient::new(
+    base_url,
+    &username,
+    &token,
+    cli.performance.timeout,
+    cli.performance.rate_limit,
+    cli.performance.retries,
+    cli.performance.page_size,
+  )?;
+  let user_info = client.test_auth().await?;
+
+  KeyringProvider::new().store(
+    &host,
+    &Credential {
+      username,
+      password: token,
+    },
+  )?;
+
+  println!(
+    "\n{} Logged in as {}; credentials stored in the OS keyring",
+    colors.success("✓"),
+    colors.emphasis(&user_info.display_name)
+  );
+  println!(
+    "  {} {}",
+    colors.dimmed("Use them with"),
+    colors.code("--credential-source keyring")
+  );
+  Ok(())
+}
+
+/// Print a prompt and read one trimmed line from stdin.
+fn prompt_line(prompt: &str) -> io::Result<String> {
+  print!("{prompt}");
+  io::stdout().flush()?;
+  let mut line = String::new();
+  io::stdin().lock().read_line(&mut line)?;
+  Ok(line.trim().to_string())
+}
+
 /// Display the currently configured authentication sources and values.
 ///
 /// The output highlights whether values came from CLI flags, environment
@@ -193,12 +314,12 @@ fn show_auth_config(cli: &Cli, colors: &ColorScheme) {
     "not set"
   };
 
-  // Try to get credentials from .netrc if URL is provided but user/token are not
+  // Try the configured credential store if URL is provided but user/token are not
+  let source = cli.auth.credential_source;
   let netrc_creds = if username.is_none() || token.is_none() {
-    url.and_then(extract_host).and_then(|host| {
-      let provider = NetrcProvider::new();
-      provider.get_credentials(&host).ok().flatten()
-    })
+    url
+      .and_then(extract_host)
+      .and_then(|host| stored_credentials(&host, source).ok().flatten())
   } else {
     None
   };
@@ -209,7 +330,7 @@ fn show_auth_config(cli: &Cli, colors: &ColorScheme) {
     println!("  {}: {}", colors.dimmed("Source"), colors.dimmed(user_source));
   } else if let Some(ref creds) = netrc_creds {
     println!("\n{}: {}", colors.emphasis("Username"), creds.username);
-    println!("  {}: {}", colors.dimmed("Source"), colors.dimmed(".netrc file"));
+    println!("  {}: {}", colors.dimmed("Source"), colors.dimmed(source.label()));
   } else {
     println!("\n{}: {}", colors.emphasis("Username"), colors.dimmed("(not set)"));
   }
@@ -231,14 +352,14 @@ fn show_auth_config(cli: &Cli, colors: &ColorScheme) {
   } else if netrc_creds.is_some() {
     // We have a password from .netrc but don't show it
     println!("\n{}: {}", colors.emphasis("API Token"), colors.dimmed("********"));
-    println!("  {}: {}", colors.dimmed("Source"), colors.dimmed(".netrc file"));
+    println!("  {}: {}", colors.dimmed("Source"), colors.dimmed(source.label()));
   } else {
     println!("\n{}: {}", colors.emphasis("API Token"), colors.dimmed("(not set)"));
   }
 
-  // Display .netrc information if found
+  // Display stored credential information if found
   if netrc_creds.is_some() && (username.is_none() || token.is_none()) {
-    println!("\n{} Credentials found in .netrc", colors.info("ℹ"));
+    println!("\n{} Credentials found in {}", colors.info("ℹ"), source.label());
     if let Some(host) = url.and_then(extract_host) {
       println!("  {}: {}", colors.dimmed("Host"), host);
     }
@@ -269,36 +390,49 @@ fn show_auth_config(cli: &Cli, colors: &ColorScheme) {
     if !has_token {
       println!("  Missing: API token (use --token or CONFLUENCE_TOKEN)");
     }
-    println!("\n  Or add credentials to ~/.netrc:");
-    if let Some(url_str) = url
-      && let Some(host) = extract_host(url_str)
-    {
-      println!("    machine {host}");
+    match source {
+      CredentialSource::Netrc => {
+        println!("\n  Or add credentials to ~/.netrc:");
+        if let Some(url_str) = url
+          && let Some(host) = extract_host(url_str)
+        {
+          println!("    machine {host}");
+        }
+        println!("      login your.email@example.com");
+        println!("      password your-api-token");
+      }
+      CredentialSource::Keyring => {
+        println!("\n  Or store them in the OS keyring:");
+        println!(
+          "    confluence-dl --url {} auth login",
+          url.unwrap_or("https://your-instance.atlassian.net")
+        );
+      }
+      CredentialSource::Env => {}
     }
-    println!("      login your.email@example.com");
-    println!("      password your-api-token");
   } else {
     println!("\n{} {}", colors.success("✓"), colors.success("Credentials configured"));
   }
 }
 
 /// Resolve Confluence credentials from CLI flags, environment variables, or
-/// `.netrc`.
+/// the credential store selected by `--credential-source`.
 ///
 /// The lookup order honors explicit CLI input first, then falls back to the
-/// host-specific entry in `.netrc`. The helper returns both username and API
+/// host-specific entry in `.netrc` or the OS keyring. The helper returns both username and API
 /// token so callers can immediately construct an API client.
 ///
 /// # Arguments
-/// * `base_url` - Base Confluence URL whose host is used for `.netrc` lookups.
+/// * `base_url` - Base Confluence URL whose host is used for credential store lookups.
 /// * `cli` - Parsed CLI struct carrying the `--user`/`--token` overrides.
 ///
 /// # Returns
 /// A tuple of `(username, token)` suitable for authenticating with Confluence.
 ///
 /// # Errors
-/// Returns an error when the base URL is invalid, when `.netrc` parsing fails,
-/// or when no credential source provides both username and token.
+/// Returns an error when the base URL is invalid, when the credential store
+/// cannot be read, or when no credential source provides both username and
+/// token.
 pub(crate) fn load_credentials(base_url: &str, cli: &Cli) -> anyhow::Result<(String, String)> {
   // Try CLI args or env vars first
   let username = cli.auth.user.clone();
@@ -309,19 +443,88 @@ pub(crate) fn load_credentials(base_url: &str, cli: &Cli) -> anyhow::Result<(Str
     return Ok((user, tok));
   }
 
-  // Try to load from .netrc
+  // Try to load from the configured credential store
   let host = extract_host(base_url).ok_or_else(|| anyhow::anyhow!("Invalid base URL"))?;
 
-  let provider = NetrcProvider::new();
-  if let Some(creds) = provider.get_credentials(&host)? {
+  let source = cli.auth.credential_source;
+  if let Some(creds) = stored_credentials(&host, source)? {
     let user = cli.auth.user.clone().unwrap_or(creds.username);
     let tok = cli.auth.token.clone().unwrap_or(creds.password);
     return Ok((user, tok));
   }
 
-  anyhow::bail!(
-    "Credentials not found. Provide --user and --token, set CONFLUENCE_USER and CONFLUENCE_TOKEN, or add to ~/.netrc"
+  match source {
+    CredentialSource::Netrc => anyhow::bail!(
+      "Credentials not found. Provide --user and --token, set CONFLUENCE_USER and CONFLUENCE_TOKEN, or add to ~/.netrc"
+    ),
+    CredentialSource::Keyring => anyhow::bail!(
+      "No credentials for {host} in the OS keyring. Run `confluence-dl --url {base_url} auth login`, or provide --user and --token"
+    ),
+    CredentialSource::Env => {
+      anyhow::bail!("Credentials not found. Provide --user and --token, or set CONFLUENCE_USER and CONFLUENCE_TOKEN")
+    }
+  }
+}
+
+/// Look up the stored credentials for `host` in the selected source.
+///
+/// # Returns
+/// `Ok(None)` when the source has no entry for the host, and always for
+/// [`CredentialSource::Env`], which has no store.
+fn stored_credentials(host: &str, source: CredentialSource) -> Result<Option<Credential>, CredentialError> {
+  match source {
+    CredentialSource::Netrc => NetrcProvider::new().get_credentials(host),
+    CredentialSource::Keyring => KeyringProvider::new().get_credentials(host),
+    CredentialSource::Env => Ok(None),
+  }
+}
+
+/// Build an API client for `base_url` from the CLI's credentials and
+/// performance settings.
+///
+/// With `--record`, responses are saved to the cassette file as they arrive.
+/// With `--replay`, responses come from the cassette and no credentials are
+/// required.
+///
+/// # Arguments
+/// * `base_url` - Base Confluence URL of the instance to talk to.
+/// * `cli` - Parsed CLI options.
+///
+/// # Returns
+/// A client ready for API calls.
+///
+/// # Errors
+/// Returns an error when credentials cannot be resolved, the replay cassette
+/// cannot be loaded, or the client cannot be built.
+pub(crate) fn create_client(base_url: &str, cli: &Cli) -> anyhow::Result<confluence::ConfluenceClient> {
+  let cassette = match (&cli.performance.record, &cli.performance.replay) {
+    (_, Some(path)) => Some(Arc::new(Cassette::replay(path)?)),
+    (Some(path), None) => Some(Arc::new(Cassette::record(path))),
+    (None, None) => None,
+  };
+  let (username, token) = match &cassette {
+    Some(cassette) if cassette.is_replay() => (String::new(), String::new()),
+    _ => load_credentials(base_url, cli)?,
+  };
+
+  let client = confluence::ConfluenceClient::new(
+    base_url,
+    username,
+    token,
+    cli.performance.timeout,
+    cli.performance.rate_limit,
+    cli.performance.retries,
+    cli.performance.page_size,
+  )?
+  .with_asset_limits(
+    Duration::from_secs(cli.performance.asset_timeout),
+    cli.performance.asset_retries.unwrap_or(cli.performance.retries),
   )
+  .with_download_buffer(usize::try_from(cli.performance.download_buffer).unwrap_or(usize::MAX));
+  Ok(match cassette {
+    Some(cassette) => client.with_cassette(cassette),
+    None => client,
+  })
 }
 
 /// Extract the hostname component from a Confluence base URL string.
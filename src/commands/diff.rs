@@ -0,0 +1,495 @@
+//! `diff` subcommand for comparing live Confluence content with an export.
+//!
+//! This module powers `confluence-dl diff`, which reads the `manifest.json` of
+//! an earlier export, fetches the current page tree, and reports the pages
+//! added, removed, and modified in Confluence since the export was written.
+//! With `--patch`, modified pages are converted again the way an export of
+//! the tree writes them and a unified diff against the exported file is
+//! printed, so changes can be reviewed before the export is refreshed and
+//! re-published.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::{fs, process};
+
+use anyhow::{Context, Result};
+
+use crate::cli::Cli;
+use crate::color::ColorScheme;
+use crate::commands::auth::create_client;
+use crate::commands::diff_page::{colorize_diff_line, render_unified_diff};
+use crate::commands::ls::resolve_url_info;
+use crate::commands::page::{
+  build_export_scope, build_external_image_fetcher, build_filename_options, build_link_context, build_process_options,
+  build_write_options, load_front_matter_rules, load_template,
+};
+use crate::confluence::{self, ConfluenceApi, Page, PageTree};
+use crate::external_images::ExternalImageFetcher;
+use crate::format::OutputFormat;
+use crate::front_matter::FrontMatter;
+use crate::front_matter_rules::FrontMatterRules;
+use crate::includes::IncludeResolver;
+use crate::link_titles::LinkTitleFetcher;
+use crate::links::LinkContext;
+use crate::manifest::{self, ManifestPage};
+use crate::processed_page::{ProcessOptions, TreeFilenames, process_page};
+use crate::templates::PageTemplate;
+use crate::users::UserResolver;
+
+/// A page that differs between Confluence and the export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PageChange {
+  id: String,
+  title: String,
+  /// Path of the exported file relative to the export root, when exported.
+  path: Option<String>,
+  /// How the page changed, for modified pages.
+  detail: Option<String>,
+}
+
+/// Differences between the live page tree and an export.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ExportDiff {
+  added: Vec<PageChange>,
+  removed: Vec<PageChange>,
+  modified: Vec<PageChange>,
+}
+
+impl ExportDiff {
+  fn is_empty(&self) -> bool {
+    self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+  }
+}
+
+/// Execute the `diff` subcommand.
+///
+/// # Arguments
+/// * `target` - Page URL or numeric page ID the export was made from.
+/// * `max_depth` - Optional traversal depth limit (0 covers only the root).
+/// * `dir` - Directory of the earlier export; defaults to `--output`.
+/// * `patch` - Print unified diffs of the modified pages.
+/// * `cli` - Top-level CLI options for auth, output format, and networking.
+/// * `colors` - Shared color palette used to render terminal output.
+pub async fn handle_diff_command(
+  target: &str,
+  max_depth: Option<usize>,
+  dir: Option<&str>,
+  patch: bool,
+  cli: &Cli,
+  colors: &ColorScheme,
+) {
+  if let Err(error) = run_diff_command(target, max_depth, dir, patch, cli, colors).await {
+    eprintln!(
+      "{} {}",
+      colors.error("✗"),
+      colors.error("Failed to compare the export with Confluence")
+    );
+    eprintln!("  {}: {}", colors.emphasis("Error"), error);
+    process::exit(1);
+  }
+}
+
+async fn run_diff_command(
+  target: &str,
+  max_depth: Option<usize>,
+  dir: Option<&str>,
+  patch: bool,
+  cli: &Cli,
+  colors: &ColorScheme,
+) -> Result<()> {
+  let root = Path::new(dir.unwrap_or(&cli.output.output));
+  let exported = manifest::read_pages(root).context("Could not read the manifest of the earlier export")?;
+
+  let url_info = resolve_url_info(target.trim(), cli).context("Could not determine page identifier")?;
+  let client = create_client(&url_info.base_url, cli).context("Unable to construct Confluence API client")?;
+  let tree = confluence::get_page_tree(
+    &client,
+    &url_info.page_id,
+    max_depth,
+    &confluence::PageFilter::default(),
+    cli.performance.resolved_parallel(),
+  )
+  .await?;
+
+  let mut live = Vec::new();
+  collect_pages(&tree, &mut live);
+  let diff = compare(&live, &exported);
+  for line in format_report_lines(&diff, &root.display().to_string(), colors) {
+    println!("{line}");
+  }
+
+  if patch {
+    let renderer = PageRenderer::new(&tree, root, cli)?;
+    let pages: BTreeMap<&str, &Page> = live.iter().map(|page| (page.id.as_str(), *page)).collect();
+    for change in &diff.modified {
+      let (Some(page), Some(path)) = (pages.get(change.id.as_str()), &change.path) else {
+        continue;
+      };
+      let file = root.join(path);
+      let exported = fs::read_to_string(&file).with_context(|| format!("Failed to read {}", file.display()))?;
+      let current = renderer.render(&client, page, path).await?;
+      let patch = render_unified_diff(
+        exported_body(&exported, cli.output.format).trim_start(),
+        current.trim_start(),
+        &format!("{path} (exported)"),
+        &format!("{path} (current)"),
+      );
+      println!();
+      for line in patch.lines() {
+        println!("{}", colorize_diff_line(line, colors));
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Converts live pages the way an export of the tree into `root` writes them.
+///
+/// Links between pages resolve against the tree's export scope and images
+/// and attachments get their exported paths, so the only differences left
+/// are changes to the pages themselves. Nothing is written to the export.
+struct PageRenderer<'a> {
+  cli: &'a Cli,
+  root: &'a Path,
+  links: LinkContext,
+  link_titles: Option<LinkTitleFetcher>,
+  users: Option<UserResolver>,
+  includes: Option<IncludeResolver>,
+  front_matter_rules: Option<FrontMatterRules>,
+  template: Option<PageTemplate>,
+  external_images: Option<ExternalImageFetcher>,
+}
+
+impl<'a> PageRenderer<'a> {
+  fn new(tree: &PageTree, root: &'a Path, cli: &'a Cli) -> Result<Self> {
+    let filenames = TreeFilenames::plan(tree, build_filename_options(cli, root));
+    let scope = build_export_scope(
+      std::slice::from_ref(tree),
+      &[],
+      build_write_options(cli).file_extension(),
+      &filenames,
+    );
+    Ok(Self {
+      cli,
+      root,
+      links: build_link_context(cli, scope),
+      link_titles: cli
+        .images_links
+        .fetch_link_titles
+        .then(|| LinkTitleFetcher::new(cli.images_links.link_title_rate_limit))
+        .transpose()?,
+      users: cli.images_links.resolve_users.then(UserResolver::new),
+      includes: cli.images_links.resolve_includes.then(IncludeResolver::new),
+      front_matter_rules: load_front_matter_rules(cli)?,
+      template: load_template(cli)?,
+      external_images: build_external_image_fetcher(cli)?,
+    })
+  }
+
+  /// Convert `page`, exported to `path` relative to the export root.
+  async fn render(&self, client: &dyn ConfluenceApi, page: &Page, path: &str) -> Result<String> {
+    let output_dir = self.root.join(path);
+    let output_dir = output_dir.parent().unwrap_or(self.root);
+    let options = ProcessOptions {
+      template: self.template.as_ref(),
+      external_images: self.external_images.as_ref(),
+      // Assets are fetched into memory rather than staged in the export
+      output_dir: None,
+      ..build_process_options(
+        self.cli,
+        output_dir,
+        &self.links,
+        self.link_titles.as_ref(),
+        self.users.as_ref(),
+        self.includes.as_ref(),
+        self.front_matter_rules.as_ref(),
+      )
+    };
+    Ok(process_page(client, page, &options).await?.content)
+  }
+}
+
+fn collect_pages<'a>(tree: &'a PageTree, pages: &mut Vec<&'a Page>) {
+  pages.push(&tree.page);
+  for child in &tree.children {
+    collect_pages(child, pages);
+  }
+}
+
+/// Compare the live pages with the pages listed in the export's manifest.
+///
+/// A page is modified when its version differs from the exported one, when
+/// either version is unknown, or when it was renamed.
+fn compare(live: &[&Page], exported: &[ManifestPage]) -> ExportDiff {
+  let exported_by_id: BTreeMap<&str, &ManifestPage> = exported.iter().map(|page| (page.id.as_str(), page)).collect();
+  let mut diff = ExportDiff::default();
+
+  for page in live {
+    let Some(entry) = exported_by_id.get(page.id.as_str()) else {
+      diff.added.push(PageChange {
+        id: page.id.clone(),
+        title: page.title.clone(),
+        path: None,
+        detail: None,
+      });
+      continue;
+    };
+
+    let live_version = page.version.as_ref().map(|version| version.number);
+    let mut details = Vec::new();
+    match (entry.version, live_version) {
+      (Some(before), Some(after)) if before == after => {}
+      (Some(before), Some(after)) => details.push(format!("version {before} → {after}")),
+      _ => details.push("version unknown".to_string()),
+    }
+    if entry.title != page.title {
+      details.push(format!("renamed from \"{}\"", entry.title));
+    }
+    if !details.is_empty() {
+      diff.modified.push(PageChange {
+        id: page.id.clone(),
+        title: page.title.clone(),
+        path: Some(entry.path.clone()),
+        detail: Some(details.join(", ")),
+      });
+    }
+  }
+
+  let live_ids: HashSet<&str> = live.iter().map(|page| page.id.as_str()).collect();
+  for entry in exported.iter().filter(|entry| !live_ids.contains(&entry.id.as_str())) {
+    diff.removed.push(PageChange {
+      id: entry.id.clone(),
+      title: entry.title.clone(),
+      path: Some(entry.path.clone()),
+      detail: None,
+    });
+  }
+  diff
+}
+
+/// The converted content of an exported page file, without its front matter.
+fn exported_body(content: &str, format: OutputFormat) -> String {
+  if format == OutputFormat::Json {
+    return serde_json::from_str::<serde_json::Value>(content)
+      .ok()
+      .and_then(|document| document.get("markdown")?.as_str().map(str::to_string))
+      .unwrap_or_default();
+  }
+  FrontMatter::split(content, format).1.to_string()
+}
+
+fn format_report_lines(diff: &ExportDiff, root: &str, colors: &ColorScheme) -> Vec<String> {
+  if diff.is_empty() {
+    return vec![format!(
+      "{} The export in {} matches Confluence",
+      colors.success("✓"),
+      colors.path(root)
+    )];
+  }
+
+  let mut lines = vec![format!(
+    "{} {} added, {} removed, {} modified since the export in {}",
+    colors.warning("⚠"),
+    colors.number(diff.added.len()),
+    colors.number(diff.removed.len()),
+    colors.number(diff.modified.len()),
+    colors.path(root)
+  )];
+  let sections = [
+    ("Added", &diff.added, "+"),
+    ("Removed", &diff.removed, "-"),
+    ("Modified", &diff.modified, "~"),
+  ];
+  for (heading, changes, marker) in sections {
+    if changes.is_empty() {
+      continue;
+    }
+    lines.push(String::new());
+    lines.push(colors.emphasis(heading).to_string());
+    for change in changes {
+      let mut line = format!("  {marker} {} [id {}]", change.title, colors.number(&change.id));
+      if let Some(path) = &change.path {
+        line.push_str(&format!(" {}", colors.path(path)));
+      }
+      if let Some(detail) = &change.detail {
+        line.push_str(&format!(" {}", colors.dimmed(format!("({detail})"))));
+      }
+      lines.push(line);
+    }
+  }
+  lines
+}
+
+#[cfg(test)]
+mod tests {
+  use async_trait::async_trait;
+  use clap::Parser;
+
+  use super::*;
+  use crate::cli::ColorOption;
+  use crate::confluence::{Attachment, ContentState, Space, UserInfo};
+  use crate::test_support::page;
+
+  /// A client for pages whose content is already fetched.
+  struct NoAttachmentsClient;
+
+  #[async_trait]
+  impl ConfluenceApi for NoAttachmentsClient {
+    async fn get_page(&self, page_id: &str) -> crate::Result<Page> {
+      Err(crate::Error::from_status(404, format!("page not found: {page_id}")))
+    }
+
+    async fn get_page_version(&self, page_id: &str, _version: u32) -> crate::Result<Page> {
+      self.get_page(page_id).await
+    }
+
+    async fn get_child_pages(&self, _page_id: &str) -> crate::Result<Vec<Page>> {
+      Ok(Vec::new())
+    }
+
+    async fn get_content_state(&self, _page_id: &str) -> crate::Result<Option<ContentState>> {
+      Ok(None)
+    }
+
+    async fn get_attachments(&self, _page_id: &str) -> crate::Result<Vec<Attachment>> {
+      Ok(Vec::new())
+    }
+
+    async fn download_attachment(&self, url: &str, _output_path: &Path) -> crate::Result<()> {
+      Err(crate::Error::from_status(404, format!("attachment not found: {url}")))
+    }
+
+    async fn fetch_attachment(&self, url: &str) -> crate::Result<Vec<u8>> {
+      Err(crate::Error::from_status(404, format!("attachment not found: {url}")))
+    }
+
+    async fn get_user(&self, account_id: &str) -> crate::Result<UserInfo> {
+      Err(crate::Error::from_status(404, format!("user not found: {account_id}")))
+    }
+
+    async fn get_space(&self, space_key: &str) -> crate::Result<Space> {
+      Err(crate::Error::from_status(404, format!("space not found: {space_key}")))
+    }
+
+    async fn find_page(&self, _space_key: &str, _title: &str) -> crate::Result<Option<Page>> {
+      Ok(None)
+    }
+
+    async fn test_auth(&self) -> crate::Result<UserInfo> {
+      Err(crate::Error::from_status(401, "unauthorized"))
+    }
+  }
+
+  fn live_page(id: &str, title: &str, version: u32) -> Page {
+    Page {
+      version: serde_json::from_value(serde_json::json!({"number": version})).unwrap(),
+      ..page(id, title)
+    }
+  }
+
+  fn exported_page(id: &str, title: &str, version: u32) -> ManifestPage {
+    ManifestPage {
+      id: id.to_string(),
+      title: title.to_string(),
+      path: format!("Guide/{title}.md"),
+      mime_type: "text/markdown".to_string(),
+      version: Some(version),
+      checksum: String::new(),
+    }
+  }
+
+  #[test]
+  fn test_compare_and_report() {
+    let live = [
+      live_page("1", "Guide", 4),
+      live_page("2", "Setup", 7),
+      live_page("3", "Troubleshooting", 1),
+      live_page("5", "Release notes", 2),
+    ];
+    let exported = [
+      exported_page("1", "Guide", 4),
+      exported_page("2", "Setup", 5),
+      exported_page("3", "FAQ", 1),
+      exported_page("4", "Retired", 9),
+    ];
+
+    let diff = compare(&live.iter().collect::<Vec<_>>(), &exported);
+    let colors = ColorScheme::new(ColorOption::Never);
+    insta::assert_snapshot!(format_report_lines(&diff, "docs", &colors).join("\n"), @r#"
+    ⚠ 1 added, 1 removed, 2 modified since the export in docs
+
+    Added
+      + Release notes [id 5]
+
+    Removed
+      - Retired [id 4] Guide/Retired.md
+
+    Modified
+      ~ Setup [id 2] Guide/Setup.md (version 5 → 7)
+      ~ Troubleshooting [id 3] Guide/FAQ.md (renamed from "FAQ")
+    "#);
+  }
+
+  #[test]
+  fn test_compare_unchanged() {
+    let live = [live_page("1", "Guide", 4)];
+    let diff = compare(&live.iter().collect::<Vec<_>>(), &[exported_page("1", "Guide", 4)]);
+    assert!(diff.is_empty());
+
+    let colors = ColorScheme::new(ColorOption::Never);
+    assert_eq!(
+      format_report_lines(&diff, "docs", &colors),
+      ["✓ The export in docs matches Confluence"]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_render_resolves_links_within_the_export() {
+    let page = |id: &str, title: &str, storage: &str| -> Page {
+      serde_json::from_value(serde_json::json!({
+        "id": id,
+        "title": title,
+        "type": "page",
+        "status": "current",
+        "space": {"key": "DOCS", "name": "Docs", "type": "global"},
+        "body": {"storage": {"value": storage, "representation": "storage"}}
+      }))
+      .unwrap()
+    };
+    let tree = PageTree {
+      page: page("1", "Guide", "<p>Start here</p>"),
+      children: vec![PageTree {
+        page: page(
+          "2",
+          "Setup",
+          r#"<p>Back to <ac:link><ri:page ri:content-title="Guide" /></ac:link></p>"#,
+        ),
+        children: Vec::new(),
+        depth: 1,
+        exported: true,
+      }],
+      depth: 0,
+      exported: true,
+    };
+    let cli = Cli::parse_from(["confluence-dl", "--output", "docs"]);
+
+    let renderer = PageRenderer::new(&tree, Path::new("docs"), &cli).unwrap();
+    let current = renderer
+      .render(&NoAttachmentsClient, &tree.children[0].page, "Guide/Setup.md")
+      .await
+      .unwrap();
+    assert_eq!(current.trim(), "Back to [Guide](../Guide.md)");
+  }
+
+  #[test]
+  fn test_exported_body() {
+    assert_eq!(
+      exported_body("---\ntitle: Guide\n---\n\n# Guide\n", OutputFormat::Markdown).trim_start(),
+      "# Guide\n"
+    );
+    assert_eq!(
+      exported_body(r##"{"id": "1", "markdown": "# Guide\n"}"##, OutputFormat::Json),
+      "# Guide\n"
+    );
+  }
+}
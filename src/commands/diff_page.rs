@@ -0,0 +1,240 @@
+//! `diff-page` subcommand for comparing two versions of a page.
+//!
+//! This module powers `confluence-dl diff-page`, which fetches two versions of
+//! a Confluence page, converts both with the same settings used for exports,
+//! and prints a unified diff of the result (or writes an HTML rendering of it)
+//! so changes to a page can be reviewed without opening the page history.
+
+use std::{fs, process};
+
+use anyhow::{Context, Result};
+use similar::{ChangeTag, TextDiff};
+
+use crate::cli::Cli;
+use crate::color::ColorScheme;
+use crate::commands::auth::create_client;
+use crate::commands::ls::resolve_url_info;
+use crate::commands::page::{build_asciidoc_options, build_markdown_options};
+use crate::confluence::{ConfluenceApi, Page};
+use crate::format::OutputFormat;
+use crate::links::LinkContext;
+use crate::{asciidoc, markdown, redaction};
+
+/// Lines of unchanged context shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Execute the `diff-page` subcommand.
+///
+/// # Arguments
+/// * `target` - Page URL or numeric page ID supplied on the CLI.
+/// * `from` - Version to diff from.
+/// * `to` - Version to diff to; `None` compares against the current version.
+/// * `html` - When set, write an HTML diff to this path instead of printing.
+/// * `cli` - Top-level CLI options for auth, output format, and networking.
+/// * `colors` - Shared color palette used to render terminal output.
+pub async fn handle_diff_page_command(
+  target: &str,
+  from: u32,
+  to: Option<u32>,
+  html: Option<&str>,
+  cli: &Cli,
+  colors: &ColorScheme,
+) {
+  if let Err(error) = run_diff_page_command(target, from, to, html, cli, colors).await {
+    eprintln!("{} {}", colors.error("✗"), colors.error("Failed to diff page versions"));
+    eprintln!("  {}: {}", colors.emphasis("Error"), error);
+    process::exit(1);
+  }
+}
+
+async fn run_diff_page_command(
+  target: &str,
+  from: u32,
+  to: Option<u32>,
+  html: Option<&str>,
+  cli: &Cli,
+  colors: &ColorScheme,
+) -> Result<()> {
+  let url_info = resolve_url_info(target.trim(), cli).context("Could not determine page identifier")?;
+  let client = create_client(&url_info.base_url, cli).context("Unable to construct Confluence API client")?;
+
+  let old_page = client.get_page_version(&url_info.page_id, from).await?;
+  let new_page = match to {
+    Some(version) => client.get_page_version(&url_info.page_id, version).await?,
+    None => client.get_page(&url_info.page_id).await?,
+  };
+
+  let old_label = format!("{} (version {from})", old_page.title);
+  let new_label = match to {
+    Some(version) => format!("{} (version {version})", new_page.title),
+    None => format!("{} (current)", new_page.title),
+  };
+  let old_text = convert_page(&old_page, cli)?;
+  let new_text = convert_page(&new_page, cli)?;
+
+  if let Some(path) = html {
+    fs::write(path, render_html_diff(&old_text, &new_text, &old_label, &new_label))
+      .with_context(|| format!("Failed to write HTML diff to {path}"))?;
+    println!("{} {}", colors.success("✓"), colors.path(path));
+    return Ok(());
+  }
+
+  let diff = render_unified_diff(&old_text, &new_text, &old_label, &new_label);
+  if diff.is_empty() {
+    println!("{}", colors.dimmed("No differences"));
+  }
+  for line in diff.lines() {
+    println!("{}", colorize_diff_line(line, colors));
+  }
+  Ok(())
+}
+
+/// Convert a page version with the export settings from the CLI.
+pub(crate) fn convert_page(page: &Page, cli: &Cli) -> Result<String> {
+  if let Some(wiki) = page.legacy_wiki()
+    && cli.output.format.content_format() == OutputFormat::Markdown
+  {
+    return Ok(markdown::wiki::wiki_to_markdown(
+      wiki,
+      &build_markdown_options(cli, &LinkContext::default()).dialect,
+    ));
+  }
+  let storage = page
+    .storage_content()
+    .ok_or_else(|| anyhow::anyhow!("Page '{}' has no storage content", page.title))?;
+  let storage = redaction::redact_storage(storage, &cli.output.redact_macro)
+    .with_context(|| format!("Failed to redact macros of page '{}'", page.title))?;
+
+  match cli.output.format {
+    OutputFormat::Markdown | OutputFormat::Json => Ok(markdown::storage_to_markdown_with_options(
+      &storage,
+      &build_markdown_options(cli, &LinkContext::default()),
+    )?),
+    OutputFormat::AsciiDoc => asciidoc::storage_to_asciidoc_with_options(&storage, &build_asciidoc_options(cli)),
+  }
+}
+
+/// Render a unified diff, or an empty string when the texts are identical.
+pub(crate) fn render_unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+  let diff = TextDiff::from_lines(old, new);
+  diff
+    .unified_diff()
+    .context_radius(CONTEXT_LINES)
+    .header(old_label, new_label)
+    .to_string()
+}
+
+/// Render a standalone HTML page showing every line with insertions and
+/// deletions highlighted.
+fn render_html_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+  let mut body = String::new();
+  for change in TextDiff::from_lines(old, new).iter_all_changes() {
+    let (tag, class, sign) = match change.tag() {
+      ChangeTag::Equal => ("span", "eq", ' '),
+      ChangeTag::Delete => ("del", "del", '-'),
+      ChangeTag::Insert => ("ins", "ins", '+'),
+    };
+    body.push_str(&format!(
+      "<{tag} class=\"{class}\">{sign} {}</{tag}>\n",
+      escape_html(change.value().trim_end_matches('\n'))
+    ));
+  }
+
+  format!(
+    r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{old} → {new}</title>
+<style>
+  pre {{ font-family: monospace; }}
+  pre > * {{ display: block; text-decoration: none; white-space: pre-wrap; }}
+  .del {{ background: #ffebe9; }}
+  .ins {{ background: #dafbe1; }}
+</style>
+</head>
+<body>
+<h1>{old} → {new}</h1>
+<pre>
+{body}</pre>
+</body>
+</html>
+"#,
+    old = escape_html(old_label),
+    new = escape_html(new_label),
+  )
+}
+
+pub(crate) fn colorize_diff_line(line: &str, colors: &ColorScheme) -> String {
+  if line.starts_with("+++") || line.starts_with("---") {
+    colors.emphasis(line)
+  } else if line.starts_with('+') {
+    colors.success(line)
+  } else if line.starts_with('-') {
+    colors.error(line)
+  } else if line.starts_with("@@") {
+    colors.info(line)
+  } else {
+    line.to_string()
+  }
+}
+
+fn escape_html(text: &str) -> String {
+  text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+  use clap::Parser;
+
+  use super::*;
+
+  #[test]
+  fn test_render_unified_diff() {
+    let old = "# Policy\n- Retain logs for 30 days.\n- Review yearly.\n";
+    let new = "# Policy\n- Retain logs for 90 days.\n- Review yearly.\n";
+
+    insta::assert_snapshot!(render_unified_diff(old, new, "Policy (version 12)", "Policy (version 15)"), @r"
+    --- Policy (version 12)
+    +++ Policy (version 15)
+    @@ -1,3 +1,3 @@
+     # Policy
+    -- Retain logs for 30 days.
+    +- Retain logs for 90 days.
+     - Review yearly.
+    ");
+    assert_eq!(render_unified_diff(old, old, "a", "b"), "");
+  }
+
+  #[test]
+  fn test_convert_page_redacts_asciidoc() {
+    let storage = r#"<ac:structured-macro ac:name="vault-secret"><ac:parameter ac:name="token">s3cr3t</ac:parameter></ac:structured-macro>"#;
+    let page: Page = serde_json::from_value(serde_json::json!({
+      "id": "1",
+      "type": "page",
+      "status": "current",
+      "title": "Secrets",
+      "body": { "storage": { "value": storage, "representation": "storage" } }
+    }))
+    .unwrap();
+    let cli = Cli::parse_from([
+      "confluence-dl",
+      "--format",
+      "ascii-doc",
+      "--annotate-macros",
+      "--redact-macro",
+      "vault-secret",
+      "1",
+    ]);
+
+    let converted = convert_page(&page, &cli).unwrap();
+    assert!(!converted.contains("s3cr3t"), "{converted}");
+  }
+
+  #[test]
+  fn test_render_html_diff_escapes_content() {
+    let html = render_html_diff("<b>old</b>\n", "new & shiny\n", "A", "B");
+    assert!(html.contains("<del class=\"del\">- &lt;b&gt;old&lt;/b&gt;</del>"));
+    assert!(html.contains("<ins class=\"ins\">+ new &amp; shiny</ins>"));
+  }
+}
@@ -2,16 +2,56 @@
 //!
 //! This module powers `confluence-dl ls`, which connects to Confluence, builds
 //! the page tree for a target page, and renders the hierarchy in a friendly
-//! ASCII tree without downloading any content.
+//! ASCII tree without downloading any content. Each page is listed with its
+//! ID, version, last-updated date, and number of children, or the whole tree
+//! is printed as JSON with `--format json`.
 
 use std::process;
 
 use anyhow::{Context, Result, anyhow};
+use serde::Serialize;
 
 use crate::cli::Cli;
 use crate::color::ColorScheme;
-use crate::commands::auth::load_credentials;
-use crate::confluence::{self, PageTree};
+use crate::commands::auth::create_client;
+use crate::confluence::{self, Page, PageTree, TreeEstimate};
+use crate::format::ListingFormat;
+
+/// A page of the tree printed with `--format json`.
+#[derive(Debug, Serialize)]
+struct ListingNode {
+  id: String,
+  title: String,
+  #[serde(rename = "type")]
+  page_type: String,
+  status: String,
+  version: Option<u32>,
+  last_updated: Option<String>,
+  depth: usize,
+  /// `None` when the page is at `--max-depth`, so its children were not
+  /// listed.
+  child_count: Option<usize>,
+  /// `None` when the subtree reaches `--max-depth`.
+  descendant_count: Option<usize>,
+  children: Vec<ListingNode>,
+}
+
+impl ListingNode {
+  fn new(tree: &PageTree, max_depth: Option<usize>) -> Self {
+    Self {
+      id: tree.page.id.clone(),
+      title: tree.page.title.clone(),
+      page_type: tree.page.page_type.clone(),
+      status: tree.page.status.clone(),
+      version: tree.page.version.as_ref().map(|version| version.number),
+      last_updated: last_updated(&tree.page).map(str::to_string),
+      depth: tree.depth,
+      child_count: (!at_max_depth(tree, max_depth)).then_some(tree.children.len()),
+      descendant_count: (!reaches_max_depth(tree, max_depth)).then(|| count_nodes(tree) - 1),
+      children: tree.children.iter().map(|child| Self::new(child, max_depth)).collect(),
+    }
+  }
+}
 
 /// Execute the `ls` subcommand to display a page tree.
 ///
@@ -23,17 +63,38 @@ use crate::confluence::{self, PageTree};
 /// # Arguments
 /// * `target` - Page URL or numeric page ID supplied on the CLI.
 /// * `max_depth` - Optional traversal depth limit (0 lists only the root).
+/// * `estimate` - When set, sample the tree with at most this many requests and print an estimate instead of the full
+///   hierarchy.
+/// * `format` - Print the tree with box-drawing characters or as JSON.
 /// * `cli` - Top-level CLI options for auth, behavior, and networking.
 /// * `colors` - Shared color palette used to render terminal output.
-pub async fn handle_ls_command(target: &str, max_depth: Option<usize>, cli: &Cli, colors: &ColorScheme) {
-  if let Err(error) = run_ls_command(target, max_depth, cli, colors).await {
+pub async fn handle_ls_command(
+  target: &str,
+  max_depth: Option<usize>,
+  estimate: Option<usize>,
+  format: ListingFormat,
+  cli: &Cli,
+  colors: &ColorScheme,
+) {
+  if let Err(error) = run_ls_command(target, max_depth, estimate, format, cli, colors).await {
     eprintln!("{} {}", colors.error("✗"), colors.error("Failed to list page tree"));
     eprintln!("  {}: {}", colors.emphasis("Error"), error);
     process::exit(1);
   }
 }
 
-async fn run_ls_command(target: &str, max_depth: Option<usize>, cli: &Cli, colors: &ColorScheme) -> Result<()> {
+async fn run_ls_command(
+  target: &str,
+  max_depth: Option<usize>,
+  estimate: Option<usize>,
+  format: ListingFormat,
+  cli: &Cli,
+  colors: &ColorScheme,
+) -> Result<()> {
+  if format == ListingFormat::Json {
+    return print_json_tree(target, max_depth, cli).await;
+  }
+
   println!("{} {}", colors.progress("→"), colors.info("Inspecting page tree"));
 
   let url_info = resolve_url_info(target.trim(), cli).context("Could not determine page identifier")?;
@@ -47,21 +108,32 @@ async fn run_ls_command(target: &str, max_depth: Option<usize>, cli: &Cli, color
     println!("  {}: {}", colors.emphasis("Max depth"), colors.number(depth));
   }
 
-  let (username, token) = load_credentials(&url_info.base_url, cli)
-    .context("Failed to resolve credentials. Provide --user/--token, env vars, or configure ~/.netrc")?;
-
   println!("\n{} {}", colors.info("→"), colors.info("Connecting to Confluence"));
-  let client = confluence::ConfluenceClient::new(
-    &url_info.base_url,
-    &username,
-    &token,
-    cli.performance.timeout,
-    cli.performance.rate_limit,
-  )
-  .context("Unable to construct Confluence API client")?;
+  let client = create_client(&url_info.base_url, cli).context("Unable to construct Confluence API client")?;
+
+  if let Some(max_requests) = estimate {
+    println!(
+      "{} {}",
+      colors.info("→"),
+      colors.info(format!("Sampling page tree (up to {max_requests} requests)"))
+    );
+    let estimate =
+      confluence::estimate_page_tree(&client, &url_info.page_id, max_requests, cli.performance.page_size).await?;
+    for line in format_estimate_lines(&estimate, colors) {
+      println!("{line}");
+    }
+    return Ok(());
+  }
 
   println!("{} {}", colors.info("→"), colors.info("Fetching page tree"));
-  let tree = confluence::get_page_tree(&client, &url_info.page_id, max_depth).await?;
+  let tree = confluence::get_page_tree(
+    &client,
+    &url_info.page_id,
+    max_depth,
+    &confluence::PageFilter::default(),
+    cli.performance.resolved_parallel(),
+  )
+  .await?;
 
   let total_pages = count_nodes(&tree);
   println!(
@@ -83,14 +155,31 @@ async fn run_ls_command(target: &str, max_depth: Option<usize>, cli: &Cli, color
   }
 
   println!("\n{}", colors.emphasis("Page Tree"));
-  for line in format_tree_lines(&tree, colors) {
+  for line in format_tree_lines(&tree, max_depth, colors) {
     println!("  {line}");
   }
 
   Ok(())
 }
 
-fn resolve_url_info(target: &str, cli: &Cli) -> Result<confluence::UrlInfo> {
+/// Print the page tree as JSON, and nothing else, so the output can be piped.
+async fn print_json_tree(target: &str, max_depth: Option<usize>, cli: &Cli) -> Result<()> {
+  let url_info = resolve_url_info(target.trim(), cli).context("Could not determine page identifier")?;
+  let client = create_client(&url_info.base_url, cli).context("Unable to construct Confluence API client")?;
+  let tree = confluence::get_page_tree(
+    &client,
+    &url_info.page_id,
+    max_depth,
+    &confluence::PageFilter::default(),
+    cli.performance.resolved_parallel(),
+  )
+  .await?;
+  println!("{}", serde_json::to_string_pretty(&ListingNode::new(&tree, max_depth))?);
+  Ok(())
+}
+
+/// Resolve a page URL or numeric ID (with `--url`) into its base URL and page ID.
+pub(crate) fn resolve_url_info(target: &str, cli: &Cli) -> Result<confluence::UrlInfo> {
   if target.contains("://") {
     return confluence::parse_confluence_url(target);
   }
@@ -108,20 +197,28 @@ fn resolve_url_info(target: &str, cli: &Cli) -> Result<confluence::UrlInfo> {
   ))
 }
 
-fn format_tree_lines(tree: &PageTree, colors: &ColorScheme) -> Vec<String> {
+fn format_tree_lines(tree: &PageTree, max_depth: Option<usize>, colors: &ColorScheme) -> Vec<String> {
   let mut lines = Vec::new();
-  format_tree_lines_recursive(tree, String::new(), true, true, colors, &mut lines);
+  let format = TreeFormat { max_depth, colors };
+  format_tree_lines_recursive(tree, String::new(), true, true, &format, &mut lines);
   lines
 }
 
+/// Settings shared by every line of a printed tree.
+struct TreeFormat<'a> {
+  max_depth: Option<usize>,
+  colors: &'a ColorScheme,
+}
+
 fn format_tree_lines_recursive(
   node: &PageTree,
   prefix: String,
   is_last: bool,
   is_root: bool,
-  colors: &ColorScheme,
+  format: &TreeFormat,
   lines: &mut Vec<String>,
 ) {
+  let colors = format.colors;
   let connector = if is_root {
     String::new()
   } else if is_last {
@@ -134,14 +231,14 @@ fn format_tree_lines_recursive(
     format!(
       "{} {}",
       colors.emphasis(&node.page.title),
-      format_metadata(node, colors)
+      format_metadata(node, format.max_depth, colors)
     )
   } else {
     format!(
       "{}{} {}",
       connector,
       colors.emphasis(&node.page.title),
-      format_metadata(node, colors)
+      format_metadata(node, format.max_depth, colors)
     )
   };
   lines.push(line);
@@ -156,18 +253,99 @@ fn format_tree_lines_recursive(
 
   for (idx, child) in node.children.iter().enumerate() {
     let child_is_last = idx + 1 == node.children.len();
-    format_tree_lines_recursive(child, next_prefix.clone(), child_is_last, false, colors, lines);
+    format_tree_lines_recursive(child, next_prefix.clone(), child_is_last, false, format, lines);
   }
 }
 
-fn format_metadata(node: &PageTree, colors: &ColorScheme) -> String {
-  format!(
-    "[id {} | depth {} | status {} | type {}]",
-    colors.number(&node.page.id),
-    colors.number(node.depth),
-    colors.dimmed(&node.page.status),
-    colors.dimmed(&node.page.page_type)
-  )
+/// Bracketed details of a page: ID, depth, status, type, version,
+/// last-updated date, and number of children and descendants.
+///
+/// Counts are left out for pages at `--max-depth`, whose children were not
+/// listed, and descendants for pages whose subtree reaches it.
+fn format_metadata(node: &PageTree, max_depth: Option<usize>, colors: &ColorScheme) -> String {
+  let page = &node.page;
+  let mut parts = vec![
+    format!("id {}", colors.number(&page.id)),
+    format!("depth {}", colors.number(node.depth)),
+    format!("status {}", colors.dimmed(&page.status)),
+    format!("type {}", colors.dimmed(&page.page_type)),
+  ];
+  if let Some(version) = &page.version {
+    parts.push(format!("v{}", colors.number(version.number)));
+  }
+  if let Some(when) = last_updated(page) {
+    let date = when.split('T').next().unwrap_or(when);
+    parts.push(format!("updated {}", colors.dimmed(date)));
+  }
+  let children = node.children.len();
+  if children > 0 && !at_max_depth(node, max_depth) {
+    let descendants = count_nodes(node) - 1;
+    let mut count = format!(
+      "{} {}",
+      colors.number(children),
+      if children == 1 { "child" } else { "children" }
+    );
+    if descendants > children && !reaches_max_depth(node, max_depth) {
+      count.push_str(&format!(", {} descendants", colors.number(descendants)));
+    }
+    parts.push(count);
+  }
+  format!("[{}]", parts.join(" | "))
+}
+
+/// Whether the page is at `--max-depth`, so its children were not listed.
+fn at_max_depth(node: &PageTree, max_depth: Option<usize>) -> bool {
+  max_depth.is_some_and(|max_depth| node.depth >= max_depth)
+}
+
+/// Whether any page of the subtree is at `--max-depth`.
+fn reaches_max_depth(node: &PageTree, max_depth: Option<usize>) -> bool {
+  at_max_depth(node, max_depth) || node.children.iter().any(|child| reaches_max_depth(child, max_depth))
+}
+
+/// Timestamp of the page's latest version.
+fn last_updated(page: &Page) -> Option<&str> {
+  page
+    .last_modified_date()
+    .or_else(|| page.version.as_ref().and_then(|version| version.when.as_deref()))
+}
+
+fn format_estimate_lines(estimate: &TreeEstimate, colors: &ColorScheme) -> Vec<String> {
+  let total = estimate.total_pages().round() as usize;
+  let exact = estimate.levels.iter().all(|level| level.exact) && !estimate.truncated;
+  let mut lines = vec![format!(
+    "  {} {}",
+    colors.success("✓"),
+    colors.info(format!(
+      "{} {} {} under {} ({} requests)",
+      if exact { "Found" } else { "Estimated" },
+      colors.number(if exact { total.to_string() } else { format!("~{total}") }),
+      if total == 1 { "page" } else { "pages" },
+      colors.emphasis(&estimate.root.title),
+      colors.number(estimate.requests)
+    ))
+  )];
+
+  lines.push(String::new());
+  lines.push(colors.emphasis("Depth Distribution").to_string());
+  for (depth, level) in estimate.levels.iter().enumerate() {
+    let pages = level.pages.round() as usize;
+    let count = if level.exact {
+      pages.to_string()
+    } else {
+      format!("~{pages}")
+    };
+    lines.push(format!("  depth {}: {}", colors.number(depth), colors.number(count)));
+  }
+  if estimate.truncated {
+    lines.push(format!(
+      "  depth {}+: {}",
+      colors.number(estimate.levels.len()),
+      colors.dimmed("not sampled (raise --estimate to look deeper)")
+    ));
+  }
+
+  lines
 }
 
 fn count_nodes(tree: &PageTree) -> usize {
@@ -179,41 +357,46 @@ mod tests {
   use super::*;
   use crate::cli::ColorOption;
   use crate::color::ColorScheme;
-  use crate::confluence::Page;
+  use crate::confluence::{LevelEstimate, PageVersion};
+  use crate::test_support::{page, tree};
 
-  fn make_page(id: &str, title: &str) -> Page {
+  fn make_versioned_page(id: &str, title: &str, number: u32, when: &str) -> Page {
     Page {
-      id: id.to_string(),
-      title: title.to_string(),
-      page_type: "page".to_string(),
-      status: "current".to_string(),
-      body: None,
-      space: None,
-      links: None,
+      version: Some(PageVersion {
+        number,
+        when: Some(when.to_string()),
+        message: None,
+        by: None,
+      }),
+      ..page(id, title)
     }
   }
 
   fn make_tree() -> PageTree {
-    PageTree {
-      page: make_page("1", "Root"),
-      depth: 0,
-      children: vec![
-        PageTree {
-          page: make_page("2", "Child A"),
-          depth: 1,
-          children: vec![PageTree {
-            page: make_page("3", "Grandchild"),
-            depth: 2,
-            children: vec![],
-          }],
-        },
-        PageTree {
-          page: make_page("4", "Child B"),
-          depth: 1,
-          children: vec![],
-        },
+    tree(
+      page("1", "Root"),
+      vec![
+        tree(page("2", "Child A"), vec![tree(page("3", "Grandchild"), vec![])]),
+        tree(page("4", "Child B"), vec![]),
       ],
-    }
+    )
+  }
+
+  fn make_detailed_tree() -> PageTree {
+    let whiteboard = Page {
+      page_type: "whiteboard".to_string(),
+      ..page("3", "Grandchild")
+    };
+    tree(
+      make_versioned_page("1", "Root", 12, "2024-05-01T10:00:00.000Z"),
+      vec![
+        tree(
+          make_versioned_page("2", "Child A", 3, "2023-11-20T08:30:00.000Z"),
+          vec![tree(whiteboard, vec![])],
+        ),
+        tree(page("4", "Child B"), vec![]),
+      ],
+    )
   }
 
   #[test]
@@ -221,12 +404,12 @@ mod tests {
     let colors = ColorScheme::new(ColorOption::Never);
     let tree = make_tree();
 
-    let lines = format_tree_lines(&tree, &colors);
+    let lines = format_tree_lines(&tree, None, &colors);
     assert_eq!(lines.len(), 4);
     assert!(lines[0].starts_with("Root [id 1"));
     assert_eq!(
       lines[1].trim_start(),
-      "├── Child A [id 2 | depth 1 | status current | type page]"
+      "├── Child A [id 2 | depth 1 | status current | type page | 1 child]"
     );
     assert_eq!(
       lines[2].trim_start(),
@@ -238,6 +421,91 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_format_tree_lines_details() {
+    let colors = ColorScheme::new(ColorOption::Never);
+    let tree = make_detailed_tree();
+
+    let lines = format_tree_lines(&tree, None, &colors);
+    assert_eq!(
+      lines[0],
+      "Root [id 1 | depth 0 | status current | type page | v12 | updated 2024-05-01 | 2 children, 3 descendants]"
+    );
+    assert_eq!(
+      lines[1].trim_start(),
+      "├── Child A [id 2 | depth 1 | status current | type page | v3 | updated 2023-11-20 | 1 child]"
+    );
+    assert_eq!(
+      lines[2].trim_start(),
+      "│   └── Grandchild [id 3 | depth 2 | status current | type whiteboard]"
+    );
+
+    // Pages at --max-depth have unlisted children, so no counts are shown
+    // for them and no descendant totals above them
+    let mut limited = make_detailed_tree();
+    limited.children[0].children.clear();
+    let lines = format_tree_lines(&limited, Some(1), &colors);
+    assert_eq!(
+      lines[0],
+      "Root [id 1 | depth 0 | status current | type page | v12 | updated 2024-05-01 | 2 children]"
+    );
+    assert_eq!(
+      lines[1].trim_start(),
+      "├── Child A [id 2 | depth 1 | status current | type page | v3 | updated 2023-11-20]"
+    );
+  }
+
+  #[test]
+  fn test_listing_node_json() {
+    let node = ListingNode::new(&make_detailed_tree(), None);
+
+    assert_eq!(node.child_count, Some(2));
+    assert_eq!(node.descendant_count, Some(3));
+    let limited = ListingNode::new(&make_detailed_tree(), Some(1));
+    assert_eq!(limited.child_count, Some(2));
+    assert_eq!(limited.descendant_count, None);
+    assert_eq!(limited.children[1].child_count, None);
+    let json = serde_json::to_value(&node).unwrap();
+    assert_eq!(json["version"], 12);
+    assert_eq!(json["last_updated"], "2024-05-01T10:00:00.000Z");
+    assert_eq!(json["children"][0]["children"][0]["type"], "whiteboard");
+    assert_eq!(json["children"][1]["version"], serde_json::Value::Null);
+  }
+
+  #[test]
+  fn test_format_estimate_lines() {
+    let colors = ColorScheme::new(ColorOption::Never);
+    let estimate = TreeEstimate {
+      root: page("1", "Root"),
+      levels: vec![
+        LevelEstimate {
+          pages: 1.0,
+          exact: true,
+        },
+        LevelEstimate {
+          pages: 12.0,
+          exact: true,
+        },
+        LevelEstimate {
+          pages: 143.6,
+          exact: false,
+        },
+      ],
+      requests: 50,
+      truncated: true,
+    };
+
+    insta::assert_snapshot!(format_estimate_lines(&estimate, &colors).join("\n"), @r"
+      ✓ Estimated ~157 pages under Root (50 requests)
+
+    Depth Distribution
+      depth 0: 1
+      depth 1: 12
+      depth 2: ~144
+      depth 3+: not sampled (raise --estimate to look deeper)
+    ");
+  }
+
   #[test]
   fn test_count_nodes() {
     let tree = make_tree();
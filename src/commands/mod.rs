@@ -5,6 +5,9 @@
 //! handlers to share utilities and types.
 
 pub mod auth;
+pub mod diff;
+pub mod diff_page;
 pub mod ls;
 pub mod page;
+pub mod stats;
 pub mod version;
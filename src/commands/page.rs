@@ -4,22 +4,62 @@
 //! converts them to Markdown, downloads assets, and persists everything to
 //! disk according to the current CLI settings.
 
-use std::path::Path;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{fs, process};
 
 use anyhow::Context;
+use chrono::Utc;
 use futures::future::join_all;
+use futures::{StreamExt, stream};
 use tokio::sync::Semaphore;
 
 use crate::asciidoc::AsciiDocOptions;
+use crate::changelog::ChangeLog;
 use crate::cli::Cli;
 use crate::color::ColorScheme;
-use crate::commands::auth::load_credentials;
+use crate::commands::auth::create_client;
 use crate::confluence::{self, ConfluenceApi};
-use crate::format::OutputFormat;
-use crate::markdown::MarkdownOptions;
-use crate::processed_page::{ProcessOptions, process_page, write_processed_page};
+use crate::conversion_cache::ConversionCache;
+use crate::error_report::{FailureReport, PARTIAL_FAILURE_EXIT_CODE, PartialFailure};
+use crate::events::{self, Event, LogFormat};
+use crate::external_images::ExternalImageFetcher;
+use crate::format::{ExportProfile, MarkdownFlavor, OutputFormat};
+use crate::front_matter_rules::FrontMatterRules;
+use crate::hooks::{self, ExportHooks, NoHooks, PageAction, PageConverted, PageStart};
+use crate::image_formats::ImageConverter;
+use crate::include_graph::IncludeGraph;
+use crate::includes::IncludeResolver;
+use crate::link_titles::LinkTitleFetcher;
+use crate::links::{ExportScope, LinkContext};
+use crate::manifest::{self, Manifest, PruneMode};
+use crate::markdown::jira_keys::JiraLinker;
+use crate::markdown::{AdmonitionStyle, MarkdownDialect, MarkdownOptions};
+use crate::notify::{self, RunReport};
+use crate::processed_page::{
+  AssetRegistry, FilenameOptions, ProcessOptions, ProcessedPage, TreeFilenames, WriteOptions, process_page,
+  render_page_file, write_processed_page,
+};
+use crate::progress::{ExportProgress, ExportSummary, StubbedContent, format_summary_lines};
+use crate::shared_assets::SharedAssets;
+use crate::space_readme::{self, SpaceReadme};
+use crate::table_export::TableSplit;
+use crate::tasks::{self, TaskReport};
+use crate::templates::PageTemplate;
+use crate::users::UserResolver;
+use crate::{accessibility, docusaurus, integrity, link_crawl, mkdocs, paths, size_limits};
+
+/// Print human-readable progress, which `--log-format json` replaces with events.
+macro_rules! say {
+  ($cli:expr, $($arg:tt)*) => {
+    if $cli.behavior.log_format == LogFormat::Text {
+      println!($($arg)*);
+    }
+  };
+}
 
 /// Execute the primary page download workflow.
 ///
@@ -32,23 +72,60 @@ use crate::processed_page::{ProcessOptions, process_page, write_processed_page};
 /// * `cli` - Parsed CLI options controlling behavior, output, and auth.
 /// * `colors` - Shared color scheme for consistent terminal output.
 pub async fn handle_page_download(page_input: &str, cli: &Cli, colors: &ColorScheme) {
-  println!("{} {}", colors.progress("→"), colors.info("Downloading page"));
-  println!("  {}: {}", colors.emphasis("URL"), colors.link(page_input));
-  println!("  {}: {}", colors.emphasis("Output"), colors.path(&cli.output.output));
+  if cli.output.stdout {
+    if let Err(e) = stream_page(page_input, cli).await {
+      eprintln!("{} {}", colors.error("✗"), colors.error("Failed to export page"));
+      eprintln!("  {}: {}", colors.emphasis("Error"), e);
+      process::exit(1);
+    }
+    return;
+  }
+
+  if cli.behavior.log_format == LogFormat::Json {
+    events::emit(&Event::ExportStarted {
+      page: page_input,
+      output: &cli.output.output,
+      children: cli.page.children,
+    });
+  }
+  say!(cli, "{} {}", colors.progress("→"), colors.info("Downloading page"));
+  say!(cli, "  {}: {}", colors.emphasis("URL"), colors.link(page_input));
+  say!(
+    cli,
+    "  {}: {}",
+    colors.emphasis("Output"),
+    colors.path(&cli.output.output)
+  );
 
   if cli.page.children {
-    println!("  {} {}", colors.success("✓"), colors.info("Including child pages"));
+    say!(
+      cli,
+      "  {} {}",
+      colors.success("✓"),
+      colors.info("Including child pages")
+    );
     if let Some(depth) = cli.page.max_depth {
-      println!("    {} {}", colors.emphasis("Maximum depth:"), colors.number(depth));
+      say!(
+        cli,
+        "    {} {}",
+        colors.emphasis("Maximum depth:"),
+        colors.number(depth)
+      );
     }
   }
 
   if cli.page.attachments {
-    println!("  {} {}", colors.success("✓"), colors.info("Including attachments"));
+    say!(
+      cli,
+      "  {} {}",
+      colors.success("✓"),
+      colors.info("Including attachments")
+    );
   }
 
   if cli.behavior.dry_run {
-    println!(
+    say!(
+      cli,
       "\n{} {}",
       colors.warning("⚠"),
       colors.warning("DRY RUN: No files will be downloaded")
@@ -57,13 +134,77 @@ pub async fn handle_page_download(page_input: &str, cli: &Cli, colors: &ColorSch
   }
 
   // Parse the input to extract page ID and base URL
-  if let Err(e) = download_page(page_input, cli, colors).await {
-    eprintln!("{} {}", colors.error("✗"), colors.error("Failed to download page"));
-    eprintln!("  {}: {}", colors.emphasis("Error"), e);
+  let started = Instant::now();
+  let mut summary = None;
+  let result = download_page(page_input, cli, colors, &NoHooks, &mut summary).await;
+  if let Some(url) = cli.behavior.notify_webhook.as_deref() {
+    let report = RunReport::new(
+      page_input,
+      &cli.output.output,
+      summary.as_ref(),
+      started.elapsed(),
+      result.as_ref().err(),
+    );
+    if let Err(e) = notify::send(url, &report, cli.behavior.notify_format).await {
+      eprintln!(
+        "{} {}",
+        colors.warning("⚠"),
+        colors.warning("Failed to send webhook notification")
+      );
+      eprintln!("  {}: {e:#}", colors.emphasis("Error"));
+    }
+  }
+
+  if let Err(e) = result {
+    if let Some(partial) = e.downcast_ref::<PartialFailure>() {
+      if cli.behavior.log_format == LogFormat::Text {
+        eprintln!("{} {}", colors.warning("⚠"), colors.warning(partial));
+      }
+      process::exit(PARTIAL_FAILURE_EXIT_CODE);
+    }
+    if cli.behavior.log_format == LogFormat::Json {
+      events::emit(&Event::Error {
+        page_id: None,
+        message: format!("{e:#}"),
+      });
+    } else {
+      eprintln!("{} {}", colors.error("✗"), colors.error("Failed to download page"));
+      eprintln!("  {}: {}", colors.emphasis("Error"), e);
+    }
     process::exit(1);
   }
 
-  println!("\n{} {}", colors.success("✓"), colors.success("Download complete"));
+  say!(cli, "\n{} {}", colors.success("✓"), colors.success("Download complete"));
+}
+
+/// Export a page, calling `hooks` at each step of every page export.
+///
+/// This is the entry point for applications embedding the exporter. It runs
+/// the same workflow as [`handle_page_download`] but returns errors instead
+/// of exiting, and leaves out `--stdout`, `--dry-run`, and webhook handling.
+///
+/// # Arguments
+/// * `page_input` - Page URL or numeric ID.
+/// * `cli` - Options controlling the export, e.g. from `Cli::parse_from`.
+/// * `colors` - Color palette for progress output.
+/// * `hooks` - Callbacks invoked before and after each page is exported.
+///
+/// # Returns
+/// The totals of the export.
+///
+/// # Errors
+/// Returns an error when any network call, filesystem write, or conversion
+/// step fails. With `--keep-going`, pages that fail are recorded instead and
+/// the export ends with a [`PartialFailure`] error.
+pub async fn export_with_hooks(
+  page_input: &str,
+  cli: &Cli,
+  colors: &ColorScheme,
+  hooks: &dyn ExportHooks,
+) -> anyhow::Result<ExportSummary> {
+  let mut summary = None;
+  download_page(page_input, cli, colors, hooks, &mut summary).await?;
+  summary.ok_or_else(|| anyhow::anyhow!("Export finished without writing any pages"))
 }
 
 /// Download a single Confluence page (optionally with attachments/children).
@@ -77,61 +218,153 @@ pub async fn handle_page_download(page_input: &str, cli: &Cli, colors: &ColorSch
 /// * `page_input` - Page URL or numeric ID.
 /// * `cli` - Parsed CLI options.
 /// * `colors` - Color palette for progress output.
+/// * `hooks` - Callbacks invoked before and after each page is exported.
+/// * `summary` - Receives the totals of the run once pages are written, even when a later step fails.
 ///
 /// # Errors
 /// Returns an error when any network call, filesystem write, or conversion
 /// step fails.
-async fn download_page(page_input: &str, cli: &Cli, colors: &ColorScheme) -> anyhow::Result<()> {
-  // Parse URL to extract page ID and base URL
-  let url_info = if page_input.contains("://") {
-    // It's a URL
-    confluence::parse_confluence_url(page_input)?
-  } else {
-    // It's a page ID - need base URL from --url
-    if let Some(ref base_url) = cli.auth.url {
-      confluence::UrlInfo {
-        base_url: base_url.clone(),
-        page_id: page_input.to_string(),
-        space_key: None,
-      }
-    } else {
-      anyhow::bail!("--url is required when using a numeric page ID");
-    }
-  };
-
-  println!("\n{} {}", colors.info("→"), colors.info("Extracting page information"));
-  println!("  {}: {}", colors.emphasis("Base URL"), colors.link(&url_info.base_url));
-  println!("  {}: {}", colors.emphasis("Page ID"), colors.number(&url_info.page_id));
+async fn download_page(
+  page_input: &str,
+  cli: &Cli,
+  colors: &ColorScheme,
+  hooks: &dyn ExportHooks,
+  summary: &mut Option<ExportSummary>,
+) -> anyhow::Result<()> {
+  let url_info = parse_page_input(page_input, cli)?;
+
+  say!(
+    cli,
+    "\n{} {}",
+    colors.info("→"),
+    colors.info("Extracting page information")
+  );
+  say!(
+    cli,
+    "  {}: {}",
+    colors.emphasis("Base URL"),
+    colors.link(&url_info.base_url)
+  );
+  say!(
+    cli,
+    "  {}: {}",
+    colors.emphasis("Page ID"),
+    colors.number(&url_info.page_id)
+  );
   if let Some(ref space) = url_info.space_key {
-    println!("  {}: {}", colors.emphasis("Space"), space);
+    say!(cli, "  {}: {}", colors.emphasis("Space"), space);
   }
 
-  // Load credentials
-  let (username, token) = load_credentials(&url_info.base_url, cli)?;
-
   // Create API client
-  println!("\n{} {}", colors.info("→"), colors.info("Connecting to Confluence"));
-  let client = confluence::ConfluenceClient::new(
-    &url_info.base_url,
-    &username,
-    &token,
-    cli.performance.timeout,
-    cli.performance.rate_limit,
-  )?;
+  say!(
+    cli,
+    "\n{} {}",
+    colors.info("→"),
+    colors.info("Connecting to Confluence")
+  );
+  let client = create_client(&url_info.base_url, cli)?;
+  if let Some(ref path) = cli.performance.replay {
+    say!(cli, "  {}: {}", colors.emphasis("Replaying"), path);
+  } else if let Some(ref path) = cli.performance.record {
+    say!(cli, "  {}: {}", colors.emphasis("Recording"), path);
+  }
 
-  // Check if we should download children
-  if cli.page.children {
-    println!("{} {}", colors.info("→"), colors.info("Fetching page tree"));
+  let link_titles = if cli.images_links.fetch_link_titles {
+    Some(LinkTitleFetcher::new(cli.images_links.link_title_rate_limit)?)
+  } else {
+    None
+  };
+  let users = cli.images_links.resolve_users.then(UserResolver::new);
+  let includes = cli.images_links.resolve_includes.then(IncludeResolver::new);
+  let front_matter_rules = load_front_matter_rules(cli)?;
+  let template = load_template(cli)?;
+  let external_images = build_external_image_fetcher(cli)?;
+  let include_graph = cli.output.include_graph.as_ref().map(|_| IncludeGraph::default());
+  let changelog = cli.output.changelog.then(ChangeLog::default);
+  let tasks = cli.output.tasks_report.then(TaskReport::default);
+  let failure_report = cli.behavior.keep_going.then(FailureReport::default);
+  let manifest = cli.output.manifest.then(|| {
+    Manifest::new(
+      Path::new(&cli.output.output),
+      &url_info.page_id,
+      build_write_options(cli).mime_type(),
+    )
+  });
+  if cli.output.verify {
+    verify_downloads(cli, colors).await;
+  }
 
-    let max_depth = cli.page.max_depth;
-    if let Some(depth) = max_depth {
-      println!("  {}: {}", colors.emphasis("Max depth"), colors.number(depth));
+  // Check if we should download children or linked pages
+  if cli.page.children || cli.page.follow_links {
+    say!(cli, "{} {}", colors.info("→"), colors.info("Fetching page tree"));
+
+    let max_depth = if cli.page.children { cli.page.max_depth } else { Some(0) };
+    if let Some(depth) = cli.page.max_depth {
+      say!(cli, "  {}: {}", colors.emphasis("Max depth"), colors.number(depth));
     }
 
-    let tree = confluence::get_page_tree(&client, &url_info.page_id, max_depth).await?;
+    let filter = build_page_filter(cli);
+    if !filter.include_labels.is_empty() {
+      say!(
+        cli,
+        "  {}: {}",
+        colors.emphasis("Include labels"),
+        filter.include_labels.join(", ")
+      );
+    }
+    if !filter.exclude_labels.is_empty() {
+      say!(
+        cli,
+        "  {}: {}",
+        colors.emphasis("Exclude labels"),
+        filter.exclude_labels.join(", ")
+      );
+    }
+    if !filter.authors.is_empty() {
+      say!(cli, "  {}: {}", colors.emphasis("Authors"), filter.authors.join(", "));
+    }
+    for (label, bound) in [
+      ("Modified since", filter.modified_since),
+      ("Modified until", filter.modified_until),
+    ] {
+      if let Some(bound) = bound {
+        say!(cli, "  {}: {}", colors.emphasis(label), bound.to_rfc3339());
+      }
+    }
 
-    let total_pages = count_pages_in_tree(&tree);
-    println!(
+    let (mut tree, failed_children) = confluence::get_page_tree_with_failures(
+      &client,
+      &url_info.page_id,
+      max_depth,
+      &filter,
+      cli.performance.resolved_parallel(),
+    )
+    .await?;
+    tree.sort(cli.page.order);
+
+    // Linked pages follow the root page, which stays first
+    let mut forest = vec![tree];
+    if cli.page.follow_links {
+      say!(
+        cli,
+        "  {}: {}",
+        colors.emphasis("Following links"),
+        colors.number(cli.page.link_depth)
+      );
+      let linked = link_crawl::crawl_linked_pages(
+        &client,
+        &forest,
+        cli.page.link_depth,
+        cli.performance.resolved_parallel(),
+      )
+      .await;
+      forest.extend(linked);
+    }
+    let tree = &forest[0];
+
+    let total_pages = forest.iter().map(count_pages_in_tree).sum::<usize>();
+    say!(
+      cli,
       "  {} Found {} {}",
       colors.success("✓"),
       colors.number(total_pages),
@@ -139,61 +372,259 @@ async fn download_page(page_input: &str, cli: &Cli, colors: &ColorScheme) -> any
     );
 
     // Download the entire tree
-    println!("\n{} {}", colors.info("→"), colors.info("Downloading pages"));
+    say!(cli, "\n{} {}", colors.info("→"), colors.info("Downloading pages"));
     if cli.behavior.verbose > 0 {
       let parallel_label = cli.performance.parallel_label();
-      println!(
+      say!(
+        cli,
         "  {}: {}",
         colors.dimmed("Parallel limit"),
         colors.number(parallel_label)
       );
     }
-    let output_dir = Path::new(&cli.output.output);
+    let pages_root = pages_root(cli);
+    let output_dir = pages_root.as_path();
+    let filenames = TreeFilenames::plan_forest(&forest, build_filename_options(cli, &pages_dir(cli, &tree.page)));
+    warn_duplicate_titles(&filenames, colors);
+    warn_shortened_filenames(&filenames, colors);
+    let tree_dirs = forest_dirs(cli, &forest);
+    let links = build_link_context(
+      cli,
+      build_export_scope(
+        &forest,
+        &tree_dirs,
+        build_write_options(cli).file_extension(),
+        &filenames,
+      ),
+    );
+    let shared_assets = cli.page.dedupe_assets.then(|| SharedAssets::new(output_dir));
+    let assets = AssetRegistry::default();
     let parallel_limit = cli.performance.resolved_parallel();
-    let semaphore = Arc::new(Semaphore::new(parallel_limit));
-    download_page_tree(&client, &tree, output_dir, cli, colors, semaphore).await?;
+    let show_bar = !cli.behavior.quiet && cli.behavior.log_format == LogFormat::Text;
+    let progress =
+      ExportProgress::new(total_pages, show_bar, colors).with_accessibility_audit(cli.output.audit_accessibility);
+    let client = client.with_download_progress(progress.download_progress());
+    let download = TreeDownload {
+      client: &client,
+      cli,
+      colors,
+      links: &links,
+      link_titles: link_titles.as_ref(),
+      users: users.as_ref(),
+      includes: includes.as_ref(),
+      front_matter_rules: front_matter_rules.as_ref(),
+      template: template.as_ref(),
+      external_images: external_images.as_ref(),
+      include_graph: include_graph.as_ref(),
+      changelog: changelog.as_ref(),
+      tasks: tasks.as_ref(),
+      failure_report: failure_report.as_ref(),
+      manifest: manifest.as_ref(),
+      shared_assets: shared_assets.as_ref(),
+      assets: &assets,
+      hooks,
+      filenames: &filenames,
+      semaphore: Arc::new(Semaphore::new(parallel_limit)),
+      progress: &progress,
+    };
+    let tree_dirs: Vec<PathBuf> = tree_dirs.iter().map(|dir| output_dir.join(dir)).collect();
+    let tree_futures = forest
+      .iter()
+      .zip(&tree_dirs)
+      .enumerate()
+      .map(|(index, (tree, dir))| download_page_tree(&download, tree, dir, index + 1));
+    let result = join_all(tree_futures)
+      .await
+      .into_iter()
+      .collect::<anyhow::Result<Vec<()>>>();
+    let totals = summary.insert(progress.finish());
+    let pages_written = totals.pages;
+    if cli.behavior.log_format == LogFormat::Json {
+      events::emit(&Event::ExportDone {
+        pages: totals.pages,
+        failures: totals.failures,
+        bytes: totals.bytes,
+      });
+    } else if !cli.behavior.quiet {
+      for line in format_summary_lines(totals, colors) {
+        println!("{line}");
+      }
+    }
+    result?;
+    // Files written next to the pages, checksummed with the export
+    let mut artifacts = Vec::new();
+    if let Some(report) = &failure_report {
+      let path = report.write(Path::new(&cli.output.output))?;
+      say!(cli, "  {} {}", colors.success("✓"), colors.path(path.display()));
+      artifacts.push(path);
+    }
+    artifacts.extend(write_include_graph(cli, include_graph.as_ref(), colors)?);
+    artifacts.extend(write_changelog(cli, changelog.as_ref(), colors)?);
+    artifacts.extend(write_tasks_report(&client, cli, tasks.as_ref(), users.as_ref(), colors).await?);
+    if let (Some(mode), Some(manifest)) = (cli.output.prune, &manifest) {
+      let incomplete = if totals.failures > 0 {
+        Some("some pages failed to export")
+      } else if !failed_children.is_empty() {
+        Some("some child pages could not be fetched")
+      } else if !build_page_filter(cli).is_empty() || cli.page.max_depth.is_some() {
+        Some("filters leave pages of the tree out of this export")
+      } else {
+        None
+      };
+      prune_stale_files(&client, cli, manifest, mode, incomplete, colors).await?;
+    }
+    write_manifest(cli, manifest.as_ref(), colors)?;
+    if let Some(index_path) = shared_assets
+      .as_ref()
+      .map(SharedAssets::write_index)
+      .transpose()?
+      .flatten()
+    {
+      say!(cli, "  {} {}", colors.success("✓"), colors.path(index_path.display()));
+      artifacts.push(index_path);
+    }
 
-    return Ok(());
+    if cli.output.profile == Some(ExportProfile::Docusaurus) {
+      let sidebar_path = output_dir.join(docusaurus::SIDEBAR_FILE);
+      fs::write(&sidebar_path, docusaurus::render_sidebar(&forest, &filenames))
+        .with_context(|| format!("Failed to write sidebar to {}", sidebar_path.display()))?;
+      say!(cli, "  {} {}", colors.success("✓"), colors.path(sidebar_path.display()));
+      artifacts.push(sidebar_path);
+    }
+    artifacts.extend(write_mkdocs_config(cli, &forest, &filenames, colors)?);
+    if cli.page.space_readme {
+      let readme = SpaceExport {
+        base_url: &url_info.base_url,
+        tree,
+        filenames: &filenames,
+        page_count: pages_written,
+      };
+      artifacts.extend(write_space_readme(&client, &readme, cli, colors).await?);
+    }
+    sign_export(cli, manifest.as_ref(), &artifacts, colors).await?;
+
+    return match failure_report.filter(|report| !report.is_empty()) {
+      Some(report) => Err(PartialFailure { failures: report.len() }.into()),
+      None => Ok(()),
+    };
   }
 
   // Fetch single page (non-children mode)
-  println!("{} {}", colors.info("→"), colors.info("Fetching page content"));
+  say!(cli, "{} {}", colors.info("→"), colors.info("Fetching page content"));
   let page = client.get_page(&url_info.page_id).await?;
+  if cli.behavior.log_format == LogFormat::Json {
+    events::emit(&Event::PageStarted {
+      page_id: &page.id,
+      title: &page.title,
+    });
+  }
+
+  say!(cli, "  {}: {}", colors.emphasis("Title"), colors.emphasis(&page.title));
+  say!(cli, "  {}: {}", colors.emphasis("Type"), page.page_type);
+  say!(cli, "  {}: {}", colors.emphasis("Status"), page.status);
 
-  println!("  {}: {}", colors.emphasis("Title"), colors.emphasis(&page.title));
-  println!("  {}: {}", colors.emphasis("Type"), page.page_type);
-  println!("  {}: {}", colors.emphasis("Status"), page.status);
+  let pages_dir = pages_dir(cli, &page);
+  let output_dir = pages_dir.as_path();
+  let start = PageStart {
+    page: &page,
+    depth: 0,
+    output_dir,
+  };
+  if hooks.on_page_start(&start) == PageAction::Skip {
+    say!(cli, "  {}", colors.dimmed("Skipped by export hooks"));
+    *summary = Some(ExportSummary {
+      pages: 0,
+      total_pages: 1,
+      bytes: 0,
+      images: 0,
+      attachments: 0,
+      failures: 0,
+      accessibility_issues: None,
+      stubs: Vec::new(),
+      elapsed: Duration::ZERO,
+    });
+    return Ok(());
+  }
 
   // Get storage content for size display
   if cli.behavior.verbose > 0
     && let Some(storage) = page.body.as_ref().and_then(|b| b.storage.as_ref())
   {
-    println!(
+    say!(
+      cli,
       "  {}: {} characters",
       colors.dimmed("Content size"),
       colors.number(storage.value.len())
     );
   }
 
-  let output_dir = Path::new(&cli.output.output);
-
   // Convert to target format
   let format_name = match cli.output.format {
     OutputFormat::Markdown => "Markdown",
     OutputFormat::AsciiDoc => "AsciiDoc",
+    OutputFormat::Json => "JSON",
   };
-  println!(
+  say!(
+    cli,
     "\n{} {}",
     colors.info("→"),
     colors.info(format!("Converting to {format_name}"))
   );
 
   // Process the page (API calls + conversion)
-  let process_options = build_process_options(cli, output_dir);
-  let processed = process_page(&client, &page, &process_options).await?;
+  let mut scope = ExportScope::default();
+  let extension = build_write_options(cli).file_extension().to_string();
+  scope.insert_with_path(
+    &page,
+    format!("{}.{extension}", build_filename_options(cli, output_dir).stem(&page)),
+  );
+  let links = build_link_context(cli, scope);
+  let assets = AssetRegistry::default();
+  let process_options = ProcessOptions {
+    template: template.as_ref(),
+    external_images: external_images.as_ref(),
+    assets: Some(&assets),
+    ..build_process_options(
+      cli,
+      output_dir,
+      &links,
+      link_titles.as_ref(),
+      users.as_ref(),
+      includes.as_ref(),
+      front_matter_rules.as_ref(),
+    )
+  };
+  let mut processed = process_page(&client, &page, &process_options).await?;
+  let mut stubs = Vec::new();
+  if !page.content_kind().is_exportable() {
+    say!(
+      cli,
+      "  {} {}",
+      colors.warning("⚠"),
+      colors.warning(format!(
+        "This {} cannot be converted; writing a stub linking to Confluence",
+        page.content_kind().label()
+      ))
+    );
+    stubs.push(StubbedContent {
+      kind: page.content_kind(),
+      title: page.title.clone(),
+    });
+  }
+  if cli.output.profile == Some(ExportProfile::Docusaurus) {
+    let mut front_matter = docusaurus::front_matter(&page, &processed.filename, 1);
+    front_matter.extend(&processed.front_matter);
+    processed.front_matter = front_matter;
+  }
+  hooks.on_page_converted(&mut PageConverted {
+    page: &page,
+    processed: &mut processed,
+  });
+  record_includes(include_graph.as_ref(), &page)?;
 
   if cli.behavior.verbose > 0 {
-    println!(
+    say!(
+      cli,
       "  {}: {} characters",
       colors.dimmed(format!("{format_name} size")),
       colors.number(processed.content.len())
@@ -202,23 +633,25 @@ async fn download_page(page_input: &str, cli: &Cli, colors: &ColorScheme) -> any
 
   // Log image/attachment processing
   if cli.images_links.download_images {
-    println!("\n{} {}", colors.info("→"), colors.info("Processing images"));
+    say!(cli, "\n{} {}", colors.info("→"), colors.info("Processing images"));
     if !processed.images.is_empty() {
-      println!(
+      say!(
+        cli,
         "  {} Processed {} {}",
         colors.success("✓"),
         colors.number(processed.images.len()),
         if processed.images.len() == 1 { "image" } else { "images" }
       );
     } else {
-      println!("  {}", colors.dimmed("No images found in page"));
+      say!(cli, "  {}", colors.dimmed("No images found in page"));
     }
   }
 
   if cli.page.attachments {
-    println!("\n{} {}", colors.info("→"), colors.info("Processing attachments"));
+    say!(cli, "\n{} {}", colors.info("→"), colors.info("Processing attachments"));
     if !processed.attachments.is_empty() {
-      println!(
+      say!(
+        cli,
         "  {} Processed {} {}",
         colors.success("✓"),
         colors.number(processed.attachments.len()),
@@ -229,18 +662,344 @@ async fn download_page(page_input: &str, cli: &Cli, colors: &ColorScheme) -> any
         }
       );
     } else {
-      println!("  {}", colors.dimmed("No attachments found in page"));
+      say!(cli, "  {}", colors.dimmed("No attachments found in page"));
     }
   }
 
   // Write to disk (I/O phase)
-  println!("\n{} {}", colors.info("→"), colors.info("Writing to disk"));
-  let output_path = write_processed_page(&processed, output_dir, cli.output.format, cli.output.overwrite)?;
-  println!("  {}: {}", colors.emphasis("File"), colors.path(output_path.display()));
+  say!(cli, "\n{} {}", colors.info("→"), colors.info("Writing to disk"));
+  let output_path = write_processed_page(&processed, output_dir, &build_write_options(cli), &assets).await?;
+  say!(
+    cli,
+    "  {}: {}",
+    colors.emphasis("File"),
+    colors.path(output_path.display())
+  );
+  warn_if_oversized(&output_path, &processed.content, cli, colors);
+  audit_accessibility(&page, cli, colors);
+  report_page_written(cli, &page, &processed, output_dir, &output_path);
+  hooks::report_written(hooks, &page, &processed, output_dir, &output_path);
+  record_changes(cli, changelog.as_ref(), &page, &processed, &output_path);
+  record_tasks(cli, tasks.as_ref(), &page, &output_path)?;
+  if let Some(manifest) = &manifest {
+    manifest.record(&page, &processed, output_dir, &output_path).await?;
+  }
+  if cli.behavior.log_format == LogFormat::Json {
+    events::emit(&Event::ExportDone {
+      pages: 1,
+      failures: 0,
+      bytes: processed.total_bytes(),
+    });
+  }
+  *summary = Some(ExportSummary {
+    pages: 1,
+    total_pages: 1,
+    bytes: processed.total_bytes(),
+    images: processed.images.len(),
+    attachments: processed.attachments.len(),
+    failures: 0,
+    accessibility_issues: None,
+    stubs,
+    elapsed: Duration::ZERO,
+  });
+  let mut artifacts = Vec::new();
+  artifacts.extend(write_include_graph(cli, include_graph.as_ref(), colors)?);
+  artifacts.extend(write_changelog(cli, changelog.as_ref(), colors)?);
+  artifacts.extend(write_tasks_report(&client, cli, tasks.as_ref(), users.as_ref(), colors).await?);
+  write_manifest(cli, manifest.as_ref(), colors)?;
+  let tree = confluence::PageTree {
+    page,
+    children: Vec::new(),
+    depth: 0,
+    exported: true,
+  };
+  artifacts.extend(write_mkdocs_config(
+    cli,
+    std::slice::from_ref(&tree),
+    &TreeFilenames::plan(&tree, build_filename_options(cli, output_dir)),
+    colors,
+  )?);
+  sign_export(cli, manifest.as_ref(), &artifacts, colors).await?;
 
   Ok(())
 }
 
+/// Directory exported pages are written to: the [`pages_root`] followed by
+/// the directories of `--layout` for the exported root page.
+fn pages_dir(cli: &Cli, root: &confluence::Page) -> PathBuf {
+  pages_root(cli).join(layout_dir(cli, root))
+}
+
+/// Root of the exported pages: the output directory, or its `docs/`
+/// subdirectory with the MkDocs profile.
+fn pages_root(cli: &Cli) -> PathBuf {
+  let output_dir = Path::new(&cli.output.output);
+  if cli.output.profile == Some(ExportProfile::MkDocs) {
+    output_dir.join(mkdocs::DOCS_DIR)
+  } else {
+    output_dir.to_path_buf()
+  }
+}
+
+/// Directories of `--layout` for each exported tree, relative to the
+/// [`pages_root`].
+///
+/// Linked pages are placed like the root page, so with `--layout space` a
+/// page linked from another space goes in that space's directory.
+fn forest_dirs(cli: &Cli, forest: &[confluence::PageTree]) -> Vec<PathBuf> {
+  forest.iter().map(|tree| layout_dir(cli, &tree.page)).collect()
+}
+
+/// Directories of `--layout` that the tree rooted at `page` is placed in,
+/// relative to the [`pages_root`].
+fn layout_dir(cli: &Cli, page: &confluence::Page) -> PathBuf {
+  if cli.output.profile == Some(ExportProfile::MkDocs) {
+    return PathBuf::new();
+  }
+  let output_dir = Path::new(&cli.output.output);
+  cli
+    .output
+    .layout
+    .directory(page, &build_filename_options(cli, output_dir))
+}
+
+/// Write `mkdocs.yml` at the export root when the MkDocs profile is selected.
+///
+/// # Returns
+/// The path of the written file, if one was written.
+///
+/// # Errors
+/// Returns an error when the file cannot be written.
+fn write_mkdocs_config(
+  cli: &Cli,
+  forest: &[confluence::PageTree],
+  filenames: &TreeFilenames,
+  colors: &ColorScheme,
+) -> anyhow::Result<Option<PathBuf>> {
+  if cli.output.profile != Some(ExportProfile::MkDocs) {
+    return Ok(None);
+  }
+  let config_path = Path::new(&cli.output.output).join(mkdocs::CONFIG_FILE);
+  let config = mkdocs::render_config(forest, filenames, build_write_options(cli).file_extension());
+  fs::write(&config_path, config)
+    .with_context(|| format!("Failed to write MkDocs configuration to {}", config_path.display()))?;
+  say!(cli, "  {} {}", colors.success("✓"), colors.path(config_path.display()));
+  Ok(Some(config_path))
+}
+
+/// The exported tree a space README describes.
+struct SpaceExport<'a> {
+  base_url: &'a str,
+  tree: &'a confluence::PageTree,
+  filenames: &'a TreeFilenames,
+  page_count: usize,
+}
+
+/// Write the space landing page (and the space logo) at the export root.
+///
+/// The README is skipped with a warning when the root page carries no space or
+/// is itself written to the README path; a logo that cannot be downloaded is left
+/// out.
+///
+/// # Returns
+/// The paths of the written files.
+///
+/// # Errors
+/// Returns an error when the space cannot be fetched or the files cannot be
+/// written.
+async fn write_space_readme(
+  client: &impl ConfluenceApi,
+  export: &SpaceExport<'_>,
+  cli: &Cli,
+  colors: &ColorScheme,
+) -> anyhow::Result<Vec<PathBuf>> {
+  let root = &export.tree.page;
+  let output_dir = pages_dir(cli, root);
+  let write_options = build_write_options(cli);
+  let root_path = format!("{}.{}", export.filenames.stem(root), write_options.file_extension());
+  let readme_file = format!("{}.{}", space_readme::README_STEM, write_options.document_extension());
+  let Some(space_key) = root.space.as_ref().map(|space| space.key.as_str()) else {
+    tracing::warn!("Skipping the space README: page {} has no space", root.id);
+    return Ok(Vec::new());
+  };
+  if root_path.eq_ignore_ascii_case(&readme_file) {
+    tracing::warn!("Skipping the space README: the root page is written to {root_path}");
+    return Ok(Vec::new());
+  }
+
+  let space = client.get_space(space_key).await?;
+  let mut written = Vec::new();
+  let icon_path = match space.icon.as_ref().filter(|icon| !icon.is_default) {
+    Some(icon) => match client.fetch_attachment(&icon.path).await {
+      Ok(content) => {
+        let name = format!("{}.{}", space_readme::ICON_STEM, space_readme::icon_extension(&content));
+        let path = output_dir.join(&name);
+        fs::write(&path, content).with_context(|| format!("Failed to write space logo to {}", path.display()))?;
+        written.push(path);
+        Some(name)
+      }
+      Err(err) => {
+        tracing::warn!("Failed to download the logo of space {space_key}: {err:#}");
+        None
+      }
+    },
+    None => None,
+  };
+
+  let readme = SpaceReadme {
+    space: &space,
+    base_url: export.base_url,
+    root_title: &root.title,
+    root_path: &root_path,
+    page_count: export.page_count,
+    exported_at: Utc::now(),
+    icon_path: icon_path.as_deref(),
+    format: cli.output.format,
+  };
+  let path = output_dir.join(&readme_file);
+  fs::write(&path, readme.render()).with_context(|| format!("Failed to write space README to {}", path.display()))?;
+  say!(cli, "  {} {}", colors.success("✓"), colors.path(path.display()));
+  written.push(path);
+  Ok(written)
+}
+
+/// Convert a single page and write it to stdout for use in pipelines.
+///
+/// Nothing else is written to stdout, and images and attachments are not
+/// downloaded, so their links keep pointing at the attachment filenames.
+///
+/// # Arguments
+/// * `page_input` - Page URL or numeric ID.
+/// * `cli` - Parsed CLI options.
+///
+/// # Errors
+/// Returns an error when fetching or converting the page fails, or when
+/// stdout cannot be written.
+async fn stream_page(page_input: &str, cli: &Cli) -> anyhow::Result<()> {
+  let url_info = parse_page_input(page_input, cli)?;
+  let client = create_client(&url_info.base_url, cli)?;
+  let link_titles = if cli.images_links.fetch_link_titles {
+    Some(LinkTitleFetcher::new(cli.images_links.link_title_rate_limit)?)
+  } else {
+    None
+  };
+  let users = cli.images_links.resolve_users.then(UserResolver::new);
+  let includes = cli.images_links.resolve_includes.then(IncludeResolver::new);
+  let front_matter_rules = load_front_matter_rules(cli)?;
+  let template = load_template(cli)?;
+
+  let page = client.get_page(&url_info.page_id).await?;
+  let write_options = build_write_options(cli);
+  let mut scope = ExportScope::default();
+  scope.insert_with_path(
+    &page,
+    format!(
+      "{}.{}",
+      build_filename_options(cli, Path::new(&cli.output.output)).stem(&page),
+      write_options.file_extension()
+    ),
+  );
+  let links = build_link_context(cli, scope);
+  let process_options = ProcessOptions {
+    download_images: false,
+    download_attachments: false,
+    save_raw: false,
+    extract_tables: None,
+    output_dir: None,
+    template: template.as_ref(),
+    ..build_process_options(
+      cli,
+      Path::new(&cli.output.output),
+      &links,
+      link_titles.as_ref(),
+      users.as_ref(),
+      includes.as_ref(),
+      front_matter_rules.as_ref(),
+    )
+  };
+  let mut processed = process_page(&client, &page, &process_options).await?;
+  if cli.output.profile == Some(ExportProfile::Docusaurus) {
+    let mut front_matter = docusaurus::front_matter(&page, &processed.filename, 1);
+    front_matter.extend(&processed.front_matter);
+    processed.front_matter = front_matter;
+  }
+
+  let document = render_page_file(&processed, write_options.format)?;
+  let mut stdout = io::stdout().lock();
+  stdout
+    .write_all(document.as_bytes())
+    .and_then(|()| stdout.flush())
+    .context("Failed to write page to stdout")
+}
+
+/// Resolve the page ID and base URL from a page URL or a numeric ID.
+///
+/// # Errors
+/// Returns an error when the URL cannot be parsed, or when a numeric ID is
+/// given without `--url`.
+fn parse_page_input(page_input: &str, cli: &Cli) -> anyhow::Result<confluence::UrlInfo> {
+  if page_input.contains("://") {
+    // It's a URL
+    return confluence::parse_confluence_url(page_input);
+  }
+
+  // It's a page ID - need base URL from --url
+  match cli.auth.url {
+    Some(ref base_url) => Ok(confluence::UrlInfo {
+      base_url: base_url.clone(),
+      page_id: page_input.to_string(),
+      space_key: None,
+    }),
+    None => anyhow::bail!("--url is required when using a numeric page ID"),
+  }
+}
+
+/// Shared state for a recursive page tree download.
+struct TreeDownload<'a> {
+  /// Confluence API implementation to fetch content from.
+  client: &'a dyn ConfluenceApi,
+  /// Parsed CLI settings controlling behavior.
+  cli: &'a Cli,
+  /// Color palette for log output.
+  colors: &'a ColorScheme,
+  /// Link resolution context shared by every page in the tree.
+  links: &'a LinkContext,
+  /// Title lookup for bare external links, when enabled.
+  link_titles: Option<&'a LinkTitleFetcher>,
+  /// Display name lookup for user mentions, when enabled.
+  users: Option<&'a UserResolver>,
+  /// Included page lookup for include macros, when enabled.
+  includes: Option<&'a IncludeResolver>,
+  /// Configured front matter rules, when given.
+  front_matter_rules: Option<&'a FrontMatterRules>,
+  /// Template wrapping each page's content, when given.
+  template: Option<&'a PageTemplate>,
+  /// Downloader for images embedded from other sites, when enabled.
+  external_images: Option<&'a ExternalImageFetcher>,
+  /// Include dependency graph being collected, when requested.
+  include_graph: Option<&'a IncludeGraph>,
+  /// Pages recorded for the sync digest, when requested.
+  changelog: Option<&'a ChangeLog>,
+  /// Open tasks recorded for the task summary, when requested.
+  tasks: Option<&'a TaskReport>,
+  /// Pages that failed, recorded instead of stopping the export with `--keep-going`.
+  failure_report: Option<&'a FailureReport>,
+  /// Pages and assets recorded for the export manifest, unless disabled.
+  manifest: Option<&'a Manifest>,
+  /// Content-addressed asset directory, when assets are deduplicated.
+  shared_assets: Option<&'a SharedAssets>,
+  /// Asset downloads and writes of the export, shared between its pages.
+  assets: &'a AssetRegistry,
+  /// Callbacks invoked before and after each page is exported.
+  hooks: &'a dyn ExportHooks,
+  /// Filenames of the pages, with duplicate sibling titles made unique.
+  filenames: &'a TreeFilenames,
+  /// Shared limiter controlling concurrent downloads.
+  semaphore: Arc<Semaphore>,
+  /// Progress bar and counters for the end-of-run summary.
+  progress: &'a ExportProgress,
+}
+
 /// Recursively download and render every node in a [`confluence::PageTree`].
 ///
 /// The traversal enforces the configured parallelism with a semaphore so that
@@ -249,12 +1008,10 @@ async fn download_page(page_input: &str, cli: &Cli, colors: &ColorScheme) -> any
 /// children are written to nested directories mirroring the tree shape.
 ///
 /// # Arguments
-/// * `client` - Confluence API implementation to fetch content from.
+/// * `download` - Client, settings, and limiter shared across the traversal.
 /// * `tree` - Current tree node describing the page and its descendants.
 /// * `output_dir` - Root directory under which files for this node are stored.
-/// * `cli` - Parsed CLI settings controlling behavior.
-/// * `colors` - Color palette for log output.
-/// * `semaphore` - Shared limiter controlling concurrent downloads.
+/// * `position` - One-based position of the page among its siblings.
 ///
 /// # Returns
 /// A future resolving once the tree rooted at `tree` is fully written.
@@ -263,14 +1020,20 @@ async fn download_page(page_input: &str, cli: &Cli, colors: &ColorScheme) -> any
 /// Returns an error when API calls fail, when data is missing required fields,
 /// or when filesystem interactions cannot be completed.
 fn download_page_tree<'a>(
-  client: &'a dyn ConfluenceApi,
+  download: &'a TreeDownload<'a>,
   tree: &'a confluence::PageTree,
   output_dir: &'a Path,
-  cli: &'a Cli,
-  colors: &'a ColorScheme,
-  semaphore: Arc<Semaphore>,
+  position: usize,
 ) -> std::pin::Pin<Box<dyn std::future::Future<Output = anyhow::Result<()>> + 'a + Send>> {
   Box::pin(async move {
+    let TreeDownload {
+      cli,
+      colors,
+      semaphore,
+      progress,
+      ..
+    } = download;
+
     let permit = semaphore
       .clone()
       .acquire_owned()
@@ -280,33 +1043,62 @@ fn download_page_tree<'a>(
     let page = &tree.page;
 
     if cli.behavior.verbose > 0 {
-      println!(
-        "{}   {} {}",
-        colors.progress("→"),
-        colors.dimmed(format!("Depth {}", tree.depth)),
-        colors.info(&page.title)
-      );
+      progress.suspend(|| {
+        say!(
+          cli,
+          "{}   {} {}",
+          colors.progress("→"),
+          colors.dimmed(format!("Depth {}", tree.depth)),
+          colors.info(&page.title)
+        )
+      });
     }
 
-    // Process the page (API calls + conversion)
-    let process_options = build_process_options(cli, output_dir);
-    let processed = process_page(client, page, &process_options).await?;
-
-    if cli.behavior.verbose > 0 && !processed.attachments.is_empty() {
-      println!(
-        "    {} {}",
-        colors.dimmed("Attachments:"),
-        colors.number(processed.attachments.len())
-      );
-    } else if cli.behavior.verbose > 1 && cli.page.attachments && processed.attachments.is_empty() {
-      println!("    {}", colors.dimmed("No attachments found"));
+    let start = PageStart {
+      page,
+      depth: tree.depth,
+      output_dir,
+    };
+    // Pages filtered out themselves only hold the directory of their children
+    let action = if tree.exported {
+      download.hooks.on_page_start(&start)
+    } else {
+      PageAction::Skip
+    };
+    if action == PageAction::Export && cli.behavior.log_format == LogFormat::Json {
+      events::emit(&Event::PageStarted {
+        page_id: &page.id,
+        title: &page.title,
+      });
     }
-
-    // Write processed page to disk (I/O phase)
-    let output_path = write_processed_page(&processed, output_dir, cli.output.format, cli.output.overwrite)?;
-
-    if !cli.behavior.quiet {
-      println!("  {} {}", colors.success("✓"), colors.path(output_path.display()));
+    let result = match action {
+      PageAction::Export => export_tree_page(download, page, output_dir, position).await.map(Some),
+      PageAction::Skip => Ok(None),
+    };
+    let processed = match result {
+      Ok(processed) => processed,
+      Err(err) => {
+        progress.record_failure();
+        if cli.behavior.log_format == LogFormat::Json {
+          events::emit(&Event::Error {
+            page_id: Some(&page.id),
+            message: format!("{err:#}"),
+          });
+        }
+        let Some(report) = download.failure_report else {
+          return Err(err);
+        };
+        progress.suspend(|| {
+          if cli.behavior.log_format == LogFormat::Text && !cli.behavior.quiet {
+            eprintln!("  {} {}: {err:#}", colors.error("✗"), colors.error(&page.title));
+          }
+        });
+        report.record(page, &err);
+        None
+      }
+    };
+    if let Some(processed) = &processed {
+      progress.record_page(processed);
     }
 
     // Release permit before scheduling children so they can use the slot.
@@ -315,14 +1107,15 @@ fn download_page_tree<'a>(
     // Download child pages recursively
     if !tree.children.is_empty() {
       // Create subdirectory for children
-      let child_dir = output_dir.join(&processed.filename);
+      let child_dir = output_dir.join(download.filenames.stem(page));
       fs::create_dir_all(&child_dir)
         .with_context(|| format!("Failed to create directory for child pages at {}", child_dir.display()))?;
 
       let child_futures = tree
         .children
         .iter()
-        .map(|child_tree| download_page_tree(client, child_tree, &child_dir, cli, colors, Arc::clone(&semaphore)));
+        .enumerate()
+        .map(|(index, child_tree)| download_page_tree(download, child_tree, &child_dir, index + 1));
 
       for result in join_all(child_futures).await {
         result?;
@@ -333,47 +1126,848 @@ fn download_page_tree<'a>(
   })
 }
 
+/// Convert one page of a tree download and write it to `output_dir`.
+///
+/// # Returns
+/// The processed page, whose filename names the directory for its children.
+async fn export_tree_page(
+  download: &TreeDownload<'_>,
+  page: &confluence::Page,
+  output_dir: &Path,
+  position: usize,
+) -> anyhow::Result<ProcessedPage> {
+  let TreeDownload {
+    client,
+    cli,
+    colors,
+    links,
+    link_titles,
+    users,
+    includes,
+    front_matter_rules,
+    template,
+    external_images,
+    include_graph,
+    changelog,
+    tasks,
+    manifest,
+    shared_assets,
+    assets,
+    hooks,
+    filenames,
+    progress,
+    ..
+  } = download;
+
+  // Process the page (API calls + conversion)
+  let process_options = ProcessOptions {
+    template: *template,
+    external_images: *external_images,
+    assets: Some(*assets),
+    ..build_process_options(
+      cli,
+      output_dir,
+      links,
+      *link_titles,
+      *users,
+      *includes,
+      *front_matter_rules,
+    )
+  };
+  let mut processed = process_page(*client, page, &process_options).await?;
+  processed.filename = filenames.stem(page);
+  if !page.content_kind().is_exportable() {
+    progress.record_stub(page);
+  }
+  if cli.output.profile == Some(ExportProfile::Docusaurus) {
+    let mut front_matter = docusaurus::front_matter(page, &processed.filename, position);
+    front_matter.extend(&processed.front_matter);
+    processed.front_matter = front_matter;
+  }
+  if let Some(shared_assets) = shared_assets {
+    shared_assets.share(&mut processed, output_dir)?;
+  }
+  hooks.on_page_converted(&mut PageConverted {
+    page,
+    processed: &mut processed,
+  });
+  record_includes(*include_graph, page)?;
+
+  progress.suspend(|| {
+    if cli.behavior.verbose > 0 && !processed.attachments.is_empty() {
+      say!(
+        cli,
+        "    {} {}",
+        colors.dimmed("Attachments:"),
+        colors.number(processed.attachments.len())
+      );
+    } else if cli.behavior.verbose > 1 && cli.page.attachments && processed.attachments.is_empty() {
+      say!(cli, "    {}", colors.dimmed("No attachments found"));
+    }
+  });
+
+  // Write processed page to disk (I/O phase)
+  let output_path = write_processed_page(&processed, output_dir, &build_write_options(cli), assets).await?;
+
+  progress.suspend(|| {
+    if !cli.behavior.quiet {
+      say!(cli, "  {} {}", colors.success("✓"), colors.path(output_path.display()));
+    }
+    warn_if_oversized(&output_path, &processed.content, cli, colors);
+    progress.record_accessibility_issues(audit_accessibility(page, cli, colors));
+  });
+  report_page_written(cli, page, &processed, output_dir, &output_path);
+  hooks::report_written(*hooks, page, &processed, output_dir, &output_path);
+  record_changes(cli, *changelog, page, &processed, &output_path);
+  record_tasks(cli, *tasks, page, &output_path)?;
+  if let Some(manifest) = manifest {
+    manifest.record(page, &processed, output_dir, &output_path).await?;
+  }
+
+  Ok(processed)
+}
+
+/// Emit `attachment_downloaded` and `page_done` events for a written page
+/// when `--log-format json` is set.
+fn report_page_written(
+  cli: &Cli,
+  page: &confluence::Page,
+  processed: &ProcessedPage,
+  output_dir: &Path,
+  output_path: &Path,
+) {
+  if cli.behavior.log_format != LogFormat::Json {
+    return;
+  }
+
+  let assets = processed
+    .images
+    .iter()
+    .map(|asset| ("image", asset))
+    .chain(processed.attachments.iter().map(|asset| ("attachment", asset)));
+  for (kind, asset) in assets {
+    events::emit(&Event::AttachmentDownloaded {
+      page_id: &page.id,
+      kind,
+      path: output_dir.join(&asset.relative_path).display().to_string(),
+      bytes: asset.content.size(),
+    });
+  }
+  events::emit(&Event::PageDone {
+    page_id: &page.id,
+    title: &page.title,
+    path: output_path.display().to_string(),
+    links: &processed.link_map,
+  });
+}
+
+/// Add a page's include dependencies to the graph when one is being collected.
+fn record_includes(graph: Option<&IncludeGraph>, page: &confluence::Page) -> anyhow::Result<()> {
+  match (graph, page.storage_content()) {
+    (Some(graph), Some(storage)) => graph.record(page, storage),
+    _ => Ok(()),
+  }
+}
+
+/// Write the collected include graph to the path given by `--include-graph`,
+/// returning that path.
+fn write_include_graph(
+  cli: &Cli,
+  graph: Option<&IncludeGraph>,
+  colors: &ColorScheme,
+) -> anyhow::Result<Option<PathBuf>> {
+  let (Some(graph), Some(path)) = (graph, cli.output.include_graph.as_deref()) else {
+    return Ok(None);
+  };
+  graph.write(Path::new(path))?;
+  say!(cli, "  {}: {}", colors.emphasis("Include graph"), colors.path(path));
+  Ok(Some(PathBuf::from(path)))
+}
+
+/// Add a written page to the sync digest when one is being collected.
+fn record_changes(
+  cli: &Cli,
+  changelog: Option<&ChangeLog>,
+  page: &confluence::Page,
+  processed: &ProcessedPage,
+  output_path: &Path,
+) {
+  if let Some(changelog) = changelog {
+    let path = output_path
+      .strip_prefix(&cli.output.output)
+      .unwrap_or(output_path)
+      .to_string_lossy();
+    changelog.record(page, processed, &path, cli.output.format);
+  }
+}
+
+/// Write the `CHANGES` digest at the export root in the output format when
+/// `--changelog` is set, returning its path.
+fn write_changelog(cli: &Cli, changelog: Option<&ChangeLog>, colors: &ColorScheme) -> anyhow::Result<Option<PathBuf>> {
+  let Some(changelog) = changelog else {
+    return Ok(None);
+  };
+  let path = changelog.write(
+    Path::new(&cli.output.output),
+    cli.output.format,
+    build_write_options(cli).document_extension(),
+  )?;
+  say!(cli, "  {} {}", colors.success("✓"), colors.path(path.display()));
+  Ok(Some(path))
+}
+
+/// Add the open tasks of a written page to the task summary when one is being
+/// collected.
+fn record_tasks(
+  cli: &Cli,
+  tasks: Option<&TaskReport>,
+  page: &confluence::Page,
+  output_path: &Path,
+) -> anyhow::Result<()> {
+  if let Some(tasks) = tasks {
+    let path = output_path
+      .strip_prefix(&cli.output.output)
+      .unwrap_or(output_path)
+      .to_string_lossy();
+    tasks.record(page, &path)?;
+  }
+  Ok(())
+}
+
+/// Write the `TASKS` report at the export root in the output format when
+/// `--tasks-report` is set, with
+/// assignees resolved to display names when `--resolve-users` is set.
+async fn write_tasks_report(
+  client: &impl ConfluenceApi,
+  cli: &Cli,
+  tasks: Option<&TaskReport>,
+  users: Option<&UserResolver>,
+  colors: &ColorScheme,
+) -> anyhow::Result<Option<PathBuf>> {
+  let Some(tasks) = tasks else {
+    return Ok(None);
+  };
+  let mut content = tasks.render(cli.output.format);
+  if let Some(users) = users {
+    content = users.apply(client, &content).await;
+  }
+  let path = Path::new(&cli.output.output).join(format!(
+    "{}.{}",
+    tasks::TASKS_STEM,
+    build_write_options(cli).document_extension()
+  ));
+  fs::write(&path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+  say!(cli, "  {} {}", colors.success("✓"), colors.path(path.display()));
+  Ok(Some(path))
+}
+
+/// Write `manifest.json` at the export root unless `--manifest false` is set.
+fn write_manifest(cli: &Cli, manifest: Option<&Manifest>, colors: &ColorScheme) -> anyhow::Result<()> {
+  if let Some(manifest) = manifest {
+    let path = manifest.write()?;
+    say!(cli, "  {} {}", colors.success("✓"), colors.path(path.display()));
+  }
+  Ok(())
+}
+
+/// Remove the files of the previous export whose pages were deleted in
+/// Confluence, moved out of the exported tree, or exported under a new path.
+///
+/// Pruning is skipped when this export is incomplete, when the previous
+/// export was of another root page, or when the previous manifest does not
+/// record its root page. A page missing from this export only counts as
+/// removed when the API confirms it; see [`confirm_removed_pages`].
+///
+/// # Arguments
+/// * `client` - API used to confirm that missing pages were removed.
+/// * `manifest` - Files written by this export.
+/// * `mode` - Whether to delete the stale files or only list them.
+/// * `incomplete` - Why this export left pages of the tree out, if it did.
+///
+/// # Errors
+/// Returns an error when a stale file cannot be deleted.
+async fn prune_stale_files(
+  client: &impl ConfluenceApi,
+  cli: &Cli,
+  manifest: &Manifest,
+  mode: PruneMode,
+  incomplete: Option<&str>,
+  colors: &ColorScheme,
+) -> anyhow::Result<()> {
+  let refuse = |reason: &str| {
+    eprintln!(
+      "  {} {}",
+      colors.warning("⚠"),
+      colors.warning(format!("Not pruning stale files because {reason}"))
+    );
+  };
+  if let Some(reason) = incomplete {
+    refuse(reason);
+    return Ok(());
+  }
+  let stale_files = match manifest.stale_files() {
+    Ok(stale_files) => stale_files,
+    Err(err) => {
+      eprintln!(
+        "  {} {}",
+        colors.warning("⚠"),
+        colors.warning(format!("Cannot prune stale files: {err:#}"))
+      );
+      return Ok(());
+    }
+  };
+  match stale_files.root_page.as_deref() {
+    Some(root_page) if root_page == manifest.root_page() => {}
+    Some(root_page) => {
+      refuse(&format!("the previous export was of page {root_page}"));
+      return Ok(());
+    }
+    None => {
+      refuse("the previous manifest does not record its root page");
+      return Ok(());
+    }
+  }
+  let removed = confirm_removed_pages(
+    client,
+    manifest.root_page(),
+    &stale_files.missing_pages,
+    cli.performance.resolved_parallel(),
+  )
+  .await;
+  let stale = stale_files.paths(&removed);
+
+  let root = Path::new(&cli.output.output);
+  let (verb, paths) = match mode {
+    PruneMode::List => ("Would remove", stale),
+    PruneMode::Delete => ("Removed", manifest::remove_files(root, &stale)?),
+  };
+  for path in &paths {
+    say!(
+      cli,
+      "  {} {verb} {}",
+      colors.info("-"),
+      colors.path(root.join(path).display())
+    );
+  }
+  say!(
+    cli,
+    "  {} {verb} {} stale {}",
+    colors.success("✓"),
+    colors.number(paths.len()),
+    if paths.len() == 1 { "file" } else { "files" }
+  );
+  Ok(())
+}
+
+/// Pages of an earlier export, missing from this one, that were deleted in
+/// Confluence or moved out of the tree below `root_page`.
+///
+/// A page counts as deleted when fetching it answers 404 or it is in the
+/// trash. Pages that cannot be fetched for another reason count as still
+/// present, so their files are kept.
+async fn confirm_removed_pages(
+  client: &impl ConfluenceApi,
+  root_page: &str,
+  ids: &[String],
+  parallel: usize,
+) -> BTreeSet<String> {
+  stream::iter(ids)
+    .map(|id| async move {
+      let removed = match client.get_page(id).await {
+        Ok(page) => page.status == "trashed" || !page.ancestors.iter().any(|ancestor| ancestor.id == root_page),
+        Err(err) => err.status() == Some(404),
+      };
+      removed.then(|| id.clone())
+    })
+    .buffer_unordered(parallel.max(1))
+    .filter_map(std::future::ready)
+    .collect()
+    .await
+}
+
+/// Remove images and attachments of an earlier export that no longer match
+/// their manifest checksum, so this run downloads them again.
+async fn verify_downloads(cli: &Cli, colors: &ColorScheme) {
+  let root = Path::new(&cli.output.output);
+  say!(
+    cli,
+    "{} {}",
+    colors.info("→"),
+    colors.info("Verifying downloaded files")
+  );
+  match manifest::remove_corrupted_assets(root).await {
+    Ok(verification) => {
+      for path in &verification.removed {
+        eprintln!(
+          "  {} {} is corrupted or truncated; downloading it again",
+          colors.warning("⚠"),
+          colors.path(path)
+        );
+      }
+      say!(
+        cli,
+        "  {} Checked {} {}",
+        colors.success("✓"),
+        colors.number(verification.checked),
+        if verification.checked == 1 { "file" } else { "files" }
+      );
+    }
+    Err(err) => eprintln!(
+      "  {} {}",
+      colors.warning("⚠"),
+      colors.warning(format!("Cannot verify downloaded files: {err:#}"))
+    ),
+  }
+}
+
+/// Warn when a written page exceeds the `--size-limit` of the target platform.
+fn warn_if_oversized(path: &Path, content: &str, cli: &Cli, colors: &ColorScheme) {
+  let limit = cli.output.size_limit;
+  let Ok(metadata) = fs::metadata(path) else {
+    return;
+  };
+  let Some(oversized) = size_limits::check_page_size(metadata.len(), content, limit) else {
+    return;
+  };
+
+  eprintln!(
+    "  {} {} is {}, over {}",
+    colors.warning("⚠"),
+    colors.path(path.display()),
+    colors.number(size_limits::format_size(oversized.size)),
+    limit
+  );
+  let hint = if oversized.table_percent >= 50 {
+    format!(
+      "Tables make up {}% of the page; consider moving them to attachments or child pages",
+      oversized.table_percent
+    )
+  } else {
+    "Consider splitting the page into child pages before publishing".to_string()
+  };
+  eprintln!("    {}", colors.dimmed(hint));
+}
+
+/// Report pages written under a suffixed filename because a sibling page has
+/// the same title.
+fn warn_duplicate_titles(filenames: &TreeFilenames, colors: &ColorScheme) {
+  let renamed = filenames.renamed();
+  if renamed.is_empty() {
+    return;
+  }
+
+  eprintln!(
+    "  {} {} {} a title with a sibling page and will be saved under a suffixed name:",
+    colors.warning("⚠"),
+    colors.number(renamed.len()),
+    if renamed.len() == 1 {
+      "page shares"
+    } else {
+      "pages share"
+    }
+  );
+  for page in renamed {
+    eprintln!(
+      "    {} ({}) → {}",
+      page.title,
+      colors.dimmed(&page.page_id),
+      colors.path(&page.filename)
+    );
+  }
+}
+
+/// Warn about pages whose filenames were shortened to respect the path length
+/// limit.
+fn warn_shortened_filenames(filenames: &TreeFilenames, colors: &ColorScheme) {
+  let shortened = filenames.shortened();
+  if shortened.is_empty() {
+    return;
+  }
+
+  eprintln!(
+    "  {} {} {} too long for the path length limit and will be saved under a shortened name:",
+    colors.warning("⚠"),
+    colors.number(shortened.len()),
+    if shortened.len() == 1 {
+      "page path is"
+    } else {
+      "page paths are"
+    }
+  );
+  for page in shortened {
+    eprintln!(
+      "    {} ({}) → {}",
+      page.title,
+      colors.dimmed(&page.page_id),
+      colors.path(&page.filename)
+    );
+  }
+}
+
+/// Run the accessibility audit on a page when `--audit-accessibility` is set
+/// and report the findings on stderr, or as events with `--log-format json`.
+///
+/// # Returns
+/// The number of findings.
+fn audit_accessibility(page: &confluence::Page, cli: &Cli, colors: &ColorScheme) -> usize {
+  if !cli.output.audit_accessibility {
+    return 0;
+  }
+  let Some(storage) = page.storage_content() else {
+    return 0;
+  };
+  let issues = match accessibility::audit_storage(storage) {
+    Ok(issues) => issues,
+    Err(err) => {
+      tracing::warn!("Skipping accessibility audit of '{}': {err:#}", page.title);
+      return 0;
+    }
+  };
+
+  if cli.behavior.log_format == LogFormat::Json {
+    for issue in &issues {
+      events::emit(&Event::AccessibilityIssue {
+        page_id: &page.id,
+        kind: issue.kind,
+        message: &issue.message,
+      });
+    }
+  } else if !issues.is_empty() {
+    eprintln!(
+      "  {} {} accessibility {} in {}",
+      colors.warning("⚠"),
+      colors.number(issues.len()),
+      if issues.len() == 1 { "issue" } else { "issues" },
+      colors.emphasis(&page.title)
+    );
+    for issue in &issues {
+      eprintln!("    {}", colors.dimmed(issue));
+    }
+  }
+  issues.len()
+}
+
+/// Write `SHA256SUMS` when `--sign` is set, covering every file the export
+/// wrote: the files recorded in `manifest`, the manifest itself, and the
+/// `artifacts` written next to the pages. It is signed when `--sign-key` is
+/// given.
+async fn sign_export(
+  cli: &Cli,
+  manifest: Option<&Manifest>,
+  artifacts: &[PathBuf],
+  colors: &ColorScheme,
+) -> anyhow::Result<()> {
+  let (true, Some(manifest)) = (cli.output.sign, manifest) else {
+    return Ok(());
+  };
+
+  say!(cli, "\n{} {}", colors.info("→"), colors.info("Writing checksums"));
+  for path in artifacts {
+    manifest.record_file(path);
+  }
+  let mut files = manifest.paths();
+  files.push(manifest::MANIFEST_FILE.to_string());
+  let checksums = integrity::write_checksums(Path::new(&cli.output.output), files).await?;
+  say!(
+    cli,
+    "  {}: {}",
+    colors.emphasis("Checksums"),
+    colors.path(checksums.display())
+  );
+
+  let Some(key) = cli.output.sign_key.as_deref() else {
+    return Ok(());
+  };
+  let signature = integrity::sign_checksums(cli.output.signer, Path::new(key), &checksums)?;
+  say!(
+    cli,
+    "  {}: {}",
+    colors.emphasis("Signature"),
+    colors.path(signature.display())
+  );
+  Ok(())
+}
+
 /// Build the processing options from CLI settings.
 ///
 /// Creates a [`ProcessOptions`] struct that controls how pages are converted
 /// and what assets are downloaded.
-fn build_process_options<'a>(cli: &Cli, output_dir: &'a Path) -> ProcessOptions<'a> {
+pub(crate) fn build_process_options<'a>(
+  cli: &Cli,
+  output_dir: &'a Path,
+  links: &LinkContext,
+  link_titles: Option<&'a LinkTitleFetcher>,
+  users: Option<&'a UserResolver>,
+  includes: Option<&'a IncludeResolver>,
+  front_matter_rules: Option<&'a FrontMatterRules>,
+) -> ProcessOptions<'a> {
   ProcessOptions {
     format: cli.output.format,
     save_raw: cli.output.save_raw,
+    extract_tables: cli.output.extract_tables,
     download_images: cli.images_links.download_images,
     images_dir: cli.images_links.images_dir.clone(),
+    image_naming: cli.images_links.image_naming,
+    image_quality: cli.images_links.image_quality,
+    image_converter: cli
+      .images_links
+      .image_converter
+      .clone()
+      .map(|converter| Arc::new(converter) as Arc<dyn ImageConverter>),
     download_attachments: cli.page.attachments,
-    markdown_options: build_markdown_options(cli),
+    attachments_layout: cli.page.attachments_layout,
+    content_state: cli.output.content_state,
+    author_metadata: cli.output.author_metadata,
+    markdown_options: build_markdown_options(cli, links),
     asciidoc_options: build_asciidoc_options(cli),
     output_dir: Some(output_dir),
     overwrite: cli.output.overwrite,
+    link_titles,
+    // Created once per export by the callers
+    external_images: None,
+    users,
+    includes,
+    front_matter_rules,
+    conversion_cache: cli
+      .performance
+      .cache_dir
+      .as_deref()
+      .map(|dir| ConversionCache::new(Path::new(dir))),
+    redacted_macros: cli.output.redact_macro.clone(),
+    // Loaded once per export by the callers
+    template: None,
+    filenames: build_filename_options(cli, output_dir),
+    // Created once per export by the callers
+    assets: None,
+  }
+}
+
+/// Naming options for page files written below `output_dir`.
+pub(crate) fn build_filename_options(cli: &Cli, output_dir: &Path) -> FilenameOptions {
+  // The limit covers the absolute path, including the separator before the
+  // page's relative path and its extension
+  let extension_len = build_write_options(cli).file_extension().chars().count() + 1;
+  let max_path = cli
+    .output
+    .max_path_length
+    .or_else(paths::default_max_path)
+    .map(|max_path| {
+      let root = std::path::absolute(output_dir).unwrap_or_else(|_| output_dir.to_path_buf());
+      max_path.saturating_sub(root.to_string_lossy().chars().count() + 1 + extension_len)
+    });
+  FilenameOptions {
+    style: cli.output.filename_style,
+    ascii: cli.output.ascii_filenames,
+    max_path,
+  }
+}
+
+/// Load the rules given with `--front-matter-rules`, if any.
+///
+/// # Errors
+/// Returns an error when the rules file cannot be read or parsed.
+pub(crate) fn load_front_matter_rules(cli: &Cli) -> anyhow::Result<Option<FrontMatterRules>> {
+  cli
+    .output
+    .front_matter_rules
+    .as_deref()
+    .map(|path| FrontMatterRules::load(Path::new(path)))
+    .transpose()
+}
+
+/// Load the template given with `--template`, if any.
+///
+/// # Errors
+/// Returns an error when the template cannot be read or parsed.
+pub(crate) fn load_template(cli: &Cli) -> anyhow::Result<Option<PageTemplate>> {
+  cli
+    .output
+    .template
+    .as_deref()
+    .map(|path| PageTemplate::load(Path::new(path)))
+    .transpose()
+}
+
+/// Create the downloader for images embedded from other sites, when
+/// `--download-external-images` is set.
+pub(crate) fn build_external_image_fetcher(cli: &Cli) -> anyhow::Result<Option<ExternalImageFetcher>> {
+  let options = &cli.images_links;
+  (options.download_images && options.download_external_images)
+    .then(|| {
+      ExternalImageFetcher::new(
+        Duration::from_secs(options.external_image_timeout),
+        options.external_image_max_size,
+      )
+    })
+    .transpose()
+}
+
+/// Build the options controlling how pages are written to disk.
+pub(crate) fn build_write_options(cli: &Cli) -> WriteOptions {
+  WriteOptions {
+    format: cli.output.format,
+    extension: cli.output.extension.clone(),
+    overwrite: cli.output.overwrite,
+    conflict: cli.output.conflict,
   }
 }
 
 /// Build the Markdown conversion options from the CLI settings.
 ///
-/// Currently propagates anchor preservation and compact table rendering flags.
-fn build_markdown_options(cli: &Cli) -> MarkdownOptions {
+/// Propagates anchor preservation, compact table rendering, table caption
+/// placement, MDX escaping, the Markdown dialect, the lint autofix pass, and
+/// the link resolution context. The Docusaurus profile additionally enables MDX escaping and
+/// `:::note` admonitions; otherwise the Markdown flavor picks the admonition syntax. An explicit
+/// `--admonition-style` overrides both.
+pub(crate) fn build_markdown_options(cli: &Cli, links: &LinkContext) -> MarkdownOptions {
+  let docusaurus = cli.output.profile == Some(ExportProfile::Docusaurus);
   MarkdownOptions {
     preserve_anchors: cli.images_links.preserve_anchors,
     compact_tables: cli.output.compact_tables,
+    table_caption_position: cli.output.table_captions,
+    table_spans: cli.output.table_spans,
+    links: links.clone(),
+    mdx_safe: cli.output.mdx_safe || docusaurus,
+    admonition_style: match (cli.output.admonition_style, docusaurus, cli.output.markdown_flavor) {
+      (Some(style), _, _) => style,
+      (None, true, _) => AdmonitionStyle::Docusaurus,
+      (None, false, Some(MarkdownFlavor::Gfm)) => AdmonitionStyle::GitHub,
+      (None, false, Some(MarkdownFlavor::Obsidian)) => AdmonitionStyle::Obsidian,
+      (None, false, Some(MarkdownFlavor::Pandoc)) => AdmonitionStyle::PandocDiv,
+      (None, false, Some(MarkdownFlavor::CommonMark) | None) => AdmonitionStyle::Blockquote,
+    },
+    emoji_style: cli.output.emoji_style,
+    image_style: cli.images_links.image_style,
+    dialect: MarkdownDialect {
+      heading_style: cli.output.heading_style,
+      bullet: cli.output.bullet_marker,
+      emphasis: cli.output.emphasis_marker,
+      strong: cli.output.strong_marker,
+      fence: cli.output.code_fence,
+    },
+    lint_fix: cli.output.lint_fix,
+    max_heading_level: cli.output.max_heading_level,
+    heading_overflow: cli.output.heading_overflow,
+    jira_links: cli
+      .images_links
+      .jira_url
+      .as_deref()
+      .map(|url| JiraLinker::new(url, cli.images_links.jira_projects.clone())),
+    annotate_macros: cli.output.annotate_macros,
+    flavor: cli.output.markdown_flavor,
+    includes: None,
+    generate_toc: cli.output.generate_toc,
+    redacted_macros: cli.output.redact_macro.clone(),
+    table_split: (cli.output.split_table_rows.is_some() || cli.output.split_table_columns.is_some()).then(|| {
+      TableSplit {
+        max_rows: cli.output.split_table_rows,
+        max_columns: cli.output.split_table_columns,
+        ..Default::default()
+      }
+    }),
   }
 }
 
-/// Build the AsciiDoc conversion options from the CLI settings.
+/// Build the link resolution context from the CLI settings.
+///
+/// The Confluence instance is taken from the page URL when one was supplied,
+/// falling back to `--url` for numeric page IDs.
+///
+/// # Arguments
+/// * `cli` - Parsed CLI options.
+/// * `scope` - Pages written by this export, used to detect links to pages that are not exported.
+pub(crate) fn build_link_context(cli: &Cli, scope: ExportScope) -> LinkContext {
+  let instance_url = cli
+    .page_input
+    .as_deref()
+    .filter(|input| input.contains("://"))
+    .and_then(|input| confluence::parse_confluence_url(input).ok())
+    .map(|info| info.base_url)
+    .or_else(|| cli.auth.url.clone());
+
+  LinkContext {
+    instance_url,
+    scope: Some(Arc::new(scope)),
+    unexported_links: cli.images_links.unexported_links,
+    ..Default::default()
+  }
+}
+
+/// Build the tree traversal filter from the CLI settings.
+fn build_page_filter(cli: &Cli) -> confluence::PageFilter {
+  confluence::PageFilter {
+    include_labels: cli.page.include_label.clone(),
+    exclude_labels: cli.page.exclude_label.clone(),
+    include_titles: cli.page.include_title.clone(),
+    exclude_titles: cli.page.exclude_title.clone(),
+    exclude_ids: cli.page.exclude_id.clone(),
+    authors: cli.page.author.clone(),
+    modified_since: cli.page.modified_since,
+    modified_until: cli.page.modified_until,
+  }
+}
+
+/// Build the export scope for page trees, recording where each page is written.
+///
+/// Paths mirror [`download_page_tree`]: each page is written as
+/// `<title>.<extension>` and its children go in a directory named after it.
 ///
-/// Currently propagates anchor preservation and compact table rendering flags.
-fn build_asciidoc_options(cli: &Cli) -> AsciiDocOptions {
+/// # Arguments
+/// * `trees` - Roots of the exported trees.
+/// * `dirs` - Directory each tree is written to, relative to the output directory; trees without one are written
+///   directly into it.
+/// * `extension` - File extension used for page files.
+pub(crate) fn build_export_scope(
+  trees: &[confluence::PageTree],
+  dirs: &[PathBuf],
+  extension: &str,
+  filenames: &TreeFilenames,
+) -> ExportScope {
+  fn visit(
+    scope: &mut ExportScope,
+    tree: &confluence::PageTree,
+    dir: &str,
+    extension: &str,
+    filenames: &TreeFilenames,
+  ) {
+    let filename = filenames.stem(&tree.page);
+    if tree.exported {
+      scope.insert_with_path(&tree.page, format!("{dir}{filename}.{extension}"));
+    }
+
+    let child_dir = format!("{dir}{filename}/");
+    for child in &tree.children {
+      visit(scope, child, &child_dir, extension, filenames);
+    }
+  }
+
+  let mut scope = ExportScope::default();
+  for (index, tree) in trees.iter().enumerate() {
+    let dir: String = dirs
+      .get(index)
+      .into_iter()
+      .flat_map(|dir| dir.iter())
+      .map(|component| format!("{}/", component.to_string_lossy()))
+      .collect();
+    visit(&mut scope, tree, &dir, extension, filenames);
+  }
+  scope
+}
+
+/// Build the AsciiDoc conversion options from the CLI settings.
+pub(crate) fn build_asciidoc_options(cli: &Cli) -> AsciiDocOptions {
   AsciiDocOptions {
     preserve_anchors: cli.images_links.preserve_anchors,
     compact_tables: cli.output.compact_tables,
+    max_heading_level: cli.output.max_heading_level,
+    heading_overflow: cli.output.heading_overflow,
+    annotate_macros: cli.output.annotate_macros,
   }
 }
 
-/// Count the number of pages represented inside a [`confluence::PageTree`].
+/// Count the exported pages inside a [`confluence::PageTree`].
 fn count_pages_in_tree(tree: &confluence::PageTree) -> usize {
-  1 + tree.children.iter().map(count_pages_in_tree).sum::<usize>()
+  usize::from(tree.exported) + tree.children.iter().map(count_pages_in_tree).sum::<usize>()
 }
 
 #[cfg(test)]
@@ -383,8 +1977,9 @@ mod tests {
   use std::sync::Arc;
   use std::time::Duration;
 
-  use anyhow::{Result, bail};
+  use anyhow::anyhow;
   use async_trait::async_trait;
+  use clap::Parser;
   use tempfile::tempdir;
   use tokio::sync::Mutex;
   use tokio::time::sleep;
@@ -393,12 +1988,19 @@ mod tests {
   use crate::cli::{
     AuthOptions, BehaviorOptions, Cli, ColorOption, ImagesLinksOptions, OutputOptions, PageOptions, PerformanceOptions,
   };
-  use crate::color::ColorScheme;
+  use crate::color::{ColorScheme, Theme};
   use crate::confluence::{
-    Attachment, AttachmentLinks, ConfluenceApi, Page, PageBody, PageTree, StorageFormat, UserInfo,
+    Attachment, AttachmentLinks, ConfluenceApi, ContentState, Page, PageBody, PageSpace, PageTree, Space,
+    StorageFormat, UserInfo,
   };
+  use crate::credentials::CredentialSource;
+  use crate::links::PageReference;
+  use crate::notify::NotifyFormat;
+  use crate::processed_page::FilenameStyle;
+  use crate::test_support;
 
   struct CountingClient {
+    pages: HashMap<String, Page>,
     attachments: HashMap<String, Vec<Attachment>>,
     counter: Arc<Mutex<usize>>,
     max_counter: Arc<Mutex<usize>>,
@@ -408,6 +2010,7 @@ mod tests {
   impl CountingClient {
     fn new(counter: Arc<Mutex<usize>>, max_counter: Arc<Mutex<usize>>, delay: Duration) -> Self {
       Self {
+        pages: HashMap::new(),
         attachments: HashMap::new(),
         counter,
         max_counter,
@@ -422,19 +2025,31 @@ mod tests {
 
   #[async_trait]
   impl ConfluenceApi for CountingClient {
-    async fn get_page(&self, page_id: &str) -> Result<Page> {
-      bail!("get_page unexpectedly called for {}", page_id);
+    async fn get_page(&self, page_id: &str) -> crate::Result<Page> {
+      self
+        .pages
+        .get(page_id)
+        .cloned()
+        .ok_or_else(|| anyhow!("get_page unexpectedly called for {}", page_id).into())
     }
 
-    async fn get_child_pages(&self, _page_id: &str) -> Result<Vec<Page>> {
+    async fn get_page_version(&self, page_id: &str, _version: u32) -> crate::Result<Page> {
+      Err(anyhow!("get_page_version unexpectedly called for {}", page_id).into())
+    }
+
+    async fn get_child_pages(&self, _page_id: &str) -> crate::Result<Vec<Page>> {
       Ok(Vec::new())
     }
 
-    async fn get_attachments(&self, page_id: &str) -> Result<Vec<Attachment>> {
+    async fn get_content_state(&self, _page_id: &str) -> crate::Result<Option<ContentState>> {
+      Ok(None)
+    }
+
+    async fn get_attachments(&self, page_id: &str) -> crate::Result<Vec<Attachment>> {
       Ok(self.attachments.get(page_id).cloned().unwrap_or_default())
     }
 
-    async fn download_attachment(&self, _url: &str, output_path: &std::path::Path) -> Result<()> {
+    async fn download_attachment(&self, _url: &str, output_path: &std::path::Path) -> crate::Result<()> {
       let bytes = self.fetch_attachment(_url).await?;
 
       if let Some(parent) = output_path.parent() {
@@ -445,7 +2060,10 @@ mod tests {
       Ok(())
     }
 
-    async fn fetch_attachment(&self, _url: &str) -> Result<Vec<u8>> {
+    async fn fetch_attachment(&self, url: &str) -> crate::Result<Vec<u8>> {
+      if url.ends_with("/broken") {
+        return Err(crate::Error::from_status(500, format!("HTTP 500 for {url}")));
+      }
       let current = {
         let mut guard = self.counter.lock().await;
         *guard += 1;
@@ -463,7 +2081,7 @@ mod tests {
 
       let result = async {
         sleep(self.delay).await;
-        Result::<Vec<u8>>::Ok(b"test-data".to_vec())
+        crate::Result::<Vec<u8>>::Ok(b"test-data".to_vec())
       }
       .await;
 
@@ -475,26 +2093,40 @@ mod tests {
       result
     }
 
-    async fn test_auth(&self) -> Result<UserInfo> {
-      bail!("test_auth unexpectedly called");
+    async fn get_user(&self, _account_id: &str) -> crate::Result<UserInfo> {
+      Err(anyhow!("get_user unexpectedly called").into())
+    }
+
+    async fn get_space(&self, _space_key: &str) -> crate::Result<Space> {
+      Err(anyhow!("get_space unexpectedly called").into())
+    }
+
+    async fn find_page(&self, space_key: &str, title: &str) -> crate::Result<Option<Page>> {
+      Ok(
+        self
+          .pages
+          .values()
+          .find(|page| page.title == title && page.space.as_ref().is_some_and(|space| space.key == space_key))
+          .cloned(),
+      )
+    }
+
+    async fn test_auth(&self) -> crate::Result<UserInfo> {
+      Err(anyhow!("test_auth unexpectedly called").into())
     }
   }
 
   fn make_page(id: &str, title: &str) -> Page {
     Page {
-      id: id.to_string(),
-      title: title.to_string(),
-      page_type: "page".to_string(),
-      status: "current".to_string(),
       body: Some(PageBody {
         storage: Some(StorageFormat {
           value: "<p>Example</p>".to_string(),
           representation: "storage".to_string(),
         }),
         view: None,
+        wiki: None,
       }),
-      space: None,
-      links: None,
+      ..test_support::page(id, title)
     }
   }
 
@@ -512,22 +2144,217 @@ mod tests {
   }
 
   fn build_tree() -> PageTree {
-    let children: Vec<PageTree> = (0..4)
-      .map(|idx| {
-        let page_id = format!("child-{idx}");
-        PageTree {
-          page: make_page(&page_id, &format!("Child {}", idx)),
-          children: Vec::new(),
-          depth: 1,
-        }
-      })
+    let children = (0..4)
+      .map(|idx| test_support::tree(make_page(&format!("child-{idx}"), &format!("Child {idx}")), Vec::new()))
       .collect();
+    test_support::tree(make_page("root", "Root Page"), children)
+  }
 
-    PageTree {
-      page: make_page("root", "Root Page"),
-      children,
+  #[test]
+  fn build_export_scope_mirrors_output_layout() {
+    let mut tree = build_tree();
+    tree.children[1].children.push(PageTree {
+      page: make_page("grandchild", "Deep: Page"),
+      children: Vec::new(),
+      depth: 2,
+      exported: true,
+    });
+
+    let scope = build_export_scope(
+      std::slice::from_ref(&tree),
+      &[],
+      "md",
+      &TreeFilenames::plan(&tree, FilenameOptions::default()),
+    );
+    assert_eq!(scope.path_of("root"), Some("Root Page.md"));
+    assert_eq!(scope.path_of("child-0"), Some("Root Page/Child 0.md"));
+    assert_eq!(scope.path_of("grandchild"), Some("Root Page/Child 1/Deep_ Page.md"));
+  }
+
+  #[tokio::test]
+  async fn linked_pages_in_other_spaces_get_their_own_layout_directory() {
+    let in_space = |id: &str, title: &str, space: &str| Page {
+      space: Some(PageSpace {
+        key: space.to_string(),
+        name: space.to_string(),
+        space_type: "global".to_string(),
+      }),
+      ..make_page(id, title)
+    };
+    let mut home = in_space("home", "Home", "DOCS");
+    home.body.as_mut().unwrap().storage.as_mut().unwrap().value =
+      r#"<p><ac:link><ri:page ri:space-key="OPS" ri:content-title="Runbook" /></ac:link></p>"#.to_string();
+    let mut client = CountingClient::new(Arc::new(Mutex::new(0)), Arc::new(Mutex::new(0)), Duration::ZERO);
+    client
+      .pages
+      .insert("runbook".to_string(), in_space("runbook", "Runbook", "OPS"));
+    let cli = Cli::parse_from(["confluence-dl", "--follow-links", "--layout", "space"]);
+
+    let mut forest = vec![PageTree {
+      page: home,
+      children: Vec::new(),
       depth: 0,
+      exported: true,
+    }];
+    forest.extend(link_crawl::crawl_linked_pages(&client, &forest, 1, 1).await);
+    let dirs = forest_dirs(&cli, &forest);
+    assert_eq!(dirs, [PathBuf::from("DOCS"), PathBuf::from("OPS")]);
+
+    let filenames = TreeFilenames::plan_forest(&forest, FilenameOptions::default());
+    let scope = build_export_scope(&forest, &dirs, "md", &filenames);
+    assert_eq!(scope.path_of("home"), Some("DOCS/Home.md"));
+    assert_eq!(scope.path_of("runbook"), Some("OPS/Runbook.md"));
+
+    let links = LinkContext {
+      current_space: Some("DOCS".to_string()),
+      current_page: Some("home".to_string()),
+      scope: Some(Arc::new(scope)),
+      ..Default::default()
+    };
+    let reference = PageReference {
+      title: Some("Runbook".to_string()),
+      space_key: Some("OPS".to_string()),
+      ..Default::default()
+    };
+    assert_eq!(links.relative_path(&reference).as_deref(), Some("../OPS/Runbook.md"));
+  }
+
+  #[tokio::test]
+  async fn confirm_removed_pages_keeps_pages_still_in_the_tree() {
+    let mut client = CountingClient::new(Arc::new(Mutex::new(0)), Arc::new(Mutex::new(0)), Duration::ZERO);
+    let ancestor = |id: &str| confluence::Ancestor {
+      id: id.to_string(),
+      title: id.to_string(),
+    };
+    let mut skipped = make_page("skipped", "Skipped");
+    skipped.ancestors = vec![ancestor("root"), ancestor("parent")];
+    let mut moved = make_page("moved", "Moved");
+    moved.ancestors = vec![ancestor("elsewhere")];
+    let mut trashed = make_page("trashed", "Trashed");
+    trashed.ancestors = vec![ancestor("root")];
+    trashed.status = "trashed".to_string();
+    for page in [skipped, moved, trashed] {
+      client.pages.insert(page.id.clone(), page);
     }
+
+    // Pages that cannot be looked up are kept as well
+    let ids = ["skipped", "moved", "trashed", "unreachable"].map(String::from);
+    let removed = confirm_removed_pages(&client, "root", &ids, 2).await;
+    assert_eq!(removed, BTreeSet::from(["moved".to_string(), "trashed".to_string()]));
+  }
+
+  #[test]
+  fn build_export_scope_skips_unexported_pages() {
+    let mut tree = build_tree();
+    tree.children[1].exported = false;
+    tree.children[1].children.push(PageTree {
+      page: make_page("grandchild", "Kept"),
+      children: Vec::new(),
+      depth: 2,
+      exported: true,
+    });
+
+    let filenames = TreeFilenames::plan(&tree, FilenameOptions::default());
+    let scope = build_export_scope(std::slice::from_ref(&tree), &[], "md", &filenames);
+
+    // The filtered-out page is not linked to, but still names the directory
+    // of its kept child
+    assert_eq!(scope.path_of("child-1"), None);
+    assert_eq!(scope.path_of("grandchild"), Some("Root Page/Child 1/Kept.md"));
+    assert_eq!(count_pages_in_tree(&tree), 5);
+  }
+
+  #[test]
+  fn duplicate_sibling_titles_get_page_id_suffixes() {
+    let mut tree = build_tree();
+    tree.children[1].page = make_page("300", "Notes");
+    tree.children[2].page = make_page("200", "notes");
+    tree.children[3].page = make_page("100", "NOTES");
+    tree.children[1].children.push(PageTree {
+      page: make_page("grandchild", "Child 0"),
+      children: Vec::new(),
+      depth: 2,
+      exported: true,
+    });
+
+    let filenames = TreeFilenames::plan(&tree, FilenameOptions::default());
+    let scope = build_export_scope(std::slice::from_ref(&tree), &[], "md", &filenames);
+
+    // The lowest page ID keeps the plain name; titles are compared case-insensitively
+    assert_eq!(scope.path_of("100"), Some("Root Page/NOTES.md"));
+    assert_eq!(scope.path_of("200"), Some("Root Page/notes (200).md"));
+    assert_eq!(scope.path_of("300"), Some("Root Page/Notes (300).md"));
+    assert_eq!(scope.path_of("grandchild"), Some("Root Page/Notes (300)/Child 0.md"));
+    assert_eq!(scope.path_of("child-0"), Some("Root Page/Child 0.md"));
+
+    let renamed: Vec<_> = filenames.renamed().iter().map(|page| page.filename.as_str()).collect();
+    assert_eq!(renamed, ["Notes (300)", "notes (200)"]);
+  }
+
+  fn styled(style: FilenameStyle) -> FilenameOptions {
+    FilenameOptions {
+      style,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn filename_styles_name_pages_and_disambiguate_siblings() {
+    let mut tree = build_tree();
+    tree.children[1].page = make_page("300", "Release Notes!");
+    tree.children[2].page = make_page("200", "release notes");
+
+    let slugs = build_export_scope(
+      std::slice::from_ref(&tree),
+      &[],
+      "md",
+      &TreeFilenames::plan(&tree, styled(FilenameStyle::Slug)),
+    );
+    assert_eq!(slugs.path_of("200"), Some("root-page/release-notes.md"));
+    assert_eq!(slugs.path_of("300"), Some("root-page/release-notes-300.md"));
+
+    let ids = build_export_scope(
+      std::slice::from_ref(&tree),
+      &[],
+      "md",
+      &TreeFilenames::plan(&tree, styled(FilenameStyle::Id)),
+    );
+    assert_eq!(ids.path_of("300"), Some("root/300.md"));
+
+    let id_titles = build_export_scope(
+      std::slice::from_ref(&tree),
+      &[],
+      "md",
+      &TreeFilenames::plan(&tree, styled(FilenameStyle::IdTitle)),
+    );
+    assert_eq!(id_titles.path_of("200"), Some("root-Root Page/200-release notes.md"));
+  }
+
+  #[test]
+  fn long_paths_are_shortened_with_page_id_suffixes() {
+    let mut tree = build_tree();
+    tree.children[0].page = make_page("100", "Überblick über die Architektur");
+    tree.children[0].children.push(PageTree {
+      page: make_page("200", "Deployment"),
+      children: Vec::new(),
+      depth: 2,
+      exported: true,
+    });
+    let options = FilenameOptions {
+      ascii: true,
+      max_path: Some(30),
+      ..Default::default()
+    };
+
+    let filenames = TreeFilenames::plan(&tree, options);
+    let scope = build_export_scope(std::slice::from_ref(&tree), &[], "md", &filenames);
+
+    // "Root Page/" leaves 20 characters for the child, 14 of them for its title
+    assert_eq!(scope.path_of("100"), Some("Root Page/Uberblick uber (100).md"));
+    assert_eq!(scope.path_of("200"), Some("Root Page/Uberblick uber (100)/200.md"));
+    assert_eq!(scope.path_of("child-1"), Some("Root Page/Child 1.md"));
+    let shortened: Vec<_> = filenames.shortened().iter().map(|page| page.page_id.as_str()).collect();
+    assert_eq!(shortened, ["100", "200"]);
   }
 
   #[tokio::test]
@@ -547,6 +2374,7 @@ mod tests {
       page: make_page("root", "Root Page"),
       children: Vec::new(),
       depth: 0,
+      exported: true,
     };
 
     let colors = ColorScheme::new(ColorOption::Never);
@@ -557,6 +2385,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: output_dir.to_string_lossy().to_string(),
@@ -564,32 +2393,65 @@ mod tests {
         save_raw: true,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: true,
         color: ColorOption::Never,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: true,
         max_depth: None,
         attachments: false,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: false,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 2,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
-    let semaphore = Arc::new(Semaphore::new(cli.performance.resolved_parallel()));
-    download_page_tree(&client, &tree, output_dir, &cli, &colors, semaphore)
+    let links = LinkContext::default();
+    let progress = ExportProgress::new(1, false, &colors);
+    let download = TreeDownload {
+      client: &client,
+      cli: &cli,
+      colors: &colors,
+      links: &links,
+      link_titles: None,
+      users: None,
+      includes: None,
+      front_matter_rules: None,
+      template: None,
+      external_images: None,
+      include_graph: None,
+      changelog: None,
+      tasks: None,
+      failure_report: None,
+      manifest: None,
+      shared_assets: None,
+      assets: &AssetRegistry::default(),
+      hooks: &NoHooks,
+      filenames: &TreeFilenames::plan(&tree, FilenameOptions::default()),
+      semaphore: Arc::new(Semaphore::new(cli.performance.resolved_parallel())),
+      progress: &progress,
+    };
+    download_page_tree(&download, &tree, output_dir, 1)
       .await
       .expect("download should succeed");
 
@@ -630,6 +2492,7 @@ mod tests {
         url: None,
         user: None,
         token: None,
+        credential_source: CredentialSource::Netrc,
       },
       output: OutputOptions {
         output: output_path.to_string_lossy().to_string(),
@@ -637,33 +2500,66 @@ mod tests {
         save_raw: false,
         compact_tables: false,
         format: OutputFormat::Markdown,
+        ..Default::default()
       },
       behavior: BehaviorOptions {
         dry_run: false,
         verbose: 0,
         quiet: true,
         color: ColorOption::Never,
+        theme: Theme::Dark,
+        log_format: LogFormat::Text,
+        notify_webhook: None,
+        keep_going: false,
+        notify_format: NotifyFormat::Json,
       },
       page: PageOptions {
         children: true,
         max_depth: None,
         attachments: true,
+        ..Default::default()
       },
       images_links: ImagesLinksOptions {
         download_images: false,
         images_dir: "images".to_string(),
         preserve_anchors: false,
+        ..Default::default()
       },
       performance: PerformanceOptions {
         parallel: 2,
         rate_limit: 10,
         timeout: 30,
+        ..Default::default()
       },
     };
 
     let limit = cli.performance.resolved_parallel();
-    let semaphore = Arc::new(Semaphore::new(limit));
-    download_page_tree(&client, &tree, output_path, &cli, &colors, semaphore)
+    let links = LinkContext::default();
+    let progress = ExportProgress::new(1, false, &colors);
+    let download = TreeDownload {
+      client: &client,
+      cli: &cli,
+      colors: &colors,
+      links: &links,
+      link_titles: None,
+      users: None,
+      includes: None,
+      front_matter_rules: None,
+      template: None,
+      external_images: None,
+      include_graph: None,
+      changelog: None,
+      tasks: None,
+      failure_report: None,
+      manifest: None,
+      shared_assets: None,
+      assets: &AssetRegistry::default(),
+      hooks: &NoHooks,
+      filenames: &TreeFilenames::plan(&tree, FilenameOptions::default()),
+      semaphore: Arc::new(Semaphore::new(limit)),
+      progress: &progress,
+    };
+    download_page_tree(&download, &tree, output_path, 1)
       .await
       .expect("download should succeed");
 
@@ -683,4 +2579,190 @@ mod tests {
       assert!(file.exists(), "expected output file {} to exist", file.display());
     }
   }
+
+  /// Hooks that skip one page, tag converted pages, and record each call.
+  #[derive(Default)]
+  struct RecordingHooks {
+    calls: std::sync::Mutex<Vec<String>>,
+  }
+
+  impl RecordingHooks {
+    fn record(&self, call: String) {
+      self.calls.lock().unwrap().push(call);
+    }
+  }
+
+  impl ExportHooks for RecordingHooks {
+    fn on_page_start(&self, start: &PageStart<'_>) -> PageAction {
+      self.record(format!("start {} at depth {}", start.page.title, start.depth));
+      if start.page.title == "Child 1" {
+        PageAction::Skip
+      } else {
+        PageAction::Export
+      }
+    }
+
+    fn on_page_converted(&self, converted: &mut PageConverted<'_>) {
+      converted.processed.content.push_str("\nTagged by hooks\n");
+    }
+
+    fn on_asset_downloaded(&self, asset: &hooks::AssetDownloaded<'_>) {
+      self.record(format!("{:?} for {}", asset.kind, asset.page.title));
+    }
+
+    fn on_page_written(&self, written: &hooks::PageWritten<'_>) {
+      self.record(format!("wrote {}", written.path.file_name().unwrap().to_string_lossy()));
+    }
+  }
+
+  #[tokio::test]
+  async fn download_page_tree_calls_hooks() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path();
+    let mut client = CountingClient::new(Arc::new(Mutex::new(0)), Arc::new(Mutex::new(0)), Duration::ZERO);
+    client.set_attachments("root", vec![make_attachment("root")]);
+    let tree = build_tree();
+    let colors = ColorScheme::new(ColorOption::Never);
+    let cli = Cli::parse_from([
+      "confluence-dl",
+      "--output",
+      &output_path.to_string_lossy(),
+      "--children",
+      "--attachments",
+      "--parallel",
+      "1",
+      "--quiet",
+    ]);
+
+    let hooks = RecordingHooks::default();
+    let links = LinkContext::default();
+    let progress = ExportProgress::new(5, false, &colors);
+    let download = TreeDownload {
+      client: &client,
+      cli: &cli,
+      colors: &colors,
+      links: &links,
+      link_titles: None,
+      users: None,
+      includes: None,
+      front_matter_rules: None,
+      template: None,
+      external_images: None,
+      include_graph: None,
+      changelog: None,
+      tasks: None,
+      failure_report: None,
+      manifest: None,
+      shared_assets: None,
+      assets: &AssetRegistry::default(),
+      hooks: &hooks,
+      filenames: &TreeFilenames::plan(&tree, FilenameOptions::default()),
+      semaphore: Arc::new(Semaphore::new(1)),
+      progress: &progress,
+    };
+    download_page_tree(&download, &tree, output_path, 1)
+      .await
+      .expect("download should succeed");
+
+    let mut calls = hooks.calls.into_inner().unwrap();
+    calls.sort();
+    insta::assert_snapshot!(calls.join("\n"), @r"
+    Attachment for Root Page
+    start Child 0 at depth 1
+    start Child 1 at depth 1
+    start Child 2 at depth 1
+    start Child 3 at depth 1
+    start Root Page at depth 0
+    wrote Child 0.md
+    wrote Child 2.md
+    wrote Child 3.md
+    wrote Root Page.md
+    ");
+    assert!(!output_path.join("Root Page").join("Child 1.md").exists());
+    let content = fs::read_to_string(output_path.join("Root Page.md")).unwrap();
+    assert!(content.ends_with("Tagged by hooks\n"));
+    assert_eq!(progress.finish().pages, 4);
+
+    // Attachments are streamed to a staging file and moved into place
+    let attachments = output_path.join("attachments");
+    assert_eq!(fs::read(attachments.join("root.dat")).unwrap(), b"test-data");
+    assert!(!attachments.join("root.dat.download").exists());
+  }
+
+  #[tokio::test]
+  async fn download_page_tree_keeps_going_after_failures() {
+    let temp_dir = tempdir().unwrap();
+    let output_path = temp_dir.path();
+    let mut client = CountingClient::new(Arc::new(Mutex::new(0)), Arc::new(Mutex::new(0)), Duration::ZERO);
+    let mut broken = make_attachment("child-1");
+    broken.links = Some(AttachmentLinks {
+      download: Some("https://example.com/broken".to_string()),
+    });
+    client.set_attachments("child-1", vec![broken]);
+    let mut tree = build_tree();
+    tree.children[1].children.push(PageTree {
+      page: make_page("grandchild", "Grandchild"),
+      children: Vec::new(),
+      depth: 2,
+      exported: true,
+    });
+    let colors = ColorScheme::new(ColorOption::Never);
+    let cli = Cli::parse_from([
+      "confluence-dl",
+      "--output",
+      &output_path.to_string_lossy(),
+      "--children",
+      "--attachments",
+      "--keep-going",
+      "--quiet",
+    ]);
+
+    let links = LinkContext::default();
+    let progress = ExportProgress::new(6, false, &colors);
+    let report = FailureReport::default();
+    let download = TreeDownload {
+      client: &client,
+      cli: &cli,
+      colors: &colors,
+      links: &links,
+      link_titles: None,
+      users: None,
+      includes: None,
+      front_matter_rules: None,
+      template: None,
+      external_images: None,
+      include_graph: None,
+      changelog: None,
+      tasks: None,
+      failure_report: Some(&report),
+      manifest: None,
+      shared_assets: None,
+      assets: &AssetRegistry::default(),
+      hooks: &NoHooks,
+      filenames: &TreeFilenames::plan(&tree, FilenameOptions::default()),
+      semaphore: Arc::new(Semaphore::new(4)),
+      progress: &progress,
+    };
+    download_page_tree(&download, &tree, output_path, 1)
+      .await
+      .expect("failures are recorded instead of returned");
+
+    // The children of the failed page are still exported
+    assert!(!output_path.join("Root Page").join("Child 1.md").exists());
+    assert!(
+      output_path
+        .join("Root Page")
+        .join("Child 1")
+        .join("Grandchild.md")
+        .exists()
+    );
+    assert_eq!(progress.finish().pages, 5);
+
+    let path = report.write(output_path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let failures = json["failures"].as_array().unwrap();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0]["page_id"], "child-1");
+    assert!(failures[0]["error"].as_str().unwrap().contains("HTTP 500"));
+  }
 }
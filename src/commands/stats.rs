@@ -0,0 +1,168 @@
+//! `stats` subcommand for summarizing a Confluence page tree.
+//!
+//! This module powers `confluence-dl stats`, which walks the page tree below a
+//! target page and reports how many pages it contains and which blueprints
+//! they were created from, so documentation teams can find boilerplate pages
+//! worth cleaning up without exporting anything.
+
+use std::process;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::blueprints::{BlueprintUsage, blueprint_usage};
+use crate::cli::Cli;
+use crate::color::ColorScheme;
+use crate::commands::auth::create_client;
+use crate::commands::ls::resolve_url_info;
+use crate::confluence::{self, PageTree};
+
+/// Machine-readable report printed with `--json`.
+#[derive(Debug, Serialize)]
+struct StatsReport {
+  root_id: String,
+  root_title: String,
+  total_pages: usize,
+  blueprints: Vec<BlueprintUsage>,
+}
+
+/// Execute the `stats` subcommand.
+///
+/// # Arguments
+/// * `target` - Page URL or numeric page ID supplied on the CLI.
+/// * `max_depth` - Optional traversal depth limit (0 covers only the root).
+/// * `json` - Print the report as JSON instead of colored text.
+/// * `cli` - Top-level CLI options for auth and networking.
+/// * `colors` - Shared color palette used to render terminal output.
+pub async fn handle_stats_command(target: &str, max_depth: Option<usize>, json: bool, cli: &Cli, colors: &ColorScheme) {
+  if let Err(error) = run_stats_command(target, max_depth, json, cli, colors).await {
+    eprintln!(
+      "{} {}",
+      colors.error("✗"),
+      colors.error("Failed to collect page tree stats")
+    );
+    eprintln!("  {}: {}", colors.emphasis("Error"), error);
+    process::exit(1);
+  }
+}
+
+async fn run_stats_command(
+  target: &str,
+  max_depth: Option<usize>,
+  json: bool,
+  cli: &Cli,
+  colors: &ColorScheme,
+) -> Result<()> {
+  let url_info = resolve_url_info(target.trim(), cli).context("Could not determine page identifier")?;
+  let client = create_client(&url_info.base_url, cli).context("Unable to construct Confluence API client")?;
+
+  let tree = confluence::get_page_tree(
+    &client,
+    &url_info.page_id,
+    max_depth,
+    &confluence::PageFilter::default(),
+    cli.performance.resolved_parallel(),
+  )
+  .await?;
+
+  let report = StatsReport {
+    root_id: tree.page.id.clone(),
+    root_title: tree.page.title.clone(),
+    total_pages: count_pages(&tree),
+    blueprints: blueprint_usage(&tree),
+  };
+
+  if json {
+    println!("{}", serde_json::to_string_pretty(&report)?);
+  } else {
+    for line in format_report_lines(&report, colors) {
+      println!("{line}");
+    }
+  }
+  Ok(())
+}
+
+fn count_pages(tree: &PageTree) -> usize {
+  1 + tree.children.iter().map(count_pages).sum::<usize>()
+}
+
+fn format_report_lines(report: &StatsReport, colors: &ColorScheme) -> Vec<String> {
+  let mut lines = vec![format!(
+    "{} {} {} under {}",
+    colors.success("✓"),
+    colors.number(report.total_pages),
+    if report.total_pages == 1 { "page" } else { "pages" },
+    colors.emphasis(&report.root_title)
+  )];
+
+  lines.push(String::new());
+  lines.push(colors.emphasis("Blueprints").to_string());
+  if report.blueprints.is_empty() {
+    lines.push(format!("  {}", colors.dimmed("No pages created from known blueprints")));
+  }
+  for usage in &report.blueprints {
+    lines.push(format!(
+      "  {}: {}",
+      colors.emphasis(&usage.blueprint),
+      colors.number(usage.pages.len())
+    ));
+    for page in &usage.pages {
+      let creator = page
+        .created_by
+        .as_deref()
+        .map(|name| format!(" by {name}"))
+        .unwrap_or_default();
+      lines.push(format!(
+        "    {} [id {}]{}",
+        page.title,
+        colors.number(&page.id),
+        colors.dimmed(creator)
+      ));
+    }
+  }
+  lines
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::blueprints::BlueprintPage;
+  use crate::cli::ColorOption;
+
+  #[test]
+  fn test_format_report_lines() {
+    let colors = ColorScheme::new(ColorOption::Never);
+    let report = StatsReport {
+      root_id: "1".to_string(),
+      root_title: "Team Space".to_string(),
+      total_pages: 12,
+      blueprints: vec![BlueprintUsage {
+        blueprint: "Meeting notes".to_string(),
+        pages: vec![
+          BlueprintPage {
+            id: "7".to_string(),
+            title: "2024-03-01 Sync".to_string(),
+            created_by: Some("Ana".to_string()),
+          },
+          BlueprintPage {
+            id: "8".to_string(),
+            title: "2024-03-08 Sync".to_string(),
+            created_by: None,
+          },
+        ],
+      }],
+    };
+
+    assert_eq!(
+      format_report_lines(&report, &colors),
+      [
+        "✓ 12 pages under Team Space",
+        "",
+        "Blueprints",
+        "  Meeting notes: 2",
+        "    2024-03-01 Sync [id 7] by Ana",
+        "    2024-03-08 Sync [id 8]",
+      ]
+    );
+  }
+}
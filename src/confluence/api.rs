@@ -2,13 +2,20 @@
 
 use std::path::Path;
 
-use anyhow::Result;
 use async_trait::async_trait;
 
-use super::models::{Attachment, Page, UserInfo};
+use super::models::{Attachment, ContentState, Page, Space, UserInfo};
+use crate::error::Result;
 
 /// Trait for Confluence API operations (enables testing with fake
 /// implementations).
+///
+/// Requests rejected by Confluence fail with [`Error::Auth`] for 401 and 403
+/// responses and [`Error::Api`] for other error statuses, so callers can tell
+/// a bad token from a missing page.
+///
+/// [`Error::Auth`]: crate::Error::Auth
+/// [`Error::Api`]: crate::Error::Api
 #[async_trait]
 pub trait ConfluenceApi: Send + Sync {
   /// Fetch a page by ID.
@@ -17,9 +24,20 @@ pub trait ConfluenceApi: Send + Sync {
   /// * `page_id` - Unique Confluence identifier for the page to retrieve.
   ///
   /// # Returns
-  /// The full `Page` record including metadata and any expanded fields.
+  /// The full `Page` record including metadata, labels, and any expanded
+  /// fields.
   async fn get_page(&self, page_id: &str) -> Result<Page>;
 
+  /// Fetch a historical version of a page.
+  ///
+  /// # Arguments
+  /// * `page_id` - Unique Confluence identifier for the page to retrieve.
+  /// * `version` - Version number as shown in the page history (starting at 1).
+  ///
+  /// # Returns
+  /// The `Page` record with the storage body as of that version.
+  async fn get_page_version(&self, page_id: &str, version: u32) -> Result<Page>;
+
   /// Get child pages for a given page ID.
   ///
   /// # Arguments
@@ -29,6 +47,16 @@ pub trait ConfluenceApi: Send + Sync {
   /// A vector of `Page` records representing each direct child of the parent.
   async fn get_child_pages(&self, page_id: &str) -> Result<Vec<Page>>;
 
+  /// Fetch the content state shown next to the page title.
+  ///
+  /// # Arguments
+  /// * `page_id` - Identifier of the page whose state should be fetched.
+  ///
+  /// # Returns
+  /// The current content state, or `None` when the page has no state or the
+  /// instance does not support content states.
+  async fn get_content_state(&self, page_id: &str) -> Result<Option<ContentState>>;
+
   /// Get attachments for a page.
   ///
   /// # Arguments
@@ -61,6 +89,34 @@ pub trait ConfluenceApi: Send + Sync {
   /// The raw bytes of the attachment on success.
   async fn fetch_attachment(&self, url: &str) -> Result<Vec<u8>>;
 
+  /// Look up a user by Atlassian account ID.
+  ///
+  /// # Arguments
+  /// * `account_id` - Account ID as referenced by `ri:user` mentions.
+  ///
+  /// # Returns
+  /// The user's profile details, including the display name.
+  async fn get_user(&self, account_id: &str) -> Result<UserInfo>;
+
+  /// Fetch a space with its description, logo, and home page.
+  ///
+  /// # Arguments
+  /// * `space_key` - Key of the space, e.g. `DOCS`.
+  ///
+  /// # Returns
+  /// The space details.
+  async fn get_space(&self, space_key: &str) -> Result<Space>;
+
+  /// Look up a page by its space and title, as `ri:page` references do.
+  ///
+  /// # Arguments
+  /// * `space_key` - Key of the space containing the page.
+  /// * `title` - Exact title of the page.
+  ///
+  /// # Returns
+  /// The page with its storage body, or `None` when no page matches.
+  async fn find_page(&self, space_key: &str, title: &str) -> Result<Option<Page>>;
+
   /// Test authentication and return user information.
   ///
   /// # Returns
@@ -0,0 +1,363 @@
+//! Recording and replaying Confluence API responses.
+//!
+//! With `--record FILE`, every response the client receives is appended to a
+//! cassette keyed by its URL relative to the Confluence base URL. The
+//! cassette is written as JSON Lines, a version header followed by one
+//! response per line, so recording stays cheap however long the run. With
+//! `--replay FILE`, the client answers requests from the cassette instead of
+//! the network, so an export can be reproduced offline: for bug reports,
+//! demos, or iterating on the converters against real tenant data. Requests
+//! repeated within a run are answered with the recorded responses in order,
+//! reusing the last one once they run out.
+//!
+//! Cassettes contain page content and attachments as returned by the API, but
+//! never the credentials used to fetch them.
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result, anyhow};
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+
+/// Format version written to new cassettes.
+const CASSETTE_VERSION: u32 = 2;
+
+/// Version of cassettes written as a single JSON document.
+const LEGACY_CASSETTE_VERSION: u32 = 1;
+
+/// A recorded API response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Interaction {
+  /// Request URL relative to the Confluence base URL.
+  url: String,
+  /// HTTP status code of the response.
+  status: u16,
+  /// Response body, when it is valid UTF-8.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  body: Option<String>,
+  /// Base64-encoded response body, for binary attachments.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  body_base64: Option<String>,
+}
+
+impl Interaction {
+  fn new(url: &str, status: u16, body: &[u8]) -> Self {
+    let (body, body_base64) = match std::str::from_utf8(body) {
+      Ok(text) => (Some(text.to_string()), None),
+      Err(_) => (None, Some(BASE64.encode(body))),
+    };
+    Self {
+      url: url.to_string(),
+      status,
+      body,
+      body_base64,
+    }
+  }
+
+  fn body_bytes(&self) -> Result<Vec<u8>> {
+    match (&self.body, &self.body_base64) {
+      (_, Some(encoded)) => BASE64
+        .decode(encoded)
+        .with_context(|| format!("Invalid base64 body recorded for {}", self.url)),
+      (Some(text), None) => Ok(text.as_bytes().to_vec()),
+      (None, None) => Ok(Vec::new()),
+    }
+  }
+}
+
+/// First line of a cassette file.
+#[derive(Debug, Serialize, Deserialize)]
+struct CassetteHeader {
+  version: u32,
+}
+
+/// On-disk layout of a version 1 cassette, rewritten as a whole after every
+/// response.
+#[derive(Debug, Deserialize)]
+struct LegacyCassetteFile {
+  interactions: Vec<Interaction>,
+}
+
+/// Read the interactions of a cassette file.
+///
+/// A last line without a newline was cut off while it was being written, so
+/// it is skipped: the responses before it are still usable.
+fn parse_interactions(contents: &str) -> Result<Vec<Interaction>> {
+  let mut lines = contents.split_inclusive('\n');
+  let header: CassetteHeader = serde_json::from_str(lines.next().unwrap_or_default())
+    .or_else(|_| serde_json::from_str(contents))
+    .context("Not a cassette")?;
+  match header.version {
+    CASSETTE_VERSION => lines
+      .filter(|line| line.ends_with('\n') && !line.trim().is_empty())
+      .map(|line| serde_json::from_str(line).context("Invalid recorded response"))
+      .collect(),
+    LEGACY_CASSETTE_VERSION => {
+      let file: LegacyCassetteFile = serde_json::from_str(contents)?;
+      Ok(file.interactions)
+    }
+    version => Err(anyhow!(
+      "Unsupported cassette version {version} (expected {CASSETTE_VERSION})"
+    )),
+  }
+}
+
+/// Responses recorded from, or replayed in place of, the Confluence API.
+#[derive(Debug)]
+pub struct Cassette {
+  path: PathBuf,
+  state: CassetteState,
+}
+
+#[derive(Debug)]
+enum CassetteState {
+  /// The cassette file responses are appended to, created on the first one.
+  Record(Mutex<Option<File>>),
+  /// Remaining responses per URL.
+  Replay(Mutex<HashMap<String, VecDeque<Interaction>>>),
+}
+
+impl Cassette {
+  /// Start recording responses to `path`.
+  ///
+  /// The file is created (or replaced) on the first response.
+  pub fn record(path: impl Into<PathBuf>) -> Self {
+    Self {
+      path: path.into(),
+      state: CassetteState::Record(Mutex::new(None)),
+    }
+  }
+
+  /// Load a recorded cassette for replay.
+  ///
+  /// # Errors
+  /// Returns an error when the file cannot be read or is not a cassette.
+  pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+    let path = path.into();
+    let contents =
+      std::fs::read_to_string(&path).with_context(|| format!("Failed to read cassette {}", path.display()))?;
+    let interactions =
+      parse_interactions(&contents).with_context(|| format!("Failed to parse cassette {}", path.display()))?;
+
+    let mut responses: HashMap<String, VecDeque<Interaction>> = HashMap::new();
+    for interaction in interactions {
+      responses
+        .entry(interaction.url.clone())
+        .or_default()
+        .push_back(interaction);
+    }
+
+    Ok(Self {
+      path,
+      state: CassetteState::Replay(Mutex::new(responses)),
+    })
+  }
+
+  /// Whether requests are answered from the cassette instead of the network.
+  pub fn is_replay(&self) -> bool {
+    matches!(self.state, CassetteState::Replay(_))
+  }
+
+  /// Path of the cassette file.
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Look up the recorded response for a request.
+  ///
+  /// # Arguments
+  /// * `url` - Request URL relative to the Confluence base URL.
+  ///
+  /// # Returns
+  /// The status code and body of the next recorded response for `url`.
+  ///
+  /// # Errors
+  /// Returns an error when the cassette is recording or holds no response for
+  /// `url`.
+  pub(crate) fn lookup(&self, url: &str) -> Result<(u16, Vec<u8>)> {
+    let CassetteState::Replay(responses) = &self.state else {
+      return Err(anyhow!("Cassette {} is recording, not replaying", self.path.display()));
+    };
+
+    let mut responses = responses.lock().expect("cassette lock poisoned");
+    let interaction = responses
+      .get_mut(url)
+      .and_then(|queue| {
+        if queue.len() > 1 {
+          queue.pop_front()
+        } else {
+          queue.front().cloned()
+        }
+      })
+      .ok_or_else(|| anyhow!("No recorded response for {url} in cassette {}", self.path.display()))?;
+
+    Ok((interaction.status, interaction.body_bytes()?))
+  }
+
+  /// Record a response by appending it to the cassette file.
+  ///
+  /// Each response is written as soon as it arrives so the cassette is usable
+  /// even when the export fails part-way, which is when it is most useful.
+  /// Replaying cassettes does nothing.
+  ///
+  /// # Arguments
+  /// * `url` - Request URL relative to the Confluence base URL.
+  /// * `status` - HTTP status code of the response.
+  /// * `body` - Response body.
+  ///
+  /// # Errors
+  /// Returns an error when the cassette file cannot be written.
+  pub(crate) fn store(&self, url: &str, status: u16, body: &[u8]) -> Result<()> {
+    let CassetteState::Record(file) = &self.state else {
+      return Ok(());
+    };
+
+    let mut line =
+      serde_json::to_string(&Interaction::new(url, status, body)).context("Failed to serialize response")?;
+    line.push('\n');
+    let mut file = file.lock().expect("cassette lock poisoned");
+    let file = match &mut *file {
+      Some(file) => file,
+      None => file.insert(self.create()?),
+    };
+    file
+      .write_all(line.as_bytes())
+      .with_context(|| format!("Failed to write cassette {}", self.path.display()))
+  }
+
+  /// Create the cassette file with its header and open it for appending.
+  ///
+  /// The header is written to a temporary file that replaces any earlier
+  /// cassette at once, so the path never holds a half-written file.
+  fn create(&self) -> Result<File> {
+    let mut temp_path = OsString::from(self.path.as_os_str());
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let mut header = serde_json::to_string(&CassetteHeader {
+      version: CASSETTE_VERSION,
+    })?;
+    header.push('\n');
+    fs::write(&temp_path, header).with_context(|| format!("Failed to write cassette {}", temp_path.display()))?;
+    fs::rename(&temp_path, &self.path)
+      .with_context(|| format!("Failed to move cassette into place at {}", self.path.display()))?;
+    OpenOptions::new()
+      .append(true)
+      .open(&self.path)
+      .with_context(|| format!("Failed to open cassette {}", self.path.display()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_cassette_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassette.json");
+
+    let recorder = Cassette::record(&path);
+    recorder
+      .store("/wiki/rest/api/content/1", 200, br#"{"id":"1"}"#)
+      .unwrap();
+    recorder.store("/wiki/rest/api/content/1", 503, b"busy").unwrap();
+    recorder.store("/download/logo.png", 200, &[0x89, 0x50, 0xff]).unwrap();
+
+    let player = Cassette::replay(&path).unwrap();
+    assert!(player.is_replay());
+    assert_eq!(
+      player.lookup("/wiki/rest/api/content/1").unwrap(),
+      (200, br#"{"id":"1"}"#.to_vec())
+    );
+    // The last response for a URL is reused once the others are consumed
+    assert_eq!(
+      player.lookup("/wiki/rest/api/content/1").unwrap(),
+      (503, b"busy".to_vec())
+    );
+    assert_eq!(
+      player.lookup("/wiki/rest/api/content/1").unwrap(),
+      (503, b"busy".to_vec())
+    );
+    assert_eq!(
+      player.lookup("/download/logo.png").unwrap(),
+      (200, vec![0x89, 0x50, 0xff])
+    );
+
+    let missing = player.lookup("/wiki/rest/api/content/2").unwrap_err();
+    assert!(
+      missing
+        .to_string()
+        .contains("No recorded response for /wiki/rest/api/content/2")
+    );
+  }
+
+  #[test]
+  fn test_cassette_rejects_unknown_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassette.json");
+    std::fs::write(&path, r#"{"version": 99, "interactions": []}"#).unwrap();
+
+    let error = Cassette::replay(&path).unwrap_err();
+
+    assert!(format!("{error:#}").contains("Unsupported cassette version 99"));
+  }
+
+  #[test]
+  fn test_cassette_appends_responses() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassette.json");
+    std::fs::write(&path, "an earlier recording").unwrap();
+
+    let recorder = Cassette::record(&path);
+    recorder.store("/wiki/rest/api/content/1", 200, b"one").unwrap();
+    recorder.store("/wiki/rest/api/content/2", 200, b"two").unwrap();
+    insta::assert_snapshot!(std::fs::read_to_string(&path).unwrap(), @r#"
+    {"version":2}
+    {"url":"/wiki/rest/api/content/1","status":200,"body":"one"}
+    {"url":"/wiki/rest/api/content/2","status":200,"body":"two"}
+    "#);
+    assert!(!dir.path().join("cassette.json.tmp").exists());
+
+    // A response cut off mid-write is dropped, keeping the ones before it
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"url":"/wiki/rest/api/content/3","sta"#).unwrap();
+    let player = Cassette::replay(&path).unwrap();
+    assert_eq!(
+      player.lookup("/wiki/rest/api/content/2").unwrap(),
+      (200, b"two".to_vec())
+    );
+    assert!(player.lookup("/wiki/rest/api/content/3").is_err());
+  }
+
+  #[test]
+  fn test_cassette_replays_version_1_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassette.json");
+    std::fs::write(
+      &path,
+      r#"{
+  "version": 1,
+  "interactions": [
+    {
+      "url": "/wiki/rest/api/content/1",
+      "status": 200,
+      "body": "one"
+    }
+  ]
+}"#,
+    )
+    .unwrap();
+
+    let player = Cassette::replay(&path).unwrap();
+    assert_eq!(
+      player.lookup("/wiki/rest/api/content/1").unwrap(),
+      (200, b"one".to_vec())
+    );
+  }
+}
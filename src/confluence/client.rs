@@ -1,6 +1,7 @@
 //! HTTP client implementation for talking to the Confluence REST API.
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -8,16 +9,66 @@ use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use base64::Engine as _;
 use base64::engine::general_purpose::STANDARD as BASE64;
+use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::sync::Mutex;
 use tokio::time::sleep;
 
 use super::api::ConfluenceApi;
-use super::models::{Attachment, AttachmentsResponse, ChildPagesResponse, Page, UserInfo};
+use super::cassette::Cassette;
+use super::models::{
+  Attachment, AttachmentsResponse, ChildPagesResponse, ContentState, ContentStateResponse, Page, Space, UserInfo,
+};
+use crate::error::{self, Error};
 
 /// Maximum number of pagination requests before aborting, as a safeguard
 /// against infinite loops caused by cyclic or malformed `next` links.
 const MAX_PAGINATION_REQUESTS: usize = 1000;
 
+/// Position of a page of results within a paginated listing.
+struct ListingCursor {
+  next: Option<String>,
+  start: usize,
+  limit: usize,
+}
+
+/// A page of results from a paginated listing endpoint.
+trait Listing: serde::de::DeserializeOwned {
+  type Item;
+
+  /// Split the page into its results and its position in the listing.
+  fn into_parts(self) -> (Vec<Self::Item>, ListingCursor);
+}
+
+impl Listing for ChildPagesResponse {
+  type Item = Page;
+
+  fn into_parts(self) -> (Vec<Page>, ListingCursor) {
+    let cursor = ListingCursor {
+      next: self.links.and_then(|links| links.next),
+      start: self.start,
+      limit: self.limit,
+    };
+    (self.results, cursor)
+  }
+}
+
+impl Listing for AttachmentsResponse {
+  type Item = Attachment;
+
+  fn into_parts(self) -> (Vec<Attachment>, ListingCursor) {
+    let cursor = ListingCursor {
+      next: self.links.and_then(|links| links.next),
+      start: self.start,
+      limit: self.limit,
+    };
+    (self.results, cursor)
+  }
+}
+
+/// Upper bound on the wait between retries, including server `Retry-After`
+/// hints.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
 /// Confluence API client.
 #[derive(Clone)]
 pub struct ConfluenceClient {
@@ -26,30 +77,112 @@ pub struct ConfluenceClient {
   token: String,
   client: reqwest::Client,
   rate_limiter: Arc<RequestRateLimiter>,
+  retries: u32,
+  asset_timeout: Duration,
+  asset_retries: u32,
+  page_size: usize,
+  download_buffer: usize,
+  download_progress: Option<DownloadProgress>,
+  cassette: Option<Arc<Cassette>>,
+}
+
+/// Callback told about the progress of attachment downloads streamed to disk.
+///
+/// It receives the file being written, the bytes written so far, and the
+/// size announced by the server, if any.
+pub type DownloadProgress = Arc<dyn Fn(&Path, u64, Option<u64>) + Send + Sync>;
+
+/// Bytes of a streamed download held in memory before they are written out.
+const DEFAULT_DOWNLOAD_BUFFER: usize = 8 * 1024 * 1024;
+
+/// What a request fetches, which decides its timeout and retry budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RequestKind {
+  /// A JSON API call, bound by the client-wide timeout and retries.
+  Api,
+  /// An image or attachment download, bound by the asset timeout and retries.
+  Asset,
 }
 
-/// Simple fixed-window rate limiter to cap the number of requests per interval.
+/// Status and body of a completed API response.
+///
+/// Bodies are read eagerly so responses can be recorded to, or replayed from,
+/// a [`Cassette`].
+struct ApiResponse {
+  status: reqwest::StatusCode,
+  body: Vec<u8>,
+}
+
+impl ApiResponse {
+  fn status(&self) -> reqwest::StatusCode {
+    self.status
+  }
+
+  /// The body as text, replacing invalid UTF-8.
+  fn text(&self) -> String {
+    String::from_utf8_lossy(&self.body).into_owned()
+  }
+
+  /// Decode the body as JSON.
+  ///
+  /// # Errors
+  /// Returns an error when the body is not valid JSON for `T`, pointing out
+  /// bodies that end mid-document, which proxies produce when they cut a
+  /// response short.
+  fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T> {
+    serde_json::from_slice(&self.body).map_err(|err| {
+      if err.is_eof() {
+        anyhow!(
+          "Response body ended unexpectedly after {} bytes; it was likely truncated by a proxy ({err})",
+          self.body.len()
+        )
+      } else {
+        err.into()
+      }
+    })
+  }
+}
+
+/// Token-bucket rate limiter shared by every request of a client.
+///
+/// The bucket holds up to `max_requests` tokens and refills at `max_requests`
+/// tokens per window. Each request takes a token and waits for the refill when
+/// the bucket is empty, so short bursts go out immediately while the sustained
+/// rate never exceeds the limit. Clones of a client share one bucket, so page,
+/// child listing, attachment, and download requests of parallel page exports
+/// are all throttled together.
+#[derive(Debug)]
+pub(crate) struct RequestRateLimiter {
+  capacity: f64,
+  refill_per_sec: f64,
+  bucket: Mutex<TokenBucket>,
+}
+
+/// Tokens currently available and when they were last topped up.
 #[derive(Debug)]
-struct RequestRateLimiter {
-  max_requests: usize,
-  window: Duration,
-  timestamps: Mutex<VecDeque<Instant>>,
+struct TokenBucket {
+  tokens: f64,
+  refilled_at: Instant,
 }
 
 impl RequestRateLimiter {
-  /// Create a rate limiter with a fixed window.
+  /// Create a rate limiter with a full bucket.
   ///
   /// # Arguments
   /// * `max_requests` - Maximum number of requests permitted within the window.
-  /// * `window` - Duration of the request window used to enforce throttling.
+  /// * `window` - Duration over which the bucket refills completely.
   ///
   /// # Returns
   /// A rate limiter that enforces the configured throughput ceiling.
-  fn new(max_requests: usize, window: Duration) -> Self {
+  pub(crate) fn new(max_requests: usize, window: Duration) -> Self {
+    let capacity = max_requests.max(1) as f64;
     Self {
-      max_requests,
-      window,
-      timestamps: Mutex::new(VecDeque::with_capacity(max_requests)),
+      capacity,
+      refill_per_sec: capacity / window.as_secs_f64().max(f64::EPSILON),
+      bucket: Mutex::new(TokenBucket {
+        tokens: capacity,
+        refilled_at: Instant::now(),
+      }),
     }
   }
 
@@ -57,36 +190,23 @@ impl RequestRateLimiter {
   /// rate limit.
   ///
   /// # Returns
-  /// Completes when the rate limiter has reserved a slot for a request.
-  async fn acquire(&self) {
+  /// Completes when the rate limiter has handed out a token for a request.
+  pub(crate) async fn acquire(&self) {
     loop {
-      let mut timestamps = self.timestamps.lock().await;
+      let mut bucket = self.bucket.lock().await;
       let now = Instant::now();
+      let refill = now.duration_since(bucket.refilled_at).as_secs_f64() * self.refill_per_sec;
+      bucket.tokens = (bucket.tokens + refill).min(self.capacity);
+      bucket.refilled_at = now;
 
-      while let Some(earliest) = timestamps.front()
-        && now.duration_since(*earliest) >= self.window
-      {
-        timestamps.pop_front();
-      }
-
-      if timestamps.len() < self.max_requests {
-        timestamps.push_back(now);
+      if bucket.tokens >= 1.0 {
+        bucket.tokens -= 1.0;
         return;
       }
 
-      let earliest = *timestamps.front().expect("rate limiter queue should never be empty");
-      let elapsed = now.duration_since(earliest);
-      let wait_duration = if elapsed >= self.window {
-        Duration::from_secs(0)
-      } else {
-        self.window - elapsed
-      };
-
-      drop(timestamps);
-
-      if wait_duration > Duration::from_secs(0) {
-        sleep(wait_duration).await;
-      }
+      let wait_duration = Duration::from_secs_f64((1.0 - bucket.tokens) / self.refill_per_sec);
+      drop(bucket);
+      sleep(wait_duration).await;
     }
   }
 }
@@ -100,20 +220,24 @@ impl ConfluenceClient {
   /// * `token` - The API token
   /// * `timeout_secs` - Request timeout in seconds
   /// * `rate_limit` - Maximum requests per second
+  /// * `retries` - Retries for throttled (429) or unavailable (5xx) responses
+  /// * `page_size` - Results requested per page from list endpoints
   ///
   /// # Returns
   /// A configured `ConfluenceClient` ready for API calls when the provided
   /// options are valid.
   ///
   /// # Errors
-  /// Returns an error if the rate limit is zero or if the underlying
-  /// `reqwest::Client` cannot be built.
+  /// Returns an error if the rate limit or page size is zero or if the
+  /// underlying `reqwest::Client` cannot be built.
   pub fn new(
     base_url: impl Into<String>,
     username: impl Into<String>,
     token: impl Into<String>,
     timeout_secs: u64,
     rate_limit: usize,
+    retries: u32,
+    page_size: usize,
   ) -> Result<Self> {
     let base_url = base_url.into();
     let username = username.into();
@@ -122,6 +246,9 @@ impl ConfluenceClient {
     if rate_limit == 0 {
       return Err(anyhow!("Rate limit must be at least 1 request per second"));
     }
+    if page_size == 0 {
+      return Err(anyhow!("Page size must be at least 1"));
+    }
 
     let base_url = base_url.trim_end_matches('/').to_string();
 
@@ -141,9 +268,57 @@ impl ConfluenceClient {
       token,
       client,
       rate_limiter: Arc::new(RequestRateLimiter::new(rate_limit, Duration::from_secs(1))),
+      retries,
+      asset_timeout: Duration::from_secs(timeout_secs),
+      asset_retries: retries,
+      page_size,
+      download_buffer: DEFAULT_DOWNLOAD_BUFFER,
+      download_progress: None,
+      cassette: None,
     })
   }
 
+  /// Record responses to, or replay them from, a cassette.
+  ///
+  /// When replaying, no requests are sent to Confluence and the credentials
+  /// are never used.
+  pub fn with_cassette(mut self, cassette: Arc<Cassette>) -> Self {
+    self.cassette = Some(cassette);
+    self
+  }
+
+  /// Use a separate timeout and retry budget for image and attachment
+  /// downloads.
+  ///
+  /// Large attachments can take far longer than API calls, so downloads get
+  /// their own limits instead of the client-wide ones. The timeout covers the
+  /// whole transfer, including reading the body.
+  ///
+  /// # Arguments
+  /// * `timeout` - Time allowed for a single download attempt.
+  /// * `retries` - Retries for downloads that time out, lose their connection, or are throttled.
+  pub fn with_asset_limits(mut self, timeout: Duration, retries: u32) -> Self {
+    self.asset_timeout = timeout;
+    self.asset_retries = retries;
+    self
+  }
+
+  /// Set how much of an attachment download is held in memory.
+  ///
+  /// [`ConfluenceApi::download_attachment`] streams the body to disk, writing
+  /// it out whenever `bytes` have been received, so attachments larger than
+  /// memory can be downloaded.
+  pub fn with_download_buffer(mut self, bytes: usize) -> Self {
+    self.download_buffer = bytes.max(1);
+    self
+  }
+
+  /// Report the progress of attachment downloads streamed to disk.
+  pub fn with_download_progress(mut self, progress: DownloadProgress) -> Self {
+    self.download_progress = Some(progress);
+    self
+  }
+
   /// Get the authorization header value (Basic auth).
   ///
   /// # Returns
@@ -153,222 +328,263 @@ impl ConfluenceClient {
     let credentials = format!("{}:{}", self.username, self.token);
     format!("Basic {}", BASE64.encode(credentials.as_bytes()))
   }
-}
-
-#[async_trait]
-impl ConfluenceApi for ConfluenceClient {
-  async fn get_page(&self, page_id: &str) -> Result<Page> {
-    self.rate_limiter.acquire().await;
-
-    let url = format!(
-      "{}/wiki/rest/api/content/{}?expand=body.storage,body.view,space",
-      self.base_url, page_id
-    );
 
+  /// Fetch and decode a single page from a content endpoint URL.
+  ///
+  /// # Arguments
+  /// * `url` - Fully qualified content URL including query parameters.
+  ///
+  /// # Returns
+  /// The decoded `Page` record.
+  async fn fetch_page(&self, url: &str) -> Result<Page> {
     let response = self
-      .client
-      .get(&url)
-      .header("Authorization", self.auth_header())
-      .header("Accept", "application/json")
-      .send()
+      .get(url, RequestKind::Api)
       .await
       .context("Failed to send request to Confluence API")?;
 
     if !response.status().is_success() {
       let status = response.status();
-      let error_text = response
-        .text()
-        .await
-        .unwrap_or_else(|_| String::from("(no error details)"));
-      return Err(anyhow!("Confluence API returned error {status}: {error_text}"));
+      let error_text = response.text();
+      return Err(
+        Error::from_status(
+          status.as_u16(),
+          format!("Confluence API returned error {status}: {error_text}"),
+        )
+        .into(),
+      );
     }
 
     let page: Page = response
       .json()
-      .await
       .context("Failed to parse page response from Confluence API")?;
 
     Ok(page)
   }
+}
 
-  async fn get_child_pages(&self, page_id: &str) -> Result<Vec<Page>> {
-    let initial_url = format!("{}/wiki/rest/api/content/{}/child/page", self.base_url, page_id);
-    let mut all_pages = Vec::new();
-    let mut next_url = Some(initial_url);
-    let mut seen_urls = HashSet::new();
-    let mut request_count: usize = 0;
+#[async_trait]
+impl ConfluenceApi for ConfluenceClient {
+  async fn get_page(&self, page_id: &str) -> error::Result<Page> {
+    let url = format!(
+      "{}/wiki/rest/api/content/{}?expand=body.storage,body.view,space,metadata.labels,history,history.lastUpdated,version,ancestors",
+      self.base_url, page_id
+    );
+    self.fetch_page(&url).await.map_err(Error::from)
+  }
 
-    while let Some(url) = next_url {
-      if !seen_urls.insert(url.clone()) {
-        tracing::warn!("Pagination cycle detected for child pages of {page_id}, stopping");
-        break;
-      }
+  async fn get_page_version(&self, page_id: &str, version: u32) -> error::Result<Page> {
+    let url = format!(
+      "{}/wiki/rest/api/content/{}?status=historical&version={}&expand=body.storage,space,metadata.labels,history,history.lastUpdated,version,ancestors",
+      self.base_url, page_id, version
+    );
+    self
+      .fetch_page(&url)
+      .await
+      .with_context(|| format!("Failed to fetch version {version} of page {page_id}"))
+      .map_err(Error::from)
+  }
 
-      request_count += 1;
-      if request_count > MAX_PAGINATION_REQUESTS {
-        tracing::warn!("Pagination limit ({MAX_PAGINATION_REQUESTS}) reached for child pages of {page_id}, stopping");
-        break;
-      }
+  async fn get_child_pages(&self, page_id: &str) -> error::Result<Vec<Page>> {
+    let url = format!(
+      "{}/wiki/rest/api/content/{}/child/page?limit={}",
+      self.base_url, page_id, self.page_size
+    );
+    self
+      .fetch_listing::<ChildPagesResponse>(&url, &format!("child pages of {page_id}"))
+      .await
+      .map_err(Error::from)
+  }
 
-      self.rate_limiter.acquire().await;
+  async fn get_content_state(&self, page_id: &str) -> error::Result<Option<ContentState>> {
+    let url = format!(
+      "{}/wiki/rest/api/content/{}/state?status=current",
+      self.base_url, page_id
+    );
 
-      let response = self
-        .client
-        .get(&url)
-        .header("Authorization", self.auth_header())
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .context("Failed to fetch child pages from Confluence API")?;
+    let response = self
+      .get(&url, RequestKind::Api)
+      .await
+      .context("Failed to fetch content state from Confluence API")?;
 
-      if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-          .text()
-          .await
-          .unwrap_or_else(|_| String::from("(no error details)"));
-        return Err(anyhow!("Confluence API returned error {status}: {error_text}"));
-      }
+    // Instances without content states (Server/Data Center) answer 404
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+      return Ok(None);
+    }
+    if !response.status().is_success() {
+      let status = response.status();
+      let error_text = response.text();
+      return Err(Error::from_status(
+        status.as_u16(),
+        format!("Confluence API returned error {status}: {error_text}"),
+      ));
+    }
 
-      let child_pages: ChildPagesResponse = response
-        .json()
+    let state: ContentStateResponse = response
+      .json()
+      .context("Failed to parse content state response from Confluence API")?;
+    Ok(state.content_state)
+  }
+
+  async fn get_attachments(&self, page_id: &str) -> error::Result<Vec<Attachment>> {
+    let url = format!(
+      "{}/wiki/rest/api/content/{}/child/attachment?limit={}",
+      self.base_url, page_id, self.page_size
+    );
+    self
+      .fetch_listing::<AttachmentsResponse>(&url, &format!("attachments of {page_id}"))
+      .await
+      .map_err(Error::from)
+  }
+
+  async fn download_attachment(&self, url: &str, output_path: &Path) -> error::Result<()> {
+    if let Some(parent) = output_path.parent() {
+      tokio::fs::create_dir_all(parent)
         .await
-        .context("Failed to parse child pages response from Confluence API")?;
+        .context("Failed to create output directory for attachment")?;
+    }
 
-      all_pages.extend(child_pages.results);
-      next_url = child_pages
-        .links
-        .and_then(|l| l.next)
-        .map(|next| self.resolve_pagination_url(&next));
+    // Cassettes store whole bodies, so recorded and replayed downloads are
+    // read into memory
+    if self.cassette.is_some() {
+      let bytes = self.fetch_attachment(url).await?;
+      tokio::fs::write(output_path, bytes)
+        .await
+        .context("Failed to write attachment to file")?;
+      return Ok(());
     }
 
-    Ok(all_pages)
+    let full_url = self.resolve_attachment_url(url);
+    let partial_path = partial_download_path(output_path);
+    let result = self.stream_to_file(&full_url, &partial_path, output_path).await;
+    if result.is_err() {
+      let _ = tokio::fs::remove_file(&partial_path).await;
+    }
+    result.map_err(Error::from)
   }
 
-  async fn get_attachments(&self, page_id: &str) -> Result<Vec<Attachment>> {
-    let initial_url = format!("{}/wiki/rest/api/content/{}/child/attachment", self.base_url, page_id);
-    let mut all_attachments = Vec::new();
-    let mut next_url = Some(initial_url);
-    let mut seen_urls = HashSet::new();
-    let mut request_count: usize = 0;
+  async fn fetch_attachment(&self, url: &str) -> error::Result<Vec<u8>> {
+    let full_url = self.resolve_attachment_url(url);
 
-    while let Some(url) = next_url {
-      if !seen_urls.insert(url.clone()) {
-        tracing::warn!("Pagination cycle detected for attachments of {page_id}, stopping");
-        break;
-      }
+    let response = self
+      .get(&full_url, RequestKind::Asset)
+      .await
+      .context("Failed to download attachment")?;
 
-      request_count += 1;
-      if request_count > MAX_PAGINATION_REQUESTS {
-        tracing::warn!("Pagination limit ({MAX_PAGINATION_REQUESTS}) reached for attachments of {page_id}, stopping");
-        break;
-      }
+    let status = response.status();
+    if !status.is_success() {
+      let error_text = response.text();
+      return Err(Error::from_status(
+        status.as_u16(),
+        format!("Failed to fetch attachment from {full_url}: {status} - {error_text}"),
+      ));
+    }
 
-      self.rate_limiter.acquire().await;
+    Ok(response.body)
+  }
 
-      let response = self
-        .client
-        .get(&url)
-        .header("Authorization", self.auth_header())
-        .header("Accept", "application/json")
-        .send()
-        .await
-        .context("Failed to fetch attachments from Confluence API")?;
+  async fn get_user(&self, account_id: &str) -> error::Result<UserInfo> {
+    let url = format!("{}/wiki/rest/api/user?accountId={}", self.base_url, account_id);
 
-      if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response
-          .text()
-          .await
-          .unwrap_or_else(|_| String::from("(no error details)"));
-        return Err(anyhow!("Confluence API returned error {status}: {error_text}"));
-      }
-
-      let attachments: AttachmentsResponse = response
-        .json()
-        .await
-        .context("Failed to parse attachments response from Confluence API")?;
+    let response = self
+      .get(&url, RequestKind::Api)
+      .await
+      .context("Failed to fetch user from Confluence API")?;
 
-      all_attachments.extend(attachments.results);
-      next_url = attachments
-        .links
-        .and_then(|l| l.next)
-        .map(|next| self.resolve_pagination_url(&next));
+    if !response.status().is_success() {
+      let status = response.status();
+      let error_text = response.text();
+      return Err(Error::from_status(
+        status.as_u16(),
+        format!("Confluence API returned error {status}: {error_text}"),
+      ));
     }
 
-    Ok(all_attachments)
+    let user_info: UserInfo = response
+      .json()
+      .context("Failed to parse user response from Confluence API")?;
+
+    Ok(user_info)
   }
 
-  async fn download_attachment(&self, url: &str, output_path: &std::path::Path) -> Result<()> {
-    let bytes = self.fetch_attachment(url).await?;
+  async fn get_space(&self, space_key: &str) -> error::Result<Space> {
+    let url = format!(
+      "{}/wiki/rest/api/space/{}?expand=description.plain,icon,homepage",
+      self.base_url, space_key
+    );
 
-    if let Some(parent) = output_path.parent() {
-      tokio::fs::create_dir_all(parent)
-        .await
-        .context("Failed to create output directory for attachment")?;
+    let response = self
+      .get(&url, RequestKind::Api)
+      .await
+      .context("Failed to fetch space from Confluence API")?;
+
+    if !response.status().is_success() {
+      let status = response.status();
+      let error_text = response.text();
+      return Err(Error::from_status(
+        status.as_u16(),
+        format!("Confluence API returned error {status}: {error_text}"),
+      ));
     }
 
-    tokio::fs::write(output_path, bytes)
-      .await
-      .context("Failed to write attachment to file")?;
+    let space: Space = response
+      .json()
+      .context("Failed to parse space response from Confluence API")?;
 
-    Ok(())
+    Ok(space)
   }
 
-  async fn fetch_attachment(&self, url: &str) -> Result<Vec<u8>> {
-    let full_url = self.resolve_attachment_url(url);
-
-    self.rate_limiter.acquire().await;
+  async fn find_page(&self, space_key: &str, title: &str) -> error::Result<Option<Page>> {
+    let url = reqwest::Url::parse_with_params(
+      &format!("{}/wiki/rest/api/content", self.base_url),
+      [
+        ("type", "page"),
+        ("spaceKey", space_key),
+        ("title", title),
+        ("expand", "body.storage,space"),
+      ],
+    )
+    .context("Failed to build page lookup URL")?;
 
     let response = self
-      .client
-      .get(&full_url)
-      .header("Authorization", self.auth_header())
-      .send()
+      .get(url.as_str(), RequestKind::Api)
       .await
-      .context("Failed to download attachment")?;
+      .context("Failed to look up page from Confluence API")?;
 
-    let status = response.status();
-    if !status.is_success() {
-      let error_text = response
-        .text()
-        .await
-        .unwrap_or_else(|_| String::from("(no error details)"));
-      return Err(anyhow!(
-        "Failed to fetch attachment from {full_url}: {status} - {error_text}"
+    if !response.status().is_success() {
+      let status = response.status();
+      let error_text = response.text();
+      return Err(Error::from_status(
+        status.as_u16(),
+        format!("Confluence API returned error {status}: {error_text}"),
       ));
     }
 
-    let bytes = response.bytes().await.context("Failed to read attachment bytes")?;
-    Ok(bytes.to_vec())
-  }
+    let pages: ChildPagesResponse = response
+      .json()
+      .context("Failed to parse page lookup response from Confluence API")?;
 
-  async fn test_auth(&self) -> Result<UserInfo> {
-    self.rate_limiter.acquire().await;
+    Ok(pages.results.into_iter().next())
+  }
 
+  async fn test_auth(&self) -> error::Result<UserInfo> {
     let url = format!("{}/wiki/rest/api/user/current", self.base_url);
 
     let response = self
-      .client
-      .get(&url)
-      .header("Authorization", self.auth_header())
-      .header("Accept", "application/json")
-      .send()
+      .get(&url, RequestKind::Api)
       .await
       .context("Failed to send authentication test request")?;
 
     if !response.status().is_success() {
       let status = response.status();
-      let error_text = response
-        .text()
-        .await
-        .unwrap_or_else(|_| String::from("(no error details)"));
-      return Err(anyhow!("Authentication failed with status {status}: {error_text}"));
+      let error_text = response.text();
+      return Err(Error::from_status(
+        status.as_u16(),
+        format!("Authentication failed with status {status}: {error_text}"),
+      ));
     }
 
     let user_info: UserInfo = response
       .json()
-      .await
       .context("Failed to parse user information from Confluence API")?;
 
     Ok(user_info)
@@ -376,6 +592,283 @@ impl ConfluenceApi for ConfluenceClient {
 }
 
 impl ConfluenceClient {
+  /// Perform a GET request, through the cassette when one is configured.
+  ///
+  /// # Arguments
+  /// * `url` - Fully qualified request URL.
+  /// * `kind` - Whether the request is an API call or an asset download.
+  ///
+  /// # Returns
+  /// The final response, which may still carry an error status.
+  ///
+  /// # Errors
+  /// Returns an error when the request fails, when a replayed cassette has no
+  /// response for the URL, or when a recorded response cannot be saved.
+  async fn get(&self, url: &str, kind: RequestKind) -> Result<ApiResponse> {
+    let key = url.strip_prefix(self.base_url.as_str()).unwrap_or(url);
+    if let Some(cassette) = self.cassette.as_deref()
+      && cassette.is_replay()
+    {
+      let (status, body) = cassette.lookup(key)?;
+      let status =
+        reqwest::StatusCode::from_u16(status).with_context(|| format!("Invalid status {status} recorded for {key}"))?;
+      return Ok(ApiResponse { status, body });
+    }
+
+    let response = self.send(url, kind).await?;
+    if let Some(cassette) = self.cassette.as_deref() {
+      cassette.store(key, response.status.as_u16(), &response.body)?;
+    }
+    Ok(response)
+  }
+
+  /// Send an authenticated, rate-limited GET request and read its body.
+  ///
+  /// Throttled (429) and unavailable (502, 503, 504) responses, transport
+  /// errors including timeouts while reading the body, and bodies that are
+  /// shorter than announced or unexpectedly empty are retried up to the
+  /// configured number of times, waiting for the server's `Retry-After` hint
+  /// or an exponential backoff. Asset downloads use the asset timeout and
+  /// retry budget instead of the client-wide ones.
+  ///
+  /// # Arguments
+  /// * `url` - Fully qualified request URL.
+  /// * `kind` - Whether the request is an API call or an asset download.
+  ///
+  /// # Returns
+  /// The final response, which may still carry an error status.
+  async fn send(&self, url: &str, kind: RequestKind) -> Result<ApiResponse> {
+    let retries = match kind {
+      RequestKind::Api => self.retries,
+      RequestKind::Asset => self.asset_retries,
+    };
+    let mut attempt = 0;
+    loop {
+      self.rate_limiter.acquire().await;
+
+      let mut request = self.client.get(url).header("Authorization", self.auth_header());
+      request = match kind {
+        RequestKind::Api => request.header("Accept", "application/json"),
+        RequestKind::Asset => request.timeout(self.asset_timeout),
+      };
+      let can_retry = attempt < retries;
+
+      let (failure, hint) = match request.send().await {
+        Ok(response) if can_retry && is_retryable_status(response.status()) => {
+          let hint = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+          (response.status().to_string(), hint)
+        }
+        Ok(response) => {
+          let status = response.status();
+          let expected_len = response.content_length();
+          match response.bytes().await {
+            Ok(body) => match body_anomaly(kind, status, expected_len, &body) {
+              Some(anomaly) if can_retry => (anomaly, None),
+              Some(anomaly) => return Err(anyhow!("{anomaly} (GET {url})")),
+              None => {
+                return Ok(ApiResponse {
+                  status,
+                  body: body.to_vec(),
+                });
+              }
+            },
+            Err(e) if can_retry && is_transient_error(&e) => (e.to_string(), None),
+            Err(e) => return Err(e).context("Failed to read response body"),
+          }
+        }
+        Err(e) if can_retry && is_transient_error(&e) => (e.to_string(), None),
+        Err(e) => return Err(e.into()),
+      };
+
+      let delay = retry_delay(attempt, hint);
+      tracing::warn!("Request to {url} failed ({failure}), retrying in {delay:?}");
+      sleep(delay).await;
+      attempt += 1;
+    }
+  }
+
+  /// Stream a download to `partial_path`, moving it to `output_path` once
+  /// complete.
+  ///
+  /// At most the download buffer size of the body is held in memory. Failed
+  /// attempts are retried like [`Self::send`] retries asset downloads,
+  /// starting the file over.
+  ///
+  /// # Errors
+  /// Returns an error when the download fails after all retries or the file
+  /// cannot be written.
+  async fn stream_to_file(&self, url: &str, partial_path: &Path, output_path: &Path) -> Result<()> {
+    let mut attempt = 0;
+    loop {
+      self.rate_limiter.acquire().await;
+      let can_retry = attempt < self.asset_retries;
+
+      let request = self
+        .client
+        .get(url)
+        .header("Authorization", self.auth_header())
+        .timeout(self.asset_timeout);
+      let (failure, hint) = match request.send().await {
+        Ok(response) if can_retry && is_retryable_status(response.status()) => {
+          let hint = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok());
+          (response.status().to_string(), hint)
+        }
+        Ok(response) if !response.status().is_success() => {
+          let status = response.status();
+          let error_text = response.text().await.unwrap_or_default();
+          return Err(
+            Error::from_status(
+              status.as_u16(),
+              format!("Failed to fetch attachment from {url}: {status} - {error_text}"),
+            )
+            .into(),
+          );
+        }
+        Ok(response) => match self.write_body(response, partial_path, output_path).await {
+          Ok(()) => break,
+          Err(BodyError::Transient(failure)) if can_retry => (failure, None),
+          Err(BodyError::Transient(failure)) => return Err(anyhow!("{failure} (GET {url})")),
+          Err(BodyError::Write(err)) => return Err(err),
+        },
+        Err(e) if can_retry && is_transient_error(&e) => (e.to_string(), None),
+        Err(e) => return Err(anyhow::Error::new(e).context("Failed to download attachment")),
+      };
+
+      let delay = retry_delay(attempt, hint);
+      tracing::warn!("Request to {url} failed ({failure}), retrying in {delay:?}");
+      sleep(delay).await;
+      attempt += 1;
+    }
+
+    tokio::fs::rename(partial_path, output_path)
+      .await
+      .with_context(|| format!("Failed to move download to {}", output_path.display()))
+  }
+
+  /// Write a response body to `partial_path`, reporting progress for
+  /// `output_path`.
+  async fn write_body(
+    &self,
+    mut response: reqwest::Response,
+    partial_path: &Path,
+    output_path: &Path,
+  ) -> std::result::Result<(), BodyError> {
+    let expected_len = response.content_length();
+    let file = tokio::fs::File::create(partial_path)
+      .await
+      .with_context(|| format!("Failed to create {}", partial_path.display()))
+      .map_err(BodyError::Write)?;
+    let mut writer = BufWriter::with_capacity(self.download_buffer, file);
+    let mut written: u64 = 0;
+
+    loop {
+      let chunk = match response.chunk().await {
+        Ok(Some(chunk)) => chunk,
+        Ok(None) => break,
+        // Connections dropped mid-body surface as decode errors when the
+        // response is compressed
+        Err(e) => return Err(BodyError::Transient(format!("{:#}", anyhow::Error::new(e)))),
+      };
+      writer
+        .write_all(&chunk)
+        .await
+        .context("Failed to write attachment to file")
+        .map_err(BodyError::Write)?;
+      written += chunk.len() as u64;
+      if let Some(progress) = &self.download_progress {
+        progress(output_path, written, expected_len);
+      }
+    }
+
+    if let Some(expected_len) = expected_len
+      && expected_len != written
+    {
+      return Err(BodyError::Transient(format!(
+        "Response body has {written} bytes but Content-Length announced {expected_len}"
+      )));
+    }
+    writer
+      .flush()
+      .await
+      .context("Failed to write attachment to file")
+      .map_err(BodyError::Write)
+  }
+
+  /// Fetch every page of a paginated listing.
+  ///
+  /// Pages are followed through their `next` links. Some Server and Data
+  /// Center versions, and proxies rewriting responses, drop those links; a
+  /// full page without one is followed by requesting the items after it
+  /// with `start`, until a page comes back short.
+  ///
+  /// # Arguments
+  /// * `initial_url` - URL of the first page, without a `start` parameter.
+  /// * `what` - Description of the listing for errors and warnings, e.g. `child pages of 123`.
+  ///
+  /// # Errors
+  /// Returns an error if a request fails or a response cannot be parsed.
+  async fn fetch_listing<R: Listing>(&self, initial_url: &str, what: &str) -> Result<Vec<R::Item>> {
+    let mut all_items = Vec::new();
+    let mut next_url = Some(initial_url.to_string());
+    let mut seen_urls = HashSet::new();
+    let mut request_count: usize = 0;
+
+    while let Some(url) = next_url {
+      if !seen_urls.insert(url.clone()) {
+        tracing::warn!("Pagination cycle detected for {what}, stopping");
+        break;
+      }
+
+      request_count += 1;
+      if request_count > MAX_PAGINATION_REQUESTS {
+        tracing::warn!("Pagination limit ({MAX_PAGINATION_REQUESTS}) reached for {what}, stopping");
+        break;
+      }
+
+      let response = self
+        .get(&url, RequestKind::Api)
+        .await
+        .with_context(|| format!("Failed to fetch {what} from Confluence API"))?;
+
+      if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text();
+        return Err(
+          Error::from_status(
+            status.as_u16(),
+            format!("Confluence API returned error {status}: {error_text}"),
+          )
+          .into(),
+        );
+      }
+
+      let listing: R = response
+        .json()
+        .with_context(|| format!("Failed to parse Confluence API response for {what}"))?;
+
+      let (items, cursor) = listing.into_parts();
+      let count = items.len();
+      all_items.extend(items);
+      next_url = match cursor.next {
+        Some(next) => Some(self.resolve_pagination_url(&next)),
+        None if cursor.limit > 0 && count >= cursor.limit => {
+          Some(format!("{initial_url}&start={}", cursor.start + count))
+        }
+        None => None,
+      };
+    }
+
+    Ok(all_items)
+  }
+
   /// Resolve a pagination `next` link to a full URL.
   ///
   /// The Confluence API typically returns relative paths in pagination links,
@@ -406,15 +899,93 @@ impl ConfluenceClient {
   }
 }
 
+/// Why streaming a response body to disk failed.
+enum BodyError {
+  /// The transfer broke off and may succeed when repeated.
+  Transient(String),
+  /// The file could not be written.
+  Write(anyhow::Error),
+}
+
+/// Path a download is written to until it is complete, so an interrupted
+/// download never leaves a truncated file under the final name.
+fn partial_download_path(output_path: &Path) -> PathBuf {
+  let mut name = output_path.file_name().unwrap_or_default().to_os_string();
+  name.push(".part");
+  output_path.with_file_name(name)
+}
+
+/// Whether a response status indicates a transient condition worth retrying.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+  matches!(status.as_u16(), 429 | 502 | 503 | 504)
+}
+
+/// Describe what is wrong with a response body, if anything.
+///
+/// Responses are requested with gzip or deflate compression; `expected_len`
+/// is only known for uncompressed responses, since decompression drops the
+/// `Content-Length` header.
+///
+/// # Returns
+/// A message for a body whose length differs from its `Content-Length`, or
+/// for a successful API response without a body, otherwise `None`.
+fn body_anomaly(
+  kind: RequestKind,
+  status: reqwest::StatusCode,
+  expected_len: Option<u64>,
+  body: &[u8],
+) -> Option<String> {
+  if let Some(expected_len) = expected_len
+    && expected_len != body.len() as u64
+  {
+    return Some(format!(
+      "Response body has {} bytes but Content-Length announced {expected_len}",
+      body.len()
+    ));
+  }
+  if kind == RequestKind::Api && status.is_success() && body.trim_ascii().is_empty() {
+    return Some(format!(
+      "Confluence API returned an empty response body with status {status}"
+    ));
+  }
+  None
+}
+
+/// Whether a transport error is likely to succeed when the request is repeated.
+fn is_transient_error(error: &reqwest::Error) -> bool {
+  error.is_timeout() || error.is_connect() || error.is_body()
+}
+
+/// Wait before retry number `attempt` (zero-based).
+///
+/// Honors a `Retry-After` hint in seconds, otherwise backs off exponentially
+/// from one second. Both are capped at [`MAX_RETRY_DELAY`].
+fn retry_delay(attempt: u32, retry_after_secs: Option<u64>) -> Duration {
+  let delay = match retry_after_secs {
+    Some(secs) => Duration::from_secs(secs),
+    None => Duration::from_secs(1u64 << attempt.min(6)),
+  };
+  delay.min(MAX_RETRY_DELAY)
+}
+
 #[cfg(test)]
 mod tests {
+  #[allow(unused_imports)]
   use base64::Engine as _;
 
   use super::*;
 
   #[test]
   fn test_confluence_client_new() {
-    let client = ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5);
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    );
     assert!(client.is_ok());
     let client = client.unwrap();
     assert_eq!(client.base_url, "https://example.atlassian.net");
@@ -430,6 +1001,8 @@ mod tests {
       "test-token",
       30,
       2,
+      3,
+      25,
     )
     .unwrap();
     assert_eq!(client.base_url, "https://example.atlassian.net");
@@ -437,8 +1010,16 @@ mod tests {
 
   #[test]
   fn test_auth_header_format() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 3).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      3,
+      3,
+      25,
+    )
+    .unwrap();
 
     let auth_header = client.auth_header();
     assert!(auth_header.starts_with("Basic "));
@@ -451,16 +1032,89 @@ mod tests {
 
   #[test]
   fn test_confluence_client_rejects_zero_rate_limit() {
-    let client = ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 0);
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      0,
+      3,
+      25,
+    );
     assert!(client.is_err());
   }
 
+  #[test]
+  fn test_confluence_client_rejects_zero_page_size() {
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      0,
+    );
+    assert!(client.is_err());
+  }
+
+  #[test]
+  fn test_body_anomaly() {
+    let ok = reqwest::StatusCode::OK;
+    assert_eq!(body_anomaly(RequestKind::Api, ok, Some(2), b"{}"), None);
+    assert_eq!(body_anomaly(RequestKind::Api, ok, None, b"{}"), None);
+    assert_eq!(
+      body_anomaly(RequestKind::Api, ok, Some(10), b"{\"id\""),
+      Some("Response body has 5 bytes but Content-Length announced 10".to_string())
+    );
+    assert_eq!(
+      body_anomaly(RequestKind::Api, ok, None, b" \n"),
+      Some("Confluence API returned an empty response body with status 200 OK".to_string())
+    );
+    assert_eq!(body_anomaly(RequestKind::Asset, ok, None, b""), None);
+    assert_eq!(
+      body_anomaly(RequestKind::Api, reqwest::StatusCode::NOT_FOUND, None, b""),
+      None
+    );
+  }
+
+  #[test]
+  fn test_truncated_json_error() {
+    let response = ApiResponse {
+      status: reqwest::StatusCode::OK,
+      body: br#"{"id": "123", "title": "Ro"#.to_vec(),
+    };
+    let error = response.json::<Page>().unwrap_err().to_string();
+    assert!(
+      error.starts_with("Response body ended unexpectedly after 26 bytes"),
+      "{error}"
+    );
+  }
+
+  #[test]
+  fn test_retry_delay() {
+    assert_eq!(retry_delay(0, None), Duration::from_secs(1));
+    assert_eq!(retry_delay(3, None), Duration::from_secs(8));
+    assert_eq!(retry_delay(10, None), MAX_RETRY_DELAY);
+    assert_eq!(retry_delay(0, Some(7)), Duration::from_secs(7));
+    assert_eq!(retry_delay(0, Some(3600)), MAX_RETRY_DELAY);
+    assert!(is_retryable_status(reqwest::StatusCode::TOO_MANY_REQUESTS));
+    assert!(!is_retryable_status(reqwest::StatusCode::NOT_FOUND));
+  }
+
   #[tokio::test]
   async fn test_rate_limiter_throttles_requests() {
     let limiter = RequestRateLimiter::new(2, Duration::from_secs(1));
     let start = Instant::now();
 
+    // A full bucket lets a burst through, then refills at 2 tokens per second
+    limiter.acquire().await;
     limiter.acquire().await;
+    assert!(
+      start.elapsed() < Duration::from_millis(200),
+      "expected the burst to pass immediately, got {:?}",
+      start.elapsed()
+    );
     limiter.acquire().await;
     limiter.acquire().await;
 
@@ -471,10 +1125,39 @@ mod tests {
     );
   }
 
+  #[tokio::test]
+  async fn test_rate_limiter_is_shared_by_concurrent_callers() {
+    let limiter = Arc::new(RequestRateLimiter::new(4, Duration::from_secs(1)));
+    let start = Instant::now();
+
+    let callers = (0..8).map(|_| {
+      let limiter = Arc::clone(&limiter);
+      tokio::spawn(async move { limiter.acquire().await })
+    });
+    for caller in futures::future::join_all(callers).await {
+      caller.unwrap();
+    }
+
+    // 4 tokens up front, the other 4 refill over one second
+    assert!(
+      start.elapsed() >= Duration::from_millis(900),
+      "expected at least 900ms elapsed, got {:?}",
+      start.elapsed()
+    );
+  }
+
   #[test]
   fn resolve_attachment_url_handles_absolute_urls() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    )
+    .unwrap();
 
     let absolute = "https://cdn.example.com/files/image.png";
     assert_eq!(client.resolve_attachment_url(absolute), absolute);
@@ -482,8 +1165,16 @@ mod tests {
 
   #[test]
   fn resolve_attachment_url_prefixes_wiki_when_missing() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    )
+    .unwrap();
 
     let relative = "/download/attachments/12345/image.png";
     assert_eq!(
@@ -494,8 +1185,16 @@ mod tests {
 
   #[test]
   fn resolve_attachment_url_keeps_existing_wiki_prefix() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    )
+    .unwrap();
 
     let relative = "/wiki/download/attachments/12345/image.png";
     assert_eq!(
@@ -506,8 +1205,16 @@ mod tests {
 
   #[test]
   fn resolve_pagination_url_prepends_base_for_relative_path() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    )
+    .unwrap();
 
     let relative = "/wiki/rest/api/content/100/child/page?start=25&limit=25";
     assert_eq!(
@@ -518,8 +1225,16 @@ mod tests {
 
   #[test]
   fn resolve_pagination_url_preserves_absolute_https_url() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    )
+    .unwrap();
 
     let absolute = "https://example.atlassian.net/wiki/rest/api/content/100/child/page?start=25&limit=25";
     assert_eq!(client.resolve_pagination_url(absolute), absolute);
@@ -527,10 +1242,197 @@ mod tests {
 
   #[test]
   fn resolve_pagination_url_preserves_absolute_http_url() {
-    let client =
-      ConfluenceClient::new("https://example.atlassian.net", "user@example.com", "test-token", 30, 5).unwrap();
+    let client = ConfluenceClient::new(
+      "https://example.atlassian.net",
+      "user@example.com",
+      "test-token",
+      30,
+      5,
+      3,
+      25,
+    )
+    .unwrap();
 
     let absolute = "http://internal.example.com/wiki/rest/api/content/100/child/page?start=25&limit=25";
     assert_eq!(client.resolve_pagination_url(absolute), absolute);
   }
+
+  #[tokio::test]
+  async fn test_replays_responses_from_cassette() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassette.json");
+    let recorder = Cassette::record(&path);
+    let page = |id: &str| format!(r#"{{"id":"{id}","title":"Page {id}","type":"page","status":"current"}}"#);
+    recorder
+      .store(
+        "/wiki/rest/api/content/1/child/page?limit=1",
+        200,
+        format!(
+          r#"{{"results":[{}],"_links":{{"next":"/wiki/rest/api/content/1/child/page?limit=1&start=1"}}}}"#,
+          page("2")
+        )
+        .as_bytes(),
+      )
+      .unwrap();
+    recorder
+      .store(
+        "/wiki/rest/api/content/1/child/page?limit=1&start=1",
+        200,
+        format!(r#"{{"results":[{}]}}"#, page("3")).as_bytes(),
+      )
+      .unwrap();
+    recorder
+      .store("/wiki/rest/api/content/1/state?status=current", 404, b"")
+      .unwrap();
+
+    // Nothing listens on this address, so every response must come from the cassette
+    let client = ConfluenceClient::new("http://127.0.0.1:9", "", "", 1, 5, 0, 1)
+      .unwrap()
+      .with_cassette(Arc::new(Cassette::replay(&path).unwrap()));
+
+    let children = client.get_child_pages("1").await.unwrap();
+    let ids: Vec<_> = children.iter().map(|page| page.id.as_str()).collect();
+    assert_eq!(ids, ["2", "3"]);
+    assert!(client.get_content_state("1").await.unwrap().is_none());
+    assert!(client.get_user("557058:abc").await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_listings_span_multiple_pages() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cassette.json");
+    let recorder = Cassette::record(&path);
+    let attachment = |id: &str| format!(r#"{{"id":"{id}","title":"{id}.png","type":"attachment"}}"#);
+    let page = |id: &str| format!(r#"{{"id":"{id}","title":"Page {id}","type":"page","status":"current"}}"#);
+    let attachments = "/wiki/rest/api/content/1/child/attachment?limit=2";
+    let children = "/wiki/rest/api/content/1/child/page?limit=2";
+    let responses = [
+      // Attachments follow `next` links
+      (
+        attachments.to_string(),
+        format!(
+          r#"{{"results":[{},{}],"_links":{{"next":"{attachments}&start=2"}}}}"#,
+          attachment("a1"),
+          attachment("a2")
+        ),
+      ),
+      (
+        format!("{attachments}&start=2"),
+        format!(r#"{{"results":[{}]}}"#, attachment("a3")),
+      ),
+      // Child pages come without links, so full pages are followed by offset
+      (
+        children.to_string(),
+        format!(
+          r#"{{"results":[{},{}],"start":0,"limit":2,"size":2}}"#,
+          page("2"),
+          page("3")
+        ),
+      ),
+      (
+        format!("{children}&start=2"),
+        format!(
+          r#"{{"results":[{},{}],"start":2,"limit":2,"size":2}}"#,
+          page("4"),
+          page("5")
+        ),
+      ),
+      (
+        format!("{children}&start=4"),
+        r#"{"results":[],"start":4,"limit":2,"size":0}"#.to_string(),
+      ),
+    ];
+    for (url, body) in responses {
+      recorder.store(&url, 200, body.as_bytes()).unwrap();
+    }
+
+    let client = ConfluenceClient::new("http://127.0.0.1:9", "", "", 1, 5, 0, 2)
+      .unwrap()
+      .with_cassette(Arc::new(Cassette::replay(&path).unwrap()));
+
+    let attachments = client.get_attachments("1").await.unwrap();
+    let ids: Vec<_> = attachments.iter().map(|attachment| attachment.id.as_str()).collect();
+    assert_eq!(ids, ["a1", "a2", "a3"]);
+    let children = client.get_child_pages("1").await.unwrap();
+    let ids: Vec<_> = children.iter().map(|page| page.id.as_str()).collect();
+    assert_eq!(ids, ["2", "3", "4", "5"]);
+  }
+
+  #[tokio::test]
+  async fn test_asset_download_retries_after_timeout() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+      // The first download stalls halfway through the body, the second completes
+      for stall in [true, false] {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        tokio::spawn(async move {
+          let mut request = [0; 1024];
+          let _ = socket.read(&mut request).await;
+          let _ = socket
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\nConnection: close\r\n\r\nfile")
+            .await;
+          if stall {
+            sleep(Duration::from_secs(5)).await;
+          }
+          let _ = socket.write_all(b"data").await;
+        });
+      }
+    });
+
+    let client = ConfluenceClient::new(format!("http://{address}"), "", "", 30, 5, 0, 25)
+      .unwrap()
+      .with_asset_limits(Duration::from_millis(300), 1);
+
+    let body = client.fetch_attachment("/download/attachments/1/a.bin").await.unwrap();
+    assert_eq!(body, b"filedata");
+    server.abort();
+  }
+
+  #[tokio::test]
+  async fn test_download_attachment_streams_to_disk() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+      // The first response is cut short, the second arrives in two chunks
+      for truncated in [true, false] {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = [0; 1024];
+        let _ = socket.read(&mut request).await;
+        let _ = socket
+          .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\nConnection: close\r\n\r\nfile")
+          .await;
+        if !truncated {
+          socket.flush().await.unwrap();
+          sleep(Duration::from_millis(50)).await;
+          let _ = socket.write_all(b"data").await;
+        }
+      }
+    });
+
+    let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let progress: DownloadProgress = {
+      let reported = Arc::clone(&reported);
+      Arc::new(move |_: &Path, written: u64, total: Option<u64>| reported.lock().unwrap().push((written, total)))
+    };
+    let client = ConfluenceClient::new(format!("http://{address}"), "", "", 30, 5, 1, 25)
+      .unwrap()
+      .with_download_buffer(2)
+      .with_download_progress(progress);
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("video.mp4");
+    client
+      .download_attachment("/download/attachments/1/video.mp4", &output)
+      .await
+      .unwrap();
+    assert_eq!(std::fs::read(&output).unwrap(), b"filedata");
+    assert!(!partial_download_path(&output).exists());
+    assert_eq!(reported.lock().unwrap().last(), Some(&(8, Some(8))));
+    server.abort();
+  }
 }
@@ -1,18 +1,26 @@
-//! Confluence module providing API abstractions, the HTTP client, data models,
-//! URL parsing helpers, and higher-level traversal utilities.
+//! Confluence module providing API abstractions, the HTTP client and its
+//! record/replay cassettes, data models, URL parsing helpers, and higher-level
+//! traversal utilities.
 
 pub mod api;
+pub mod cassette;
 pub mod client;
 pub mod models;
 pub mod tree;
 pub mod url;
 
 pub use api::ConfluenceApi;
-pub use client::ConfluenceClient;
+pub use cassette::Cassette;
+pub use client::{ConfluenceClient, DownloadProgress};
 #[allow(unused_imports)]
 pub use models::{
-  Attachment, AttachmentLinks, AttachmentsResponse, ChildPagesResponse, Page, PageBody, PageLinks, PageSpace,
-  PaginationLinks, StorageFormat, UserInfo, ViewFormat,
+  Ancestor, Attachment, AttachmentLinks, AttachmentsResponse, ChildPagesResponse, ContentKind, ContentState,
+  ContentStateResponse, Label, LabelsResponse, Page, PageBody, PageHistory, PageLinks, PageMetadata, PageSpace,
+  PageVersion, PageVersionInfo, PaginationLinks, PlainText, Space, SpaceDescription, SpaceHomepage, SpaceIcon,
+  StorageFormat, User, UserInfo, ViewFormat, WikiFormat,
+};
+pub use tree::{
+  LevelEstimate, PageFilter, PageOrder, PageTree, TreeEstimate, estimate_page_tree, get_page_tree,
+  get_page_tree_with_failures,
 };
-pub use tree::{PageTree, get_page_tree};
 pub use url::{UrlInfo, parse_confluence_url};
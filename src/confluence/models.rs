@@ -21,6 +21,266 @@ pub struct Page {
   #[serde(rename = "_links")]
   /// Useful hyperlinks, including the canonical UI URL.
   pub links: Option<PageLinks>,
+  /// Labels and other metadata, present when `metadata.labels` is expanded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub metadata: Option<PageMetadata>,
+  /// Creation details, present when `history` is expanded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub history: Option<PageHistory>,
+  /// Current version, present when `version` is expanded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub version: Option<PageVersion>,
+  /// Pages above this one, from the top of the space down, present when
+  /// `ancestors` is expanded.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub ancestors: Vec<Ancestor>,
+}
+
+impl Page {
+  /// Names of the labels attached to the page, in API order.
+  ///
+  /// Returns an empty list when labels were not expanded in the response.
+  pub fn labels(&self) -> Vec<&str> {
+    self
+      .metadata
+      .as_ref()
+      .and_then(|metadata| metadata.labels.as_ref())
+      .map(|labels| labels.results.iter().map(|label| label.name.as_str()).collect())
+      .unwrap_or_default()
+  }
+
+  /// Display name of the user who created the page, when `history` was
+  /// expanded in the response.
+  pub fn created_by(&self) -> Option<&str> {
+    self
+      .history
+      .as_ref()
+      .and_then(|history| history.created_by.as_ref())
+      .and_then(|user| user.display_name.as_deref())
+  }
+
+  /// Display name of the user who published the page's current version, when
+  /// `version` was expanded in the response.
+  pub fn last_modified_by(&self) -> Option<&str> {
+    self
+      .version
+      .as_ref()
+      .and_then(|version| version.by.as_ref())
+      .and_then(|user| user.display_name.as_deref())
+  }
+
+  /// Whether the page was created by, or last modified by, the user named by
+  /// `query` (see [`User::matches`]).
+  pub fn is_authored_by(&self, query: &str) -> bool {
+    let creator = self.history.as_ref().and_then(|history| history.created_by.as_ref());
+    let editor = self.version.as_ref().and_then(|version| version.by.as_ref());
+    [creator, editor].into_iter().flatten().any(|user| user.matches(query))
+  }
+
+  /// ISO 8601 timestamp of the page's creation, when `history` was expanded.
+  pub fn created_date(&self) -> Option<&str> {
+    self
+      .history
+      .as_ref()
+      .and_then(|history| history.created_date.as_deref())
+  }
+
+  /// ISO 8601 timestamp of the page's latest version, when
+  /// `history.lastUpdated` was expanded.
+  pub fn last_modified_date(&self) -> Option<&str> {
+    self
+      .history
+      .as_ref()
+      .and_then(|history| history.last_updated.as_ref())
+      .and_then(|version| version.when.as_deref())
+  }
+
+  /// Storage-format XHTML of the page, when the response contains any.
+  ///
+  /// Storage bodies that actually hold legacy wiki markup are not returned;
+  /// see [`Page::legacy_wiki`].
+  pub fn storage_content(&self) -> Option<&str> {
+    self
+      .body
+      .as_ref()
+      .and_then(|body| body.storage.as_ref())
+      .filter(|storage| storage.representation != WIKI_REPRESENTATION && !storage.value.trim().is_empty())
+      .map(|storage| storage.value.as_str())
+  }
+
+  /// Legacy wiki markup of the page, when it has no storage XHTML.
+  ///
+  /// Very old pages may still return their body in the `wiki`
+  /// representation, either as a `wiki` body or labelled as such in place of
+  /// the storage body.
+  pub fn legacy_wiki(&self) -> Option<&str> {
+    if self.storage_content().is_some() {
+      return None;
+    }
+    let body = self.body.as_ref()?;
+    body
+      .wiki
+      .as_ref()
+      .map(|wiki| wiki.value.as_str())
+      .or_else(|| {
+        body
+          .storage
+          .as_ref()
+          .filter(|storage| storage.representation == WIKI_REPRESENTATION)
+          .map(|storage| storage.value.as_str())
+      })
+      .filter(|wiki| !wiki.trim().is_empty())
+  }
+
+  /// Kind of content the record describes, from its content type.
+  pub fn content_kind(&self) -> ContentKind {
+    ContentKind::from_type(&self.page_type)
+  }
+}
+
+/// Kind of content in a page tree.
+///
+/// Whiteboards, databases, and Smart Link embeds sit in the page tree next to
+/// pages, but the API returns them without a storage body, so they cannot be
+/// converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContentKind {
+  /// A page, blog post, or any other content with a storage body.
+  Document,
+  /// A whiteboard.
+  Whiteboard,
+  /// A database.
+  Database,
+  /// A Smart Link embed.
+  Embed,
+}
+
+impl ContentKind {
+  /// Kind of content of the given API content type.
+  pub fn from_type(content_type: &str) -> Self {
+    match content_type {
+      "whiteboard" => Self::Whiteboard,
+      "database" => Self::Database,
+      "embed" => Self::Embed,
+      _ => Self::Document,
+    }
+  }
+
+  /// Whether content of this kind has a body that can be converted.
+  pub fn is_exportable(self) -> bool {
+    self == Self::Document
+  }
+
+  /// Name of the kind for messages, e.g. `whiteboard`.
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Document => "page",
+      Self::Whiteboard => "whiteboard",
+      Self::Database => "database",
+      Self::Embed => "Smart Link embed",
+    }
+  }
+}
+
+/// Representation name of legacy wiki markup bodies.
+const WIKI_REPRESENTATION: &str = "wiki";
+
+/// Page metadata returned through the `metadata` expansion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageMetadata {
+  /// Labels attached to the page.
+  #[serde(default)]
+  pub labels: Option<LabelsResponse>,
+}
+
+/// Labels response wrapper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelsResponse {
+  /// Labels included in the response.
+  pub results: Vec<Label>,
+}
+
+/// A label attached to a page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Label {
+  /// Label name as shown in the UI (always lowercase).
+  pub name: String,
+  /// Label namespace such as `"global"` or `"my"`.
+  #[serde(default)]
+  pub prefix: Option<String>,
+}
+
+/// Page creation details returned through the `history` expansion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageHistory {
+  /// User who created the page.
+  #[serde(rename = "createdBy", default)]
+  pub created_by: Option<User>,
+  /// ISO 8601 timestamp of the page's creation.
+  #[serde(rename = "createdDate", default)]
+  pub created_date: Option<String>,
+  /// Latest version of the page, present when `history.lastUpdated` is expanded.
+  #[serde(rename = "lastUpdated", default, skip_serializing_if = "Option::is_none")]
+  pub last_updated: Option<PageVersionInfo>,
+}
+
+/// Summary of a page version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageVersionInfo {
+  /// ISO 8601 timestamp of the version.
+  #[serde(default)]
+  pub when: Option<String>,
+}
+
+/// Version details returned through the `version` expansion.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageVersion {
+  /// Version number, starting at 1 for the first published version.
+  pub number: u32,
+  /// ISO 8601 timestamp of the version.
+  #[serde(default)]
+  pub when: Option<String>,
+  /// Comment entered when the version was published.
+  #[serde(default)]
+  pub message: Option<String>,
+  /// User who published the version.
+  #[serde(default)]
+  pub by: Option<User>,
+}
+
+/// A page above another in the page tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ancestor {
+  /// Page identifier.
+  pub id: String,
+  /// Page title.
+  pub title: String,
+}
+
+/// A user referenced from content metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct User {
+  /// Stable Atlassian account identifier.
+  #[serde(rename = "accountId", default)]
+  pub account_id: Option<String>,
+  /// Full display name configured in the Atlassian profile.
+  #[serde(rename = "displayName", default)]
+  pub display_name: Option<String>,
+  /// Email address, when the user's profile visibility allows it.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub email: Option<String>,
+}
+
+impl User {
+  /// Whether the user is the one named by `query`: an email address, account
+  /// ID, or display name, compared case-insensitively.
+  pub fn matches(&self, query: &str) -> bool {
+    let query = query.trim();
+    [&self.email, &self.account_id, &self.display_name]
+      .into_iter()
+      .flatten()
+      .any(|value| value.eq_ignore_ascii_case(query))
+  }
 }
 
 /// Page body content in various formats.
@@ -30,6 +290,9 @@ pub struct PageBody {
   pub storage: Option<StorageFormat>,
   /// Rendered HTML view supplied by the API when expanded.
   pub view: Option<ViewFormat>,
+  /// Legacy wiki markup returned for very old pages.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub wiki: Option<WikiFormat>,
 }
 
 /// Storage format (Confluence's internal format).
@@ -50,6 +313,15 @@ pub struct ViewFormat {
   pub representation: String,
 }
 
+/// Legacy wiki markup format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WikiFormat {
+  /// Raw wiki markup, e.g. `h1. Title`.
+  pub value: String,
+  /// Representation name (typically `"wiki"`).
+  pub representation: String,
+}
+
 /// Space information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageSpace {
@@ -62,6 +334,72 @@ pub struct PageSpace {
   pub space_type: String,
 }
 
+/// Space details returned by the space endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Space {
+  /// Short key that uniquely identifies the space.
+  pub key: String,
+  /// Human-readable space name.
+  pub name: String,
+  #[serde(rename = "type")]
+  /// Space classification such as `"global"` or `"personal"`.
+  pub space_type: String,
+  /// Space description, present when `description.plain` is expanded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub description: Option<SpaceDescription>,
+  /// Space logo, present when `icon` is expanded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub icon: Option<SpaceIcon>,
+  /// The space's home page, present when `homepage` is expanded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub homepage: Option<SpaceHomepage>,
+}
+
+impl Space {
+  /// The plain-text description, when the space has a non-empty one.
+  pub fn description_text(&self) -> Option<&str> {
+    self
+      .description
+      .as_ref()
+      .and_then(|description| description.plain.as_ref())
+      .map(|plain| plain.value.trim())
+      .filter(|value| !value.is_empty())
+  }
+}
+
+/// Space description in the requested representations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceDescription {
+  /// Plain-text description.
+  pub plain: Option<PlainText>,
+}
+
+/// Plain-text value of a description.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlainText {
+  /// The text.
+  pub value: String,
+}
+
+/// Space logo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceIcon {
+  /// Download path of the logo image, relative to the instance.
+  pub path: String,
+  /// Whether this is Confluence's generic logo rather than an uploaded one.
+  #[serde(rename = "isDefault", default)]
+  pub is_default: bool,
+}
+
+/// Home page of a space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpaceHomepage {
+  /// Page ID of the home page.
+  pub id: String,
+  /// Title of the home page.
+  pub title: String,
+}
+
 /// Page links.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PageLinks {
@@ -116,6 +454,12 @@ pub struct AttachmentsResponse {
   /// Number of items returned in this page.
   #[serde(default)]
   pub size: usize,
+  /// Offset of the first item of this page within the whole listing.
+  #[serde(default)]
+  pub start: usize,
+  /// Maximum number of items per page, as applied by the server.
+  #[serde(default)]
+  pub limit: usize,
   /// Pagination links for traversing result pages.
   #[serde(rename = "_links")]
   pub links: Option<PaginationLinks>,
@@ -129,11 +473,36 @@ pub struct ChildPagesResponse {
   /// Number of items returned in this page.
   #[serde(default)]
   pub size: usize,
+  /// Offset of the first item of this page within the whole listing.
+  #[serde(default)]
+  pub start: usize,
+  /// Maximum number of items per page, as applied by the server.
+  #[serde(default)]
+  pub limit: usize,
   /// Pagination links for traversing result pages.
   #[serde(rename = "_links")]
   pub links: Option<PaginationLinks>,
 }
 
+/// Content state ("Draft", "In review", "Verified") shown next to a page
+/// title in Confluence Cloud.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentState {
+  /// Display name of the state.
+  pub name: String,
+  /// Hex color of the state chip, such as `"#ffc400"`.
+  #[serde(default)]
+  pub color: Option<String>,
+}
+
+/// Content state response wrapper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentStateResponse {
+  /// The page's state; `null` when none is set.
+  #[serde(rename = "contentState", default)]
+  pub content_state: Option<ContentState>,
+}
+
 /// User information from authentication test.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserInfo {
@@ -201,6 +570,47 @@ mod tests {
     assert!(response.links.is_none());
   }
 
+  #[test]
+  fn page_labels_deserialize_from_metadata_expansion() {
+    let json = serde_json::json!({
+      "id": "1",
+      "title": "API Guide",
+      "type": "page",
+      "status": "current",
+      "metadata": {
+        "labels": {
+          "results": [
+            {"prefix": "global", "name": "public-docs", "id": "10"},
+            {"prefix": "global", "name": "api", "id": "11"}
+          ],
+          "size": 2
+        }
+      }
+    });
+
+    let page: Page = serde_json::from_value(json).unwrap();
+    assert_eq!(page.labels(), ["public-docs", "api"]);
+
+    let page: Page = serde_json::from_value(serde_json::json!({
+      "id": "2", "title": "Plain", "type": "page", "status": "current"
+    }))
+    .unwrap();
+    assert!(page.labels().is_empty());
+  }
+
+  #[test]
+  fn content_state_response_deserializes_with_and_without_state() {
+    let json = serde_json::json!({
+      "contentState": {"id": 7, "name": "In review", "color": "#ffc400"},
+      "lastUpdated": "2024-05-01T10:00:00.000Z"
+    });
+    let response: ContentStateResponse = serde_json::from_value(json).unwrap();
+    assert_eq!(response.content_state.unwrap().name, "In review");
+
+    let response: ContentStateResponse = serde_json::from_value(serde_json::json!({"contentState": null})).unwrap();
+    assert!(response.content_state.is_none());
+  }
+
   #[test]
   fn attachments_response_deserializes_with_pagination() {
     let json = serde_json::json!({
@@ -1,13 +1,13 @@
 //! Utilities for traversing Confluence page hierarchies.
 
 use std::collections::HashSet;
-use std::future::Future;
-use std::pin::Pin;
-use std::sync::Arc;
 
 use anyhow::{Result, anyhow};
+use chrono::{DateTime, FixedOffset, Utc};
+use clap::ValueEnum;
 use futures::future::join_all;
-use tokio::sync::Mutex;
+use futures::{StreamExt, stream};
+use tracing::{debug, warn};
 
 use super::api::ConfluenceApi;
 use super::models::Page;
@@ -21,113 +21,490 @@ pub struct PageTree {
   pub children: Vec<PageTree>,
   /// Zero-based depth where `0` is the original root.
   pub depth: usize,
+  /// Whether the page itself is exported. Pages left out by a [`PageFilter`]
+  /// stay in the tree without being exported when they have kept
+  /// descendants, so those keep the directories they have in Confluence.
+  pub exported: bool,
 }
 
-/// Build a page tree recursively from a root page.
+impl PageTree {
+  /// Sort the children of every node in the tree.
+  ///
+  /// [`PageOrder::Position`] keeps the API order, which follows the manual
+  /// ordering set in Confluence. The other orders are stable, so pages that
+  /// compare equal (or lack the date being sorted on, which sort last) keep
+  /// their relative position.
+  pub fn sort(&mut self, order: PageOrder) {
+    match order {
+      PageOrder::Position => return,
+      PageOrder::Title => self
+        .children
+        .sort_by_cached_key(|child| child.page.title.to_lowercase()),
+      PageOrder::Created => sort_by_date(&mut self.children, Page::created_date),
+      PageOrder::Modified => sort_by_date(&mut self.children, Page::last_modified_date),
+    }
+    for child in &mut self.children {
+      child.sort(order);
+    }
+  }
+}
+
+/// Sort trees oldest first by a page timestamp, putting missing dates last.
+fn sort_by_date(trees: &mut [PageTree], date: fn(&Page) -> Option<&str>) {
+  trees.sort_by_cached_key(|tree| {
+    let parsed = date(&tree.page).and_then(|value| DateTime::<FixedOffset>::parse_from_rfc3339(value).ok());
+    (parsed.is_none(), parsed)
+  });
+}
+
+/// Order of sibling pages in an exported tree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PageOrder {
+  /// Confluence's manual ordering, as returned by the API
+  #[default]
+  Position,
+  /// Alphabetical by title, ignoring case
+  Title,
+  /// Oldest created first
+  Created,
+  /// Least recently modified first
+  Modified,
+}
+
+/// Criteria selecting which pages of a tree are exported.
+///
+/// The root page is always kept. A page carrying an excluded label, matching
+/// an excluded title glob, or listed in the excluded IDs is dropped together
+/// with its subtree, which is never fetched; title and ID exclusions are
+/// checked against the child listing so the page itself is not fetched
+/// either. A page without any of the included labels, or whose title matches
+/// none of the included globs, is not exported, but its descendants are still
+/// visited; when any of them is kept, the page stays in the tree as an
+/// unexported node so their output paths mirror the Confluence hierarchy.
+///
+/// Title globs are matched case-insensitively against the whole title, with
+/// `*` matching any run of characters and `?` a single character.
+///
+/// Pages not created or last modified by one of the authors, and pages last
+/// modified outside the modified-date window, are left out the same way as
+/// pages without an included label, since the authors and age of a page say
+/// nothing about those of its children. Pages without a modification date
+/// are kept.
+#[derive(Debug, Clone, Default)]
+pub struct PageFilter {
+  /// Keep only pages with at least one of these labels (all pages when empty).
+  pub include_labels: Vec<String>,
+  /// Skip pages with any of these labels, including their descendants.
+  pub exclude_labels: Vec<String>,
+  /// Keep only pages whose title matches one of these globs (all pages when empty).
+  pub include_titles: Vec<String>,
+  /// Skip pages whose title matches any of these globs, including their descendants.
+  pub exclude_titles: Vec<String>,
+  /// Skip pages with these IDs, including their descendants.
+  pub exclude_ids: Vec<String>,
+  /// Keep only pages created or last modified by one of these users, named by
+  /// email address, account ID, or display name (all pages when empty).
+  pub authors: Vec<String>,
+  /// Keep only pages last modified at or after this time.
+  pub modified_since: Option<DateTime<Utc>>,
+  /// Keep only pages last modified at or before this time.
+  pub modified_until: Option<DateTime<Utc>>,
+}
+
+impl PageFilter {
+  /// Whether the filter keeps every page.
+  pub fn is_empty(&self) -> bool {
+    self.include_labels.is_empty()
+      && self.exclude_labels.is_empty()
+      && self.include_titles.is_empty()
+      && self.exclude_titles.is_empty()
+      && self.exclude_ids.is_empty()
+      && self.authors.is_empty()
+      && self.modified_since.is_none()
+      && self.modified_until.is_none()
+  }
+
+  /// Whether a page from a child listing should be skipped with its subtree
+  /// before it is fetched.
+  fn prunes_listing(&self, page: &Page) -> bool {
+    self.exclude_ids.contains(&page.id) || self.exclude_titles.iter().any(|glob| glob_matches(glob, &page.title))
+  }
+
+  /// Whether the page and its subtree should be skipped entirely.
+  fn prunes(&self, page: &Page) -> bool {
+    self.prunes_listing(page)
+      || page.labels().iter().any(|label| {
+        self
+          .exclude_labels
+          .iter()
+          .any(|excluded| excluded.eq_ignore_ascii_case(label))
+      })
+  }
+
+  /// Whether the page itself should be exported.
+  fn keeps(&self, page: &Page) -> bool {
+    let label_matches = self.include_labels.is_empty()
+      || page.labels().iter().any(|label| {
+        self
+          .include_labels
+          .iter()
+          .any(|included| included.eq_ignore_ascii_case(label))
+      });
+    let title_matches =
+      self.include_titles.is_empty() || self.include_titles.iter().any(|glob| glob_matches(glob, &page.title));
+    let author_matches = self.authors.is_empty() || self.authors.iter().any(|author| page.is_authored_by(author));
+    label_matches && title_matches && author_matches && self.modified_in_window(page)
+  }
+
+  /// Whether the page was last modified inside the modified-date window.
+  fn modified_in_window(&self, page: &Page) -> bool {
+    if self.modified_since.is_none() && self.modified_until.is_none() {
+      return true;
+    }
+    let modified = page
+      .last_modified_date()
+      .or_else(|| page.version.as_ref().and_then(|version| version.when.as_deref()))
+      .and_then(|when| DateTime::parse_from_rfc3339(when).ok());
+    let Some(modified) = modified else {
+      debug!("Page {} has no modification date; keeping it", page.id);
+      return true;
+    };
+    self.modified_since.is_none_or(|since| modified >= since)
+      && self.modified_until.is_none_or(|until| modified <= until)
+  }
+}
+
+/// Case-insensitive glob match of a whole string supporting `*` and `?`.
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+  let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+  let text: Vec<char> = text.to_lowercase().chars().collect();
+
+  // Greedy matching with backtracking to the most recent `*`
+  let (mut p, mut t) = (0, 0);
+  let mut star: Option<(usize, usize)> = None;
+  while t < text.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+      p += 1;
+      t += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      star = Some((p, t));
+      p += 1;
+    } else if let Some((star_p, star_t)) = star {
+      p = star_p + 1;
+      t = star_t + 1;
+      star = Some((star_p, star_t + 1));
+    } else {
+      return false;
+    }
+  }
+  pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Build a page tree from a root page.
 ///
-/// This function traverses the page hierarchy starting from a root page,
-/// downloading child pages up to the specified maximum depth.
+/// The hierarchy is walked breadth-first: the pages of each level and their
+/// child listings are fetched concurrently, up to `parallel` pages at a time,
+/// with every request still passing through the client's rate limiter. A
+/// child that fails to fetch, or that was already visited through another
+/// parent, is skipped with a warning.
 ///
 /// # Arguments
 /// * `client` - API implementation used for fetching page and child metadata.
 /// * `page_id` - Identifier of the root page to use as the tree entry point.
 /// * `max_depth` - Optional maximum depth; `None` fetches the entire hierarchy.
+/// * `filter` - Criteria for the descendants to keep; see [`PageFilter`].
+/// * `parallel` - Maximum number of pages fetched at the same time.
 ///
 /// # Returns
 /// A [`PageTree`] containing the root page and any fetched children.
 ///
 /// # Errors
-/// Returns an error if fetching the page tree encounters a failure, or if a
-/// circular reference is detected.
-pub async fn get_page_tree(client: &dyn ConfluenceApi, page_id: &str, max_depth: Option<usize>) -> Result<PageTree> {
-  get_page_tree_recursive(
-    client,
-    page_id.to_string(),
-    0,
-    max_depth,
-    Arc::new(Mutex::new(HashSet::new())),
-  )
-  .await
+/// Returns an error if the root page or its child listing cannot be fetched.
+pub async fn get_page_tree(
+  client: &dyn ConfluenceApi,
+  page_id: &str,
+  max_depth: Option<usize>,
+  filter: &PageFilter,
+  parallel: usize,
+) -> Result<PageTree> {
+  let (tree, _) = get_page_tree_with_failures(client, page_id, max_depth, filter, parallel).await?;
+  Ok(tree)
 }
 
-/// Recursive helper that builds the page tree while tracking visited nodes.
+/// Build a page tree from a root page like [`get_page_tree`], also returning
+/// the IDs of the children that failed to fetch and were left out with their
+/// subtrees.
 ///
-/// # Arguments
-/// * `client` - API implementation used for fetching page data.
-/// * `page_id` - Current page being processed.
-/// * `current_depth` - Depth of the current page in the traversal.
-/// * `max_depth` - Optional maximum depth; `None` fetches until pages are exhausted.
-/// * `visited` - Set of page IDs already seen, used to detect cycles.
+/// # Errors
+/// Returns an error if the root page or its child listing cannot be fetched.
+pub async fn get_page_tree_with_failures(
+  client: &dyn ConfluenceApi,
+  page_id: &str,
+  max_depth: Option<usize>,
+  filter: &PageFilter,
+  parallel: usize,
+) -> Result<(PageTree, Vec<String>)> {
+  let (root, child_pages) = fetch_node(client, page_id, 0, max_depth, filter)
+    .await?
+    .ok_or_else(|| anyhow!("Root page {page_id} was filtered out"))?;
+  let mut nodes = vec![TreeNode {
+    page: root,
+    depth: 0,
+    children: Vec::new(),
+  }];
+  let mut visited = HashSet::from([page_id.to_string()]);
+  let mut frontier = frontier_entries(0, child_pages, filter, &mut visited);
+  let mut failed = Vec::new();
+
+  while !frontier.is_empty() {
+    let results: Vec<_> = stream::iter(&frontier)
+      .map(|(parent, child_id)| {
+        let depth = nodes[*parent].depth + 1;
+        async move { (depth, fetch_node(client, child_id, depth, max_depth, filter).await) }
+      })
+      .buffered(parallel.max(1))
+      .collect()
+      .await;
+
+    let mut next = Vec::new();
+    for ((parent, child_id), (depth, result)) in frontier.into_iter().zip(results) {
+      match result {
+        Ok(Some((page, child_pages))) => {
+          let index = nodes.len();
+          nodes.push(TreeNode {
+            page,
+            depth,
+            children: Vec::new(),
+          });
+          nodes[parent].children.push(index);
+          next.extend(frontier_entries(index, child_pages, filter, &mut visited));
+        }
+        Ok(None) => {}
+        Err(e) => {
+          warn!("Failed to fetch child page {child_id}: {e:#}");
+          failed.push(child_id);
+        }
+      }
+    }
+    frontier = next;
+  }
+
+  let mut slots: Vec<_> = nodes.into_iter().map(Some).collect();
+  // The root is always kept
+  let tree = assemble(&mut slots, 0, filter).ok_or_else(|| anyhow!("Root page {page_id} was filtered out"))?;
+  Ok((tree, failed))
+}
+
+/// A fetched page whose children are indexes into the list of fetched pages.
+struct TreeNode {
+  page: Page,
+  depth: usize,
+  children: Vec<usize>,
+}
+
+/// Fetch a page and, above `max_depth`, its child listing.
 ///
 /// # Returns
-/// A future that resolves to the [`PageTree`] for the provided page.
+/// `None` when the page is pruned by the filter, together with its subtree.
 ///
 /// # Errors
-/// Returns an error if a cycle is detected or if API calls fail.
-fn get_page_tree_recursive<'a>(
-  client: &'a dyn ConfluenceApi,
-  page_id: String,
-  current_depth: usize,
+/// Returns an error if API calls fail.
+async fn fetch_node(
+  client: &dyn ConfluenceApi,
+  page_id: &str,
+  depth: usize,
   max_depth: Option<usize>,
-  visited: Arc<Mutex<HashSet<String>>>,
-) -> Pin<Box<dyn Future<Output = Result<PageTree>> + Send + 'a>> {
-  Box::pin(async move {
-    {
-      let mut vis = visited.lock().await;
-      if vis.contains(&page_id) {
-        return Err(anyhow!("Circular reference detected: page {page_id} already visited"));
-      }
-      vis.insert(page_id.clone());
-    }
-
-    let page = client.get_page(&page_id).await?;
-
-    let children = if max_depth.is_none() || current_depth < max_depth.unwrap() {
-      let child_pages = client.get_child_pages(&page_id).await?;
-
-      let child_futures: Vec<_> = child_pages
-        .into_iter()
-        .map(|child_page| {
-          let child_id = child_page.id.clone();
-          let visited = Arc::clone(&visited);
-          async move {
-            let result = get_page_tree_recursive(client, child_page.id, current_depth + 1, max_depth, visited).await;
-            (child_id, result)
-          }
-        })
-        .collect();
-
-      let results = join_all(child_futures).await;
-      let mut child_trees = Vec::new();
-      for (child_id, result) in results {
-        match result {
-          Ok(child_tree) => child_trees.push(child_tree),
-          Err(e) => eprintln!("Warning: Failed to fetch child page {child_id}: {e}"),
-        }
+  filter: &PageFilter,
+) -> Result<Option<(Page, Vec<Page>)>> {
+  let page = client.get_page(page_id).await?;
+  if depth > 0 && filter.prunes(&page) {
+    return Ok(None);
+  }
+  let child_pages = if max_depth.is_none_or(|max_depth| depth < max_depth) {
+    client.get_child_pages(page_id).await?
+  } else {
+    Vec::new()
+  };
+  Ok(Some((page, child_pages)))
+}
+
+/// Pair the children of a fetched page with its index for the next level,
+/// dropping pruned children and pages already visited through another parent.
+fn frontier_entries(
+  parent: usize,
+  child_pages: Vec<Page>,
+  filter: &PageFilter,
+  visited: &mut HashSet<String>,
+) -> Vec<(usize, String)> {
+  child_pages
+    .into_iter()
+    .filter(|child_page| !filter.prunes_listing(child_page))
+    .filter_map(|child_page| {
+      if visited.insert(child_page.id.clone()) {
+        Some((parent, child_page.id))
+      } else {
+        warn!(
+          "Failed to fetch child page {}: Circular reference detected: page {} already visited",
+          child_page.id, child_page.id
+        );
+        None
       }
+    })
+    .collect()
+}
+
+/// Build the tree of a fetched page, or `None` when neither the page nor any
+/// of its descendants is kept.
+///
+/// A page filtered out itself stays as an unexported node above its kept
+/// descendants.
+fn assemble(nodes: &mut [Option<TreeNode>], index: usize, filter: &PageFilter) -> Option<PageTree> {
+  let node = nodes[index].take().expect("every page has a single parent");
+  let children: Vec<PageTree> = node
+    .children
+    .iter()
+    .filter_map(|&child| assemble(nodes, child, filter))
+    .collect();
+
+  let exported = node.depth == 0 || filter.keeps(&node.page);
+  if !exported && children.is_empty() {
+    return None;
+  }
+  Some(PageTree {
+    page: node.page,
+    children,
+    depth: node.depth,
+    exported,
+  })
+}
+
+/// Estimated page count at one depth of a sampled tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelEstimate {
+  /// Estimated number of pages at this depth.
+  pub pages: f64,
+  /// Whether every page at this depth was observed rather than extrapolated.
+  pub exact: bool,
+}
 
-      child_trees
+/// Result of [`estimate_page_tree`].
+#[derive(Debug, Clone)]
+pub struct TreeEstimate {
+  /// The root page.
+  pub root: Page,
+  /// Per-depth estimates, starting with the root at depth 0.
+  pub levels: Vec<LevelEstimate>,
+  /// Number of API requests spent sampling, counting each page of a
+  /// paginated child listing.
+  pub requests: usize,
+  /// Whether the tree may extend past the last level: either the request
+  /// budget ran out while deeper pages were still expected, or the last
+  /// level was only sampled, so unsampled pages may have children.
+  pub truncated: bool,
+}
+
+impl TreeEstimate {
+  /// Estimated total number of pages across all sampled levels.
+  pub fn total_pages(&self) -> f64 {
+    self.levels.iter().map(|level| level.pages).sum()
+  }
+}
+
+/// Estimate the size and shape of a page tree without walking all of it.
+///
+/// Levels are sampled breadth-first. At each depth an evenly spaced subset of
+/// the known pages is expanded, and the mean number of children per expanded
+/// page extrapolates the size of the next level. Levels small enough to
+/// expand completely are counted exactly. Requests still pass through the
+/// client's rate limiter.
+///
+/// Every page of a paginated child listing counts as a request. Since the
+/// number of pages is only known once a listing is fetched, the last level
+/// may overrun the budget by the extra pages of its listings.
+///
+/// # Arguments
+/// * `client` - API implementation used for fetching page data.
+/// * `page_id` - Identifier of the root page.
+/// * `max_requests` - Budget of API requests, including the root page fetch.
+/// * `page_size` - Number of results per request of child listings.
+///
+/// # Returns
+/// A [`TreeEstimate`] with per-depth page counts.
+///
+/// # Errors
+/// Returns an error if the root page or a child listing cannot be fetched.
+pub async fn estimate_page_tree(
+  client: &dyn ConfluenceApi,
+  page_id: &str,
+  max_requests: usize,
+  page_size: usize,
+) -> Result<TreeEstimate> {
+  let root = client.get_page(page_id).await?;
+  let mut requests = 1;
+  let mut levels = vec![LevelEstimate {
+    pages: 1.0,
+    exact: true,
+  }];
+  let mut frontier = vec![root.id.clone()];
+
+  loop {
+    let remaining = max_requests.saturating_sub(requests);
+    if remaining == 0 {
+      return Ok(TreeEstimate {
+        root,
+        levels,
+        requests,
+        truncated: !frontier.is_empty(),
+      });
+    }
+
+    // Spend at most half the remaining budget per level so deeper levels
+    // still get sampled.
+    let sample_size = frontier.len().min(remaining.div_ceil(2));
+    let sample: Vec<&String> = (0..sample_size)
+      .map(|i| &frontier[i * frontier.len() / sample_size])
+      .collect();
+    let results = join_all(sample.iter().map(|id| client.get_child_pages(id))).await;
+
+    let mut children = Vec::new();
+    for result in results {
+      let listing = result?;
+      requests += listing.len().div_ceil(page_size.max(1)).max(1);
+      children.extend(listing.into_iter().map(|page| page.id));
+    }
+
+    let sampled = sample_size < frontier.len();
+    if children.is_empty() {
+      return Ok(TreeEstimate {
+        root,
+        levels,
+        requests,
+        truncated: sampled,
+      });
+    }
+
+    let current = levels.last().expect("root level is always present");
+    let exact = current.exact && !sampled;
+    let pages = if exact {
+      children.len() as f64
     } else {
-      Vec::new()
+      current.pages * children.len() as f64 / sample_size as f64
     };
-
-    Ok(PageTree {
-      page,
-      children,
-      depth: current_depth,
-    })
-  })
+    levels.push(LevelEstimate { pages, exact });
+    frontier = children;
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use std::collections::HashMap;
   use std::path::Path;
+  use std::sync::atomic::{AtomicUsize, Ordering};
 
   use async_trait::async_trait;
 
   use super::*;
-  use crate::confluence::models::{Attachment, PageBody, StorageFormat, UserInfo};
+  use crate::confluence::models::{Attachment, ContentState, PageBody, Space, StorageFormat, UserInfo};
 
   /// A fake client with a configurable number of children per page,
   /// used to verify that `get_page_tree` works when the underlying
@@ -136,6 +513,11 @@ mod tests {
   struct ManyChildrenClient {
     pages: HashMap<String, Page>,
     children: HashMap<String, Vec<String>>,
+    /// Pages still listed as children that fail to fetch.
+    unavailable: HashSet<String>,
+    fetched: std::sync::Mutex<Vec<String>>,
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
   }
 
   impl ManyChildrenClient {
@@ -143,6 +525,10 @@ mod tests {
       Self {
         pages: HashMap::new(),
         children: HashMap::new(),
+        unavailable: HashSet::new(),
+        fetched: std::sync::Mutex::new(Vec::new()),
+        in_flight: AtomicUsize::new(0),
+        max_in_flight: AtomicUsize::new(0),
       }
     }
 
@@ -160,29 +546,60 @@ mod tests {
               representation: "storage".to_string(),
             }),
             view: None,
+            wiki: None,
           }),
           space: None,
           links: None,
+          metadata: None,
+          history: None,
+          version: None,
+          ancestors: Vec::new(),
         },
       );
     }
 
+    fn set_labels(&mut self, id: &str, labels: &[&str]) {
+      let results: Vec<_> = labels.iter().map(|name| serde_json::json!({ "name": name })).collect();
+      self.pages.get_mut(id).unwrap().metadata =
+        Some(serde_json::from_value(serde_json::json!({ "labels": { "results": results } })).unwrap());
+    }
+
     fn set_children(&mut self, parent_id: &str, child_ids: Vec<String>) {
       self.children.insert(parent_id.to_string(), child_ids);
     }
+
+    fn set_author(&mut self, id: &str, email: &str) {
+      self.pages.get_mut(id).unwrap().history =
+        Some(serde_json::from_value(serde_json::json!({ "createdBy": { "email": email } })).unwrap());
+    }
+
+    fn set_modified(&mut self, id: &str, when: &str) {
+      self.pages.get_mut(id).unwrap().history =
+        Some(serde_json::from_value(serde_json::json!({ "lastUpdated": { "when": when } })).unwrap());
+    }
   }
 
   #[async_trait]
   impl ConfluenceApi for ManyChildrenClient {
-    async fn get_page(&self, page_id: &str) -> Result<Page> {
+    async fn get_page(&self, page_id: &str) -> crate::Result<Page> {
+      self.fetched.lock().unwrap().push(page_id.to_string());
+      let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+      self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+      tokio::task::yield_now().await;
+      self.in_flight.fetch_sub(1, Ordering::SeqCst);
       self
         .pages
         .get(page_id)
+        .filter(|_| !self.unavailable.contains(page_id))
         .cloned()
-        .ok_or_else(|| anyhow!("page not found: {page_id}"))
+        .ok_or_else(|| crate::Error::from_status(404, format!("page not found: {page_id}")))
     }
 
-    async fn get_child_pages(&self, page_id: &str) -> Result<Vec<Page>> {
+    async fn get_page_version(&self, page_id: &str, _version: u32) -> crate::Result<Page> {
+      self.get_page(page_id).await
+    }
+
+    async fn get_child_pages(&self, page_id: &str) -> crate::Result<Vec<Page>> {
       let ids = self.children.get(page_id).cloned().unwrap_or_default();
       let mut pages = Vec::new();
       for id in ids {
@@ -193,19 +610,38 @@ mod tests {
       Ok(pages)
     }
 
-    async fn get_attachments(&self, _page_id: &str) -> Result<Vec<Attachment>> {
+    async fn get_content_state(&self, _page_id: &str) -> crate::Result<Option<ContentState>> {
+      Ok(None)
+    }
+
+    async fn get_attachments(&self, _page_id: &str) -> crate::Result<Vec<Attachment>> {
       Ok(Vec::new())
     }
 
-    async fn download_attachment(&self, _url: &str, _output_path: &Path) -> Result<()> {
+    async fn download_attachment(&self, _url: &str, _output_path: &Path) -> crate::Result<()> {
       Ok(())
     }
 
-    async fn fetch_attachment(&self, _url: &str) -> Result<Vec<u8>> {
+    async fn fetch_attachment(&self, _url: &str) -> crate::Result<Vec<u8>> {
       Ok(Vec::new())
     }
 
-    async fn test_auth(&self) -> Result<UserInfo> {
+    async fn get_user(&self, account_id: &str) -> crate::Result<UserInfo> {
+      Err(crate::Error::from_status(404, format!("user not found: {account_id}")))
+    }
+
+    async fn get_space(&self, space_key: &str) -> crate::Result<Space> {
+      Err(crate::Error::from_status(404, format!("space not found: {space_key}")))
+    }
+
+    async fn find_page(&self, space_key: &str, title: &str) -> crate::Result<Option<Page>> {
+      Err(crate::Error::from_status(
+        404,
+        format!("page not found: {space_key}/{title}"),
+      ))
+    }
+
+    async fn test_auth(&self) -> crate::Result<UserInfo> {
       Ok(UserInfo {
         account_id: "test".to_string(),
         email: None,
@@ -227,7 +663,9 @@ mod tests {
     }
     client.set_children("root", child_ids);
 
-    let tree = get_page_tree(&client, "root", None).await.unwrap();
+    let tree = get_page_tree(&client, "root", None, &PageFilter::default(), 4)
+      .await
+      .unwrap();
     assert_eq!(tree.children.len(), 30);
     assert_eq!(tree.page.title, "Root");
     assert_eq!(tree.depth, 0);
@@ -238,6 +676,35 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn get_page_tree_bounds_concurrent_fetches() {
+    let mut client = ManyChildrenClient::new();
+    client.add_page("root", "Root");
+    let child_ids: Vec<String> = (0..10).map(|i| format!("child-{i}")).collect();
+    for id in &child_ids {
+      client.add_page(id, id);
+      client.add_page(&format!("{id}-leaf"), "Leaf");
+      client.set_children(id, vec![format!("{id}-leaf")]);
+    }
+    client.set_children("root", child_ids.clone());
+
+    let tree = get_page_tree(&client, "root", None, &PageFilter::default(), 3)
+      .await
+      .unwrap();
+    let max_in_flight = client.max_in_flight.load(Ordering::SeqCst);
+    assert!((2..=3).contains(&max_in_flight), "{max_in_flight}");
+
+    // Siblings keep the listing order regardless of completion order
+    let children: Vec<_> = tree.children.iter().map(|child| child.page.id.clone()).collect();
+    assert_eq!(children, child_ids);
+    assert!(
+      tree
+        .children
+        .iter()
+        .all(|child| child.children.len() == 1 && child.children[0].depth == 2)
+    );
+  }
+
   #[tokio::test]
   async fn get_page_tree_respects_max_depth() {
     let mut client = ManyChildrenClient::new();
@@ -248,20 +715,276 @@ mod tests {
     client.set_children("child", vec!["grandchild".to_string()]);
 
     // Depth 0 should only return root, no children
-    let tree = get_page_tree(&client, "root", Some(0)).await.unwrap();
+    let tree = get_page_tree(&client, "root", Some(0), &PageFilter::default(), 4)
+      .await
+      .unwrap();
     assert_eq!(tree.children.len(), 0);
 
     // Depth 1 should return root + child, but not grandchild
-    let tree = get_page_tree(&client, "root", Some(1)).await.unwrap();
+    let tree = get_page_tree(&client, "root", Some(1), &PageFilter::default(), 4)
+      .await
+      .unwrap();
     assert_eq!(tree.children.len(), 1);
     assert_eq!(tree.children[0].children.len(), 0);
 
     // No limit should return all
-    let tree = get_page_tree(&client, "root", None).await.unwrap();
+    let tree = get_page_tree(&client, "root", None, &PageFilter::default(), 4)
+      .await
+      .unwrap();
     assert_eq!(tree.children.len(), 1);
     assert_eq!(tree.children[0].children.len(), 1);
   }
 
+  #[tokio::test]
+  async fn get_page_tree_filters_by_label() {
+    let mut client = ManyChildrenClient::new();
+    for id in ["root", "guides", "setup", "internal", "draft", "draft-child"] {
+      client.add_page(id, id);
+    }
+    client.set_children("root", vec!["guides".to_string(), "draft".to_string()]);
+    client.set_children("guides", vec!["setup".to_string(), "internal".to_string()]);
+    client.set_children("draft", vec!["draft-child".to_string()]);
+    client.set_labels("setup", &["public-docs"]);
+    client.set_labels("draft", &["Draft"]);
+    client.set_labels("draft-child", &["public-docs"]);
+
+    let filter = PageFilter {
+      include_labels: vec!["public-docs".to_string()],
+      exclude_labels: vec!["draft".to_string()],
+      ..Default::default()
+    };
+    let tree = get_page_tree(&client, "root", None, &filter, 4).await.unwrap();
+
+    // "guides" is not exported but stays above its labelled child; the draft
+    // subtree is pruned even though its child is labelled
+    assert_eq!(tree.page.id, "root");
+    let children: Vec<_> = tree.children.iter().map(|child| child.page.id.as_str()).collect();
+    assert_eq!(children, ["guides"]);
+    assert!(!tree.children[0].exported);
+    let grandchildren: Vec<_> = tree.children[0]
+      .children
+      .iter()
+      .map(|child| child.page.id.as_str())
+      .collect();
+    assert_eq!(grandchildren, ["setup"]);
+    assert!(tree.children[0].children[0].exported);
+    assert_eq!(tree.children[0].children[0].depth, 2);
+  }
+
+  #[tokio::test]
+  async fn get_page_tree_filters_by_title_and_id() {
+    let mut client = ManyChildrenClient::new();
+    for (id, title) in [
+      ("root", "Team"),
+      ("archive", "Archive"),
+      ("old", "Old plan"),
+      ("notes", "Meeting notes 2019"),
+      ("guide", "Setup guide"),
+      ("faq", "FAQ"),
+      ("faq-guide", "FAQ guide"),
+    ] {
+      client.add_page(id, title);
+    }
+    client.set_children(
+      "root",
+      ["archive", "notes", "guide", "faq"].map(str::to_string).to_vec(),
+    );
+    client.set_children("archive", vec!["old".to_string()]);
+    client.set_children("faq", vec!["faq-guide".to_string()]);
+
+    let filter = PageFilter {
+      include_titles: vec!["* guide".to_string()],
+      exclude_titles: vec!["meeting notes 20??".to_string()],
+      exclude_ids: vec!["archive".to_string()],
+      ..Default::default()
+    };
+    let tree = get_page_tree(&client, "root", None, &filter, 4).await.unwrap();
+
+    let children: Vec<_> = tree.children.iter().map(|child| child.page.id.as_str()).collect();
+    assert_eq!(children, ["guide", "faq"]);
+    assert!(!tree.children[1].exported);
+    assert_eq!(tree.children[1].children[0].page.id, "faq-guide");
+    // Excluded subtrees are pruned from the listing without being fetched
+    let fetched = client.fetched.lock().unwrap();
+    assert!(
+      !fetched
+        .iter()
+        .any(|id| ["archive", "old", "notes"].contains(&id.as_str()))
+    );
+  }
+
+  #[tokio::test]
+  async fn get_page_tree_filters_by_author() {
+    let mut client = ManyChildrenClient::new();
+    for id in ["root", "theirs", "mine", "also-mine"] {
+      client.add_page(id, id);
+    }
+    client.set_children("root", vec!["theirs".to_string(), "also-mine".to_string()]);
+    client.set_children("theirs", vec!["mine".to_string()]);
+    client.set_author("theirs", "ben@example.com");
+    client.set_author("mine", "ana@example.com");
+    client.set_author("also-mine", "Ana@Example.com");
+
+    let filter = PageFilter {
+      authors: vec!["ana@example.com".to_string()],
+      ..Default::default()
+    };
+    let tree = get_page_tree(&client, "root", None, &filter, 4).await.unwrap();
+
+    let children: Vec<_> = tree.children.iter().map(|child| child.page.id.as_str()).collect();
+    assert_eq!(children, ["theirs", "also-mine"]);
+    assert!(!tree.children[0].exported);
+    assert_eq!(tree.children[0].children[0].page.id, "mine");
+  }
+
+  #[tokio::test]
+  async fn get_page_tree_filters_by_modified_date() {
+    let mut client = ManyChildrenClient::new();
+    for id in ["root", "stale", "fresh", "late", "undated"] {
+      client.add_page(id, id);
+    }
+    client.set_children("root", ["stale", "late", "undated"].map(str::to_string).to_vec());
+    client.set_children("stale", vec!["fresh".to_string()]);
+    client.set_modified("stale", "2022-06-30T12:00:00.000Z");
+    client.set_modified("fresh", "2023-12-31T23:30:00.000+01:00");
+    client.set_modified("late", "2024-07-01T00:00:00.000Z");
+
+    let filter = PageFilter {
+      modified_since: Some("2023-07-01T00:00:00Z".parse().unwrap()),
+      modified_until: Some("2024-06-30T23:59:59Z".parse().unwrap()),
+      ..Default::default()
+    };
+    let tree = get_page_tree(&client, "root", None, &filter, 4).await.unwrap();
+
+    // Descendants of a page outside the window are still visited and stay
+    // below it, so their paths do not depend on the window
+    let children: Vec<_> = tree.children.iter().map(|child| child.page.id.as_str()).collect();
+    assert_eq!(children, ["stale", "undated"]);
+    assert!(!tree.children[0].exported);
+    assert!(tree.children[1].exported);
+    assert_eq!(tree.children[0].children[0].page.id, "fresh");
+    assert!(tree.children[0].children[0].exported);
+  }
+
+  #[test]
+  fn page_tree_sort_orders_siblings() {
+    let page = |id: &str, title: &str, created: Option<&str>| -> Page {
+      serde_json::from_value(serde_json::json!({
+        "id": id,
+        "title": title,
+        "type": "page",
+        "status": "current",
+        "history": {"createdDate": created, "lastUpdated": {"when": created}}
+      }))
+      .unwrap()
+    };
+    let node = crate::test_support::tree;
+    let tree = node(
+      page("root", "Root", None),
+      vec![
+        node(page("b", "beta", Some("2024-03-01T10:00:00.000Z")), Vec::new()),
+        node(page("u", "Undated", None), Vec::new()),
+        node(
+          page("a", "Alpha", Some("2023-01-01T10:00:00.000+02:00")),
+          vec![
+            node(page("a2", "Zulu", None), Vec::new()),
+            node(page("a1", "echo", None), Vec::new()),
+          ],
+        ),
+      ],
+    );
+    let ids = |tree: &PageTree| {
+      tree
+        .children
+        .iter()
+        .map(|child| child.page.id.clone())
+        .collect::<Vec<_>>()
+    };
+
+    let mut sorted = tree.clone();
+    sorted.sort(PageOrder::Position);
+    assert_eq!(ids(&sorted), ["b", "u", "a"]);
+
+    sorted.sort(PageOrder::Title);
+    assert_eq!(ids(&sorted), ["a", "b", "u"]);
+    assert_eq!(ids(&sorted.children[0]), ["a1", "a2"]);
+
+    let mut sorted = tree.clone();
+    sorted.sort(PageOrder::Created);
+    assert_eq!(ids(&sorted), ["a", "b", "u"]);
+
+    let mut sorted = tree;
+    sorted.sort(PageOrder::Modified);
+    assert_eq!(ids(&sorted), ["a", "b", "u"]);
+  }
+
+  #[test]
+  fn glob_matches_whole_title_case_insensitively() {
+    assert!(glob_matches("archive*", "Archive 2019"));
+    assert!(glob_matches("*notes*", "Meeting Notes"));
+    assert!(glob_matches("v?", "V2"));
+    assert!(!glob_matches("archive", "Archive 2019"));
+    assert!(!glob_matches("v?", "v10"));
+  }
+
+  #[tokio::test]
+  async fn estimate_page_tree_extrapolates_sampled_levels() {
+    let mut client = ManyChildrenClient::new();
+    client.add_page("root", "Root");
+    let child_ids: Vec<String> = (0..10).map(|i| format!("child-{i}")).collect();
+    for id in &child_ids {
+      client.add_page(id, id);
+      let grandchild_ids: Vec<String> = (0..4).map(|i| format!("{id}-{i}")).collect();
+      for grandchild in &grandchild_ids {
+        client.add_page(grandchild, grandchild);
+      }
+      client.set_children(id, grandchild_ids);
+    }
+    client.set_children("root", child_ids);
+
+    // Enough budget to walk everything: counts are exact
+    let estimate = estimate_page_tree(&client, "root", 1000, 25).await.unwrap();
+    assert_eq!(estimate.total_pages(), 51.0);
+    assert!(estimate.levels.iter().all(|level| level.exact));
+    assert!(!estimate.truncated);
+
+    // Root, root's children, 2 of the 10 children, then 1 grandchild: depth 2
+    // is extrapolated and the leaf sample ends the walk, though unsampled
+    // grandchildren may have children
+    let estimate = estimate_page_tree(&client, "root", 5, 25).await.unwrap();
+    assert_eq!(estimate.requests, 5);
+    assert_eq!(
+      estimate.levels,
+      vec![
+        LevelEstimate {
+          pages: 1.0,
+          exact: true
+        },
+        LevelEstimate {
+          pages: 10.0,
+          exact: true
+        },
+        LevelEstimate {
+          pages: 40.0,
+          exact: false
+        },
+      ]
+    );
+    assert!(estimate.truncated);
+
+    // Budget exhausted before the children are listed
+    let estimate = estimate_page_tree(&client, "root", 1, 25).await.unwrap();
+    assert_eq!(estimate.total_pages(), 1.0);
+    assert!(estimate.truncated);
+
+    // Listing the 10 children 4 at a time takes 3 requests, and each listing
+    // of 4 grandchildren 1
+    let estimate = estimate_page_tree(&client, "root", 1000, 4).await.unwrap();
+    assert_eq!(estimate.requests, 1 + 3 + 10 + 40);
+    assert_eq!(estimate.total_pages(), 51.0);
+    assert!(!estimate.truncated);
+  }
+
   #[tokio::test]
   async fn get_page_tree_detects_circular_reference() {
     let mut client = ManyChildrenClient::new();
@@ -273,9 +996,28 @@ mod tests {
 
     // The tree builder should handle the cycle gracefully via the warning
     // (child page "a" will be skipped with a warning printed to stderr)
-    let tree = get_page_tree(&client, "a", None).await.unwrap();
+    let tree = get_page_tree(&client, "a", None, &PageFilter::default(), 4)
+      .await
+      .unwrap();
     assert_eq!(tree.children.len(), 1);
     // The grandchild "a" should not appear because it was already visited
     assert_eq!(tree.children[0].children.len(), 0);
   }
+
+  #[tokio::test]
+  async fn get_page_tree_reports_failed_children() {
+    let mut client = ManyChildrenClient::new();
+    client.add_page("root", "Root");
+    client.add_page("kept", "Kept");
+    client.set_children("root", vec!["kept".to_string(), "deleted".to_string()]);
+    // The listing names a page that no longer answers
+    client.add_page("deleted", "Deleted");
+    client.unavailable.insert("deleted".to_string());
+
+    let (tree, failed) = get_page_tree_with_failures(&client, "root", None, &PageFilter::default(), 4)
+      .await
+      .unwrap();
+    assert_eq!(tree.children.len(), 1);
+    assert_eq!(failed, ["deleted"]);
+  }
 }
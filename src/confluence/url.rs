@@ -14,6 +14,9 @@ pub struct UrlInfo {
   pub space_key: Option<String>,
 }
 
+/// Path segments followed by the content ID in Confluence URLs.
+const CONTENT_SEGMENTS: &[&str] = &["pages", "whiteboard", "database", "embed"];
+
 /// Parse a Confluence URL to extract page ID, base URL, and optional space key.
 ///
 /// Supports various Confluence URL formats:
@@ -21,6 +24,9 @@ pub struct UrlInfo {
 /// - https://example.atlassian.net/wiki/pages/123456
 /// - https://example.atlassian.net/pages/123456
 ///
+/// Whiteboard, database, and embed URLs (`/whiteboard/123456`,
+/// `/database/123456`, `/embed/123456`) are accepted the same way.
+///
 /// # Arguments
 /// * `url` - User-supplied Confluence URL that should resolve to a specific page.
 ///
@@ -30,7 +36,7 @@ pub struct UrlInfo {
 ///
 /// # Errors
 /// Returns an error when the URL is malformed, missing the expected `pages`
-/// segment, or contains a non-numeric page ID.
+/// (or content type) segment, or contains a non-numeric page ID.
 pub fn parse_confluence_url(url: &str) -> Result<UrlInfo> {
   let parsed = Url::parse(url).context("Invalid URL format")?;
 
@@ -45,11 +51,14 @@ pub fn parse_confluence_url(url: &str) -> Result<UrlInfo> {
 
   let page_id_pos = segments
     .iter()
-    .position(|&s| s == "pages")
+    .position(|&s| CONTENT_SEGMENTS.contains(&s))
     .context("URL does not contain 'pages' segment")?;
 
   if page_id_pos + 1 >= segments.len() {
-    return Err(anyhow!("URL does not contain page ID after 'pages' segment"));
+    return Err(anyhow!(
+      "URL does not contain page ID after '{}' segment",
+      segments[page_id_pos]
+    ));
   }
 
   let page_id = segments[page_id_pos + 1];
@@ -98,6 +107,15 @@ mod tests {
     assert_eq!(info.space_key, None);
   }
 
+  #[test]
+  fn test_parse_confluence_url_whiteboard() {
+    let url = "https://example.atlassian.net/wiki/spaces/ENG/whiteboard/778899";
+    let info = parse_confluence_url(url).unwrap();
+
+    assert_eq!(info.page_id, "778899");
+    assert_eq!(info.space_key, Some("ENG".to_string()));
+  }
+
   #[test]
   fn test_parse_confluence_url_invalid() {
     let url = "https://example.com/not-a-confluence-url";
@@ -0,0 +1,106 @@
+//! Stubs for content that cannot be converted.
+//!
+//! Whiteboards, databases, and Smart Link embeds are listed in the page tree
+//! like pages, but the API returns them without a storage body, so converting
+//! them used to fail or produce an empty file. They are written as a stub
+//! naming the content type and linking back to Confluence instead, and listed
+//! in the summary at the end of the run.
+
+use crate::confluence::Page;
+use crate::format::OutputFormat;
+
+/// Render the file written in place of content that cannot be converted.
+///
+/// # Arguments
+/// * `page` - The content record.
+/// * `instance_url` - Base URL of the Confluence instance, used to link back to the content.
+/// * `format` - Format of the page file; JSON exports hold Markdown content.
+pub fn render_stub(page: &Page, instance_url: Option<&str>, format: OutputFormat) -> String {
+  let note = format!(
+    "This Confluence {} has no content that can be converted.",
+    page.content_kind().label()
+  );
+  let url = content_url(page, instance_url);
+  match format.content_format() {
+    OutputFormat::AsciiDoc => match url {
+      Some(url) => format!("= {}\n\nNOTE: {note} link:{url}[Open it in Confluence].\n", page.title),
+      None => format!("= {}\n\nNOTE: {note}\n", page.title),
+    },
+    _ => match url {
+      Some(url) => format!(
+        "# {}\n\n> **Note:** {note} [Open it in Confluence]({url}).\n",
+        page.title
+      ),
+      None => format!("# {}\n\n> **Note:** {note}\n", page.title),
+    },
+  }
+}
+
+/// Address of the content in the Confluence web UI.
+///
+/// Uses the `webui` link of the record, resolved against `instance_url`, and
+/// falls back to the page ID lookup when the record has no links.
+pub fn content_url(page: &Page, instance_url: Option<&str>) -> Option<String> {
+  let web_ui = page.links.as_ref().and_then(|links| links.web_ui.as_deref());
+  if let Some(web_ui) = web_ui
+    && web_ui.starts_with("http")
+  {
+    return Some(web_ui.to_string());
+  }
+
+  let base = instance_url?.trim_end_matches('/');
+  Some(match web_ui {
+    Some(path) if path.starts_with("/wiki/") => format!("{base}{path}"),
+    Some(path) => format!("{base}/wiki{path}"),
+    None => format!("{base}/wiki/pages/viewpage.action?pageId={}", page.id),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn whiteboard(web_ui: Option<&str>) -> Page {
+    serde_json::from_value(serde_json::json!({
+      "id": "778899",
+      "title": "Architecture sketch",
+      "type": "whiteboard",
+      "status": "current",
+      "_links": {"webui": web_ui}
+    }))
+    .unwrap()
+  }
+
+  #[test]
+  fn test_render_stub() {
+    let page = whiteboard(Some("/spaces/ENG/whiteboard/778899"));
+
+    insta::assert_snapshot!(
+      render_stub(&page, Some("https://example.atlassian.net"), OutputFormat::Markdown),
+      @r"
+    # Architecture sketch
+
+    > **Note:** This Confluence whiteboard has no content that can be converted. [Open it in Confluence](https://example.atlassian.net/wiki/spaces/ENG/whiteboard/778899).
+    "
+    );
+    insta::assert_snapshot!(render_stub(&page, None, OutputFormat::AsciiDoc), @r"
+    = Architecture sketch
+
+    NOTE: This Confluence whiteboard has no content that can be converted.
+    ");
+  }
+
+  #[test]
+  fn test_content_url() {
+    let base = Some("https://example.atlassian.net/");
+
+    assert_eq!(
+      content_url(&whiteboard(Some("/wiki/spaces/ENG/whiteboard/778899")), base).as_deref(),
+      Some("https://example.atlassian.net/wiki/spaces/ENG/whiteboard/778899")
+    );
+    assert_eq!(
+      content_url(&whiteboard(None), base).as_deref(),
+      Some("https://example.atlassian.net/wiki/pages/viewpage.action?pageId=778899")
+    );
+  }
+}
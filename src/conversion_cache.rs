@@ -0,0 +1,148 @@
+//! On-disk cache of converted page content.
+//!
+//! Converting large pages is the most expensive step of an export once API
+//! responses are fast. With `--cache-dir`, the output of every conversion is
+//! stored under a key derived from the page's storage content, the output
+//! format, the conversion options, and the version of confluence-dl, so
+//! re-running an export over unchanged pages with unchanged options skips
+//! conversion entirely. Any change to one of them produces a new key; stale
+//! entries are never read and can be removed by deleting the directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tracing::debug;
+
+use crate::asciidoc::AsciiDocOptions;
+use crate::format::OutputFormat;
+use crate::integrity::sha256_hex;
+use crate::markdown::MarkdownOptions;
+
+/// Subdirectory of the cache directory holding converted pages.
+const CONVERSIONS_DIR: &str = "conversions";
+
+/// Converted page content stored in a cache directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionCache {
+  dir: PathBuf,
+}
+
+impl ConversionCache {
+  /// Use `cache_dir` for cached conversions; it is created on first write.
+  pub fn new(cache_dir: &Path) -> Self {
+    Self {
+      dir: cache_dir.join(CONVERSIONS_DIR),
+    }
+  }
+
+  /// Cache key of a conversion.
+  ///
+  /// # Arguments
+  /// * `storage_content` - Storage format being converted.
+  /// * `format` - Target output format.
+  /// * `markdown_options` - Options used when converting to Markdown.
+  /// * `asciidoc_options` - Options used when converting to AsciiDoc.
+  pub fn key(
+    storage_content: &str,
+    format: OutputFormat,
+    markdown_options: &MarkdownOptions,
+    asciidoc_options: &AsciiDocOptions,
+  ) -> String {
+    let options = match format {
+      OutputFormat::Markdown | OutputFormat::Json => markdown_fingerprint(markdown_options),
+      OutputFormat::AsciiDoc => format!("{asciidoc_options:?}"),
+    };
+    let storage_hash = sha256_hex(storage_content.as_bytes());
+    sha256_hex(format!("{}\n{format:?}\n{options}\n{storage_hash}", env!("CARGO_PKG_VERSION")).as_bytes())
+  }
+
+  /// Converted content stored under `key`, if any.
+  pub fn get(&self, key: &str) -> Option<String> {
+    let path = self.path(key);
+    match fs::read_to_string(&path) {
+      Ok(content) => {
+        debug!("Using cached conversion {}", path.display());
+        Some(content)
+      }
+      Err(_) => None,
+    }
+  }
+
+  /// Store converted content under `key`.
+  ///
+  /// The content is written to a temporary file and renamed into place, so
+  /// concurrent exports sharing the directory never read a partial entry.
+  ///
+  /// # Errors
+  /// Returns an error when the cache directory or file cannot be written.
+  pub fn put(&self, key: &str, content: &str) -> Result<()> {
+    let path = self.path(key);
+    let dir = path.parent().unwrap_or(&self.dir);
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create cache directory {}", dir.display()))?;
+    let temp_path = path.with_extension(format!("tmp-{}", std::process::id()));
+    fs::write(&temp_path, content).with_context(|| format!("Failed to write {}", temp_path.display()))?;
+    fs::rename(&temp_path, &path).with_context(|| format!("Failed to write {}", path.display()))
+  }
+
+  /// File of an entry, spread over subdirectories named after the first two
+  /// hex digits of the key.
+  fn path(&self, key: &str) -> PathBuf {
+    self.dir.join(&key[..2]).join(key)
+  }
+}
+
+/// Stable description of the Markdown options.
+///
+/// The export scope is summarized by its own fingerprint because its pages
+/// are held in a hash map, whose debug output changes order between runs.
+fn markdown_fingerprint(options: &MarkdownOptions) -> String {
+  let scope = options.links.scope.as_ref().map(|scope| scope.fingerprint());
+  let mut options = options.clone();
+  options.links.scope = None;
+  format!("{options:?}\n{scope:?}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_key_depends_on_content_format_and_options() {
+    let markdown = MarkdownOptions::default();
+    let asciidoc = AsciiDocOptions::default();
+    let key = ConversionCache::key("<p>Hi</p>", OutputFormat::Markdown, &markdown, &asciidoc);
+
+    assert_eq!(
+      key,
+      ConversionCache::key("<p>Hi</p>", OutputFormat::Markdown, &markdown, &asciidoc)
+    );
+    assert_ne!(
+      key,
+      ConversionCache::key("<p>Hello</p>", OutputFormat::Markdown, &markdown, &asciidoc)
+    );
+    assert_ne!(
+      key,
+      ConversionCache::key("<p>Hi</p>", OutputFormat::AsciiDoc, &markdown, &asciidoc)
+    );
+    let compact = MarkdownOptions {
+      compact_tables: true,
+      ..Default::default()
+    };
+    assert_ne!(
+      key,
+      ConversionCache::key("<p>Hi</p>", OutputFormat::Markdown, &compact, &asciidoc)
+    );
+  }
+
+  #[test]
+  fn test_put_then_get() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache = ConversionCache::new(temp_dir.path());
+    let key = sha256_hex(b"key");
+
+    assert_eq!(cache.get(&key), None);
+    cache.put(&key, "# Converted\n").unwrap();
+    assert_eq!(cache.get(&key).as_deref(), Some("# Converted\n"));
+  }
+}
@@ -0,0 +1,132 @@
+//! OS keyring credential provider.
+//!
+//! Stores Confluence credentials in the platform's secure credential store:
+//! the macOS Keychain, the Windows Credential Manager, or a Secret Service
+//! implementation such as GNOME Keyring or KWallet on Linux. Entries are keyed
+//! by host under the `confluence-dl` service, so one login per Confluence
+//! instance is kept. The username and API token are stored together as the
+//! entry's secret.
+
+use ::keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+use super::{Credential, CredentialError, CredentialsProvider};
+
+/// Service name that keyring entries are stored under.
+const SERVICE: &str = "confluence-dl";
+
+/// Credential provider backed by the OS keyring.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct KeyringProvider;
+
+/// Secret stored in a keyring entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSecret {
+  username: String,
+  token: String,
+}
+
+impl KeyringProvider {
+  /// Create a provider for the platform's default keyring.
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Store credentials for a host, replacing any existing entry.
+  ///
+  /// # Arguments
+  /// * `host` - Hostname the credentials belong to (e.g., `example.atlassian.net`).
+  /// * `credential` - Username and API token to store.
+  ///
+  /// # Errors
+  /// Returns [`CredentialError::Keyring`] when the keyring is unavailable or
+  /// refuses the entry.
+  pub fn store(&self, host: &str, credential: &Credential) -> Result<(), CredentialError> {
+    let secret = encode_secret(credential);
+    on_keyring_thread(|| entry(host)?.set_password(&secret).map_err(keyring_error))
+  }
+
+  /// Remove the stored credentials for a host.
+  ///
+  /// # Returns
+  /// Whether an entry existed.
+  ///
+  /// # Errors
+  /// Returns [`CredentialError::Keyring`] when the keyring is unavailable.
+  pub fn delete(&self, host: &str) -> Result<bool, CredentialError> {
+    on_keyring_thread(|| match entry(host)?.delete_credential() {
+      Ok(()) => Ok(true),
+      Err(::keyring::Error::NoEntry) => Ok(false),
+      Err(err) => Err(keyring_error(err)),
+    })
+  }
+}
+
+impl CredentialsProvider for KeyringProvider {
+  fn get_credentials(&self, host: &str) -> Result<Option<Credential>, CredentialError> {
+    let secret = on_keyring_thread(|| match entry(host)?.get_password() {
+      Ok(secret) => Ok(Some(secret)),
+      Err(::keyring::Error::NoEntry) => Ok(None),
+      Err(err) => Err(keyring_error(err)),
+    })?;
+    secret.map(|secret| decode_secret(&secret)).transpose()
+  }
+}
+
+fn entry(host: &str) -> Result<Entry, CredentialError> {
+  Entry::new(SERVICE, host).map_err(keyring_error)
+}
+
+fn keyring_error(err: ::keyring::Error) -> CredentialError {
+  CredentialError::Keyring(err.to_string())
+}
+
+/// Run a keyring call on its own thread.
+///
+/// The Secret Service backend drives its D-Bus connection on a private Tokio
+/// runtime, which deadlocks when called from a thread that is already running
+/// the application's runtime.
+fn on_keyring_thread<T: Send>(call: impl FnOnce() -> Result<T, CredentialError> + Send) -> Result<T, CredentialError> {
+  std::thread::scope(|scope| {
+    scope
+      .spawn(call)
+      .join()
+      .unwrap_or_else(|_| Err(CredentialError::Keyring("keyring access panicked".to_string())))
+  })
+}
+
+fn encode_secret(credential: &Credential) -> String {
+  let secret = StoredSecret {
+    username: credential.username.clone(),
+    token: credential.password.clone(),
+  };
+  serde_json::to_string(&secret).unwrap_or_default()
+}
+
+fn decode_secret(secret: &str) -> Result<Credential, CredentialError> {
+  let secret: StoredSecret = serde_json::from_str(secret)
+    .map_err(|err| CredentialError::Keyring(format!("stored credentials are not in the expected format: {err}")))?;
+  Ok(Credential {
+    username: secret.username,
+    password: secret.token,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_secret_round_trip() {
+    let credential = Credential {
+      username: "me@example.com".to_string(),
+      password: "tok\"en".to_string(),
+    };
+
+    let secret = encode_secret(&credential);
+
+    assert_eq!(secret, r#"{"username":"me@example.com","token":"tok\"en"}"#);
+    assert_eq!(decode_secret(&secret).unwrap(), credential);
+    assert!(matches!(decode_secret("plain-token"), Err(CredentialError::Keyring(_))));
+  }
+}
@@ -17,11 +17,17 @@
 //!
 //! **Important**: Use your email address as the login and your API token as the
 //! password.
+//!
+//! On shared machines, `confluence-dl auth login` stores the credentials in
+//! the OS keyring instead, and `--credential-source keyring` reads them from
+//! there.
 
+mod keyring;
 mod netrc;
 mod provider;
 mod types;
 
+pub use keyring::KeyringProvider;
 pub use netrc::NetrcProvider;
 pub use provider::CredentialsProvider;
-pub use types::{Credential, CredentialError};
+pub use types::{Credential, CredentialError, CredentialSource};
@@ -6,6 +6,8 @@
 
 use std::fmt;
 
+use clap::ValueEnum;
+
 /// Represents a set of credentials for authenticating with a host.
 ///
 /// For Atlassian Cloud/Confluence:
@@ -19,6 +21,29 @@ pub struct Credential {
   pub password: String,
 }
 
+/// Where credentials are looked up when `--user`/`--token` do not provide both.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum CredentialSource {
+  /// The `~/.netrc` entry for the host
+  #[default]
+  Netrc,
+  /// The OS keyring entry stored by `confluence-dl auth login`
+  Keyring,
+  /// Only `--user`/`--token` and `CONFLUENCE_USER`/`CONFLUENCE_TOKEN`
+  Env,
+}
+
+impl CredentialSource {
+  /// Human-readable name of the source.
+  pub fn label(self) -> &'static str {
+    match self {
+      Self::Netrc => ".netrc file",
+      Self::Keyring => "OS keyring",
+      Self::Env => "environment",
+    }
+  }
+}
+
 /// Errors that can occur during credential operations.
 #[derive(Debug)]
 pub enum CredentialError {
@@ -29,6 +54,8 @@ pub enum CredentialError {
   NetrcParseError(String),
   /// An I/O error occurred while reading credentials
   IoError(std::io::Error),
+  /// The OS keyring is unavailable or holds an unreadable entry
+  Keyring(String),
 }
 
 impl fmt::Display for CredentialError {
@@ -37,6 +64,7 @@ impl fmt::Display for CredentialError {
       Self::NetrcNotFound => write!(f, ".netrc file not found"),
       Self::NetrcParseError(msg) => write!(f, "failed to parse .netrc: {msg}"),
       Self::IoError(err) => write!(f, "I/O error: {err}"),
+      Self::Keyring(msg) => write!(f, "keyring error: {msg}"),
     }
   }
 }
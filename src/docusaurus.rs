@@ -0,0 +1,191 @@
+//! Docusaurus export profile.
+//!
+//! Builds the per-page front matter Docusaurus reads (`id`, `title`,
+//! `sidebar_position`, and `tags` from the page labels) and a `sidebars.js`
+//! fragment mirroring the exported page hierarchy. Doc IDs follow Docusaurus'
+//! path-based scheme: the directory a page is written to, followed by the
+//! `id` declared in its front matter, which is the page's filename.
+
+use crate::confluence::{Page, PageTree};
+use crate::front_matter::FrontMatter;
+use crate::processed_page::TreeFilenames;
+
+/// File name of the generated sidebar fragment.
+pub const SIDEBAR_FILE: &str = "sidebars.js";
+
+/// Build the front matter for a page.
+///
+/// # Arguments
+/// * `page` - The page being exported.
+/// * `stem` - Filename, without extension, the page is written to.
+/// * `position` - One-based position of the page among its siblings.
+pub fn front_matter(page: &Page, stem: &str, position: usize) -> FrontMatter {
+  let mut front_matter = FrontMatter::default();
+  front_matter.insert("id", stem.to_string());
+  front_matter.insert("title", page.title.clone());
+  front_matter.insert_number("sidebar_position", position);
+  let labels = page.labels();
+  if !labels.is_empty() {
+    front_matter.insert_list("tags", labels);
+  }
+  front_matter
+}
+
+/// Render a `sidebars.js` fragment for exported page trees.
+///
+/// Pages with children become categories linking to their own doc, or
+/// without a link when the page itself was filtered out; leaf pages are
+/// referenced by doc ID. Each tree, such as a page pulled in with
+/// `--follow-links`, is a top-level item in export order.
+///
+/// # Arguments
+/// * `trees` - Roots of the exported trees, written directly into the output directory.
+/// * `filenames` - Filenames the pages were written to, naming the child directories.
+pub fn render_sidebar(trees: &[PageTree], filenames: &TreeFilenames) -> String {
+  let mut result = String::from("module.exports = {\n  confluenceSidebar: [\n");
+  for tree in trees {
+    render_sidebar_item(tree, "", 2, filenames, &mut result);
+  }
+  result.push_str("  ],\n};\n");
+  result
+}
+
+fn render_sidebar_item(tree: &PageTree, dir: &str, depth: usize, filenames: &TreeFilenames, result: &mut String) {
+  let indent = "  ".repeat(depth);
+  let stem = filenames.stem(&tree.page);
+  let id = format!("{dir}{stem}");
+
+  if tree.children.is_empty() {
+    result.push_str(&format!("{indent}{},\n", js_string(&id)));
+    return;
+  }
+
+  result.push_str(&format!("{indent}{{\n"));
+  result.push_str(&format!("{indent}  type: 'category',\n"));
+  result.push_str(&format!("{indent}  label: {},\n", js_string(&tree.page.title)));
+  if tree.exported {
+    result.push_str(&format!("{indent}  link: {{ type: 'doc', id: {} }},\n", js_string(&id)));
+  }
+  result.push_str(&format!("{indent}  items: [\n"));
+  let child_dir = format!("{dir}{stem}/");
+  for child in &tree.children {
+    render_sidebar_item(child, &child_dir, depth + 2, filenames, result);
+  }
+  result.push_str(&format!("{indent}  ],\n"));
+  result.push_str(&format!("{indent}}},\n"));
+}
+
+/// Convert a page title into a lowercase, hyphen-separated slug.
+pub(crate) fn slugify(title: &str) -> String {
+  let mut slug = String::new();
+  for c in title.chars().flat_map(char::to_lowercase) {
+    if c.is_alphanumeric() {
+      slug.push(c);
+    } else if !slug.is_empty() && !slug.ends_with('-') {
+      slug.push('-');
+    }
+  }
+  let slug = slug.trim_end_matches('-');
+  if slug.is_empty() {
+    "page".to_string()
+  } else {
+    slug.to_string()
+  }
+}
+
+/// Quote a string as a single-quoted JavaScript literal.
+fn js_string(value: &str) -> String {
+  format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::format::OutputFormat;
+  use crate::processed_page::FilenameOptions;
+  use crate::test_support::{page, tree};
+
+  #[test]
+  fn test_front_matter() {
+    let mut page = page("1", "Getting Started: Setup");
+    page.metadata = serde_json::from_value(serde_json::json!({
+      "labels": {"results": [{"prefix": "global", "name": "onboarding"}]}
+    }))
+    .unwrap();
+    insta::assert_snapshot!(front_matter(&page, "Getting Started_ Setup", 3).render(OutputFormat::Markdown), @r#"
+    ---
+    id: Getting Started_ Setup
+    title: "Getting Started: Setup"
+    sidebar_position: 3
+    tags: [onboarding]
+    ---
+    "#);
+  }
+
+  #[test]
+  fn test_render_sidebar() {
+    let root = tree(
+      page("1", "Team Handbook"),
+      vec![
+        tree(page("2", "On-call"), vec![tree(page("3", "Rotations"), vec![])]),
+        tree(page("4", "Engineer's FAQ"), vec![]),
+      ],
+    );
+
+    let forest = [root, tree(page("5", "Runbook"), vec![])];
+
+    insta::assert_snapshot!(render_sidebar(&forest, &TreeFilenames::plan_forest(&forest, FilenameOptions::default())), @r"
+    module.exports = {
+      confluenceSidebar: [
+        {
+          type: 'category',
+          label: 'Team Handbook',
+          link: { type: 'doc', id: 'Team Handbook' },
+          items: [
+            {
+              type: 'category',
+              label: 'On-call',
+              link: { type: 'doc', id: 'Team Handbook/On-call' },
+              items: [
+                'Team Handbook/On-call/Rotations',
+              ],
+            },
+            'Team Handbook/Engineer_s FAQ',
+          ],
+        },
+        'Runbook',
+      ],
+    };
+    ");
+  }
+
+  #[test]
+  fn test_render_sidebar_unexported_category() {
+    let mut section = tree(page("2", "On-call"), vec![tree(page("3", "Rotations"), vec![])]);
+    section.exported = false;
+    let root = tree(page("1", "Team Handbook"), vec![section]);
+
+    let filenames = TreeFilenames::plan(&root, FilenameOptions::default());
+
+    insta::assert_snapshot!(render_sidebar(std::slice::from_ref(&root), &filenames), @r"
+    module.exports = {
+      confluenceSidebar: [
+        {
+          type: 'category',
+          label: 'Team Handbook',
+          link: { type: 'doc', id: 'Team Handbook' },
+          items: [
+            {
+              type: 'category',
+              label: 'On-call',
+              items: [
+                'Team Handbook/On-call/Rotations',
+              ],
+            },
+          ],
+        },
+      ],
+    };
+    ");
+  }
+}
@@ -0,0 +1,188 @@
+//! Errors returned by the library.
+//!
+//! The Confluence API, the Markdown converter, and attachment downloads
+//! return [`Error`], so applications embedding the exporter can tell a
+//! rejected token from a missing page or an unparsable page body without
+//! matching on messages. Internally, errors are built with `anyhow` and
+//! gather context on the way up; [`Error`] keeps that message and classifies
+//! it by the first typed cause in its chain.
+
+use std::{fmt, io};
+
+/// Result type of the library's public API.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error returned by the library's public API.
+///
+/// Every variant displays the full message, including the context added
+/// while the error was propagated.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+  /// Confluence answered a request with an error status.
+  Api {
+    /// HTTP status code of the response.
+    status: u16,
+    /// Description of the failed request.
+    message: String,
+  },
+  /// Confluence rejected the credentials (HTTP 401 or 403).
+  Auth {
+    /// HTTP status code of the response, when one was received.
+    status: Option<u16>,
+    /// Description of the failed request.
+    message: String,
+  },
+  /// A response or page body could not be parsed.
+  Parse {
+    /// Description of what could not be parsed.
+    message: String,
+  },
+  /// A file could not be read or written, or the connection to Confluence
+  /// failed.
+  Io(io::Error),
+  /// Any other failure, such as a missing recording in a replayed cassette.
+  Other {
+    /// Description of the failure.
+    message: String,
+  },
+}
+
+impl Error {
+  /// Error for a response with an unsuccessful status: [`Error::Auth`] for
+  /// 401 and 403, [`Error::Api`] otherwise.
+  pub fn from_status(status: u16, message: impl Into<String>) -> Self {
+    let message = message.into();
+    match status {
+      401 | 403 => Self::Auth {
+        status: Some(status),
+        message,
+      },
+      _ => Self::Api { status, message },
+    }
+  }
+
+  /// Error for content that could not be parsed.
+  pub fn parse(message: impl Into<String>) -> Self {
+    Self::Parse {
+      message: message.into(),
+    }
+  }
+
+  /// HTTP status code of the response that caused the error, if any.
+  pub fn status(&self) -> Option<u16> {
+    match self {
+      Self::Api { status, .. } => Some(*status),
+      Self::Auth { status, .. } => *status,
+      _ => None,
+    }
+  }
+
+  /// The same kind of error with a different message.
+  fn with_message(&self, message: String) -> Self {
+    match self {
+      Self::Api { status, .. } => Self::Api {
+        status: *status,
+        message,
+      },
+      Self::Auth { status, .. } => Self::Auth {
+        status: *status,
+        message,
+      },
+      Self::Parse { .. } => Self::Parse { message },
+      Self::Io(err) => Self::Io(io::Error::new(err.kind(), message)),
+      Self::Other { .. } => Self::Other { message },
+    }
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Api { message, .. } | Self::Auth { message, .. } | Self::Parse { message } | Self::Other { message } => {
+        f.write_str(message)
+      }
+      Self::Io(err) => write!(f, "{err}"),
+    }
+  }
+}
+
+impl std::error::Error for Error {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Self::Io(err) => Some(err),
+      _ => None,
+    }
+  }
+}
+
+impl From<io::Error> for Error {
+  fn from(err: io::Error) -> Self {
+    Self::Io(err)
+  }
+}
+
+impl From<anyhow::Error> for Error {
+  fn from(err: anyhow::Error) -> Self {
+    let message = format!("{err:#}");
+    for cause in err.chain() {
+      if let Some(typed) = cause.downcast_ref::<Error>() {
+        return typed.with_message(message);
+      }
+      if let Some(http) = cause.downcast_ref::<reqwest::Error>() {
+        return match http.status() {
+          Some(status) => Self::from_status(status.as_u16(), message),
+          None if http.is_decode() => Self::Parse { message },
+          None => Self::Io(io::Error::other(message)),
+        };
+      }
+      if cause.is::<serde_json::Error>() || cause.is::<roxmltree::Error>() {
+        return Self::Parse { message };
+      }
+      if let Some(io) = cause.downcast_ref::<io::Error>() {
+        return Self::Io(io::Error::new(io.kind(), message));
+      }
+    }
+    Self::Other { message }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use anyhow::Context;
+
+  use super::*;
+
+  #[test]
+  fn test_error_from_anyhow_keeps_kind_and_context() {
+    let err: Error = Err::<(), _>(Error::from_status(404, "Confluence API returned error 404 Not Found"))
+      .context("Failed to fetch page 123")
+      .unwrap_err()
+      .into();
+    assert!(matches!(err, Error::Api { status: 404, .. }), "{err:?}");
+    assert_eq!(
+      err.to_string(),
+      "Failed to fetch page 123: Confluence API returned error 404 Not Found"
+    );
+
+    assert!(matches!(
+      Error::from_status(401, "denied"),
+      Error::Auth { status: Some(401), .. }
+    ));
+
+    let json = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+    let err: Error = anyhow::Error::new(json).context("Failed to parse page").into();
+    assert!(matches!(err, Error::Parse { .. }), "{err:?}");
+
+    let missing = std::fs::read("/nonexistent/confluence-dl").unwrap_err();
+    let err: Error = anyhow::Error::new(missing).into();
+    assert!(
+      matches!(&err, Error::Io(io) if io.kind() == io::ErrorKind::NotFound),
+      "{err:?}"
+    );
+    assert!(std::error::Error::source(&err).is_some_and(|source| source.is::<io::Error>()));
+
+    let err: Error = anyhow::anyhow!("No recording for request").into();
+    assert!(matches!(err, Error::Other { .. }));
+  }
+}
@@ -0,0 +1,108 @@
+//! Report of the pages that failed during a tree export.
+//!
+//! By default the first page that fails to export stops the run. With
+//! `--keep-going`, [`FailureReport`] records each failing page instead, the
+//! rest of the tree is exported, and the failures are written to
+//! [`ERRORS_FILE`] at the export root. The run then ends with
+//! [`PartialFailure`], which the CLI maps to [`PARTIAL_FAILURE_EXIT_CODE`] so
+//! scripts can tell a partial export from one that failed outright.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::confluence::Page;
+
+/// File name of the failure report, at the export root.
+pub const ERRORS_FILE: &str = "errors.json";
+
+/// Exit code of a `--keep-going` run in which some pages failed.
+pub const PARTIAL_FAILURE_EXIT_CODE: i32 = 6;
+
+/// A page that failed to export.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct PageFailure {
+  /// Page title.
+  pub title: String,
+  /// Page ID.
+  pub page_id: String,
+  /// The error with its causes.
+  pub error: String,
+}
+
+/// Failures recorded during an export.
+#[derive(Debug, Default)]
+pub struct FailureReport {
+  failures: Mutex<Vec<PageFailure>>,
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+  failures: &'a [PageFailure],
+}
+
+impl FailureReport {
+  /// Record a page that failed to export.
+  pub fn record(&self, page: &Page, error: &anyhow::Error) {
+    self
+      .failures
+      .lock()
+      .expect("failure report lock poisoned")
+      .push(PageFailure {
+        title: page.title.clone(),
+        page_id: page.id.clone(),
+        error: format!("{error:#}"),
+      });
+  }
+
+  /// Number of recorded failures.
+  pub fn len(&self) -> usize {
+    self.failures.lock().expect("failure report lock poisoned").len()
+  }
+
+  /// Whether no page failed.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Write [`ERRORS_FILE`] listing the failures by title, with an empty list
+  /// when every page was exported.
+  ///
+  /// # Returns
+  /// The path of the written report.
+  ///
+  /// # Errors
+  /// Returns an error when the report cannot be written.
+  pub fn write(&self, root: &Path) -> Result<PathBuf> {
+    let mut failures = self.failures.lock().expect("failure report lock poisoned").clone();
+    failures.sort();
+    let json =
+      serde_json::to_string_pretty(&Report { failures: &failures }).context("Failed to serialize failure report")?;
+    let path = root.join(ERRORS_FILE);
+    std::fs::write(&path, format!("{json}\n")).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+  }
+}
+
+/// Error ending a `--keep-going` export in which some pages failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartialFailure {
+  /// Number of pages that failed.
+  pub failures: usize,
+}
+
+impl fmt::Display for PartialFailure {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} {} failed to export; see {ERRORS_FILE}",
+      self.failures,
+      if self.failures == 1 { "page" } else { "pages" }
+    )
+  }
+}
+
+impl std::error::Error for PartialFailure {}
@@ -0,0 +1,150 @@
+//! Machine-readable export events.
+//!
+//! With `--log-format json`, the page download workflow reports its progress
+//! and results as line-delimited JSON on stdout instead of colored text, so
+//! CI pipelines can drive the tool and parse what happened. Each line is one
+//! [`Event`] with an `event` field naming its kind.
+
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::accessibility::IssueKind;
+use crate::processed_page::LinkMap;
+
+/// How progress and results are reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+  /// Colored, human-readable text
+  #[default]
+  Text,
+  /// Line-delimited JSON events on stdout
+  Json,
+}
+
+/// A progress or result event of an export.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+  /// An export of a page (and optionally its descendants) started.
+  ExportStarted {
+    /// Page URL or ID given on the command line.
+    page: &'a str,
+    /// Output directory.
+    output: &'a str,
+    /// Whether child pages are exported.
+    children: bool,
+  },
+  /// Conversion of a page started.
+  PageStarted {
+    /// Confluence page ID.
+    page_id: &'a str,
+    /// Page title.
+    title: &'a str,
+  },
+  /// An image or attachment of a page was written.
+  AttachmentDownloaded {
+    /// ID of the page the file belongs to.
+    page_id: &'a str,
+    /// `image` or `attachment`.
+    kind: &'static str,
+    /// Path of the written file.
+    path: String,
+    /// Size of the file in bytes.
+    bytes: usize,
+  },
+  /// A page was written.
+  PageDone {
+    /// Confluence page ID.
+    page_id: &'a str,
+    /// Page title.
+    title: &'a str,
+    /// Path of the written page file.
+    path: String,
+    /// Links rewritten to downloaded images and attachments, and to other
+    /// exported pages.
+    #[serde(skip_serializing_if = "LinkMap::is_empty")]
+    links: &'a LinkMap,
+  },
+  /// The accessibility audit found a problem in a page.
+  AccessibilityIssue {
+    /// Confluence page ID.
+    page_id: &'a str,
+    /// Category of the finding.
+    kind: IssueKind,
+    /// Description of the finding.
+    message: &'a str,
+  },
+  /// An export or a single page failed.
+  Error {
+    /// ID of the failed page, when the error is specific to one page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page_id: Option<&'a str>,
+    /// Error message including its causes.
+    message: String,
+  },
+  /// The export finished.
+  ExportDone {
+    /// Pages written.
+    pages: usize,
+    /// Pages that failed.
+    failures: usize,
+    /// Bytes of page content, images, and attachments written.
+    bytes: u64,
+  },
+}
+
+/// Write an event to stdout as one line of JSON.
+pub fn emit(event: &Event) {
+  let Ok(line) = serde_json::to_string(event) else {
+    return;
+  };
+  let mut stdout = io::stdout().lock();
+  let _ = writeln!(stdout, "{line}");
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_event_serialization() {
+    let started = Event::PageStarted {
+      page_id: "42",
+      title: "Runbook",
+    };
+    let error = Event::Error {
+      page_id: None,
+      message: "boom".to_string(),
+    };
+
+    assert_eq!(
+      serde_json::to_string(&started).unwrap(),
+      r#"{"event":"page_started","page_id":"42","title":"Runbook"}"#
+    );
+    assert_eq!(
+      serde_json::to_string(&error).unwrap(),
+      r#"{"event":"error","message":"boom"}"#
+    );
+
+    let mut links = LinkMap::default();
+    links
+      .attachments
+      .insert("spec.pdf".to_string(), "attachments/spec.pdf".to_string());
+    links.pages.insert(
+      "https://example.atlassian.net/wiki/display/OPS/Setup".to_string(),
+      "Setup.md".to_string(),
+    );
+    let done = Event::PageDone {
+      page_id: "42",
+      title: "Runbook",
+      path: "out/Runbook.md".to_string(),
+      links: &links,
+    };
+    assert_eq!(
+      serde_json::to_string(&done).unwrap(),
+      r#"{"event":"page_done","page_id":"42","title":"Runbook","path":"out/Runbook.md","links":{"images":{},"attachments":{"spec.pdf":"attachments/spec.pdf"},"pages":{"https://example.atlassian.net/wiki/display/OPS/Setup":"Setup.md"}}}"#
+    );
+  }
+}
@@ -0,0 +1,159 @@
+//! Downloads of images embedded from other sites.
+//!
+//! Images inserted by URL (`<ri:url>`) are hotlinks to another site and
+//! break once that site moves or removes them. With
+//! `--download-external-images`, [`ExternalImageFetcher`] downloads them into
+//! the images directory like attachment images. Since the other site is not
+//! under the export's control, every request times out, responses that are
+//! not images or exceed the size cap are rejected, and no Confluence
+//! credentials are sent.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+
+use crate::integrity::sha256_hex;
+
+/// Number of hex digits of the URL hash added to downloaded file names.
+const URL_HASH_LEN: usize = 8;
+
+/// Downloads images from URLs outside Confluence.
+#[derive(Debug)]
+pub struct ExternalImageFetcher {
+  client: reqwest::Client,
+  max_bytes: u64,
+}
+
+impl ExternalImageFetcher {
+  /// Create a fetcher.
+  ///
+  /// # Arguments
+  /// * `timeout` - Maximum time spent on a single image.
+  /// * `max_bytes` - Largest image downloaded; larger images stay hotlinked.
+  ///
+  /// # Errors
+  /// Returns an error if the HTTP client cannot be built.
+  pub fn new(timeout: Duration, max_bytes: u64) -> Result<Self> {
+    let client = reqwest::Client::builder()
+      .timeout(timeout)
+      .user_agent(concat!("confluence-dl/", env!("CARGO_PKG_VERSION")))
+      .build()
+      .context("Failed to build HTTP client for external images")?;
+    Ok(Self { client, max_bytes })
+  }
+
+  /// Download the image at `url`.
+  ///
+  /// # Errors
+  /// Returns an error when the request fails, the response is not an image,
+  /// or the image is larger than the size cap.
+  pub async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+    let mut response = self
+      .client
+      .get(url)
+      .header(reqwest::header::ACCEPT, "image/*")
+      .send()
+      .await?
+      .error_for_status()?;
+
+    let content_type = response
+      .headers()
+      .get(reqwest::header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .unwrap_or_default()
+      .to_string();
+    if !content_type.trim_start().to_ascii_lowercase().starts_with("image/") {
+      bail!("Not an image (content type '{content_type}')");
+    }
+    if response.content_length().is_some_and(|length| length > self.max_bytes) {
+      bail!("Image is larger than {} bytes", self.max_bytes);
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+      body.extend_from_slice(&chunk);
+      if body.len() as u64 > self.max_bytes {
+        bail!("Image is larger than {} bytes", self.max_bytes);
+      }
+    }
+    Ok(body)
+  }
+}
+
+/// File name for the image at `url`: the last path segment with a hash of
+/// the URL added, since unrelated sites often use the same names such as
+/// `logo.png`.
+pub fn file_name(url: &str) -> String {
+  let path = url.split(['?', '#']).next().unwrap_or_default();
+  let path = path.split_once("://").map_or(path, |(_, rest)| rest);
+  let segment = path
+    .split_once('/')
+    .and_then(|(_, path)| path.rsplit('/').next())
+    .filter(|segment| !segment.is_empty())
+    .unwrap_or("image");
+  let segment = url::form_urlencoded::parse(segment.as_bytes())
+    .next()
+    .map_or_else(|| segment.to_string(), |(decoded, _)| decoded.into_owned());
+  let hash = &sha256_hex(url.as_bytes())[..URL_HASH_LEN];
+  match segment.rsplit_once('.') {
+    Some((stem, extension)) if !stem.is_empty() => format!("{stem}-{hash}.{extension}"),
+    _ => format!("{segment}-{hash}"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_file_name() {
+    let name = file_name("https://cdn.example.com/assets/Build%20status.svg?branch=main");
+    assert!(name.starts_with("Build status-"), "{name}");
+    assert!(name.ends_with(".svg"), "{name}");
+    assert_ne!(name, file_name("https://other.example.com/Build%20status.svg"));
+
+    assert!(file_name("https://example.com/").starts_with("image-"));
+    assert!(file_name("https://example.com").starts_with("image-"));
+  }
+
+  #[tokio::test]
+  async fn test_fetch_checks_type_and_size() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+      loop {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        tokio::spawn(async move {
+          let mut request = [0; 1024];
+          let length = socket.read(&mut request).await.unwrap_or_default();
+          let request = String::from_utf8_lossy(&request[..length]);
+          let (content_type, body) = if request.starts_with("GET /page") {
+            ("text/html", "<html></html>")
+          } else if request.starts_with("GET /large") {
+            ("image/png", "0123456789abcdef")
+          } else {
+            ("image/png", "PNG")
+          };
+          let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+          );
+          let _ = socket.write_all(response.as_bytes()).await;
+        });
+      }
+    });
+
+    let fetcher = ExternalImageFetcher::new(Duration::from_secs(5), 8).unwrap();
+    assert_eq!(
+      fetcher.fetch(&format!("http://{address}/logo.png")).await.unwrap(),
+      b"PNG"
+    );
+    let err = fetcher.fetch(&format!("http://{address}/page")).await.unwrap_err();
+    assert!(err.to_string().contains("Not an image"), "{err}");
+    let err = fetcher.fetch(&format!("http://{address}/large.png")).await.unwrap_err();
+    assert!(err.to_string().contains("larger than 8 bytes"), "{err}");
+    server.abort();
+  }
+}
@@ -11,6 +11,9 @@ pub enum OutputFormat {
   /// AsciiDoc output (Asciidoctor-compatible)
   #[value(alias = "adoc")]
   AsciiDoc,
+  /// JSON document per page holding its metadata, Markdown content, raw
+  /// storage, and downloaded files
+  Json,
 }
 
 impl OutputFormat {
@@ -19,10 +22,263 @@ impl OutputFormat {
     match self {
       OutputFormat::Markdown => "md",
       OutputFormat::AsciiDoc => "adoc",
+      OutputFormat::Json => "json",
+    }
+  }
+
+  /// Returns the MIME type of documents in this output format.
+  pub fn mime_type(&self) -> &'static str {
+    match self {
+      OutputFormat::Markdown => "text/markdown",
+      OutputFormat::AsciiDoc => "text/asciidoc",
+      OutputFormat::Json => "application/json",
+    }
+  }
+
+  /// Returns the format page content is converted to; JSON documents carry
+  /// Markdown.
+  pub fn content_format(self) -> OutputFormat {
+    match self {
+      OutputFormat::Json => OutputFormat::Markdown,
+      format => format,
+    }
+  }
+
+  /// A heading of a document generated next to the pages, such as the space
+  /// README or the task report; level 1 is the document title.
+  pub fn heading(self, level: usize, text: &str) -> String {
+    let marker = match self.content_format() {
+      OutputFormat::AsciiDoc => "=",
+      _ => "#",
+    };
+    format!("{} {text}", marker.repeat(level))
+  }
+
+  /// A link of a generated document to a URL or to a relative path whose
+  /// spaces are already escaped.
+  pub fn link(self, text: &str, target: &str) -> String {
+    match self.content_format() {
+      OutputFormat::AsciiDoc if target.contains("://") => format!("{target}[{}]", text.replace(']', "\\]")),
+      OutputFormat::AsciiDoc => format!("link:{target}[{}]", text.replace(']', "\\]")),
+      _ => format!("[{text}]({target})"),
+    }
+  }
+
+  /// The marker of an unordered list item in a generated document.
+  pub fn bullet(self) -> &'static str {
+    match self.content_format() {
+      OutputFormat::AsciiDoc => "*",
+      _ => "-",
+    }
+  }
+
+  /// Bold text in a generated document.
+  pub fn strong(self, text: &str) -> String {
+    match self.content_format() {
+      OutputFormat::AsciiDoc => format!("*{text}*"),
+      _ => format!("**{text}**"),
+    }
+  }
+}
+
+/// Placement of table captions relative to the rendered table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TableCaptionPosition {
+  /// Emit the caption on its own line before the table (default)
+  #[default]
+  Above,
+  /// Emit the caption on its own line after the table
+  Below,
+}
+
+/// File format of tables extracted with `--extract-tables`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TableFormat {
+  /// Comma-separated values
+  Csv,
+  /// Tab-separated values
+  Tsv,
+}
+
+impl TableFormat {
+  /// Returns the file extension for this table format (without leading dot).
+  pub fn file_extension(&self) -> &'static str {
+    match self {
+      TableFormat::Csv => "csv",
+      TableFormat::Tsv => "tsv",
+    }
+  }
+}
+
+/// How Markdown tables render cells that span several rows or columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TableSpanMode {
+  /// Render tables with spanning cells as HTML tables (default)
+  #[default]
+  Html,
+  /// Repeat a spanning cell's content in every cell it covers
+  Duplicate,
+  /// Keep a spanning cell's content in its first cell and leave the rest empty
+  Pad,
+}
+
+/// Syntax used for level 1 and 2 headings in Markdown output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum HeadingStyle {
+  /// `# Title` (default)
+  #[default]
+  Atx,
+  /// Underlined with `===` or `---`; deeper headings stay ATX
+  Setext,
+}
+
+/// How headings deeper than `--max-heading-level` are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum HeadingOverflow {
+  /// Render them at the maximum level (default)
+  #[default]
+  Clamp,
+  /// Render them as bold paragraphs
+  Bold,
+}
+
+impl HeadingOverflow {
+  /// The level a heading is rendered at, or `None` when it becomes a bold
+  /// paragraph.
+  ///
+  /// # Arguments
+  /// * `level` - Heading level from the source page (1-6).
+  /// * `max_level` - Deepest heading level allowed, if any.
+  pub fn apply(self, level: usize, max_level: Option<u8>) -> Option<usize> {
+    match max_level.map(usize::from) {
+      Some(max) if level > max => match self {
+        HeadingOverflow::Clamp => Some(max),
+        HeadingOverflow::Bold => None,
+      },
+      _ => Some(level),
     }
   }
 }
 
+/// Marker used for unordered list items in Markdown output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum BulletMarker {
+  /// `- item` (default)
+  #[default]
+  Dash,
+  /// `* item`
+  Asterisk,
+  /// `+ item`
+  Plus,
+}
+
+impl BulletMarker {
+  /// The marker character.
+  pub fn as_char(&self) -> char {
+    match self {
+      BulletMarker::Dash => '-',
+      BulletMarker::Asterisk => '*',
+      BulletMarker::Plus => '+',
+    }
+  }
+}
+
+/// Delimiter used for emphasis and strong emphasis in Markdown output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum EmphasisMarker {
+  /// `_text_` / `__text__`
+  Underscore,
+  /// `*text*` / `**text**`
+  Asterisk,
+}
+
+impl EmphasisMarker {
+  /// The delimiter character.
+  pub fn as_char(&self) -> char {
+    match self {
+      EmphasisMarker::Underscore => '_',
+      EmphasisMarker::Asterisk => '*',
+    }
+  }
+}
+
+/// Fence used for code blocks in Markdown output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum FenceStyle {
+  /// ```` ``` ```` (default)
+  #[default]
+  Backtick,
+  /// `~~~`
+  Tilde,
+}
+
+/// Markdown variant targeted by the output.
+///
+/// Decides the syntax of constructs that plain Markdown has no spelling for:
+/// admonitions, task lists, and links to pages without a known output path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MarkdownFlavor {
+  /// GitHub Flavored Markdown: `> [!NOTE]` alerts and `- [ ]` task lists
+  Gfm,
+  /// Strict CommonMark: blockquote admonitions and no extension syntax
+  #[value(name = "commonmark")]
+  CommonMark,
+  /// Pandoc Markdown: `::: note` fenced divs for admonitions and panels, grid
+  /// tables for tables with block content, native definition lists, and
+  /// `[[Title]]` wiki links
+  Pandoc,
+  /// Obsidian: `> [!note]` callouts and `[[Title]]` wiki links
+  Obsidian,
+}
+
+/// Presets tailoring the export for a documentation site generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportProfile {
+  /// Docusaurus: page front matter, `:::note` admonitions, MDX escaping, and
+  /// a `sidebars.js` fragment
+  Docusaurus,
+  /// MkDocs: pages written under `docs/` and a `mkdocs.yml` whose nav mirrors
+  /// the page tree
+  #[value(name = "mkdocs")]
+  MkDocs,
+}
+
+/// Where a page's content state ("Draft", "In review", "Verified") is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ContentStateDisplay {
+  /// Do not fetch content states (default)
+  #[default]
+  Off,
+  /// A `content_state` front matter field
+  FrontMatter,
+  /// A status line at the top of the page body
+  Badge,
+  /// Both the front matter field and the status line
+  Both,
+}
+
+impl ContentStateDisplay {
+  /// Whether the state is recorded in front matter.
+  pub fn in_front_matter(self) -> bool {
+    matches!(self, Self::FrontMatter | Self::Both)
+  }
+
+  /// Whether the state is shown in the page body.
+  pub fn as_badge(self) -> bool {
+    matches!(self, Self::Badge | Self::Both)
+  }
+}
+
+/// How `ls` prints the page tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ListingFormat {
+  /// Box-drawing tree with page metadata (default)
+  #[default]
+  Tree,
+  /// Nested JSON document, without progress output
+  Json,
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -33,8 +289,40 @@ mod tests {
     assert_eq!(OutputFormat::AsciiDoc.file_extension(), "adoc");
   }
 
+  #[test]
+  fn test_mime_type() {
+    assert_eq!(OutputFormat::Markdown.mime_type(), "text/markdown");
+    assert_eq!(OutputFormat::AsciiDoc.mime_type(), "text/asciidoc");
+  }
+
+  #[test]
+  fn test_document_markup() {
+    assert_eq!(OutputFormat::Markdown.heading(2, "Tasks"), "## Tasks");
+    assert_eq!(OutputFormat::AsciiDoc.heading(2, "Tasks"), "== Tasks");
+    assert_eq!(OutputFormat::Json.link("Plan", "Plan.md"), "[Plan](Plan.md)");
+    assert_eq!(
+      OutputFormat::AsciiDoc.link("Plan [v2]", "Plan.adoc"),
+      "link:Plan.adoc[Plan [v2\\]]"
+    );
+    assert_eq!(
+      OutputFormat::AsciiDoc.link("DOCS", "https://example.com/wiki"),
+      "https://example.com/wiki[DOCS]"
+    );
+    assert_eq!(OutputFormat::AsciiDoc.bullet(), "*");
+    assert_eq!(OutputFormat::Markdown.strong("Space:"), "**Space:**");
+  }
+
   #[test]
   fn test_default_is_markdown() {
     assert_eq!(OutputFormat::default(), OutputFormat::Markdown);
   }
+
+  #[test]
+  fn test_heading_overflow_apply() {
+    assert_eq!(HeadingOverflow::Clamp.apply(5, None), Some(5));
+    assert_eq!(HeadingOverflow::Clamp.apply(3, Some(3)), Some(3));
+    assert_eq!(HeadingOverflow::Clamp.apply(6, Some(3)), Some(3));
+    assert_eq!(HeadingOverflow::Bold.apply(6, Some(3)), None);
+    assert_eq!(HeadingOverflow::Bold.apply(2, Some(3)), Some(2));
+  }
 }
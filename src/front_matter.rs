@@ -0,0 +1,358 @@
+//! Front matter blocks written at the top of exported pages.
+//!
+//! Markdown output uses a YAML block delimited by `---` lines, while AsciiDoc
+//! output uses document attribute entries (`:key: value`) so the file remains
+//! a valid Asciidoctor document. The block records metadata such as the
+//! content hash used to detect local edits when re-exporting.
+
+use crate::format::OutputFormat;
+use crate::integrity::sha256_hex;
+
+/// Front matter key holding the hash of the exported page body.
+pub const CONTENT_HASH_KEY: &str = "confluence_dl_hash";
+
+/// Ordered set of front matter fields.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrontMatter {
+  fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Field {
+  key: String,
+  value: String,
+  /// Emit the value without YAML quoting (numbers, booleans).
+  verbatim: bool,
+}
+
+impl Field {
+  fn json_value(&self) -> serde_json::Value {
+    if !self.verbatim {
+      return serde_json::Value::String(self.value.clone());
+    }
+    if let Some(items) = self.value.strip_prefix('[').and_then(|value| value.strip_suffix(']')) {
+      return split_flow_sequence(items)
+        .into_iter()
+        .map(|item| serde_json::Value::String(parse_yaml_scalar(item)))
+        .collect();
+    }
+    serde_json::from_str(&self.value).unwrap_or_else(|_| serde_json::Value::String(self.value.clone()))
+  }
+}
+
+impl FrontMatter {
+  /// Set a string field, replacing any previous value while keeping its
+  /// position.
+  pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+    self.set(key.into(), value.into(), false);
+  }
+
+  /// Set a numeric field so YAML consumers read it as a number rather than a
+  /// string.
+  pub fn insert_number(&mut self, key: impl Into<String>, value: usize) {
+    self.set(key.into(), value.to_string(), true);
+  }
+
+  /// Set a field written without quoting, such as a boolean or a decimal
+  /// number.
+  pub fn insert_literal(&mut self, key: impl Into<String>, value: impl ToString) {
+    self.set(key.into(), value.to_string(), true);
+  }
+
+  /// Set a list field, rendered as a YAML flow sequence (`[a, b]`).
+  pub fn insert_list<I, S>(&mut self, key: impl Into<String>, values: I)
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    let items: Vec<String> = values.into_iter().map(|value| yaml_scalar(value.as_ref())).collect();
+    self.set(key.into(), format!("[{}]", items.join(", ")), true);
+  }
+
+  fn set(&mut self, key: String, value: String, verbatim: bool) {
+    match self.fields.iter_mut().find(|field| field.key == key) {
+      Some(field) => {
+        field.value = value;
+        field.verbatim = verbatim;
+      }
+      None => self.fields.push(Field { key, value, verbatim }),
+    }
+  }
+
+  /// Look up a field by key.
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self
+      .fields
+      .iter()
+      .find(|field| field.key == key)
+      .map(|field| field.value.as_str())
+  }
+
+  /// Copy every field of `other` into this block, replacing existing values.
+  pub fn extend(&mut self, other: &FrontMatter) {
+    for field in &other.fields {
+      self.set(field.key.clone(), field.value.clone(), field.verbatim);
+    }
+  }
+
+  /// The fields as a JSON object, keeping numbers, booleans, and lists
+  /// typed.
+  pub fn to_json(&self) -> serde_json::Map<String, serde_json::Value> {
+    self
+      .fields
+      .iter()
+      .map(|field| (field.key.clone(), field.json_value()))
+      .collect()
+  }
+
+  /// Whether the block has no fields.
+  pub fn is_empty(&self) -> bool {
+    self.fields.is_empty()
+  }
+
+  /// Render the block for the given output format.
+  ///
+  /// # Returns
+  /// The block followed by a blank line, or an empty string when there are no
+  /// fields.
+  pub fn render(&self, format: OutputFormat) -> String {
+    if self.is_empty() {
+      return String::new();
+    }
+
+    let mut result = String::new();
+    match format {
+      OutputFormat::Markdown => {
+        result.push_str("---\n");
+        for field in &self.fields {
+          let value = if field.verbatim {
+            field.value.clone()
+          } else {
+            yaml_scalar(&field.value)
+          };
+          result.push_str(&format!("{}: {value}\n", field.key));
+        }
+        result.push_str("---\n");
+      }
+      OutputFormat::AsciiDoc => {
+        for field in &self.fields {
+          result.push_str(&format!(":{}: {}\n", field.key, field.value));
+        }
+      }
+      // JSON documents hold the fields in their own `front_matter` object
+      OutputFormat::Json => return String::new(),
+    }
+    result.push('\n');
+    result
+  }
+
+  /// Split a previously exported document into its front matter and body.
+  ///
+  /// This is the inverse of prefixing a body with [`FrontMatter::render`].
+  ///
+  /// # Arguments
+  /// * `content` - Full file content.
+  /// * `format` - Format the file was written in.
+  ///
+  /// # Returns
+  /// The parsed block (if the document starts with one) and the remaining
+  /// body.
+  pub fn split(content: &str, format: OutputFormat) -> (Option<Self>, &str) {
+    match format {
+      OutputFormat::Markdown => split_yaml(content),
+      OutputFormat::AsciiDoc => split_attributes(content),
+      OutputFormat::Json => (None, content),
+    }
+  }
+}
+
+/// Compute the hash recorded in front matter for a page body.
+pub fn content_hash(body: &str) -> String {
+  format!("sha256:{}", sha256_hex(body.as_bytes()))
+}
+
+/// Check whether an exported file was edited since it was written.
+///
+/// # Returns
+/// `None` when the file records no hash, such as one exported without
+/// `--conflict` or by an earlier version, so edits cannot be ruled out.
+pub fn has_local_edits(content: &str, format: OutputFormat) -> Option<bool> {
+  let (front_matter, body) = FrontMatter::split(content, format);
+  front_matter
+    .as_ref()
+    .and_then(|front_matter| front_matter.get(CONTENT_HASH_KEY))
+    .map(|hash| hash != content_hash(body))
+}
+
+fn split_yaml(content: &str) -> (Option<FrontMatter>, &str) {
+  let Some(rest) = content.strip_prefix("---\n") else {
+    return (None, content);
+  };
+  let Some(end) = rest.find("\n---\n") else {
+    return (None, content);
+  };
+
+  let mut front_matter = FrontMatter::default();
+  for line in rest[..end].lines() {
+    if let Some((key, value)) = line.split_once(':') {
+      let value = value.trim();
+      let verbatim = !value.starts_with('"') && yaml_scalar(value) != value;
+      front_matter.set(key.trim().to_string(), parse_yaml_scalar(value), verbatim);
+    }
+  }
+
+  let body = &rest[end + "\n---\n".len()..];
+  (Some(front_matter), body.strip_prefix('\n').unwrap_or(body))
+}
+
+fn split_attributes(content: &str) -> (Option<FrontMatter>, &str) {
+  let mut front_matter = FrontMatter::default();
+  let mut offset = 0;
+
+  for line in content.split_inclusive('\n') {
+    let Some((key, value)) = line.strip_prefix(':').and_then(|entry| entry.split_once(": ")) else {
+      break;
+    };
+    front_matter.insert(key, value.trim_end_matches('\n'));
+    offset += line.len();
+  }
+
+  if front_matter.is_empty() {
+    return (None, content);
+  }
+
+  let body = &content[offset..];
+  (Some(front_matter), body.strip_prefix('\n').unwrap_or(body))
+}
+
+/// Render a string as a YAML scalar, quoting only when required.
+pub(crate) fn yaml_scalar(value: &str) -> String {
+  let plain = !value.is_empty()
+    && value == value.trim()
+    && value
+      .chars()
+      .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.' | '/' | ':'))
+    && !value.contains(": ")
+    && !value.starts_with(['-', ':'])
+    && !matches!(
+      value.to_ascii_lowercase().as_str(),
+      "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+    )
+    && value.parse::<f64>().is_err();
+
+  if plain {
+    value.to_string()
+  } else {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+  }
+}
+
+/// Split the items of a YAML flow sequence written by
+/// [`FrontMatter::insert_list`], keeping commas inside quoted items.
+fn split_flow_sequence(items: &str) -> Vec<&str> {
+  let mut result = Vec::new();
+  let mut start = 0;
+  let mut quoted = false;
+  let mut escaped = false;
+  for (index, c) in items.char_indices() {
+    match c {
+      _ if escaped => escaped = false,
+      '\\' if quoted => escaped = true,
+      '"' => quoted = !quoted,
+      ',' if !quoted => {
+        result.push(items[start..index].trim());
+        start = index + 1;
+      }
+      _ => {}
+    }
+  }
+  result.push(items[start..].trim());
+  result.retain(|item| !item.is_empty());
+  result
+}
+
+fn parse_yaml_scalar(value: &str) -> String {
+  match value.strip_prefix('"').and_then(|inner| inner.strip_suffix('"')) {
+    Some(inner) => inner.replace("\\\"", "\"").replace("\\\\", "\\"),
+    None => value.to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_render_and_split_round_trip() {
+    let mut front_matter = FrontMatter::default();
+    front_matter.insert("title", "Release: \"2.0\"");
+    front_matter.insert(CONTENT_HASH_KEY, content_hash("# Body\n"));
+
+    for format in [OutputFormat::Markdown, OutputFormat::AsciiDoc] {
+      let document = format!("{}# Body\n", front_matter.render(format));
+      let (parsed, body) = FrontMatter::split(&document, format);
+      assert_eq!(parsed.as_ref(), Some(&front_matter));
+      assert_eq!(body, "# Body\n");
+    }
+  }
+
+  #[test]
+  fn test_render_markdown_quotes_when_needed() {
+    let mut front_matter = FrontMatter::default();
+    front_matter.insert("title", "Getting Started");
+    front_matter.insert("version", "2");
+    front_matter.insert("note", "a: b");
+    front_matter.insert_number("position", 2);
+    front_matter.insert_list("tags", ["public-docs", "yes"]);
+
+    let rendered = front_matter.render(OutputFormat::Markdown);
+    insta::assert_snapshot!(rendered, @r#"
+    ---
+    title: Getting Started
+    version: "2"
+    note: "a: b"
+    position: 2
+    tags: [public-docs, "yes"]
+    ---
+    "#);
+
+    let (parsed, _) = FrontMatter::split(&rendered, OutputFormat::Markdown);
+    assert_eq!(parsed, Some(front_matter));
+  }
+
+  #[test]
+  fn test_to_json() {
+    let mut front_matter = FrontMatter::default();
+    front_matter.insert("version", "2");
+    front_matter.insert_number("position", 2);
+    front_matter.insert_literal("draft", true);
+    front_matter.insert_list("tags", ["public-docs", "a, \"b\""]);
+
+    assert_eq!(
+      serde_json::Value::Object(front_matter.to_json()),
+      serde_json::json!({"version": "2", "position": 2, "draft": true, "tags": ["public-docs", "a, \"b\""]})
+    );
+  }
+
+  #[test]
+  fn test_has_local_edits() {
+    let mut front_matter = FrontMatter::default();
+    front_matter.insert(CONTENT_HASH_KEY, content_hash("Original\n"));
+    let exported = format!("{}Original\n", front_matter.render(OutputFormat::Markdown));
+
+    assert_eq!(has_local_edits(&exported, OutputFormat::Markdown), Some(false));
+    assert_eq!(
+      has_local_edits(&exported.replace("Original", "Edited"), OutputFormat::Markdown),
+      Some(true)
+    );
+  }
+
+  #[test]
+  fn test_has_local_edits_without_recorded_hash() {
+    assert_eq!(has_local_edits("Original\n", OutputFormat::Markdown), None);
+    assert_eq!(
+      has_local_edits("---\ntitle: Guide\n---\n\nEdited\n", OutputFormat::Markdown),
+      None
+    );
+  }
+}
@@ -0,0 +1,173 @@
+//! Front matter fields added to pages by configured rules.
+//!
+//! Documentation portals often expect fields Confluence does not store, such
+//! as an owning team. `--front-matter-rules` reads a JSON file of rules, each
+//! naming the pages it applies to by space key, label, or title glob and the
+//! fields to add to them:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "label": "platform", "fields": { "owner": "platform-team" } },
+//!     { "space": "ENG", "title": "RFC-*", "fields": { "type": "rfc", "tags": ["design"] } }
+//!   ]
+//! }
+//! ```
+//!
+//! A rule matches a page when every condition it names holds; a rule without
+//! conditions matches every page. Matching rules apply in file order, so later
+//! rules override fields set by earlier ones and by the exporter itself.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+
+use crate::confluence::Page;
+use crate::confluence::tree::glob_matches;
+use crate::front_matter::FrontMatter;
+
+/// Rules adding front matter fields to matching pages.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FrontMatterRules {
+  rules: Vec<FrontMatterRule>,
+}
+
+/// Fields to add to the pages matching every given condition.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrontMatterRule {
+  /// Key of the page's space, compared case-insensitively.
+  space: Option<String>,
+  /// Label the page must carry, compared case-insensitively.
+  label: Option<String>,
+  /// Glob (`*`, `?`) matching the whole page title, case-insensitively.
+  title: Option<String>,
+  /// Fields to add, in key order.
+  fields: BTreeMap<String, FieldValue>,
+}
+
+/// Value of a configured front matter field.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum FieldValue {
+  Text(String),
+  Flag(bool),
+  Number(serde_json::Number),
+  List(Vec<String>),
+}
+
+impl FrontMatterRules {
+  /// Read rules from a JSON file.
+  ///
+  /// # Errors
+  /// Returns an error when the file cannot be read, is not valid rules JSON,
+  /// or names an empty field key.
+  pub fn load(path: &Path) -> Result<Self> {
+    let content = std::fs::read_to_string(path)
+      .with_context(|| format!("Failed to read front matter rules from {}", path.display()))?;
+    Self::parse(&content).with_context(|| format!("Invalid front matter rules in {}", path.display()))
+  }
+
+  /// Parse rules from JSON.
+  ///
+  /// # Errors
+  /// Returns an error when the JSON does not describe rules or names an empty
+  /// field key.
+  pub fn parse(json: &str) -> Result<Self> {
+    let rules: Self = serde_json::from_str(json)?;
+    if rules
+      .rules
+      .iter()
+      .any(|rule| rule.fields.keys().any(|key| key.trim().is_empty()))
+    {
+      bail!("front matter field keys must not be empty");
+    }
+    Ok(rules)
+  }
+
+  /// Add the fields of every rule matching `page` to `front_matter`.
+  pub fn apply(&self, page: &Page, front_matter: &mut FrontMatter) {
+    for rule in self.rules.iter().filter(|rule| rule.matches(page)) {
+      for (key, value) in &rule.fields {
+        match value {
+          FieldValue::Text(text) => front_matter.insert(key, text),
+          FieldValue::Flag(flag) => front_matter.insert_literal(key, flag),
+          FieldValue::Number(number) => front_matter.insert_literal(key, number),
+          FieldValue::List(items) => front_matter.insert_list(key, items),
+        }
+      }
+    }
+  }
+}
+
+impl FrontMatterRule {
+  fn matches(&self, page: &Page) -> bool {
+    let space_matches = self.space.as_ref().is_none_or(|space| {
+      page
+        .space
+        .as_ref()
+        .is_some_and(|page_space| page_space.key.eq_ignore_ascii_case(space))
+    });
+    let label_matches = self.label.as_ref().is_none_or(|label| {
+      page
+        .labels()
+        .iter()
+        .any(|page_label| page_label.eq_ignore_ascii_case(label))
+    });
+    let title_matches = self.title.as_ref().is_none_or(|glob| glob_matches(glob, &page.title));
+    space_matches && label_matches && title_matches
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::format::OutputFormat;
+
+  #[test]
+  fn test_apply_matching_rules() {
+    let rules = FrontMatterRules::parse(
+      r#"{"rules": [
+        {"fields": {"source": "confluence"}},
+        {"label": "Platform", "fields": {"owner": "platform-team", "reviewed": true}},
+        {"space": "eng", "title": "RFC-*", "fields": {"owner": "architecture", "tags": ["design", "rfc"], "weight": 10}},
+        {"space": "OPS", "fields": {"owner": "ops"}}
+      ]}"#,
+    )
+    .unwrap();
+    let page: Page = serde_json::from_value(serde_json::json!({
+      "id": "1",
+      "type": "page",
+      "status": "current",
+      "title": "RFC-12 Storage",
+      "space": {"key": "ENG", "name": "Engineering", "type": "global"},
+      "metadata": {"labels": {"results": [{"prefix": "global", "name": "platform"}]}}
+    }))
+    .unwrap();
+
+    let mut front_matter = FrontMatter::default();
+    front_matter.insert("title", "RFC-12 Storage");
+    rules.apply(&page, &mut front_matter);
+
+    insta::assert_snapshot!(front_matter.render(OutputFormat::Markdown), @r"
+    ---
+    title: RFC-12 Storage
+    source: confluence
+    owner: architecture
+    reviewed: true
+    tags: [design, rfc]
+    weight: 10
+    ---
+    ");
+  }
+
+  #[test]
+  fn test_parse_rejects_invalid_rules() {
+    assert!(FrontMatterRules::parse(r#"{"rules": [{"lable": "x", "fields": {}}]}"#).is_err());
+    assert!(FrontMatterRules::parse(r#"{"rules": [{"fields": {" ": "x"}}]}"#).is_err());
+    assert!(FrontMatterRules::parse(r#"{"rules": [{"fields": {"owner": {"team": "x"}}}]}"#).is_err());
+  }
+}
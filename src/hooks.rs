@@ -0,0 +1,138 @@
+//! Hook points for applications embedding the exporter.
+//!
+//! [`ExportHooks`] is called at each step of a page export: before a page is
+//! fetched for conversion, after it is converted, for every file written
+//! alongside it, and once the page file is written. Embedding applications
+//! implement it to drive their own progress UI, to skip pages they already
+//! have cached, or to adjust converted pages before they are written, and
+//! pass it to [`export_with_hooks`](crate::commands::page::export_with_hooks).
+//! Every method has a no-op default, so implementations only override the
+//! events they need.
+
+use std::path::Path;
+
+use crate::confluence::Page;
+use crate::processed_page::ProcessedPage;
+
+/// Whether a page should be exported, as decided by
+/// [`ExportHooks::on_page_start`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PageAction {
+  /// Convert and write the page (default).
+  #[default]
+  Export,
+  /// Leave the page out; its child pages are still exported.
+  Skip,
+}
+
+/// Kind of file written alongside a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+  /// An image embedded in the page.
+  Image,
+  /// A file attached to the page.
+  Attachment,
+  /// A table extracted to CSV or TSV.
+  Table,
+}
+
+/// A page about to be exported.
+#[derive(Debug, Clone, Copy)]
+pub struct PageStart<'a> {
+  /// The page, with its storage content.
+  pub page: &'a Page,
+  /// Depth of the page in the exported tree; the root page has depth 0.
+  pub depth: usize,
+  /// Directory the page file will be written to.
+  pub output_dir: &'a Path,
+}
+
+/// A converted page that has not been written yet.
+#[derive(Debug)]
+pub struct PageConverted<'a> {
+  /// The page the content was converted from.
+  pub page: &'a Page,
+  /// Converted content, front matter, and assets; changes are written.
+  pub processed: &'a mut ProcessedPage,
+}
+
+/// A file written alongside a page.
+#[derive(Debug, Clone, Copy)]
+pub struct AssetDownloaded<'a> {
+  /// The page the file belongs to.
+  pub page: &'a Page,
+  /// Kind of file.
+  pub kind: AssetKind,
+  /// Path of the written file.
+  pub path: &'a Path,
+  /// Size of the file in bytes.
+  pub bytes: usize,
+}
+
+/// A page whose file was written.
+#[derive(Debug, Clone, Copy)]
+pub struct PageWritten<'a> {
+  /// The exported page.
+  pub page: &'a Page,
+  /// The written content and assets.
+  pub processed: &'a ProcessedPage,
+  /// Path of the written page file.
+  pub path: &'a Path,
+}
+
+/// Callbacks invoked while pages are exported.
+///
+/// Pages of a tree are exported in parallel, so hooks may be called
+/// concurrently from several tasks.
+pub trait ExportHooks: Send + Sync {
+  /// Called before a page is converted.
+  ///
+  /// # Returns
+  /// [`PageAction::Skip`] to leave the page out of the export.
+  fn on_page_start(&self, _start: &PageStart<'_>) -> PageAction {
+    PageAction::Export
+  }
+
+  /// Called after a page is converted, before anything is written.
+  fn on_page_converted(&self, _converted: &mut PageConverted<'_>) {}
+
+  /// Called for each image, attachment, and extracted table written for a
+  /// page.
+  fn on_asset_downloaded(&self, _asset: &AssetDownloaded<'_>) {}
+
+  /// Called after a page file is written.
+  fn on_page_written(&self, _written: &PageWritten<'_>) {}
+}
+
+/// Hooks that do nothing, used by the command-line export.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoHooks;
+
+impl ExportHooks for NoHooks {}
+
+/// Call [`ExportHooks::on_asset_downloaded`] for every file written with a
+/// page, then [`ExportHooks::on_page_written`].
+///
+/// # Arguments
+/// * `hooks` - Hooks to notify.
+/// * `page` - The exported page.
+/// * `processed` - The written content and assets.
+/// * `output_dir` - Directory the page and its assets were written to.
+/// * `path` - Path of the written page file.
+pub fn report_written(hooks: &dyn ExportHooks, page: &Page, processed: &ProcessedPage, output_dir: &Path, path: &Path) {
+  let assets = processed
+    .images
+    .iter()
+    .map(|asset| (AssetKind::Image, asset))
+    .chain(processed.attachments.iter().map(|asset| (AssetKind::Attachment, asset)))
+    .chain(processed.tables.iter().map(|asset| (AssetKind::Table, asset)));
+  for (kind, asset) in assets {
+    hooks.on_asset_downloaded(&AssetDownloaded {
+      page,
+      kind,
+      path: &output_dir.join(&asset.relative_path),
+      bytes: asset.content.size(),
+    });
+  }
+  hooks.on_page_written(&PageWritten { page, processed, path });
+}
@@ -0,0 +1,185 @@
+//! File formats of downloaded images.
+//!
+//! Attachment names do not always end in the extension of their content:
+//! diagram exports are uploaded as `flow.drawio`, pasted images may have no
+//! extension at all, and browsers only render a file linked from Markdown
+//! when its extension matches. [`image_file_name`] gives each image the
+//! extension of its media type. Some formats, such as EMF drawings from
+//! Office documents or TIFF scans, cannot be shown by browsers at all; an
+//! [`ImageConverter`] turns them into PNG files when configured with
+//! `--image-converter`.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Context, Result, bail};
+
+/// Extension of the files converted images are written as.
+pub const CONVERTED_EXTENSION: &str = "png";
+
+/// Image formats browsers cannot render.
+const UNSUPPORTED_EXTENSIONS: &[&str] = &["emf", "wmf", "tif", "tiff", "eps"];
+
+/// Extensions of image media types, with alternative spellings after the
+/// first.
+const MEDIA_TYPE_EXTENSIONS: &[(&str, &[&str])] = &[
+  ("image/png", &["png"]),
+  ("image/jpeg", &["jpg", "jpeg", "jpe"]),
+  ("image/gif", &["gif"]),
+  ("image/svg+xml", &["svg"]),
+  ("image/webp", &["webp"]),
+  ("image/bmp", &["bmp"]),
+  ("image/avif", &["avif"]),
+  ("image/tiff", &["tiff", "tif"]),
+  ("image/emf", &["emf"]),
+  ("image/x-emf", &["emf"]),
+  ("image/wmf", &["wmf"]),
+  ("image/x-wmf", &["wmf"]),
+];
+
+/// File name for an image with the given media type.
+///
+/// The name is kept when its extension matches the media type, or when the
+/// media type is unknown; otherwise the media type's extension is appended,
+/// so `flow.drawio` served as `image/png` becomes `flow.drawio.png`.
+pub fn image_file_name(filename: &str, media_type: Option<&str>) -> String {
+  let Some(extensions) = media_type.and_then(|media_type| {
+    let media_type = media_type.split(';').next().unwrap_or_default().trim();
+    MEDIA_TYPE_EXTENSIONS
+      .iter()
+      .find(|(known, _)| known.eq_ignore_ascii_case(media_type))
+      .map(|(_, extensions)| *extensions)
+  }) else {
+    return filename.to_string();
+  };
+
+  let matches = extension(filename).is_some_and(|current| extensions.contains(&current.as_str()));
+  if matches {
+    filename.to_string()
+  } else {
+    format!("{filename}.{}", extensions[0])
+  }
+}
+
+/// Whether browsers cannot render images with this file name.
+pub fn is_unsupported(filename: &str) -> bool {
+  extension(filename).is_some_and(|extension| UNSUPPORTED_EXTENSIONS.contains(&extension.as_str()))
+}
+
+fn extension(filename: &str) -> Option<String> {
+  filename
+    .rsplit_once('.')
+    .map(|(_, extension)| extension.to_ascii_lowercase())
+}
+
+/// Converts images browsers cannot render into PNG images.
+pub trait ImageConverter: fmt::Debug + Send + Sync {
+  /// Convert an image.
+  ///
+  /// # Arguments
+  /// * `content` - The downloaded image.
+  /// * `extension` - Lowercase extension of the image's format, e.g. `emf`.
+  ///
+  /// # Errors
+  /// Returns an error when the image cannot be converted.
+  fn convert(&self, content: &[u8], extension: &str) -> Result<Vec<u8>>;
+}
+
+/// Converts images with an external program, such as ImageMagick or
+/// Inkscape.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandConverter {
+  program: String,
+  args: Vec<String>,
+}
+
+/// Counter keeping the temporary files of concurrent conversions apart.
+static CONVERSIONS: AtomicUsize = AtomicUsize::new(0);
+
+impl CommandConverter {
+  /// Parse a converter command line.
+  ///
+  /// The command is split on whitespace and run without a shell; `{input}`
+  /// and `{output}` in its arguments are replaced with the paths of the image
+  /// to convert and of the PNG file to write, e.g.
+  /// `magick {input} {output}`.
+  ///
+  /// # Errors
+  /// Returns an error when the command is empty or lacks either placeholder.
+  pub fn parse(command: &str) -> Result<Self> {
+    let mut words = command.split_whitespace().map(str::to_string);
+    let Some(program) = words.next() else {
+      bail!("The image converter command is empty");
+    };
+    let args: Vec<String> = words.collect();
+    for placeholder in ["{input}", "{output}"] {
+      if !args.iter().any(|arg| arg.contains(placeholder)) {
+        bail!("The image converter command has no {placeholder} placeholder");
+      }
+    }
+    Ok(Self { program, args })
+  }
+}
+
+impl ImageConverter for CommandConverter {
+  fn convert(&self, content: &[u8], extension: &str) -> Result<Vec<u8>> {
+    let id = CONVERSIONS.fetch_add(1, Ordering::Relaxed);
+    let base = std::env::temp_dir().join(format!("confluence-dl-{}-{id}", std::process::id()));
+    let input = PathBuf::from(format!("{}.{extension}", base.display()));
+    let output = PathBuf::from(format!("{}.{CONVERTED_EXTENSION}", base.display()));
+
+    let result = (|| {
+      std::fs::write(&input, content).with_context(|| format!("Failed to write {}", input.display()))?;
+      let args = self.args.iter().map(|arg| {
+        arg
+          .replace("{input}", &input.to_string_lossy())
+          .replace("{output}", &output.to_string_lossy())
+      });
+      let status = Command::new(&self.program)
+        .args(args)
+        .stdout(Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run {}", self.program))?;
+      if !status.success() {
+        bail!("{} exited with {status}", self.program);
+      }
+      std::fs::read(&output).with_context(|| format!("{} did not write {}", self.program, output.display()))
+    })();
+
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+    result
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_image_file_name() {
+    assert_eq!(image_file_name("flow.drawio", Some("image/png")), "flow.drawio.png");
+    assert_eq!(image_file_name("pasted", Some("image/svg+xml")), "pasted.svg");
+    assert_eq!(image_file_name("Photo.JPEG", Some("image/jpeg")), "Photo.JPEG");
+    assert_eq!(image_file_name("logo.svg", None), "logo.svg");
+    assert_eq!(image_file_name("chart.emf", Some("image/x-emf")), "chart.emf");
+    assert!(is_unsupported("chart.EMF"));
+    assert!(!is_unsupported("flow.drawio.png"));
+  }
+
+  #[test]
+  fn test_command_converter() {
+    assert!(CommandConverter::parse("magick {input}").is_err());
+    assert!(CommandConverter::parse("  ").is_err());
+
+    // `cp` stands in for a real converter
+    if cfg!(unix) {
+      let converter = CommandConverter::parse("cp {input} {output}").unwrap();
+      assert_eq!(converter.convert(b"EMF", "emf").unwrap(), b"EMF");
+      let failing = CommandConverter::parse("false {input} {output}").unwrap();
+      assert!(failing.convert(b"EMF", "emf").is_err());
+    }
+  }
+}
@@ -8,11 +8,75 @@ use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use clap::ValueEnum;
+use futures::{StreamExt, TryStreamExt, stream};
+use percent_encoding::percent_decode_str;
 use roxmltree::{Document, Node};
 
 const SYNTHETIC_NS_BASE: &str = "https://confluence.example/";
 
+use crate::attachments::MAX_CONCURRENT_ASSET_DOWNLOADS;
 use crate::confluence::ConfluenceApi;
+use crate::format::OutputFormat;
+use crate::markdown::utils::{escape_attribute, get_diagram_reference};
+use crate::paths;
+
+/// How downloaded images are named in the images directory.
+///
+/// Sibling pages of a tree export share one images directory, so two pages
+/// with different attachments of the same name need distinct file names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImageNaming {
+  /// The attachment filename (default)
+  #[default]
+  Name,
+  /// The attachment filename prefixed with the page ID
+  PageId,
+  /// The attachment filename with a hash of the image content appended
+  Hash,
+}
+
+/// Resolution of downloaded attachment images.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImageQuality {
+  /// The uploaded file at full resolution (default)
+  #[default]
+  Original,
+  /// The preview Confluence generates for raster images, much smaller than
+  /// full-resolution screenshots
+  Thumbnail,
+}
+
+/// Extensions of the images Confluence generates thumbnails for.
+const THUMBNAIL_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp"];
+
+impl ImageQuality {
+  /// The file name and URL to download an attachment image with.
+  ///
+  /// Thumbnails are saved with a `-thumbnail` suffix, so a later export of
+  /// the originals does not mistake them for already downloaded images.
+  /// Images without a thumbnail, such as SVGs or images outside Confluence,
+  /// keep their name and URL.
+  ///
+  /// # Arguments
+  /// * `filename` - Attachment filename of the image.
+  /// * `url` - Download URL of the original image.
+  pub fn resolve(self, filename: &str, url: &str) -> (String, String) {
+    match filename.rsplit_once('.') {
+      Some((stem, extension))
+        if self == ImageQuality::Thumbnail
+          && url.contains("/download/attachments/")
+          && THUMBNAIL_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()) =>
+      {
+        (
+          format!("{stem}-thumbnail.{extension}"),
+          url.replacen("/download/attachments/", "/download/thumbnails/", 1),
+        )
+      }
+      _ => (filename.to_string(), url.to_string()),
+    }
+  }
+}
 
 /// Information about an image found in Confluence content
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -26,7 +90,8 @@ pub struct ImageReference {
 /// Extracts image references from Confluence storage format content.
 ///
 /// Parses the HTML/XML content to find `<ac:image>` tags and extracts
-/// the attachment filenames and alt text.
+/// the attachment filenames and alt text. The PNG exports of `drawio` and
+/// `gliffy` diagram macros are included as well.
 ///
 /// # Arguments
 /// * `storage_content` - Raw storage format XML/HTML snippet from Confluence.
@@ -57,9 +122,181 @@ pub fn extract_image_references(storage_content: &str) -> Result<Vec<ImageRefere
     }
   }
 
+  // Diagram macros keep a PNG export of the diagram as an attachment
+  for diagram in document
+    .descendants()
+    .filter(|node| matches_tag(*node, "ac:structured-macro"))
+    .filter_map(get_diagram_reference)
+  {
+    images.push(ImageReference {
+      filename: diagram.preview_filename(),
+      alt_text: diagram.name,
+    });
+  }
+
   Ok(images)
 }
 
+/// Extracts the URLs of images embedded from other sites.
+///
+/// Returns the `http(s)` URLs of `<ac:image>` tags with an `ri:url` target,
+/// each once, in document order.
+///
+/// # Arguments
+/// * `storage_content` - Raw storage format XML/HTML snippet from Confluence.
+pub fn extract_external_image_urls(storage_content: &str) -> Result<Vec<String>> {
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document = Document::parse(&wrapped).context("Failed to parse Confluence storage content for images")?;
+  let mut urls: Vec<String> = Vec::new();
+
+  for url in document
+    .descendants()
+    .filter(|node| matches_tag(*node, "ac:image"))
+    .flat_map(|image| image.children().filter(|child| matches_tag(*child, "ri:url")))
+    .filter_map(|url| get_attribute(url, "ri:value"))
+  {
+    let url = url.trim().to_string();
+    if (url.starts_with("https://") || url.starts_with("http://")) && !urls.contains(&url) {
+      urls.push(url);
+    }
+  }
+
+  Ok(urls)
+}
+
+/// An image found only in the rendered view HTML of a page.
+///
+/// Older pages sometimes embed images as plain `<img src="/download/...">`
+/// tags without an `ri:attachment` reference in storage, so they are only
+/// discoverable from the view representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewImage {
+  /// The `src` URL as written in the view HTML (usually instance-relative).
+  pub url: String,
+  /// The decoded file name taken from the last path segment of the URL.
+  pub filename: String,
+  /// The alt text for the image
+  pub alt_text: String,
+}
+
+/// Extracts Confluence-hosted images from a page's view HTML.
+///
+/// Only `<img>` tags whose `src` points at a Confluence download path
+/// (`/download/...`) are returned; external images and emoticons are ignored.
+/// Each URL is returned once, in document order.
+///
+/// # Arguments
+/// * `view_html` - Rendered view representation of the page.
+///
+/// # Returns
+/// A vector of [`ViewImage`] values describing discovered images.
+pub fn extract_view_image_references(view_html: &str) -> Vec<ViewImage> {
+  let lower = view_html.to_ascii_lowercase();
+  let mut images: Vec<ViewImage> = Vec::new();
+  let mut offset = 0;
+
+  while let Some(found) = lower[offset..].find("<img") {
+    let start = offset + found;
+    let end = lower[start..].find('>').map_or(lower.len(), |idx| start + idx);
+    offset = end;
+
+    let tag = &view_html[start..end];
+    let Some(url) = html_attribute(tag, "src").map(|src| src.replace("&amp;", "&")) else {
+      continue;
+    };
+    if !url.contains("/download/") || images.iter().any(|image| image.url == url) {
+      continue;
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let segment = path.rsplit('/').next().unwrap_or_default();
+    let filename = percent_decode_str(segment).decode_utf8_lossy().into_owned();
+    if filename.is_empty() {
+      continue;
+    }
+
+    images.push(ViewImage {
+      alt_text: html_attribute(tag, "alt")
+        .filter(|alt| !alt.is_empty())
+        .unwrap_or_else(|| filename.clone()),
+      url,
+      filename,
+    });
+  }
+
+  images
+}
+
+/// Reads a quoted attribute from the text of an HTML start tag.
+fn html_attribute(tag: &str, name: &str) -> Option<String> {
+  let lower = tag.to_ascii_lowercase();
+  let mut offset = 0;
+  while let Some(found) = lower[offset..].find(name) {
+    let start = offset + found;
+    offset = start + name.len();
+
+    let preceded_by_space = lower[..start].ends_with(|c: char| c.is_ascii_whitespace());
+    let rest = lower[offset..].trim_start();
+    if !preceded_by_space || !rest.starts_with('=') {
+      continue;
+    }
+
+    let value_start = tag.len() - rest.len() + 1;
+    let value = tag[value_start..].trim_start();
+    let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+    let value = &value[1..];
+    return value.find(quote).map(|end| value[..end].to_string());
+  }
+  None
+}
+
+/// Points view-only images at their downloaded files.
+///
+/// References already present in the converted content (for example via an
+/// `ri:url` image) are rewritten in place. Images the storage format did not
+/// reference at all are appended to the end of the document so they are not
+/// lost.
+///
+/// # Arguments
+/// * `content` - Converted page content.
+/// * `images` - Images extracted from the view HTML.
+/// * `paths` - Mapping from image URL to the local relative path.
+/// * `format` - Format of `content`.
+///
+/// # Returns
+/// The content with every downloaded view image referenced locally.
+pub fn link_view_images(
+  content: &str,
+  images: &[ViewImage],
+  paths: &HashMap<String, PathBuf>,
+  format: OutputFormat,
+) -> String {
+  let mut result = match format {
+    OutputFormat::Markdown | OutputFormat::Json => update_markdown_image_links(content, paths),
+    OutputFormat::AsciiDoc => update_asciidoc_image_links(content, paths),
+  };
+
+  for image in images {
+    let Some(path) = paths.get(&image.url) else {
+      continue;
+    };
+    let path = path.to_str().unwrap_or("").replace('\\', "/");
+    if result.contains(&path) {
+      continue;
+    }
+    if !result.ends_with("\n\n") {
+      result.push_str(if result.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    match format {
+      OutputFormat::Markdown | OutputFormat::Json => result.push_str(&format!("![{}]({path})\n", image.alt_text)),
+      OutputFormat::AsciiDoc => result.push_str(&format!("image::{path}[{}]\n", image.alt_text)),
+    }
+  }
+
+  result
+}
+
 /// Splits a qualified tag or attribute name into prefix and local name.
 ///
 /// # Arguments
@@ -257,6 +494,7 @@ fn preprocess_html_entities(text: &str) -> String {
 /// * `output_dir` - Root directory where assets should be written.
 /// * `images_subdir` - Subdirectory under `output_dir` for storing images.
 /// * `overwrite` - When `true`, existing files are replaced.
+/// * `quality` - Whether originals or thumbnails are downloaded.
 ///
 /// # Returns
 /// A map from original attachment filenames to relative filesystem paths.
@@ -267,6 +505,7 @@ pub async fn download_images(
   output_dir: &Path,
   images_subdir: &str,
   overwrite: bool,
+  quality: ImageQuality,
 ) -> Result<HashMap<String, PathBuf>> {
   let mut filename_map = HashMap::new();
 
@@ -286,44 +525,46 @@ pub async fn download_images(
     .await
     .context("Failed to create images directory")?;
 
-  // Download each image
+  // Resolve every image before downloading so a missing attachment fails fast
+  let mut pending = Vec::new();
   for image_ref in image_refs {
-    // Find the attachment matching this image
     let attachment = attachments
       .iter()
       .find(|a| a.title == image_ref.filename)
       .with_context(|| format!("Attachment not found: {}", image_ref.filename))?;
 
-    // Sanitize filename for filesystem
-    let safe_filename = sanitize_filename(&image_ref.filename);
-    let output_path = images_dir.join(&safe_filename);
-
-    // Skip if file exists and overwrite is false
-    if output_path.exists() && !overwrite {
-      // Still add to map
-      let relative_path = PathBuf::from(images_subdir).join(&safe_filename);
-      filename_map.insert(image_ref.filename.clone(), relative_path);
-      continue;
-    }
-
-    // Get download URL from attachment links
     let download_url = attachment
       .links
       .as_ref()
       .and_then(|l| l.download.as_ref())
       .with_context(|| format!("No download link for attachment: {}", image_ref.filename))?;
+    let (name, download_url) = quality.resolve(&image_ref.filename, download_url);
 
-    // Download the image
-    client
-      .download_attachment(download_url, &output_path)
-      .await
-      .with_context(|| format!("Failed to download image: {}", image_ref.filename))?;
-
-    // Store the relative path (relative to output_dir)
+    // Sanitize filename for filesystem
+    let safe_filename = sanitize_filename(&name);
+    let output_path = images_dir.join(&safe_filename);
     let relative_path = PathBuf::from(images_subdir).join(&safe_filename);
     filename_map.insert(image_ref.filename.clone(), relative_path);
+
+    // Skip if file exists and overwrite is false
+    if output_path.exists() && !overwrite {
+      continue;
+    }
+    pending.push((image_ref.filename.as_str(), download_url, output_path));
   }
 
+  // Download the images concurrently, bounded to keep the rate limiter fair
+  stream::iter(pending)
+    .map(|(filename, download_url, output_path)| async move {
+      client
+        .download_attachment(&download_url, &output_path)
+        .await
+        .with_context(|| format!("Failed to download image: {filename}"))
+    })
+    .buffer_unordered(MAX_CONCURRENT_ASSET_DOWNLOADS)
+    .try_collect::<Vec<()>>()
+    .await?;
+
   Ok(filename_map)
 }
 
@@ -351,6 +592,9 @@ pub fn update_markdown_image_links(markdown: &str, filename_map: &HashMap<String
     // Find all occurrences of the filename in the markdown
     let pattern = format!("]({original_filename})");
     result = result.replace(&pattern, &format!("]({local_path_str})"));
+    // Images written as HTML with `--image-style html`
+    let pattern = format!("src=\"{}\"", escape_attribute(original_filename));
+    result = result.replace(&pattern, &format!("src=\"{}\"", escape_attribute(&local_path_str)));
 
     // Also handle the case where it might be wrapped in other URL context
     let pattern_empty = "![]()";
@@ -437,19 +681,81 @@ fn replace_non_block_image(content: &str, pattern: &str, replacement: &str) -> S
 /// # Returns
 /// A sanitized filename that can be safely written to disk.
 fn sanitize_filename(filename: &str) -> String {
-  filename
-    .chars()
-    .map(|c| match c {
-      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-      c => c,
-    })
-    .collect()
+  paths::sanitize_asset_filename(filename)
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
 
+  #[test]
+  fn test_extract_view_image_references() {
+    let view = r#"
+      <p><img class="confluence-embedded-image" src="/wiki/download/attachments/123/Flow%20Chart.png?version=2&amp;api=v2" alt=""></p>
+      <p><IMG SRC='/download/thumbnails/123/photo.jpg' ALT='Team photo'/></p>
+      <img src="https://cdn.example.com/logo.png" alt="External">
+      <img src="/wiki/download/attachments/123/Flow%20Chart.png?version=2&amp;api=v2">
+      <img class="emoticon" src="/wiki/images/icons/emoticons/smile.svg" alt="(smile)">
+      <img src="/wiki/download/attachments/123/Q%26A=v1+v2.png">
+    "#;
+
+    assert_eq!(
+      extract_view_image_references(view),
+      vec![
+        ViewImage {
+          url: "/wiki/download/attachments/123/Flow%20Chart.png?version=2&api=v2".to_string(),
+          filename: "Flow Chart.png".to_string(),
+          alt_text: "Flow Chart.png".to_string(),
+        },
+        ViewImage {
+          url: "/download/thumbnails/123/photo.jpg".to_string(),
+          filename: "photo.jpg".to_string(),
+          alt_text: "Team photo".to_string(),
+        },
+        ViewImage {
+          url: "/wiki/download/attachments/123/Q%26A=v1+v2.png".to_string(),
+          filename: "Q&A=v1+v2.png".to_string(),
+          alt_text: "Q&A=v1+v2.png".to_string(),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn test_link_view_images() {
+    let images = vec![
+      ViewImage {
+        url: "/download/attachments/1/a.png".to_string(),
+        filename: "a.png".to_string(),
+        alt_text: "A".to_string(),
+      },
+      ViewImage {
+        url: "/download/attachments/1/b.png".to_string(),
+        filename: "b.png".to_string(),
+        alt_text: "B".to_string(),
+      },
+    ];
+    let paths = HashMap::from([
+      (
+        "/download/attachments/1/a.png".to_string(),
+        PathBuf::from("images/a.png"),
+      ),
+      (
+        "/download/attachments/1/b.png".to_string(),
+        PathBuf::from("images/b.png"),
+      ),
+    ]);
+
+    let markdown = "# Page\n\n![A](/download/attachments/1/a.png)\n";
+    insta::assert_snapshot!(link_view_images(markdown, &images, &paths, OutputFormat::Markdown), @r"
+    # Page
+
+    ![A](images/a.png)
+
+    ![B](images/b.png)
+    ");
+  }
+
   #[test]
   fn test_extract_image_references_with_attachment() {
     let storage = r#"
@@ -464,6 +770,43 @@ mod tests {
     assert_eq!(refs[0].alt_text, "diagram");
   }
 
+  #[test]
+  fn test_image_quality_resolve() {
+    let url = "/wiki/download/attachments/123/Screen%20shot.png?version=2&api=v2";
+    assert_eq!(
+      ImageQuality::Original.resolve("Screen shot.png", url),
+      ("Screen shot.png".to_string(), url.to_string())
+    );
+    assert_eq!(
+      ImageQuality::Thumbnail.resolve("Screen shot.png", url),
+      (
+        "Screen shot-thumbnail.png".to_string(),
+        "/wiki/download/thumbnails/123/Screen%20shot.png?version=2&api=v2".to_string()
+      )
+    );
+
+    let svg = "/wiki/download/attachments/123/flow.svg";
+    assert_eq!(
+      ImageQuality::Thumbnail.resolve("flow.svg", svg),
+      ("flow.svg".to_string(), svg.to_string())
+    );
+  }
+
+  #[test]
+  fn test_extract_external_image_urls() {
+    let content = r#"
+      <ac:image><ri:url ri:value="https://cdn.example.com/badge.svg?a=1&amp;b=2" /></ac:image>
+      <ac:image><ri:attachment ri:filename="local.png" /></ac:image>
+      <ac:image><ri:url ri:value="https://cdn.example.com/badge.svg?a=1&amp;b=2" /></ac:image>
+      <ac:image><ri:url ri:value="/wiki/download/relative.png" /></ac:image>
+    "#;
+
+    assert_eq!(
+      extract_external_image_urls(content).unwrap(),
+      ["https://cdn.example.com/badge.svg?a=1&b=2"]
+    );
+  }
+
   #[test]
   fn test_extract_image_references_no_images() {
     let storage = "<p>Just some text</p>";
@@ -502,6 +845,25 @@ mod tests {
     assert_eq!(refs[0].alt_text, "image");
   }
 
+  #[test]
+  fn test_extract_image_references_diagram_previews() {
+    let storage = r#"
+      <ac:structured-macro ac:name="drawio">
+        <ac:parameter ac:name="diagramName">Architecture</ac:parameter>
+      </ac:structured-macro>
+      <ac:structured-macro ac:name="gliffy">
+        <ac:parameter ac:name="name">Login flow</ac:parameter>
+      </ac:structured-macro>
+      <ac:structured-macro ac:name="plantuml" />
+    "#;
+
+    let refs = extract_image_references(storage).unwrap();
+    assert_eq!(refs.len(), 2);
+    assert_eq!(refs[0].filename, "Architecture.png");
+    assert_eq!(refs[0].alt_text, "Architecture");
+    assert_eq!(refs[1].filename, "Login flow.png");
+  }
+
   #[test]
   fn test_sanitize_filename() {
     assert_eq!(sanitize_filename("normal.png"), "normal.png");
@@ -525,6 +887,18 @@ mod tests {
     assert!(result.contains("](images/photo.jpg)"));
   }
 
+  #[test]
+  fn test_update_markdown_image_links_html_src() {
+    let markdown = "<img src=\"photo.jpg\" alt=\"photo\" width=\"300\" />\n<img src=\"Q&amp;A.png\" alt=\"image\" />";
+    let mut map = HashMap::new();
+    map.insert("photo.jpg".to_string(), PathBuf::from("images/photo.jpg"));
+    map.insert("Q&A.png".to_string(), PathBuf::from("images/Q&A.png"));
+
+    let result = update_markdown_image_links(markdown, &map);
+    assert!(result.contains("<img src=\"images/photo.jpg\" alt=\"photo\" width=\"300\" />"));
+    assert!(result.contains("<img src=\"images/Q&amp;A.png\" alt=\"image\" />"));
+  }
+
   #[test]
   fn test_update_markdown_no_images() {
     let markdown = "Just some text without images";
@@ -0,0 +1,241 @@
+//! Dependency graph of content reused across pages.
+//!
+//! Confluence composes pages from other pages with the `include` (whole page),
+//! `excerpt-include` (the source page's excerpt), and `multiexcerpt-include`
+//! (a named excerpt of the source page) macros. The graph records
+//! which source pages feed which composite pages so owners can see the impact
+//! of moving or rewriting a page before restructuring a space. It is written
+//! as Graphviz DOT or JSON.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use roxmltree::Document;
+use serde::Serialize;
+
+use crate::confluence::Page;
+use crate::markdown::html_entities::preprocess_html_entities;
+use crate::markdown::utils::{get_attribute, matches_tag, wrap_with_namespaces};
+
+/// How a composite page reuses a source page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IncludeKind {
+  /// The whole source page is embedded (`include` macro).
+  Include,
+  /// The excerpt of the source page is embedded (`excerpt-include` macro).
+  ExcerptInclude,
+  /// A named excerpt of the source page is embedded (`multiexcerpt-include` macro).
+  MultiExcerptInclude,
+}
+
+impl IncludeKind {
+  pub(crate) fn from_macro_name(name: &str) -> Option<Self> {
+    match name {
+      "include" => Some(Self::Include),
+      "excerpt-include" => Some(Self::ExcerptInclude),
+      "multiexcerpt-include" | "multiexcerpt-include-macro" => Some(Self::MultiExcerptInclude),
+      _ => None,
+    }
+  }
+
+  fn label(self) -> &'static str {
+    match self {
+      Self::Include => "include",
+      Self::ExcerptInclude => "excerpt-include",
+      Self::MultiExcerptInclude => "multiexcerpt-include",
+    }
+  }
+}
+
+/// A page in the graph, identified by space and title as in `ri:page`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct GraphPage {
+  /// Space key; `None` when neither the reference nor the page names one.
+  pub space: Option<String>,
+  /// Page title.
+  pub title: String,
+}
+
+impl GraphPage {
+  fn label(&self) -> String {
+    match &self.space {
+      Some(space) => format!("{space}: {}", self.title),
+      None => self.title.clone(),
+    }
+  }
+}
+
+/// A source page feeding content into a composite page.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct IncludeEdge {
+  /// Page whose content is reused.
+  pub source: GraphPage,
+  /// Page embedding the content.
+  pub target: GraphPage,
+  /// Macro used to embed the content.
+  pub kind: IncludeKind,
+}
+
+/// Include dependencies collected while exporting pages.
+///
+/// Pages may be recorded concurrently from parallel downloads.
+#[derive(Debug, Default)]
+pub struct IncludeGraph {
+  edges: Mutex<BTreeSet<IncludeEdge>>,
+}
+
+impl IncludeGraph {
+  /// Record the includes found in a page's storage content.
+  ///
+  /// # Errors
+  /// Returns an error when the storage content cannot be parsed.
+  pub fn record(&self, page: &Page, storage_content: &str) -> Result<()> {
+    let current_space = page.space.as_ref().map(|space| space.key.clone());
+    let target = GraphPage {
+      space: current_space.clone(),
+      title: page.title.clone(),
+    };
+
+    let edges = extract_includes(storage_content)?
+      .into_iter()
+      .map(|(kind, source)| IncludeEdge {
+        source: GraphPage {
+          space: source.space.or_else(|| current_space.clone()),
+          title: source.title,
+        },
+        target: target.clone(),
+        kind,
+      });
+
+    self.edges.lock().expect("include graph lock poisoned").extend(edges);
+    Ok(())
+  }
+
+  /// Render the graph in Graphviz DOT format.
+  pub fn to_dot(&self) -> String {
+    let edges = self.edges.lock().expect("include graph lock poisoned");
+    let mut result = String::from("digraph includes {\n  rankdir=LR;\n  node [shape=box];\n");
+    for edge in edges.iter() {
+      result.push_str(&format!(
+        "  {} -> {} [label={}];\n",
+        dot_string(&edge.source.label()),
+        dot_string(&edge.target.label()),
+        dot_string(edge.kind.label())
+      ));
+    }
+    result.push_str("}\n");
+    result
+  }
+
+  /// Render the graph as JSON: `{"edges": [{"source", "target", "kind"}]}`.
+  pub fn to_json(&self) -> Result<String> {
+    #[derive(Serialize)]
+    struct Output<'a> {
+      edges: Vec<&'a IncludeEdge>,
+    }
+
+    let edges = self.edges.lock().expect("include graph lock poisoned");
+    let output = Output {
+      edges: edges.iter().collect(),
+    };
+    let mut json = serde_json::to_string_pretty(&output).context("Failed to serialize include graph")?;
+    json.push('\n');
+    Ok(json)
+  }
+
+  /// Write the graph to `path`, choosing JSON for a `.json` extension and DOT
+  /// otherwise.
+  ///
+  /// # Errors
+  /// Returns an error when serialization or the file write fails.
+  pub fn write(&self, path: &Path) -> Result<()> {
+    let is_json = path
+      .extension()
+      .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    let content = if is_json { self.to_json()? } else { self.to_dot() };
+
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+      std::fs::create_dir_all(parent).with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    std::fs::write(path, content).with_context(|| format!("Failed to write include graph to {}", path.display()))
+  }
+}
+
+/// Find the pages referenced by include macros, as written in the storage
+/// content; references without a space key keep `space` unset.
+pub(crate) fn extract_includes(storage_content: &str) -> Result<Vec<(IncludeKind, GraphPage)>> {
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document = Document::parse(&wrapped).context("Failed to parse Confluence storage content for includes")?;
+
+  let includes = document
+    .descendants()
+    .filter(|node| matches_tag(*node, "ac:structured-macro"))
+    .filter_map(|node| {
+      let kind = IncludeKind::from_macro_name(&get_attribute(node, "ac:name")?)?;
+      let page = node.descendants().find(|child| matches_tag(*child, "ri:page"))?;
+      let title = get_attribute(page, "ri:content-title")?;
+      Some((
+        kind,
+        GraphPage {
+          space: get_attribute(page, "ri:space-key"),
+          title,
+        },
+      ))
+    })
+    .collect();
+  Ok(includes)
+}
+
+/// Quote a string as a DOT identifier.
+fn dot_string(value: &str) -> String {
+  format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::page_in_space;
+
+  const STORAGE: &str = r#"
+    <ac:structured-macro ac:name="include">
+      <ac:parameter ac:name=""><ac:link><ri:page ri:content-title="Shared Setup" /></ac:link></ac:parameter>
+    </ac:structured-macro>
+    <ac:structured-macro ac:name="excerpt-include">
+      <ac:parameter ac:name=""><ac:link><ri:page ri:space-key="OPS" ri:content-title="On-call &quot;Rules&quot;" /></ac:link></ac:parameter>
+    </ac:structured-macro>
+    <ac:structured-macro ac:name="info"><ac:rich-text-body><p>Not an include</p></ac:rich-text-body></ac:structured-macro>
+  "#;
+
+  #[test]
+  fn test_to_dot() {
+    let graph = IncludeGraph::default();
+    graph.record(&page_in_space("1", "Runbook", "ENG"), STORAGE).unwrap();
+
+    insta::assert_snapshot!(graph.to_dot(), @r#"
+    digraph includes {
+      rankdir=LR;
+      node [shape=box];
+      "ENG: Shared Setup" -> "ENG: Runbook" [label="include"];
+      "OPS: On-call \"Rules\"" -> "ENG: Runbook" [label="excerpt-include"];
+    }
+    "#);
+  }
+
+  #[test]
+  fn test_write_json() {
+    let graph = IncludeGraph::default();
+    graph.record(&page_in_space("1", "Runbook", "ENG"), STORAGE).unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("includes.json");
+
+    graph.write(&path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["edges"][1]["kind"], "excerpt-include");
+    assert_eq!(json["edges"][1]["source"]["space"], "OPS");
+    assert_eq!(json["edges"][1]["target"]["title"], "Runbook");
+  }
+}
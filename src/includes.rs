@@ -0,0 +1,132 @@
+//! Content transcluded from other pages.
+//!
+//! The `include`, `excerpt-include`, and `multiexcerpt-include` macros store
+//! only a reference to the source page, so their content is missing from a
+//! plain export. With `--resolve-includes`, [`IncludeResolver`] fetches every
+//! referenced page before a page is converted, following includes of included
+//! pages up to [`MAX_INCLUDE_DEPTH`] levels deep. The macro handlers then
+//! inline the fetched content, leaving a placeholder link for pages that
+//! cannot be fetched and for circular includes.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::confluence::{ConfluenceApi, Page};
+use crate::include_graph::{GraphPage, extract_includes};
+
+/// Deepest chain of includes followed from an exported page.
+pub const MAX_INCLUDE_DEPTH: usize = 10;
+
+/// Storage content of the pages a page includes, keyed by space and title.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IncludedPages {
+  /// Storage content of each fetched page.
+  pub pages: Arc<BTreeMap<GraphPage, Arc<str>>>,
+  /// Pages being inlined, outermost first, used to detect circular includes.
+  pub stack: Vec<GraphPage>,
+}
+
+impl IncludedPages {
+  /// Storage content of an included page, if it was fetched.
+  pub fn get(&self, page: &GraphPage) -> Option<&str> {
+    self.pages.get(page).map(AsRef::as_ref)
+  }
+
+  /// Check whether inlining `page` would include it inside itself.
+  pub fn is_circular(&self, page: &GraphPage) -> bool {
+    self.stack.contains(page)
+  }
+}
+
+/// Fetches and caches the pages referenced by include macros.
+#[derive(Debug, Default)]
+pub struct IncludeResolver {
+  cache: Mutex<HashMap<GraphPage, Option<Arc<str>>>>,
+}
+
+impl IncludeResolver {
+  /// Create a resolver with an empty cache.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Fetch the pages included by `page`, and the pages they include in turn.
+  ///
+  /// # Arguments
+  /// * `client` - API used to look up pages not yet in the cache.
+  /// * `page` - The page being exported.
+  /// * `storage_content` - Storage format of the page.
+  ///
+  /// # Returns
+  /// The fetched pages, with `page` itself on the stack so that includes of
+  /// the page into itself are reported as circular. Pages that cannot be
+  /// fetched are left out.
+  pub async fn resolve(&self, client: &dyn ConfluenceApi, page: &Page, storage_content: &str) -> IncludedPages {
+    let current_space = page.space.as_ref().map(|space| space.key.clone());
+    let mut pages = BTreeMap::new();
+    let mut seen = BTreeSet::new();
+    let mut pending = vec![(current_space.clone(), Arc::<str>::from(storage_content), 0)];
+
+    while let Some((space, content, depth)) = pending.pop() {
+      if depth >= MAX_INCLUDE_DEPTH {
+        debug!("Include depth limit ({MAX_INCLUDE_DEPTH}) reached, not following further includes");
+        continue;
+      }
+      let references = match extract_includes(&content) {
+        Ok(references) => references,
+        Err(err) => {
+          debug!("Failed to find includes: {err:#}");
+          continue;
+        }
+      };
+
+      for (_, reference) in references {
+        let key = GraphPage {
+          space: reference.space.or_else(|| space.clone()),
+          title: reference.title,
+        };
+        if !seen.insert(key.clone()) {
+          continue;
+        }
+        if let Some(included) = self.fetch(client, &key).await {
+          pending.push((key.space.clone(), included.clone(), depth + 1));
+          pages.insert(key, included);
+        }
+      }
+    }
+
+    IncludedPages {
+      pages: Arc::new(pages),
+      stack: vec![GraphPage {
+        space: current_space,
+        title: page.title.clone(),
+      }],
+    }
+  }
+
+  async fn fetch(&self, client: &dyn ConfluenceApi, key: &GraphPage) -> Option<Arc<str>> {
+    if let Some(cached) = self.cache.lock().await.get(key) {
+      return cached.clone();
+    }
+
+    let content = match &key.space {
+      Some(space) => match client.find_page(space, &key.title).await {
+        Ok(Some(page)) => page.storage_content().map(Arc::from),
+        Ok(None) => {
+          debug!("Included page {space}: {} not found", key.title);
+          None
+        }
+        Err(err) => {
+          debug!("Failed to fetch included page {space}: {}: {err:#}", key.title);
+          None
+        }
+      },
+      None => None,
+    };
+    self.cache.lock().await.insert(key.clone(), content.clone());
+    content
+  }
+}
@@ -0,0 +1,226 @@
+//! Tamper-evident checksums for exported archives.
+//!
+//! After an export, [`write_checksums`] records the SHA-256 digest of every
+//! file the export wrote, from its pages and assets to its reports and site
+//! configuration, in a `SHA256SUMS` file that `sha256sum --check`
+//! understands. Given a key, the file is then signed with `minisign` or
+//! `ssh-keygen -Y sign` so compliance archives can be verified later.
+//! Unrelated files in the output directory, such as a `.git` checkout, are
+//! left out.
+//!
+//! Files are streamed through the hasher on the blocking thread pool, several
+//! at a time, and each line is written as soon as its digest is ready, so
+//! large attachments neither fill memory nor hold up the end of the run.
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use futures::stream::{self, StreamExt};
+use sha2::{Digest, Sha256};
+
+/// Name of the checksum file written into the output directory.
+pub const CHECKSUMS_FILE: &str = "SHA256SUMS";
+
+/// External tool used to sign the checksum file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Signer {
+  /// `minisign -S`, producing `SHA256SUMS.minisig` (default)
+  #[default]
+  Minisign,
+  /// `ssh-keygen -Y sign`, producing `SHA256SUMS.sig`
+  Ssh,
+}
+
+impl Signer {
+  /// The signature file the tool writes next to the checksum file.
+  pub fn signature_path(&self, checksums: &Path) -> PathBuf {
+    let mut path = checksums.as_os_str().to_owned();
+    path.push(match self {
+      Signer::Minisign => ".minisig",
+      Signer::Ssh => ".sig",
+    });
+    PathBuf::from(path)
+  }
+
+  fn command(&self, key: &Path, checksums: &Path) -> Command {
+    let mut command = match self {
+      Signer::Minisign => {
+        let mut command = Command::new("minisign");
+        command.arg("-S").arg("-s").arg(key).arg("-m").arg(checksums);
+        command
+      }
+      Signer::Ssh => {
+        let mut command = Command::new("ssh-keygen");
+        command.args(["-Y", "sign", "-n", "file", "-f"]).arg(key).arg(checksums);
+        command
+      }
+    };
+    // Both tools may prompt for the key passphrase
+    command.stdin(Stdio::inherit());
+    command
+  }
+}
+
+/// Hex-encoded SHA-256 digest of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+  to_hex(&Sha256::digest(bytes))
+}
+
+fn to_hex(digest: &[u8]) -> String {
+  digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Size of the chunks files are read in while hashing.
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Hex-encoded SHA-256 digest of a file's contents.
+///
+/// The file is read in chunks, so its size does not matter.
+///
+/// # Errors
+/// Returns an error when the file cannot be opened or read.
+pub fn sha256_file(path: &Path) -> Result<String> {
+  let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+  let mut hasher = Sha256::new();
+  let mut buffer = vec![0; HASH_CHUNK_SIZE];
+  loop {
+    let read = file
+      .read(&mut buffer)
+      .with_context(|| format!("Failed to read {}", path.display()))?;
+    if read == 0 {
+      break;
+    }
+    hasher.update(&buffer[..read]);
+  }
+  Ok(to_hex(&hasher.finalize()))
+}
+
+/// Write `SHA256SUMS` covering `files` in `output_dir`.
+///
+/// Paths are written sorted, without duplicates, as given. Previous checksum
+/// and signature files are not included. Files are hashed in parallel on the
+/// blocking thread pool, one per core, and lines are written in order as
+/// their digests complete.
+///
+/// # Arguments
+/// * `output_dir` - Root directory of the export.
+/// * `files` - Paths of the exported files relative to `output_dir`, with `/` separators.
+///
+/// # Returns
+/// The path of the written checksum file.
+///
+/// # Errors
+/// Returns an error when a file cannot be read or the checksum file cannot
+/// be written.
+pub async fn write_checksums(output_dir: &Path, mut files: Vec<String>) -> Result<PathBuf> {
+  files.retain(|relative| !is_integrity_file(relative));
+  files.sort();
+  files.dedup();
+
+  let path = output_dir.join(CHECKSUMS_FILE);
+  let file = File::create(&path).with_context(|| format!("Failed to write {}", path.display()))?;
+  let mut writer = BufWriter::new(file);
+
+  let parallelism = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+  let mut digests = stream::iter(files)
+    .map(|relative| {
+      let file_path = output_dir.join(&relative);
+      async move {
+        let digest = tokio::task::spawn_blocking(move || sha256_file(&file_path))
+          .await
+          .context("Hashing task failed")??;
+        Ok::<_, anyhow::Error>((relative, digest))
+      }
+    })
+    .buffered(parallelism);
+
+  while let Some(entry) = digests.next().await {
+    let (relative, digest) = entry?;
+    writeln!(writer, "{digest}  {relative}").with_context(|| format!("Failed to write {}", path.display()))?;
+  }
+  writer
+    .flush()
+    .with_context(|| format!("Failed to write {}", path.display()))?;
+  Ok(path)
+}
+
+/// Sign the checksum file with an external tool.
+///
+/// # Arguments
+/// * `signer` - Tool to invoke.
+/// * `key` - Secret key file passed to the tool.
+/// * `checksums` - Path of the checksum file to sign.
+///
+/// # Returns
+/// The path of the signature file.
+///
+/// # Errors
+/// Returns an error when the tool cannot be started or exits unsuccessfully.
+pub fn sign_checksums(signer: Signer, key: &Path, checksums: &Path) -> Result<PathBuf> {
+  let mut command = signer.command(key, checksums);
+  let program = command.get_program().to_string_lossy().into_owned();
+  let status = command
+    .status()
+    .with_context(|| format!("Failed to run {program}; is it installed?"))?;
+  if !status.success() {
+    bail!("{program} failed to sign {} ({status})", checksums.display());
+  }
+  Ok(signer.signature_path(checksums))
+}
+
+fn is_integrity_file(relative: &str) -> bool {
+  relative
+    .strip_prefix(CHECKSUMS_FILE)
+    .is_some_and(|suffix| matches!(suffix, "" | ".minisig" | ".sig"))
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn test_write_checksums() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("Root/images")).unwrap();
+    fs::write(dir.path().join("Root.md"), "root").unwrap();
+    fs::write(dir.path().join("Root/images/a.png"), "png").unwrap();
+    fs::write(dir.path().join("SHA256SUMS.minisig"), "old signature").unwrap();
+    fs::create_dir_all(dir.path().join(".git")).unwrap();
+    fs::write(dir.path().join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+
+    let files = ["Root/images/a.png", "Root.md", "SHA256SUMS.minisig", "Root.md"].map(String::from);
+    let path = write_checksums(dir.path(), files.to_vec()).await.unwrap();
+    insta::assert_snapshot!(fs::read_to_string(path).unwrap(), @r"
+    4813494d137e1631bba301d5acab6e7bb7aa74ce1185d456565ef51d737677b2  Root.md
+    8f8cbb7dcf46e0bc7d53265749a6c17d116093a6ba95e442764060c76fd4a86c  Root/images/a.png
+    ");
+  }
+
+  #[test]
+  fn test_sha256_file_matches_in_memory_digest() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("large.bin");
+    let content: Vec<u8> = (0..HASH_CHUNK_SIZE * 2 + 17).map(|i| (i % 251) as u8).collect();
+    fs::write(&path, &content).unwrap();
+
+    assert_eq!(sha256_file(&path).unwrap(), sha256_hex(&content));
+  }
+
+  #[test]
+  fn test_signer_command() {
+    let command = Signer::Ssh.command(Path::new("id_ed25519"), Path::new("out/SHA256SUMS"));
+    let args: Vec<_> = command.get_args().map(|arg| arg.to_string_lossy()).collect();
+    assert_eq!(command.get_program(), "ssh-keygen");
+    assert_eq!(args, ["-Y", "sign", "-n", "file", "-f", "id_ed25519", "out/SHA256SUMS"]);
+    assert_eq!(
+      Signer::Minisign.signature_path(Path::new("out/SHA256SUMS")),
+      PathBuf::from("out/SHA256SUMS.minisig")
+    );
+  }
+}
@@ -0,0 +1,214 @@
+//! JSON documents for `--format json`.
+//!
+//! Search indexes and other pipelines need a page's metadata next to its
+//! content, and recovering it from Markdown front matter is lossy. JSON
+//! exports write one document per page holding the page's identity, position
+//! in the tree, labels, and version, its converted Markdown and raw storage
+//! format, and a manifest of the images, attachments, and tables written
+//! alongside it.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::confluence::{Ancestor, Page};
+use crate::processed_page::{AssetData, ProcessedPage, link_path};
+
+/// Details of a page recorded in its JSON document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PageRecord {
+  /// Confluence page ID.
+  pub id: String,
+  /// Page title.
+  pub title: String,
+  /// Content type, `page` or `blogpost`.
+  #[serde(rename = "type")]
+  pub page_type: String,
+  /// Publication status such as `current`.
+  pub status: String,
+  /// Key of the space the page belongs to.
+  pub space: Option<String>,
+  /// Pages above this one, from the top of the space down.
+  pub ancestors: Vec<Ancestor>,
+  /// Label names.
+  pub labels: Vec<String>,
+  /// Current version number.
+  pub version: Option<u32>,
+  /// Display name of the page's creator.
+  pub created_by: Option<String>,
+  /// ISO 8601 timestamp of the page's creation.
+  pub created: Option<String>,
+  /// ISO 8601 timestamp of the page's latest version.
+  pub last_modified: Option<String>,
+  /// Storage format the content was converted from.
+  pub storage: Option<String>,
+}
+
+impl PageRecord {
+  /// Record the details of `page`.
+  ///
+  /// # Arguments
+  /// * `page` - The exported page.
+  /// * `storage_content` - Storage format of the page as converted, after redaction.
+  pub fn new(page: &Page, storage_content: Option<&str>) -> Self {
+    Self {
+      id: page.id.clone(),
+      title: page.title.clone(),
+      page_type: page.page_type.clone(),
+      status: page.status.clone(),
+      space: page.space.as_ref().map(|space| space.key.clone()),
+      ancestors: page.ancestors.clone(),
+      labels: page.labels().into_iter().map(str::to_string).collect(),
+      version: page.version.as_ref().map(|version| version.number),
+      created_by: page.created_by().map(str::to_string),
+      created: page.created_date().map(str::to_string),
+      last_modified: page
+        .last_modified_date()
+        .or_else(|| page.version.as_ref().and_then(|version| version.when.as_deref()))
+        .map(str::to_string),
+      storage: storage_content.map(str::to_string),
+    }
+  }
+}
+
+/// The JSON document written for a page.
+#[derive(Debug, Serialize)]
+struct Document<'a> {
+  #[serde(flatten)]
+  record: &'a PageRecord,
+  front_matter: serde_json::Map<String, serde_json::Value>,
+  markdown: &'a str,
+  images: Vec<FileEntry>,
+  attachments: Vec<FileEntry>,
+  tables: Vec<FileEntry>,
+}
+
+/// A file written alongside a page.
+#[derive(Debug, Serialize)]
+struct FileEntry {
+  /// Name of the attachment the file was downloaded from.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  name: Option<String>,
+  /// Path relative to the page's directory, as linked from the content.
+  path: String,
+  /// Size in bytes, unless an existing file was kept instead of downloaded.
+  bytes: Option<usize>,
+}
+
+/// Render the JSON document of a processed page.
+///
+/// # Errors
+/// Returns an error when the document cannot be serialized.
+pub fn render_document(page: &ProcessedPage) -> Result<String> {
+  let default_record = PageRecord::default();
+  let document = Document {
+    record: page.record.as_ref().unwrap_or(&default_record),
+    front_matter: page.front_matter.to_json(),
+    markdown: &page.content,
+    images: file_entries(&page.link_map.images, &page.images),
+    attachments: file_entries(&page.link_map.attachments, &page.attachments),
+    tables: page
+      .tables
+      .iter()
+      .map(|table| FileEntry {
+        name: None,
+        path: link_path(&table.relative_path),
+        bytes: Some(table.content.size()),
+      })
+      .collect(),
+  };
+  let json = serde_json::to_string_pretty(&document)
+    .with_context(|| format!("Failed to serialize page '{}' as JSON", page.filename))?;
+  Ok(format!("{json}\n"))
+}
+
+fn file_entries(links: &BTreeMap<String, String>, assets: &[AssetData]) -> Vec<FileEntry> {
+  links
+    .iter()
+    .map(|(name, path)| FileEntry {
+      name: Some(name.clone()),
+      path: path.clone(),
+      bytes: assets
+        .iter()
+        .find(|asset| link_path(&asset.relative_path) == *path)
+        .map(|asset| asset.content.size()),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::front_matter::FrontMatter;
+  use crate::processed_page::LinkMap;
+  use crate::test_support::processed_page;
+
+  #[test]
+  fn test_render_document() {
+    let page: Page = serde_json::from_value(serde_json::json!({
+      "id": "42",
+      "title": "Runbook",
+      "type": "page",
+      "status": "current",
+      "space": {"key": "OPS", "name": "Operations", "type": "global"},
+      "metadata": {"labels": {"results": [{"name": "oncall"}]}},
+      "version": {"number": 7, "when": "2024-05-01T10:00:00.000Z"},
+      "ancestors": [{"id": "1", "title": "Operations Home"}]
+    }))
+    .unwrap();
+    let mut front_matter = FrontMatter::default();
+    front_matter.insert("owner", "sre");
+    let processed = ProcessedPage {
+      front_matter,
+      link_map: LinkMap {
+        images: BTreeMap::from([("graph.png".to_string(), "images/graph.png".to_string())]),
+        attachments: BTreeMap::new(),
+        pages: BTreeMap::new(),
+      },
+      record: Some(PageRecord::new(&page, Some("<h1>Runbook</h1>"))),
+      ..processed_page(
+        "Runbook",
+        "# Runbook\n\n![Graph](images/graph.png)\n",
+        &[("images/graph.png", b"PNG")],
+      )
+    };
+
+    insta::assert_snapshot!(render_document(&processed).unwrap(), @r##"
+    {
+      "id": "42",
+      "title": "Runbook",
+      "type": "page",
+      "status": "current",
+      "space": "OPS",
+      "ancestors": [
+        {
+          "id": "1",
+          "title": "Operations Home"
+        }
+      ],
+      "labels": [
+        "oncall"
+      ],
+      "version": 7,
+      "created_by": null,
+      "created": null,
+      "last_modified": "2024-05-01T10:00:00.000Z",
+      "storage": "<h1>Runbook</h1>",
+      "front_matter": {
+        "owner": "sre"
+      },
+      "markdown": "# Runbook\n\n![Graph](images/graph.png)\n",
+      "images": [
+        {
+          "name": "graph.png",
+          "path": "images/graph.png",
+          "bytes": 3
+        }
+      ],
+      "attachments": [],
+      "tables": []
+    }
+    "##);
+  }
+}
@@ -3,14 +3,52 @@
 //! This library provides functionality to export Confluence spaces and pages to
 //! Markdown.
 
+pub mod accessibility;
 pub mod asciidoc;
 pub mod attachments;
+pub mod blueprints;
+pub mod changelog;
 pub mod cli;
 pub mod color;
 pub mod commands;
 pub mod confluence;
+pub mod content_stubs;
+pub mod conversion_cache;
 pub mod credentials;
+pub mod docusaurus;
+pub mod error;
+pub mod error_report;
+pub mod events;
+pub mod external_images;
 pub mod format;
+pub mod front_matter;
+pub mod front_matter_rules;
+pub mod hooks;
+pub mod image_formats;
 pub mod images;
+pub mod include_graph;
+pub mod includes;
+pub mod integrity;
+pub mod json_export;
+pub mod link_crawl;
+pub mod link_titles;
+pub mod links;
+pub mod manifest;
 pub mod markdown;
+pub mod mkdocs;
+pub mod notify;
+pub mod paths;
 pub mod processed_page;
+pub mod progress;
+pub mod redaction;
+pub mod shared_assets;
+pub mod size_limits;
+pub mod space_readme;
+pub mod table_export;
+pub mod tasks;
+pub mod templates;
+#[cfg(test)]
+mod test_support;
+pub mod users;
+
+pub use error::{Error, Result};
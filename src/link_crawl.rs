@@ -0,0 +1,188 @@
+//! Export of pages linked from the exported pages.
+//!
+//! Documentation is often a cluster of pages linking to each other across
+//! different parts of a space, or across spaces. With `--follow-links`,
+//! [`crawl_linked_pages`] follows the `ri:page` links of the exported pages
+//! to the pages they point at, and the links of those pages in turn, up to
+//! `--link-depth` hops away, so the cluster can be exported without
+//! exporting whole spaces.
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use futures::{StreamExt, stream};
+use roxmltree::Document;
+use tracing::{debug, warn};
+
+use crate::confluence::{ConfluenceApi, Page, PageTree};
+use crate::markdown::html_entities::preprocess_html_entities;
+use crate::markdown::utils::{get_attribute, matches_tag, wrap_with_namespaces};
+
+/// A page linked by space and title, as in `ri:page`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LinkedPage {
+  space: String,
+  title: String,
+}
+
+impl LinkedPage {
+  /// Key identifying the page regardless of title case.
+  fn key(&self) -> (String, String) {
+    (self.space.to_lowercase(), self.title.to_lowercase())
+  }
+}
+
+/// Find the pages linked from storage content.
+///
+/// # Arguments
+/// * `storage_content` - Storage format of the linking page.
+/// * `current_space` - Space of the linking page, for links without `ri:space-key`.
+///
+/// # Errors
+/// Returns an error if the storage content cannot be parsed.
+fn extract_page_links(storage_content: &str, current_space: &str) -> Result<Vec<LinkedPage>> {
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document = Document::parse(&wrapped).context("Failed to parse Confluence storage content for links")?;
+
+  let links = document
+    .descendants()
+    .filter(|node| matches_tag(*node, "ri:page"))
+    .filter_map(|node| {
+      Some(LinkedPage {
+        space: get_attribute(node, "ri:space-key").unwrap_or_else(|| current_space.to_string()),
+        title: get_attribute(node, "ri:content-title")?,
+      })
+    })
+    .collect();
+  Ok(links)
+}
+
+/// Fetch the pages linked from `trees` up to `depth` hops away.
+///
+/// Pages already part of `trees` are not fetched again. Links to pages that
+/// do not exist, or that cannot be fetched, are skipped with a warning.
+///
+/// # Arguments
+/// * `client` - API implementation used for looking up and fetching pages.
+/// * `trees` - The exported page trees whose links are followed.
+/// * `depth` - Number of hops followed; `1` fetches the pages linked directly.
+/// * `parallel` - Maximum number of pages fetched at the same time.
+///
+/// # Returns
+/// The linked pages in the order they were found, each as a tree without
+/// children.
+pub async fn crawl_linked_pages(
+  client: &dyn ConfluenceApi,
+  trees: &[PageTree],
+  depth: usize,
+  parallel: usize,
+) -> Vec<PageTree> {
+  let mut seen_ids = HashSet::new();
+  let mut seen_links = HashSet::new();
+  let mut frontier = Vec::new();
+  let mut stack: Vec<&PageTree> = trees.iter().collect();
+  while let Some(tree) = stack.pop() {
+    seen_ids.insert(tree.page.id.clone());
+    if let Some(space) = &tree.page.space {
+      seen_links.insert((space.key.to_lowercase(), tree.page.title.to_lowercase()));
+    }
+    if tree.exported {
+      frontier.push(tree.page.clone());
+    }
+    stack.extend(&tree.children);
+  }
+
+  let mut linked = Vec::new();
+  for hop in 1..=depth {
+    let mut links = Vec::new();
+    for page in &frontier {
+      let (Some(storage), Some(space)) = (page.storage_content(), page.space.as_ref()) else {
+        continue;
+      };
+      match extract_page_links(storage, &space.key) {
+        Ok(found) => links.extend(found.into_iter().filter(|link| seen_links.insert(link.key()))),
+        Err(err) => warn!("Failed to read the links of page {}: {err:#}", page.id),
+      }
+    }
+    if links.is_empty() {
+      break;
+    }
+    debug!("Following {} links at hop {hop}", links.len());
+
+    let fetched: Vec<Option<Page>> = stream::iter(&links)
+      .map(|link| fetch_linked_page(client, link))
+      .buffered(parallel.max(1))
+      .collect()
+      .await;
+    frontier = fetched
+      .into_iter()
+      .flatten()
+      .filter(|page| seen_ids.insert(page.id.clone()))
+      .collect();
+    linked.extend(frontier.iter().cloned());
+  }
+
+  linked
+    .into_iter()
+    .map(|page| PageTree {
+      page,
+      children: Vec::new(),
+      depth: 0,
+      exported: true,
+    })
+    .collect()
+}
+
+/// Look up a linked page and fetch it with everything an export needs.
+async fn fetch_linked_page(client: &dyn ConfluenceApi, link: &LinkedPage) -> Option<Page> {
+  let result = async {
+    match client.find_page(&link.space, &link.title).await? {
+      Some(found) => client.get_page(&found.id).await.map(Some),
+      None => Ok(None),
+    }
+  }
+  .await;
+  match result {
+    Ok(Some(page)) => Some(page),
+    Ok(None) => {
+      warn!("Linked page \"{}\" in space {} does not exist", link.title, link.space);
+      None
+    }
+    Err(err) => {
+      warn!(
+        "Failed to fetch linked page \"{}\" in space {}: {err:#}",
+        link.title, link.space
+      );
+      None
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_extract_page_links() {
+    let storage = r#"
+      <p><ac:link><ri:page ri:content-title="Setup" /></ac:link></p>
+      <p><ac:link><ri:page ri:space-key="OPS" ri:content-title="On-call &amp; Escalation" /><ac:plain-text-link-body><![CDATA[on-call]]></ac:plain-text-link-body></ac:link></p>
+      <p><ac:link><ri:attachment ri:filename="diagram.png" /></ac:link></p>
+    "#;
+    let links = extract_page_links(storage, "DOCS").unwrap();
+    assert_eq!(
+      links,
+      [
+        LinkedPage {
+          space: "DOCS".to_string(),
+          title: "Setup".to_string(),
+        },
+        LinkedPage {
+          space: "OPS".to_string(),
+          title: "On-call & Escalation".to_string(),
+        },
+      ]
+    );
+  }
+}
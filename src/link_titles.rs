@@ -0,0 +1,349 @@
+//! Optional title lookup for bare external links.
+//!
+//! Confluence pages often contain pasted URLs whose link text is the URL
+//! itself. When enabled, [`LinkTitleFetcher`] requests each such URL, reads the
+//! HTML `<title>`, and uses it as the Markdown link text. Lookups are rate
+//! limited, time out quickly, and are cached so a URL repeated across pages is
+//! only requested once. Any failure leaves the link unchanged, as do links
+//! inside code blocks and code spans.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use tokio::sync::{Mutex, OnceCell};
+use tracing::debug;
+
+use crate::confluence::client::RequestRateLimiter;
+
+/// Maximum time spent on a single title lookup.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Number of bytes read from a response while looking for its `<title>`.
+const MAX_TITLE_SCAN_BYTES: usize = 64 * 1024;
+
+/// Fetches and caches page titles for bare external links.
+#[derive(Debug)]
+pub struct LinkTitleFetcher {
+  client: reqwest::Client,
+  rate_limiter: RequestRateLimiter,
+  /// One cell per URL, so pages converted at the same time wait for a
+  /// lookup already in flight instead of starting another.
+  cache: Mutex<HashMap<String, Arc<OnceCell<Option<String>>>>>,
+}
+
+impl LinkTitleFetcher {
+  /// Create a fetcher.
+  ///
+  /// # Arguments
+  /// * `rate_limit` - Maximum title requests per second across all pages.
+  ///
+  /// # Errors
+  /// Returns an error if the rate limit is zero or the HTTP client cannot be
+  /// built.
+  pub fn new(rate_limit: usize) -> Result<Self> {
+    if rate_limit == 0 {
+      bail!("Link title rate limit must be at least 1 request per second");
+    }
+
+    let client = reqwest::Client::builder()
+      .timeout(FETCH_TIMEOUT)
+      .user_agent(concat!("confluence-dl/", env!("CARGO_PKG_VERSION")))
+      .build()
+      .context("Failed to build HTTP client for link titles")?;
+
+    Ok(Self {
+      client,
+      rate_limiter: RequestRateLimiter::new(rate_limit, Duration::from_secs(1)),
+      cache: Mutex::new(HashMap::new()),
+    })
+  }
+
+  /// Replace the text of bare links in Markdown with the target page title.
+  ///
+  /// A bare link is `[url](url)` where the text equals an `http(s)` target.
+  /// Links inside fenced code blocks or code spans, and links whose title
+  /// cannot be fetched, are left unchanged.
+  pub async fn apply_to_markdown(&self, markdown: &str) -> String {
+    let links = find_bare_links(markdown);
+    if links.is_empty() {
+      return markdown.to_string();
+    }
+
+    let mut result = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for (start, end, url) in links {
+      let Some(title) = self.title(url).await else {
+        continue;
+      };
+      result.push_str(&markdown[last..start]);
+      result.push_str(&format!("[{}]({url})", escape_link_text(&title)));
+      last = end;
+    }
+    result.push_str(&markdown[last..]);
+    result
+  }
+
+  /// Look up the title of `url`, consulting the cache first.
+  async fn title(&self, url: &str) -> Option<String> {
+    let cell = Arc::clone(self.cache.lock().await.entry(url.to_string()).or_default());
+    cell
+      .get_or_init(|| async {
+        self.rate_limiter.acquire().await;
+        match self.fetch_title(url).await {
+          Ok(title) => title,
+          Err(e) => {
+            debug!("Failed to fetch title for {url}: {e:#}");
+            None
+          }
+        }
+      })
+      .await
+      .clone()
+  }
+
+  async fn fetch_title(&self, url: &str) -> Result<Option<String>> {
+    let mut response = self
+      .client
+      .get(url)
+      .header(reqwest::header::ACCEPT, "text/html")
+      .send()
+      .await?
+      .error_for_status()?;
+
+    let is_html = response
+      .headers()
+      .get(reqwest::header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .is_some_and(|value| value.contains("html"));
+    if !is_html {
+      return Ok(None);
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+      body.extend_from_slice(&chunk);
+      if body.len() >= MAX_TITLE_SCAN_BYTES || contains_ignore_case(&body, b"</title>") {
+        break;
+      }
+    }
+
+    Ok(extract_title(&String::from_utf8_lossy(&body)))
+  }
+}
+
+/// Locate bare `[url](url)` links outside code.
+///
+/// # Returns
+/// The byte range of each link and its URL, in document order.
+fn find_bare_links(markdown: &str) -> Vec<(usize, usize, &str)> {
+  let code = code_ranges(markdown);
+  let mut links = Vec::new();
+  let mut offset = 0;
+
+  while let Some(found) = markdown[offset..].find("[http") {
+    let start = offset + found;
+    offset = start + 1;
+    if let Some(range) = code.iter().find(|range| range.contains(&start)) {
+      offset = range.end;
+      continue;
+    }
+
+    let rest = &markdown[start + 1..];
+    let Some(text_end) = rest.find(']') else {
+      break;
+    };
+    let url = &rest[..text_end];
+    if !(url.starts_with("http://") || url.starts_with("https://")) || url.contains(char::is_whitespace) {
+      continue;
+    }
+
+    let target = format!("]({url})");
+    if rest[text_end..].starts_with(&target) {
+      let end = start + 1 + text_end + target.len();
+      links.push((start, end, url));
+      offset = end;
+    }
+  }
+
+  links
+}
+
+/// Byte ranges of fenced code blocks and inline code spans, in document
+/// order.
+fn code_ranges(markdown: &str) -> Vec<Range<usize>> {
+  let mut ranges = Vec::new();
+  // Marker, run length, and start of the open fenced block
+  let mut fence: Option<(char, usize, usize)> = None;
+  let mut line_start = 0;
+
+  for line in markdown.split_inclusive('\n') {
+    let line_end = line_start + line.len();
+    let content = line.trim_start();
+    let marker = content.chars().next().filter(|&c| c == '`' || c == '~');
+    let run = marker.map_or(0, |marker| content.chars().take_while(|&c| c == marker).count());
+
+    match fence {
+      Some((open, length, start)) => {
+        if marker == Some(open) && run >= length && content[run..].trim().is_empty() {
+          ranges.push(start..line_end);
+          fence = None;
+        }
+      }
+      None if run >= 3 => fence = Some((marker.unwrap_or('`'), run, line_start)),
+      None => ranges.extend(
+        code_spans(line)
+          .into_iter()
+          .map(|span| line_start + span.start..line_start + span.end),
+      ),
+    }
+    line_start = line_end;
+  }
+
+  if let Some((_, _, start)) = fence {
+    ranges.push(start..markdown.len());
+  }
+  ranges
+}
+
+/// Byte ranges of the code spans of a line: a run of backticks up to the
+/// next run of the same length.
+fn code_spans(line: &str) -> Vec<Range<usize>> {
+  let bytes = line.as_bytes();
+  let run_at = |index: usize| bytes[index..].iter().take_while(|&&b| b == b'`').count();
+  let mut spans = Vec::new();
+  let mut index = 0;
+
+  while index < bytes.len() {
+    if bytes[index] != b'`' {
+      index += 1;
+      continue;
+    }
+    let length = run_at(index);
+    let mut close = index + length;
+    let end = loop {
+      match bytes[close..].iter().position(|&b| b == b'`') {
+        Some(found) => {
+          let candidate = close + found;
+          let candidate_length = run_at(candidate);
+          if candidate_length == length {
+            break Some(candidate + length);
+          }
+          close = candidate + candidate_length;
+        }
+        None => break None,
+      }
+    };
+    match end {
+      Some(end) => {
+        spans.push(index..end);
+        index = end;
+      }
+      None => index += length,
+    }
+  }
+  spans
+}
+
+/// Extract and normalise the text of the first `<title>` element.
+fn extract_title(html: &str) -> Option<String> {
+  let lower = html.to_ascii_lowercase();
+  let open = lower.find("<title")?;
+  let content_start = open + lower[open..].find('>')? + 1;
+  let content_end = content_start + lower[content_start..].find("</title>")?;
+
+  let title = decode_entities(&html[content_start..content_end])
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ");
+  (!title.is_empty()).then_some(title)
+}
+
+/// Decode the handful of HTML entities commonly found in titles.
+fn decode_entities(text: &str) -> String {
+  text
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&quot;", "\"")
+    .replace("&#39;", "'")
+    .replace("&#x27;", "'")
+    .replace("&nbsp;", " ")
+    .replace("&amp;", "&")
+}
+
+/// Backslash-escape characters that would end the link text or, in MDX,
+/// start a JSX expression. Both are valid CommonMark escapes.
+fn escape_link_text(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(c, '[' | ']' | '{' | '}' | '<') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+fn contains_ignore_case(haystack: &[u8], needle: &[u8]) -> bool {
+  haystack
+    .windows(needle.len())
+    .any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_find_bare_links() {
+    let markdown = "See [https://a.example/x](https://a.example/x) and [docs](https://b.example) \
+                    or [http://c.example](http://c.example).";
+    let links: Vec<_> = find_bare_links(markdown).into_iter().map(|(_, _, url)| url).collect();
+    assert_eq!(links, vec!["https://a.example/x", "http://c.example"]);
+  }
+
+  #[test]
+  fn test_find_bare_links_skips_code() {
+    let markdown = "Run `curl [https://a.example](https://a.example)` first.\n\
+                    \n\
+                    ```\n\
+                    [https://b.example](https://b.example)\n\
+                    ```\n\
+                    \n\
+                    See [https://c.example](https://c.example).\n";
+    let links: Vec<_> = find_bare_links(markdown).into_iter().map(|(_, _, url)| url).collect();
+    assert_eq!(links, vec!["https://c.example"]);
+  }
+
+  #[test]
+  fn test_extract_title() {
+    let html = "<html><head><TITLE lang=\"en\">\n  Rust &amp; Cargo\n  Guide </TITLE></head></html>";
+    assert_eq!(extract_title(html).as_deref(), Some("Rust & Cargo Guide"));
+    assert_eq!(extract_title("<html><title> </title></html>"), None);
+    assert_eq!(extract_title("<html><body>No title</body></html>"), None);
+  }
+
+  #[tokio::test]
+  async fn test_apply_to_markdown_uses_cached_titles() {
+    let fetcher = LinkTitleFetcher::new(1).unwrap();
+    {
+      let mut cache = fetcher.cache.lock().await;
+      cache.insert(
+        "https://a.example/".to_string(),
+        Arc::new(OnceCell::new_with(Some(Some("Example [A]".to_string())))),
+      );
+      cache.insert(
+        "https://b.example/".to_string(),
+        Arc::new(OnceCell::new_with(Some(None))),
+      );
+    }
+
+    let markdown = "- [https://a.example/](https://a.example/)\n- [https://b.example/](https://b.example/)\n";
+    insta::assert_snapshot!(fetcher.apply_to_markdown(markdown).await, @r"
+    - [Example \[A\]](https://a.example/)
+    - [https://b.example/](https://b.example/)
+    ");
+  }
+}
@@ -0,0 +1,630 @@
+//! Resolution of links that point at other Confluence pages.
+//!
+//! Confluence references pages either structurally (`<ri:page>` inside an
+//! `<ac:link>`) or through absolute URLs pasted into the editor. Both forms
+//! are normalized into a [`PageReference`] so the converters can render them
+//! the same way instead of leaving pasted URLs pointing back at Confluence.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use percent_encoding::percent_decode_str;
+use roxmltree::Document;
+use url::{Url, form_urlencoded};
+
+use crate::confluence::{Page, PageTree};
+use crate::integrity::sha256_hex;
+use crate::markdown::html_entities::preprocess_html_entities;
+use crate::markdown::utils::{get_attribute, matches_tag, wrap_with_namespaces};
+
+/// A reference to a Confluence page found in storage content.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageReference {
+  /// Numeric page ID when the reference carries one (URLs do, `ri:page` does not).
+  pub page_id: Option<String>,
+  /// Page title from `ri:content-title` or the URL slug.
+  pub title: Option<String>,
+  /// Space key when the reference names one.
+  pub space_key: Option<String>,
+  /// Fragment targeting a heading or anchor on the referenced page.
+  pub anchor: Option<String>,
+  /// The absolute URL the reference was parsed from, if any.
+  pub source_url: Option<String>,
+}
+
+/// How to render links to pages that are not part of the export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum UnexportedLinkStyle {
+  /// Link to the page on Confluence (default)
+  #[default]
+  Keep,
+  /// Link to the page on Confluence, marked as external
+  Confluence,
+  /// Emit the page title with a "not exported" annotation
+  Annotate,
+}
+
+/// A page included in the current export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScopedPage {
+  title: String,
+  space_key: Option<String>,
+  /// Output file relative to the export root, using `/` separators.
+  path: Option<String>,
+}
+
+/// The set of pages written by the current export.
+///
+/// Link resolution consults the scope to tell links that will resolve to an
+/// exported file apart from links to pages left behind in Confluence.
+#[derive(Debug, Clone, Default)]
+pub struct ExportScope {
+  pages: HashMap<String, ScopedPage>,
+  /// Digest of `pages`, computed on first use.
+  fingerprint: OnceLock<String>,
+}
+
+impl PartialEq for ExportScope {
+  fn eq(&self, other: &Self) -> bool {
+    self.pages == other.pages
+  }
+}
+
+impl Eq for ExportScope {}
+
+impl ExportScope {
+  /// Build a scope containing every page in `tree`.
+  pub fn from_tree(tree: &PageTree) -> Self {
+    let mut scope = Self::default();
+    let mut stack = vec![tree];
+    while let Some(node) = stack.pop() {
+      scope.insert(&node.page);
+      stack.extend(&node.children);
+    }
+    scope
+  }
+
+  /// Add a page to the scope.
+  pub fn insert(&mut self, page: &Page) {
+    self.insert_page(page, None);
+  }
+
+  /// Add a page to the scope together with the file it is written to.
+  ///
+  /// # Arguments
+  /// * `page` - The exported page.
+  /// * `path` - Output file relative to the export root, using `/` separators.
+  pub fn insert_with_path(&mut self, page: &Page, path: impl Into<String>) {
+    self.insert_page(page, Some(path.into()));
+  }
+
+  fn insert_page(&mut self, page: &Page, path: Option<String>) {
+    self.fingerprint.take();
+    self.pages.insert(
+      page.id.clone(),
+      ScopedPage {
+        title: page.title.clone(),
+        space_key: page.space.as_ref().map(|space| space.key.clone()),
+        path,
+      },
+    );
+  }
+
+  /// Look up the title of an exported page by ID.
+  pub fn title(&self, page_id: &str) -> Option<&str> {
+    self.pages.get(page_id).map(|page| page.title.as_str())
+  }
+
+  /// Check whether a reference targets an exported page.
+  ///
+  /// References with an ID match by ID. Title-only references match by
+  /// title, and additionally by space when both sides know their space.
+  ///
+  /// # Arguments
+  /// * `reference` - The page reference to look up.
+  /// * `current_space` - Space of the page containing the link, used for `ri:page` links that omit `ri:space-key`.
+  pub fn contains(&self, reference: &PageReference, current_space: Option<&str>) -> bool {
+    self.find(reference, current_space).is_some()
+  }
+
+  /// Look up the output file of the page a reference targets.
+  ///
+  /// Matching follows [`ExportScope::contains`].
+  ///
+  /// # Returns
+  /// The path relative to the export root, or `None` when the page is not
+  /// exported or was added without a path.
+  pub fn path(&self, reference: &PageReference, current_space: Option<&str>) -> Option<&str> {
+    self.find(reference, current_space)?.path.as_deref()
+  }
+
+  /// Look up the output file of an exported page by ID.
+  pub fn path_of(&self, page_id: &str) -> Option<&str> {
+    self.pages.get(page_id)?.path.as_deref()
+  }
+
+  /// Digest of the scope's pages that does not depend on insertion order.
+  pub fn fingerprint(&self) -> &str {
+    self.fingerprint.get_or_init(|| {
+      let mut pages: Vec<_> = self.pages.iter().collect();
+      pages.sort_by_key(|(page_id, _)| *page_id);
+      sha256_hex(format!("{pages:?}").as_bytes())
+    })
+  }
+
+  fn find(&self, reference: &PageReference, current_space: Option<&str>) -> Option<&ScopedPage> {
+    if let Some(page_id) = reference.page_id.as_deref() {
+      return self.pages.get(page_id);
+    }
+
+    let title = reference.title.as_deref()?;
+    let space_key = reference.space_key.as_deref().or(current_space);
+
+    self.pages.values().find(|page| {
+      page.title == title
+        && match (space_key, page.space_key.as_deref()) {
+          (Some(expected), Some(actual)) => expected == actual,
+          _ => true,
+        }
+    })
+  }
+}
+
+/// Context used by the converters to resolve links to other pages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkContext {
+  /// Base URL of the Confluence instance being exported, e.g.
+  /// `https://example.atlassian.net`. Absolute links to this host are treated
+  /// like `ri:page` links.
+  pub instance_url: Option<String>,
+  /// Space key of the page being converted.
+  pub current_space: Option<String>,
+  /// ID of the page being converted, used to compute relative links.
+  pub current_page: Option<String>,
+  /// Pages written by this export. `None` treats every page as exported.
+  pub scope: Option<Arc<ExportScope>>,
+  /// How to render links to pages outside [`LinkContext::scope`].
+  pub unexported_links: UnexportedLinkStyle,
+}
+
+impl LinkContext {
+  /// Create a context for content exported from `instance_url`.
+  pub fn new(instance_url: impl Into<String>) -> Self {
+    Self {
+      instance_url: Some(instance_url.into()),
+      ..Default::default()
+    }
+  }
+
+  /// Check whether a reference targets a page written by this export.
+  pub fn is_exported(&self, reference: &PageReference) -> bool {
+    self
+      .scope
+      .as_ref()
+      .is_none_or(|scope| scope.contains(reference, self.current_space.as_deref()))
+  }
+
+  /// Title to display for a reference, looking up ID-only references in the
+  /// export scope.
+  pub fn title<'a>(&'a self, reference: &'a PageReference) -> Option<&'a str> {
+    reference.title.as_deref().or_else(|| {
+      let page_id = reference.page_id.as_deref()?;
+      self.scope.as_ref()?.title(page_id)
+    })
+  }
+
+  /// Relative link from the page being converted to an exported page.
+  ///
+  /// Both pages must have an output path in the export scope. Path segments
+  /// are percent-encoded where Markdown link destinations require it, and the
+  /// reference's anchor is appended as a fragment.
+  ///
+  /// # Returns
+  /// `Some(String)` such as `../Guides/Setup.md#install`, or `None` when
+  /// either path is unknown.
+  pub fn relative_path(&self, reference: &PageReference) -> Option<String> {
+    let scope = self.scope.as_ref()?;
+    let target = scope.path(reference, self.current_space.as_deref())?;
+    let current = scope.path_of(self.current_page.as_deref()?)?;
+
+    let mut path = relative_to(current, target);
+    if let Some(anchor) = &reference.anchor {
+      path.push('#');
+      path.push_str(anchor);
+    }
+    Some(path)
+  }
+
+  /// Relative links written for the exported pages referenced in storage
+  /// content.
+  ///
+  /// Finds the references the converters render, `ri:page` links and pasted
+  /// URLs to this instance, and resolves them with
+  /// [`LinkContext::relative_path`], so the result matches the rewritten
+  /// links in the converted page.
+  ///
+  /// # Returns
+  /// The relative link of each reference, keyed by its Confluence URL, or by
+  /// its title when no URL can be built.
+  ///
+  /// # Errors
+  /// Returns an error if the storage content cannot be parsed.
+  pub fn rewritten_links(&self, storage_content: &str) -> Result<BTreeMap<String, String>> {
+    let preprocessed = preprocess_html_entities(storage_content);
+    let wrapped = wrap_with_namespaces(&preprocessed);
+    let document = Document::parse(&wrapped).context("Failed to parse Confluence storage content for page links")?;
+
+    let links = document
+      .descendants()
+      .filter_map(|node| {
+        if matches_tag(node, "ri:page") {
+          Some(PageReference {
+            title: Some(get_attribute(node, "ri:content-title")?),
+            space_key: get_attribute(node, "ri:space-key"),
+            anchor: node.parent().and_then(|link| get_attribute(link, "ac:anchor")),
+            ..Default::default()
+          })
+        } else if matches_tag(node, "a") {
+          self.page_reference_from_url(&get_attribute(node, "href")?)
+        } else {
+          None
+        }
+      })
+      .filter(|reference| self.is_exported(reference))
+      .filter_map(|reference| {
+        let path = self.relative_path(&reference)?;
+        let original = self
+          .confluence_url(&reference)
+          .or_else(|| self.title(&reference).map(String::from))?;
+        Some((original, path))
+      })
+      .collect();
+    Ok(links)
+  }
+
+  /// Build a Confluence URL for a referenced page.
+  ///
+  /// Prefers the URL the reference was parsed from, then an ID-based URL, and
+  /// finally a `/display/SPACE/Title` URL.
+  ///
+  /// # Returns
+  /// `Some(String)` when enough information is available, otherwise `None`.
+  pub fn confluence_url(&self, reference: &PageReference) -> Option<String> {
+    if let Some(url) = &reference.source_url {
+      return Some(url.clone());
+    }
+
+    let base = self.instance_url.as_deref()?.trim_end_matches('/');
+    let url = if let Some(page_id) = &reference.page_id {
+      format!("{base}/wiki/pages/viewpage.action?pageId={page_id}")
+    } else {
+      let title = reference.title.as_deref()?;
+      let space = reference.space_key.as_deref().or(self.current_space.as_deref())?;
+      let encoded: String = form_urlencoded::byte_serialize(title.as_bytes()).collect();
+      format!("{base}/wiki/display/{space}/{encoded}")
+    };
+
+    Some(match &reference.anchor {
+      Some(anchor) => format!("{url}#{anchor}"),
+      None => url,
+    })
+  }
+
+  /// Check whether an absolute URL points at the exported Confluence instance.
+  ///
+  /// # Arguments
+  /// * `href` - Link target taken from the content.
+  ///
+  /// # Returns
+  /// `true` when `href` is an HTTP(S) URL on the same host as
+  /// [`LinkContext::instance_url`].
+  pub fn is_instance_url(&self, href: &str) -> bool {
+    let Some(instance) = self.instance_url.as_deref().and_then(|url| Url::parse(url).ok()) else {
+      return false;
+    };
+    let Ok(target) = Url::parse(href) else {
+      return false;
+    };
+
+    matches!(target.scheme(), "http" | "https")
+      && instance.host_str().is_some()
+      && instance.host_str() == target.host_str()
+  }
+
+  /// Turn an absolute same-instance page URL into a [`PageReference`].
+  ///
+  /// Recognizes the modern `/spaces/KEY/pages/ID/Title` form, the legacy
+  /// `/display/KEY/Title` form, and `viewpage.action?pageId=ID`.
+  ///
+  /// # Arguments
+  /// * `href` - Link target taken from the content.
+  ///
+  /// # Returns
+  /// `Some(PageReference)` when the URL targets a page on the exported
+  /// instance, otherwise `None` (external links, attachments, space overviews).
+  pub fn page_reference_from_url(&self, href: &str) -> Option<PageReference> {
+    if !self.is_instance_url(href) {
+      return None;
+    }
+
+    let url = Url::parse(href).ok()?;
+    let anchor = url.fragment().filter(|fragment| !fragment.is_empty()).map(String::from);
+    let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+
+    let mut reference = if segments.last() == Some(&"viewpage.action") {
+      let page_id = url
+        .query_pairs()
+        .find(|(key, _)| key == "pageId")
+        .map(|(_, value)| value.into_owned())
+        .filter(|id| is_numeric(id))?;
+
+      PageReference {
+        page_id: Some(page_id),
+        ..Default::default()
+      }
+    } else if let Some(pages_pos) = segments.iter().position(|&segment| segment == "pages") {
+      let page_id = segments.get(pages_pos + 1).filter(|id| is_numeric(id))?;
+      let space_key = segments
+        .iter()
+        .position(|&segment| segment == "spaces")
+        .filter(|&pos| pos + 2 == pages_pos)
+        .map(|pos| segments[pos + 1].to_string());
+
+      PageReference {
+        page_id: Some(page_id.to_string()),
+        title: segments.get(pages_pos + 2).and_then(|slug| decode_slug(slug)),
+        space_key,
+        ..Default::default()
+      }
+    } else {
+      let display_pos = segments.iter().position(|&segment| segment == "display")?;
+      let space_key = segments.get(display_pos + 1)?;
+      let title = segments.get(display_pos + 2).and_then(|slug| decode_slug(slug))?;
+
+      PageReference {
+        page_id: None,
+        title: Some(title),
+        space_key: Some(space_key.to_string()),
+        ..Default::default()
+      }
+    };
+
+    reference.anchor = anchor;
+    reference.source_url = Some(href.to_string());
+    Some(reference)
+  }
+}
+
+/// Decode a URL path slug such as `Getting+Started%3A+Basics` into a title.
+///
+/// Confluence encodes spaces as `+` and a literal `+` as `%2B`.
+fn decode_slug(slug: &str) -> Option<String> {
+  let spaced = slug.replace('+', " ");
+  let decoded = percent_decode_str(&spaced).decode_utf8_lossy();
+  let title = decoded.trim();
+  (!title.is_empty()).then(|| title.to_string())
+}
+
+/// Express `target` relative to the directory containing `from`.
+///
+/// Both paths are relative to the export root and use `/` separators.
+fn relative_to(from: &str, target: &str) -> String {
+  let from_dirs: Vec<&str> = from.split('/').collect();
+  let from_dirs = &from_dirs[..from_dirs.len() - 1];
+  let target_parts: Vec<&str> = target.split('/').collect();
+
+  let common = from_dirs
+    .iter()
+    .zip(&target_parts)
+    .take_while(|(a, b)| a == b)
+    .count()
+    .min(target_parts.len() - 1);
+
+  let mut parts = vec!["..".to_string(); from_dirs.len() - common];
+  parts.extend(target_parts[common..].iter().map(|part| encode_path_segment(part)));
+  parts.join("/")
+}
+
+/// Percent-encode characters that would end or break a Markdown link
+/// destination.
+fn encode_path_segment(segment: &str) -> String {
+  let mut encoded = String::with_capacity(segment.len());
+  for c in segment.chars() {
+    match c {
+      ' ' => encoded.push_str("%20"),
+      '(' => encoded.push_str("%28"),
+      ')' => encoded.push_str("%29"),
+      '#' => encoded.push_str("%23"),
+      '%' => encoded.push_str("%25"),
+      '<' => encoded.push_str("%3C"),
+      '>' => encoded.push_str("%3E"),
+      c => encoded.push(c),
+    }
+  }
+  encoded
+}
+
+fn is_numeric(value: &str) -> bool {
+  !value.is_empty() && value.chars().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::page_in_space;
+
+  fn context() -> LinkContext {
+    LinkContext::new("https://example.atlassian.net")
+  }
+
+  #[test]
+  fn test_page_reference_from_modern_url() {
+    let reference = context()
+      .page_reference_from_url(
+        "https://example.atlassian.net/wiki/spaces/DOCS/pages/123/Getting+Started%3A+Basics#Setup",
+      )
+      .unwrap();
+
+    assert_eq!(
+      reference,
+      PageReference {
+        page_id: Some("123".to_string()),
+        title: Some("Getting Started: Basics".to_string()),
+        space_key: Some("DOCS".to_string()),
+        anchor: Some("Setup".to_string()),
+        source_url: Some(
+          "https://example.atlassian.net/wiki/spaces/DOCS/pages/123/Getting+Started%3A+Basics#Setup".to_string()
+        ),
+      }
+    );
+  }
+
+  #[test]
+  fn test_page_reference_from_legacy_urls() {
+    let display = context()
+      .page_reference_from_url("https://example.atlassian.net/wiki/display/DOCS/Release+Notes")
+      .unwrap();
+    assert_eq!(display.title.as_deref(), Some("Release Notes"));
+    assert_eq!(display.space_key.as_deref(), Some("DOCS"));
+
+    let special = context()
+      .page_reference_from_url("https://example.atlassian.net/wiki/display/DOCS/Q%26A+a%3Db+C%2B%2B")
+      .unwrap();
+    assert_eq!(special.title.as_deref(), Some("Q&A a=b C++"));
+
+    let viewpage = context()
+      .page_reference_from_url("https://example.atlassian.net/wiki/pages/viewpage.action?pageId=987")
+      .unwrap();
+    assert_eq!(viewpage.page_id.as_deref(), Some("987"));
+    assert_eq!(viewpage.title, None);
+  }
+
+  #[test]
+  fn test_page_reference_ignores_other_hosts_and_non_page_urls() {
+    let context = context();
+    assert!(
+      context
+        .page_reference_from_url("https://other.atlassian.net/wiki/spaces/DOCS/pages/123/Title")
+        .is_none()
+    );
+    assert!(
+      context
+        .page_reference_from_url("https://example.atlassian.net/wiki/download/attachments/123/file.png")
+        .is_none()
+    );
+    assert!(
+      LinkContext::default()
+        .page_reference_from_url("https://example.atlassian.net/wiki/pages/123")
+        .is_none()
+    );
+  }
+
+  #[test]
+  fn test_export_scope_matches_by_id_and_title() {
+    let mut scope = ExportScope::default();
+    scope.insert(&page_in_space("1", "Home", "DOCS"));
+
+    let by_id = PageReference {
+      page_id: Some("1".to_string()),
+      ..Default::default()
+    };
+    let by_title = PageReference {
+      title: Some("Home".to_string()),
+      ..Default::default()
+    };
+    let other_space = PageReference {
+      title: Some("Home".to_string()),
+      space_key: Some("OPS".to_string()),
+      ..Default::default()
+    };
+
+    assert!(scope.contains(&by_id, None));
+    assert!(scope.contains(&by_title, Some("DOCS")));
+    assert!(!scope.contains(&by_title, Some("OPS")));
+    assert!(!scope.contains(&other_space, Some("DOCS")));
+  }
+
+  #[test]
+  fn test_relative_path_between_exported_pages() {
+    let mut scope = ExportScope::default();
+    scope.insert_with_path(&page_in_space("1", "Handbook", "DOCS"), "Handbook.md");
+    scope.insert_with_path(
+      &page_in_space("2", "Setup (Mac)", "DOCS"),
+      "Handbook/Guides/Setup (Mac).md",
+    );
+    scope.insert_with_path(&page_in_space("3", "FAQ", "DOCS"), "Handbook/FAQ.md");
+    scope.insert(&page_in_space("4", "Unplaced", "DOCS"));
+    let context = LinkContext {
+      current_space: Some("DOCS".to_string()),
+      current_page: Some("3".to_string()),
+      scope: Some(Arc::new(scope)),
+      ..context()
+    };
+    let by_title = |title: &str| PageReference {
+      title: Some(title.to_string()),
+      ..Default::default()
+    };
+
+    assert_eq!(
+      context.relative_path(&by_title("Handbook")).as_deref(),
+      Some("../Handbook.md")
+    );
+    assert_eq!(
+      context
+        .relative_path(&PageReference {
+          anchor: Some("brew".to_string()),
+          ..by_title("Setup (Mac)")
+        })
+        .as_deref(),
+      Some("Guides/Setup%20%28Mac%29.md#brew")
+    );
+    assert_eq!(context.relative_path(&by_title("FAQ")).as_deref(), Some("FAQ.md"));
+    assert_eq!(context.relative_path(&by_title("Unplaced")), None);
+    assert_eq!(context.relative_path(&by_title("Missing")), None);
+  }
+
+  #[test]
+  fn test_rewritten_links() {
+    let mut scope = ExportScope::default();
+    scope.insert_with_path(&page_in_space("1", "Handbook", "DOCS"), "Handbook.md");
+    scope.insert_with_path(&page_in_space("2", "Setup", "DOCS"), "Handbook/Setup.md");
+    let context = LinkContext {
+      current_space: Some("DOCS".to_string()),
+      current_page: Some("2".to_string()),
+      scope: Some(Arc::new(scope)),
+      ..context()
+    };
+    let storage = r#"
+      <p><ac:link ac:anchor="intro"><ri:page ri:content-title="Handbook" /></ac:link></p>
+      <p><a href="https://example.atlassian.net/wiki/spaces/DOCS/pages/1/Handbook">pasted</a></p>
+      <p><ac:link><ri:page ri:content-title="Elsewhere" /></ac:link></p>
+      <p><a href="https://example.com/Handbook">external</a></p>
+    "#;
+
+    insta::assert_debug_snapshot!(context.rewritten_links(storage).unwrap(), @r#"
+    {
+        "https://example.atlassian.net/wiki/display/DOCS/Handbook#intro": "../Handbook.md#intro",
+        "https://example.atlassian.net/wiki/spaces/DOCS/pages/1/Handbook": "../Handbook.md",
+    }
+    "#);
+  }
+
+  #[test]
+  fn test_confluence_url_for_title_reference() {
+    let context = LinkContext {
+      current_space: Some("DOCS".to_string()),
+      ..context()
+    };
+    let reference = PageReference {
+      title: Some("Release Notes".to_string()),
+      ..Default::default()
+    };
+
+    assert_eq!(
+      context.confluence_url(&reference).as_deref(),
+      Some("https://example.atlassian.net/wiki/display/DOCS/Release+Notes")
+    );
+  }
+}
@@ -0,0 +1,893 @@
+//! Machine-readable record of an export.
+//!
+//! Tooling that consumes an export needs to know which pages were written,
+//! where they landed, and whether their files changed since it last looked.
+//! After every run, [`MANIFEST_FILE`] at the export root lists each exported
+//! page with its ID, title, path, MIME type, version, and the SHA-256
+//! checksum of its file, and each image, attachment, and table file with its size, checksum,
+//! and the pages referencing it.
+//!
+//! Files are hashed on the blocking thread pool as each page is written, so
+//! the checksums are ready when the run ends, and each page is appended to
+//! [`JOURNAL_FILE`] as soon as it is recorded.
+//!
+//! Later runs skip assets that already exist. With `--verify`,
+//! [`remove_corrupted_assets`] first re-hashes the assets listed in the
+//! previous manifest and deletes those whose size or checksum no longer
+//! match, so they are downloaded again instead of being kept broken.
+//!
+//! Pages deleted or moved in Confluence leave their files behind in an
+//! export that is refreshed in place. With `--prune`, [`Manifest::stale_files`]
+//! compares the previous manifest with the files written by this run. Pages
+//! missing from this run may only have been left out of it, so
+//! [`StaleFiles::paths`] only returns the files of pages confirmed to be
+//! deleted or moved, which [`remove_files`] then deletes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::confluence::Page;
+use crate::integrity::sha256_file;
+use crate::processed_page::{ProcessedPage, edited_page_path, link_path, normalize_path};
+
+/// File name of the manifest, at the export root.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// File name of the journal at the export root that pages are appended to as
+/// they are recorded, one JSON object per line, until [`Manifest::write`]
+/// replaces it with the manifest. A run that is interrupted leaves it behind
+/// as the record of the files it wrote.
+pub const JOURNAL_FILE: &str = "manifest.partial.jsonl";
+
+/// An exported page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestPage {
+  /// Confluence page ID.
+  pub id: String,
+  /// Page title.
+  pub title: String,
+  /// Path of the page file relative to the export root.
+  pub path: String,
+  /// MIME type of the page file, following the output format and extension.
+  #[serde(default)]
+  pub mime_type: String,
+  /// Version of the page that was exported, when known.
+  pub version: Option<u32>,
+  /// SHA-256 checksum of the page file.
+  pub checksum: String,
+}
+
+/// An image, attachment, or table file written with the pages.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestAsset {
+  /// Path of the file relative to the export root.
+  pub path: String,
+  /// Size of the file in bytes.
+  pub bytes: u64,
+  /// SHA-256 checksum of the file.
+  pub checksum: String,
+  /// IDs of the pages referencing the file.
+  pub pages: BTreeSet<String>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+  /// Pages recorded so far, appended one line each as they are recorded.
+  journal: Option<File>,
+  pages: BTreeMap<String, ManifestPage>,
+  assets: BTreeMap<String, ManifestAsset>,
+  /// Other files this export wrote, which the manifest does not list: `.new`
+  /// files next to edited pages, raw storage, reports, and site
+  /// configuration.
+  files: BTreeSet<String>,
+}
+
+/// Pages and assets recorded during an export.
+#[derive(Debug)]
+pub struct Manifest {
+  root: PathBuf,
+  root_page: String,
+  mime_type: &'static str,
+  entries: Arc<Mutex<Entries>>,
+}
+
+impl Manifest {
+  /// Collect a manifest for an export of the page `root_page` written below
+  /// `root` whose page files have the MIME type `mime_type`.
+  pub fn new(root: &Path, root_page: &str, mime_type: &'static str) -> Self {
+    Self {
+      root: normalize_path(root),
+      root_page: root_page.to_string(),
+      mime_type,
+      entries: Arc::default(),
+    }
+  }
+
+  /// Record a written page and the files written with it.
+  ///
+  /// A page written as `.new` next to an edited file keeps the edited file
+  /// as its path, so `--prune` does not delete it.
+  ///
+  /// # Arguments
+  /// * `page` - The exported page.
+  /// * `processed` - Its converted content and downloaded files.
+  /// * `output_dir` - Directory the page was written to.
+  /// * `output_path` - Path of the written page file.
+  ///
+  /// # Errors
+  /// Returns an error when a written file cannot be read for its checksum.
+  pub async fn record(
+    &self,
+    page: &Page,
+    processed: &ProcessedPage,
+    output_dir: &Path,
+    output_path: &Path,
+  ) -> Result<()> {
+    let edited_path = edited_page_path(output_path);
+    let page_path = edited_path.clone().unwrap_or_else(|| output_path.to_path_buf());
+    let mut files: Vec<PathBuf> = edited_path.map(|_| output_path.to_path_buf()).into_iter().collect();
+    if processed.raw_storage.is_some() {
+      files.push(output_dir.join(format!("{}.raw.xml", processed.filename)));
+    }
+    let asset_paths: BTreeSet<PathBuf> = processed
+      .link_map
+      .images
+      .values()
+      .chain(processed.link_map.attachments.values())
+      .map(PathBuf::from)
+      .chain(processed.tables.iter().map(|table| table.relative_path.clone()))
+      .map(|relative| normalize_path(&output_dir.join(relative)))
+      .collect();
+    let entry = ManifestPage {
+      id: page.id.clone(),
+      title: page.title.clone(),
+      path: relative_path(&self.root, &page_path),
+      mime_type: self.mime_type.to_string(),
+      version: page.version.as_ref().map(|version| version.number),
+      checksum: String::new(),
+    };
+
+    // Hashing reads whole files, so it runs on the blocking pool alongside
+    // the downloads of other pages
+    let root = self.root.clone();
+    let entries = Arc::clone(&self.entries);
+    tokio::task::spawn_blocking(move || {
+      let (entry, assets) = hash_page(&root, entry, &page_path, asset_paths)?;
+      let files: Vec<String> = files.iter().map(|path| relative_path(&root, path)).collect();
+      let mut entries = entries.lock().expect("manifest lock poisoned");
+      entries.add(&root, entry, assets, files)
+    })
+    .await
+    .context("Manifest hashing task failed")?
+  }
+
+  /// Write [`MANIFEST_FILE`] at the export root.
+  ///
+  /// # Returns
+  /// The path of the written manifest.
+  ///
+  /// # Errors
+  /// Returns an error when the manifest cannot be written.
+  pub fn write(&self) -> Result<PathBuf> {
+    let mut entries = self.entries.lock().expect("manifest lock poisoned");
+    let generated_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let path = self.root.join(MANIFEST_FILE);
+    fs::write(&path, render_manifest(&entries, &self.root_page, &generated_at)?)
+      .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    entries.journal = None;
+    let journal = self.root.join(JOURNAL_FILE);
+    match fs::remove_file(&journal) {
+      Err(err) if err.kind() != ErrorKind::NotFound => {
+        Err(err).with_context(|| format!("Failed to remove {}", journal.display()))
+      }
+      _ => Ok(path),
+    }
+  }
+
+  /// Record a file this export wrote that the manifest does not list, such
+  /// as a report or site configuration, so it is checksummed with the
+  /// export. Files outside the export root are ignored.
+  pub fn record_file(&self, path: &Path) {
+    let path = normalize_path(path);
+    if let Ok(relative) = path.strip_prefix(&self.root) {
+      let relative = link_path(relative);
+      self
+        .entries
+        .lock()
+        .expect("manifest lock poisoned")
+        .files
+        .insert(relative);
+    }
+  }
+
+  /// Files this export wrote: the pages, assets, and other files recorded so
+  /// far.
+  ///
+  /// # Returns
+  /// Paths relative to the export root, sorted.
+  pub fn paths(&self) -> Vec<String> {
+    let entries = self.entries.lock().expect("manifest lock poisoned");
+    let paths: BTreeSet<String> = entries
+      .pages
+      .values()
+      .map(|page| page.path.clone())
+      .chain(entries.assets.keys().cloned())
+      .chain(entries.files.iter().cloned())
+      .collect();
+    paths.into_iter().collect()
+  }
+
+  /// ID of the root page of this export.
+  pub fn root_page(&self) -> &str {
+    &self.root_page
+  }
+
+  /// Compare the previous manifest with the files this export wrote.
+  ///
+  /// Call this before [`Manifest::write`] replaces the previous manifest.
+  ///
+  /// # Errors
+  /// Returns an error when the previous manifest is missing or cannot be
+  /// parsed.
+  pub fn stale_files(&self) -> Result<StaleFiles> {
+    let previous = read_manifest(&self.root)?;
+    let entries = self.entries.lock().expect("manifest lock poisoned");
+    let written: BTreeSet<String> = entries
+      .pages
+      .values()
+      .map(|page| page.path.clone())
+      .chain(entries.assets.keys().cloned())
+      .chain(entries.files.iter().cloned())
+      .collect();
+    let exported: BTreeSet<String> = entries.pages.keys().cloned().collect();
+    let missing_pages = previous
+      .pages
+      .iter()
+      .map(|page| page.id.clone())
+      .filter(|id| !exported.contains(id))
+      .collect();
+    Ok(StaleFiles {
+      root_page: previous.root_page,
+      missing_pages,
+      pages: previous.pages,
+      assets: previous.assets,
+      exported,
+      written,
+    })
+  }
+}
+
+impl Entries {
+  /// Add a recorded page with its assets and other files, and append the
+  /// page to the [`JOURNAL_FILE`], which is created on first use.
+  fn add(&mut self, root: &Path, page: ManifestPage, assets: Vec<ManifestAsset>, files: Vec<String>) -> Result<()> {
+    let line = serde_json::to_string(&JournalEntry {
+      page: &page,
+      assets: &assets,
+    })
+    .context("Failed to serialize manifest entry")?;
+    let path = root.join(JOURNAL_FILE);
+    let journal = match &mut self.journal {
+      Some(journal) => journal,
+      None => self
+        .journal
+        .insert(File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?),
+    };
+    writeln!(journal, "{line}").with_context(|| format!("Failed to write {}", path.display()))?;
+
+    for asset in assets {
+      self
+        .assets
+        .entry(asset.path.clone())
+        .or_insert(asset)
+        .pages
+        .insert(page.id.clone());
+    }
+    self.files.extend(files);
+    self.pages.insert(page.id.clone(), page);
+    Ok(())
+  }
+}
+
+/// Checksum a written page file and the assets among `asset_paths` that
+/// exist, each referenced by the page.
+///
+/// Files kept from an earlier run are only known by their links, so assets
+/// are looked up on disk rather than taken from the downloads.
+fn hash_page(
+  root: &Path,
+  mut page: ManifestPage,
+  page_path: &Path,
+  asset_paths: BTreeSet<PathBuf>,
+) -> Result<(ManifestPage, Vec<ManifestAsset>)> {
+  page.checksum = sha256_file(page_path)?;
+  let mut assets = Vec::with_capacity(asset_paths.len());
+  for path in asset_paths.into_iter().filter(|path| path.is_file()) {
+    let bytes = fs::metadata(&path)
+      .with_context(|| format!("Failed to read {}", path.display()))?
+      .len();
+    assets.push(ManifestAsset {
+      path: relative_path(root, &path),
+      bytes,
+      checksum: sha256_file(&path)?,
+      pages: BTreeSet::from([page.id.clone()]),
+    });
+  }
+  Ok((page, assets))
+}
+
+/// Path relative to the export root, with `/` separators.
+fn relative_path(root: &Path, path: &Path) -> String {
+  let path = normalize_path(path);
+  link_path(path.strip_prefix(root).unwrap_or(&path))
+}
+
+/// Files of the previous export that the current export did not write.
+///
+/// Pages missing from the current export may have been deleted or moved out
+/// of the exported tree, but also only left out of this run by filters,
+/// hooks, or failed fetches, so their files are not stale until their
+/// removal is confirmed.
+#[derive(Debug)]
+pub struct StaleFiles {
+  /// Root page of the previous export, when its manifest records one.
+  pub root_page: Option<String>,
+  /// IDs of the pages of the previous export that the current export did
+  /// not write.
+  pub missing_pages: Vec<String>,
+  pages: Vec<ManifestPage>,
+  assets: Vec<ManifestAsset>,
+  exported: BTreeSet<String>,
+  written: BTreeSet<String>,
+}
+
+impl StaleFiles {
+  /// Files of the previous export to delete.
+  ///
+  /// A page file is stale when the page was exported elsewhere by the
+  /// current export, or is among the `removed` missing pages. An asset is
+  /// stale when every page that referenced it is. Files the current export
+  /// wrote, and paths that would leave the export root, are never returned.
+  ///
+  /// # Arguments
+  /// * `removed` - Missing pages confirmed to be deleted or moved out of the exported tree.
+  ///
+  /// # Returns
+  /// Paths relative to the export root, sorted.
+  pub fn paths(&self, removed: &BTreeSet<String>) -> Vec<String> {
+    let gone = |id: &String| self.exported.contains(id) || removed.contains(id);
+    let stale: BTreeSet<&str> = self
+      .pages
+      .iter()
+      .filter(|page| gone(&page.id))
+      .map(|page| page.path.as_str())
+      .chain(
+        self
+          .assets
+          .iter()
+          .filter(|asset| asset.pages.iter().all(gone))
+          .map(|asset| asset.path.as_str()),
+      )
+      .filter(|path| !self.written.contains(*path) && is_inside_root(path))
+      .collect();
+    stale.into_iter().map(str::to_string).collect()
+  }
+}
+
+/// A line of the [`JOURNAL_FILE`].
+#[derive(Debug, Serialize)]
+struct JournalEntry<'a> {
+  page: &'a ManifestPage,
+  assets: &'a [ManifestAsset],
+}
+
+/// The manifest document.
+#[derive(Debug, Serialize)]
+struct Document<'a> {
+  generator: String,
+  generated_at: &'a str,
+  root_page: &'a str,
+  pages: Vec<&'a ManifestPage>,
+  assets: Vec<&'a ManifestAsset>,
+}
+
+/// The parts of a written manifest read back by later commands.
+#[derive(Debug, Deserialize)]
+struct StoredManifest {
+  #[serde(default)]
+  root_page: Option<String>,
+  pages: Vec<ManifestPage>,
+  #[serde(default)]
+  assets: Vec<ManifestAsset>,
+}
+
+/// Read the pages listed in the [`MANIFEST_FILE`] of an earlier export.
+///
+/// # Errors
+/// Returns an error when the manifest is missing or cannot be parsed.
+pub fn read_pages(root: &Path) -> Result<Vec<ManifestPage>> {
+  Ok(read_manifest(root)?.pages)
+}
+
+fn read_manifest(root: &Path) -> Result<StoredManifest> {
+  let path = root.join(MANIFEST_FILE);
+  let json = fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+  serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// What `--prune` does with files no longer part of the export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PruneMode {
+  /// Only list the files that would be deleted
+  #[default]
+  List,
+  /// Delete the files
+  Delete,
+}
+
+/// Whether a manifest path stays below the export root.
+fn is_inside_root(path: &str) -> bool {
+  Path::new(path)
+    .components()
+    .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// Delete files below `root`, then the directories left empty by them.
+///
+/// Files that no longer exist are skipped.
+///
+/// # Returns
+/// The paths of the files that were deleted.
+///
+/// # Errors
+/// Returns an error when a file cannot be deleted.
+pub fn remove_files(root: &Path, paths: &[String]) -> Result<Vec<String>> {
+  let mut removed = Vec::new();
+  for relative in paths {
+    let path = root.join(relative);
+    if !path.is_file() {
+      continue;
+    }
+    fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    removed.push(relative.clone());
+
+    // Directories that still hold files fail to be removed, ending the walk
+    let mut dir = path.parent();
+    while let Some(parent) = dir
+      && parent != root
+      && fs::remove_dir(parent).is_ok()
+    {
+      dir = parent.parent();
+    }
+  }
+  Ok(removed)
+}
+
+/// Outcome of [`remove_corrupted_assets`].
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Verification {
+  /// Number of existing assets that were re-hashed.
+  pub checked: usize,
+  /// Paths, relative to the export root, of the assets that were removed.
+  pub removed: Vec<String>,
+}
+
+/// Re-hash the assets listed in the [`MANIFEST_FILE`] of an earlier export
+/// and remove those that are corrupted or truncated.
+///
+/// An asset whose size or SHA-256 checksum differs from its manifest entry
+/// is deleted, so the export downloads it again instead of skipping it
+/// because it exists. Assets that no longer exist, and paths leading outside
+/// the export root, are not checked. Assets are hashed in parallel on the
+/// blocking thread pool, one per core.
+///
+/// # Errors
+/// Returns an error when the manifest is missing or cannot be parsed, or
+/// when a corrupted asset cannot be removed.
+pub async fn remove_corrupted_assets(root: &Path) -> Result<Verification> {
+  let assets = read_manifest(root)?.assets;
+  let parallelism = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+  let mut checks = stream::iter(assets.into_iter().filter(|asset| is_inside_root(&asset.path)))
+    .map(|asset| {
+      let path = root.join(&asset.path);
+      async move {
+        let (bytes, checksum) = (asset.bytes, asset.checksum);
+        let removed = tokio::task::spawn_blocking(move || remove_if_corrupted(&path, bytes, &checksum))
+          .await
+          .context("Hashing task failed")??;
+        Ok::<_, anyhow::Error>((asset.path, removed))
+      }
+    })
+    .buffered(parallelism);
+
+  let mut verification = Verification::default();
+  while let Some(check) = checks.next().await {
+    let (path, removed) = check?;
+    match removed {
+      Some(true) => {
+        verification.checked += 1;
+        verification.removed.push(path);
+      }
+      Some(false) => verification.checked += 1,
+      None => {}
+    }
+  }
+  Ok(verification)
+}
+
+/// Delete a file whose size or SHA-256 checksum differs from the expected
+/// ones.
+///
+/// # Returns
+/// Whether the file was removed, or `None` when it does not exist.
+fn remove_if_corrupted(path: &Path, bytes: u64, checksum: &str) -> Result<Option<bool>> {
+  let Ok(metadata) = fs::metadata(path) else {
+    return Ok(None);
+  };
+  let intact = metadata.len() == bytes && sha256_file(path).is_ok_and(|actual| actual == checksum);
+  if !intact {
+    fs::remove_file(path).with_context(|| format!("Failed to remove corrupted {}", path.display()))?;
+  }
+  Ok(Some(!intact))
+}
+
+/// Render the manifest with pages and assets sorted by path.
+fn render_manifest(entries: &Entries, root_page: &str, generated_at: &str) -> Result<String> {
+  let mut pages: Vec<&ManifestPage> = entries.pages.values().collect();
+  pages.sort_by(|a, b| a.path.cmp(&b.path));
+  let document = Document {
+    generator: format!("confluence-dl {}", env!("CARGO_PKG_VERSION")),
+    generated_at,
+    root_page,
+    pages,
+    assets: entries.assets.values().collect(),
+  };
+  let json = serde_json::to_string_pretty(&document).context("Failed to serialize export manifest")?;
+  Ok(format!("{json}\n"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::processed_page::LinkMap;
+
+  #[tokio::test]
+  async fn test_record_and_render() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let guide_dir = root.join("Guide");
+    fs::create_dir_all(guide_dir.join("images")).unwrap();
+    fs::write(root.join("Guide.md"), "# Guide\n").unwrap();
+    fs::write(guide_dir.join("Setup.md"), "# Setup\n").unwrap();
+    fs::write(guide_dir.join("images/arch.png"), b"PNG").unwrap();
+
+    let page = |id: &str, title: &str, version: u32| -> Page {
+      serde_json::from_value(serde_json::json!({
+        "id": id,
+        "title": title,
+        "type": "page",
+        "status": "current",
+        "version": {"number": version}
+      }))
+      .unwrap()
+    };
+    let with_image = ProcessedPage {
+      link_map: LinkMap {
+        images: BTreeMap::from([("arch.png".to_string(), "images/arch.png".to_string())]),
+        attachments: BTreeMap::new(),
+        pages: BTreeMap::new(),
+      },
+      ..Default::default()
+    };
+
+    let manifest = Manifest::new(root, "1", "text/mdx");
+    manifest
+      .record(
+        &page("2", "Setup", 3),
+        &with_image,
+        &guide_dir,
+        &guide_dir.join("Setup.md"),
+      )
+      .await
+      .unwrap();
+    manifest
+      .record(
+        &page("1", "Guide", 7),
+        &ProcessedPage::default(),
+        root,
+        &root.join("Guide.md"),
+      )
+      .await
+      .unwrap();
+
+    // Pages are journaled as they are recorded, until the manifest replaces
+    // the journal
+    let journal = fs::read_to_string(root.join(JOURNAL_FILE)).unwrap();
+    let journaled: Vec<serde_json::Value> = journal
+      .lines()
+      .map(|line| serde_json::from_str(line).unwrap())
+      .collect();
+    assert_eq!(journaled.len(), 2);
+    assert_eq!(journaled[0]["page"]["path"], "Guide/Setup.md");
+    assert_eq!(journaled[0]["assets"][0]["path"], "Guide/images/arch.png");
+
+    let written = manifest.write().unwrap();
+    assert!(!root.join(JOURNAL_FILE).exists());
+    let pages = read_pages(root).unwrap();
+    assert_eq!(written, root.join(MANIFEST_FILE));
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1].path, "Guide/Setup.md");
+
+    let entries = manifest.entries.lock().unwrap();
+    let rendered = render_manifest(&entries, "1", "2024-05-01T10:00:00Z").unwrap();
+    let rendered = rendered.replace(env!("CARGO_PKG_VERSION"), "[version]");
+    insta::assert_snapshot!(rendered, @r#"
+    {
+      "generator": "confluence-dl [version]",
+      "generated_at": "2024-05-01T10:00:00Z",
+      "root_page": "1",
+      "pages": [
+        {
+          "id": "1",
+          "title": "Guide",
+          "path": "Guide.md",
+          "mime_type": "text/mdx",
+          "version": 7,
+          "checksum": "bc553ffe57e544498b12a9865dbf3abc2004c474e349c52c378eaa402287424b"
+        },
+        {
+          "id": "2",
+          "title": "Setup",
+          "path": "Guide/Setup.md",
+          "mime_type": "text/mdx",
+          "version": 3,
+          "checksum": "777044cc4b499b3cf9f57a63106f3952f17e7962324f8dfc432aea0abdbd833b"
+        }
+      ],
+      "assets": [
+        {
+          "path": "Guide/images/arch.png",
+          "bytes": 3,
+          "checksum": "796120837694d3f3f29259cfeb25091698c2a0aa87873658d840b4993ee889b3",
+          "pages": [
+            "2"
+          ]
+        }
+      ]
+    }
+    "#);
+  }
+
+  #[tokio::test]
+  async fn test_paths_include_other_written_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path().join("export");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("Guide.md"), "# Guide\n").unwrap();
+    let page: Page =
+      serde_json::from_value(serde_json::json!({"id": "1", "title": "Guide", "type": "page", "status": "current"}))
+        .unwrap();
+    let processed = ProcessedPage {
+      filename: "Guide".to_string(),
+      raw_storage: Some("<p>Guide</p>".to_string()),
+      ..Default::default()
+    };
+
+    let manifest = Manifest::new(&root, "1", "text/markdown");
+    manifest
+      .record(&page, &processed, &root, &root.join("Guide.md"))
+      .await
+      .unwrap();
+    manifest.record_file(&root.join("CHANGES.md"));
+    manifest.record_file(&root.join("docs/../mkdocs.yml"));
+    manifest.record_file(&temp_dir.path().join("graph.dot"));
+
+    assert_eq!(
+      manifest.paths(),
+      ["CHANGES.md", "Guide.md", "Guide.raw.xml", "mkdocs.yml"]
+    );
+  }
+
+  #[tokio::test]
+  async fn test_remove_corrupted_assets() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("images")).unwrap();
+    fs::write(root.join("Guide.md"), "# Guide\n").unwrap();
+    for name in ["intact.png", "truncated.png", "corrupted.png", "missing.png"] {
+      fs::write(root.join("images").join(name), b"PNG").unwrap();
+    }
+    let page: Page =
+      serde_json::from_value(serde_json::json!({"id": "1", "title": "Guide", "type": "page", "status": "current"}))
+        .unwrap();
+    let images = ["intact.png", "truncated.png", "corrupted.png", "missing.png"]
+      .map(|name| (name.to_string(), format!("images/{name}")));
+    let processed = ProcessedPage {
+      link_map: LinkMap {
+        images: BTreeMap::from(images),
+        attachments: BTreeMap::new(),
+        pages: BTreeMap::new(),
+      },
+      ..Default::default()
+    };
+    let manifest = Manifest::new(root, "1", "text/markdown");
+    manifest
+      .record(&page, &processed, root, &root.join("Guide.md"))
+      .await
+      .unwrap();
+    manifest.write().unwrap();
+
+    fs::write(root.join("images/truncated.png"), b"PN").unwrap();
+    fs::write(root.join("images/corrupted.png"), b"GIF").unwrap();
+    fs::remove_file(root.join("images/missing.png")).unwrap();
+
+    let verification = remove_corrupted_assets(root).await.unwrap();
+    assert_eq!(verification.checked, 3);
+    assert_eq!(verification.removed, ["images/corrupted.png", "images/truncated.png"]);
+    assert!(root.join("images/intact.png").exists());
+    assert!(!root.join("images/corrupted.png").exists());
+    assert!(remove_corrupted_assets(&root.join("images")).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_remove_corrupted_assets_stays_inside_root() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path().join("export");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(temp_dir.path().join("outside.png"), b"PNG").unwrap();
+    let manifest = serde_json::json!({
+      "pages": [],
+      "assets": [{ "path": "../outside.png", "bytes": 1, "checksum": "0", "pages": [] }]
+    });
+    fs::write(root.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+
+    let verification = remove_corrupted_assets(&root).await.unwrap();
+    assert_eq!(verification, Verification::default());
+    assert!(temp_dir.path().join("outside.png").exists());
+  }
+
+  #[tokio::test]
+  async fn test_prune_stale_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let page = |id: &str, title: &str| -> Page {
+      serde_json::from_value(serde_json::json!({"id": id, "title": title, "type": "page", "status": "current"}))
+        .unwrap()
+    };
+    fs::create_dir_all(root.join("Guide/images")).unwrap();
+    fs::write(root.join("Guide.md"), "# Guide\n").unwrap();
+    fs::write(root.join("Guide/Old.md"), "# Old\n").unwrap();
+    fs::write(root.join("Guide/images/old.png"), b"PNG").unwrap();
+    let with_image = ProcessedPage {
+      link_map: LinkMap {
+        images: BTreeMap::from([("old.png".to_string(), "images/old.png".to_string())]),
+        attachments: BTreeMap::new(),
+        pages: BTreeMap::new(),
+      },
+      ..Default::default()
+    };
+    let previous = Manifest::new(root, "1", "text/markdown");
+    previous
+      .record(
+        &page("1", "Guide"),
+        &ProcessedPage::default(),
+        root,
+        &root.join("Guide.md"),
+      )
+      .await
+      .unwrap();
+    previous
+      .record(
+        &page("2", "Old"),
+        &with_image,
+        &root.join("Guide"),
+        &root.join("Guide/Old.md"),
+      )
+      .await
+      .unwrap();
+    previous.write().unwrap();
+
+    // The next run only exports the root page
+    let current = Manifest::new(root, "1", "text/markdown");
+    current
+      .record(
+        &page("1", "Guide"),
+        &ProcessedPage::default(),
+        root,
+        &root.join("Guide.md"),
+      )
+      .await
+      .unwrap();
+    let stale_files = current.stale_files().unwrap();
+    assert_eq!(stale_files.root_page.as_deref(), Some("1"));
+    assert_eq!(stale_files.missing_pages, ["2"]);
+    // Pages only left out of this run keep their files
+    assert!(stale_files.paths(&BTreeSet::new()).is_empty());
+    let stale = stale_files.paths(&BTreeSet::from(["2".to_string()]));
+    assert_eq!(stale, ["Guide/Old.md", "Guide/images/old.png"]);
+
+    let removed = remove_files(root, &stale).unwrap();
+    assert_eq!(removed, stale);
+    assert!(root.join("Guide.md").exists());
+    assert!(!root.join("Guide").exists());
+    assert!(remove_files(root, &stale).unwrap().is_empty());
+
+    assert!(is_inside_root("Guide/Old.md"));
+    assert!(!is_inside_root("../Other.md"));
+    assert!(!is_inside_root("/etc/passwd"));
+  }
+
+  #[tokio::test]
+  async fn test_prune_renamed_page() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let page = |title: &str| -> Page {
+      serde_json::from_value(serde_json::json!({"id": "2", "title": title, "type": "page", "status": "current"}))
+        .unwrap()
+    };
+    fs::write(root.join("Old.md"), "# Old\n").unwrap();
+    fs::write(root.join("New.md"), "# New\n").unwrap();
+    let previous = Manifest::new(root, "1", "text/markdown");
+    previous
+      .record(&page("Old"), &ProcessedPage::default(), root, &root.join("Old.md"))
+      .await
+      .unwrap();
+    previous.write().unwrap();
+
+    // The file of a page exported under a new title needs no confirmation
+    let current = Manifest::new(root, "1", "text/markdown");
+    current
+      .record(&page("New"), &ProcessedPage::default(), root, &root.join("New.md"))
+      .await
+      .unwrap();
+    let stale_files = current.stale_files().unwrap();
+    assert!(stale_files.missing_pages.is_empty());
+    assert_eq!(stale_files.paths(&BTreeSet::new()), ["Old.md"]);
+  }
+
+  #[tokio::test]
+  async fn test_prune_keeps_pages_written_as_new() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let page: Page =
+      serde_json::from_value(serde_json::json!({"id": "1", "title": "Guide", "type": "page", "status": "current"}))
+        .unwrap();
+    fs::write(root.join("Guide.md"), "# Guide\n").unwrap();
+    let previous = Manifest::new(root, "1", "text/markdown");
+    previous
+      .record(&page, &ProcessedPage::default(), root, &root.join("Guide.md"))
+      .await
+      .unwrap();
+    previous.write().unwrap();
+
+    // `--conflict new` keeps the edited file and writes the export next to it
+    fs::write(root.join("Guide.md"), "# Guide\n\nLocal notes\n").unwrap();
+    fs::write(root.join("Guide.md.new"), "# Guide\n").unwrap();
+    let current = Manifest::new(root, "1", "text/markdown");
+    current
+      .record(&page, &ProcessedPage::default(), root, &root.join("Guide.md.new"))
+      .await
+      .unwrap();
+
+    let stale_files = current.stale_files().unwrap();
+    assert!(stale_files.missing_pages.is_empty());
+    assert!(stale_files.paths(&BTreeSet::new()).is_empty());
+    assert_eq!(current.paths(), ["Guide.md", "Guide.md.new"]);
+    let entries = current.entries.lock().unwrap();
+    assert_eq!(entries.pages["1"].path, "Guide.md");
+    assert_eq!(
+      entries.pages["1"].checksum,
+      crate::integrity::sha256_hex(b"# Guide\n\nLocal notes\n")
+    );
+  }
+}
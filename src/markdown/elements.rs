@@ -6,15 +6,16 @@
 use roxmltree::Node;
 use tracing::debug;
 
-use super::MarkdownOptions;
 use super::emoji::{convert_emoji_to_markdown, convert_span_emoji};
 use super::html_entities::decode_html_entities;
 use super::macros::{
   convert_adf_extension_to_markdown, convert_confluence_link_to_markdown, convert_image_to_markdown,
-  convert_macro_to_markdown, convert_task_list_to_markdown, render_admonition_block,
+  convert_macro_to_markdown, convert_task_list_to_markdown, render_admonition, render_page_link_with_text,
 };
 use super::tables::{convert_table_to_markdown, render_markdown_table};
-use super::utils::{get_attribute, get_element_text, matches_tag};
+use super::utils::{escape_text, get_attribute, get_element_text, matches_tag};
+use super::{MarkdownOptions, jira_keys};
+use crate::format::MarkdownFlavor;
 
 /// Checks whether a line appears to start with a Markdown list marker.
 ///
@@ -53,6 +54,141 @@ fn looks_like_list_marker(line: &str) -> bool {
   false
 }
 
+/// Renders a `<dl>` definition list.
+///
+/// Pandoc has native definition lists, with each definition on a `:   ` line
+/// below its term. The other flavors have no definition list syntax, so each
+/// term becomes a bold paragraph followed by its definitions indented below
+/// it.
+fn render_definition_list(element: Node, options: &MarkdownOptions) -> String {
+  let native = options.flavor == Some(MarkdownFlavor::Pandoc);
+  let mut result = String::from("\n");
+  for child in element.children().filter(|node| node.is_element()) {
+    let content = convert_node_to_markdown(child, options);
+    let content = content.trim();
+    if content.is_empty() {
+      continue;
+    }
+    if matches_tag(child, "dt") {
+      if result.len() > 1 {
+        result.push('\n');
+      }
+      if native {
+        result.push_str(content);
+      } else {
+        result.push_str(&options.dialect.strong(content));
+        result.push('\n');
+      }
+      result.push('\n');
+    } else if matches_tag(child, "dd") {
+      if native {
+        result.push_str(&format_list_item(content, ":   "));
+      } else {
+        result.push_str(&format_list_item(content, "  "));
+        result.push('\n');
+      }
+    }
+  }
+  result.push('\n');
+  result
+}
+
+/// Numbering style of an ordered list, from its `type` attribute or its
+/// `list-style-type` style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListNumbering {
+  Decimal,
+  LowerAlpha,
+  UpperAlpha,
+  LowerRoman,
+  UpperRoman,
+}
+
+impl ListNumbering {
+  fn of(element: Node) -> Self {
+    let style = get_attribute(element, "style").and_then(|style| extract_style_property(&style, "list-style-type"));
+    match (get_attribute(element, "type").as_deref(), style.as_deref()) {
+      (Some("a"), _) | (_, Some("lower-alpha" | "lower-latin")) => Self::LowerAlpha,
+      (Some("A"), _) | (_, Some("upper-alpha" | "upper-latin")) => Self::UpperAlpha,
+      (Some("i"), _) | (_, Some("lower-roman")) => Self::LowerRoman,
+      (Some("I"), _) | (_, Some("upper-roman")) => Self::UpperRoman,
+      _ => Self::Decimal,
+    }
+  }
+
+  /// The list item prefix for item `number`, counting from 1.
+  ///
+  /// Pandoc reads a single capital letter followed by one space as an
+  /// initial, so upper-case letter markers are followed by two.
+  fn marker(self, number: usize) -> String {
+    match self {
+      Self::Decimal => format!("{number}. "),
+      Self::LowerAlpha => format!("{}. ", alpha(number)),
+      Self::UpperAlpha => format!("{}.  ", alpha(number).to_ascii_uppercase()),
+      Self::LowerRoman => format!("{}. ", roman(number).to_ascii_lowercase()),
+      Self::UpperRoman => format!("{}. ", roman(number)),
+    }
+  }
+}
+
+/// The letter numbering item `number` of a list, from `a` to `z`.
+fn alpha(number: usize) -> char {
+  char::from(b'a' + (number.clamp(1, 26) - 1) as u8)
+}
+
+/// Roman numeral for `number`.
+fn roman(mut number: usize) -> String {
+  const NUMERALS: &[(usize, &str)] = &[
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+  ];
+  let mut result = String::new();
+  for &(value, numeral) in NUMERALS {
+    while number >= value {
+      result.push_str(numeral);
+      number -= value;
+    }
+  }
+  result
+}
+
+/// Renders the `<li>` children of a list with the prefix `marker` returns
+/// for each item's index.
+///
+/// When an item holds several blocks, such as two paragraphs or a paragraph
+/// and a code block, the items are separated by blank lines so the list is
+/// read as one loose list rather than ending after that item.
+fn render_list(element: Node, options: &MarkdownOptions, marker: impl Fn(usize) -> String) -> String {
+  let items: Vec<String> = element
+    .children()
+    .filter(|n| matches_tag(*n, "li"))
+    .enumerate()
+    .map(|(index, li)| format_list_item(&convert_node_to_markdown(li, options), &marker(index)))
+    .collect();
+  let loose = items.iter().any(|item| has_multiple_blocks(item));
+  format!("\n{}\n", items.join(if loose { "\n" } else { "" }))
+}
+
+/// Whether a rendered list item continues with another block after a blank
+/// line, other than a nested list.
+fn has_multiple_blocks(item: &str) -> bool {
+  let lines: Vec<&str> = item.trim_end().lines().collect();
+  lines
+    .windows(2)
+    .any(|pair| pair[0].trim().is_empty() && !pair[1].trim().is_empty() && !looks_like_list_marker(pair[1]))
+}
+
 /// Formats a converted list item, preserving nested list structure.
 ///
 /// The helper ensures that existing list markers remain untouched while
@@ -161,7 +297,7 @@ fn convert_legacy_admonition_block(node: Node, options: &MarkdownOptions, headin
     .map(|body| convert_node_to_markdown(body, options))
     .unwrap_or_else(|| get_element_text(node));
 
-  render_admonition_block(heading, body.trim())
+  render_admonition(&heading.to_lowercase(), heading, body.trim(), options)
 }
 
 fn convert_layout_section(section: Node, options: &MarkdownOptions) -> String {
@@ -255,6 +391,11 @@ fn convert_layout_to_markdown(layout: Node, options: &MarkdownOptions) -> String
 }
 
 fn render_styled_span(node: Node, options: &MarkdownOptions) -> Option<String> {
+  // JSX requires `style` to be an object, so MDX output drops inline colors
+  if options.mdx_safe {
+    return None;
+  }
+
   let style = collect_span_color_styles(node)?;
 
   let mut content = String::new();
@@ -262,7 +403,7 @@ fn render_styled_span(node: Node, options: &MarkdownOptions) -> Option<String> {
     match child.node_type() {
       roxmltree::NodeType::Text => {
         if let Some(text) = child.text() {
-          content.push_str(&decode_html_entities(text));
+          content.push_str(&render_text(child, text, options));
         }
       }
       roxmltree::NodeType::Element => content.push_str(&convert_element_node(child, options)),
@@ -349,24 +490,14 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
 
   match local_name {
     // Headings
-    "h1" => result.push_str(&format!("\n# {}\n\n", convert_node_to_markdown(child, options).trim())),
-    "h2" => result.push_str(&format!("\n## {}\n\n", convert_node_to_markdown(child, options).trim())),
-    "h3" => result.push_str(&format!(
-      "\n### {}\n\n",
-      convert_node_to_markdown(child, options).trim()
-    )),
-    "h4" => result.push_str(&format!(
-      "\n#### {}\n\n",
-      convert_node_to_markdown(child, options).trim()
-    )),
-    "h5" => result.push_str(&format!(
-      "\n##### {}\n\n",
-      convert_node_to_markdown(child, options).trim()
-    )),
-    "h6" => result.push_str(&format!(
-      "\n###### {}\n\n",
-      convert_node_to_markdown(child, options).trim()
-    )),
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+      let level = usize::from(local_name.as_bytes()[1] - b'0');
+      let text = convert_node_to_markdown(child, options);
+      match options.heading_overflow.apply(level, options.max_heading_level) {
+        Some(level) => result.push_str(&format!("\n{}\n\n", options.dialect.heading(level, text.trim()))),
+        None => result.push_str(&format!("\n{}\n\n", options.dialect.strong(text.trim()))),
+      }
+    }
 
     // Paragraphs
     "p" => {
@@ -378,11 +509,22 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
     }
 
     // Text formatting
-    "strong" | "b" => result.push_str(&format!("**{}**", convert_node_to_markdown(child, options))),
-    "em" | "i" => result.push_str(&format!("_{}_", convert_node_to_markdown(child, options))),
-    "u" => result.push_str(&format!("_{}_", convert_node_to_markdown(child, options))),
+    "strong" | "b" => result.push_str(&options.dialect.strong(&convert_node_to_markdown(child, options))),
+    "em" | "i" | "u" => result.push_str(&options.dialect.emphasis(&convert_node_to_markdown(child, options))),
     "s" | "del" => result.push_str(&format!("~~{}~~", convert_node_to_markdown(child, options))),
-    "code" => result.push_str(&format!("`{}`", convert_node_to_markdown(child, options))),
+    "code" => {
+      // Code spans are literal in MDX too, so they must not be escaped
+      let content = if options.mdx_safe {
+        let literal = MarkdownOptions {
+          mdx_safe: false,
+          ..options.clone()
+        };
+        convert_node_to_markdown(child, &literal)
+      } else {
+        convert_node_to_markdown(child, options)
+      };
+      result.push_str(&format!("`{content}`"));
+    }
     "sub" => {
       let content = convert_node_to_markdown(child, options);
       let trimmed = content.trim();
@@ -409,29 +551,38 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
     }
 
     // Lists
-    "ul" => {
-      result.push('\n');
-      for li in child.children().filter(|n| matches_tag(*n, "li")) {
-        let item = convert_node_to_markdown(li, options);
-        result.push_str(&format_list_item(&item, "- "));
-      }
-      result.push('\n');
-    }
+    "ul" => result.push_str(&render_list(child, options, |_| options.dialect.bullet())),
+    "dl" => result.push_str(&render_definition_list(child, options)),
     "ol" => {
-      result.push('\n');
-      for (index, li) in child.children().filter(|n| matches_tag(*n, "li")).enumerate() {
-        let item = convert_node_to_markdown(li, options);
-        let prefix = format!("{}. ", index + 1);
-        result.push_str(&format_list_item(&item, &prefix));
-      }
-      result.push('\n');
+      let start = get_attribute(child, "start")
+        .and_then(|start| start.trim().parse::<usize>().ok())
+        .unwrap_or(1);
+      let items = child.children().filter(|n| matches_tag(*n, "li")).count();
+      let numbering = match ListNumbering::of(child) {
+        // Only Pandoc has letter and roman numeral lists, and only up to z
+        _ if options.flavor != Some(MarkdownFlavor::Pandoc) => ListNumbering::Decimal,
+        ListNumbering::LowerAlpha | ListNumbering::UpperAlpha if start + items > 27 => ListNumbering::Decimal,
+        numbering => numbering,
+      };
+      result.push_str(&render_list(child, options, |index| numbering.marker(start + index)));
     }
 
     // Links
     "a" => {
       let text = convert_node_to_markdown(child, options);
       let href = get_attribute(child, "href").unwrap_or_default();
-      result.push_str(&format!("[{}]({})", text.trim(), href));
+      // Pasted URLs to the exported instance render like `ri:page` links,
+      // keeping their link text unless it is the URL itself
+      let link_text = Some(text.trim()).filter(|text| !text.is_empty() && *text != href);
+      if let Some(link) = options
+        .links
+        .page_reference_from_url(&href)
+        .and_then(|reference| render_page_link_with_text(&reference, link_text, options))
+      {
+        result.push_str(&link);
+      } else {
+        result.push_str(&format!("[{}]({})", text.trim(), href));
+      }
     }
 
     // Line breaks and horizontal rules
@@ -441,7 +592,8 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
     // Code blocks
     "pre" => {
       let code = get_element_text(child);
-      result.push_str(&format!("\n```\n{}\n```\n\n", code.trim()));
+      let fence = options.dialect.fence(&code);
+      result.push_str(&format!("\n{fence}\n{}\n{fence}\n\n", code.trim()));
     }
 
     // Tables
@@ -449,7 +601,7 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
 
     // Confluence-specific elements
     "link" if matches_tag(child, "ac:link") => {
-      result.push_str(&convert_confluence_link_to_markdown(child));
+      result.push_str(&convert_confluence_link_to_markdown(child, options));
     }
     "note" if matches_tag(child, "ac:note") => {
       result.push_str(&convert_legacy_admonition_block(child, options, "Note"));
@@ -471,15 +623,17 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
       ));
     }
     "task-list" if matches_tag(child, "ac:task-list") => {
-      result.push_str(&convert_task_list_to_markdown(child));
+      result.push_str(&convert_task_list_to_markdown(child, options));
     }
     "image" if matches_tag(child, "ac:image") => {
-      result.push_str(&convert_image_to_markdown(child));
+      result.push_str(&convert_image_to_markdown(child, options));
     }
     "adf-extension" if matches_tag(child, "ac:adf-extension") => {
-      result.push_str(&convert_adf_extension_to_markdown(child, &|node| {
-        convert_node_to_markdown(node, options)
-      }));
+      result.push_str(&convert_adf_extension_to_markdown(
+        child,
+        &|node| convert_node_to_markdown(node, options),
+        options,
+      ));
     }
 
     // Layout elements
@@ -502,7 +656,7 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
     "task-id" if matches_tag(child, "ac:task-id") => {}
     "task-status" if matches_tag(child, "ac:task-status") => {}
     "task-body" if matches_tag(child, "ac:task-body") => {
-      result.push_str(&get_element_text(child));
+      result.push_str(&escape_text(&get_element_text(child), options));
     }
     "placeholder" if matches_tag(child, "ac:placeholder") => {}
 
@@ -518,7 +672,7 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
 
     // Span elements (check for emoji metadata)
     "span" => {
-      if let Some(emoji) = convert_span_emoji(child) {
+      if let Some(emoji) = convert_span_emoji(child, options.emoji_style) {
         result.push_str(&emoji);
       } else if let Some(styled) = render_styled_span(child, options) {
         result.push_str(&styled);
@@ -529,10 +683,10 @@ fn convert_element_node(child: Node, options: &MarkdownOptions) -> String {
 
     // Emoji elements
     "emoji" if matches_tag(child, "ac:emoji") => {
-      result.push_str(&convert_emoji_to_markdown(child));
+      result.push_str(&convert_emoji_to_markdown(child, options.emoji_style));
     }
     "emoticon" if matches_tag(child, "ac:emoticon") => {
-      result.push_str(&convert_emoji_to_markdown(child));
+      result.push_str(&convert_emoji_to_markdown(child, options.emoji_style));
     }
 
     // Unknown elements - extract content
@@ -562,8 +716,7 @@ pub fn convert_node_to_markdown(node: Node, options: &MarkdownOptions) -> String
       roxmltree::NodeType::Element => result.push_str(&convert_element_node(child, options)),
       roxmltree::NodeType::Text => {
         if let Some(text) = child.text() {
-          let decoded = decode_html_entities(text);
-          result.push_str(&decoded);
+          result.push_str(&render_text(child, text, options));
         }
       }
       _ => {}
@@ -573,6 +726,56 @@ pub fn convert_node_to_markdown(node: Node, options: &MarkdownOptions) -> String
   result
 }
 
+/// Converts the content of a table cell into lines of inline Markdown.
+///
+/// Only `<br>` elements and paragraph boundaries start a new line; line breaks
+/// in the source markup are whitespace like any other and are collapsed.
+///
+/// # Arguments
+/// * `cell` - The `<td>` or `<th>` node to convert.
+/// * `options` - Conversion behaviour flags.
+///
+/// # Returns
+/// The non-empty lines of the cell, in order.
+pub(super) fn convert_cell_lines(cell: Node, options: &MarkdownOptions) -> Vec<String> {
+  let mut lines = vec![String::new()];
+  collect_cell_lines(cell, options, &mut lines);
+  lines
+    .into_iter()
+    .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+    .filter(|line| !line.is_empty())
+    .collect()
+}
+
+fn collect_cell_lines(node: Node, options: &MarkdownOptions, lines: &mut Vec<String>) {
+  for child in node.children() {
+    if matches_tag(child, "br") {
+      lines.push(String::new());
+    } else if matches_tag(child, "p") || matches_tag(child, "div") {
+      lines.push(String::new());
+      collect_cell_lines(child, options, lines);
+      lines.push(String::new());
+    } else if let Some(line) = lines.last_mut() {
+      if child.is_element() {
+        line.push_str(&convert_element_node(child, options).replace('\n', " "));
+      } else if let Some(text) = child.text() {
+        line.push_str(&render_text(child, text, options));
+      }
+    }
+  }
+}
+
+/// Decodes and escapes a text node, linking bare Jira keys when enabled.
+fn render_text(node: Node, text: &str, options: &MarkdownOptions) -> String {
+  let decoded = decode_html_entities(text);
+  match &options.jira_links {
+    Some(linker) if !jira_keys::in_literal_context(node) => {
+      linker.link_keys(&decoded, |segment| escape_text(segment, options))
+    }
+    _ => escape_text(&decoded, options),
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -654,6 +857,77 @@ mod tests {
     insta::assert_snapshot!(output, @r"- Item 1\n- Item 2\n\n      \n1. First\n2. Second\n");
   }
 
+  #[test]
+  fn test_convert_ordered_list_start_and_type() {
+    let input = r#"<ol start="3"><li>Third</li><li>Fourth<ol type="a" start="2"><li>Nested</li></ol></li></ol>"#;
+    assert_eq!(convert_to_markdown(input), "3. Third\n4. Fourth\n   2. Nested\n");
+
+    let options = MarkdownOptions {
+      flavor: Some(MarkdownFlavor::Pandoc),
+      ..Default::default()
+    };
+    let input = r#"<ol type="a" start="2"><li>Bravo<ol style="list-style-type: upper-roman;"><li>One</li><li>Two</li></ol></li><li>Charlie</li></ol><ol type="A"><li>Alpha</li></ol>"#;
+    insta::assert_snapshot!(crate::markdown::storage_to_markdown_with_options(input, &options).unwrap(), @r"
+    b. Bravo
+       I. One
+       II. Two
+    c. Charlie
+
+    A.  Alpha
+    ");
+  }
+
+  #[test]
+  fn test_convert_list_items_with_several_blocks() {
+    let input = concat!(
+      r#"<ol><li><p>Install the agent.</p><p>It runs as a service.</p>"#,
+      r#"<ac:structured-macro ac:name="code"><ac:plain-text-body><![CDATA[systemctl start agent"#,
+      "\n\n",
+      r#"systemctl status agent]]></ac:plain-text-body></ac:structured-macro>"#,
+      r#"<ul><li>Check the logs.</li></ul></li><li><p>Configure it.</p></li></ol>"#
+    );
+
+    insta::assert_snapshot!(convert_to_markdown(input), @r"
+    1. Install the agent.
+
+       It runs as a service.
+
+       ```
+       systemctl start agent
+
+       systemctl status agent
+       ```
+
+       - Check the logs.
+
+    2. Configure it.
+    ");
+  }
+
+  #[test]
+  fn test_convert_definition_list() {
+    let input = concat!(
+      "<dl><dt>API</dt><dd>Application programming interface</dd>",
+      "<dt>SDK</dt><dd><p>Software development kit.</p><ul><li>Libraries</li><li>Tools</li></ul></dd>",
+      "<dd>Also a release bundle.</dd></dl>"
+    );
+
+    insta::assert_snapshot!(convert_to_markdown(input), @r"
+    **API**
+
+      Application programming interface
+
+    **SDK**
+
+      Software development kit.
+
+      - Libraries
+      - Tools
+
+      Also a release bundle.
+    ");
+  }
+
   #[test]
   fn test_convert_blockquote_simple() {
     let input = "<blockquote><p>Quote text</p></blockquote>";
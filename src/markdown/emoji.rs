@@ -1,13 +1,90 @@
 //! Emoji conversion utilities for Confluence content.
 //!
 //! Handles conversion of Confluence emoji elements and attributes to Unicode
-//! emoji.
+//! emoji, `:shortcode:` text, or nothing, depending on the [`EmojiStyle`].
 
 use roxmltree::Node;
 use tracing::{debug, trace};
 
+use super::EmojiStyle;
 use super::utils::{get_attribute, get_element_text, get_plain_text};
 
+/// Shortcodes of common emoji whose Confluence markup carries no shortname,
+/// following the names GitHub and Slack use.
+const SHORTCODES: &[(&str, &str)] = &[
+  ("👋", "wave"),
+  ("✅", "white_check_mark"),
+  ("✔", "heavy_check_mark"),
+  ("❌", "x"),
+  ("⚠", "warning"),
+  ("ℹ", "information_source"),
+  ("❗", "exclamation"),
+  ("❓", "question"),
+  ("🛑", "stop_sign"),
+  ("🚧", "construction"),
+  ("👍", "+1"),
+  ("👎", "-1"),
+  ("🙏", "pray"),
+  ("👀", "eyes"),
+  ("👉", "point_right"),
+  ("😀", "grinning"),
+  ("😃", "smiley"),
+  ("😄", "smile"),
+  ("😊", "blush"),
+  ("🙂", "slightly_smiling_face"),
+  ("😉", "wink"),
+  ("😛", "stuck_out_tongue"),
+  ("😢", "cry"),
+  ("🎉", "tada"),
+  ("✨", "sparkles"),
+  ("🚀", "rocket"),
+  ("🔥", "fire"),
+  ("❤", "heart"),
+  ("⭐", "star"),
+  ("💡", "bulb"),
+  ("📌", "pushpin"),
+  ("📝", "memo"),
+  ("📅", "date"),
+  ("🔗", "link"),
+  ("🔒", "lock"),
+  ("🐛", "bug"),
+  ("⏳", "hourglass_flowing_sand"),
+  ("💯", "100"),
+];
+
+/// Render a resolved emoji in the requested style.
+///
+/// # Arguments
+/// * `emoji` - The emoji as resolved for [`EmojiStyle::Unicode`].
+/// * `shortname` - The shortname Confluence stored with the emoji, such as `:wave:`, if any.
+/// * `style` - How emoji are written.
+///
+/// # Returns
+/// The emoji, its `:shortcode:`, or an empty string when emoji are stripped.
+/// Shortcodes fall back to the emoji itself when no name is known.
+pub fn render_emoji(emoji: &str, shortname: Option<&str>, style: EmojiStyle) -> String {
+  match style {
+    EmojiStyle::Unicode => emoji.to_string(),
+    EmojiStyle::Strip => String::new(),
+    EmojiStyle::Shortcode => shortname
+      .map(|name| name.trim().trim_matches(':'))
+      .filter(|name| {
+        !name.is_empty()
+          && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '+' | '-'))
+      })
+      .or_else(|| {
+        let bare = emoji.trim_end_matches('\u{fe0f}');
+        SHORTCODES
+          .iter()
+          .find(|(candidate, _)| *candidate == bare)
+          .map(|(_, name)| *name)
+      })
+      .map_or_else(|| emoji.to_string(), |name| format!(":{name}:")),
+  }
+}
+
 /// Converts an emoji element to Markdown by resolving its codepoint.
 ///
 /// Confluence stores emojis with various attributes:
@@ -18,11 +95,18 @@ use super::utils::{get_attribute, get_element_text, get_plain_text};
 ///
 /// # Arguments
 /// * `element` - The `<ac:emoji>` node to convert.
+/// * `style` - How emoji are written.
 ///
 /// # Returns
-/// The best matching emoji text or an empty string when the element cannot be
-/// resolved.
-pub fn convert_emoji_to_markdown(element: Node) -> String {
+/// The best matching emoji text in the requested style, or an empty string
+/// when the element cannot be resolved.
+pub fn convert_emoji_to_markdown(element: Node, style: EmojiStyle) -> String {
+  let shortname = get_attribute(element, "ac:shortname").or_else(|| get_attribute(element, "ac:emoji-shortname"));
+  render_emoji(&resolve_emoji(element), shortname.as_deref(), style)
+}
+
+/// The Unicode emoji, or the best text stand-in, for an `<ac:emoji>` node.
+fn resolve_emoji(element: Node) -> String {
   let emoji_id = get_attribute(element, "ac:emoji-id");
   let shortcut = get_attribute(element, "ac:shortcut");
   let shortname = get_attribute(element, "ac:shortname").or_else(|| get_attribute(element, "ac:emoji-shortname"));
@@ -64,11 +148,19 @@ pub fn convert_emoji_to_markdown(element: Node) -> String {
 ///
 /// # Arguments
 /// * `element` - The span node that may contain emoji metadata attributes.
+/// * `style` - How emoji are written.
 ///
 /// # Returns
 /// `Some(String)` containing the resolved emoji text, or `None` when no emoji
 /// metadata is present.
-pub fn convert_span_emoji(element: Node) -> Option<String> {
+pub fn convert_span_emoji(element: Node, style: EmojiStyle) -> Option<String> {
+  let shortname = get_attribute(element, "data-emoji-shortname");
+  resolve_span_emoji(element).map(|emoji| render_emoji(&emoji, shortname.as_deref(), style))
+}
+
+/// The Unicode emoji, or the best text stand-in, for a span carrying emoji
+/// metadata.
+fn resolve_span_emoji(element: Node) -> Option<String> {
   let emoji_id = get_attribute(element, "data-emoji-id");
   let emoji_shortname = get_attribute(element, "data-emoji-shortname");
   let emoji_fallback = get_attribute(element, "data-emoji-fallback");
@@ -176,7 +268,7 @@ mod tests {
       .descendants()
       .find(|node| matches_tag(*node, "ac:emoji"))
       .unwrap();
-    let result = convert_emoji_to_markdown(emoji_node);
+    let result = convert_emoji_to_markdown(emoji_node, EmojiStyle::Unicode);
     assert_eq!(result, "👋");
   }
 
@@ -189,7 +281,7 @@ mod tests {
       .descendants()
       .find(|node| matches_tag(*node, "ac:emoji"))
       .unwrap();
-    let result = convert_emoji_to_markdown(emoji_node);
+    let result = convert_emoji_to_markdown(emoji_node, EmojiStyle::Unicode);
     assert_eq!(result, "👩‍💻");
   }
 
@@ -202,10 +294,35 @@ mod tests {
       .descendants()
       .find(|node| matches_tag(*node, "ac:emoji"))
       .unwrap();
-    let result = convert_emoji_to_markdown(emoji_node);
+    let result = convert_emoji_to_markdown(emoji_node, EmojiStyle::Unicode);
     assert_eq!(result, ":)");
   }
 
+  #[test]
+  fn test_convert_confluence_emoji_styles() {
+    let input = r#"<p><ac:emoji ac:emoji-id="2705" ac:shortname=":white_check_mark:" /><ac:emoji ac:emoji-id="1f44b" /><ac:emoji ac:emoji-id="1f9a9" /></p>"#;
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let render = |style| {
+      document
+        .descendants()
+        .filter(|node| matches_tag(*node, "ac:emoji"))
+        .map(|node| convert_emoji_to_markdown(node, style))
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(render(EmojiStyle::Unicode), ["✅", "👋", "🦩"]);
+    assert_eq!(render(EmojiStyle::Shortcode), [":white_check_mark:", ":wave:", "🦩"]);
+    assert_eq!(render(EmojiStyle::Strip), ["", "", ""]);
+  }
+
+  #[test]
+  fn test_render_emoji_shortcode() {
+    assert_eq!(render_emoji("⚠\u{fe0f}", None, EmojiStyle::Shortcode), ":warning:");
+    assert_eq!(render_emoji("🙂", Some("smile"), EmojiStyle::Shortcode), ":smile:");
+    assert_eq!(render_emoji(":)", Some(":)"), EmojiStyle::Shortcode), ":)");
+  }
+
   #[test]
   fn test_emoji_id_to_unicode() {
     assert_eq!(emoji_id_to_unicode("1f44b"), Some("👋".to_string()));
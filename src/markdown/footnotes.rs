@@ -0,0 +1,136 @@
+//! Footnotes collected from the converted page.
+//!
+//! Footnote numbers follow the order of the footnotes in the whole page, and
+//! their text belongs at the end of the page (or where a `display-footnotes`
+//! macro stands), so the `footnote` macro leaves its converted text between
+//! [`placeholder`] markers and [`expand`] numbers the footnotes and gathers
+//! their definitions in a second pass over the Markdown.
+
+use crate::format::MarkdownFlavor;
+
+/// Comment opening the text of a footnote.
+const START: &str = "<!-- confluence-dl:footnote -->";
+/// Comment closing the text of a footnote.
+const END: &str = "<!-- /confluence-dl:footnote -->";
+/// Comment standing in for the list of footnotes.
+pub(crate) const LIST_PLACEHOLDER: &str = "<!-- confluence-dl:footnotes -->";
+
+/// The markers a `footnote` macro is rendered as until [`expand`] runs.
+pub(crate) fn placeholder(text: &str) -> String {
+  format!("{START}{}{END}", text.trim())
+}
+
+/// Replace footnote markers with numbered references and write the
+/// footnotes' text as definitions.
+///
+/// # Arguments
+/// * `markdown` - Converted page content.
+/// * `flavor` - Markdown variant; CommonMark has no footnote syntax, so plain `[1]` references and paragraphs are
+///   written instead.
+///
+/// # Returns
+/// The content with `[^1]` references and their definitions, placed at the
+/// first `display-footnotes` macro or else at the end of the page.
+pub(crate) fn expand(markdown: &str, flavor: Option<MarkdownFlavor>) -> String {
+  if !markdown.contains(START) && !markdown.contains(LIST_PLACEHOLDER) {
+    return markdown.to_string();
+  }
+  let plain = flavor == Some(MarkdownFlavor::CommonMark);
+
+  let mut result = String::with_capacity(markdown.len());
+  let mut footnotes = Vec::new();
+  let mut rest = markdown;
+  while let Some(start) = rest.find(START) {
+    let Some(length) = rest[start + START.len()..].find(END) else {
+      break;
+    };
+    footnotes.push(&rest[start + START.len()..start + START.len() + length]);
+    result.push_str(&rest[..start]);
+    result.push_str(&reference(footnotes.len(), plain));
+    rest = &rest[start + START.len() + length + END.len()..];
+  }
+  result.push_str(rest);
+
+  let definitions = footnotes
+    .iter()
+    .enumerate()
+    .map(|(index, text)| definition(index + 1, text, plain))
+    .collect::<Vec<_>>()
+    .join("\n\n");
+  match result.find(LIST_PLACEHOLDER) {
+    Some(position) => {
+      result.replace_range(position..position + LIST_PLACEHOLDER.len(), &definitions);
+      result.replace(LIST_PLACEHOLDER, "")
+    }
+    None if definitions.is_empty() => result,
+    None => format!("{}\n\n{definitions}\n", result.trim_end()),
+  }
+}
+
+fn reference(number: usize, plain: bool) -> String {
+  if plain {
+    format!("[{number}]")
+  } else {
+    format!("[^{number}]")
+  }
+}
+
+/// A footnote definition; lines after the first are indented so that
+/// multi-paragraph footnotes stay part of the definition.
+fn definition(number: usize, text: &str, plain: bool) -> String {
+  let (label, indent) = if plain {
+    (reference(number, plain), "")
+  } else {
+    (format!("{}:", reference(number, plain)), "    ")
+  };
+  let mut lines = text.lines();
+  let mut result = format!("{label} {}", lines.next().unwrap_or_default());
+  for line in lines {
+    result.push('\n');
+    if !line.is_empty() {
+      result.push_str(indent);
+    }
+    result.push_str(line);
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_expand() {
+    let markdown = format!(
+      "Retention is 90 days{}.\n\nBackups run nightly{}.\n",
+      placeholder("Per the 2024 policy."),
+      placeholder("Except on weekends.\n\nSee the runbook.")
+    );
+
+    insta::assert_snapshot!(expand(&markdown, None), @r"
+    Retention is 90 days[^1].
+
+    Backups run nightly[^2].
+
+    [^1]: Per the 2024 policy.
+
+    [^2]: Except on weekends.
+
+        See the runbook.
+    ");
+  }
+
+  #[test]
+  fn test_expand_at_display_footnotes() {
+    let markdown = format!(
+      "Retention is 90 days{}.\n\n## Notes\n\n{LIST_PLACEHOLDER}\n\n## History\n",
+      placeholder("Per the 2024 policy.")
+    );
+
+    assert_eq!(
+      expand(&markdown, Some(MarkdownFlavor::CommonMark)),
+      "Retention is 90 days[1].\n\n## Notes\n\n[1] Per the 2024 policy.\n\n## History\n"
+    );
+    assert_eq!(expand(LIST_PLACEHOLDER, None), "");
+  }
+}
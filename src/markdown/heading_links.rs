@@ -0,0 +1,142 @@
+//! Same-page links pointed at the anchors Markdown renderers give headings.
+//!
+//! Confluence links to a heading by its text (`ac:link ac:anchor="Setup"`) or
+//! by the ID Confluence generated for it, such as `#Guide-GettingStarted`,
+//! neither of which exists once the page is rendered from Markdown. Renderers
+//! such as GitHub derive heading anchors from the heading text instead, so
+//! [`rewrite`] matches every same-page link to a heading of the converted page
+//! and replaces its fragment with that heading's slug.
+
+use std::collections::HashSet;
+
+use crate::format::HeadingStyle;
+use crate::markdown::toc::{Heading, collect_headings, heading_slug};
+
+/// Point same-page anchor links at the headings they refer to.
+///
+/// # Arguments
+/// * `markdown` - Converted page content.
+/// * `style` - Syntax the headings were written in.
+///
+/// # Returns
+/// The content with rewritten links. Links to an anchor kept with
+/// `--preserve-anchors`, links already using a heading's slug, and links
+/// matching no heading are left as they are.
+pub(crate) fn rewrite(markdown: &str, style: HeadingStyle) -> String {
+  if !markdown.contains("](#") {
+    return markdown.to_string();
+  }
+  let headings = collect_headings(markdown, style);
+  let ids = anchor_ids(markdown);
+  let slugs: HashSet<&str> = headings.iter().map(|heading| heading.slug.as_str()).collect();
+
+  let mut result = String::with_capacity(markdown.len());
+  let mut fence: Option<String> = None;
+  for line in markdown.split_inclusive('\n') {
+    let trimmed = line.trim_start();
+    let marker: String = trimmed.chars().take_while(|c| matches!(c, '`' | '~')).collect();
+    match &fence {
+      Some(open) if trimmed.starts_with(open.as_str()) => fence = None,
+      Some(_) => {}
+      None if marker.len() >= 3 => fence = Some(marker),
+      None => {
+        result.push_str(&rewrite_line(line, |target| {
+          if ids.contains(target) || slugs.contains(target) {
+            return None;
+          }
+          find_heading(&headings, target).map(|heading| heading.slug.clone())
+        }));
+        continue;
+      }
+    }
+    result.push_str(line);
+  }
+  result
+}
+
+/// Replace the fragment of every `](#fragment)` link on a line for which
+/// `resolve` returns a new one.
+fn rewrite_line(line: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+  let mut result = String::with_capacity(line.len());
+  let mut rest = line;
+  while let Some(start) = rest.find("](#") {
+    let fragment_start = start + 3;
+    let Some(length) = rest[fragment_start..].find(')') else {
+      break;
+    };
+    let target = &rest[fragment_start..fragment_start + length];
+    result.push_str(&rest[..fragment_start]);
+    result.push_str(&resolve(target).unwrap_or_else(|| target.to_string()));
+    rest = &rest[fragment_start + length..];
+  }
+  result.push_str(rest);
+  result
+}
+
+/// The heading a Confluence anchor refers to, by its text or by the
+/// `PageTitle-HeadingText` ID Confluence generates.
+fn find_heading<'a>(headings: &'a [Heading], target: &str) -> Option<&'a Heading> {
+  let target = target.replace("%20", " ");
+  let slug = heading_slug(&target);
+  let compact_target = compact(&target);
+  let without_prefix = target.split_once('-').map(|(_, rest)| compact(rest));
+  headings
+    .iter()
+    .find(|heading| heading.slug == slug)
+    .or_else(|| headings.iter().find(|heading| compact(&heading.text) == compact_target))
+    .or_else(|| {
+      let without_prefix = without_prefix.filter(|rest| !rest.is_empty())?;
+      headings.iter().find(|heading| compact(&heading.text) == without_prefix)
+    })
+}
+
+/// Lowercase letters and digits of a heading or anchor, ignoring spaces and
+/// punctuation the way Confluence drops them from heading IDs.
+fn compact(text: &str) -> String {
+  text
+    .chars()
+    .filter(|c| c.is_alphanumeric())
+    .flat_map(char::to_lowercase)
+    .collect()
+}
+
+/// IDs of the HTML anchors kept in the content.
+fn anchor_ids(markdown: &str) -> HashSet<&str> {
+  markdown
+    .match_indices("<a id=\"")
+    .filter_map(|(start, prefix)| {
+      let rest = &markdown[start + prefix.len()..];
+      rest.find('"').map(|end| &rest[..end])
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rewrite() {
+    let markdown = concat!(
+      "See [setup](#Setup%20and%20Install), [usage](#Guide-UsageNotes), [kept](#keep-me), ",
+      "[done](#setup-and-install) and [missing](#Nowhere).\n\n",
+      "<a id=\"keep-me\"></a>\n\n",
+      "```\n[code](#Setup)\n```\n\n",
+      "## Setup and Install\n\n## Usage *notes*\n",
+    );
+
+    insta::assert_snapshot!(rewrite(markdown, HeadingStyle::Atx), @r#"
+    See [setup](#setup-and-install), [usage](#usage-notes), [kept](#keep-me), [done](#setup-and-install) and [missing](#Nowhere).
+
+    <a id="keep-me"></a>
+
+    ```
+    [code](#Setup)
+    ```
+
+    ## Setup and Install
+
+    ## Usage *notes*
+    "#);
+  }
+}
@@ -0,0 +1,156 @@
+//! Auto-linking of bare Jira issue keys in prose.
+//!
+//! Pages often mention issues as plain text (`See ABC-123`) instead of using
+//! the Jira macro. When a Jira base URL is configured, such keys are turned
+//! into links to `<base>/browse/<KEY>`. Text inside links and code is left
+//! alone, and an optional project allowlist keeps look-alikes such as
+//! `UTF-8` or `SHA-256` from being linked.
+
+use roxmltree::Node;
+
+use super::utils::matches_tag;
+
+/// Elements whose text must never be rewritten into links.
+const LITERAL_TAGS: &[&str] = &[
+  "a",
+  "code",
+  "pre",
+  "ac:link",
+  "ac:plain-text-body",
+  "ac:plain-text-link-body",
+  "ac:link-body",
+];
+
+/// Settings for linking bare Jira keys.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct JiraLinker {
+  /// Jira site URL without a trailing slash, e.g. `https://example.atlassian.net`.
+  pub base_url: String,
+  /// Project keys to link (all keys when empty).
+  pub projects: Vec<String>,
+}
+
+impl JiraLinker {
+  /// Create a linker for a Jira site.
+  ///
+  /// # Arguments
+  /// * `base_url` - Jira site URL; a trailing slash is ignored.
+  /// * `projects` - Project keys to link, matched case-insensitively; empty links every key.
+  pub fn new(base_url: &str, projects: Vec<String>) -> Self {
+    Self {
+      base_url: base_url.trim_end_matches('/').to_string(),
+      projects,
+    }
+  }
+
+  /// Replace issue keys in a text node with Markdown links.
+  ///
+  /// # Arguments
+  /// * `text` - Decoded text of the node.
+  /// * `escape` - Escaping applied to the text around the keys.
+  ///
+  /// # Returns
+  /// The escaped text with each recognized key rendered as `[KEY](url)`.
+  pub fn link_keys(&self, text: &str, escape: impl Fn(&str) -> String) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for (start, end) in self.find_keys(text) {
+      result.push_str(&escape(&text[last..start]));
+      let key = &text[start..end];
+      result.push_str(&format!("[{key}]({}/browse/{key})", self.base_url));
+      last = end;
+    }
+    result.push_str(&escape(&text[last..]));
+    result
+  }
+
+  /// Byte ranges of the issue keys in `text`.
+  fn find_keys(&self, text: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut keys = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+      let at_boundary = index == 0 || !is_key_char(bytes[index - 1]);
+      if !at_boundary || !bytes[index].is_ascii_uppercase() {
+        index += 1;
+        continue;
+      }
+
+      let project_end = index
+        + bytes[index..]
+          .iter()
+          .take_while(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || **b == b'_')
+          .count();
+      let digits = bytes[project_end..]
+        .get(1..)
+        .map_or(0, |rest| rest.iter().take_while(|b| b.is_ascii_digit()).count());
+      let end = project_end + 1 + digits;
+
+      let is_key = project_end - index >= 2
+        && bytes.get(project_end) == Some(&b'-')
+        && digits > 0
+        && bytes.get(end).is_none_or(|b| !is_key_char(*b));
+      if is_key && self.allows(&text[index..project_end]) {
+        keys.push((index, end));
+        index = end;
+      } else {
+        index = project_end.max(index + 1);
+      }
+    }
+    keys
+  }
+
+  fn allows(&self, project: &str) -> bool {
+    self.projects.is_empty()
+      || self
+        .projects
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(project))
+  }
+}
+
+/// Whether a text node sits inside a link or code, where keys stay as-is.
+pub fn in_literal_context(node: Node) -> bool {
+  node
+    .ancestors()
+    .any(|ancestor| LITERAL_TAGS.iter().any(|tag| matches_tag(ancestor, tag)))
+}
+
+fn is_key_char(byte: u8) -> bool {
+  byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'-'
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn link(linker: &JiraLinker, text: &str) -> String {
+    linker.link_keys(text, str::to_string)
+  }
+
+  #[test]
+  fn test_link_keys_in_text() {
+    let linker = JiraLinker::new("https://jira.example.com/", Vec::new());
+
+    assert_eq!(
+      link(&linker, "Fixed in ABC-123, see OPS2-7."),
+      "Fixed in [ABC-123](https://jira.example.com/browse/ABC-123), see [OPS2-7](https://jira.example.com/browse/OPS2-7)."
+    );
+    assert_eq!(
+      link(&linker, "abc-123 A-1 ABC-12x xABC-1 ABC-"),
+      "abc-123 A-1 ABC-12x xABC-1 ABC-"
+    );
+  }
+
+  #[test]
+  fn test_link_keys_respects_project_allowlist() {
+    let linker = JiraLinker::new("https://jira.example.com", vec!["abc".to_string()]);
+
+    assert_eq!(
+      link(&linker, "ABC-1 uses UTF-8"),
+      "[ABC-1](https://jira.example.com/browse/ABC-1) uses UTF-8"
+    );
+  }
+}
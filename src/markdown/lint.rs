@@ -0,0 +1,286 @@
+//! Optional markdownlint-style autofix pass.
+//!
+//! The converter emits Markdown that renders correctly but does not always
+//! satisfy the stricter markdownlint defaults many documentation repositories
+//! enforce in CI. [`autofix`] rewrites the converted output so it passes the
+//! common whitespace and structure rules:
+//!
+//! - MD009: no trailing spaces
+//! - MD012: no multiple consecutive blank lines
+//! - MD022 / MD031 / MD032 / MD058: blank lines around headings, fenced code blocks, lists, and tables
+//! - MD007 / MD005: list items indented consistently by nesting level
+//! - MD046: stray indentation that would turn text into an indented code block is removed
+//! - MD047: the file ends with a single newline
+//!
+//! Content inside fenced code blocks is never modified beyond shifting it
+//! along with the list item it belongs to.
+
+/// The kind of the most recently emitted line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+  Blank,
+  Heading,
+  Fence,
+  Table,
+  Quote,
+  List,
+  Text,
+}
+
+impl LineKind {
+  /// Whether a block of this kind must be separated from different
+  /// neighbouring blocks by a blank line.
+  fn is_block(self) -> bool {
+    matches!(
+      self,
+      LineKind::Heading | LineKind::Fence | LineKind::Table | LineKind::Quote | LineKind::List
+    )
+  }
+}
+
+/// A list item currently open while walking the document.
+#[derive(Debug, Clone, Copy)]
+struct ListLevel {
+  /// Indentation of the item marker in the input.
+  indent: usize,
+  /// Indentation of the item's content in the output.
+  content_indent: usize,
+  /// Shift applied to the item and its continuation lines.
+  delta: isize,
+}
+
+/// An open fenced code block.
+#[derive(Debug, Clone, Copy)]
+struct Fence {
+  marker: char,
+  length: usize,
+  delta: isize,
+}
+
+/// Apply markdownlint-style fixes to converted Markdown.
+///
+/// # Arguments
+/// * `markdown` - Markdown produced by the converter.
+///
+/// # Returns
+/// The fixed Markdown, ending with exactly one newline (or empty when the
+/// input has no content).
+pub fn autofix(markdown: &str) -> String {
+  let mut out: Vec<String> = Vec::new();
+  let mut prev = LineKind::Blank;
+  let mut lists: Vec<ListLevel> = Vec::new();
+  let mut fence: Option<Fence> = None;
+
+  for raw in markdown.lines() {
+    if let Some(open) = fence {
+      let line = if open.delta == 0 {
+        raw.to_string()
+      } else {
+        shift(raw, open.delta)
+      };
+      if closes_fence(line.trim(), open) {
+        fence = None;
+        out.push(line.trim_end().to_string());
+      } else {
+        out.push(line);
+      }
+      continue;
+    }
+
+    let line = raw.trim_end();
+    if line.is_empty() {
+      if prev != LineKind::Blank {
+        out.push(String::new());
+      }
+      prev = LineKind::Blank;
+      continue;
+    }
+
+    let indent = indentation(line);
+    let content = line.trim_start();
+
+    // Lines indented no deeper than an open item end that item
+    while lists.last().is_some_and(|level| indent <= level.indent) {
+      lists.pop();
+    }
+
+    let (kind, text, delta) = if let Some(width) = list_marker_width(content) {
+      let new_indent = lists.last().map_or(0, |level| level.content_indent);
+      let delta = new_indent as isize - indent as isize;
+      lists.push(ListLevel {
+        indent,
+        content_indent: new_indent + width,
+        delta,
+      });
+      (LineKind::List, format!("{}{content}", " ".repeat(new_indent)), delta)
+    } else if let Some(level) = lists.last() {
+      // Continuation of the innermost open item
+      (LineKind::List, shift(line, level.delta), level.delta)
+    } else {
+      (classify(content), content.to_string(), -(indent as isize))
+    };
+
+    let mut separate = prev != LineKind::Blank
+      && (kind != prev || matches!(kind, LineKind::Heading | LineKind::Fence))
+      && (kind.is_block() || prev.is_block());
+
+    let kind = if let Some((marker, length)) = fence_marker(content) {
+      fence = Some(Fence { marker, length, delta });
+      separate = prev != LineKind::Blank;
+      LineKind::Fence
+    } else if kind == LineKind::Text && prev == LineKind::Text && is_setext_underline(content) {
+      // The previous line is the heading text; separate it from what precedes it
+      let text_index = out.len() - 1;
+      if text_index > 0 && !out[text_index - 1].is_empty() {
+        out.insert(text_index, String::new());
+      }
+      separate = false;
+      LineKind::Heading
+    } else {
+      kind
+    };
+
+    if separate {
+      out.push(String::new());
+    }
+    out.push(text);
+    prev = kind;
+  }
+
+  while out.last().is_some_and(|line| line.is_empty()) {
+    out.pop();
+  }
+  let start = out.iter().position(|line| !line.is_empty()).unwrap_or(out.len());
+  let mut result = out[start..].join("\n");
+  if !result.is_empty() {
+    result.push('\n');
+  }
+  result
+}
+
+/// Classify a line outside lists and fenced code.
+fn classify(content: &str) -> LineKind {
+  if is_atx_heading(content) {
+    LineKind::Heading
+  } else if content.starts_with('|') {
+    LineKind::Table
+  } else if content.starts_with('>') {
+    LineKind::Quote
+  } else {
+    LineKind::Text
+  }
+}
+
+/// Width of the list marker and following space when `content` is a list item.
+fn list_marker_width(content: &str) -> Option<usize> {
+  if is_thematic_break(content) {
+    return None;
+  }
+
+  let bytes = content.as_bytes();
+  if matches!(bytes.first(), Some(b'-' | b'*' | b'+')) && matches!(bytes.get(1), Some(b' ')) {
+    return Some(2);
+  }
+
+  let digits = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+  if (1..=9).contains(&digits)
+    && matches!(bytes.get(digits), Some(b'.' | b')'))
+    && matches!(bytes.get(digits + 1), Some(b' '))
+  {
+    return Some(digits + 2);
+  }
+  None
+}
+
+fn is_atx_heading(content: &str) -> bool {
+  let hashes = content.chars().take_while(|&c| c == '#').count();
+  (1..=6).contains(&hashes) && content[hashes..].chars().next().is_none_or(|c| c == ' ')
+}
+
+fn is_thematic_break(content: &str) -> bool {
+  let mut chars = content.chars().filter(|c| !c.is_whitespace());
+  let Some(first) = chars.next() else {
+    return false;
+  };
+  matches!(first, '-' | '*' | '_') && chars.clone().all(|c| c == first) && chars.count() >= 2
+}
+
+fn is_setext_underline(content: &str) -> bool {
+  let first = content.chars().next();
+  matches!(first, Some('=' | '-')) && content.chars().all(|c| Some(c) == first)
+}
+
+/// The fence character and run length when `content` opens a fenced code block.
+fn fence_marker(content: &str) -> Option<(char, usize)> {
+  let marker = content.chars().next().filter(|&c| c == '`' || c == '~')?;
+  let length = content.chars().take_while(|&c| c == marker).count();
+  (length >= 3).then_some((marker, length))
+}
+
+fn closes_fence(content: &str, fence: Fence) -> bool {
+  let length = content.chars().take_while(|&c| c == fence.marker).count();
+  length >= fence.length && content[length * fence.marker.len_utf8()..].trim().is_empty()
+}
+
+/// Count leading whitespace, expanding tabs to four columns.
+fn indentation(line: &str) -> usize {
+  line
+    .chars()
+    .take_while(|c| c.is_whitespace())
+    .map(|c| if c == '\t' { 4 } else { 1 })
+    .sum()
+}
+
+/// Re-indent a line by `delta` columns, never below zero.
+fn shift(line: &str, delta: isize) -> String {
+  let indent = indentation(line);
+  let content = line.trim_start();
+  let new_indent = (indent as isize + delta).max(0) as usize;
+  if content.is_empty() {
+    String::new()
+  } else {
+    format!("{}{content}", " ".repeat(new_indent))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_autofix_spacing_and_indentation() {
+    let input = "# Title\nIntro   \n\n\n\n      Stray indent\n- One\n    - Nested\n- Two\nAfter list\n| a |\n|---|\nText\n```\n  keep\n```\nEnd";
+
+    insta::assert_snapshot!(autofix(input), @r"
+    # Title
+
+    Intro
+
+    Stray indent
+
+    - One
+      - Nested
+    - Two
+
+    After list
+
+    | a |
+    |---|
+
+    Text
+
+    ```
+      keep
+    ```
+
+    End
+    ");
+  }
+
+  #[test]
+  fn test_autofix_setext_and_stray_list_indent() {
+    let input = "Before\nGuide\n=====\n      * One\n      * Two\n\n\n";
+
+    assert_eq!(autofix(input), "Before\n\nGuide\n=====\n\n* One\n* Two\n");
+  }
+}
@@ -0,0 +1,124 @@
+//! Renderers for ADF nodes embedded in `<ac:adf-extension>` blocks.
+//!
+//! Each renderer mirrors the output of the equivalent storage-format element
+//! or macro so content looks the same whether the editor saved it as a macro
+//! or as an ADF node.
+
+use roxmltree::Node;
+
+use super::decisions::{attribute_lookup, collect_adf_attributes};
+use super::{basic, expand};
+use crate::markdown::MarkdownOptions;
+use crate::markdown::tables::render_markdown_table;
+use crate::markdown::utils::{escape_text, get_attribute, get_element_text, matches_tag};
+
+/// Converts an ADF `expand` or `nested-expand` node into a `<details>` block.
+///
+/// # Arguments
+/// * `node` - The `<ac:adf-node type="expand">` element.
+/// * `convert_node` - Callback used to render the expand body into Markdown.
+///
+/// # Returns
+/// The rendered block, or `None` when the node has no content.
+pub(super) fn convert_adf_expand(node: Node, convert_node: &dyn Fn(Node) -> String) -> Option<String> {
+  let body = render_content(node, convert_node)?;
+  let title = attribute_lookup(&collect_adf_attributes(node), &["title"]).unwrap_or_else(|| "Details".to_string());
+
+  Some(expand::render_details(&title, &body))
+}
+
+/// Converts an ADF `status` node into the same badge as the status macro.
+///
+/// # Arguments
+/// * `node` - The `<ac:adf-node type="status">` element.
+///
+/// # Returns
+/// An inline code badge such as `` `[In Progress]` ``, or `None` without text.
+pub(super) fn convert_adf_status(node: Node) -> Option<String> {
+  let text = attribute_lookup(&collect_adf_attributes(node), &["text", "title"])?;
+  Some(basic::format_status(&text))
+}
+
+/// Converts an ADF `task-list` node into a Markdown checklist.
+///
+/// # Arguments
+/// * `node` - The `<ac:adf-node type="task-list">` element.
+/// * `convert_node` - Callback used to render each task's content.
+/// * `options` - Markdown rendering options providing the list marker.
+///
+/// # Returns
+/// One `[x]` or `[ ]` item per task, or `None` when the list has no tasks.
+pub(super) fn convert_adf_task_list(
+  node: Node,
+  convert_node: &dyn Fn(Node) -> String,
+  options: &MarkdownOptions,
+) -> Option<String> {
+  let mut result = String::new();
+
+  for task in adf_children(node, &["task-item"]) {
+    let state = attribute_lookup(&collect_adf_attributes(task), &["state", "status"]).unwrap_or_default();
+    let checkbox = options.task_checkbox(state.eq_ignore_ascii_case("done") || state.eq_ignore_ascii_case("complete"));
+    let body = render_content(task, convert_node).unwrap_or_default();
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    result.push_str(&format!("{}{} {}\n", options.dialect.bullet(), checkbox, body));
+  }
+
+  if result.is_empty() {
+    return None;
+  }
+
+  result.push('\n');
+  Some(result)
+}
+
+/// Converts an ADF `table` node into a Markdown table.
+///
+/// Rows are `table-row` nodes holding `table-header` or `table-cell` nodes;
+/// the first row becomes the Markdown header, as for storage-format tables.
+///
+/// # Arguments
+/// * `node` - The `<ac:adf-node type="table">` element.
+/// * `options` - Markdown rendering options controlling table layout.
+///
+/// # Returns
+/// The rendered table, or `None` when the node has no rows.
+pub(super) fn convert_adf_table(node: Node, options: &MarkdownOptions) -> Option<String> {
+  let rows: Vec<Vec<String>> = adf_children(node, &["table-row"])
+    .map(|row| {
+      adf_children(row, &["table-header", "table-cell"])
+        .map(|cell| {
+          let text = get_element_text(cell).split_whitespace().collect::<Vec<_>>().join(" ");
+          escape_text(&text, options)
+        })
+        .collect::<Vec<_>>()
+    })
+    .filter(|cells| !cells.is_empty())
+    .collect();
+
+  render_markdown_table(rows, options.compact_tables)
+}
+
+/// Iterates over the direct `ac:adf-node` children whose type is one of `types`.
+fn adf_children<'a, 'input>(node: Node<'a, 'input>, types: &[&str]) -> impl Iterator<Item = Node<'a, 'input>> {
+  let types: Vec<String> = types.iter().map(|kind| kind.to_string()).collect();
+  node.children().filter(move |child| {
+    matches_tag(*child, "ac:adf-node") && get_attribute(*child, "type").is_some_and(|kind| types.contains(&kind))
+  })
+}
+
+/// Converts the node's `ac:adf-content` children, returning `None` when empty.
+fn render_content(node: Node, convert_node: &dyn Fn(Node) -> String) -> Option<String> {
+  let segments: Vec<String> = node
+    .children()
+    .filter(|child| matches_tag(*child, "ac:adf-content") || matches_tag(*child, "ac:adf-node"))
+    .map(convert_node)
+    .map(|rendered| rendered.trim().to_string())
+    .filter(|rendered| !rendered.is_empty())
+    .collect();
+
+  if segments.is_empty() {
+    None
+  } else {
+    Some(segments.join("\n\n"))
+  }
+}
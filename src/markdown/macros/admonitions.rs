@@ -1,24 +1,24 @@
 use roxmltree::Node;
 
-use crate::markdown::MarkdownOptions;
 use crate::markdown::utils::{find_child_by_tag, find_child_by_tag_and_attr, get_element_text};
+use crate::markdown::{AdmonitionStyle, MarkdownDialect, MarkdownOptions};
 
 /// Converts Confluence admonition macros (note, info, warning, tip) into
-/// Markdown blockquotes.
+/// Markdown admonitions.
 ///
 /// # Arguments
 /// * `macro_name` - The macro name that determines the default heading label.
 /// * `element` - The `<ac:structured-macro>` node describing the admonition.
 /// * `convert_node` - Callback used to render the rich text body into Markdown.
-/// * `_options` - Conversion flags (currently unused; kept for signature parity).
+/// * `options` - Conversion flags selecting the admonition syntax.
 ///
 /// # Returns
-/// Markdown blockquote for the admonition with an emphasized heading.
+/// The admonition rendered in the configured [`AdmonitionStyle`].
 pub(super) fn handle_macro(
   macro_name: &str,
   element: Node,
   convert_node: &dyn Fn(Node) -> String,
-  _options: &MarkdownOptions,
+  options: &MarkdownOptions,
 ) -> Option<String> {
   let title = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "title")
     .map(get_element_text)
@@ -29,7 +29,7 @@ pub(super) fn handle_macro(
     .unwrap_or_else(|| get_element_text(element));
 
   let heading = resolve_heading(macro_name, title.trim());
-  Some(render_admonition_block(&heading, body.trim()))
+  Some(render_admonition(macro_name, &heading, body.trim(), options))
 }
 
 fn resolve_heading(macro_name: &str, explicit_title: &str) -> String {
@@ -47,26 +47,155 @@ fn resolve_heading(macro_name: &str, explicit_title: &str) -> String {
   }
 }
 
+/// Renders an admonition in the syntax selected by
+/// [`MarkdownOptions::admonition_style`].
+///
+/// # Arguments
+/// * `kind` - Admonition type such as `note`, `info`, `tip`, `warning`, or an ADF panel type like `success` or `error`.
+/// * `heading` - Title to display; Docusaurus output omits it when it merely repeats the type.
+/// * `body` - Markdown body contents.
+/// * `options` - Conversion flags selecting the admonition syntax.
+///
+/// # Returns
+/// The rendered admonition surrounded by blank lines.
+pub(crate) fn render_admonition(kind: &str, heading: &str, body: &str, options: &MarkdownOptions) -> String {
+  match options.admonition_style {
+    AdmonitionStyle::Blockquote => render_admonition_block(heading, body, &options.dialect),
+    AdmonitionStyle::Docusaurus => render_docusaurus_admonition(kind, heading, body),
+    AdmonitionStyle::GitHub => render_github_alert(kind, heading, body),
+    AdmonitionStyle::Obsidian => render_obsidian_callout(kind, heading, body),
+    AdmonitionStyle::PandocDiv => render_pandoc_div(kind, heading, body),
+  }
+}
+
+/// Whether a heading adds nothing over the admonition type.
+fn is_default_heading(kind: &str, heading: &str) -> bool {
+  heading.is_empty() || heading.eq_ignore_ascii_case(kind)
+}
+
+/// Prefix every line of `body` with `> `, leaving blank lines as `>`.
+fn quote_lines(body: &str) -> String {
+  body
+    .lines()
+    .map(|line| match line.trim() {
+      "" => "\n>".to_string(),
+      line => format!("\n> {line}"),
+    })
+    .collect()
+}
+
+/// GitHub alerts have five fixed types and no custom titles, so a custom
+/// heading becomes the first line of the alert.
+fn render_github_alert(kind: &str, heading: &str, body: &str) -> String {
+  let alert = match kind {
+    "tip" | "success" => "TIP",
+    "info" => "IMPORTANT",
+    "warning" => "WARNING",
+    "error" | "danger" => "CAUTION",
+    _ => "NOTE",
+  };
+
+  let mut result = format!("\n> [!{alert}]");
+  if !is_default_heading(kind, heading) {
+    result.push_str(&format!("\n> **{heading}**"));
+    if !body.trim().is_empty() {
+      result.push_str("\n>");
+    }
+  }
+  result.push_str(&quote_lines(body.trim()));
+  result.push_str("\n\n");
+  result
+}
+
+fn render_obsidian_callout(kind: &str, heading: &str, body: &str) -> String {
+  let callout = match kind {
+    "info" | "tip" | "success" | "warning" | "danger" => kind,
+    "error" => "danger",
+    _ => "note",
+  };
+
+  let mut result = format!("\n> [!{callout}]");
+  if !is_default_heading(kind, heading) {
+    result.push(' ');
+    result.push_str(heading);
+  }
+  result.push_str(&quote_lines(body.trim()));
+  result.push_str("\n\n");
+  result
+}
+
+fn render_pandoc_div(kind: &str, heading: &str, body: &str) -> String {
+  let class = match kind {
+    "info" | "tip" | "warning" => kind,
+    "success" => "tip",
+    "error" | "danger" => "caution",
+    _ => "note",
+  };
+
+  let mut result = if is_default_heading(kind, heading) {
+    format!("\n::: {class}\n")
+  } else {
+    format!("\n::: {{.{class} title=\"{}\"}}\n", heading.replace('"', "\\\""))
+  };
+
+  let body = body.trim();
+  if !body.is_empty() {
+    result.push_str(body);
+    result.push('\n');
+  }
+
+  result.push_str(":::\n\n");
+  result
+}
+
+fn render_docusaurus_admonition(kind: &str, heading: &str, body: &str) -> String {
+  let directive = match kind {
+    "info" => "info",
+    "tip" | "success" => "tip",
+    "warning" => "warning",
+    "error" | "danger" => "danger",
+    _ => "note",
+  };
+
+  let mut result = format!("\n:::{directive}");
+  if !heading.is_empty() && !heading.eq_ignore_ascii_case(kind) {
+    result.push(' ');
+    result.push_str(heading);
+  }
+  result.push_str("\n\n");
+
+  let body = body.trim();
+  if !body.is_empty() {
+    result.push_str(body);
+    result.push_str("\n\n");
+  }
+
+  result.push_str(":::\n\n");
+  result
+}
+
 /// Formats the Markdown blockquote for an admonition macro.
 ///
 /// # Arguments
 /// * `heading` - Title to display for the admonition.
 /// * `body` - Markdown body contents, expected to be trimmed and possibly multiline.
+/// * `dialect` - Markdown syntax choices for the strong emphasis marker.
 ///
 /// # Returns
 /// Markdown blockquote containing the heading and body lines.
-pub(crate) fn render_admonition_block(heading: &str, body: &str) -> String {
+pub(crate) fn render_admonition_block(heading: &str, body: &str, dialect: &MarkdownDialect) -> String {
   let body = body.trim();
+  let heading = dialect.strong(&format!("{heading}:"));
 
   if body.is_empty() {
-    return format!("\n> **{heading}:**\n\n");
+    return format!("\n> {heading}\n\n");
   }
 
   let mut result = String::new();
   let mut lines = body.lines();
 
   if let Some(first_line) = lines.next() {
-    result.push_str(&format!("\n> **{heading}:** {}", first_line.trim()));
+    result.push_str(&format!("\n> {heading} {}", first_line.trim()));
   }
 
   for line in lines {
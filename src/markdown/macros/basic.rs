@@ -1,6 +1,8 @@
 use roxmltree::Node;
 
+use crate::format::MarkdownFlavor;
 use crate::markdown::MarkdownOptions;
+use crate::markdown::toc::{self, TocSettings};
 use crate::markdown::utils::{find_child_by_tag, find_child_by_tag_and_attr, get_element_text};
 
 /// Handles basic Confluence macros such as table of contents, panels, and
@@ -18,16 +20,39 @@ pub(super) fn handle_macro(
   macro_name: &str,
   element: Node,
   convert_node: &dyn Fn(Node) -> String,
-  _options: &MarkdownOptions,
+  options: &MarkdownOptions,
 ) -> Option<String> {
   match macro_name {
-    "toc" => Some("\n**Table of Contents**\n\n".to_string()),
+    "toc" if options.generate_toc => Some(format!("\n{}\n\n", toc::placeholder(&toc_settings(element)))),
+    "toc" => Some(format!("\n{}\n\n", options.dialect.strong("Table of Contents"))),
+    "panel" if options.flavor == Some(MarkdownFlavor::Pandoc) => Some(render_panel_div(element, convert_node)),
     "panel" => Some(render_panel(element, convert_node)),
     "status" => Some(render_status(element)),
     _ => None,
   }
 }
 
+/// Reads the level range and layout of a `toc` macro.
+fn toc_settings(element: Node) -> TocSettings {
+  let param = |name| {
+    find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", name)
+      .map(|param| get_element_text(param).trim().to_string())
+      .filter(|value| !value.is_empty())
+  };
+  let defaults = TocSettings::default();
+  let level = |name, default: usize| {
+    param(name)
+      .and_then(|value| value.parse::<usize>().ok())
+      .map_or(default, |level| level.clamp(1, 6))
+  };
+  TocSettings {
+    min_level: level("minLevel", defaults.min_level),
+    max_level: level("maxLevel", defaults.max_level),
+    flat: param("type").is_some_and(|value| value.eq_ignore_ascii_case("flat")),
+    separator: param("separator").unwrap_or(defaults.separator),
+  }
+}
+
 /// Renders a Confluence panel macro into a Markdown blockquote-style section.
 ///
 /// # Arguments
@@ -43,6 +68,29 @@ fn render_panel(element: Node, convert_node: &dyn Fn(Node) -> String) -> String
   format!("\n> {}\n\n", body.trim())
 }
 
+/// Renders a Confluence panel macro as a Pandoc fenced div of class `panel`,
+/// keeping the panel title as a `title` attribute.
+fn render_panel_div(element: Node, convert_node: &dyn Fn(Node) -> String) -> String {
+  let body = find_child_by_tag(element, "ac:rich-text-body")
+    .map(convert_node)
+    .unwrap_or_else(|| get_element_text(element));
+  let title = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "title")
+    .map(|param| get_element_text(param).trim().to_string())
+    .filter(|title| !title.is_empty());
+
+  let mut result = match title {
+    Some(title) => format!("\n::: {{.panel title=\"{}\"}}\n", title.replace('"', "\\\"")),
+    None => "\n::: panel\n".to_string(),
+  };
+  let body = body.trim();
+  if !body.is_empty() {
+    result.push_str(body);
+    result.push('\n');
+  }
+  result.push_str(":::\n\n");
+  result
+}
+
 /// Renders the Confluence status macro into inline code-style Markdown.
 ///
 /// # Arguments
@@ -55,5 +103,10 @@ fn render_status(element: Node) -> String {
   let title = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "title")
     .map(get_element_text)
     .unwrap_or_default();
-  format!("`[{title}]`")
+  format_status(&title)
+}
+
+/// Formats status text as the inline code badge used for status lozenges.
+pub(super) fn format_status(text: &str) -> String {
+  format!("`[{text}]`")
 }
@@ -1,8 +1,8 @@
 use roxmltree::Node;
 use tracing::debug;
 
-use crate::markdown::MarkdownOptions;
 use crate::markdown::utils::{find_child_by_tag, find_child_by_tag_and_attr, get_element_text};
+use crate::markdown::{MarkdownDialect, MarkdownOptions};
 
 /// Renders Confluence code macros into fenced Markdown code blocks.
 ///
@@ -10,7 +10,7 @@ use crate::markdown::utils::{find_child_by_tag, find_child_by_tag_and_attr, get_
 /// * `_macro_name` - Present for signature compatibility; only `"code"` variants reach here.
 /// * `element` - The `<ac:structured-macro>` node that contains code parameters and body.
 /// * `_convert_node` - Ignored callback because code bodies are plain text.
-/// * `_options` - Markdown conversion options (not currently used for code blocks).
+/// * `options` - Markdown conversion options providing the code fence style.
 ///
 /// # Returns
 /// Markdown fenced code block using the detected language when provided.
@@ -18,20 +18,21 @@ pub(super) fn handle_macro(
   _macro_name: &str,
   element: Node,
   _convert_node: &dyn Fn(Node) -> String,
-  _options: &MarkdownOptions,
+  options: &MarkdownOptions,
 ) -> Option<String> {
-  Some(format_code_block(element))
+  Some(format_code_block(element, &options.dialect))
 }
 
 /// Builds a fenced code block from a Confluence code macro element.
 ///
 /// # Arguments
 /// * `element` - The `<ac:structured-macro>` node containing `language` parameters and body text.
+/// * `dialect` - Markdown syntax choices for the code fence.
 ///
 /// # Returns
 /// A fenced code block surrounded by blank lines, including the language hint
 /// when available.
-fn format_code_block(element: Node) -> String {
+fn format_code_block(element: Node, dialect: &MarkdownDialect) -> String {
   let language = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "language")
     .map(get_element_text)
     .unwrap_or_default();
@@ -45,9 +46,10 @@ fn format_code_block(element: Node) -> String {
     .or_else(|| find_child_by_tag(element, "ac:rich-text-body").map(get_element_text))
     .unwrap_or_else(|| get_element_text(element));
 
+  let fence = dialect.fence(&body);
   let mut result = String::new();
   result.push('\n');
-  result.push_str("```");
+  result.push_str(&fence);
   let trimmed_language = language.trim();
   if !trimmed_language.is_empty() {
     result.push_str(trimmed_language);
@@ -60,6 +62,7 @@ fn format_code_block(element: Node) -> String {
     result.push('\n');
   }
 
-  result.push_str("```\n\n");
+  result.push_str(&fence);
+  result.push_str("\n\n");
   result
 }
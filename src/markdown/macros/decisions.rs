@@ -2,11 +2,12 @@ use std::collections::BTreeMap;
 
 use roxmltree::{Node, NodeType};
 
-use super::render_admonition_block;
-use crate::markdown::MarkdownOptions;
+use super::adf::{convert_adf_expand, convert_adf_status, convert_adf_table, convert_adf_task_list};
+use super::render_admonition;
 use crate::markdown::utils::{
   find_child_by_tag, find_child_by_tag_and_attr, get_attribute, get_element_text, matches_tag,
 };
+use crate::markdown::{MarkdownDialect, MarkdownOptions};
 
 /// Converts Confluence decision macros into descriptive Markdown blocks.
 ///
@@ -14,7 +15,7 @@ use crate::markdown::utils::{
 /// * `macro_name` - The specific decision macro variant (`decision`, `decision-list`, `decisionreport`).
 /// * `element` - The `<ac:structured-macro>` node containing decision metadata and body.
 /// * `convert_node` - Callback used to render nested rich text nodes into Markdown.
-/// * `_options` - Markdown rendering options (not currently used by decision macros).
+/// * `options` - Markdown rendering options providing the dialect for list and emphasis markers.
 ///
 /// # Returns
 /// Markdown representation for the decision macro, or `None` when unhandled.
@@ -22,28 +23,38 @@ pub(super) fn handle_macro(
   macro_name: &str,
   element: Node,
   convert_node: &dyn Fn(Node) -> String,
-  _options: &MarkdownOptions,
+  options: &MarkdownOptions,
 ) -> Option<String> {
+  let dialect = &options.dialect;
   let rendered = match macro_name {
-    "decisionreport" => format_decision_report(element),
-    "decision" => format_decision(element, convert_node),
-    "decision-list" => format_decision_list(element, convert_node),
+    "decisionreport" => format_decision_report(element, dialect),
+    "decision" => format_decision(element, convert_node, dialect),
+    "decision-list" => format_decision_list(element, convert_node, dialect),
     _ => return None,
   };
 
   Some(rendered)
 }
 
-/// Convert Atlassian Document Format decision extensions to Markdown.
+/// Convert Atlassian Document Format extensions to Markdown.
+///
+/// Decision lists, panels, expands, status lozenges, task lists, and tables
+/// are rendered like their storage-format equivalents; other nodes are
+/// converted as plain content.
 ///
 /// # Arguments
-/// * `element` - The `<ac:adf-extension>` node describing decision content.
+/// * `element` - The `<ac:adf-extension>` node describing the content.
 /// * `convert_node` - Callback used to render nested rich text into Markdown.
+/// * `options` - Conversion flags selecting list markers, table layout, and admonition syntax.
 ///
 /// # Returns
-/// A Markdown fragment representing the decision content when available,
+/// A Markdown fragment representing the structured content when available,
 /// otherwise the fallback rendering of embedded nodes.
-pub fn convert_adf_extension_to_markdown(element: Node, convert_node: &dyn Fn(Node) -> String) -> String {
+pub fn convert_adf_extension_to_markdown(
+  element: Node,
+  convert_node: &dyn Fn(Node) -> String,
+  options: &MarkdownOptions,
+) -> String {
   let mut result = String::new();
   let mut preferred_rendering = false;
   let mut segments: Vec<(String, bool)> = Vec::new();
@@ -52,18 +63,28 @@ pub fn convert_adf_extension_to_markdown(element: Node, convert_node: &dyn Fn(No
     if matches_tag(child, "ac:adf-node") {
       match get_attribute(child, "type").as_deref() {
         Some("decision-list") => {
-          let rendered = convert_adf_decision_list(child);
+          let rendered = convert_adf_decision_list(child, &options.dialect);
           if !rendered.is_empty() {
             flush_adf_segments(&mut result, &mut segments, false);
             result.push_str(&rendered);
             preferred_rendering = true;
           }
         }
-        Some("panel") => {
-          if let Some(rendered) = convert_adf_panel(child, convert_node) {
-            flush_adf_segments(&mut result, &mut segments, false);
-            result.push_str(&rendered);
-            preferred_rendering = true;
+        Some(kind @ ("panel" | "expand" | "nested-expand" | "status" | "task-list" | "table")) => {
+          let rendered = match kind {
+            "panel" => convert_adf_panel(child, convert_node, options),
+            "status" => convert_adf_status(child),
+            "task-list" => convert_adf_task_list(child, convert_node, options),
+            "table" => convert_adf_table(child, options),
+            _ => convert_adf_expand(child, convert_node),
+          };
+          match rendered {
+            Some(rendered) => {
+              flush_adf_segments(&mut result, &mut segments, false);
+              result.push_str(&rendered);
+              preferred_rendering = true;
+            }
+            None => append_adf_segment(&mut segments, convert_node(child), false),
           }
         }
         _ => append_adf_segment(&mut segments, convert_node(child), false),
@@ -113,7 +134,7 @@ fn flush_adf_segments(result: &mut String, segments: &mut Vec<(String, bool)>, i
   segments.clear();
 }
 
-fn convert_adf_panel(node: Node, convert_node: &dyn Fn(Node) -> String) -> Option<String> {
+fn convert_adf_panel(node: Node, convert_node: &dyn Fn(Node) -> String, options: &MarkdownOptions) -> Option<String> {
   let mut panel_type: Option<String> = None;
   let mut explicit_title: Option<String> = None;
   let mut body_segments: Vec<String> = Vec::new();
@@ -150,8 +171,9 @@ fn convert_adf_panel(node: Node, convert_node: &dyn Fn(Node) -> String) -> Optio
 
   let body = body_segments.join("\n\n");
   let heading = resolve_panel_heading(panel_type.as_deref(), explicit_title.as_deref());
+  let kind = panel_type.as_deref().unwrap_or("panel");
 
-  Some(render_admonition_block(&heading, &body))
+  Some(render_admonition(kind, &heading, &body, options))
 }
 
 fn resolve_panel_heading(panel_type: Option<&str>, explicit_title: Option<&str>) -> String {
@@ -202,20 +224,27 @@ struct DecisionInfo {
 ///
 /// # Arguments
 /// * `element` - The `<ac:structured-macro>` node for `decisionreport` containing an optional CQL query.
+/// * `dialect` - Markdown syntax choices for the emphasis marker.
 ///
 /// # Returns
 /// Markdown note explaining that the dynamic content is not exported, with the
 /// CQL query when provided.
-fn format_decision_report(element: Node) -> String {
+fn format_decision_report(element: Node, dialect: &MarkdownDialect) -> String {
   let query = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "cql")
     .map(get_element_text)
     .unwrap_or_default();
   if query.is_empty() {
-    "\n> _Decision report macro (dynamic content not exported)._ \n\n".to_string()
+    format!(
+      "\n> {} \n\n",
+      dialect.emphasis("Decision report macro (dynamic content not exported).")
+    )
   } else {
     format!(
-      "\n> _Decision report macro (CQL: {}). Dynamic content not exported._\n\n",
-      query.trim()
+      "\n> {}\n\n",
+      dialect.emphasis(&format!(
+        "Decision report macro (CQL: {}). Dynamic content not exported.",
+        query.trim()
+      ))
     )
   }
 }
@@ -338,15 +367,17 @@ fn parse_decision(element: Node, convert_node: &dyn Fn(Node) -> String) -> Decis
 ///
 /// # Arguments
 /// * `info` - Parsed decision metadata and body content.
+/// * `dialect` - Markdown syntax choices for the strong emphasis marker.
 ///
 /// # Returns
 /// Markdown string beginning with a bolded decision title followed by metadata
 /// and body text.
-fn format_decision_content(info: &DecisionInfo) -> String {
+fn format_decision_content(info: &DecisionInfo, dialect: &MarkdownDialect) -> String {
   let mut content = String::new();
 
   let title = info.title.trim();
-  content.push_str("**Decision:** ");
+  content.push_str(&dialect.strong("Decision:"));
+  content.push(' ');
   content.push_str(if title.is_empty() { "Untitled decision" } else { title });
 
   let mut metadata = Vec::new();
@@ -385,11 +416,12 @@ fn format_decision_content(info: &DecisionInfo) -> String {
 ///
 /// # Arguments
 /// * `content` - Fully formatted decision content that may span multiple lines.
+/// * `dialect` - Markdown syntax choices for the list marker.
 ///
 /// # Returns
-/// List item string starting with `-` when content is non-empty, otherwise
+/// List item string starting with the bullet marker when content is non-empty, otherwise
 /// `None`.
-fn render_list_item(content: &str) -> Option<String> {
+fn render_list_item(content: &str, dialect: &MarkdownDialect) -> Option<String> {
   let mut lines = content.lines();
   let first_line = lines.next()?.trim();
   if first_line.is_empty() {
@@ -397,7 +429,7 @@ fn render_list_item(content: &str) -> Option<String> {
   }
 
   let mut result = String::new();
-  result.push_str("- ");
+  result.push_str(&dialect.bullet());
   result.push_str(first_line);
   result.push('\n');
 
@@ -436,11 +468,12 @@ fn decision_info_has_content(info: &DecisionInfo) -> bool {
 /// # Arguments
 /// * `decisions` - Parsed decision entries to render in order.
 /// * `skip_empty` - When true, suppresses entries without titles or body content.
+/// * `dialect` - Markdown syntax choices for list and emphasis markers.
 ///
 /// # Returns
 /// Markdown list separated by blank lines, or an empty string when nothing
 /// qualifies.
-fn render_decision_infos(decisions: Vec<DecisionInfo>, skip_empty: bool) -> String {
+fn render_decision_infos(decisions: Vec<DecisionInfo>, skip_empty: bool, dialect: &MarkdownDialect) -> String {
   let mut result = String::new();
   let mut wrote_any = false;
 
@@ -449,8 +482,8 @@ fn render_decision_infos(decisions: Vec<DecisionInfo>, skip_empty: bool) -> Stri
       continue;
     }
 
-    let content = format_decision_content(&info);
-    if let Some(item) = render_list_item(&content) {
+    let content = format_decision_content(&info, dialect);
+    if let Some(item) = render_list_item(&content, dialect) {
       if !wrote_any {
         result.push('\n');
         wrote_any = true;
@@ -475,13 +508,14 @@ fn render_decision_infos(decisions: Vec<DecisionInfo>, skip_empty: bool) -> Stri
 /// # Arguments
 /// * `element` - The decision macro element to parse.
 /// * `convert_node` - Callback used to render the rich-text body.
+/// * `dialect` - Markdown syntax choices for emphasis markers.
 ///
 /// # Returns
 /// Markdown block containing the formatted decision, or an empty string when it
 /// lacks content.
-fn format_decision(element: Node, convert_node: &dyn Fn(Node) -> String) -> String {
+fn format_decision(element: Node, convert_node: &dyn Fn(Node) -> String, dialect: &MarkdownDialect) -> String {
   let info = parse_decision(element, convert_node);
-  let content = format_decision_content(&info);
+  let content = format_decision_content(&info, dialect);
 
   if content.trim().is_empty() {
     return String::new();
@@ -500,11 +534,12 @@ fn format_decision(element: Node, convert_node: &dyn Fn(Node) -> String) -> Stri
 /// # Arguments
 /// * `element` - The decision list macro element containing a rich-text body.
 /// * `convert_node` - Callback used to render fallback content and nested bodies.
+/// * `dialect` - Markdown syntax choices for list and emphasis markers.
 ///
 /// # Returns
 /// Markdown list of decisions or a fallback rendering when no structured
 /// decisions exist.
-fn format_decision_list(element: Node, convert_node: &dyn Fn(Node) -> String) -> String {
+fn format_decision_list(element: Node, convert_node: &dyn Fn(Node) -> String, dialect: &MarkdownDialect) -> String {
   let body = match find_child_by_tag(element, "ac:rich-text-body") {
     Some(body) => body,
     None => {
@@ -536,17 +571,18 @@ fn format_decision_list(element: Node, convert_node: &dyn Fn(Node) -> String) ->
     .into_iter()
     .map(|decision| parse_decision(decision, convert_node))
     .collect();
-  render_decision_infos(infos, false)
+  render_decision_infos(infos, false, dialect)
 }
 
 /// Converts an ADF decision list node into Markdown list items.
 ///
 /// # Arguments
 /// * `element` - The `<ac:adf-node type=\"decision-list\">` element to process.
+/// * `dialect` - Markdown syntax choices for list and emphasis markers.
 ///
 /// # Returns
 /// Markdown bullet list or an empty string when no decision items are present.
-fn convert_adf_decision_list(element: Node) -> String {
+fn convert_adf_decision_list(element: Node, dialect: &MarkdownDialect) -> String {
   let decisions: Vec<_> = element
     .children()
     .filter(|child| matches_tag(*child, "ac:adf-node"))
@@ -558,7 +594,7 @@ fn convert_adf_decision_list(element: Node) -> String {
     return String::new();
   }
 
-  render_decision_infos(decisions, true)
+  render_decision_infos(decisions, true, dialect)
 }
 
 /// Parses a single ADF decision item node into `DecisionInfo`.
@@ -621,7 +657,7 @@ fn parse_adf_decision(node: Node) -> Option<DecisionInfo> {
 ///
 /// # Returns
 /// Map of lowercased attribute keys to trimmed string values.
-fn collect_adf_attributes(node: Node) -> BTreeMap<String, String> {
+pub(super) fn collect_adf_attributes(node: Node) -> BTreeMap<String, String> {
   let mut attributes = BTreeMap::new();
 
   for child in node.children().filter(|child| matches_tag(*child, "ac:adf-attribute")) {
@@ -645,7 +681,7 @@ fn collect_adf_attributes(node: Node) -> BTreeMap<String, String> {
 ///
 /// # Returns
 /// Trimmed attribute value when found, otherwise `None`.
-fn attribute_lookup(attributes: &BTreeMap<String, String>, keys: &[&str]) -> Option<String> {
+pub(super) fn attribute_lookup(attributes: &BTreeMap<String, String>, keys: &[&str]) -> Option<String> {
   for key in keys {
     let normalized = key.to_ascii_lowercase();
     if let Some(value) = attributes.get(&normalized) {
@@ -0,0 +1,54 @@
+//! Diagram macros (`drawio`, `gliffy`, `plantuml`).
+//!
+//! draw.io and Gliffy store the diagram source and a PNG export as page
+//! attachments, so they render as the exported image followed by a link to
+//! the source. Both are rewritten to local paths when images and attachments
+//! are downloaded. PlantUML keeps its source in the macro body, which is
+//! emitted as a fenced `plantuml` block for downstream renderers.
+
+use roxmltree::Node;
+
+use crate::markdown::MarkdownOptions;
+use crate::markdown::utils::{find_child_by_tag, get_diagram_reference, get_element_text};
+
+/// Renders diagram macros into Markdown.
+///
+/// # Arguments
+/// * `macro_name` - Either `"drawio"`, `"gliffy"`, or `"plantuml"`.
+/// * `element` - The `<ac:structured-macro>` node describing the diagram.
+/// * `_convert_node` - Ignored callback because diagrams have no rich body.
+/// * `options` - Markdown conversion options providing the code fence style.
+///
+/// # Returns
+/// The diagram image and source link, a fenced PlantUML block, or `None` when
+/// the macro does not name a diagram or holds no source.
+pub(super) fn handle_macro(
+  macro_name: &str,
+  element: Node,
+  _convert_node: &dyn Fn(Node) -> String,
+  options: &MarkdownOptions,
+) -> Option<String> {
+  if macro_name == "plantuml" {
+    return format_plantuml(element, options);
+  }
+
+  let diagram = get_diagram_reference(element)?;
+  Some(format!(
+    "\n![{name}]({preview})\n\n[{name} ({tool} source)]({name})\n\n",
+    name = diagram.name,
+    preview = diagram.preview_filename(),
+    tool = diagram.tool,
+  ))
+}
+
+/// Wraps the PlantUML source of the macro body in a fenced block.
+fn format_plantuml(element: Node, options: &MarkdownOptions) -> Option<String> {
+  let body = find_child_by_tag(element, "ac:plain-text-body").map(get_element_text)?;
+  let source = body.trim_matches(|c| matches!(c, '\n' | '\r'));
+  if source.trim().is_empty() {
+    return None;
+  }
+
+  let fence = options.dialect.fence(source);
+  Some(format!("\n{fence}plantuml\n{source}\n{fence}\n\n"))
+}
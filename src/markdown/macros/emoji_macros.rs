@@ -2,37 +2,39 @@ use roxmltree::Node;
 use tracing::debug;
 
 use crate::markdown::MarkdownOptions;
-use crate::markdown::emoji::emoji_id_to_unicode;
+use crate::markdown::emoji::{emoji_id_to_unicode, render_emoji};
 use crate::markdown::utils::{find_child_by_tag_and_attr, get_element_text};
 
-/// Renders Confluence emoji macros into plain Unicode characters.
+/// Renders Confluence emoji macros into Unicode characters or shortcodes.
 ///
 /// # Arguments
 /// * `_macro_name` - Present for signature compatibility; not used.
 /// * `element` - The `<ac:structured-macro>` node that contains emoji parameters.
 /// * `_convert_node` - Unused callback since emoji macros have no inner content.
-/// * `_options` - Markdown conversion options (unused for emoji rendering).
+/// * `options` - Markdown conversion options selecting the emoji style.
 ///
 /// # Returns
-/// Unicode emoji or shortname text when a matching mapping is found.
+/// Unicode emoji or shortname text when a matching mapping is found, written
+/// in the requested emoji style.
 pub(super) fn handle_macro(
   _macro_name: &str,
   element: Node,
   _convert_node: &dyn Fn(Node) -> String,
-  _options: &MarkdownOptions,
+  options: &MarkdownOptions,
 ) -> Option<String> {
   let emoji_id = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "emoji-id").map(get_element_text);
+  let shortname = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "shortname").map(get_element_text);
 
   let result = emoji_id
     .as_deref()
     .and_then(|id| emoji_id_to_unicode(id.trim()))
     .or_else(|| find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "emoji").map(get_element_text))
-    .or_else(|| find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", "shortname").map(get_element_text))
+    .or_else(|| shortname.clone())
     .unwrap_or_default();
 
   if !result.is_empty() {
     debug!("Macro emoji: id={emoji_id:?} -> {result}");
   }
 
-  Some(result)
+  Some(render_emoji(&result, shortname.as_deref(), options.emoji_style))
 }
@@ -14,7 +14,7 @@ pub(super) fn handle_macro(
   _macro_name: &str,
   element: Node,
   convert_node: &dyn Fn(Node) -> String,
-  _options: &MarkdownOptions,
+  options: &MarkdownOptions,
 ) -> Option<String> {
   let body = find_child_by_tag(element, "ac:rich-text-body")
     .map(convert_node)
@@ -43,6 +43,6 @@ pub(super) fn handle_macro(
       Some(format!("{trimmed}\n\n"))
     }
   } else {
-    Some(render_admonition_block("Excerpt", body.trim()))
+    Some(render_admonition_block("Excerpt", body.trim(), &options.dialect))
   }
 }
@@ -39,6 +39,18 @@ fn render_expand(element: Node, convert_node: &dyn Fn(Node) -> String) -> String
     .map(convert_node)
     .unwrap_or_else(|| get_element_text(element));
 
+  render_details(&title, &body)
+}
+
+/// Wraps converted content in an HTML `<details>` block.
+///
+/// # Arguments
+/// * `title` - Text shown in the `<summary>` element.
+/// * `body` - Markdown content revealed when the block is expanded.
+///
+/// # Returns
+/// HTML `<details>` section surrounded by blank lines.
+pub(super) fn render_details(title: &str, body: &str) -> String {
   format!(
     "\n<details>\n<summary>{}</summary>\n\n{}\n</details>\n\n",
     title,
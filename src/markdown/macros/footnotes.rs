@@ -0,0 +1,30 @@
+use roxmltree::Node;
+
+use crate::markdown::MarkdownOptions;
+use crate::markdown::footnotes::{LIST_PLACEHOLDER, placeholder};
+use crate::markdown::utils::{find_child_by_tag, get_element_text};
+
+/// Converts the footnote macros into Markdown footnotes.
+///
+/// A `footnote` macro becomes a placeholder holding its converted text, which
+/// is numbered once the whole page is converted; `display-footnotes` marks
+/// where the footnote definitions are written instead of the end of the page.
+pub(super) fn handle_macro(
+  macro_name: &str,
+  element: Node,
+  convert_node: &dyn Fn(Node) -> String,
+  _options: &MarkdownOptions,
+) -> Option<String> {
+  if macro_name == "display-footnotes" {
+    return Some(format!("\n\n{LIST_PLACEHOLDER}\n\n"));
+  }
+
+  let text = find_child_by_tag(element, "ac:rich-text-body")
+    .map(convert_node)
+    .or_else(|| find_child_by_tag(element, "ac:plain-text-body").map(get_element_text))
+    .unwrap_or_else(|| get_element_text(element));
+  if text.trim().is_empty() {
+    return Some(String::new());
+  }
+  Some(placeholder(&text))
+}
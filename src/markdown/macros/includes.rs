@@ -0,0 +1,139 @@
+//! Include macros (`include`, `excerpt-include`, `multiexcerpt-include`).
+//!
+//! The macros reference content stored on another page. When the referenced
+//! page was fetched into [`MarkdownOptions::includes`], the whole page, its
+//! excerpt, or the named multi-excerpt is converted in place. Otherwise, and
+//! for circular includes, a note links to the source page so the gap in the
+//! export is visible.
+
+use roxmltree::{Document, Node};
+
+use super::{render_admonition, render_page_link};
+use crate::include_graph::{GraphPage, IncludeKind};
+use crate::links::PageReference;
+use crate::markdown::utils::{
+  clean_markdown, find_child_by_tag_and_attr, get_attribute, get_element_text, matches_tag, wrap_with_namespaces,
+};
+use crate::markdown::{MarkdownOptions, convert_node_to_markdown, html_entities};
+
+/// Macros holding a named excerpt for `multiexcerpt-include`.
+const MULTIEXCERPT_MACROS: &[&str] = &["multiexcerpt", "multiexcerpt-macro"];
+
+/// Inlines the content referenced by an include macro.
+///
+/// # Arguments
+/// * `macro_name` - One of the include macro names.
+/// * `element` - The `<ac:structured-macro>` node referencing the source page.
+/// * `_convert_node` - Ignored callback; the included content is converted from the source page.
+/// * `options` - Conversion options providing the fetched pages and link context.
+///
+/// # Returns
+/// The included content, a note linking to the source page when the content
+/// is unavailable, or `None` when the macro references no page.
+pub(super) fn handle_macro(
+  macro_name: &str,
+  element: Node,
+  _convert_node: &dyn Fn(Node) -> String,
+  options: &MarkdownOptions,
+) -> Option<String> {
+  let kind = IncludeKind::from_macro_name(macro_name)?;
+  let page_node = element.descendants().find(|node| matches_tag(*node, "ri:page"))?;
+  let title = get_attribute(page_node, "ri:content-title").filter(|title| !title.is_empty())?;
+  let space_key = get_attribute(page_node, "ri:space-key");
+  let excerpt_name = parameter(element, "MultiExcerptName").or_else(|| parameter(element, "name"));
+
+  let source = GraphPage {
+    space: space_key.clone().or_else(|| options.links.current_space.clone()),
+    title: title.clone(),
+  };
+  let circular = options
+    .includes
+    .as_ref()
+    .is_some_and(|includes| includes.is_circular(&source));
+  if !circular && let Some(content) = render_included(kind, &source, excerpt_name.as_deref(), options) {
+    return Some(content);
+  }
+
+  let reference = PageReference {
+    title: Some(title),
+    space_key,
+    ..Default::default()
+  };
+  let link = render_page_link(&reference, options)?;
+  let mut note = match (kind, &excerpt_name) {
+    (IncludeKind::Include, _) => format!("Content included from {link}."),
+    (IncludeKind::MultiExcerptInclude, Some(name)) => format!("Excerpt `{name}` included from {link}."),
+    _ => format!("Excerpt included from {link}."),
+  };
+  if circular {
+    note.push_str(" Not expanded because the include is circular.");
+  }
+  Some(render_admonition("info", "Included content", &note, options))
+}
+
+/// Converts the fetched content of an included page.
+///
+/// The source page is pushed onto the include stack while its content is
+/// converted, and links without a space key resolve against its space.
+fn render_included(
+  kind: IncludeKind,
+  source: &GraphPage,
+  excerpt_name: Option<&str>,
+  options: &MarkdownOptions,
+) -> Option<String> {
+  let includes = options.includes.as_ref()?;
+  let storage = includes.get(source)?;
+
+  let preprocessed = html_entities::preprocess_html_entities(storage);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document = Document::parse(&wrapped).ok()?;
+  let root = document.root_element();
+  let fragment = match kind {
+    IncludeKind::Include => root,
+    IncludeKind::ExcerptInclude => excerpt_body(root, &["excerpt"], None)?,
+    IncludeKind::MultiExcerptInclude => excerpt_body(root, MULTIEXCERPT_MACROS, excerpt_name)?,
+  };
+
+  let mut nested = options.clone();
+  nested.links.current_space = source.space.clone();
+  // Split tables are extracted from the including page's own storage only
+  nested.table_split = None;
+  if let Some(includes) = &mut nested.includes {
+    includes.stack.push(source.clone());
+  }
+  let content = clean_markdown(&convert_node_to_markdown(fragment, &nested));
+  let content = content.trim();
+  Some(if content.is_empty() {
+    String::new()
+  } else {
+    format!("\n{content}\n\n")
+  })
+}
+
+/// Finds the body of the first excerpt macro, or of the one named `name`.
+fn excerpt_body<'a, 'input>(
+  root: Node<'a, 'input>,
+  macro_names: &[&str],
+  name: Option<&str>,
+) -> Option<Node<'a, 'input>> {
+  root
+    .descendants()
+    .filter(|node| matches_tag(*node, "ac:structured-macro"))
+    .filter(|node| get_attribute(*node, "ac:name").is_some_and(|macro_name| macro_names.contains(&macro_name.as_str())))
+    .find(|node| {
+      name.is_none_or(|name| {
+        parameter(*node, "MultiExcerptName")
+          .or_else(|| parameter(*node, "name"))
+          .is_some_and(|value| value == name)
+      })
+    })?
+    .children()
+    .find(|child| matches_tag(*child, "ac:rich-text-body"))
+}
+
+/// Trimmed value of a macro parameter.
+fn parameter(element: Node, name: &str) -> Option<String> {
+  find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", name)
+    .map(|param| get_element_text(param).trim().to_string())
+    .filter(|value| !value.is_empty())
+}
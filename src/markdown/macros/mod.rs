@@ -5,20 +5,32 @@
 use roxmltree::Node;
 use tracing::debug;
 
-use crate::markdown::MarkdownOptions;
-use crate::markdown::utils::{find_child_by_tag, get_attribute, get_element_text};
-
+use crate::format::MarkdownFlavor;
+use crate::links::{PageReference, UnexportedLinkStyle};
+use crate::markdown::utils::{
+  append_annotation, describe_macro, escape_attribute, find_child_by_tag, get_attribute, get_element_text,
+  render_comment,
+};
+use crate::markdown::{ImageStyle, MarkdownOptions};
+use crate::redaction;
+use crate::tasks::InlineTask;
+
+mod adf;
 mod admonitions;
 mod anchors;
 mod basic;
 mod code;
 mod decisions;
+mod diagrams;
 mod emoji_macros;
 mod excerpts;
 mod expand;
+mod footnotes;
+mod includes;
 mod jira;
+mod page_properties;
 
-pub(crate) use admonitions::render_admonition_block;
+pub(crate) use admonitions::{render_admonition, render_admonition_block};
 pub use decisions::convert_adf_extension_to_markdown;
 
 /// Signature used by all macro handlers.
@@ -42,6 +54,15 @@ const HANDLERS: &[Handler] = &[
     names: &["excerpt"],
     func: excerpts::handle_macro,
   },
+  Handler {
+    names: &[
+      "include",
+      "excerpt-include",
+      "multiexcerpt-include",
+      "multiexcerpt-include-macro",
+    ],
+    func: includes::handle_macro,
+  },
   Handler {
     names: &["code", "code-block"],
     func: code::handle_macro,
@@ -66,11 +87,25 @@ const HANDLERS: &[Handler] = &[
     names: &["jira"],
     func: jira::handle_macro,
   },
+  Handler {
+    names: &["drawio", "gliffy", "plantuml"],
+    func: diagrams::handle_macro,
+  },
+  Handler {
+    names: &["details", "detailssummary"],
+    func: page_properties::handle_macro,
+  },
+  Handler {
+    names: &["footnote", "display-footnotes"],
+    func: footnotes::handle_macro,
+  },
 ];
 
 /// Converts Confluence structured macros to Markdown.
 ///
-/// Unknown macros fall back to returning their text content.
+/// Unknown macros fall back to returning their text content. With
+/// `annotate_macros` set, a comment naming the macro and its parameters
+/// follows the output.
 ///
 /// # Arguments
 /// * `element` - The `<ac:structured-macro>` node being processed.
@@ -86,6 +121,21 @@ pub fn convert_macro_to_markdown(
   options: &MarkdownOptions,
 ) -> String {
   let macro_name = get_attribute(element, "ac:name").unwrap_or_default();
+  if redaction::is_redacted(&options.redacted_macros, &macro_name) {
+    return redaction::REDACTED.to_string();
+  }
+
+  let rendered = render_macro(element, convert_node, options);
+  if options.annotate_macros {
+    append_annotation(rendered, &render_comment(&describe_macro(element), options))
+  } else {
+    rendered
+  }
+}
+
+/// Dispatches a structured macro to its handler.
+fn render_macro(element: Node, convert_node: &dyn Fn(Node) -> String, options: &MarkdownOptions) -> String {
+  let macro_name = get_attribute(element, "ac:name").unwrap_or_default();
 
   for handler in HANDLERS {
     if handler.names.iter().any(|name| *name == macro_name)
@@ -103,26 +153,21 @@ pub fn convert_macro_to_markdown(
 ///
 /// # Arguments
 /// * `element` - The `<ac:task-list>` node to convert.
+/// * `options` - Conversion options providing the list marker.
 ///
 /// # Returns
-/// Markdown representing each task as a checkbox list item.
-pub fn convert_task_list_to_markdown(element: Node) -> String {
+/// Markdown representing each task as a checkbox list item, followed by the
+/// task's assignee and due date.
+pub fn convert_task_list_to_markdown(element: Node, options: &MarkdownOptions) -> String {
   let mut result = String::new();
 
   for task in element
     .children()
     .filter(|child| crate::markdown::utils::matches_tag(*child, "ac:task"))
   {
-    let status = find_child_by_tag(task, "ac:task-status")
-      .map(get_element_text)
-      .unwrap_or_else(|| "incomplete".to_string());
-
-    let body = find_child_by_tag(task, "ac:task-body")
-      .map(get_element_text)
-      .unwrap_or_default();
-
-    let checkbox = if status.trim() == "complete" { "[x]" } else { "[ ]" };
-    result.push_str(&format!("- {} {}\n", checkbox, body.trim()));
+    let task = InlineTask::from_node(task);
+    let checkbox = options.task_checkbox(task.complete);
+    result.push_str(&format!("{}{} {}\n", options.dialect.bullet(), checkbox, task.label()));
   }
 
   result.push('\n');
@@ -133,25 +178,72 @@ pub fn convert_task_list_to_markdown(element: Node) -> String {
 ///
 /// # Arguments
 /// * `element` - The `<ac:image>` node to convert.
+/// * `options` - Conversion options selecting the image style and flavor.
 ///
 /// # Returns
-/// Markdown `![alt](source)` markup using either attachment filenames or URLs.
-pub fn convert_image_to_markdown(element: Node) -> String {
+/// Markdown `![alt](source)` markup using either attachment filenames or URLs,
+/// or an HTML `<img>` keeping the image's size, alignment, border, and caption
+/// with [`ImageStyle::Html`].
+pub fn convert_image_to_markdown(element: Node, options: &MarkdownOptions) -> String {
   let alt = get_attribute(element, "ac:alt").unwrap_or_else(|| "image".to_string());
-
-  if let Some(url) = find_child_by_tag(element, "ri:url").and_then(|e| get_attribute(e, "ri:value"))
-    && !url.is_empty()
-  {
-    return format!("\n![{alt}]({url})\n\n");
+  let source = find_child_by_tag(element, "ri:url")
+    .and_then(|e| get_attribute(e, "ri:value"))
+    .filter(|url| !url.is_empty())
+    .or_else(|| find_child_by_tag(element, "ri:attachment").and_then(|e| get_attribute(e, "ri:filename")))
+    .unwrap_or_default();
+  let caption = find_child_by_tag(element, "ac:caption")
+    .map(get_element_text)
+    .map(|caption| caption.split_whitespace().collect::<Vec<_>>().join(" "))
+    .filter(|caption| !caption.is_empty());
+  let size = |name: &str| get_attribute(element, name).filter(|value| value.trim().parse::<u32>().is_ok());
+  let (width, height) = (size("ac:width"), size("ac:height"));
+
+  if options.image_style == ImageStyle::Html {
+    let mut img = format!(
+      "<img src=\"{}\" alt=\"{}\"",
+      escape_attribute(&source),
+      escape_attribute(&alt)
+    );
+    for (name, value) in [("width", &width), ("height", &height)] {
+      if let Some(value) = value {
+        img.push_str(&format!(" {name}=\"{}\"", value.trim()));
+      }
+    }
+    if let Some(align @ ("left" | "right")) = get_attribute(element, "ac:align").as_deref() {
+      img.push_str(&format!(" align=\"{align}\""));
+    }
+    if get_attribute(element, "ac:border").as_deref() == Some("true") {
+      img.push_str(" style=\"border: 1px solid\"");
+    }
+    img.push_str(" />");
+
+    let centered = get_attribute(element, "ac:align").as_deref() == Some("center");
+    return match (caption, centered) {
+      (Some(caption), centered) => format!(
+        "\n<figure{}>\n{img}\n<figcaption>{}</figcaption>\n</figure>\n\n",
+        if centered { " align=\"center\"" } else { "" },
+        escape_attribute(&caption)
+      ),
+      (None, true) => format!("\n<p align=\"center\">{img}</p>\n\n"),
+      (None, false) => format!("\n{img}\n\n"),
+    };
   }
 
-  if let Some(filename) = find_child_by_tag(element, "ri:attachment").and_then(|e| get_attribute(e, "ri:filename"))
-    && !filename.is_empty()
-  {
-    return format!("\n![{alt}]({filename})\n\n");
+  // Pandoc's link attributes keep the size of an image
+  let mut attributes = Vec::new();
+  if options.flavor == Some(MarkdownFlavor::Pandoc) {
+    for (name, value) in [("width", &width), ("height", &height)] {
+      if let Some(value) = value {
+        attributes.push(format!("{name}={}px", value.trim()));
+      }
+    }
   }
-
-  format!("\n![{alt}]()\n\n")
+  let attributes = if attributes.is_empty() {
+    String::new()
+  } else {
+    format!("{{{}}}", attributes.join(" "))
+  };
+  format!("\n![{alt}]({source}){attributes}\n\n")
 }
 
 /// Converts Confluence links to Markdown.
@@ -161,18 +253,18 @@ pub fn convert_image_to_markdown(element: Node) -> String {
 ///
 /// # Arguments
 /// * `element` - The `<ac:link>` node to convert.
+/// * `options` - Conversion options providing the link resolution context.
 ///
 /// # Returns
 /// Markdown-formatted text representing the link target or mention.
-pub fn convert_confluence_link_to_markdown(element: Node) -> String {
+pub fn convert_confluence_link_to_markdown(element: Node, options: &MarkdownOptions) -> String {
   // Check for user mention
   if let Some(user_node) = find_child_by_tag(element, "ri:user") {
     let account_id = get_attribute(user_node, "ri:account-id").unwrap_or_default();
 
     debug!("User mention: account_id={account_id}");
 
-    // Format as @mention with account ID as fallback
-    // In the future, we could look up display names via API
+    // Display names are filled in after conversion by `UserResolver` when enabled
     return format!("@user:{account_id}");
   }
 
@@ -182,8 +274,13 @@ pub fn convert_confluence_link_to_markdown(element: Node) -> String {
 
     debug!("Page link: title={title}");
 
-    // Format as wiki-style link
-    return format!("[[{title}]]");
+    let reference = PageReference {
+      title: Some(title),
+      space_key: get_attribute(page_node, "ri:space-key"),
+      anchor: get_attribute(element, "ac:anchor"),
+      ..Default::default()
+    };
+    return render_page_link(&reference, options).unwrap_or_default();
   }
 
   // Check for attachment link
@@ -200,8 +297,16 @@ pub fn convert_confluence_link_to_markdown(element: Node) -> String {
     }
   }
 
-  // Fall back to regular link handling if it has an href
+  // Links to an anchor on the same page; the fragment is matched to a
+  // heading by `heading_links::rewrite` once the page is converted
   let text = get_element_text(element);
+  if let Some(anchor) = get_attribute(element, "ac:anchor").filter(|anchor| !anchor.trim().is_empty()) {
+    let anchor = anchor.trim();
+    let text = if text.trim().is_empty() { anchor } else { text.trim() };
+    return format!("[{text}](#{})", anchor.replace(' ', "%20"));
+  }
+
+  // Fall back to regular link handling if it has an href
   if let Some(href) = get_attribute(element, "href") {
     return format!("[{text}]({href})");
   }
@@ -210,6 +315,78 @@ pub fn convert_confluence_link_to_markdown(element: Node) -> String {
   text
 }
 
+/// Renders a resolved page reference as a Markdown link.
+///
+/// Links to exported pages become relative links to the exported file when
+/// the export scope records output paths, and wiki-style `[[Title]]` links
+/// otherwise. Links to pages outside the export scope follow
+/// [`LinkContext::unexported_links`](crate::links::LinkContext::unexported_links).
+///
+/// # Arguments
+/// * `reference` - The page reference to render.
+/// * `options` - Conversion options providing the link resolution context and dialect.
+///
+/// # Returns
+/// `Some(String)` with the rendered link, or `None` when the reference lacks
+/// a title to link by.
+pub fn render_page_link(reference: &PageReference, options: &MarkdownOptions) -> Option<String> {
+  render_page_link_with_text(reference, None, options)
+}
+
+/// Renders a resolved page reference as a Markdown link with the given link
+/// text.
+///
+/// Behaves like [`render_page_link`], but labels the link with `text`
+/// instead of the page title, keeping the wording of the original link.
+/// Wiki-style links keep the title as their target, as `[[Title|text]]`.
+///
+/// # Arguments
+/// * `reference` - The page reference to render.
+/// * `text` - Link text of the original link; the page title is used when `None`.
+/// * `options` - Conversion options providing the link resolution context and dialect.
+///
+/// # Returns
+/// `Some(String)` with the rendered link, or `None` when the reference lacks
+/// a title to link by.
+pub fn render_page_link_with_text(
+  reference: &PageReference,
+  text: Option<&str>,
+  options: &MarkdownOptions,
+) -> Option<String> {
+  let links = &options.links;
+  let not_exported = options.dialect.emphasis("(not exported)");
+  let title = links.title(reference)?;
+  let label = text.unwrap_or(title);
+  let wiki_link = || match text {
+    Some(text) if text != title => match options.flavor {
+      Some(MarkdownFlavor::Gfm | MarkdownFlavor::CommonMark) => text.to_string(),
+      Some(MarkdownFlavor::Pandoc | MarkdownFlavor::Obsidian) | None => format!("[[{title}|{text}]]"),
+    },
+    _ => options.wiki_link(title),
+  };
+
+  if links.is_exported(reference) {
+    return Some(match links.relative_path(reference) {
+      Some(path) => format!("[{label}]({path})"),
+      None => wiki_link(),
+    });
+  }
+
+  debug!("Link to page outside export scope: {title}");
+
+  match links.unexported_links {
+    UnexportedLinkStyle::Keep => match links.confluence_url(reference) {
+      Some(url) => Some(format!("[{label}]({url})")),
+      None => Some(wiki_link()),
+    },
+    UnexportedLinkStyle::Confluence => match links.confluence_url(reference) {
+      Some(url) => Some(format!("[{label} (Confluence)]({url})")),
+      None => Some(format!("{label} {not_exported}")),
+    },
+    UnexportedLinkStyle::Annotate => Some(format!("{label} {not_exported}")),
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use roxmltree::Document;
@@ -256,6 +433,25 @@ mod tests {
     assert!(output.contains("**Table of Contents**"));
   }
 
+  #[test]
+  fn test_redacted_macro_hides_content_and_annotation() {
+    let input = r#"<ac:structured-macro ac:name="vault-secret"><ac:parameter ac:name="token">s3cr3t</ac:parameter></ac:structured-macro>"#;
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let macro_node = document
+      .descendants()
+      .find(|node| matches_tag(*node, "ac:structured-macro"))
+      .unwrap();
+    let options = MarkdownOptions {
+      annotate_macros: true,
+      redacted_macros: vec!["Vault-Secret".to_string()],
+      ..Default::default()
+    };
+
+    let output = convert_macro_to_markdown(macro_node, &simple_convert_node, &options);
+    assert_eq!(output, "[macro redacted]");
+  }
+
   #[test]
   fn test_anchor_macro_ignored_by_default() {
     let input = r#"
@@ -472,7 +668,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:task-list"))
       .unwrap();
-    let output = convert_task_list_to_markdown(task_list);
+    let output = convert_task_list_to_markdown(task_list, &MarkdownOptions::default());
     insta::assert_snapshot!(output, @r###"
     - [ ] Task 1
     - [x] Task 2
@@ -488,7 +684,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:image"))
       .unwrap();
-    let output = convert_image_to_markdown(image);
+    let output = convert_image_to_markdown(image, &MarkdownOptions::default());
     assert!(output.contains("![test image](https://example.com/image.png)"));
   }
 
@@ -501,7 +697,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:image"))
       .unwrap();
-    let output = convert_image_to_markdown(image);
+    let output = convert_image_to_markdown(image, &MarkdownOptions::default());
     assert!(output.contains("![diagram](diagram.png)"));
   }
 
@@ -519,7 +715,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:link"))
       .unwrap();
-    let output = convert_confluence_link_to_markdown(link);
+    let output = convert_confluence_link_to_markdown(link, &MarkdownOptions::default());
     assert_eq!(output, "[Download spec](spec.pdf)");
   }
 
@@ -543,7 +739,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:adf-extension"))
       .unwrap();
-    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node);
+    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node, &MarkdownOptions::default());
     assert_eq!(output, "Intro text.\n- **Decision:** Decision Title\n\nOutro text.");
   }
 
@@ -560,7 +756,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:adf-extension"))
       .unwrap();
-    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node);
+    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node, &MarkdownOptions::default());
     assert_eq!(output, "Fallback only.");
   }
 
@@ -581,7 +777,7 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:adf-extension"))
       .unwrap();
-    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node);
+    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node, &MarkdownOptions::default());
     assert!(output.contains("> **Note:** This is Note.Next line."));
     assert!(!output.contains("Fallback panel markup"));
   }
@@ -603,10 +799,166 @@ line 2]]></ac:plain-text-body>
       .descendants()
       .find(|node| matches_tag(*node, "ac:adf-extension"))
       .unwrap();
-    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node);
+    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node, &MarkdownOptions::default());
     assert!(output.contains("> **Important:** Body copy."));
   }
 
+  #[test]
+  fn test_convert_adf_expand_status_and_task_list() {
+    let input = concat!(
+      "<ac:adf-extension>",
+      "<ac:adf-node type=\"status\">",
+      "<ac:adf-attribute key=\"text\">In Progress</ac:adf-attribute>",
+      "<ac:adf-attribute key=\"color\">blue</ac:adf-attribute>",
+      "</ac:adf-node>",
+      "<ac:adf-node type=\"expand\">",
+      "<ac:adf-attribute key=\"title\">Rollout plan</ac:adf-attribute>",
+      "<ac:adf-content><p>Ship on Monday.</p></ac:adf-content>",
+      "</ac:adf-node>",
+      "<ac:adf-node type=\"task-list\">",
+      "<ac:adf-node type=\"task-item\">",
+      "<ac:adf-attribute key=\"state\">DONE</ac:adf-attribute>",
+      "<ac:adf-content>Write changelog</ac:adf-content>",
+      "</ac:adf-node>",
+      "<ac:adf-node type=\"task-item\">",
+      "<ac:adf-attribute key=\"state\">TODO</ac:adf-attribute>",
+      "<ac:adf-content>Tag release</ac:adf-content>",
+      "</ac:adf-node>",
+      "</ac:adf-node>",
+      "<ac:adf-fallback>Fallback markup.</ac:adf-fallback>",
+      "</ac:adf-extension>"
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let extension = document
+      .descendants()
+      .find(|node| matches_tag(*node, "ac:adf-extension"))
+      .unwrap();
+    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node, &MarkdownOptions::default());
+
+    insta::assert_snapshot!(output, @r"
+    `[In Progress]`
+    <details>
+    <summary>Rollout plan</summary>
+
+    Ship on Monday.
+    </details>
+
+    - [x] Write changelog
+    - [ ] Tag release
+    ");
+  }
+
+  #[test]
+  fn test_convert_adf_table() {
+    let input = concat!(
+      "<ac:adf-extension>",
+      "<ac:adf-node type=\"table\">",
+      "<ac:adf-node type=\"table-row\">",
+      "<ac:adf-node type=\"table-header\"><ac:adf-content>Service</ac:adf-content></ac:adf-node>",
+      "<ac:adf-node type=\"table-header\"><ac:adf-content>Owner</ac:adf-content></ac:adf-node>",
+      "</ac:adf-node>",
+      "<ac:adf-node type=\"table-row\">",
+      "<ac:adf-node type=\"table-cell\"><ac:adf-content>Gateway</ac:adf-content></ac:adf-node>",
+      "<ac:adf-node type=\"table-cell\"><ac:adf-content>Platform</ac:adf-content></ac:adf-node>",
+      "</ac:adf-node>",
+      "</ac:adf-node>",
+      "<ac:adf-fallback>Fallback table.</ac:adf-fallback>",
+      "</ac:adf-extension>"
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let extension = document
+      .descendants()
+      .find(|node| matches_tag(*node, "ac:adf-extension"))
+      .unwrap();
+    let output = convert_adf_extension_to_markdown(extension, &simple_convert_node, &MarkdownOptions::default());
+
+    insta::assert_snapshot!(output, @r"
+    | Service | Owner    |
+    | ------- | -------- |
+    | Gateway | Platform |
+    ");
+    assert!(!output.contains("Fallback table"));
+  }
+
+  #[test]
+  fn test_annotate_macros_with_parameters() {
+    let input = concat!(
+      "<p>",
+      "<ac:structured-macro ac:name=\"status\">",
+      "<ac:parameter ac:name=\"title\">Done</ac:parameter>",
+      "<ac:parameter ac:name=\"colour\">Green</ac:parameter>",
+      "</ac:structured-macro>",
+      "</p>",
+      "<ac:structured-macro ac:name=\"code\">",
+      "<ac:parameter ac:name=\"language\">rust</ac:parameter>",
+      "<ac:plain-text-body><![CDATA[fn main() {}]]></ac:plain-text-body>",
+      "</ac:structured-macro>"
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let options = MarkdownOptions {
+      annotate_macros: true,
+      ..Default::default()
+    };
+    let output: String = document
+      .descendants()
+      .filter(|node| matches_tag(*node, "ac:structured-macro"))
+      .map(|node| convert_macro_to_markdown(node, &simple_convert_node, &options))
+      .collect();
+
+    insta::assert_snapshot!(output, @r"
+    `[Done]`<!-- confluence-macro: status title=Done colour=Green -->
+    ```rust
+    fn main() {}
+    ```
+    <!-- confluence-macro: code language=rust -->
+    ");
+  }
+
+  #[test]
+  fn test_convert_diagram_macros() {
+    let input = concat!(
+      "<div>",
+      "<ac:structured-macro ac:name=\"drawio\">",
+      "<ac:parameter ac:name=\"diagramName\">Architecture</ac:parameter>",
+      "</ac:structured-macro>",
+      "<ac:structured-macro ac:name=\"gliffy\">",
+      "<ac:parameter ac:name=\"name\">Login flow</ac:parameter>",
+      "</ac:structured-macro>",
+      "<ac:structured-macro ac:name=\"plantuml\">",
+      "<ac:plain-text-body><![CDATA[@startuml\nAlice -> Bob: Hello\n@enduml]]></ac:plain-text-body>",
+      "</ac:structured-macro>",
+      "</div>"
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let output: String = document
+      .descendants()
+      .filter(|node| matches_tag(*node, "ac:structured-macro"))
+      .map(|node| convert_macro_to_markdown(node, &simple_convert_node, &MarkdownOptions::default()))
+      .collect();
+
+    insta::assert_snapshot!(output, @r"
+    ![Architecture](Architecture.png)
+
+    [Architecture (draw.io source)](Architecture)
+
+
+    ![Login flow](Login flow.png)
+
+    [Login flow (Gliffy source)](Login flow)
+
+
+    ```plantuml
+    @startuml
+    Alice -> Bob: Hello
+    @enduml
+    ```
+    ");
+  }
+
   #[test]
   fn test_convert_decision_macro() {
     let input = r#"
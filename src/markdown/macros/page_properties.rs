@@ -0,0 +1,96 @@
+use roxmltree::Node;
+
+use super::render_admonition;
+use crate::markdown::MarkdownOptions;
+use crate::markdown::tables::{has_block_content, render_cell, render_markdown_table, table_rows};
+use crate::markdown::utils::{find_child_by_tag, find_child_by_tag_and_attr, get_element_text, matches_tag};
+
+/// Converts page properties (`details`) and page properties report
+/// (`detailssummary`) macros into Markdown.
+///
+/// # Arguments
+/// * `macro_name` - Either `details` or `detailssummary`.
+/// * `element` - The `<ac:structured-macro>` node to convert.
+/// * `convert_node` - Callback used to render the macro body when it is not a key/value table.
+/// * `options` - Conversion flags controlling table and admonition rendering.
+///
+/// # Returns
+/// The properties as a two-column table, a note describing the report, or
+/// `None` for other macros.
+pub(super) fn handle_macro(
+  macro_name: &str,
+  element: Node,
+  convert_node: &dyn Fn(Node) -> String,
+  options: &MarkdownOptions,
+) -> Option<String> {
+  match macro_name {
+    "details" => Some(render_page_properties(element, convert_node, options)),
+    "detailssummary" => Some(render_properties_report(element, options)),
+    _ => None,
+  }
+}
+
+/// Renders the key/value table of a page properties macro.
+///
+/// Each row becomes a bold property name and its value. Bodies that are not a
+/// table of two-cell rows, or whose values hold block content, are converted
+/// as regular content instead.
+fn render_page_properties(element: Node, convert_node: &dyn Fn(Node) -> String, options: &MarkdownOptions) -> String {
+  let Some(body) = find_child_by_tag(element, "ac:rich-text-body") else {
+    return String::new();
+  };
+  let Some(table) = body.descendants().find(|node| matches_tag(*node, "table")) else {
+    return convert_node(body);
+  };
+
+  let mut rows = vec![vec!["Property".to_string(), "Value".to_string()]];
+  for cells in table_rows(table) {
+    let [key, value] = cells[..] else {
+      return convert_node(body);
+    };
+    if has_block_content(value) {
+      return convert_node(body);
+    }
+
+    let key = get_element_text(key).split_whitespace().collect::<Vec<_>>().join(" ");
+    if key.is_empty() {
+      continue;
+    }
+    rows.push(vec![
+      options.dialect.strong(&key.replace('|', "\\|")),
+      render_cell(value, options),
+    ]);
+  }
+
+  if rows.len() == 1 {
+    return convert_node(body);
+  }
+  render_markdown_table(rows, options.compact_tables).unwrap_or_default()
+}
+
+/// Renders a page properties report as a note naming the labels and query
+/// that select its pages; the report itself is generated by Confluence and
+/// cannot be exported.
+fn render_properties_report(element: Node, options: &MarkdownOptions) -> String {
+  let parameter = |name| {
+    find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", name)
+      .map(|node| get_element_text(node).trim().to_string())
+      .filter(|value| !value.is_empty())
+  };
+
+  let mut body = "Dynamic content not exported.".to_string();
+  if let Some(labels) = parameter("label") {
+    let labels: Vec<_> = labels
+      .split(',')
+      .map(str::trim)
+      .filter(|label| !label.is_empty())
+      .map(|label| format!("`{label}`"))
+      .collect();
+    body.push_str(&format!("\n\nLabels: {}", labels.join(", ")));
+  }
+  if let Some(cql) = parameter("cql") {
+    body.push_str(&format!("\n\nCQL: `{cql}`"));
+  }
+
+  render_admonition("note", "Page properties report", &body, options)
+}
@@ -11,7 +11,11 @@
 //! - [`tables`] - HTML table to Markdown table conversion
 //! - [`macros`] - Confluence macro handling (panels, notes, etc.)
 //! - [`elements`] - Basic HTML element converters
+//! - [`heading_links`] - Same-page anchor links pointed at heading anchors
+//! - [`lint`] - Optional markdownlint-style autofix pass
+//! - [`toc`] - Table of contents generated from the converted headings
 //! - [`utils`] - Utility functions for XML parsing and manipulation
+//! - [`wiki`] - Legacy wiki markup conversion for pages without storage XHTML
 //!
 //! # Example
 //!
@@ -27,28 +31,231 @@
 
 use std::time::Instant;
 
-use anyhow::Result;
+use clap::ValueEnum;
 use roxmltree::Document;
 use tracing::{debug, error, trace};
 
+use crate::error::{Error, Result};
+use crate::format::{
+  BulletMarker, EmphasisMarker, FenceStyle, HeadingOverflow, HeadingStyle, MarkdownFlavor, TableCaptionPosition,
+  TableSpanMode,
+};
+use crate::includes::IncludedPages;
+use crate::links::LinkContext;
+use crate::markdown::jira_keys::JiraLinker;
+use crate::table_export::TableSplit;
+
 // Module declarations
 mod elements;
 mod emoji;
+mod footnotes;
+mod heading_links;
 pub mod html_entities;
+pub mod jira_keys;
+pub mod lint;
 mod macros;
 mod tables;
+mod toc;
 pub mod utils;
+pub mod wiki;
 
 // Public API - re-export main conversion function
 pub use elements::convert_node_to_markdown;
+pub use tables::table_grid;
+
+/// Syntax used for admonition blocks such as notes and warnings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum AdmonitionStyle {
+  /// Blockquote with a bold heading, e.g. `> **Note:** text` (default)
+  #[default]
+  Blockquote,
+  /// Docusaurus directives, e.g. `:::note` ... `:::`
+  Docusaurus,
+  /// GitHub alerts, e.g. `> [!WARNING]`
+  #[value(name = "github")]
+  GitHub,
+  /// Obsidian callouts, e.g. `> [!note] Title`
+  Obsidian,
+  /// Pandoc fenced divs, e.g. `::: note` ... `:::`
+  #[value(name = "pandoc")]
+  PandocDiv,
+}
+
+/// How emoji are written in Markdown output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum EmojiStyle {
+  /// Unicode characters, e.g. `✅` (default)
+  #[default]
+  Unicode,
+  /// Shortcodes, e.g. `:white_check_mark:`, for platforms that render them
+  Shortcode,
+  /// No emoji, for plain-text downstreams
+  Strip,
+}
+
+/// How images are written in Markdown output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ImageStyle {
+  /// Markdown images (default); the pandoc flavor also keeps their width and
+  /// height
+  #[default]
+  Markdown,
+  /// HTML `<img>` tags keeping width, height, alignment, and border, with
+  /// captioned images wrapped in `<figure>`
+  Html,
+}
+
+/// Syntax choices for Markdown constructs that have more than one spelling.
+///
+/// Lets exports match a repository's markdownlint configuration (heading,
+/// list, emphasis, strong, and code fence style rules) without a separate
+/// formatting pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarkdownDialect {
+  /// Syntax for level 1 and 2 headings.
+  pub heading_style: HeadingStyle,
+  /// Marker for unordered list items.
+  pub bullet: BulletMarker,
+  /// Delimiter for emphasis.
+  pub emphasis: EmphasisMarker,
+  /// Delimiter for strong emphasis.
+  pub strong: EmphasisMarker,
+  /// Fence for code blocks.
+  pub fence: FenceStyle,
+}
+
+impl Default for MarkdownDialect {
+  fn default() -> Self {
+    Self {
+      heading_style: HeadingStyle::Atx,
+      bullet: BulletMarker::Dash,
+      emphasis: EmphasisMarker::Underscore,
+      strong: EmphasisMarker::Asterisk,
+      fence: FenceStyle::Backtick,
+    }
+  }
+}
+
+impl MarkdownDialect {
+  /// Render a heading without surrounding blank lines.
+  ///
+  /// # Arguments
+  /// * `level` - Heading level from 1 to 6.
+  /// * `text` - Heading text, already converted to Markdown.
+  pub fn heading(&self, level: usize, text: &str) -> String {
+    let underline = match (self.heading_style, level) {
+      (HeadingStyle::Setext, 1) if !text.is_empty() => '=',
+      (HeadingStyle::Setext, 2) if !text.is_empty() => '-',
+      _ => return format!("{} {text}", "#".repeat(level)),
+    };
+    let width = text.lines().map(|line| line.chars().count()).max().unwrap_or(1);
+    format!("{text}\n{}", underline.to_string().repeat(width.max(3)))
+  }
+
+  /// The unordered list item prefix, e.g. `"- "`.
+  pub fn bullet(&self) -> String {
+    format!("{} ", self.bullet.as_char())
+  }
+
+  /// Wrap text in emphasis delimiters.
+  pub fn emphasis(&self, text: &str) -> String {
+    let marker = self.emphasis.as_char();
+    format!("{marker}{text}{marker}")
+  }
+
+  /// Wrap text in strong emphasis delimiters.
+  pub fn strong(&self, text: &str) -> String {
+    let marker = self.strong.as_char().to_string().repeat(2);
+    format!("{marker}{text}{marker}")
+  }
+
+  /// A code fence long enough not to be closed by fence runs inside `code`.
+  pub fn fence(&self, code: &str) -> String {
+    let marker = match self.fence {
+      FenceStyle::Backtick => '`',
+      FenceStyle::Tilde => '~',
+    };
+    let longest_run = code
+      .lines()
+      .map(|line| line.trim_start().chars().take_while(|&c| c == marker).count())
+      .max()
+      .unwrap_or(0);
+    marker.to_string().repeat(longest_run.max(2) + 1)
+  }
+}
 
 /// Options that control Markdown conversion behaviour.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct MarkdownOptions {
   /// Preserve Confluence anchor macros as HTML anchors in the output.
   pub preserve_anchors: bool,
   /// Render Markdown tables without padding cells to align columns.
   pub compact_tables: bool,
+  /// Where to place the italic caption line of captioned tables.
+  pub table_caption_position: TableCaptionPosition,
+  /// How cells spanning several rows or columns are rendered.
+  pub table_spans: TableSpanMode,
+  /// Context for resolving links to other Confluence pages.
+  pub links: LinkContext,
+  /// Escape prose characters that MDX would interpret as JSX or expressions.
+  pub mdx_safe: bool,
+  /// Syntax used for admonition blocks.
+  pub admonition_style: AdmonitionStyle,
+  /// How emoji are written.
+  pub emoji_style: EmojiStyle,
+  /// How images are written.
+  pub image_style: ImageStyle,
+  /// Syntax choices for headings, lists, emphasis, and code fences.
+  pub dialect: MarkdownDialect,
+  /// Run the markdownlint-style [`lint::autofix`] pass on the output.
+  pub lint_fix: bool,
+  /// Deepest heading level to emit; deeper headings follow `heading_overflow`.
+  pub max_heading_level: Option<u8>,
+  /// How headings deeper than `max_heading_level` are rendered.
+  pub heading_overflow: HeadingOverflow,
+  /// Link bare Jira issue keys in prose when set.
+  pub jira_links: Option<JiraLinker>,
+  /// Follow each converted macro with a comment recording its name and parameters.
+  pub annotate_macros: bool,
+  /// Markdown variant deciding task list and wiki link syntax; `None` keeps
+  /// GFM task lists and `[[Title]]` wiki links.
+  pub flavor: Option<MarkdownFlavor>,
+  /// Content of included pages, inlined by include macros when set.
+  pub includes: Option<IncludedPages>,
+  /// Replace `toc` macros with links to the page's headings instead of a
+  /// "Table of Contents" label.
+  pub generate_toc: bool,
+  /// Names of macros rendered as `[macro redacted]` instead of their content.
+  pub redacted_macros: Vec<String>,
+  /// Limits above which tables are cut down to a preview linking to a CSV
+  /// file; `None` keeps every table whole.
+  pub table_split: Option<TableSplit>,
+}
+
+impl MarkdownOptions {
+  /// The checkbox of a task list item.
+  ///
+  /// Strict CommonMark has no task lists, so ballot box characters stand in
+  /// for `[ ]` and `[x]`.
+  pub(crate) fn task_checkbox(&self, done: bool) -> &'static str {
+    match (self.flavor, done) {
+      (Some(MarkdownFlavor::CommonMark), false) => "☐",
+      (Some(MarkdownFlavor::CommonMark), true) => "☒",
+      (_, false) => "[ ]",
+      (_, true) => "[x]",
+    }
+  }
+
+  /// A link to an exported page whose output path is unknown.
+  ///
+  /// GFM and CommonMark have no wiki links, so the title is emitted as plain
+  /// text instead of an unresolvable `[[Title]]`.
+  pub(crate) fn wiki_link(&self, title: &str) -> String {
+    match self.flavor {
+      Some(MarkdownFlavor::Gfm | MarkdownFlavor::CommonMark) => title.to_string(),
+      Some(MarkdownFlavor::Pandoc | MarkdownFlavor::Obsidian) | None => format!("[[{title}]]"),
+    }
+  }
 }
 
 /// Convert Confluence storage format to Markdown using the provided options.
@@ -60,8 +267,11 @@ pub struct MarkdownOptions {
 ///
 /// # Returns
 ///
-/// `Result<String>` containing the converted Markdown content, or an error if
-/// parsing fails.
+/// `Result<String>` containing the converted Markdown content.
+///
+/// # Errors
+///
+/// Returns [`Error::Parse`] if the storage content is not well-formed XML.
 ///
 /// # Examples
 ///
@@ -90,7 +300,7 @@ pub fn storage_to_markdown_with_options(storage_content: &str, options: &Markdow
     error!("XML parse error: {e}");
     error!("Wrapped XML length: {} chars", wrapped.len());
     trace!("Full wrapped XML:\n{wrapped}");
-    anyhow::anyhow!("Failed to parse Confluence storage content: {e}")
+    Error::parse(format!("Failed to parse Confluence storage content: {e}"))
   })?;
 
   debug!(
@@ -100,10 +310,18 @@ pub fn storage_to_markdown_with_options(storage_content: &str, options: &Markdow
   );
 
   // Convert to markdown
-  let markdown = convert_node_to_markdown(document.root_element(), options);
+  let mut markdown = convert_node_to_markdown(document.root_element(), options);
+  markdown = footnotes::expand(&markdown, options.flavor);
+  if options.generate_toc {
+    markdown = toc::expand(&markdown, &options.dialect);
+  }
+  markdown = heading_links::rewrite(&markdown, options.dialect.heading_style);
 
   // Clean up the result
   let cleaned = utils::clean_markdown(&markdown);
+  if options.lint_fix {
+    return Ok(lint::autofix(&cleaned));
+  }
 
   Ok(cleaned)
 }
@@ -193,6 +411,29 @@ mod tests {
     assert!(!output.contains("**Excerpt:**"));
   }
 
+  #[test]
+  fn test_convert_footnote_macros() {
+    let input = concat!(
+      r#"<p>Logs are kept for 90 days<ac:structured-macro ac:name="footnote"><ac:rich-text-body><p>See the "#,
+      r#"<strong>2024</strong> retention policy.</p></ac:rich-text-body></ac:structured-macro> and backups for a "#,
+      r#"year<ac:structured-macro ac:name="footnote"><ac:plain-text-body><![CDATA[Longer for audited "#,
+      r#"systems.]]></ac:plain-text-body></ac:structured-macro>.</p><h2>Notes</h2>"#,
+      r#"<ac:structured-macro ac:name="display-footnotes" /><h2>History</h2>"#,
+    );
+
+    insta::assert_snapshot!(render(input), @r"
+    Logs are kept for 90 days[^1] and backups for a year[^2].
+
+    ## Notes
+
+    [^1]: See the **2024** retention policy.
+
+    [^2]: Longer for audited systems.
+
+    ## History
+    ");
+  }
+
   #[test]
   fn test_convert_legacy_note_block() {
     let input = r#"
@@ -214,6 +455,380 @@ mod tests {
     assert!(output.contains("[Example](https://example.com)"));
   }
 
+  #[test]
+  fn test_same_instance_links_render_like_page_links() {
+    let input = r#"
+      <p><a href="https://example.atlassian.net/wiki/spaces/DOCS/pages/123/Getting+Started">here</a></p>
+      <p><a href="https://example.atlassian.net/wiki/display/DOCS/Q%26A">https://example.atlassian.net/wiki/display/DOCS/Q%26A</a></p>
+      <p><a href="https://example.atlassian.net/wiki/pages/456">no slug</a></p>
+      <p><a href="https://other.example.com/wiki/spaces/DOCS/pages/123/Getting+Started">elsewhere</a></p>
+    "#;
+    let options = MarkdownOptions {
+      links: LinkContext::new("https://example.atlassian.net"),
+      ..Default::default()
+    };
+
+    let output = storage_to_markdown_with_options(input, &options).unwrap();
+    assert!(output.contains("[[Getting Started|here]]"));
+    assert!(output.contains("[[Q&A]]"));
+    assert!(output.contains("[no slug](https://example.atlassian.net/wiki/pages/456)"));
+    assert!(output.contains("[elsewhere](https://other.example.com/wiki/spaces/DOCS/pages/123/Getting+Started)"));
+  }
+
+  #[test]
+  fn test_unexported_page_links_follow_configured_style() {
+    use std::sync::Arc;
+
+    use crate::links::{ExportScope, UnexportedLinkStyle};
+
+    let input = r#"<p><ac:link><ri:page ri:content-title="Elsewhere" ri:space-key="OPS" /></ac:link></p>"#;
+    let links = LinkContext {
+      scope: Some(Arc::new(ExportScope::default())),
+      ..LinkContext::new("https://example.atlassian.net")
+    };
+
+    let render_with = |style| {
+      let options = MarkdownOptions {
+        links: LinkContext {
+          unexported_links: style,
+          ..links.clone()
+        },
+        ..Default::default()
+      };
+      storage_to_markdown_with_options(input, &options).unwrap()
+    };
+
+    assert_eq!(
+      render_with(UnexportedLinkStyle::Keep).trim(),
+      "[Elsewhere](https://example.atlassian.net/wiki/display/OPS/Elsewhere)"
+    );
+    assert_eq!(
+      render_with(UnexportedLinkStyle::Confluence).trim(),
+      "[Elsewhere (Confluence)](https://example.atlassian.net/wiki/display/OPS/Elsewhere)"
+    );
+    assert_eq!(
+      render_with(UnexportedLinkStyle::Annotate).trim(),
+      "Elsewhere _(not exported)_"
+    );
+  }
+
+  #[test]
+  fn test_max_heading_level_clamps_or_bolds_deep_headings() {
+    use crate::format::HeadingOverflow;
+
+    let input = "<h2>Setup</h2><h4>Linux</h4><h6>Notes</h6>";
+    let render = |heading_overflow| {
+      let options = MarkdownOptions {
+        max_heading_level: Some(3),
+        heading_overflow,
+        ..Default::default()
+      };
+      storage_to_markdown_with_options(input, &options).unwrap()
+    };
+
+    assert_eq!(
+      render(HeadingOverflow::Clamp).trim(),
+      "## Setup\n\n### Linux\n\n### Notes"
+    );
+    assert_eq!(
+      render(HeadingOverflow::Bold).trim(),
+      "## Setup\n\n**Linux**\n\n**Notes**"
+    );
+  }
+
+  #[test]
+  fn test_generate_toc_lists_page_headings() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="toc"><ac:parameter ac:name="maxLevel">2</ac:parameter></ac:structured-macro>"#,
+      "<h1>Overview</h1><h2>Getting started</h2><h3>Details</h3><h1>FAQ</h1>",
+    );
+    let options = MarkdownOptions {
+      generate_toc: true,
+      ..Default::default()
+    };
+
+    insta::assert_snapshot!(storage_to_markdown_with_options(input, &options).unwrap(), @r"
+    - [Overview](#overview)
+      - [Getting started](#getting-started)
+    - [FAQ](#faq)
+
+    # Overview
+
+    ## Getting started
+
+    ### Details
+
+    # FAQ
+    ");
+  }
+
+  #[test]
+  fn test_jira_keys_linked_outside_code_and_links() {
+    use crate::markdown::jira_keys::JiraLinker;
+
+    let input = concat!(
+      "<p>Tracked in <strong>OPS-42</strong> and <code>OPS-43</code>.</p>",
+      r#"<p><a href="https://example.com">OPS-44</a></p>"#,
+    );
+    let options = MarkdownOptions {
+      jira_links: Some(JiraLinker::new("https://jira.example.com", Vec::new())),
+      ..Default::default()
+    };
+
+    insta::assert_snapshot!(storage_to_markdown_with_options(input, &options).unwrap(), @r"
+    Tracked in **[OPS-42](https://jira.example.com/browse/OPS-42)** and `OPS-43`.
+
+    [OPS-44](https://example.com)
+    ");
+  }
+
+  #[test]
+  fn test_dialect_controls_markdown_syntax() {
+    use crate::format::{BulletMarker, EmphasisMarker, FenceStyle, HeadingStyle};
+
+    let input = concat!(
+      "<h1>Guide</h1><h3>Details</h3>",
+      "<p><strong>bold</strong> and <em>italic</em></p>",
+      "<ul><li>One</li><li>Two</li></ul>",
+      r#"<ac:structured-macro ac:name="code"><ac:parameter ac:name="language">md</ac:parameter>"#,
+      "<ac:plain-text-body><![CDATA[~~~\nnested\n~~~]]></ac:plain-text-body></ac:structured-macro>",
+    );
+    let options = MarkdownOptions {
+      dialect: MarkdownDialect {
+        heading_style: HeadingStyle::Setext,
+        bullet: BulletMarker::Asterisk,
+        emphasis: EmphasisMarker::Asterisk,
+        strong: EmphasisMarker::Underscore,
+        fence: FenceStyle::Tilde,
+      },
+      ..Default::default()
+    };
+
+    insta::assert_snapshot!(storage_to_markdown_with_options(input, &options).unwrap(), @r"
+    Guide
+    =====
+
+    ### Details
+
+    __bold__ and *italic*
+
+    * One
+    * Two
+
+    ~~~~md
+    ~~~
+    nested
+    ~~~
+    ~~~~
+    ");
+  }
+
+  #[test]
+  fn test_mdx_safe_escapes_prose_but_not_code() {
+    let input = r#"
+      <p>Use {name} or <code>{name}</code> when a &lt; b.</p>
+      <pre>fn main() { }</pre>
+      <p><span style="color: red">Red</span></p>
+    "#;
+    let options = MarkdownOptions {
+      mdx_safe: true,
+      ..Default::default()
+    };
+
+    let output = storage_to_markdown_with_options(input, &options).unwrap();
+    assert!(output.contains(r"Use \{name\} or `{name}` when a \< b."), "{output}");
+    assert!(output.contains("fn main() { }"));
+    assert!(!output.contains("<span"));
+  }
+
+  #[test]
+  fn test_docusaurus_admonitions() {
+    let input = r#"
+      <ac:structured-macro ac:name="warning">
+        <ac:parameter ac:name="title">Careful</ac:parameter>
+        <ac:rich-text-body><p>Mind the gap.</p></ac:rich-text-body>
+      </ac:structured-macro><ac:note><ac:rich-text-body><p>Legacy note.</p></ac:rich-text-body></ac:note>
+    "#;
+    let options = MarkdownOptions {
+      admonition_style: AdmonitionStyle::Docusaurus,
+      ..Default::default()
+    };
+
+    let output = storage_to_markdown_with_options(input, &options).unwrap();
+    insta::assert_snapshot!(output, @r"
+    :::warning Careful
+
+    Mind the gap.
+
+    :::
+
+    :::note
+
+    Legacy note.
+
+    :::
+    ");
+  }
+
+  #[test]
+  fn test_github_alert_admonitions() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="note"><ac:rich-text-body><p>Read first.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="info"><ac:rich-text-body><p>FYI.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="tip"><ac:rich-text-body><p>Try it.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="warning"><ac:rich-text-body><p>First.</p><p>Second.</p></ac:rich-text-body></ac:structured-macro>"#,
+    );
+    let options = MarkdownOptions {
+      admonition_style: AdmonitionStyle::GitHub,
+      ..Default::default()
+    };
+
+    let output = storage_to_markdown_with_options(input, &options).unwrap();
+    insta::assert_snapshot!(output, @r"
+    > [!NOTE]
+    > Read first.
+
+    > [!IMPORTANT]
+    > FYI.
+
+    > [!TIP]
+    > Try it.
+
+    > [!WARNING]
+    > First.
+    >
+    > Second.
+    ");
+  }
+
+  #[test]
+  fn test_page_properties_macros() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="details"><ac:parameter ac:name="id">project</ac:parameter><ac:rich-text-body>"#,
+      r#"<table><tbody><tr><th><p><strong>Owner</strong></p></th><td><p><ac:link><ri:user ri:account-id="abc" /></ac:link></p></td></tr>"#,
+      r#"<tr><th>Status</th><td><ac:structured-macro ac:name="status"><ac:parameter ac:name="title">In progress</ac:parameter></ac:structured-macro></td></tr>"#,
+      r#"<tr><th>Links</th><td><a href="https://example.com/spec">Spec</a><br/>v2</td></tr></tbody></table>"#,
+      r#"</ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="detailssummary"><ac:parameter ac:name="label">project, roadmap</ac:parameter>"#,
+      r#"<ac:parameter ac:name="cql">label = "project" and space = currentSpace()</ac:parameter></ac:structured-macro>"#,
+    );
+
+    let markdown = storage_to_markdown_with_options(input, &MarkdownOptions::default()).unwrap();
+    insta::assert_snapshot!(markdown, @r#"
+    | Property   | Value                                    |
+    | ---------- | ---------------------------------------- |
+    | **Owner**  | @user:abc                                |
+    | **Status** | `[In progress]`                          |
+    | **Links**  | [Spec](https://example.com/spec)<br />v2 |
+
+    > **Page properties report:** Dynamic content not exported.
+    >
+    > Labels: `project`, `roadmap`
+    >
+    > CQL: `label = "project" and space = currentSpace()`
+    "#);
+  }
+
+  #[test]
+  fn test_markdown_flavors() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="warning"><ac:parameter ac:name="title">Careful</ac:parameter>"#,
+      r#"<ac:rich-text-body><p>Mind the gap.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="tip"><ac:rich-text-body><p>Try it.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:task-list><ac:task><ac:task-status>complete</ac:task-status><ac:task-body>Ship</ac:task-body></ac:task></ac:task-list>"#,
+      r#"<p><ac:link><ri:page ri:content-title="Setup" /></ac:link></p>"#,
+    );
+    let render = |flavor, admonition_style| {
+      let options = MarkdownOptions {
+        flavor: Some(flavor),
+        admonition_style,
+        ..Default::default()
+      };
+      storage_to_markdown_with_options(input, &options).unwrap()
+    };
+
+    insta::assert_snapshot!(render(MarkdownFlavor::Gfm, AdmonitionStyle::GitHub), @r"
+    > [!WARNING]
+    > **Careful**
+    >
+    > Mind the gap.
+
+    > [!TIP]
+    > Try it.
+
+    - [x] Ship
+
+    Setup
+    ");
+    insta::assert_snapshot!(render(MarkdownFlavor::CommonMark, AdmonitionStyle::Blockquote), @r"
+    > **Careful:** Mind the gap.
+
+    > **Tip:** Try it.
+
+    - ☒ Ship
+
+    Setup
+    ");
+    insta::assert_snapshot!(render(MarkdownFlavor::Pandoc, AdmonitionStyle::PandocDiv), @r#"
+    ::: {.warning title="Careful"}
+    Mind the gap.
+    :::
+
+    ::: tip
+    Try it.
+    :::
+
+    - [x] Ship
+
+    [[Setup]]
+    "#);
+    insta::assert_snapshot!(render(MarkdownFlavor::Obsidian, AdmonitionStyle::Obsidian), @r"
+    > [!warning] Careful
+    > Mind the gap.
+
+    > [!tip]
+    > Try it.
+
+    - [x] Ship
+
+    [[Setup]]
+    ");
+  }
+
+  #[test]
+  fn test_pandoc_flavor_constructs() {
+    let input = concat!(
+      r#"<ac:structured-macro ac:name="panel"><ac:parameter ac:name="title">Scope</ac:parameter>"#,
+      r#"<ac:rich-text-body><p>In scope.</p></ac:rich-text-body></ac:structured-macro>"#,
+      "<dl><dt>API</dt><dd>Application programming interface</dd><dt>SDK</dt><dd>Software development kit</dd></dl>",
+      "<table><tbody><tr><th>Step</th><th>Details</th></tr>",
+      "<tr><td>Install</td><td><ul><li>Download</li><li>Unpack</li></ul></td></tr></tbody></table>",
+    );
+    let options = MarkdownOptions {
+      flavor: Some(MarkdownFlavor::Pandoc),
+      ..Default::default()
+    };
+
+    insta::assert_snapshot!(storage_to_markdown_with_options(input, &options).unwrap(), @r#"
+    ::: {.panel title="Scope"}
+    In scope.
+    :::
+
+    API
+    :   Application programming interface
+
+    SDK
+    :   Software development kit
+
+    +---------+------------+
+    | Step    | Details    |
+    +=========+============+
+    | Install | - Download |
+    |         | - Unpack   |
+    +---------+------------+
+    "#);
+  }
+
   #[test]
   fn test_anchor_macro_not_preserved_by_default() {
     let input = r#"
@@ -226,6 +841,18 @@ mod tests {
     assert!(!output.contains("<a id=\"my-anchor\"></a>"));
   }
 
+  #[test]
+  fn test_same_page_anchor_links_point_at_headings() {
+    let input = concat!(
+      r#"<p><ac:link ac:anchor="Known issues"><ac:plain-text-link-body><![CDATA[issues]]></ac:plain-text-link-body></ac:link>"#,
+      r##" and <a href="#Guide-KnownIssues">more</a></p><h2>Known issues</h2>"##,
+    );
+    assert_eq!(
+      render(input),
+      "[issues](#known-issues) and [more](#known-issues)\n\n## Known issues\n"
+    );
+  }
+
   #[test]
   fn test_anchor_macro_preserved_when_requested() {
     let input = r#"
@@ -263,6 +890,20 @@ mod tests {
     "###);
   }
 
+  #[test]
+  fn test_convert_task_list_with_assignee_and_due_date() {
+    let input = r#"
+      <ac:task-list>
+        <ac:task>
+          <ac:task-status>incomplete</ac:task-status>
+          <ac:task-body>Update the runbook <ac:link><ri:user ri:account-id="abc123" /></ac:link> <time datetime="2024-05-01" /></ac:task-body>
+        </ac:task>
+      </ac:task-list>
+    "#;
+    let output = render(input);
+    assert_eq!(output.trim(), "- [ ] Update the runbook @user:abc123 📅 2024-05-01");
+  }
+
   #[test]
   fn test_convert_image() {
     let input = r#"<ac:image ac:alt="test image"><ri:url ri:value="https://example.com/image.png" /></ac:image>"#;
@@ -270,6 +911,42 @@ mod tests {
     assert!(output.contains("![test image](https://example.com/image.png)"));
   }
 
+  #[test]
+  fn test_convert_image_size_and_caption() {
+    let input = concat!(
+      r#"<ac:image ac:align="center" ac:border="true" ac:width="480" ac:height="270" ac:alt="Login &quot;screen&quot;">"#,
+      r#"<ri:attachment ri:filename="login.png" /><ac:caption><p>The login screen</p></ac:caption></ac:image>"#,
+      r#"<ac:image ac:align="right" ac:width="64"><ri:attachment ri:filename="logo.png" /></ac:image>"#,
+    );
+    let render_with = |image_style, flavor| {
+      let options = MarkdownOptions {
+        image_style,
+        flavor,
+        ..Default::default()
+      };
+      storage_to_markdown_with_options(input, &options).unwrap()
+    };
+
+    insta::assert_snapshot!(render_with(ImageStyle::Markdown, None), @r#"
+    ![Login "screen"](login.png)
+
+    ![image](logo.png)
+    "#);
+    insta::assert_snapshot!(render_with(ImageStyle::Markdown, Some(MarkdownFlavor::Pandoc)), @r#"
+    ![Login "screen"](login.png){width=480px height=270px}
+
+    ![image](logo.png){width=64px}
+    "#);
+    insta::assert_snapshot!(render_with(ImageStyle::Html, None), @r#"
+    <figure align="center">
+    <img src="login.png" alt="Login &quot;screen&quot;" width="480" height="270" style="border: 1px solid" />
+    <figcaption>The login screen</figcaption>
+    </figure>
+
+    <img src="logo.png" alt="image" width="64" align="right" />
+    "#);
+  }
+
   #[test]
   fn test_convert_table() {
     let input = r#"
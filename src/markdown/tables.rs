@@ -1,17 +1,64 @@
 //! HTML table to Markdown table conversion.
 //!
 //! Converts Confluence HTML tables to properly formatted Markdown tables.
+//! Cells keep their inline formatting; tables whose cells hold block content
+//! such as lists or code blocks fall back to an HTML table. Cells spanning
+//! several rows or columns are handled according to
+//! [`MarkdownOptions::table_spans`]. Tables over the
+//! [`MarkdownOptions::table_split`] limits are cut down to a preview followed
+//! by a link to the full table's CSV file.
 
 use roxmltree::Node;
 use unicode_width::UnicodeWidthStr;
 
 use super::MarkdownOptions;
-use super::utils::{get_element_text, matches_tag};
+use super::elements::{convert_cell_lines, convert_node_to_markdown};
+use super::utils::{escape_inline, get_attribute, get_table_caption, get_table_summary, matches_tag, render_comment};
+use crate::format::{MarkdownFlavor, TableCaptionPosition, TableFormat, TableSpanMode};
+use crate::table_export::{TableSplit, table_path};
+
+/// Block-level elements that cannot be expressed inside a Markdown table cell.
+const BLOCK_TAGS: &[&str] = &[
+  "ul",
+  "ol",
+  "pre",
+  "table",
+  "blockquote",
+  "h1",
+  "h2",
+  "h3",
+  "h4",
+  "h5",
+  "h6",
+];
+
+/// Largest `colspan` or `rowspan` honoured; larger values are clamped.
+const MAX_SPAN: usize = 100;
+
+/// Macros that render as block content.
+const BLOCK_MACROS: &[&str] = &[
+  "code",
+  "code-block",
+  "noformat",
+  "info",
+  "note",
+  "tip",
+  "warning",
+  "panel",
+  "expand",
+];
 
 /// Convert an HTML table element into Markdown table syntax.
 ///
 /// Handles tables with `thead`, `tbody`, `tfoot` sections, or direct `tr`
 /// children. Automatically aligns columns and formats with consistent spacing.
+/// Cell content keeps its inline formatting, with pipes escaped and line
+/// breaks rendered as `<br />`. When any cell holds block content (lists, code
+/// blocks, nested tables), the table is rendered as HTML instead. Spanning
+/// cells either switch the table to HTML as well or are expanded into the
+/// Markdown grid, depending on [`MarkdownOptions::table_spans`].
+/// A `<caption>` is emitted as an italic line above or below the table and a
+/// `summary` attribute is kept as an HTML comment preceding it.
 ///
 /// # Arguments
 /// * `element` - The `<table>` node whose content should be rendered.
@@ -21,9 +68,112 @@ use super::utils::{get_element_text, matches_tag};
 /// A Markdown fragment beginning with a newline that contains the formatted
 /// table, or an empty string when the table has no meaningful content.
 pub fn convert_table_to_markdown(element: Node, options: &MarkdownOptions) -> String {
-  let mut rows: Vec<Vec<String>> = Vec::new();
+  let mut rows = table_rows(element);
+  let mut split_note = None;
+  if let Some(split) = &options.table_split {
+    let column_count = layout_grid(&rows, |_| String::new(), false)
+      .iter()
+      .map(Vec::len)
+      .max()
+      .unwrap_or_default();
+    if split.exceeded(rows.len(), column_count) {
+      split_note = Some(render_split_note(element, split, rows.len(), column_count, options));
+      rows.truncate(split.preview_rows() + 1);
+      if let Some(max_columns) = split.max_columns {
+        for row in &mut rows {
+          row.truncate(max_columns);
+        }
+      }
+    }
+  }
+
+  let has_spans = rows.iter().flatten().any(|cell| cell_span(*cell) != (1, 1));
+  let html_spans = has_spans && options.table_spans == TableSpanMode::Html;
+  let has_blocks = rows.iter().flatten().any(|cell| has_block_content(*cell));
+  let duplicate = options.table_spans == TableSpanMode::Duplicate;
+  let table = if has_blocks && !html_spans && options.flavor == Some(MarkdownFlavor::Pandoc) {
+    let grid = layout_grid(
+      &rows,
+      |cell| tidy_cell_markdown(&convert_node_to_markdown(cell, options)),
+      duplicate,
+    );
+    render_grid_table(grid)
+  } else if has_blocks || html_spans {
+    render_html_table(&rows, options)
+  } else {
+    let grid = layout_grid(&rows, |cell| render_cell(cell, options), duplicate);
+    render_markdown_table(grid, options.compact_tables)
+  };
+  let Some(table) = table else {
+    return String::new();
+  };
+
+  let mut result = String::new();
+  if let Some(summary) = get_table_summary(element) {
+    let comment = render_comment(&format!("Table summary: {summary}"), options);
+    result.push_str(&format!("\n{comment}\n"));
+  }
+
+  match (get_table_caption(element), options.table_caption_position) {
+    (Some(caption), TableCaptionPosition::Above) => {
+      result.push_str(&format!(
+        "\n{}\n",
+        options.dialect.emphasis(&escape_inline(&caption, options))
+      ));
+      result.push_str(&table);
+    }
+    (Some(caption), TableCaptionPosition::Below) => {
+      result.push_str(&table);
+      result.push_str(&format!(
+        "{}\n\n",
+        options.dialect.emphasis(&escape_inline(&caption, options))
+      ));
+    }
+    (None, _) => result.push_str(&table),
+  }
+  if let Some(note) = split_note {
+    result.push_str(&note);
+  }
+
+  result
+}
+
+/// Note below a split table's preview, linking to the CSV file holding the
+/// full table.
+fn render_split_note(
+  element: Node,
+  split: &TableSplit,
+  row_count: usize,
+  column_count: usize,
+  options: &MarkdownOptions,
+) -> String {
+  let data_rows = row_count.saturating_sub(1);
+  let mut shown = Vec::new();
+  if data_rows > split.preview_rows() {
+    shown.push(format!("{} of {data_rows} rows", split.preview_rows()));
+  }
+  if let Some(max_columns) = split.max_columns.filter(|max| column_count > *max) {
+    shown.push(format!("{max_columns} of {column_count} columns"));
+  }
+  let mut text = format!("Showing the first {}", shown.join(" and "));
+
+  // Tables are numbered like the extracted CSV files: every table with at
+  // least one row, in document order
+  let index = element
+    .document()
+    .descendants()
+    .filter(|node| matches_tag(*node, "table") && !table_rows(*node).is_empty())
+    .position(|node| node == element)
+    .unwrap_or_default();
+  let path = table_path(&split.page_filename, index + 1, TableFormat::Csv);
+  let path = path.to_string_lossy().replace('\\', "/");
+  let name = path.rsplit('/').next().unwrap_or(&path);
+  text.push_str(&format!(". Full table: [{name}]({})", path.replace(' ', "%20")));
+  format!("{}\n\n", options.dialect.emphasis(&text))
+}
 
-  // Collect all <tr> elements from the table
+/// Collect the non-empty rows of a table as lists of `<th>`/`<td>` nodes.
+pub(super) fn table_rows<'a, 'input>(element: Node<'a, 'input>) -> Vec<Vec<Node<'a, 'input>>> {
   // In HTML tables, rows are typically wrapped in <tbody>, <thead>, or <tfoot>
   let mut tr_elements = Vec::new();
 
@@ -39,29 +189,174 @@ pub fn convert_table_to_markdown(element: Node, options: &MarkdownOptions) -> St
     }
   }
 
-  // Process all collected rows
-  for tr in tr_elements {
-    let mut cells: Vec<String> = Vec::new();
-
-    for cell in tr
-      .children()
-      .filter(|child| matches_tag(*child, "th") || matches_tag(*child, "td"))
-    {
-      let text = get_element_text(cell)
-        .split_whitespace()
+  tr_elements
+    .into_iter()
+    .map(|tr| {
+      tr.children()
+        .filter(|child| matches_tag(*child, "th") || matches_tag(*child, "td"))
         .collect::<Vec<_>>()
-        .join(" ")
-        .trim()
-        .to_string();
-      cells.push(text);
+    })
+    .filter(|cells| !cells.is_empty())
+    .collect()
+}
+
+/// The `(colspan, rowspan)` of a cell, defaulting to 1 for missing or invalid
+/// values.
+fn cell_span(cell: Node) -> (usize, usize) {
+  let span = |attribute| {
+    get_attribute(cell, attribute)
+      .and_then(|value| value.trim().parse::<usize>().ok())
+      .map_or(1, |span| span.clamp(1, MAX_SPAN))
+  };
+  (span("colspan"), span("rowspan"))
+}
+
+/// Lay out a table as a rectangular grid of cell values.
+///
+/// Cells covered by a `colspan` or `rowspan` are left empty, so every value
+/// lines up with its column.
+///
+/// # Arguments
+/// * `element` - The `<table>` node to lay out.
+/// * `render` - Produces the value of a `<th>` or `<td>` node.
+///
+/// # Returns
+/// The rows of the table, all of the same length; empty when the table has no
+/// cells.
+pub fn table_grid(element: Node, render: impl Fn(Node) -> String) -> Vec<Vec<String>> {
+  let mut grid = layout_grid(&table_rows(element), render, false);
+  let column_count = grid.iter().map(Vec::len).max().unwrap_or_default();
+  for row in &mut grid {
+    row.resize(column_count, String::new());
+  }
+  grid
+}
+
+/// Lay out table rows as a grid of rendered cells.
+///
+/// Each cell is placed in the first column not already covered by a
+/// `rowspan` from a row above, and its spans are filled with copies of its
+/// content when `duplicate` is set or left empty otherwise. Row spans
+/// reaching past the last row are cut off.
+fn layout_grid(rows: &[Vec<Node>], render: impl Fn(Node) -> String, duplicate: bool) -> Vec<Vec<String>> {
+  let mut grid: Vec<Vec<Option<String>>> = vec![Vec::new(); rows.len()];
+
+  for (row_index, cells) in rows.iter().enumerate() {
+    let mut column = 0;
+    for cell in cells {
+      while grid[row_index].get(column).is_some_and(Option::is_some) {
+        column += 1;
+      }
+
+      let content = render(*cell);
+      let (colspan, rowspan) = cell_span(*cell);
+      for (offset, row) in grid[row_index..].iter_mut().take(rowspan).enumerate() {
+        if row.len() < column + colspan {
+          row.resize(column + colspan, None);
+        }
+        for (index, slot) in row[column..column + colspan].iter_mut().enumerate() {
+          let first = offset == 0 && index == 0;
+          *slot = Some(if first || duplicate {
+            content.clone()
+          } else {
+            String::new()
+          });
+        }
+      }
+      column += colspan;
+    }
+  }
+
+  grid
+    .into_iter()
+    .map(|row| row.into_iter().map(Option::unwrap_or_default).collect())
+    .collect()
+}
+
+/// Whether a cell contains content that a Markdown table cell cannot hold.
+pub(super) fn has_block_content(cell: Node) -> bool {
+  cell.descendants().skip(1).any(|node| {
+    BLOCK_TAGS.iter().any(|tag| matches_tag(node, tag))
+      || matches_tag(node, "ac:task-list")
+      || (matches_tag(node, "ac:structured-macro")
+        && get_attribute(node, "ac:name").is_some_and(|name| BLOCK_MACROS.contains(&name.as_str())))
+  })
+}
+
+/// Render a cell's inline content for a Markdown table row.
+pub(super) fn render_cell(cell: Node, options: &MarkdownOptions) -> String {
+  convert_cell_lines(cell, options)
+    .iter()
+    .map(|line| escape_pipes(line))
+    .collect::<Vec<_>>()
+    .join("<br />")
+}
+
+/// Escape `|` so it does not end the table cell.
+///
+/// Pipes already preceded by a backslash are left alone.
+fn escape_pipes(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  let mut previous = None;
+  for c in text.chars() {
+    if c == '|' && previous != Some('\\') {
+      escaped.push('\\');
     }
+    escaped.push(c);
+    previous = Some(c);
+  }
+  escaped
+}
+
+/// Render a table as HTML, converting each cell's content to Markdown.
+///
+/// Cell content is surrounded by blank lines so Markdown renderers parse it as
+/// Markdown rather than raw HTML. `colspan` and `rowspan` are kept.
+///
+/// # Returns
+/// `Some(String)` with the rendered table surrounded by newlines, or `None`
+/// when the table has no rows.
+fn render_html_table(rows: &[Vec<Node>], options: &MarkdownOptions) -> Option<String> {
+  if rows.is_empty() {
+    return None;
+  }
 
-    if !cells.is_empty() {
-      rows.push(cells);
+  let mut result = String::from("\n<table>\n");
+  for cells in rows {
+    result.push_str("<tr>\n");
+    for cell in cells {
+      let tag = cell.tag_name().name();
+      let (colspan, rowspan) = cell_span(*cell);
+      let mut attributes = String::new();
+      if colspan > 1 {
+        attributes.push_str(&format!(" colspan=\"{colspan}\""));
+      }
+      if rowspan > 1 {
+        attributes.push_str(&format!(" rowspan=\"{rowspan}\""));
+      }
+      let content = tidy_cell_markdown(&convert_node_to_markdown(*cell, options));
+      if content.is_empty() {
+        result.push_str(&format!("<{tag}{attributes}></{tag}>\n"));
+      } else {
+        result.push_str(&format!("<{tag}{attributes}>\n\n{content}\n\n</{tag}>\n"));
+      }
     }
+    result.push_str("</tr>\n");
   }
+  result.push_str("</table>\n\n");
+  Some(result)
+}
 
-  render_markdown_table(rows, options.compact_tables).unwrap_or_default()
+/// Trim converted cell Markdown and collapse runs of blank lines.
+fn tidy_cell_markdown(markdown: &str) -> String {
+  let mut lines: Vec<&str> = Vec::new();
+  for line in markdown.trim().lines().map(str::trim_end) {
+    if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+      continue;
+    }
+    lines.push(line);
+  }
+  lines.join("\n")
 }
 
 /// Pretty-print Markdown tables with optional column padding.
@@ -129,6 +424,54 @@ pub fn render_markdown_table(mut rows: Vec<Vec<String>>, compact: bool) -> Optio
   Some(result)
 }
 
+/// Render rows as a Pandoc grid table, whose cells can hold block content.
+///
+/// The first row is the header. Each cell's lines are padded to the widest
+/// line of its column, and rows are as tall as their tallest cell.
+///
+/// # Returns
+/// `Some(String)` with the table surrounded by newlines, or `None` when the
+/// rows have no cells.
+fn render_grid_table(mut rows: Vec<Vec<String>>) -> Option<String> {
+  let column_count = rows.iter().map(Vec::len).max().filter(|count| *count > 0)?;
+  for row in &mut rows {
+    row.resize(column_count, String::new());
+  }
+
+  let mut column_widths = vec![3; column_count];
+  for row in &rows {
+    for (index, cell) in row.iter().enumerate() {
+      let widest = cell.lines().map(cell_display_width).max().unwrap_or_default();
+      column_widths[index] = column_widths[index].max(widest);
+    }
+  }
+  let border = |fill: &str| {
+    let segments: Vec<String> = column_widths.iter().map(|width| fill.repeat(width + 2)).collect();
+    format!("+{}+\n", segments.join("+"))
+  };
+
+  let mut result = String::from("\n");
+  result.push_str(&border("-"));
+  for (row_index, row) in rows.iter().enumerate() {
+    let cell_lines: Vec<Vec<&str>> = row.iter().map(|cell| cell.lines().collect()).collect();
+    let height = cell_lines.iter().map(Vec::len).max().unwrap_or_default().max(1);
+    for line_index in 0..height {
+      result.push('|');
+      for (lines, width) in cell_lines.iter().zip(&column_widths) {
+        let line = lines.get(line_index).copied().unwrap_or_default();
+        result.push(' ');
+        result.push_str(line);
+        result.push_str(&" ".repeat(width - cell_display_width(line)));
+        result.push_str(" |");
+      }
+      result.push('\n');
+    }
+    result.push_str(&border(if row_index == 0 { "=" } else { "-" }));
+  }
+  result.push('\n');
+  Some(result)
+}
+
 /// Format a single table row with proper column alignment.
 ///
 /// # Arguments
@@ -160,6 +503,11 @@ fn format_row(row: &[String], column_widths: &[usize], compact: bool) -> String
   line
 }
 
+/// Measure the rendered width of a cell, accounting for wide characters.
+fn cell_display_width(cell: &str) -> usize {
+  UnicodeWidthStr::width(cell)
+}
+
 #[cfg(test)]
 mod tests {
   use roxmltree::Document;
@@ -214,6 +562,38 @@ mod tests {
     ");
   }
 
+  #[test]
+  fn test_convert_table_split() {
+    let input = r#"
+      <table>
+        <tr><th>Name</th><th>Region</th><th>Owner</th></tr>
+        <tr><td>alpha</td><td>eu</td><td>ops</td></tr>
+        <tr><td>beta</td><td>us</td><td>dev</td></tr>
+        <tr><td>gamma</td><td>ap</td><td>qa</td></tr>
+      </table>
+    "#;
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let table = document.descendants().find(|node| matches_tag(*node, "table")).unwrap();
+    let options = MarkdownOptions {
+      table_split: Some(TableSplit {
+        max_rows: Some(2),
+        max_columns: Some(2),
+        page_filename: "Service Catalog".to_string(),
+      }),
+      ..Default::default()
+    };
+    let output = convert_table_to_markdown(table, &options);
+    insta::assert_snapshot!(output, @r"
+    | Name  | Region |
+    | ----- | ------ |
+    | alpha | eu     |
+    | beta  | us     |
+
+    _Showing the first 2 of 3 rows and 2 of 3 columns. Full table: [Service Catalog-1.csv](tables/Service%20Catalog-1.csv)_
+    ");
+  }
+
   #[test]
   fn test_convert_table_empty() {
     let input = "<table></table>";
@@ -226,6 +606,194 @@ mod tests {
     assert!(!output.contains("|"));
   }
 
+  #[test]
+  fn test_convert_table_caption_and_summary() {
+    let input = r#"
+      <table summary="Quarterly totals --- per region">
+        <caption>Regional   sales_2024 *draft*</caption>
+        <tr><th>Region</th><th>Total</th></tr>
+        <tr><td>North</td><td>42</td></tr>
+      </table>
+    "#;
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let table = document.descendants().find(|node| matches_tag(*node, "table")).unwrap();
+
+    let output = convert_table_to_markdown(table, &MarkdownOptions::default());
+    insta::assert_snapshot!(output, @r"
+    <!-- Table summary: Quarterly totals - - - per region -->
+
+    _Regional sales\_2024 \*draft\*_
+
+    | Region | Total |
+    | ------ | ----- |
+    | North  | 42    |
+    ");
+
+    let options = MarkdownOptions {
+      table_caption_position: TableCaptionPosition::Below,
+      ..Default::default()
+    };
+    let output = convert_table_to_markdown(table, &options);
+    insta::assert_snapshot!(output, @r"
+    <!-- Table summary: Quarterly totals - - - per region -->
+
+    | Region | Total |
+    | ------ | ----- |
+    | North  | 42    |
+
+    _Regional sales\_2024 \*draft\*_
+    ");
+  }
+
+  #[test]
+  fn test_convert_table_keeps_inline_formatting() {
+    let input = concat!(
+      r#"<table><tr><th>Name</th><th>Details</th></tr>"#,
+      r#"<tr><td><a href="https://example.com">Site</a></td>"#,
+      r#"<td><p>Run <code>a | b</code> <strong>first</strong></p><p>then<br/>rest</p></td></tr></table>"#,
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let table = document.descendants().find(|node| matches_tag(*node, "table")).unwrap();
+
+    let output = convert_table_to_markdown(table, &MarkdownOptions::default());
+    insta::assert_snapshot!(output, @r"
+    | Name                        | Details                                    |
+    | --------------------------- | ------------------------------------------ |
+    | [Site](https://example.com) | Run `a \| b` **first**<br />then<br />rest |
+    ");
+  }
+
+  #[test]
+  fn test_convert_table_with_block_content_falls_back_to_html() {
+    let input = concat!(
+      r#"<table><tr><th>Step</th><th>Notes</th></tr>"#,
+      r#"<tr><td>Install</td><td><ul><li>Download</li><li>Unpack</li></ul></td></tr>"#,
+      r#"<tr><td><em>Verify</em></td><td></td></tr></table>"#,
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let table = document.descendants().find(|node| matches_tag(*node, "table")).unwrap();
+
+    let output = convert_table_to_markdown(table, &MarkdownOptions::default());
+    insta::assert_snapshot!(output, @r"
+    <table>
+    <tr>
+    <th>
+
+    Step
+
+    </th>
+    <th>
+
+    Notes
+
+    </th>
+    </tr>
+    <tr>
+    <td>
+
+    Install
+
+    </td>
+    <td>
+
+    - Download
+    - Unpack
+
+    </td>
+    </tr>
+    <tr>
+    <td>
+
+    _Verify_
+
+    </td>
+    <td></td>
+    </tr>
+    </table>
+    ");
+  }
+
+  #[test]
+  fn test_convert_table_with_spans() {
+    let input = concat!(
+      r#"<table><tr><th colspan="2">Region</th><th>Total</th></tr>"#,
+      r#"<tr><td rowspan="2">North</td><td>East</td><td>4</td></tr>"#,
+      r#"<tr><td>West</td><td>2</td></tr></table>"#,
+    );
+    let wrapped = wrap_with_namespaces(input);
+    let document = Document::parse(&wrapped).unwrap();
+    let table = document.descendants().find(|node| matches_tag(*node, "table")).unwrap();
+
+    let render = |table_spans| {
+      let options = MarkdownOptions {
+        table_spans,
+        ..Default::default()
+      };
+      convert_table_to_markdown(table, &options)
+    };
+
+    insta::assert_snapshot!(render(TableSpanMode::Duplicate), @r"
+    | Region | Region | Total |
+    | ------ | ------ | ----- |
+    | North  | East   | 4     |
+    | North  | West   | 2     |
+    ");
+    insta::assert_snapshot!(render(TableSpanMode::Pad), @r"
+    | Region |      | Total |
+    | ------ | ---- | ----- |
+    | North  | East | 4     |
+    |        | West | 2     |
+    ");
+    insta::assert_snapshot!(render(TableSpanMode::Html), @r#"
+    <table>
+    <tr>
+    <th colspan="2">
+
+    Region
+
+    </th>
+    <th>
+
+    Total
+
+    </th>
+    </tr>
+    <tr>
+    <td rowspan="2">
+
+    North
+
+    </td>
+    <td>
+
+    East
+
+    </td>
+    <td>
+
+    4
+
+    </td>
+    </tr>
+    <tr>
+    <td>
+
+    West
+
+    </td>
+    <td>
+
+    2
+
+    </td>
+    </tr>
+    </table>
+    "#);
+  }
+
   #[test]
   fn test_render_table_handles_emojis() {
     assert_eq!(cell_display_width("😀"), 2);
@@ -245,6 +813,3 @@ mod tests {
     "###);
   }
 }
-fn cell_display_width(cell: &str) -> usize {
-  UnicodeWidthStr::width(cell)
-}
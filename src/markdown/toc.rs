@@ -0,0 +1,320 @@
+//! Table of contents generated from the converted page.
+//!
+//! Headings are only known once the whole page is converted, so with
+//! [`MarkdownOptions::generate_toc`](super::MarkdownOptions::generate_toc) the
+//! `toc` macro leaves a [`placeholder`] recording its parameters, and
+//! [`expand`] replaces every placeholder with links to the page's headings in
+//! a second pass over the Markdown.
+
+use std::collections::HashMap;
+
+use crate::format::HeadingStyle;
+use crate::markdown::MarkdownDialect;
+
+/// Prefix of the comment standing in for a table of contents.
+const PLACEHOLDER_PREFIX: &str = "<!-- confluence-dl:toc ";
+
+/// Parameters of a `toc` macro.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TocSettings {
+  /// Shallowest heading level listed.
+  pub min_level: usize,
+  /// Deepest heading level listed.
+  pub max_level: usize,
+  /// Render the entries on one line instead of as a nested list.
+  pub flat: bool,
+  /// Separator style of a flat table of contents: `brackets`, `braces`,
+  /// `parens`, `pipe`, or literal separator text.
+  pub separator: String,
+}
+
+impl Default for TocSettings {
+  fn default() -> Self {
+    Self {
+      min_level: 1,
+      max_level: 6,
+      flat: false,
+      separator: "brackets".to_string(),
+    }
+  }
+}
+
+/// A heading found in converted Markdown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Heading {
+  pub level: usize,
+  /// Heading text without Markdown markup.
+  pub text: String,
+  /// Anchor of the heading, made unique with a `-1`, `-2`, ... suffix.
+  pub slug: String,
+}
+
+/// The comment a `toc` macro is rendered as until [`expand`] runs.
+pub(crate) fn placeholder(settings: &TocSettings) -> String {
+  format!(
+    "{PLACEHOLDER_PREFIX}min={} max={} type={} separator={} -->",
+    settings.min_level,
+    settings.max_level,
+    if settings.flat { "flat" } else { "list" },
+    settings.separator.replace(' ', "%20"),
+  )
+}
+
+/// Replace table of contents placeholders with links to the headings of
+/// `markdown`.
+///
+/// # Arguments
+/// * `markdown` - Converted page content.
+/// * `dialect` - Syntax the headings were written in and lists are written with.
+///
+/// # Returns
+/// The content with every placeholder replaced; placeholders for which no
+/// heading is in range are removed.
+pub(crate) fn expand(markdown: &str, dialect: &MarkdownDialect) -> String {
+  if !markdown.contains(PLACEHOLDER_PREFIX) {
+    return markdown.to_string();
+  }
+
+  let headings = collect_headings(markdown, dialect.heading_style);
+  let mut result = String::with_capacity(markdown.len());
+  for line in markdown.split_inclusive('\n') {
+    match parse_placeholder(line.trim()) {
+      Some(settings) => result.push_str(&render(&headings, &settings, dialect)),
+      None => result.push_str(line),
+    }
+  }
+  result
+}
+
+/// Anchor GitHub generates for a heading: lowercase, punctuation removed, and
+/// spaces replaced with hyphens.
+pub(crate) fn heading_slug(text: &str) -> String {
+  plain_text(text)
+    .trim()
+    .to_lowercase()
+    .chars()
+    .filter_map(|c| match c {
+      ' ' => Some('-'),
+      c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+      _ => None,
+    })
+    .collect()
+}
+
+fn parse_placeholder(line: &str) -> Option<TocSettings> {
+  let params = line.strip_prefix(PLACEHOLDER_PREFIX)?.strip_suffix("-->")?;
+  let mut settings = TocSettings::default();
+  for param in params.split_whitespace() {
+    match param.split_once('=')? {
+      ("min", value) => settings.min_level = value.parse().ok()?,
+      ("max", value) => settings.max_level = value.parse().ok()?,
+      ("type", value) => settings.flat = value == "flat",
+      ("separator", value) => settings.separator = value.replace("%20", " "),
+      _ => {}
+    }
+  }
+  Some(settings)
+}
+
+/// Headings of the page in document order, skipping fenced code blocks.
+pub(super) fn collect_headings(markdown: &str, style: HeadingStyle) -> Vec<Heading> {
+  let mut headings = Vec::new();
+  let mut slugs: HashMap<String, usize> = HashMap::new();
+  let mut fence: Option<String> = None;
+  let mut previous: Option<&str> = None;
+
+  for line in markdown.lines() {
+    let trimmed = line.trim_start();
+    if let Some(open) = &fence {
+      if trimmed.starts_with(open.as_str()) {
+        fence = None;
+      }
+      previous = None;
+      continue;
+    }
+    let marker: String = trimmed.chars().take_while(|c| matches!(c, '`' | '~')).collect();
+    if marker.len() >= 3 {
+      fence = Some(marker);
+      previous = None;
+      continue;
+    }
+
+    let heading = atx_heading(line).or_else(|| {
+      let text = previous.filter(|_| style == HeadingStyle::Setext)?;
+      setext_level(line).map(|level| (level, text.trim()))
+    });
+    if let Some((level, text)) = heading {
+      let base = heading_slug(text);
+      let count = slugs.entry(base.clone()).or_default();
+      let slug = if *count == 0 { base } else { format!("{base}-{count}") };
+      *count += 1;
+      headings.push(Heading {
+        level,
+        text: plain_text(text).trim().to_string(),
+        slug,
+      });
+      previous = None;
+    } else {
+      previous = (!line.trim().is_empty()).then_some(line);
+    }
+  }
+  headings
+}
+
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+  let level = line.chars().take_while(|&c| c == '#').count();
+  if !(1..=6).contains(&level) {
+    return None;
+  }
+  let text = line[level..].strip_prefix(' ')?;
+  Some((level, text.trim()))
+}
+
+fn setext_level(line: &str) -> Option<usize> {
+  let line = line.trim_end();
+  if line.len() >= 3 && line.chars().all(|c| c == '=') {
+    Some(1)
+  } else if line.len() >= 3 && line.chars().all(|c| c == '-') {
+    Some(2)
+  } else {
+    None
+  }
+}
+
+fn render(headings: &[Heading], settings: &TocSettings, dialect: &MarkdownDialect) -> String {
+  let selected: Vec<&Heading> = headings
+    .iter()
+    .filter(|heading| (settings.min_level..=settings.max_level).contains(&heading.level))
+    .collect();
+  if selected.is_empty() {
+    return String::new();
+  }
+
+  let link = |heading: &Heading| format!("[{}](#{})", heading.text, heading.slug);
+  if settings.flat {
+    let entries: Vec<String> = selected.iter().map(|heading| link(heading)).collect();
+    let wrapped = |open: &str, close: &str| {
+      let wrapped: Vec<String> = entries.iter().map(|entry| format!("{open} {entry} {close}")).collect();
+      wrapped.join(" ")
+    };
+    let line = match settings.separator.as_str() {
+      "brackets" => wrapped("\\[", "\\]"),
+      "braces" => wrapped("{", "}"),
+      "parens" => wrapped("(", ")"),
+      "pipe" => entries.join(" | "),
+      custom => entries.join(&format!(" {} ", custom.trim())),
+    };
+    return format!("{line}\n");
+  }
+
+  let mut result = String::new();
+  let mut open_levels: Vec<usize> = Vec::new();
+  for heading in selected {
+    while open_levels.last().is_some_and(|&level| level >= heading.level) {
+      open_levels.pop();
+    }
+    result.push_str(&"  ".repeat(open_levels.len()));
+    result.push_str(&dialect.bullet());
+    result.push_str(&link(heading));
+    result.push('\n');
+    open_levels.push(heading.level);
+  }
+  result
+}
+
+/// Heading text without links, inline HTML, or emphasis markers.
+fn plain_text(text: &str) -> String {
+  let mut result = String::with_capacity(text.len());
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      '<' => {
+        for next in chars.by_ref() {
+          if next == '>' {
+            break;
+          }
+        }
+      }
+      ']' if chars.peek() == Some(&'(') => {
+        for next in chars.by_ref() {
+          if next == ')' {
+            break;
+          }
+        }
+      }
+      '[' | '*' | '`' | '\\' => {}
+      '_' if result.is_empty() || chars.peek().is_none_or(|next| !next.is_alphanumeric()) => {}
+      c => result.push(c),
+    }
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_expand_nested_list() {
+    let settings = TocSettings {
+      min_level: 2,
+      max_level: 3,
+      ..Default::default()
+    };
+    let markdown = format!(
+      "# Guide\n\n{}\n\n## Install\n\n### On *macOS*\n\n```\n## not a heading\n```\n\n#### Deep\n\n## Install\n\n## [Links](https://example.com)\n",
+      placeholder(&settings)
+    );
+
+    insta::assert_snapshot!(expand(&markdown, &MarkdownDialect::default()), @r"
+    # Guide
+
+    - [Install](#install)
+      - [On macOS](#on-macos)
+    - [Install](#install-1)
+    - [Links](#links)
+
+    ## Install
+
+    ### On *macOS*
+
+    ```
+    ## not a heading
+    ```
+
+    #### Deep
+
+    ## Install
+
+    ## [Links](https://example.com)
+    ");
+  }
+
+  #[test]
+  fn test_expand_flat_and_setext() {
+    let dialect = MarkdownDialect {
+      heading_style: HeadingStyle::Setext,
+      ..Default::default()
+    };
+    let flat = TocSettings {
+      flat: true,
+      separator: "pipe".to_string(),
+      ..Default::default()
+    };
+    let markdown = format!(
+      "{}\n\nIntro\n=====\n\nUsage & Setup\n-------------\n",
+      placeholder(&flat)
+    );
+
+    assert_eq!(
+      expand(&markdown, &dialect).lines().next(),
+      Some("[Intro](#intro) | [Usage & Setup](#usage--setup)")
+    );
+  }
+
+  #[test]
+  fn test_expand_removes_empty_toc() {
+    let markdown = format!("{}\n\nNo headings.\n", placeholder(&TocSettings::default()));
+    assert_eq!(expand(&markdown, &MarkdownDialect::default()), "\nNo headings.\n");
+  }
+}
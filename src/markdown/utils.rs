@@ -8,6 +8,7 @@ use std::collections::BTreeSet;
 use roxmltree::Node;
 
 use super::emoji::{convert_emoji_to_markdown, convert_span_emoji};
+use super::{EmojiStyle, MarkdownOptions};
 
 /// Synthetic namespace base URL for Confluence namespaces.
 pub const SYNTHETIC_NS_BASE: &str = "https://confluence.example/";
@@ -47,12 +48,12 @@ fn collect_element_text(node: Node, detect_inline_emoji: bool) -> String {
       roxmltree::NodeType::Element => {
         if detect_inline_emoji {
           if matches_tag(child, "ac:emoji") || matches_tag(child, "ac:emoticon") {
-            text.push_str(&convert_emoji_to_markdown(child));
+            text.push_str(&convert_emoji_to_markdown(child, EmojiStyle::Unicode));
             continue;
           }
 
           if child.tag_name().name() == "span"
-            && let Some(emoji) = convert_span_emoji(child)
+            && let Some(emoji) = convert_span_emoji(child, EmojiStyle::Unicode)
           {
             text.push_str(&emoji);
             continue;
@@ -277,6 +278,216 @@ pub fn find_child_by_tag_and_attr<'a, 'input>(
     .find(|child| matches_tag(*child, tag_name) && get_attribute(*child, attr_name).as_deref() == Some(attr_value))
 }
 
+/// Extracts the normalized `<caption>` text of a table element.
+///
+/// # Arguments
+/// * `table` - The `<table>` node to inspect.
+///
+/// # Returns
+/// `Some(String)` with whitespace collapsed when the table has a non-empty
+/// caption, otherwise `None`.
+pub fn get_table_caption(table: Node) -> Option<String> {
+  let caption = find_child_by_tag(table, "caption")?;
+  let text = get_element_text(caption)
+    .split_whitespace()
+    .collect::<Vec<_>>()
+    .join(" ");
+  (!text.is_empty()).then_some(text)
+}
+
+/// Extracts the normalized `summary` attribute of a table element.
+///
+/// The attribute is obsolete in HTML5 but still emitted by older Confluence
+/// editors and carries accessibility information worth preserving.
+///
+/// # Arguments
+/// * `table` - The `<table>` node to inspect.
+///
+/// # Returns
+/// `Some(String)` with whitespace collapsed when the summary is non-empty,
+/// otherwise `None`.
+pub fn get_table_summary(table: Node) -> Option<String> {
+  let summary = get_attribute(table, "summary")?;
+  let text = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+  (!text.is_empty()).then_some(text)
+}
+
+/// A diagram drawn with a Confluence app and stored as page attachments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramReference {
+  /// Attachment name of the diagram source (draw.io XML or Gliffy JSON).
+  pub name: String,
+  /// Human-readable name of the diagramming app.
+  pub tool: &'static str,
+}
+
+impl DiagramReference {
+  /// Attachment name of the PNG export the app keeps next to the source.
+  pub fn preview_filename(&self) -> String {
+    format!("{}.png", self.name)
+  }
+}
+
+/// Reads the diagram attachment referenced by a `drawio` or `gliffy` macro.
+///
+/// # Arguments
+/// * `element` - The `<ac:structured-macro>` node to inspect.
+///
+/// # Returns
+/// `Some(DiagramReference)` for diagram macros naming their diagram,
+/// otherwise `None`.
+pub fn get_diagram_reference(element: Node) -> Option<DiagramReference> {
+  let (parameter, tool) = match get_attribute(element, "ac:name")?.as_str() {
+    "drawio" => ("diagramName", "draw.io"),
+    "gliffy" => ("name", "Gliffy"),
+    _ => return None,
+  };
+  let name = find_child_by_tag_and_attr(element, "ac:parameter", "ac:name", parameter)
+    .map(get_element_text)
+    .map(|name| name.trim().to_string())
+    .filter(|name| !name.is_empty())?;
+
+  Some(DiagramReference { name, tool })
+}
+
+/// Escapes prose text according to the active Markdown options.
+///
+/// In MDX-safe mode, `{`, `}`, and `<` are backslash-escaped so MDX does not
+/// parse them as expressions or JSX tags. Otherwise the text is returned
+/// unchanged.
+///
+/// # Arguments
+/// * `text` - Decoded prose text.
+/// * `options` - Conversion options controlling escaping.
+///
+/// # Returns
+/// The text, escaped when required.
+pub fn escape_text(text: &str, options: &MarkdownOptions) -> String {
+  if !options.mdx_safe {
+    return text.to_string();
+  }
+
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(c, '{' | '}' | '<') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Escapes plain text placed inside inline Markdown markup, such as an
+/// emphasized caption.
+///
+/// Backslashes, emphasis markers, backticks, and brackets are
+/// backslash-escaped so the text cannot end the surrounding markup or start
+/// new markup; the result is then escaped like [`escape_text`].
+///
+/// # Arguments
+/// * `text` - Decoded plain text.
+/// * `options` - Conversion options controlling escaping.
+///
+/// # Returns
+/// The escaped text.
+pub fn escape_inline(text: &str, options: &MarkdownOptions) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']') {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escape_text(&escaped, options)
+}
+
+/// Escapes text for an HTML attribute value or element content.
+///
+/// # Arguments
+/// * `text` - Plain text to place inside HTML markup.
+///
+/// # Returns
+/// The text with `&`, `"`, `<`, and `>` replaced by character references.
+pub fn escape_attribute(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('"', "&quot;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+/// Describes a structured macro for fidelity-audit comments.
+///
+/// # Arguments
+/// * `element` - The `<ac:structured-macro>` node to describe.
+///
+/// # Returns
+/// Text such as `confluence-macro: code language=rust title=main.rs`, listing
+/// every parameter with whitespace collapsed and long values shortened.
+pub fn describe_macro(element: Node) -> String {
+  const MAX_VALUE_CHARS: usize = 60;
+
+  let name = get_attribute(element, "ac:name").unwrap_or_default();
+  let mut description = format!("confluence-macro: {name}");
+  for parameter in element.children().filter(|child| matches_tag(*child, "ac:parameter")) {
+    let key = get_attribute(parameter, "ac:name").unwrap_or_default();
+    let value = get_element_text(parameter)
+      .split_whitespace()
+      .collect::<Vec<_>>()
+      .join(" ");
+    let value = if value.chars().count() > MAX_VALUE_CHARS {
+      format!("{}…", value.chars().take(MAX_VALUE_CHARS).collect::<String>())
+    } else {
+      value
+    };
+    if key.is_empty() {
+      description.push_str(&format!(" {value}"));
+    } else {
+      description.push_str(&format!(" {key}={value}"));
+    }
+  }
+  description
+}
+
+/// Appends a comment after converted output, keeping block output on its own
+/// lines and inline output inline.
+///
+/// # Arguments
+/// * `rendered` - Converted output of the annotated element.
+/// * `comment` - Fully formatted comment to append.
+pub fn append_annotation(rendered: String, comment: &str) -> String {
+  if rendered.ends_with('\n') {
+    format!("{}\n{comment}\n\n", rendered.trim_end_matches('\n'))
+  } else {
+    format!("{rendered}{comment}")
+  }
+}
+
+/// Renders a comment that is invisible in the rendered document.
+///
+/// MDX rejects HTML comments, so MDX-safe output uses a JSX expression
+/// comment instead.
+///
+/// # Arguments
+/// * `text` - Comment body; `--` and `*/` sequences are defused.
+/// * `options` - Conversion options selecting the comment syntax.
+///
+/// # Returns
+/// The comment without surrounding newlines.
+pub fn render_comment(text: &str, options: &MarkdownOptions) -> String {
+  let (delimiter, defused) = if options.mdx_safe { ("*/", "* /") } else { ("--", "- -") };
+  let mut text = text.to_string();
+  // A single pass leaves `--` behind in runs such as `---`
+  while text.contains(delimiter) {
+    text = text.replace(delimiter, defused);
+  }
+  if options.mdx_safe {
+    format!("{{/* {text} */}}")
+  } else {
+    format!("<!-- {text} -->")
+  }
+}
+
 /// Clean up the markdown output for more predictable downstream processing.
 ///
 /// - Removes excessive blank lines (more than 2 consecutive)
@@ -313,6 +524,29 @@ mod tests {
 
   use super::*;
 
+  #[test]
+  fn test_escape_text_mdx_safe() {
+    let options = MarkdownOptions {
+      mdx_safe: true,
+      ..Default::default()
+    };
+    assert_eq!(escape_text("a {b} <c>", &options), "a \\{b\\} \\<c>");
+    assert_eq!(escape_text("a {b} <c>", &MarkdownOptions::default()), "a {b} <c>");
+  }
+
+  #[test]
+  fn test_render_comment_syntax() {
+    let options = MarkdownOptions {
+      mdx_safe: true,
+      ..Default::default()
+    };
+    assert_eq!(
+      render_comment("a -- b", &MarkdownOptions::default()),
+      "<!-- a - - b -->"
+    );
+    assert_eq!(render_comment("a */ b", &options), "{/* a * / b */}");
+  }
+
   #[test]
   fn test_clean_markdown_removes_excessive_newlines() {
     let input = "Line 1\n\n\n\n\nLine 2";
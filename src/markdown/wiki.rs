@@ -0,0 +1,620 @@
+//! Legacy Confluence wiki markup conversion.
+//!
+//! Pages last edited before Confluence switched to the XHTML storage format
+//! may still return their body only as wiki markup (`h1. Title`, `* item`,
+//! `[text|url]`, `{code}`). Rather than exporting those pages as empty files,
+//! [`wiki_to_markdown`] converts the common constructs: headings, lists,
+//! links, code blocks and monospace text, bold, italic and struck-through
+//! text, quotes, rules, simple tables, and images. Other macros are dropped,
+//! keeping their content.
+
+use crate::images::ImageReference;
+use crate::markdown::MarkdownDialect;
+
+/// Schemes of link targets that point outside Confluence.
+const EXTERNAL_SCHEMES: [&str; 5] = ["http://", "https://", "mailto:", "ftp://", "file:"];
+
+/// Convert legacy wiki markup to Markdown.
+///
+/// # Arguments
+/// * `wiki` - Wiki markup of the page.
+/// * `dialect` - Syntax for headings, lists, emphasis, and code fences.
+///
+/// # Returns
+/// The Markdown document, ending with a newline unless it is empty.
+pub fn wiki_to_markdown(wiki: &str, dialect: &MarkdownDialect) -> String {
+  let mut converter = Converter::new(dialect);
+  converter.convert(wiki);
+  converter.finish()
+}
+
+/// Attachment images embedded in legacy wiki markup, in document order.
+///
+/// Images referenced by URL are not included, as they are not attachments.
+pub fn wiki_image_references(wiki: &str) -> Vec<ImageReference> {
+  let dialect = MarkdownDialect::default();
+  let mut converter = Converter::new(&dialect);
+  converter.convert(wiki);
+  converter.images
+}
+
+/// A block being accumulated from consecutive lines.
+enum OpenBlock {
+  None,
+  Paragraph(Vec<String>),
+  List(Vec<String>, Vec<ListLevel>),
+  Table(Vec<Vec<String>>),
+}
+
+/// An open level of a nested list.
+struct ListLevel {
+  ordered: bool,
+  counter: usize,
+  /// Width of the item prefix, which nested items are indented by.
+  width: usize,
+}
+
+struct Converter<'a> {
+  dialect: &'a MarkdownDialect,
+  blocks: Vec<String>,
+  open: OpenBlock,
+  images: Vec<ImageReference>,
+}
+
+impl<'a> Converter<'a> {
+  fn new(dialect: &'a MarkdownDialect) -> Self {
+    Self {
+      dialect,
+      blocks: Vec::new(),
+      open: OpenBlock::None,
+      images: Vec::new(),
+    }
+  }
+
+  fn convert(&mut self, wiki: &str) {
+    let mut lines = wiki.lines();
+    while let Some(line) = lines.next() {
+      let trimmed = line.trim();
+
+      if let Some((tag, rest)) = code_block_start(trimmed) {
+        self.flush();
+        let language = code_language(tag);
+        let code = collect_code_block(tag, rest, &mut lines);
+        let fence = self.dialect.fence(&code);
+        let body = if code.is_empty() {
+          String::new()
+        } else {
+          format!("{code}\n")
+        };
+        self.blocks.push(format!("{fence}{language}\n{body}{fence}"));
+      } else if trimmed.is_empty() {
+        self.flush();
+      } else if let Some((level, text)) = heading(trimmed) {
+        self.flush();
+        let text = self.inline(text);
+        if !text.is_empty() {
+          self.blocks.push(self.dialect.heading(level, &text));
+        }
+      } else if trimmed.len() >= 4 && trimmed.chars().all(|c| c == '-') {
+        self.flush();
+        self.blocks.push("---".to_string());
+      } else if let Some(text) = trimmed.strip_prefix("bq.") {
+        self.flush();
+        let text = self.inline(text.trim());
+        self.blocks.push(format!("> {text}"));
+      } else if let Some((markers, text)) = list_item(trimmed) {
+        self.list_item(markers, text);
+      } else if trimmed.starts_with('|') {
+        self.table_row(trimmed);
+      } else if is_macro_tag(trimmed) {
+        // Lines holding only a macro tag, such as `{panel}` or `{toc}`
+        continue;
+      } else {
+        let text = self.inline(trimmed);
+        match &mut self.open {
+          OpenBlock::Paragraph(lines) => lines.push(text),
+          _ => {
+            self.flush();
+            self.open = OpenBlock::Paragraph(vec![text]);
+          }
+        }
+      }
+    }
+  }
+
+  fn finish(mut self) -> String {
+    self.flush();
+    let blocks: Vec<_> = self.blocks.into_iter().filter(|block| !block.is_empty()).collect();
+    if blocks.is_empty() {
+      return String::new();
+    }
+    format!("{}\n", blocks.join("\n\n"))
+  }
+
+  /// Close the open block, if any.
+  fn flush(&mut self) {
+    match std::mem::replace(&mut self.open, OpenBlock::None) {
+      OpenBlock::None => {}
+      OpenBlock::Paragraph(lines) => self.blocks.push(lines.join("\n")),
+      OpenBlock::List(items, _) => self.blocks.push(items.join("\n")),
+      OpenBlock::Table(rows) => self.blocks.push(render_table(&rows)),
+    }
+  }
+
+  fn list_item(&mut self, markers: &str, text: &str) {
+    if !matches!(self.open, OpenBlock::List(..)) {
+      self.flush();
+      self.open = OpenBlock::List(Vec::new(), Vec::new());
+    }
+    let text = self.inline(text);
+    let bullet = self.dialect.bullet();
+    let OpenBlock::List(items, levels) = &mut self.open else {
+      return;
+    };
+
+    let depth = markers.chars().count();
+    let ordered = markers.ends_with('#');
+    levels.truncate(depth);
+    // Levels skipped by the markup are opened as bullets
+    while levels.len() < depth - 1 {
+      levels.push(ListLevel {
+        ordered: false,
+        counter: 0,
+        width: bullet.chars().count(),
+      });
+    }
+    if levels.len() == depth && levels[depth - 1].ordered != ordered {
+      levels.pop();
+    }
+    if levels.len() < depth {
+      levels.push(ListLevel {
+        ordered,
+        counter: 0,
+        width: 0,
+      });
+    }
+
+    let indent: usize = levels[..depth - 1].iter().map(|level| level.width).sum();
+    let level = &mut levels[depth - 1];
+    level.counter += 1;
+    let prefix = if ordered {
+      format!("{}. ", level.counter)
+    } else {
+      bullet
+    };
+    level.width = prefix.chars().count();
+    items.push(format!("{}{prefix}{text}", " ".repeat(indent)));
+  }
+
+  fn table_row(&mut self, line: &str) {
+    let cells: Vec<String> = split_cells(line)
+      .into_iter()
+      .map(|cell| self.inline(cell.trim()).replace('|', "\\|"))
+      .collect();
+    match &mut self.open {
+      OpenBlock::Table(rows) => rows.push(cells),
+      _ => {
+        self.flush();
+        self.open = OpenBlock::Table(vec![cells]);
+      }
+    }
+  }
+
+  /// Convert the inline markup of a line.
+  fn inline(&mut self, text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+      let c = chars[i];
+      let next = chars.get(i + 1).copied();
+      match c {
+        '\\' if next == Some('\\') => {
+          out.push('\n');
+          i += 2;
+        }
+        '\\' if next.is_some() => {
+          let escaped = next.unwrap_or_default();
+          if escaped.is_ascii_punctuation() {
+            out.push('\\');
+          }
+          out.push(escaped);
+          i += 2;
+        }
+        '{' if next == Some('{') => match find(&chars, i + 2, "}}") {
+          Some(end) => {
+            out.push_str(&code_span(&collect(&chars[i + 2..end])));
+            i = end + 2;
+          }
+          None => {
+            out.push_str("{{");
+            i += 2;
+          }
+        },
+        '{' => match find(&chars, i + 1, "}") {
+          Some(end) if is_macro_tag(&collect(&chars[i..=end])) => i = end + 1,
+          _ => {
+            out.push(c);
+            i += 1;
+          }
+        },
+        '[' => match find(&chars, i + 1, "]") {
+          Some(end) if end > i + 1 => {
+            out.push_str(&self.link(&collect(&chars[i + 1..end])));
+            i = end + 1;
+          }
+          _ => {
+            out.push(c);
+            i += 1;
+          }
+        },
+        '!' => match find(&chars, i + 1, "!").filter(|&end| is_image(&chars[i + 1..end])) {
+          Some(end) => {
+            out.push_str(&self.image(&collect(&chars[i + 1..end])));
+            i = end + 1;
+          }
+          None => {
+            out.push(c);
+            i += 1;
+          }
+        },
+        '*' | '_' | '-' => match closing_delimiter(&chars, i) {
+          Some(end) => {
+            let inner = self.inline(&collect(&chars[i + 1..end]));
+            out.push_str(&match c {
+              '*' => self.dialect.strong(&inner),
+              '_' => self.dialect.emphasis(&inner),
+              _ => format!("~~{inner}~~"),
+            });
+            i = end + 1;
+          }
+          None => {
+            out.push(c);
+            i += 1;
+          }
+        },
+        _ => {
+          out.push(c);
+          i += 1;
+        }
+      }
+    }
+
+    out
+  }
+
+  /// Convert `[alias|target]` link markup.
+  fn link(&mut self, inner: &str) -> String {
+    let (alias, target) = match inner.split_once('|') {
+      Some((alias, rest)) => (Some(alias.trim()), rest.split('|').next().unwrap_or_default().trim()),
+      None => (None, inner.trim()),
+    };
+    let label = |fallback: &str, converter: &mut Self| match alias {
+      Some(alias) if !alias.is_empty() => converter.inline(alias),
+      _ => fallback.to_string(),
+    };
+
+    if let Some(user) = target.strip_prefix('~') {
+      return label(&format!("@{user}"), self);
+    }
+    if EXTERNAL_SCHEMES.iter().any(|scheme| target.starts_with(scheme)) {
+      return match alias {
+        Some(alias) if !alias.is_empty() => format!("[{}]({target})", self.inline(alias)),
+        _ => format!("<{target}>"),
+      };
+    }
+    if let Some(anchor) = target.strip_prefix('#') {
+      return format!("[{}](#{anchor})", label(anchor, self));
+    }
+    if let Some(attachment) = target.strip_prefix('^') {
+      return format!("[{}]({attachment})", label(attachment, self));
+    }
+
+    // Links to other pages (`SPACE:Title#anchor`) keep only their text
+    let title = target.split(['#', '^']).next().unwrap_or(target);
+    let title = title.split_once(':').map_or(title, |(_, title)| title);
+    label(title, self)
+  }
+
+  /// Convert `!file.png|alt=text!` image markup.
+  fn image(&mut self, inner: &str) -> String {
+    let mut parts = inner.split('|');
+    let target = parts.next().unwrap_or_default().trim();
+    let alt_text = parts
+      .flat_map(|params| params.split(','))
+      .find_map(|param| {
+        param
+          .trim()
+          .strip_prefix("alt=")
+          .map(|alt| alt.trim_matches('"').to_string())
+      })
+      .unwrap_or_else(|| "image".to_string());
+
+    if EXTERNAL_SCHEMES.iter().any(|scheme| target.starts_with(scheme)) {
+      return format!("![{alt_text}]({target})");
+    }
+
+    // Images attached to another page are written as `Page^file.png`
+    let filename = target.rsplit('^').next().unwrap_or(target).to_string();
+    self.images.push(ImageReference {
+      filename: filename.clone(),
+      alt_text: alt_text.clone(),
+    });
+    format!("![{alt_text}]({filename})")
+  }
+}
+
+/// The tag and the rest of the line when `line` opens a `{code}` or
+/// `{noformat}` block.
+fn code_block_start(line: &str) -> Option<(&str, &str)> {
+  if !(line.starts_with("{code") || line.starts_with("{noformat")) {
+    return None;
+  }
+  let end = line.find('}')?;
+  let tag = &line[1..end];
+  let name = tag.split(':').next().unwrap_or_default();
+  matches!(name, "code" | "noformat").then(|| (tag, &line[end + 1..]))
+}
+
+/// Language of a `{code:java}` or `{code:title=x|language=java}` tag.
+fn code_language(tag: &str) -> &str {
+  let Some((_, params)) = tag.split_once(':') else {
+    return "";
+  };
+  if !tag.starts_with("code") {
+    return "";
+  }
+  params
+    .split('|')
+    .find_map(|param| match param.split_once('=') {
+      Some((name, value)) => (name.trim() == "language").then(|| value.trim()),
+      None => Some(param.trim()),
+    })
+    .unwrap_or_default()
+}
+
+/// Collect the lines of a code block up to its closing tag.
+fn collect_code_block<'a>(tag: &str, first: &'a str, lines: &mut impl Iterator<Item = &'a str>) -> String {
+  let closing = if tag.starts_with("code") {
+    "{code}"
+  } else {
+    "{noformat}"
+  };
+  if let Some((code, _)) = first.split_once(closing) {
+    return code.to_string();
+  }
+
+  let mut code_lines = Vec::new();
+  if !first.trim().is_empty() {
+    code_lines.push(first);
+  }
+  for line in lines.by_ref() {
+    if let Some((code, _)) = line.split_once(closing) {
+      if !code.trim().is_empty() {
+        code_lines.push(code);
+      }
+      break;
+    }
+    code_lines.push(line);
+  }
+  code_lines.join("\n")
+}
+
+/// Level and text of an `h1.`-`h6.` heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+  let bytes = line.as_bytes();
+  match bytes {
+    [b'h', digit @ b'1'..=b'6', b'.', ..] => Some((usize::from(digit - b'0'), line[3..].trim())),
+    _ => None,
+  }
+}
+
+/// Markers and text of a `*`, `#`, or `-` list item line.
+fn list_item(line: &str) -> Option<(&str, &str)> {
+  let marker_len = line.chars().take_while(|c| matches!(c, '*' | '#')).count();
+  let marker_len = if marker_len == 0 && line.starts_with('-') {
+    1
+  } else {
+    marker_len
+  };
+  let (markers, rest) = line.split_at(marker_len);
+  if markers.is_empty() || !rest.starts_with(char::is_whitespace) {
+    return None;
+  }
+  Some((markers, rest.trim()))
+}
+
+/// Whether `text` is a single macro tag such as `{panel:title=x}` or `{toc}`.
+fn is_macro_tag(text: &str) -> bool {
+  let Some(inner) = text.strip_prefix('{').and_then(|text| text.strip_suffix('}')) else {
+    return false;
+  };
+  let name = inner.split(':').next().unwrap_or_default();
+  !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') && !inner.contains(['{', '}'])
+}
+
+/// Whether the text between two `!` is an image reference rather than prose.
+fn is_image(inner: &[char]) -> bool {
+  let target: String = inner.iter().take_while(|&&c| c != '|').collect();
+  match (inner.first(), inner.last()) {
+    (Some(first), Some(last)) => !first.is_whitespace() && !last.is_whitespace() && target.contains('.'),
+    _ => false,
+  }
+}
+
+/// Position of the delimiter closing the emphasis opened at `start`, if any.
+///
+/// Delimiters only count at word boundaries, so `snake_case_name` and
+/// `well-known` are left alone.
+fn closing_delimiter(chars: &[char], start: usize) -> Option<usize> {
+  let delimiter = chars[start];
+  if start > 0 && chars[start - 1].is_alphanumeric() {
+    return None;
+  }
+  if chars
+    .get(start + 1)
+    .is_none_or(|c| c.is_whitespace() || *c == delimiter)
+  {
+    return None;
+  }
+  (start + 2..chars.len()).find(|&end| {
+    chars[end] == delimiter
+      && !chars[end - 1].is_whitespace()
+      && chars.get(end + 1).is_none_or(|c| !c.is_alphanumeric())
+  })
+}
+
+/// Position of `pattern` in `chars` at or after `from`.
+fn find(chars: &[char], from: usize, pattern: &str) -> Option<usize> {
+  let pattern: Vec<char> = pattern.chars().collect();
+  (from..chars.len()).find(|&i| chars[i..].starts_with(&pattern))
+}
+
+fn collect(chars: &[char]) -> String {
+  chars.iter().collect()
+}
+
+/// Markdown code span long enough not to be closed by backticks in `code`.
+fn code_span(code: &str) -> String {
+  let longest_run = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+  let ticks = "`".repeat(longest_run + 1);
+  if longest_run > 0 {
+    format!("{ticks} {code} {ticks}")
+  } else {
+    format!("{ticks}{code}{ticks}")
+  }
+}
+
+/// Split a `||header||` or `|cell|` table row, ignoring pipes inside links.
+fn split_cells(line: &str) -> Vec<&str> {
+  let mut cells = Vec::new();
+  let mut depth = 0usize;
+  let mut start = 0;
+  for (index, c) in line.char_indices() {
+    match c {
+      '[' => depth += 1,
+      ']' => depth = depth.saturating_sub(1),
+      '|' if depth == 0 => {
+        cells.push(&line[start..index]);
+        start = index + 1;
+      }
+      _ => {}
+    }
+  }
+  cells.push(&line[start..]);
+  cells.into_iter().filter(|cell| !cell.is_empty()).collect()
+}
+
+/// Render table rows, using the first row as the header.
+fn render_table(rows: &[Vec<String>]) -> String {
+  let columns = rows.iter().map(Vec::len).max().unwrap_or(0).max(1);
+  let render_row = |row: &[String]| {
+    let cells: Vec<&str> = (0..columns)
+      .map(|column| row.get(column).map_or("", String::as_str))
+      .collect();
+    format!("| {} |", cells.join(" | "))
+  };
+
+  let mut lines = Vec::new();
+  if let Some((header, body)) = rows.split_first() {
+    lines.push(render_row(header));
+    lines.push(format!("|{}", " --- |".repeat(columns)));
+    lines.extend(body.iter().map(|row| render_row(row)));
+  }
+  lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn render(wiki: &str) -> String {
+    wiki_to_markdown(wiki, &MarkdownDialect::default())
+  }
+
+  #[test]
+  fn test_wiki_to_markdown() {
+    let wiki = r"h1. Release *process*
+Run the {{deploy}} script, see [the guide|https://example.com/guide] or [Ops:Runbook].
+Ask [~jdoe] about _snake_case_names_.
+
+* First
+** Nested
+*# Numbered
+*# Again
+* Second
+
+{code:language=bash|title=Deploy}
+./deploy --env prod
+{code}
+
+bq. Mind the gap
+
+||Name||Link||
+|Docs|[home|https://example.com]|
+----
+!diagram.png|alt=Flow!";
+
+    insta::assert_snapshot!(render(wiki), @r"
+    # Release **process**
+
+    Run the `deploy` script, see [the guide](https://example.com/guide) or Runbook.
+    Ask @jdoe about _snake_case_names_.
+
+    - First
+      - Nested
+      1. Numbered
+      2. Again
+    - Second
+
+    ```bash
+    ./deploy --env prod
+    ```
+
+    > Mind the gap
+
+    | Name | Link |
+    | --- | --- |
+    | Docs | [home](https://example.com) |
+
+    ---
+
+    ![Flow](diagram.png)
+    ");
+  }
+
+  #[test]
+  fn test_wiki_inline_markup_is_left_alone_inside_words() {
+    assert_eq!(
+      render("a well-known 2*3*4 value with a - dash"),
+      "a well-known 2*3*4 value with a - dash\n"
+    );
+    assert_eq!(render("{color:red}Alert{color} -gone-"), "Alert ~~gone~~\n");
+  }
+
+  #[test]
+  fn test_wiki_noformat_block_on_one_line() {
+    assert_eq!(render("{noformat}*raw*{noformat}"), "```\n*raw*\n```\n");
+  }
+
+  #[test]
+  fn test_wiki_image_references() {
+    let wiki = "!a.png! and !https://example.com/b.png!\n{code}\n!c.png!\n{code}\nSee !Home^d.gif|alt=D!";
+    let images = wiki_image_references(wiki);
+
+    assert_eq!(
+      images,
+      vec![
+        ImageReference {
+          filename: "a.png".to_string(),
+          alt_text: "image".to_string(),
+        },
+        ImageReference {
+          filename: "d.gif".to_string(),
+          alt_text: "D".to_string(),
+        },
+      ]
+    );
+  }
+}
@@ -0,0 +1,118 @@
+//! MkDocs export profile.
+//!
+//! MkDocs builds a site from the Markdown files under a `docs/` directory and
+//! the navigation declared in `mkdocs.yml` next to it. With `--profile mkdocs`,
+//! pages are written below [`DOCS_DIR`] and [`render_config`] produces a
+//! `mkdocs.yml` whose `nav` mirrors the exported page hierarchy, so the export
+//! can be served with `mkdocs serve` as is.
+
+use crate::confluence::PageTree;
+use crate::front_matter::yaml_scalar;
+use crate::processed_page::TreeFilenames;
+
+/// File name of the generated site configuration.
+pub const CONFIG_FILE: &str = "mkdocs.yml";
+
+/// Directory, relative to the export root, that pages are written to.
+pub const DOCS_DIR: &str = "docs";
+
+/// Render a `mkdocs.yml` for exported page trees.
+///
+/// The site is named after the first root page. Pages with children become
+/// nav sections listing their own page first, unless it was filtered out,
+/// followed by their children;
+/// each further tree, such as a page pulled in with `--follow-links`, is a
+/// top-level nav entry after it.
+///
+/// # Arguments
+/// * `trees` - Roots of the exported trees, written directly into [`DOCS_DIR`].
+/// * `filenames` - Filenames the pages were written to, naming the child directories.
+/// * `extension` - File extension of the page files.
+pub fn render_config(trees: &[PageTree], filenames: &TreeFilenames, extension: &str) -> String {
+  let site_name = trees.first().map_or("", |tree| tree.page.title.as_str());
+  let mut result = format!("site_name: {}\ndocs_dir: {DOCS_DIR}\nnav:\n", yaml_scalar(site_name));
+  for tree in trees {
+    render_nav_item(tree, "", 1, filenames, extension, &mut result);
+  }
+  result
+}
+
+fn render_nav_item(
+  tree: &PageTree,
+  dir: &str,
+  depth: usize,
+  filenames: &TreeFilenames,
+  extension: &str,
+  result: &mut String,
+) {
+  let indent = "  ".repeat(depth);
+  let title = yaml_scalar(&tree.page.title);
+  let stem = filenames.stem(&tree.page);
+  let path = yaml_scalar(&format!("{dir}{stem}.{extension}"));
+
+  if tree.children.is_empty() {
+    result.push_str(&format!("{indent}- {title}: {path}\n"));
+    return;
+  }
+
+  result.push_str(&format!("{indent}- {title}:\n"));
+  if tree.exported {
+    result.push_str(&format!("{indent}  - {title}: {path}\n"));
+  }
+  let child_dir = format!("{dir}{stem}/");
+  for child in &tree.children {
+    render_nav_item(child, &child_dir, depth + 1, filenames, extension, result);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::processed_page::FilenameOptions;
+  use crate::test_support::{page, tree};
+
+  #[test]
+  fn test_render_config() {
+    let root = tree(
+      page("1", "Team Handbook"),
+      vec![
+        tree(page("2", "On-call"), vec![tree(page("3", "Rotations"), vec![])]),
+        tree(page("4", "Engineer's FAQ"), vec![]),
+      ],
+    );
+
+    let forest = [root, tree(page("5", "Runbook"), vec![])];
+    let filenames = TreeFilenames::plan_forest(&forest, FilenameOptions::default());
+
+    insta::assert_snapshot!(render_config(&forest, &filenames, "md"), @r#"
+    site_name: Team Handbook
+    docs_dir: docs
+    nav:
+      - Team Handbook:
+        - Team Handbook: Team Handbook.md
+        - On-call:
+          - On-call: Team Handbook/On-call.md
+          - Rotations: Team Handbook/On-call/Rotations.md
+        - "Engineer's FAQ": Team Handbook/Engineer_s FAQ.md
+      - Runbook: Runbook.md
+    "#);
+  }
+
+  #[test]
+  fn test_render_config_unexported_section() {
+    let mut section = tree(page("2", "On-call"), vec![tree(page("3", "Rotations"), vec![])]);
+    section.exported = false;
+    let root = tree(page("1", "Team Handbook"), vec![section]);
+    let filenames = TreeFilenames::plan(&root, FilenameOptions::default());
+
+    insta::assert_snapshot!(render_config(std::slice::from_ref(&root), &filenames, "md"), @r"
+    site_name: Team Handbook
+    docs_dir: docs
+    nav:
+      - Team Handbook:
+        - Team Handbook: Team Handbook.md
+        - On-call:
+          - Rotations: Team Handbook/On-call/Rotations.md
+    ");
+  }
+}
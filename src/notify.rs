@@ -0,0 +1,213 @@
+//! Webhook notifications for finished exports.
+//!
+//! With `--notify-webhook`, the end-of-run summary is posted to a webhook once
+//! the export completes or fails, so scheduled mirrors report broken runs
+//! without anyone reading their logs. The payload is either the plain JSON
+//! [`RunReport`] or a Slack-compatible `{"text": ...}` message.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::error_report::PartialFailure;
+use crate::progress::ExportSummary;
+use crate::size_limits::format_size;
+
+/// Time allowed for the webhook to accept a notification.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Payload format of webhook notifications.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum NotifyFormat {
+  /// The run report as a JSON object (default)
+  #[default]
+  Json,
+  /// A Slack incoming-webhook message
+  Slack,
+}
+
+/// Outcome of an export run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+  /// Every page was exported.
+  Success,
+  /// The export finished, but some pages failed, as with `--keep-going`.
+  Partial,
+  /// The export stopped with an error.
+  Failed,
+}
+
+/// Summary of an export run sent to the webhook.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunReport<'a> {
+  /// Outcome of the run.
+  pub status: RunStatus,
+  /// Page URL or ID given on the command line.
+  pub page: &'a str,
+  /// Output directory.
+  pub output: &'a str,
+  /// Pages written.
+  pub pages: usize,
+  /// Pages that failed to export.
+  pub failures: usize,
+  /// Bytes of page content, images, and attachments written.
+  pub bytes: u64,
+  /// Wall-clock duration of the run in seconds.
+  pub duration_secs: f64,
+  /// Error that stopped the run.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub error: Option<String>,
+}
+
+impl<'a> RunReport<'a> {
+  /// Build a report from the totals of a run.
+  ///
+  /// # Arguments
+  /// * `page` - Page URL or ID given on the command line.
+  /// * `output` - Output directory.
+  /// * `summary` - Totals of the run, when it got far enough to count pages.
+  /// * `elapsed` - Duration of the whole run.
+  /// * `error` - Error that ended the run, if any; [`PartialFailure`] marks a partial export rather than a failed one.
+  pub fn new(
+    page: &'a str,
+    output: &'a str,
+    summary: Option<&ExportSummary>,
+    elapsed: Duration,
+    error: Option<&anyhow::Error>,
+  ) -> Self {
+    let failures = summary.map_or(0, |summary| summary.failures);
+    let status = match error {
+      Some(error) if !error.is::<PartialFailure>() => RunStatus::Failed,
+      Some(_) => RunStatus::Partial,
+      None if failures > 0 => RunStatus::Partial,
+      None => RunStatus::Success,
+    };
+    Self {
+      status,
+      page,
+      output,
+      pages: summary.map_or(0, |summary| summary.pages),
+      failures,
+      bytes: summary.map_or(0, |summary| summary.bytes),
+      duration_secs: (elapsed.as_secs_f64() * 10.0).round() / 10.0,
+      error: error.map(|error| format!("{error:#}")),
+    }
+  }
+
+  /// The notification body in the given format.
+  pub fn payload(&self, format: NotifyFormat) -> serde_json::Value {
+    match format {
+      NotifyFormat::Json => json!(self),
+      NotifyFormat::Slack => json!({ "text": self.slack_text() }),
+    }
+  }
+
+  fn slack_text(&self) -> String {
+    let totals = format!(
+      "{} {}, {} {}, {} in {:.1}s",
+      self.pages,
+      if self.pages == 1 { "page" } else { "pages" },
+      self.failures,
+      if self.failures == 1 { "failure" } else { "failures" },
+      format_size(self.bytes),
+      self.duration_secs
+    );
+    match (&self.status, &self.error) {
+      (RunStatus::Failed, Some(error)) => format!(
+        ":x: confluence-dl export of {} to `{}` failed: {totals}\n```{error}```",
+        self.page, self.output
+      ),
+      (RunStatus::Partial, _) => format!(
+        ":warning: confluence-dl export of {} to `{}` finished with failures: {totals}",
+        self.page, self.output
+      ),
+      _ => format!(
+        ":white_check_mark: confluence-dl export of {} to `{}` finished: {totals}",
+        self.page, self.output
+      ),
+    }
+  }
+}
+
+/// Post a run report to a webhook.
+///
+/// # Arguments
+/// * `url` - Webhook URL.
+/// * `report` - Summary of the run.
+/// * `format` - Payload format.
+///
+/// # Errors
+/// Returns an error when the request fails or the webhook answers with an
+/// error status.
+pub async fn send(url: &str, report: &RunReport<'_>, format: NotifyFormat) -> Result<()> {
+  let client = reqwest::Client::builder()
+    .timeout(WEBHOOK_TIMEOUT)
+    .build()
+    .context("Failed to create HTTP client")?;
+  let response = client
+    .post(url)
+    .json(&report.payload(format))
+    .send()
+    .await
+    .context("Failed to send webhook notification")?;
+
+  let status = response.status();
+  if !status.is_success() {
+    let body = response.text().await.unwrap_or_default();
+    return Err(anyhow!("Webhook returned error {status}: {body}"));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_report_payloads() {
+    let summary = ExportSummary {
+      pages: 11,
+      total_pages: 12,
+      bytes: 2048,
+      images: 0,
+      attachments: 0,
+      failures: 1,
+      accessibility_issues: None,
+      stubs: Vec::new(),
+      elapsed: Duration::from_secs(3),
+    };
+    let report = RunReport::new(
+      "123",
+      "docs",
+      Some(&summary),
+      Duration::from_millis(4250),
+      Some(&anyhow!("Failed to fetch page 123")),
+    );
+
+    assert_eq!(
+      report.payload(NotifyFormat::Json).to_string(),
+      r#"{"bytes":2048,"duration_secs":4.3,"error":"Failed to fetch page 123","failures":1,"output":"docs","page":"123","pages":11,"status":"failed"}"#
+    );
+    insta::assert_snapshot!(report.payload(NotifyFormat::Slack)["text"].as_str().unwrap(), @r"
+    :x: confluence-dl export of 123 to `docs` failed: 11 pages, 1 failure, 2 KiB in 4.3s
+    ```Failed to fetch page 123```
+    ");
+
+    let partial = anyhow::Error::new(PartialFailure { failures: 1 });
+    let report = RunReport::new("123", "docs", Some(&summary), Duration::from_secs(1), Some(&partial));
+    assert_eq!(report.status, RunStatus::Partial);
+    assert_eq!(report.payload(NotifyFormat::Json)["status"], "partial");
+    assert!(report.slack_text().starts_with(":warning:"));
+
+    let report = RunReport::new("123", "docs", Some(&summary), Duration::from_secs(1), None);
+    assert_eq!(report.status, RunStatus::Partial);
+
+    let report = RunReport::new("123", "docs", None, Duration::from_secs(1), None);
+    assert_eq!(report.status, RunStatus::Success);
+    assert!(report.slack_text().starts_with(":white_check_mark:"));
+  }
+}
@@ -0,0 +1,203 @@
+//! Filesystem-safe names for exported files.
+//!
+//! Page titles and attachment names become file and directory names, which
+//! must be valid on every platform an export may be copied to. Windows is the
+//! strictest: it rejects reserved device names such as `CON` or `NUL` (with
+//! any extension), names ending in a dot or space, and, unless long paths are
+//! enabled, paths longer than [`WINDOWS_MAX_PATH`] characters. Every name is
+//! made valid on all platforms; page filenames can additionally be restricted
+//! to ASCII with [`to_ascii`] and shortened with [`truncate`] to keep deep
+//! trees within a path length limit.
+
+/// Longest path, in characters, that Windows accepts without long path
+/// support.
+pub const WINDOWS_MAX_PATH: usize = 260;
+
+/// Longest filename stem kept, in bytes, leaving room for a page ID suffix
+/// and an extension within the 255-byte name limit of common filesystems.
+pub const MAX_STEM_BYTES: usize = 200;
+
+/// Device names Windows reserves in every directory, with or without an
+/// extension.
+const RESERVED_NAMES: &[&str] = &[
+  "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+  "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Default path length limit of the current platform, if any.
+pub fn default_max_path() -> Option<usize> {
+  cfg!(windows).then_some(WINDOWS_MAX_PATH)
+}
+
+/// Sanitize a Confluence page title so it can be used as a filesystem name.
+///
+/// Removes/normalizes characters that are potentially unsafe across
+/// platforms, collapsing repeated whitespace while keeping readability.
+/// Reserved Windows device names get `_` appended, and very long titles are
+/// cut to [`MAX_STEM_BYTES`].
+pub fn sanitize_filename(title: &str) -> String {
+  let sanitized = title
+    .chars()
+    .map(|c| {
+      if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' {
+        c
+      } else {
+        '_'
+      }
+    })
+    .collect::<String>()
+    .replace("  ", " ");
+  make_portable(&truncate(sanitized.trim(), MAX_STEM_BYTES))
+}
+
+/// Sanitize an attachment or image filename, keeping its extension.
+///
+/// Path separators and characters Windows rejects are replaced with `_`.
+pub fn sanitize_asset_filename(filename: &str) -> String {
+  let sanitized: String = filename
+    .chars()
+    .map(|c| match c {
+      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+      c if c.is_control() => '_',
+      c => c,
+    })
+    .collect();
+  make_portable(&sanitized)
+}
+
+/// Make a filename valid on Windows.
+///
+/// Trailing dots and spaces, which Windows silently strips, are removed, and
+/// reserved device names get `_` appended to their stem, e.g. `CON.txt`
+/// becomes `CON_.txt`. A name made only of dots and spaces becomes `_`.
+pub fn make_portable(name: &str) -> String {
+  let trimmed = name.trim_end_matches(['.', ' ']);
+  if trimmed.is_empty() {
+    return if name.is_empty() {
+      String::new()
+    } else {
+      "_".to_string()
+    };
+  }
+
+  let stem = trimmed.split('.').next().unwrap_or(trimmed);
+  if RESERVED_NAMES
+    .iter()
+    .any(|reserved| reserved.eq_ignore_ascii_case(stem.trim_end()))
+  {
+    format!("{stem}_{}", &trimmed[stem.len()..])
+  } else {
+    trimmed.to_string()
+  }
+}
+
+/// Cut `name` to at most `max_bytes` bytes on a character boundary, without
+/// leaving a trailing separator.
+pub fn truncate(name: &str, max_bytes: usize) -> String {
+  if name.len() <= max_bytes {
+    return name.to_string();
+  }
+  let mut end = max_bytes;
+  while !name.is_char_boundary(end) {
+    end -= 1;
+  }
+  name[..end].trim_end_matches([' ', '_', '-', '.']).to_string()
+}
+
+/// Cut `name` to at most `max_chars` characters, without leaving a trailing
+/// separator.
+pub fn truncate_chars(name: &str, max_chars: usize) -> String {
+  match name.char_indices().nth(max_chars) {
+    Some((end, _)) => name[..end].trim_end_matches([' ', '_', '-', '.']).to_string(),
+    None => name.to_string(),
+  }
+}
+
+/// Replace non-ASCII characters of a filename.
+///
+/// Latin letters with diacritics and ligatures are transliterated (`é` → `e`,
+/// `ß` → `ss`); other characters become `_`.
+pub fn to_ascii(name: &str) -> String {
+  let mut result = String::with_capacity(name.len());
+  for c in name.chars() {
+    if c.is_ascii() {
+      result.push(c);
+      continue;
+    }
+    let lower = c.to_lowercase().next().unwrap_or(c);
+    match transliterate(lower) {
+      Some(ascii) if c != lower => {
+        let mut chars = ascii.chars();
+        result.extend(chars.next().map(|first| first.to_ascii_uppercase()));
+        result.push_str(chars.as_str());
+      }
+      Some(ascii) => result.push_str(ascii),
+      None => result.push('_'),
+    }
+  }
+  result
+}
+
+/// ASCII spelling of a lowercase Latin letter.
+fn transliterate(c: char) -> Option<&'static str> {
+  Some(match c {
+    'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+    'æ' => "ae",
+    'ç' | 'ć' | 'č' => "c",
+    'ď' | 'đ' | 'ð' => "d",
+    'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+    'ğ' => "g",
+    'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+    'ł' | 'ľ' => "l",
+    'ñ' | 'ń' | 'ň' => "n",
+    'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+    'œ' => "oe",
+    'ř' => "r",
+    'ß' => "ss",
+    'ś' | 'š' | 'ş' => "s",
+    'ť' | 'ţ' => "t",
+    'þ' => "th",
+    'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+    'ý' | 'ÿ' => "y",
+    'ź' | 'ż' | 'ž' => "z",
+    _ => return None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_sanitize_filename() {
+    assert_eq!(sanitize_filename("Release notes v1.2"), "Release notes v1_2");
+    assert_eq!(sanitize_filename("con"), "con_");
+    assert_eq!(sanitize_filename(&"a".repeat(300)).len(), MAX_STEM_BYTES);
+  }
+
+  #[test]
+  fn test_sanitize_asset_filename() {
+    assert_eq!(sanitize_asset_filename("NUL.txt"), "NUL_.txt");
+    assert_eq!(sanitize_asset_filename("com1"), "com1_");
+    assert_eq!(sanitize_asset_filename("notes. "), "notes");
+    assert_eq!(sanitize_asset_filename("..."), "_");
+    assert_eq!(sanitize_asset_filename("tab\there.png"), "tab_here.png");
+    assert_eq!(sanitize_asset_filename("console.log"), "console.log");
+  }
+
+  #[test]
+  fn test_truncate() {
+    assert_eq!(truncate("Short", 10), "Short");
+    assert_eq!(truncate("Release notes", 8), "Release");
+    assert_eq!(truncate("Überblick", 2), "Ü");
+    assert_eq!(truncate("Überblick", 1), "");
+    assert_eq!(truncate_chars("Überblick", 2), "Üb");
+  }
+
+  #[test]
+  fn test_to_ascii() {
+    assert_eq!(to_ascii("Überblick Straße"), "Uberblick Strasse");
+    assert_eq!(to_ascii("Æsir Ærø"), "Aesir Aero");
+    assert_eq!(to_ascii("概要 Overview"), "__ Overview");
+  }
+}
@@ -5,28 +5,97 @@
 //! concerns of fetching and converting content from persisting it to the
 //! filesystem.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, OpenOptions};
 use std::io::{ErrorKind, Write as IoWrite};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex as StdMutex};
 
 use anyhow::{Context, Result, bail};
-use futures::future::try_join_all;
+use clap::ValueEnum;
+use futures::future::BoxFuture;
+use futures::{FutureExt, StreamExt, TryStreamExt, stream};
+use serde::Serialize;
+use tokio::sync::{Mutex as AsyncMutex, OnceCell, Semaphore};
+use tracing::{debug, warn};
 
 use crate::asciidoc::{self, AsciiDocOptions};
-use crate::attachments::{self, ATTACHMENTS_DIR, DownloadedAttachment};
-use crate::confluence::{ConfluenceApi, Page};
-use crate::format::OutputFormat;
-use crate::images::{self, ImageReference};
-use crate::markdown::{self, MarkdownOptions};
+use crate::attachments::{self, AttachmentLayout, DownloadedAttachment, MAX_CONCURRENT_ASSET_DOWNLOADS};
+use crate::confluence::{ConfluenceApi, ContentState, Page, PageTree};
+use crate::conversion_cache::ConversionCache;
+use crate::external_images::{self, ExternalImageFetcher};
+use crate::format::{ContentStateDisplay, OutputFormat, TableFormat};
+use crate::front_matter::{self, CONTENT_HASH_KEY, FrontMatter};
+use crate::front_matter_rules::FrontMatterRules;
+use crate::image_formats::{self, ImageConverter};
+use crate::images::{self, ImageNaming, ImageQuality, ImageReference, ViewImage};
+use crate::includes::IncludeResolver;
+use crate::integrity::{sha256_file, sha256_hex};
+use crate::json_export::{self, PageRecord};
+use crate::link_titles::LinkTitleFetcher;
+use crate::markdown::{self, MarkdownDialect, MarkdownOptions};
+use crate::paths::sanitize_asset_filename;
+pub use crate::paths::sanitize_filename;
+use crate::templates::PageTemplate;
+use crate::users::UserResolver;
+use crate::{content_stubs, docusaurus, paths, redaction, table_export};
 
 /// Data about an asset (image or attachment) ready to be written to disk.
 #[derive(Debug, Clone)]
 pub struct AssetData {
   /// The relative path where this asset should be written (e.g., "images/photo.png").
   pub relative_path: PathBuf,
-  /// The raw bytes of the asset.
-  pub content: Vec<u8>,
+  /// The content of the asset.
+  pub content: AssetContent,
+}
+
+/// Where the content of an asset is held until its page is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetContent {
+  /// Raw bytes held in memory.
+  Bytes(Vec<u8>),
+  /// A download streamed to a staging file, moved into place when the page
+  /// is written so large attachments never sit in memory.
+  Staged(StagedFile),
+}
+
+/// A downloaded asset waiting on disk to be moved to its final path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedFile {
+  /// Path of the staging file.
+  pub path: PathBuf,
+  /// Size of the file in bytes.
+  pub size: u64,
+  /// Hex-encoded SHA-256 digest of the file, computed from disk.
+  pub sha256: String,
+}
+
+impl AssetContent {
+  /// Size of the content in bytes.
+  pub fn size(&self) -> usize {
+    match self {
+      Self::Bytes(bytes) => bytes.len(),
+      Self::Staged(staged) => staged.size as usize,
+    }
+  }
+
+  /// Hex-encoded SHA-256 digest of the content.
+  pub fn sha256(&self) -> String {
+    match self {
+      Self::Bytes(bytes) => sha256_hex(bytes),
+      Self::Staged(staged) => staged.sha256.clone(),
+    }
+  }
+
+  /// Whether the file at `path` already holds this content.
+  fn matches_file(&self, path: &Path) -> bool {
+    let same_size = fs::metadata(path).is_ok_and(|metadata| metadata.len() == self.size() as u64);
+    same_size
+      && match self {
+        Self::Bytes(bytes) => fs::read(path).is_ok_and(|existing| existing == *bytes),
+        Self::Staged(staged) => sha256_file(path).is_ok_and(|digest| digest == staged.sha256),
+      }
+  }
 }
 
 /// A fully processed page ready to be written to disk.
@@ -35,7 +104,7 @@ pub struct AssetData {
 /// without requiring any further API calls or transformations. It enables
 /// clean separation between the processing phase (API calls + conversion) and
 /// the I/O phase (writing files to disk).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ProcessedPage {
   /// Sanitized filename (without extension) for the output file.
   pub filename: String,
@@ -48,6 +117,111 @@ pub struct ProcessedPage {
   pub images: Vec<AssetData>,
   /// Attachments to write to disk.
   pub attachments: Vec<AssetData>,
+  /// Tables extracted to CSV or TSV files.
+  pub tables: Vec<AssetData>,
+  /// Front matter written above the content; omitted when empty.
+  pub front_matter: FrontMatter,
+  /// Links that were rewritten to point at downloaded files.
+  pub link_map: LinkMap,
+  /// Page details written to the page's JSON document; set for JSON exports.
+  pub record: Option<PageRecord>,
+}
+
+/// The link rewrites applied to a page, from the original reference to the
+/// local path relative to the page's output directory.
+///
+/// Pipelines consuming an export can use these to build redirect tables or
+/// asset manifests without re-parsing the converted content.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LinkMap {
+  /// Image attachment filenames, or image URLs for view-only images.
+  pub images: BTreeMap<String, String>,
+  /// Attachment filenames.
+  pub attachments: BTreeMap<String, String>,
+  /// Confluence URLs of other exported pages, mapped to the relative links
+  /// to their files.
+  pub pages: BTreeMap<String, String>,
+}
+
+impl LinkMap {
+  /// Whether no links were rewritten.
+  pub fn is_empty(&self) -> bool {
+    self.images.is_empty() && self.attachments.is_empty() && self.pages.is_empty()
+  }
+}
+
+/// Render a relative path with forward slashes, as used in links.
+pub(crate) fn link_path(path: &Path) -> String {
+  path.to_string_lossy().replace('\\', "/")
+}
+
+impl ProcessedPage {
+  /// Total size of the page content, images, and attachments in bytes.
+  pub fn total_bytes(&self) -> u64 {
+    let asset_bytes: usize = self
+      .images
+      .iter()
+      .chain(&self.attachments)
+      .chain(&self.tables)
+      .map(|asset| asset.content.size())
+      .sum();
+    (self.content.len() + asset_bytes) as u64
+  }
+}
+
+/// Suffix of the file [`ConflictPolicy::New`] writes next to an edited page.
+const CONFLICT_SUFFIX: &str = ".new";
+
+/// What to do when a re-export would replace a locally edited page file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictPolicy {
+  /// Keep the edited file and leave the new export unwritten
+  Skip,
+  /// Replace the edited file with the new export
+  Overwrite,
+  /// Keep the edited file and write the new export next to it as `.new`
+  New,
+}
+
+/// Options controlling how a processed page is written to disk.
+#[derive(Debug, Clone, Default)]
+pub struct WriteOptions {
+  /// The output format the page content was converted to.
+  pub format: OutputFormat,
+  /// File extension (without the leading dot) overriding the format default,
+  /// e.g. `mdx` or `markdown`.
+  pub extension: Option<String>,
+  /// Whether to overwrite existing files.
+  pub overwrite: bool,
+  /// When set, a content hash is recorded in front matter and existing page
+  /// files are checked for local edits before being replaced. Assets are
+  /// always refreshed in this mode.
+  pub conflict: Option<ConflictPolicy>,
+}
+
+impl WriteOptions {
+  /// The extension used for page files.
+  pub fn file_extension(&self) -> &str {
+    self.extension.as_deref().unwrap_or(self.format.file_extension())
+  }
+
+  /// The extension of documents generated next to the pages, such as the
+  /// space README: the page extension, or `md` for JSON exports, whose
+  /// documents are Markdown.
+  pub fn document_extension(&self) -> &str {
+    match self.format {
+      OutputFormat::Json => OutputFormat::Markdown.file_extension(),
+      _ => self.file_extension(),
+    }
+  }
+
+  /// The MIME type of page files, taking the extension override into account.
+  pub fn mime_type(&self) -> &'static str {
+    match self.file_extension() {
+      "mdx" => "text/mdx",
+      _ => self.format.mime_type(),
+    }
+  }
 }
 
 /// Options controlling how a page should be processed.
@@ -57,12 +231,26 @@ pub struct ProcessOptions<'a> {
   pub format: OutputFormat,
   /// Whether to preserve raw storage content for debugging.
   pub save_raw: bool,
+  /// Format to extract the page's tables to, if any.
+  pub extract_tables: Option<TableFormat>,
   /// Whether to download images referenced in the page.
   pub download_images: bool,
   /// Subdirectory name for storing downloaded images.
   pub images_dir: String,
+  /// How downloaded images are named in `images_dir`.
+  pub image_naming: ImageNaming,
+  /// Whether original images or their thumbnails are downloaded.
+  pub image_quality: ImageQuality,
+  /// Converts images browsers cannot render, such as EMF, to PNG when set.
+  pub image_converter: Option<Arc<dyn ImageConverter>>,
   /// Whether to download attachments.
   pub download_attachments: bool,
+  /// How downloaded attachments are arranged in subdirectories.
+  pub attachments_layout: AttachmentLayout,
+  /// Where the page's content state is shown; `Off` skips fetching it.
+  pub content_state: ContentStateDisplay,
+  /// Whether to add the page's author and last editor to front matter.
+  pub author_metadata: bool,
   /// Markdown-specific conversion options.
   pub markdown_options: MarkdownOptions,
   /// AsciiDoc-specific conversion options.
@@ -73,6 +261,26 @@ pub struct ProcessOptions<'a> {
   /// Whether to overwrite existing files. When `false` and `output_dir` is set,
   /// existing files will be skipped during fetch.
   pub overwrite: bool,
+  /// Fetches titles for bare external links in Markdown output when set.
+  pub link_titles: Option<&'a LinkTitleFetcher>,
+  /// Downloads images embedded from other sites along with the page's images when set.
+  pub external_images: Option<&'a ExternalImageFetcher>,
+  /// Resolves `@user:<account-id>` mentions to display names when set.
+  pub users: Option<&'a UserResolver>,
+  /// Fetches the pages referenced by include macros so their content is inlined when set.
+  pub includes: Option<&'a IncludeResolver>,
+  /// Adds configured front matter fields to matching pages when set.
+  pub front_matter_rules: Option<&'a FrontMatterRules>,
+  /// Reuses earlier conversions of unchanged pages when set.
+  pub conversion_cache: Option<ConversionCache>,
+  /// Names of macros whose parameters are redacted from converted and raw output.
+  pub redacted_macros: Vec<String>,
+  /// Wraps the converted content in a template when set.
+  pub template: Option<&'a PageTemplate>,
+  /// How the page file, and the directories named after it, are named.
+  pub filenames: FilenameOptions,
+  /// Shares asset downloads with the other pages of the same export when set.
+  pub assets: Option<&'a AssetRegistry>,
 }
 
 impl Default for ProcessOptions<'_> {
@@ -80,13 +288,30 @@ impl Default for ProcessOptions<'_> {
     Self {
       format: OutputFormat::Markdown,
       save_raw: false,
+      extract_tables: None,
       download_images: false,
       images_dir: "images".to_string(),
+      image_naming: ImageNaming::default(),
+      image_quality: ImageQuality::default(),
+      image_converter: None,
       download_attachments: false,
+      attachments_layout: AttachmentLayout::default(),
+      content_state: ContentStateDisplay::default(),
+      author_metadata: false,
       markdown_options: MarkdownOptions::default(),
       asciidoc_options: AsciiDocOptions::default(),
       output_dir: None,
       overwrite: false,
+      link_titles: None,
+      external_images: None,
+      users: None,
+      includes: None,
+      front_matter_rules: None,
+      conversion_cache: None,
+      redacted_macros: Vec::new(),
+      template: None,
+      filenames: FilenameOptions::default(),
+      assets: None,
     }
   }
 }
@@ -100,7 +325,8 @@ impl Default for ProcessOptions<'_> {
 ///
 /// # Arguments
 /// * `client` - Confluence API client for fetching attachments.
-/// * `page` - The page to process (must have storage content).
+/// * `page` - The page to process (must have storage content, unless it is a whiteboard, database, or embed, which
+///   becomes a stub).
 /// * `options` - Processing options controlling conversion and downloads.
 ///
 /// # Returns
@@ -110,26 +336,81 @@ pub async fn process_page(
   page: &Page,
   options: &ProcessOptions<'_>,
 ) -> Result<ProcessedPage> {
-  let storage_content = page
-    .body
-    .as_ref()
-    .and_then(|b| b.storage.as_ref())
-    .map(|s| s.value.as_str())
+  if !page.content_kind().is_exportable() {
+    return Ok(stub_page(page, options));
+  }
+
+  let redacted_storage = page
+    .storage_content()
+    .map(|storage| redaction::redact_storage(storage, &options.redacted_macros))
+    .transpose()
+    .with_context(|| format!("Failed to redact macros of page '{}'", page.title))?;
+  let storage_content = redacted_storage.as_deref();
+  let legacy_wiki = page.legacy_wiki();
+  let source = storage_content
+    .or(legacy_wiki)
     .ok_or_else(|| anyhow::anyhow!("Page '{}' has no storage content", page.title))?;
 
-  let filename = sanitize_filename(&page.title);
+  let filename = options.filenames.stem(page);
+  let format = options.format.content_format();
+
+  // Links without an explicit space refer to the space of the page itself,
+  // and relative links are resolved from the page's own output file
+  let mut markdown_options = options.markdown_options.clone();
+  if let Some(space) = &page.space {
+    markdown_options.links.current_space = Some(space.key.clone());
+  }
+  markdown_options.links.current_page = Some(page.id.clone());
+  markdown_options.redacted_macros.clone_from(&options.redacted_macros);
+  if let Some(split) = &mut markdown_options.table_split {
+    split.page_filename.clone_from(&filename);
+  }
+  if format == OutputFormat::Markdown
+    && let (Some(resolver), Some(storage_content)) = (options.includes, storage_content)
+  {
+    markdown_options.includes = Some(resolver.resolve(client, page, storage_content).await);
+  }
+
+  let table_split = markdown_options.table_split.clone();
+  let page_links = markdown_options.links.clone();
 
   // Convert to target format
-  let mut output_content = match options.format {
-    OutputFormat::Markdown => markdown::storage_to_markdown_with_options(storage_content, &options.markdown_options)
-      .map_err(|e| anyhow::anyhow!("Failed to convert page '{}' to markdown: {}", page.title, e))?,
-    OutputFormat::AsciiDoc => asciidoc::storage_to_asciidoc_with_options(storage_content, &options.asciidoc_options)
-      .map_err(|e| anyhow::anyhow!("Failed to convert page '{}' to asciidoc: {}", page.title, e))?,
+  let format_name = match format {
+    OutputFormat::Markdown | OutputFormat::Json => "markdown",
+    OutputFormat::AsciiDoc => "asciidoc",
   };
+  let mut output_content = match (storage_content, legacy_wiki) {
+    (None, Some(wiki)) => convert_legacy_wiki(page, wiki, format, &markdown_options)?,
+    _ => convert_cached(source, options, markdown_options)
+      .await
+      .map_err(|e| anyhow::anyhow!("Failed to convert page '{}' to {}: {}", page.title, format_name, e))?,
+  };
+
+  if format == OutputFormat::Markdown
+    && let Some(fetcher) = options.link_titles
+  {
+    output_content = fetcher.apply_to_markdown(&output_content).await;
+  }
+
+  if let Some(users) = options.users {
+    output_content = users.apply(client, &output_content).await;
+  }
 
   let mut images = Vec::new();
   let mut downloaded_image_filenames = HashSet::new();
   let mut attachments_data = Vec::new();
+  let mut link_map = LinkMap::default();
+  // Only the Markdown converter writes relative links to other pages
+  if format != OutputFormat::AsciiDoc
+    && let Some(storage_content) = storage_content
+  {
+    link_map.pages = page_links.rewritten_links(storage_content)?;
+  }
+
+  let fetcher = AssetFetcher {
+    client,
+    assets: options.assets,
+  };
 
   // Fetch attachments once if we need them for images or attachments
   let page_attachments = if options.download_images || options.download_attachments {
@@ -144,30 +425,83 @@ pub async fn process_page(
   };
 
   // Process images if requested
+  let image_placement = ImagePlacement {
+    images_dir: &options.images_dir,
+    naming: options.image_naming,
+    quality: options.image_quality,
+    page_id: &page.id,
+  };
   if options.download_images {
-    let image_refs = images::extract_image_references(storage_content)?;
+    let image_refs = match storage_content {
+      Some(storage_content) => images::extract_image_references(storage_content)?,
+      None => markdown::wiki::wiki_image_references(source),
+    };
 
     if !image_refs.is_empty()
       && let Some(ref attachments) = page_attachments
     {
       let (downloaded_images, filename_map) = fetch_images_from_attachments(
-        client,
+        &fetcher,
         attachments,
         &image_refs,
-        &options.images_dir,
+        &image_placement,
         options.output_dir,
         options.overwrite,
+        options.image_converter.as_ref(),
       )
       .await?;
 
       images = downloaded_images;
       downloaded_image_filenames.extend(filename_map.keys().cloned());
+      link_map.images = filename_map
+        .iter()
+        .map(|(filename, path)| (filename.clone(), link_path(path)))
+        .collect();
 
       // Update content with local image paths
-      output_content = match options.format {
-        OutputFormat::Markdown => images::update_markdown_image_links(&output_content, &filename_map),
+      output_content = match format {
+        OutputFormat::Markdown | OutputFormat::Json => {
+          images::update_markdown_image_links(&output_content, &filename_map)
+        }
         OutputFormat::AsciiDoc => images::update_asciidoc_image_links(&output_content, &filename_map),
       };
+    } else if image_refs.is_empty()
+      && let Some(view) = page.body.as_ref().and_then(|b| b.view.as_ref())
+    {
+      // Older pages may only embed images in the rendered view
+      let view_images = images::extract_view_image_references(&view.value);
+      if !view_images.is_empty() {
+        let (downloaded_images, url_map) = fetch_view_images(
+          &fetcher,
+          &view_images,
+          &image_placement,
+          options.output_dir,
+          options.overwrite,
+        )
+        .await?;
+
+        images = downloaded_images;
+        downloaded_image_filenames.extend(view_images.iter().map(|image| image.filename.clone()));
+        link_map.images = url_map
+          .iter()
+          .map(|(url, path)| (url.clone(), link_path(path)))
+          .collect();
+        output_content = images::link_view_images(&output_content, &view_images, &url_map, format);
+      }
+    }
+
+    if let (Some(fetcher), Some(storage_content)) = (options.external_images, storage_content) {
+      let urls = images::extract_external_image_urls(storage_content)?;
+      let (downloaded_images, url_map) =
+        fetch_external_images(fetcher, &urls, &image_placement, options.output_dir, options.overwrite).await;
+      images.extend(downloaded_images);
+      link_map
+        .images
+        .extend(url_map.iter().map(|(url, path)| (url.clone(), link_path(path))));
+      output_content = match format {
+        OutputFormat::Markdown | OutputFormat::Json => images::update_markdown_image_links(&output_content, &url_map),
+        OutputFormat::AsciiDoc => images::update_asciidoc_image_links(&output_content, &url_map),
+      };
     }
   }
 
@@ -180,30 +514,257 @@ pub async fn process_page(
     };
 
     if let Some(ref attachments) = page_attachments {
+      let placement = AttachmentPlacement {
+        layout: options.attachments_layout,
+        page_dir: filename.clone(),
+        skip_titles,
+      };
       let (fetched_attachments, downloaded_info) =
-        fetch_attachments_from_list(client, attachments, skip_titles, options.output_dir, options.overwrite).await?;
+        fetch_attachments_from_list(&fetcher, attachments, &placement, options.output_dir, options.overwrite).await?;
 
       attachments_data = fetched_attachments;
+      link_map.attachments = downloaded_info
+        .iter()
+        .map(|attachment| (attachment.original_name.clone(), link_path(&attachment.relative_path)))
+        .collect();
 
       if !downloaded_info.is_empty() {
-        output_content = attachments::update_markdown_attachment_links(&output_content, &downloaded_info);
+        output_content = match format {
+          OutputFormat::Markdown | OutputFormat::Json => {
+            attachments::update_markdown_attachment_links(&output_content, &downloaded_info)
+          }
+          OutputFormat::AsciiDoc => attachments::update_asciidoc_attachment_links(&output_content, &downloaded_info),
+        };
       }
     }
   }
 
   let raw_storage = if options.save_raw {
-    Some(storage_content.to_string())
+    Some(source.to_string())
   } else {
     None
   };
 
+  let mut tables = match (options.extract_tables, storage_content) {
+    (Some(table_format), Some(storage_content)) => {
+      table_export::extract_tables(storage_content, &filename, table_format)?
+    }
+    _ => Vec::new(),
+  };
+  // Tables extracted to CSV above already hold the split tables' files
+  if let (Some(split), Some(storage_content), OutputFormat::Markdown) = (&table_split, storage_content, format)
+    && options.extract_tables != Some(TableFormat::Csv)
+  {
+    tables.extend(table_export::extract_split_tables(storage_content, split)?);
+  }
+
+  let mut front_matter = page_front_matter(page, options);
+  if let Some(state) = fetch_content_state(client, page, options.content_state).await {
+    if options.content_state.in_front_matter() {
+      front_matter.insert("content_state", state.name.clone());
+    }
+    if options.content_state.as_badge() {
+      let badge = render_content_state_badge(&state, format, &options.markdown_options.dialect);
+      output_content = format!("{badge}{output_content}");
+    }
+  }
+  if let Some(rules) = options.front_matter_rules {
+    rules.apply(page, &mut front_matter);
+  }
+  if let Some(template) = options.template {
+    output_content = template.render(
+      page,
+      &output_content,
+      &link_map,
+      options.markdown_options.links.instance_url.as_deref(),
+    );
+  }
+
   Ok(ProcessedPage {
     filename,
     content: output_content,
     raw_storage,
     images,
     attachments: attachments_data,
+    tables,
+    front_matter,
+    link_map,
+    record: (options.format == OutputFormat::Json).then(|| PageRecord::new(page, storage_content)),
+  })
+}
+
+/// Page written for content that cannot be converted, linking back to
+/// Confluence.
+fn stub_page(page: &Page, options: &ProcessOptions<'_>) -> ProcessedPage {
+  let mut front_matter = page_front_matter(page, options);
+  if let Some(rules) = options.front_matter_rules {
+    rules.apply(page, &mut front_matter);
+  }
+
+  let instance_url = options.markdown_options.links.instance_url.as_deref();
+  let mut content = content_stubs::render_stub(page, instance_url, options.format);
+  if let Some(template) = options.template {
+    content = template.render(page, &content, &LinkMap::default(), instance_url);
+  }
+
+  ProcessedPage {
+    filename: options.filenames.stem(page),
+    content,
+    front_matter,
+    record: (options.format == OutputFormat::Json).then(|| PageRecord::new(page, None)),
+    ..Default::default()
+  }
+}
+
+/// Front matter fields taken from the page's metadata.
+fn page_front_matter(page: &Page, options: &ProcessOptions<'_>) -> FrontMatter {
+  let mut front_matter = FrontMatter::default();
+  if options.author_metadata {
+    if let Some(author) = page.created_by() {
+      front_matter.insert("created_by", author);
+    }
+    if let Some(editor) = page.last_modified_by() {
+      front_matter.insert("last_modified_by", editor);
+    }
+  }
+  front_matter
+}
+
+/// Fetch the page's content state when it is shown anywhere.
+///
+/// Failures are logged and treated as "no state" so a page is still exported
+/// when the state cannot be read.
+async fn fetch_content_state(
+  client: &dyn ConfluenceApi,
+  page: &Page,
+  display: ContentStateDisplay,
+) -> Option<ContentState> {
+  if display == ContentStateDisplay::Off {
+    return None;
+  }
+
+  match client.get_content_state(&page.id).await {
+    Ok(state) => state,
+    Err(err) => {
+      warn!("Failed to fetch content state of '{}': {err:#}", page.title);
+      None
+    }
+  }
+}
+
+/// Render a content state as a status line placed above the page body.
+fn render_content_state_badge(state: &ContentState, format: OutputFormat, dialect: &MarkdownDialect) -> String {
+  match format {
+    OutputFormat::Markdown | OutputFormat::Json => format!("{} {}\n\n", dialect.strong("Status:"), state.name),
+    OutputFormat::AsciiDoc => format!("*Status:* {}\n\n", state.name),
+  }
+}
+
+/// Convert a page that only has legacy wiki markup.
+///
+/// The wiki converter only produces Markdown, so such pages cannot be
+/// exported as AsciiDoc.
+fn convert_legacy_wiki(
+  page: &Page,
+  wiki: &str,
+  format: OutputFormat,
+  markdown_options: &MarkdownOptions,
+) -> Result<String> {
+  if format != OutputFormat::Markdown {
+    bail!(
+      "Page '{}' only has legacy wiki markup, which can only be exported as Markdown",
+      page.title
+    );
+  }
+  debug!("Converting legacy wiki markup of '{}'", page.title);
+  let markdown = markdown::wiki::wiki_to_markdown(wiki, &markdown_options.dialect);
+  if markdown_options.lint_fix {
+    return Ok(markdown::lint::autofix(&markdown));
+  }
+  Ok(markdown)
+}
+
+/// Convert storage content, reusing the result of an identical earlier
+/// conversion from the conversion cache when one is configured.
+async fn convert_cached(
+  storage_content: &str,
+  options: &ProcessOptions<'_>,
+  markdown_options: MarkdownOptions,
+) -> Result<String> {
+  let Some(cache) = &options.conversion_cache else {
+    return convert_storage(
+      storage_content,
+      options.format.content_format(),
+      markdown_options,
+      options.asciidoc_options,
+    )
+    .await;
+  };
+
+  let key = ConversionCache::key(
+    storage_content,
+    options.format.content_format(),
+    &markdown_options,
+    &options.asciidoc_options,
+  );
+  if let Some(content) = cache.get(&key) {
+    return Ok(content);
+  }
+  let content = convert_storage(
+    storage_content,
+    options.format.content_format(),
+    markdown_options,
+    options.asciidoc_options,
+  )
+  .await?;
+  if let Err(err) = cache.put(&key, &content) {
+    warn!("Failed to cache conversion: {err:#}");
+  }
+  Ok(content)
+}
+
+/// Bounds the number of conversions queued on the blocking thread pool.
+static CONVERSION_SLOTS: LazyLock<Semaphore> = LazyLock::new(|| {
+  let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+  Semaphore::new(cores)
+});
+
+/// Convert storage content to the target format on the blocking thread pool.
+///
+/// Conversion is CPU-bound, so running it on the async worker threads would
+/// stall network I/O for other pages on large documents. At most one
+/// conversion per core runs at a time; further pages wait for a slot without
+/// occupying a pool thread.
+///
+/// # Arguments
+/// * `storage_content` - Confluence storage format to convert.
+/// * `format` - Target output format.
+/// * `markdown_options` - Options used when `format` is Markdown.
+/// * `asciidoc_options` - Options used when `format` is AsciiDoc.
+///
+/// # Errors
+/// Returns an error when parsing fails or the conversion task panics.
+async fn convert_storage(
+  storage_content: &str,
+  format: OutputFormat,
+  markdown_options: MarkdownOptions,
+  asciidoc_options: AsciiDocOptions,
+) -> Result<String> {
+  let _slot = CONVERSION_SLOTS
+    .acquire()
+    .await
+    .context("Conversion limiter became unavailable")?;
+
+  let storage_content = storage_content.to_string();
+  tokio::task::spawn_blocking(move || match format {
+    OutputFormat::Markdown | OutputFormat::Json => Ok(markdown::storage_to_markdown_with_options(
+      &storage_content,
+      &markdown_options,
+    )?),
+    OutputFormat::AsciiDoc => asciidoc::storage_to_asciidoc_with_options(&storage_content, &asciidoc_options),
   })
+  .await
+  .context("Conversion task failed")?
 }
 
 /// Write a processed page to disk.
@@ -215,31 +776,45 @@ pub async fn process_page(
 /// # Arguments
 /// * `page` - The processed page data to write.
 /// * `output_dir` - The directory where the page and assets should be written.
-/// * `format` - The output format (determines file extension).
-/// * `overwrite` - Whether to overwrite existing files.
+/// * `options` - Format, file extension, and overwrite/conflict behaviour.
+/// * `assets` - Registry of the export, serializing asset writes of its pages.
 ///
 /// # Returns
-/// The path to the written page file on success.
-pub fn write_processed_page(
+/// The path to the written page file on success. With
+/// [`ConflictPolicy::New`] this is the `.new` file written next to an edited
+/// page; with [`ConflictPolicy::Skip`] it is the untouched existing file.
+pub async fn write_processed_page(
   page: &ProcessedPage,
   output_dir: &Path,
-  format: OutputFormat,
-  overwrite: bool,
+  options: &WriteOptions,
+  assets: &AssetRegistry,
 ) -> Result<PathBuf> {
+  // Re-syncing with conflict detection refreshes everything but edited pages
+  let overwrite = options.overwrite || options.conflict.is_some();
+
   // Create output directory
   fs::create_dir_all(output_dir)
     .with_context(|| format!("Failed to create output directory {}", output_dir.display()))?;
 
   // Write images
   for image in &page.images {
-    let image_path = output_dir.join(&image.relative_path);
-    write_asset(&image_path, &image.content, overwrite)?;
+    let image_path = normalize_path(&output_dir.join(&image.relative_path));
+    assets
+      .write(&page.filename, &image_path, &image.content, overwrite)
+      .await?;
   }
 
   // Write attachments
   for attachment in &page.attachments {
-    let attachment_path = output_dir.join(&attachment.relative_path);
-    write_asset(&attachment_path, &attachment.content, overwrite)?;
+    let attachment_path = normalize_path(&output_dir.join(&attachment.relative_path));
+    assets
+      .write(&page.filename, &attachment_path, &attachment.content, overwrite)
+      .await?;
+  }
+
+  // Write extracted tables
+  for table in &page.tables {
+    write_content(&output_dir.join(&table.relative_path), &table.content, overwrite)?;
   }
 
   // Write raw storage if present
@@ -249,11 +824,112 @@ pub fn write_processed_page(
   }
 
   // Write main content
-  let extension = format.file_extension();
+  let extension = options.file_extension();
   let output_path = output_dir.join(format!("{}.{}", page.filename, extension));
-  write_file(&output_path, page.content.as_bytes(), overwrite)?;
+  match options.conflict {
+    Some(policy) => write_tracked_page(&output_path, page, options, policy),
+    None => {
+      let content = render_page_file(page, options.format)?;
+      write_file(&output_path, content.as_bytes(), overwrite)?;
+      Ok(output_path)
+    }
+  }
+}
+
+/// Full content of a page file: the front matter followed by the content, or
+/// the page's JSON document for JSON exports.
+///
+/// # Errors
+/// Returns an error when a JSON document cannot be serialized.
+pub fn render_page_file(page: &ProcessedPage, format: OutputFormat) -> Result<String> {
+  match format {
+    OutputFormat::Json => json_export::render_document(page),
+    format => Ok(format!("{}{}", page.front_matter.render(format), page.content)),
+  }
+}
+
+/// Resolve `..` components of a joined asset path.
+///
+/// Shared assets are referenced from nested page directories as
+/// `../assets/<file>`, so the same file must map to one path for the asset
+/// registry to serialize its writes.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+        normalized.pop();
+      }
+      Component::CurDir => {}
+      component => normalized.push(component),
+    }
+  }
+  normalized
+}
+
+/// Write page content with a content hash in its front matter, applying the
+/// conflict policy when the existing file was edited since the last export.
+///
+/// Existing files without a recorded hash may hold edits that cannot be
+/// detected, so they are only replaced with `--overwrite` and otherwise go
+/// through the conflict policy like edited files.
+fn write_tracked_page(
+  path: &Path,
+  page: &ProcessedPage,
+  options: &WriteOptions,
+  policy: ConflictPolicy,
+) -> Result<PathBuf> {
+  let format = options.format;
+  let mut front_matter = page.front_matter.clone();
+  front_matter.insert(CONTENT_HASH_KEY, front_matter::content_hash(&page.content));
+  let content = format!("{}{}", front_matter.render(format), page.content);
+
+  let existing = match fs::read_to_string(path) {
+    Ok(existing) => existing,
+    Err(err) if err.kind() == ErrorKind::NotFound => {
+      write_file(path, content.as_bytes(), false)?;
+      return Ok(path.to_path_buf());
+    }
+    Err(err) => return Err(err).with_context(|| format!("Failed to read {}", path.display())),
+  };
+
+  let reason = match front_matter::has_local_edits(&existing, format) {
+    Some(false) => None,
+    Some(true) => Some("Local edits detected in"),
+    None if options.overwrite => None,
+    None => Some("No content hash recorded in"),
+  };
+  let Some(reason) = reason else {
+    write_file(path, content.as_bytes(), true)?;
+    return Ok(path.to_path_buf());
+  };
+
+  match policy {
+    ConflictPolicy::Skip => {
+      warn!("{reason} {}; skipping", path.display());
+      Ok(path.to_path_buf())
+    }
+    ConflictPolicy::Overwrite => {
+      warn!("{reason} {}; overwriting", path.display());
+      write_file(path, content.as_bytes(), true)?;
+      Ok(path.to_path_buf())
+    }
+    ConflictPolicy::New => {
+      let mut new_path = path.as_os_str().to_owned();
+      new_path.push(CONFLICT_SUFFIX);
+      let new_path = PathBuf::from(new_path);
+      warn!("{reason} {}; writing {}", path.display(), new_path.display());
+      write_file(&new_path, content.as_bytes(), true)?;
+      Ok(new_path)
+    }
+  }
+}
 
-  Ok(output_path)
+/// The edited page file that a file written by [`ConflictPolicy::New`] stands
+/// next to, or `None` for any other file.
+pub(crate) fn edited_page_path(path: &Path) -> Option<PathBuf> {
+  let stem = path.to_str()?.strip_suffix(CONFLICT_SUFFIX)?;
+  Some(PathBuf::from(stem))
 }
 
 /// Fetch images from a pre-fetched attachments list and return their data
@@ -262,12 +938,13 @@ pub fn write_processed_page(
 /// When `output_dir` is provided and `overwrite` is false, skips fetching
 /// images that already exist on disk to avoid unnecessary network requests.
 async fn fetch_images_from_attachments(
-  client: &dyn ConfluenceApi,
+  fetcher: &AssetFetcher<'_>,
   attachments: &[crate::confluence::Attachment],
   image_refs: &[ImageReference],
-  images_subdir: &str,
+  placement: &ImagePlacement<'_>,
   output_dir: Option<&Path>,
   overwrite: bool,
+  converter: Option<&Arc<dyn ImageConverter>>,
 ) -> Result<(Vec<AssetData>, HashMap<String, PathBuf>)> {
   let mut filename_map = HashMap::new();
 
@@ -280,9 +957,13 @@ async fn fetch_images_from_attachments(
     image_filename: String,
     download_url: String,
     relative_path: PathBuf,
+    /// Path of the downloaded image when it is converted to `relative_path`,
+    /// used if the conversion fails.
+    unconverted_path: Option<PathBuf>,
   }
 
   let mut tasks = Vec::new();
+  let mut used_paths = HashMap::new();
   for image_ref in image_refs {
     let attachment = attachments
       .iter()
@@ -294,15 +975,20 @@ async fn fetch_images_from_attachments(
       .as_ref()
       .and_then(|l| l.download.as_ref())
       .with_context(|| format!("No download link for attachment: {}", image_ref.filename))?;
-
-    let safe_filename = sanitize_asset_filename(&image_ref.filename);
-    let relative_path = PathBuf::from(images_subdir).join(&safe_filename);
-
-    let needs_fetch = if let Some(dir) = output_dir {
-      let full_path = dir.join(&relative_path);
-      overwrite || !full_path.exists()
-    } else {
-      true
+    let (name, download_url) = placement.quality.resolve(&image_ref.filename, download_url);
+    let name = image_formats::image_file_name(&name, attachment.media_type.as_deref());
+
+    // Formats browsers cannot render are written as PNG when a converter is set
+    let unconverted_path =
+      (converter.is_some() && image_formats::is_unsupported(&name)).then(|| placement.relative_path(&name));
+    let relative_path = match &unconverted_path {
+      Some(path) => path.with_extension(image_formats::CONVERTED_EXTENSION),
+      None => placement.relative_path(&name),
+    };
+    let relative_path = unique_image_path(&mut used_paths, relative_path, &image_ref.filename);
+    let needs_fetch = match output_dir {
+      Some(dir) => overwrite || placement.naming == ImageNaming::Hash || !dir.join(&relative_path).exists(),
+      None => true,
     };
 
     filename_map.insert(image_ref.filename.clone(), relative_path.clone());
@@ -310,46 +996,344 @@ async fn fetch_images_from_attachments(
     if needs_fetch {
       tasks.push(ImageFetchTask {
         image_filename: image_ref.filename.clone(),
-        download_url: download_url.clone(),
+        download_url,
         relative_path,
+        unconverted_path,
       });
     }
   }
 
   // Phase 2: Fetch all needed images concurrently
-  let fetch_futures: Vec<_> = tasks
+  let fetch_futures = tasks
     .iter()
     .map(|task| {
       let url = task.download_url.clone();
       let filename = task.image_filename.clone();
       let path = task.relative_path.clone();
+      let unconverted_path = task.unconverted_path.clone();
       async move {
-        let bytes = client
-          .fetch_attachment(&url)
+        // Images to convert are read into memory for the converter
+        let convert = unconverted_path.is_some() && converter.is_some();
+        let content = fetcher
+          .fetch(&url, output_dir.filter(|_| !convert), &path)
           .await
           .with_context(|| format!("Failed to fetch image: {filename}"))?;
-        Ok::<_, anyhow::Error>(AssetData {
-          relative_path: path,
-          content: bytes,
+        let (path, content) = match (unconverted_path, converter, content) {
+          (Some(unconverted_path), Some(converter), AssetContent::Bytes(bytes)) => {
+            let (path, bytes) = convert_image(converter, &filename, path, unconverted_path, bytes).await;
+            (path, AssetContent::Bytes(bytes))
+          }
+          (_, _, content) => (path, content),
+        };
+        Ok(AssetData {
+          relative_path: placement.content_path(&path, &content.sha256()),
+          content,
         })
       }
+      .boxed()
     })
     .collect();
 
-  let assets = try_join_all(fetch_futures).await?;
+  let assets = fetch_assets(fetch_futures).await?;
+  for (task, asset) in tasks.iter().zip(&assets) {
+    filename_map.insert(task.image_filename.clone(), asset.relative_path.clone());
+  }
 
   Ok((assets, filename_map))
 }
 
+/// A path for an image that no other image of the page is written to.
+///
+/// Distinct attachments can end up with the same file name once names are
+/// sanitized or given the extension of their format; later ones get a `-2`,
+/// `-3`, ... suffix.
+fn unique_image_path(used: &mut HashMap<PathBuf, String>, path: PathBuf, filename: &str) -> PathBuf {
+  let name = path
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  let (base, ext) = split_name_and_extension(&name);
+  let mut candidate = path.clone();
+  let mut suffix = 2;
+  loop {
+    match used.get(&candidate) {
+      Some(owner) if owner != filename => {
+        candidate = path.with_file_name(next_candidate(&base, &ext, suffix));
+        suffix += 1;
+      }
+      Some(_) => return candidate,
+      None => {
+        used.insert(candidate.clone(), filename.to_string());
+        return candidate;
+      }
+    }
+  }
+}
+
+/// Convert a downloaded image to PNG.
+///
+/// # Returns
+/// The converted image and its path, or the downloaded image and
+/// `unconverted_path` when the conversion fails.
+async fn convert_image(
+  converter: &Arc<dyn ImageConverter>,
+  filename: &str,
+  path: PathBuf,
+  unconverted_path: PathBuf,
+  bytes: Vec<u8>,
+) -> (PathBuf, Vec<u8>) {
+  let extension = unconverted_path
+    .extension()
+    .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+    .unwrap_or_default();
+  let task = {
+    let converter = Arc::clone(converter);
+    let bytes = bytes.clone();
+    tokio::task::spawn_blocking(move || converter.convert(&bytes, &extension))
+  };
+  match task.await.map_err(anyhow::Error::from).and_then(|result| result) {
+    Ok(converted) => (path, converted),
+    Err(err) => {
+      warn!("Failed to convert image {filename}; keeping the original: {err:#}");
+      (unconverted_path, bytes)
+    }
+  }
+}
+
+/// Downloads the assets of a page through the export's [`AssetRegistry`].
+struct AssetFetcher<'a> {
+  client: &'a dyn ConfluenceApi,
+  assets: Option<&'a AssetRegistry>,
+}
+
+impl AssetFetcher<'_> {
+  /// Download an asset, sharing the download with other pages of the export
+  /// that fetch the same file to the same path at the same time.
+  ///
+  /// With an output directory the download is streamed to a staging file
+  /// next to its final path, reporting progress through the client, and
+  /// moved into place when the page is written. Without one it is read into
+  /// memory.
+  async fn fetch(&self, url: &str, output_dir: Option<&Path>, relative_path: &Path) -> Result<AssetContent> {
+    let client = self.client;
+    let target = output_dir.map_or_else(|| relative_path.to_path_buf(), |dir| dir.join(relative_path));
+    let fetch = || async {
+      match output_dir {
+        Some(_) => stage_download(client, url, &target).await,
+        None => Ok(AssetContent::Bytes(client.fetch_attachment(url).await?)),
+      }
+    };
+    match self.assets {
+      Some(assets) => assets.download(target.clone(), url, fetch).await,
+      None => fetch().await,
+    }
+  }
+}
+
+/// Stream a download to the staging file of `target` and hash it from disk.
+async fn stage_download(client: &dyn ConfluenceApi, url: &str, target: &Path) -> Result<AssetContent> {
+  let path = staging_path(target);
+  client.download_attachment(url, &path).await?;
+
+  let staged = path.clone();
+  let (size, sha256) = tokio::task::spawn_blocking(move || -> Result<_> {
+    let metadata = fs::metadata(&staged).with_context(|| format!("Failed to read {}", staged.display()))?;
+    Ok((metadata.len(), sha256_file(&staged)?))
+  })
+  .await
+  .context("Hashing task failed")??;
+  Ok(AssetContent::Staged(StagedFile { path, size, sha256 }))
+}
+
+/// Path a download for `target` waits at until its page is written.
+fn staging_path(target: &Path) -> PathBuf {
+  let mut name = target.file_name().unwrap_or_default().to_os_string();
+  name.push(".download");
+  target.with_file_name(name)
+}
+
+/// Await asset fetches with at most [`MAX_CONCURRENT_ASSET_DOWNLOADS`] in
+/// flight, keeping the assets in the order of the fetches.
+async fn fetch_assets(fetches: Vec<BoxFuture<'_, Result<AssetData>>>) -> Result<Vec<AssetData>> {
+  stream::iter(fetches)
+    .buffered(MAX_CONCURRENT_ASSET_DOWNLOADS)
+    .try_collect()
+    .await
+}
+
+/// Fetch images referenced only by the view HTML of a page.
+///
+/// Images are downloaded directly from their `src` URLs through the
+/// authenticated client.
+///
+/// # Returns
+/// A tuple of (assets to write, mapping from image URL to relative path).
+async fn fetch_view_images(
+  fetcher: &AssetFetcher<'_>,
+  view_images: &[ViewImage],
+  placement: &ImagePlacement<'_>,
+  output_dir: Option<&Path>,
+  overwrite: bool,
+) -> Result<(Vec<AssetData>, HashMap<String, PathBuf>)> {
+  let mut url_map = HashMap::new();
+  let mut tasks = Vec::new();
+
+  for image in view_images {
+    let (name, download_url) = placement.quality.resolve(&image.filename, &image.url);
+    let relative_path = placement.relative_path(&name);
+    let needs_fetch = match output_dir {
+      Some(dir) => overwrite || placement.naming == ImageNaming::Hash || !dir.join(&relative_path).exists(),
+      None => true,
+    };
+
+    url_map.insert(image.url.clone(), relative_path.clone());
+    if needs_fetch {
+      tasks.push((image, download_url, relative_path));
+    }
+  }
+
+  let fetch_futures = tasks
+    .iter()
+    .map(|(image, download_url, relative_path)| {
+      async move {
+        let content = fetcher
+          .fetch(download_url, output_dir, relative_path)
+          .await
+          .with_context(|| format!("Failed to fetch image: {}", image.filename))?;
+        Ok(AssetData {
+          relative_path: placement.content_path(relative_path, &content.sha256()),
+          content,
+        })
+      }
+      .boxed()
+    })
+    .collect();
+
+  let assets = fetch_assets(fetch_futures).await?;
+  for ((image, _, _), asset) in tasks.iter().zip(&assets) {
+    url_map.insert(image.url.clone(), asset.relative_path.clone());
+  }
+  Ok((assets, url_map))
+}
+
+/// Download images embedded from other sites.
+///
+/// Images that cannot be downloaded, are not images, or exceed the size cap
+/// are reported and stay linked to their URL instead of failing the page.
+///
+/// # Returns
+/// A tuple of (assets to write, mapping from image URL to relative path).
+async fn fetch_external_images(
+  fetcher: &ExternalImageFetcher,
+  urls: &[String],
+  placement: &ImagePlacement<'_>,
+  output_dir: Option<&Path>,
+  overwrite: bool,
+) -> (Vec<AssetData>, HashMap<String, PathBuf>) {
+  let mut url_map = HashMap::new();
+  let mut tasks = Vec::new();
+  for url in urls {
+    let relative_path = placement.relative_path(&external_images::file_name(url));
+    let exists = output_dir.is_some_and(|dir| dir.join(&relative_path).exists());
+    if exists && !overwrite && placement.naming != ImageNaming::Hash {
+      url_map.insert(url.clone(), relative_path);
+    } else {
+      tasks.push((url, relative_path));
+    }
+  }
+
+  let fetch_futures: Vec<BoxFuture<'_, Option<(&String, AssetData)>>> = tasks
+    .into_iter()
+    .map(|(url, relative_path)| {
+      async move {
+        match fetcher.fetch(url).await {
+          Ok(content) => Some((
+            url,
+            AssetData {
+              relative_path: placement.content_path(&relative_path, &sha256_hex(&content)),
+              content: AssetContent::Bytes(content),
+            },
+          )),
+          Err(err) => {
+            warn!("Keeping external image {url} linked: {err:#}");
+            None
+          }
+        }
+      }
+      .boxed()
+    })
+    .collect();
+
+  let fetched: Vec<_> = stream::iter(fetch_futures)
+    .buffered(MAX_CONCURRENT_ASSET_DOWNLOADS)
+    .collect()
+    .await;
+  let mut assets = Vec::new();
+  for (url, asset) in fetched.into_iter().flatten() {
+    url_map.insert(url.clone(), asset.relative_path.clone());
+    assets.push(asset);
+  }
+  (assets, url_map)
+}
+
+/// Where the images of one page are placed.
+struct ImagePlacement<'a> {
+  /// Subdirectory of the page's output directory holding images.
+  images_dir: &'a str,
+  naming: ImageNaming,
+  /// Whether original images or their thumbnails are downloaded.
+  quality: ImageQuality,
+  /// ID of the page, used by [`ImageNaming::PageId`].
+  page_id: &'a str,
+}
+
+impl ImagePlacement<'_> {
+  /// Path of an image relative to the page's output directory.
+  ///
+  /// With [`ImageNaming::Hash`] this is only the provisional path used until
+  /// the content is known; see [`ImagePlacement::content_path`].
+  fn relative_path(&self, filename: &str) -> PathBuf {
+    let safe_filename = sanitize_asset_filename(filename);
+    let name = match self.naming {
+      ImageNaming::PageId => format!("{}-{safe_filename}", self.page_id),
+      ImageNaming::Name | ImageNaming::Hash => safe_filename,
+    };
+    PathBuf::from(self.images_dir).join(name)
+  }
+
+  /// Final path of a downloaded image, appending its content hash `digest` to
+  /// the file stem with [`ImageNaming::Hash`].
+  fn content_path(&self, relative_path: &Path, digest: &str) -> PathBuf {
+    if self.naming != ImageNaming::Hash {
+      return relative_path.to_path_buf();
+    }
+    let name = relative_path
+      .file_name()
+      .map(|name| name.to_string_lossy().into_owned())
+      .unwrap_or_default();
+    let (base, ext) = split_name_and_extension(&name);
+    relative_path.with_file_name(next_candidate(&base, &ext, &digest[..IMAGE_HASH_LEN]))
+  }
+}
+
+/// Where the attachments of one page are placed.
+struct AttachmentPlacement<'a> {
+  layout: AttachmentLayout,
+  /// Sanitized page title, used by [`AttachmentLayout::PerPage`].
+  page_dir: String,
+  /// Attachment titles already handled as images.
+  skip_titles: Option<&'a HashSet<String>>,
+}
+
 /// Fetch attachments from a pre-fetched list and return their data along with
 /// metadata for link rewriting.
 ///
 /// When `output_dir` is provided and `overwrite` is false, skips fetching
 /// attachments that already exist on disk to avoid unnecessary network requests.
 async fn fetch_attachments_from_list(
-  client: &dyn ConfluenceApi,
+  fetcher: &AssetFetcher<'_>,
   attachments: &[crate::confluence::Attachment],
-  skip_titles: Option<&HashSet<String>>,
+  placement: &AttachmentPlacement<'_>,
   output_dir: Option<&Path>,
   overwrite: bool,
 ) -> Result<(Vec<AssetData>, Vec<DownloadedAttachment>)> {
@@ -367,10 +1351,10 @@ async fn fetch_attachments_from_list(
   }
 
   let mut tasks = Vec::new();
-  let mut used_filenames = HashSet::new();
+  let mut used_paths = HashSet::new();
 
   for attachment in attachments {
-    if let Some(skip) = skip_titles
+    if let Some(skip) = placement.skip_titles
       && skip.contains(&attachment.title)
     {
       continue;
@@ -381,18 +1365,17 @@ async fn fetch_attachments_from_list(
       None => continue,
     };
 
+    let directory = placement.layout.directory(attachment, &placement.page_dir);
     let sanitized = sanitize_asset_filename(&attachment.title);
     let (base, ext) = split_name_and_extension(&sanitized);
-    let mut filename = sanitized.clone();
+    let mut relative_path = directory.join(&sanitized);
     let mut counter = 1;
 
-    while used_filenames.contains(&filename) {
-      filename = next_candidate(&base, &ext, counter);
+    while used_paths.contains(&relative_path) {
+      relative_path = directory.join(next_candidate(&base, &ext, counter));
       counter += 1;
     }
-    used_filenames.insert(filename.clone());
-
-    let relative_path = PathBuf::from(ATTACHMENTS_DIR).join(&filename);
+    used_paths.insert(relative_path.clone());
 
     let needs_fetch = if let Some(dir) = output_dir {
       let full_path = dir.join(&relative_path);
@@ -416,30 +1399,166 @@ async fn fetch_attachments_from_list(
   }
 
   // Phase 2: Fetch all needed attachments concurrently
-  let fetch_futures: Vec<_> = tasks
+  let fetch_futures = tasks
     .iter()
     .map(|task| {
       let url = task.download_url.clone();
       let name = task.original_name.clone();
       let path = task.relative_path.clone();
       async move {
-        let bytes = client
-          .fetch_attachment(&url)
+        let content = fetcher
+          .fetch(&url, output_dir, &path)
           .await
           .with_context(|| format!("Failed to fetch attachment: {name}"))?;
-        Ok::<_, anyhow::Error>(AssetData {
+        Ok(AssetData {
           relative_path: path,
-          content: bytes,
+          content,
         })
       }
+      .boxed()
     })
     .collect();
 
-  let assets = try_join_all(fetch_futures).await?;
+  let assets = fetch_assets(fetch_futures).await?;
 
   Ok((assets, downloaded_info))
 }
 
+/// Hex digits of the content hash appended to image names by
+/// [`ImageNaming::Hash`].
+const IMAGE_HASH_LEN: usize = 12;
+
+/// Content of a download, filled in by whichever page started it.
+type SharedDownload = Arc<OnceCell<AssetContent>>;
+
+/// Registry of in-flight asset downloads and per-path write locks of one
+/// export.
+///
+/// Sibling pages of a tree export share their images and attachments
+/// directories and are exported concurrently, so two pages referencing the
+/// same attachment would otherwise download it twice and race on writing the
+/// same file, leaving it truncated. Concurrent downloads of the same URL to
+/// the same path are shared, and writes to a path are serialized, skipping
+/// files that already hold the same content. Two pages writing different
+/// content to the same path is an error rather than a silent overwrite.
+///
+/// Create one per export and pass it to [`process_page`] through
+/// [`ProcessOptions::assets`] and to [`write_processed_page`].
+#[derive(Debug, Default)]
+pub struct AssetRegistry {
+  /// Downloads in flight, keyed by target path and URL.
+  downloads: StdMutex<HashMap<(PathBuf, String), SharedDownload>>,
+  /// Write locks, keyed by target path.
+  writes: StdMutex<HashMap<PathBuf, Arc<AsyncMutex<()>>>>,
+  /// Page and content hash of every asset written, keyed by path.
+  written: StdMutex<HashMap<PathBuf, (String, String)>>,
+}
+
+impl AssetRegistry {
+  /// Download `url` for `target`, or wait for a download of it already in
+  /// flight and share its content.
+  ///
+  /// A failed download is not shared: waiting callers retry it themselves.
+  async fn download<F, Fut>(&self, target: PathBuf, url: &str, fetch: F) -> Result<AssetContent>
+  where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<AssetContent>>,
+  {
+    let key = (target, url.to_string());
+    let cell = Arc::clone(
+      self
+        .downloads
+        .lock()
+        .expect("asset registry lock poisoned")
+        .entry(key.clone())
+        .or_default(),
+    );
+    let result = cell.get_or_try_init(fetch).await.cloned();
+
+    // Forget finished downloads so their bytes are not kept for the whole run
+    let mut downloads = self.downloads.lock().expect("asset registry lock poisoned");
+    if downloads.get(&key).is_some_and(|current| Arc::ptr_eq(current, &cell)) && cell.initialized() {
+      downloads.remove(&key);
+    }
+    result
+  }
+
+  /// Write an asset file of page `owner` while holding the lock for its path.
+  ///
+  /// Files that already hold `content`, typically written by another page
+  /// moments ago, are left alone, so writing the same asset twice succeeds
+  /// even without `overwrite`.
+  ///
+  /// # Errors
+  /// Returns an error when another page already wrote different content to
+  /// `path` during this export, or when the write fails.
+  async fn write(&self, owner: &str, path: &Path, content: &AssetContent, overwrite: bool) -> Result<()> {
+    self.claim(owner, path, content.sha256())?;
+
+    let lock = Arc::clone(
+      self
+        .writes
+        .lock()
+        .expect("asset registry lock poisoned")
+        .entry(path.to_path_buf())
+        .or_default(),
+    );
+    let _guard = lock.lock().await;
+
+    // Staged downloads are small to clone; in-memory assets are converted or
+    // external images, which are capped in size
+    let (path, content) = (path.to_path_buf(), content.clone());
+    tokio::task::spawn_blocking(move || {
+      if content.matches_file(&path) {
+        if let AssetContent::Staged(staged) = &content {
+          let _ = fs::remove_file(&staged.path);
+        }
+        return Ok(());
+      }
+      write_content(&path, &content, overwrite)
+    })
+    .await
+    .context("Asset write task failed")?
+  }
+
+  /// Record that `owner` writes content with the hash `digest` to `path`,
+  /// failing when another page wrote different content there.
+  fn claim(&self, owner: &str, path: &Path, digest: String) -> Result<()> {
+    let mut written = self.written.lock().expect("asset registry lock poisoned");
+    match written.get(path) {
+      Some((previous, previous_digest)) if previous != owner && *previous_digest != digest => bail!(
+        "Asset filename collision: {} is used by pages \"{previous}\" and \"{owner}\" for different files. \
+         Use --image-naming page-id or hash for images, or --attachments-layout per-page for attachments",
+        path.display()
+      ),
+      Some((previous, _)) if previous != owner => Ok(()),
+      _ => {
+        written.insert(path.to_path_buf(), (owner.to_string(), digest));
+        Ok(())
+      }
+    }
+  }
+}
+
+/// Write asset content to `path`, moving a staged download into place.
+fn write_content(path: &Path, content: &AssetContent, overwrite: bool) -> Result<()> {
+  let staged = match content {
+    AssetContent::Bytes(bytes) => return write_asset(path, bytes, overwrite),
+    AssetContent::Staged(staged) => staged,
+  };
+  if !overwrite && path.exists() {
+    bail!(
+      "File already exists: {}. Use --overwrite to replace it.",
+      path.display()
+    );
+  }
+  if let Some(parent) = path.parent() {
+    fs::create_dir_all(parent).with_context(|| format!("Failed to create directory {}", parent.display()))?;
+  }
+  fs::rename(&staged.path, path)
+    .with_context(|| format!("Failed to move {} to {}", staged.path.display(), path.display()))
+}
+
 /// Write an asset file to disk, creating parent directories as needed.
 fn write_asset(path: &Path, content: &[u8], overwrite: bool) -> Result<()> {
   if let Some(parent) = path.parent() {
@@ -473,35 +1592,259 @@ fn write_file(path: &Path, content: &[u8], overwrite: bool) -> Result<()> {
   Ok(())
 }
 
-/// Sanitize a Confluence page title so it can be used as a filesystem name.
+/// How page files are named.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum FilenameStyle {
+  /// The title with unsafe characters replaced, e.g. `Release Notes`
+  #[default]
+  Title,
+  /// The title lowercased with dashes between words, e.g. `release-notes`
+  Slug,
+  /// The page ID followed by the title, e.g. `12345-Release Notes`
+  IdTitle,
+  /// The page ID alone, e.g. `12345`
+  Id,
+}
+
+impl FilenameStyle {
+  /// Filename of a page whose stem collides with a sibling's, made unique by
+  /// appending the page ID.
+  fn disambiguate(self, stem: &str, page_id: &str) -> String {
+    match self {
+      Self::Slug => format!("{stem}-{page_id}"),
+      _ => format!("{stem} ({page_id})"),
+    }
+  }
+}
+
+/// Directories an export is placed in below the output directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputLayout {
+  /// Directly in the output directory
+  #[default]
+  Flat,
+  /// In a directory named after the space key, e.g. `DOCS/`
+  Space,
+  /// In directories named after the pages above the exported page, e.g. `Home/Guides/`
+  Ancestors,
+  /// In the space directory, then the ancestor directories, e.g. `DOCS/Home/Guides/`
+  #[value(alias = "space/ancestors")]
+  SpaceAncestors,
+}
+
+impl OutputLayout {
+  /// Directory, relative to the output directory, that `page` and its
+  /// descendants are written to.
+  ///
+  /// Ancestor directories are named like page directories, so an export of
+  /// a parent page into the same layout later merges with this one. Pages
+  /// without a space get no space directory.
+  pub fn directory(self, page: &Page, filenames: &FilenameOptions) -> PathBuf {
+    let mut dir = PathBuf::new();
+    if matches!(self, Self::Space | Self::SpaceAncestors)
+      && let Some(space) = &page.space
+    {
+      dir.push(sanitize_filename(&space.key));
+    }
+    if matches!(self, Self::Ancestors | Self::SpaceAncestors) {
+      for ancestor in &page.ancestors {
+        dir.push(filenames.title_stem(&ancestor.id, &ancestor.title));
+      }
+    }
+    dir
+  }
+}
+
+/// Options controlling how page files and their directories are named.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilenameOptions {
+  /// Naming style.
+  pub style: FilenameStyle,
+  /// Whether to transliterate or replace non-ASCII characters.
+  pub ascii: bool,
+  /// Longest path, in characters and without the extension, that a page file
+  /// may have relative to the output directory. Longer names are shortened
+  /// and get their page ID appended.
+  pub max_path: Option<usize>,
+}
+
+impl FilenameOptions {
+  /// Filename (without extension) of a page written directly to the output
+  /// directory.
+  pub fn stem(&self, page: &Page) -> String {
+    self.fit(self.full_stem(page), &page.id, self.max_path)
+  }
+
+  /// Filename of `page` in the configured style, before any shortening.
+  fn full_stem(&self, page: &Page) -> String {
+    self.title_stem(&page.id, &page.title)
+  }
+
+  /// Name of a page with the given ID and title in the configured style.
+  ///
+  /// Titles with no usable characters fall back to the page ID.
+  fn title_stem(&self, id: &str, title: &str) -> String {
+    let title = if self.ascii {
+      paths::to_ascii(title)
+    } else {
+      title.to_string()
+    };
+    let stem = match self.style {
+      FilenameStyle::Title => sanitize_filename(&title),
+      FilenameStyle::Slug => docusaurus::slugify(&title),
+      FilenameStyle::IdTitle => format!("{id}-{}", sanitize_filename(&title)),
+      FilenameStyle::Id => id.to_string(),
+    };
+    if stem.trim_matches(['_', ' ']).is_empty() {
+      id.to_string()
+    } else {
+      stem
+    }
+  }
+
+  /// `stem`, or a shortened stem with the page ID appended when it exceeds
+  /// `budget` characters.
+  fn fit(&self, stem: String, page_id: &str, budget: Option<usize>) -> String {
+    match budget {
+      Some(budget) if stem.chars().count() > budget => self.suffixed(&stem, page_id, Some(budget)),
+      _ => stem,
+    }
+  }
+
+  /// `stem` with the page ID appended, shortened to fit `budget` characters.
+  fn suffixed(&self, stem: &str, page_id: &str, budget: Option<usize>) -> String {
+    let suffix_len = self.style.disambiguate("", page_id).chars().count();
+    let kept = budget.map_or(usize::MAX, |budget| budget.saturating_sub(suffix_len));
+    let shortened = paths::truncate_chars(stem, kept);
+    if shortened.is_empty() {
+      page_id.to_string()
+    } else {
+      self.style.disambiguate(&shortened, page_id)
+    }
+  }
+}
+
+/// A page whose filename was suffixed because a sibling has the same title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenamedPage {
+  /// Confluence page ID.
+  pub page_id: String,
+  /// Page title.
+  pub title: String,
+  /// Filename (without extension) the page is written to.
+  pub filename: String,
+}
+
+/// Filenames (without extension) of the pages in an exported tree.
+///
+/// Sibling pages are written to the same directory, so titles that sanitize
+/// to the same name (ignoring case, for case-insensitive filesystems) would
+/// overwrite each other. Among such siblings the page with the lowest ID keeps
+/// the plain name and the others get their page ID appended, e.g.
+/// `Notes (12345)` (or `notes-12345` with [`FilenameStyle::Slug`]), which
+/// stays stable across runs and sort orders.
 ///
-/// Removes/normalizes characters that are potentially unsafe across
-/// platforms, collapsing repeated whitespace while keeping readability.
-pub fn sanitize_filename(title: &str) -> String {
-  title
-    .chars()
-    .map(|c| {
-      if c.is_alphanumeric() || c == '-' || c == '_' || c == ' ' {
-        c
-      } else {
-        '_'
+/// With a path length limit, pages whose path would exceed it are shortened
+/// the same way, e.g. `A very long t (12345)`, leaving less room for their
+/// descendants' names.
+#[derive(Debug, Default)]
+pub struct TreeFilenames {
+  options: FilenameOptions,
+  stems: HashMap<String, String>,
+  renamed: Vec<RenamedPage>,
+  shortened: Vec<RenamedPage>,
+}
+
+impl TreeFilenames {
+  /// Assign filenames to every page of `tree`.
+  pub fn plan(tree: &PageTree, options: FilenameOptions) -> Self {
+    let mut filenames = Self {
+      options,
+      ..Default::default()
+    };
+    let root_len = filenames.stem(&tree.page).chars().count();
+    filenames.plan_siblings(&tree.children, root_len + 1);
+    filenames
+  }
+
+  /// Assign filenames to every page of trees written side by side, such as
+  /// an exported tree and the pages linked from it.
+  pub fn plan_forest(trees: &[PageTree], options: FilenameOptions) -> Self {
+    let mut filenames = Self {
+      options,
+      ..Default::default()
+    };
+    filenames.plan_siblings(trees, 0);
+    filenames
+  }
+
+  /// Assign filenames to sibling pages and their descendants, whose
+  /// directory path is `dir_len` characters long including the trailing
+  /// separator.
+  fn plan_siblings(&mut self, siblings: &[PageTree], dir_len: usize) {
+    let options = self.options;
+    let budget = options.max_path.map(|max_path| max_path.saturating_sub(dir_len));
+    let mut stems: Vec<String> = Vec::with_capacity(siblings.len());
+    for child in siblings {
+      let full_stem = options.full_stem(&child.page);
+      let stem = options.fit(full_stem.clone(), &child.page.id, budget);
+      if stem != full_stem {
+        self.shortened.push(RenamedPage {
+          page_id: child.page.id.clone(),
+          title: child.page.title.clone(),
+          filename: stem.clone(),
+        });
       }
-    })
-    .collect::<String>()
-    .replace("  ", " ")
-    .trim()
-    .to_string()
-}
-
-/// Sanitize an asset filename for safe filesystem storage.
-fn sanitize_asset_filename(filename: &str) -> String {
-  filename
-    .chars()
-    .map(|c| match c {
-      '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
-      c => c,
-    })
-    .collect()
+      stems.push(stem);
+    }
+
+    let mut groups: HashMap<String, Vec<&Page>> = HashMap::new();
+    for (child, stem) in siblings.iter().zip(&stems) {
+      groups.entry(stem.to_lowercase()).or_default().push(&child.page);
+    }
+
+    for (child, stem) in siblings.iter().zip(&mut stems) {
+      let group = &groups[&stem.to_lowercase()];
+      let keeper = group
+        .iter()
+        .min_by_key(|page| (page.id.parse::<u64>().unwrap_or(u64::MAX), page.id.as_str()))
+        .map(|page| page.id.as_str());
+      if group.len() > 1 && keeper != Some(child.page.id.as_str()) {
+        *stem = options.suffixed(stem, &child.page.id, budget);
+        self.renamed.push(RenamedPage {
+          page_id: child.page.id.clone(),
+          title: child.page.title.clone(),
+          filename: stem.clone(),
+        });
+      }
+      self.stems.insert(child.page.id.clone(), stem.clone());
+    }
+
+    for (child, stem) in siblings.iter().zip(&stems) {
+      self.plan_siblings(&child.children, dir_len + stem.chars().count() + 1);
+    }
+  }
+
+  /// Filename (without extension) of `page`.
+  pub fn stem(&self, page: &Page) -> String {
+    self
+      .stems
+      .get(&page.id)
+      .cloned()
+      .unwrap_or_else(|| self.options.stem(page))
+  }
+
+  /// Pages that were given a suffixed filename because a sibling has the
+  /// same title, in tree order.
+  pub fn renamed(&self) -> &[RenamedPage] {
+    &self.renamed
+  }
+
+  /// Pages whose filename was shortened to stay within the path length
+  /// limit, in tree order.
+  pub fn shortened(&self) -> &[RenamedPage] {
+    &self.shortened
+  }
 }
 
 fn split_name_and_extension(name: &str) -> (String, String) {
@@ -512,11 +1855,11 @@ fn split_name_and_extension(name: &str) -> (String, String) {
   }
 }
 
-fn next_candidate(base: &str, ext: &str, counter: usize) -> String {
+fn next_candidate(base: &str, ext: &str, suffix: impl std::fmt::Display) -> String {
   if ext.is_empty() {
-    format!("{base}-{counter}")
+    format!("{base}-{suffix}")
   } else {
-    format!("{base}-{counter}.{ext}")
+    format!("{base}-{suffix}.{ext}")
   }
 }
 
@@ -544,6 +1887,198 @@ mod tests {
     assert_eq!(sanitize_asset_filename("file:with:colons.png"), "file_with_colons.png");
   }
 
+  #[tokio::test]
+  async fn test_asset_registry_shares_concurrent_downloads() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let registry = AssetRegistry::default();
+    let fetches = AtomicUsize::new(0);
+    let fetch = || async {
+      fetches.fetch_add(1, Ordering::SeqCst);
+      tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+      Ok(AssetContent::Bytes(b"png".to_vec()))
+    };
+    let target = PathBuf::from("out/images/logo.png");
+
+    let (first, second) = tokio::join!(
+      registry.download(target.clone(), "/download/logo.png", fetch),
+      registry.download(target.clone(), "/download/logo.png", fetch),
+    );
+
+    assert_eq!(first.unwrap(), AssetContent::Bytes(b"png".to_vec()));
+    assert_eq!(second.unwrap(), AssetContent::Bytes(b"png".to_vec()));
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    assert!(registry.downloads.lock().unwrap().is_empty());
+
+    // A failed download is retried by the next caller
+    let failed = registry
+      .download(target.clone(), "/download/other.png", || async { bail!("boom") })
+      .await;
+    assert!(failed.is_err());
+    let retried = registry
+      .download(target, "/download/other.png", || async {
+        Ok(AssetContent::Bytes(b"ok".to_vec()))
+      })
+      .await;
+    assert_eq!(retried.unwrap(), AssetContent::Bytes(b"ok".to_vec()));
+  }
+
+  #[tokio::test]
+  async fn test_asset_registry_writes_are_idempotent() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("images").join("logo.png");
+    let registry = AssetRegistry::default();
+
+    registry
+      .write("Page", &path, &AssetContent::Bytes(b"png".to_vec()), false)
+      .await
+      .unwrap();
+    registry
+      .write("Page", &path, &AssetContent::Bytes(b"png".to_vec()), false)
+      .await
+      .unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"png");
+
+    let err = registry
+      .write("Page", &path, &AssetContent::Bytes(b"other".to_vec()), false)
+      .await
+      .unwrap_err();
+    assert!(err.to_string().contains("File already exists"));
+  }
+
+  #[tokio::test]
+  async fn test_asset_registry_moves_staged_downloads() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("attachments").join("video.mp4");
+    let stage = |content: &[u8]| {
+      let staged = staging_path(&path);
+      fs::create_dir_all(staged.parent().unwrap()).unwrap();
+      fs::write(&staged, content).unwrap();
+      AssetContent::Staged(StagedFile {
+        path: staged,
+        size: content.len() as u64,
+        sha256: sha256_hex(content),
+      })
+    };
+    let registry = AssetRegistry::default();
+
+    let video = stage(b"video");
+    registry.write("Page", &path, &video, false).await.unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"video");
+    assert!(!staging_path(&path).exists());
+
+    // A second download of the same file is dropped in favour of the first
+    registry.write("Other", &path, &stage(b"video"), false).await.unwrap();
+    assert!(!staging_path(&path).exists());
+
+    let err = registry
+      .write("Page", &path, &stage(b"other"), false)
+      .await
+      .unwrap_err();
+    assert!(err.to_string().contains("File already exists"));
+    assert_eq!(fs::read(&path).unwrap(), b"video");
+  }
+
+  #[tokio::test]
+  async fn test_asset_registry_detects_collisions() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("images").join("diagram.png");
+    let registry = AssetRegistry::default();
+
+    registry
+      .write("Design", &path, &AssetContent::Bytes(b"design".to_vec()), true)
+      .await
+      .unwrap();
+    registry
+      .write("Overview", &path, &AssetContent::Bytes(b"design".to_vec()), true)
+      .await
+      .unwrap();
+    let err = registry
+      .write("Roadmap", &path, &AssetContent::Bytes(b"roadmap".to_vec()), true)
+      .await
+      .unwrap_err();
+
+    assert!(err.to_string().starts_with("Asset filename collision: "));
+    assert!(err.to_string().contains(r#"pages "Design" and "Roadmap""#));
+    assert_eq!(fs::read(&path).unwrap(), b"design");
+
+    // A later export in the same process starts from a fresh registry
+    AssetRegistry::default()
+      .write("Roadmap", &path, &AssetContent::Bytes(b"roadmap".to_vec()), true)
+      .await
+      .unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"roadmap");
+  }
+
+  #[test]
+  fn test_normalize_path_resolves_parent_components() {
+    assert_eq!(
+      normalize_path(Path::new("export/Home/Guide/../../assets/./logo.png")),
+      PathBuf::from("export/assets/logo.png")
+    );
+    assert_eq!(
+      normalize_path(Path::new("../assets/logo.png")),
+      PathBuf::from("../assets/logo.png")
+    );
+  }
+
+  #[test]
+  fn test_image_placement_naming() {
+    let placement = |naming| ImagePlacement {
+      images_dir: "images",
+      naming,
+      quality: ImageQuality::Original,
+      page_id: "42",
+    };
+
+    let by_name = placement(ImageNaming::Name);
+    assert_eq!(by_name.relative_path("a:b.png"), PathBuf::from("images/a_b.png"));
+    let by_page = placement(ImageNaming::PageId);
+    assert_eq!(by_page.relative_path("logo.png"), PathBuf::from("images/42-logo.png"));
+    assert_eq!(
+      by_page.content_path(Path::new("images/42-logo.png"), &sha256_hex(b"png")),
+      PathBuf::from("images/42-logo.png")
+    );
+    let by_hash = placement(ImageNaming::Hash);
+    let path = by_hash.relative_path("logo.png");
+    assert_eq!(path, PathBuf::from("images/logo.png"));
+    assert_eq!(
+      by_hash.content_path(&path, &sha256_hex(b"png")),
+      PathBuf::from(format!("images/logo-{}.png", &sha256_hex(b"png")[..12]))
+    );
+  }
+
+  #[test]
+  fn test_unique_image_path() {
+    let mut used = HashMap::new();
+    let path = |name: &str| PathBuf::from("images").join(name);
+    assert_eq!(
+      unique_image_path(&mut used, path("a_b.png"), "a:b.png"),
+      path("a_b.png")
+    );
+    assert_eq!(
+      unique_image_path(&mut used, path("a_b.png"), "a:b.png"),
+      path("a_b.png")
+    );
+    assert_eq!(
+      unique_image_path(&mut used, path("a_b.png"), "a?b.png"),
+      path("a_b-2.png")
+    );
+    assert_eq!(
+      unique_image_path(&mut used, path("a_b.png"), "a*b.png"),
+      path("a_b-3.png")
+    );
+    // A converted `chart.emf` and an uploaded `chart.png`
+    assert_eq!(
+      unique_image_path(&mut used, path("chart.png"), "chart.emf"),
+      path("chart.png")
+    );
+    assert_eq!(
+      unique_image_path(&mut used, path("chart.png"), "chart.png"),
+      path("chart-2.png")
+    );
+  }
+
   #[test]
   fn test_write_file_creates_new_file() {
     let temp_dir = tempdir().unwrap();
@@ -578,8 +2113,16 @@ mod tests {
     assert_eq!(fs::read_to_string(&file_path).unwrap(), "new content");
   }
 
-  #[test]
-  fn test_write_processed_page_creates_all_files() {
+  fn write_options(format: OutputFormat) -> WriteOptions {
+    WriteOptions {
+      format,
+      overwrite: true,
+      ..Default::default()
+    }
+  }
+
+  #[tokio::test]
+  async fn test_write_processed_page_creates_all_files() {
     let temp_dir = tempdir().unwrap();
     let output_dir = temp_dir.path();
 
@@ -589,15 +2132,28 @@ mod tests {
       raw_storage: Some("<p>Test</p>".to_string()),
       images: vec![AssetData {
         relative_path: PathBuf::from("images/test.png"),
-        content: b"PNG".to_vec(),
+        content: AssetContent::Bytes(b"PNG".to_vec()),
       }],
       attachments: vec![AssetData {
         relative_path: PathBuf::from("attachments/doc.pdf"),
-        content: b"PDF".to_vec(),
+        content: AssetContent::Bytes(b"PDF".to_vec()),
       }],
+      tables: vec![AssetData {
+        relative_path: PathBuf::from("tables/Test Page-1.csv"),
+        content: AssetContent::Bytes(b"a,b\r\n".to_vec()),
+      }],
+      front_matter: FrontMatter::default(),
+      link_map: LinkMap::default(),
+      record: None,
     };
 
-    let result = write_processed_page(&page, output_dir, OutputFormat::Markdown, true);
+    let result = write_processed_page(
+      &page,
+      output_dir,
+      &write_options(OutputFormat::Markdown),
+      &AssetRegistry::default(),
+    )
+    .await;
     assert!(result.is_ok());
 
     let written_path = result.unwrap();
@@ -610,6 +2166,10 @@ mod tests {
     assert!(raw_path.exists());
     assert_eq!(fs::read_to_string(&raw_path).unwrap(), "<p>Test</p>");
 
+    // Check extracted tables
+    let table_path = output_dir.join("tables/Test Page-1.csv");
+    assert_eq!(fs::read_to_string(&table_path).unwrap(), "a,b\r\n");
+
     // Check images
     let image_path = output_dir.join("images/test.png");
     assert!(image_path.exists());
@@ -621,8 +2181,8 @@ mod tests {
     assert_eq!(fs::read(&attachment_path).unwrap(), b"PDF");
   }
 
-  #[test]
-  fn test_write_processed_page_asciidoc_extension() {
+  #[tokio::test]
+  async fn test_write_processed_page_asciidoc_extension() {
     let temp_dir = tempdir().unwrap();
     let output_dir = temp_dir.path();
 
@@ -632,15 +2192,117 @@ mod tests {
       raw_storage: None,
       images: vec![],
       attachments: vec![],
+      tables: Vec::new(),
+      front_matter: FrontMatter::default(),
+      link_map: LinkMap::default(),
+      record: None,
     };
 
-    let result = write_processed_page(&page, output_dir, OutputFormat::AsciiDoc, true);
+    let result = write_processed_page(
+      &page,
+      output_dir,
+      &write_options(OutputFormat::AsciiDoc),
+      &AssetRegistry::default(),
+    )
+    .await;
     assert!(result.is_ok());
 
     let written_path = result.unwrap();
     assert_eq!(written_path, output_dir.join("Test.adoc"));
   }
 
+  #[tokio::test]
+  async fn test_write_processed_page_extension_override() {
+    let temp_dir = tempdir().unwrap();
+    let output_dir = temp_dir.path();
+
+    let page = ProcessedPage {
+      filename: "Test".to_string(),
+      content: "# Test".to_string(),
+      raw_storage: None,
+      images: vec![],
+      attachments: vec![],
+      tables: Vec::new(),
+      front_matter: FrontMatter::default(),
+      link_map: LinkMap::default(),
+      record: None,
+    };
+    let options = WriteOptions {
+      extension: Some("mdx".to_string()),
+      ..write_options(OutputFormat::Markdown)
+    };
+
+    let written_path = write_processed_page(&page, output_dir, &options, &AssetRegistry::default())
+      .await
+      .unwrap();
+    assert_eq!(written_path, output_dir.join("Test.mdx"));
+    assert_eq!(options.mime_type(), "text/mdx");
+    assert_eq!(options.document_extension(), "mdx");
+    assert_eq!(write_options(OutputFormat::Json).document_extension(), "md");
+  }
+
+  #[tokio::test]
+  async fn test_write_processed_page_conflict_policies() {
+    let temp_dir = tempdir().unwrap();
+    let output_dir = temp_dir.path();
+    let page_path = output_dir.join("Test.md");
+
+    let export_with = async |content: &str, policy, overwrite| {
+      let page = ProcessedPage {
+        filename: "Test".to_string(),
+        content: content.to_string(),
+        raw_storage: None,
+        images: vec![],
+        attachments: vec![],
+        tables: Vec::new(),
+        front_matter: FrontMatter::default(),
+        link_map: LinkMap::default(),
+        record: None,
+      };
+      let options = WriteOptions {
+        conflict: Some(policy),
+        overwrite,
+        ..Default::default()
+      };
+      write_processed_page(&page, output_dir, &options, &AssetRegistry::default())
+        .await
+        .unwrap()
+    };
+    let export = async |content: &str, policy| export_with(content, policy, false).await;
+
+    // Files from exports without hashes may hold edits and need --overwrite
+    fs::write(&page_path, "Hand-edited\n").unwrap();
+    assert_eq!(export("First\n", ConflictPolicy::Skip).await, page_path);
+    assert_eq!(fs::read_to_string(&page_path).unwrap(), "Hand-edited\n");
+    assert_eq!(
+      export("First\n", ConflictPolicy::New).await,
+      output_dir.join("Test.md.new")
+    );
+    assert_eq!(fs::read_to_string(&page_path).unwrap(), "Hand-edited\n");
+    fs::remove_file(output_dir.join("Test.md.new")).unwrap();
+
+    // --overwrite replaces them; from then on unedited files are refreshed
+    // without it
+    export_with("First\n", ConflictPolicy::Skip, true).await;
+    assert_eq!(export("Second\n", ConflictPolicy::Skip).await, page_path);
+    let written = fs::read_to_string(&page_path).unwrap();
+    assert!(written.starts_with("---\nconfluence_dl_hash: sha256:"));
+    assert!(written.ends_with("\n---\n\nSecond\n"));
+
+    // Edited files are protected according to the policy
+    fs::write(&page_path, written.replace("Second", "Edited")).unwrap();
+    export("Third\n", ConflictPolicy::Skip).await;
+    assert!(fs::read_to_string(&page_path).unwrap().ends_with("Edited\n"));
+
+    let new_path = export("Third\n", ConflictPolicy::New).await;
+    assert_eq!(new_path, output_dir.join("Test.md.new"));
+    assert!(fs::read_to_string(&new_path).unwrap().ends_with("Third\n"));
+    assert!(fs::read_to_string(&page_path).unwrap().ends_with("Edited\n"));
+
+    export("Third\n", ConflictPolicy::Overwrite).await;
+    assert!(fs::read_to_string(&page_path).unwrap().ends_with("Third\n"));
+  }
+
   #[test]
   fn test_split_name_and_extension() {
     let (base, ext) = split_name_and_extension("report.pdf");
@@ -658,4 +2320,33 @@ mod tests {
     assert_eq!(next_candidate("file", "txt", 2), "file-2.txt");
     assert_eq!(next_candidate("file", "", 1), "file-1");
   }
+
+  #[test]
+  fn test_output_layout_directory() {
+    let page: Page = serde_json::from_value(serde_json::json!({
+      "id": "3",
+      "title": "Setup",
+      "type": "page",
+      "status": "current",
+      "space": {"key": "DOCS", "name": "Docs", "type": "global"},
+      "ancestors": [{"id": "1", "title": "Home"}, {"id": "2", "title": "Guides: Admin"}]
+    }))
+    .unwrap();
+    let options = FilenameOptions::default();
+
+    assert_eq!(OutputLayout::Flat.directory(&page, &options), PathBuf::new());
+    assert_eq!(OutputLayout::Space.directory(&page, &options), PathBuf::from("DOCS"));
+    assert_eq!(
+      OutputLayout::Ancestors.directory(&page, &options),
+      Path::new("Home").join("Guides_ Admin")
+    );
+    let slugs = FilenameOptions {
+      style: FilenameStyle::Slug,
+      ..Default::default()
+    };
+    assert_eq!(
+      OutputLayout::SpaceAncestors.directory(&page, &slugs),
+      Path::new("DOCS").join("home").join("guides-admin")
+    );
+  }
 }
@@ -0,0 +1,313 @@
+//! Progress reporting for page tree exports.
+//!
+//! [`ExportProgress`] drives a progress bar showing how many pages of the tree
+//! have been written and how much data was downloaded, and keeps the counters
+//! for the summary printed at the end of the run. The bar is drawn on stderr
+//! and hides itself when stderr is not a terminal; other output should go
+//! through [`ExportProgress::suspend`] so it does not tear the bar.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::color::ColorScheme;
+use crate::confluence::{ContentKind, DownloadProgress, Page};
+use crate::processed_page::ProcessedPage;
+use crate::size_limits::format_size;
+
+/// Downloads smaller than this are not shown on the bar while they run.
+const LARGE_DOWNLOAD: u64 = 10 * 1024 * 1024;
+
+/// Progress bar and counters for a running export.
+#[derive(Debug)]
+pub struct ExportProgress {
+  bar: ProgressBar,
+  total_pages: usize,
+  pages: AtomicUsize,
+  bytes: Arc<AtomicU64>,
+  images: AtomicUsize,
+  attachments: AtomicUsize,
+  failures: AtomicUsize,
+  accessibility_audit: bool,
+  accessibility_issues: AtomicUsize,
+  stubs: Mutex<Vec<StubbedContent>>,
+  started: Instant,
+}
+
+/// Content written as a stub because it cannot be converted.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StubbedContent {
+  /// Kind of the content, such as a whiteboard.
+  pub kind: ContentKind,
+  /// Title of the content.
+  pub title: String,
+}
+
+/// Totals of a finished (or aborted) export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportSummary {
+  /// Pages written to disk.
+  pub pages: usize,
+  /// Pages in the exported tree.
+  pub total_pages: usize,
+  /// Bytes of page content, images, and attachments written.
+  pub bytes: u64,
+  /// Images written.
+  pub images: usize,
+  /// Attachments written.
+  pub attachments: usize,
+  /// Pages that failed to export.
+  pub failures: usize,
+  /// Accessibility findings, when the audit ran.
+  pub accessibility_issues: Option<usize>,
+  /// Whiteboards, databases, and embeds written as stubs.
+  pub stubs: Vec<StubbedContent>,
+  /// Time since the export started.
+  pub elapsed: Duration,
+}
+
+impl ExportProgress {
+  /// Start tracking an export of `total_pages` pages.
+  ///
+  /// # Arguments
+  /// * `total_pages` - Number of pages in the tree being exported.
+  /// * `show_bar` - Whether to draw the progress bar (it is still hidden when stderr is not a terminal).
+  /// * `colors` - Color palette; the bar is drawn without colors when disabled.
+  pub fn new(total_pages: usize, show_bar: bool, colors: &ColorScheme) -> Self {
+    let bar = if show_bar {
+      ProgressBar::new(total_pages as u64)
+    } else {
+      ProgressBar::hidden()
+    };
+    let template = if colors.is_enabled() {
+      "{spinner:.cyan} [{bar:30.cyan/blue}] {pos}/{len} pages · {msg} · {elapsed}"
+    } else {
+      "{spinner} [{bar:30}] {pos}/{len} pages · {msg} · {elapsed}"
+    };
+    if let Ok(style) = ProgressStyle::with_template(template) {
+      bar.set_style(style.progress_chars("=> "));
+    }
+    bar.set_message(format_size(0));
+    bar.enable_steady_tick(Duration::from_millis(120));
+
+    Self {
+      bar,
+      total_pages,
+      pages: AtomicUsize::new(0),
+      bytes: Arc::new(AtomicU64::new(0)),
+      images: AtomicUsize::new(0),
+      attachments: AtomicUsize::new(0),
+      failures: AtomicUsize::new(0),
+      accessibility_audit: false,
+      accessibility_issues: AtomicUsize::new(0),
+      stubs: Mutex::new(Vec::new()),
+      started: Instant::now(),
+    }
+  }
+
+  /// Include the accessibility findings in the summary.
+  pub fn with_accessibility_audit(mut self, enabled: bool) -> Self {
+    self.accessibility_audit = enabled;
+    self
+  }
+
+  /// Run `f` with the bar cleared so its output does not interleave with it.
+  pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+    self.bar.suspend(f)
+  }
+
+  /// Count a page that was written to disk, with its images and attachments.
+  pub fn record_page(&self, page: &ProcessedPage) {
+    let bytes = page.total_bytes();
+
+    self.pages.fetch_add(1, Ordering::Relaxed);
+    self.images.fetch_add(page.images.len(), Ordering::Relaxed);
+    self.attachments.fetch_add(page.attachments.len(), Ordering::Relaxed);
+    let total_bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+
+    self.bar.set_message(format_size(total_bytes));
+    self.bar.inc(1);
+  }
+
+  /// A callback showing large attachment downloads on the bar while they run.
+  pub fn download_progress(&self) -> DownloadProgress {
+    let bar = self.bar.clone();
+    let bytes = Arc::clone(&self.bytes);
+    Arc::new(move |path: &Path, written: u64, total: Option<u64>| {
+      if total.is_some_and(|total| total < LARGE_DOWNLOAD) {
+        return;
+      }
+      let name = path.file_name().unwrap_or_default().to_string_lossy();
+      let transfer = match total {
+        Some(total) => format!("{} of {}", format_size(written), format_size(total)),
+        None => format_size(written),
+      };
+      bar.set_message(format!(
+        "{} · {name} {transfer}",
+        format_size(bytes.load(Ordering::Relaxed))
+      ));
+    })
+  }
+
+  /// Count a page that failed to export.
+  pub fn record_failure(&self) {
+    self.failures.fetch_add(1, Ordering::Relaxed);
+  }
+
+  /// Note a page that was written as a stub.
+  pub fn record_stub(&self, page: &Page) {
+    self.stubs.lock().expect("progress lock poisoned").push(StubbedContent {
+      kind: page.content_kind(),
+      title: page.title.clone(),
+    });
+  }
+
+  /// Count accessibility findings of a page.
+  pub fn record_accessibility_issues(&self, count: usize) {
+    self.accessibility_issues.fetch_add(count, Ordering::Relaxed);
+  }
+
+  /// Remove the bar and return the totals of the run.
+  pub fn finish(&self) -> ExportSummary {
+    self.bar.finish_and_clear();
+    let mut stubs = self.stubs.lock().expect("progress lock poisoned").clone();
+    stubs.sort();
+    ExportSummary {
+      pages: self.pages.load(Ordering::Relaxed),
+      total_pages: self.total_pages,
+      bytes: self.bytes.load(Ordering::Relaxed),
+      images: self.images.load(Ordering::Relaxed),
+      attachments: self.attachments.load(Ordering::Relaxed),
+      failures: self.failures.load(Ordering::Relaxed),
+      accessibility_issues: self
+        .accessibility_audit
+        .then(|| self.accessibility_issues.load(Ordering::Relaxed)),
+      stubs,
+      elapsed: self.started.elapsed(),
+    }
+  }
+}
+
+/// Format the end-of-run summary table.
+pub fn format_summary_lines(summary: &ExportSummary, colors: &ColorScheme) -> Vec<String> {
+  let failures = if summary.failures == 0 {
+    colors.number(summary.failures)
+  } else {
+    colors.error(summary.failures)
+  };
+  let mut rows = vec![
+    (
+      "Pages",
+      format!(
+        "{}/{}",
+        colors.number(summary.pages),
+        colors.number(summary.total_pages)
+      ),
+    ),
+    ("Downloaded", colors.number(format_size(summary.bytes))),
+    ("Images", colors.number(summary.images)),
+    ("Attachments", colors.number(summary.attachments)),
+    ("Failures", failures),
+  ];
+  if let Some(issues) = summary.accessibility_issues {
+    let issues = if issues == 0 {
+      colors.number(issues)
+    } else {
+      colors.warning(issues)
+    };
+    rows.push(("Accessibility", issues));
+  }
+  if !summary.stubs.is_empty() {
+    rows.push(("Stubs", colors.warning(summary.stubs.len())));
+  }
+  rows.push((
+    "Elapsed",
+    colors.number(format!("{:.1}s", summary.elapsed.as_secs_f64())),
+  ));
+
+  let mut lines = vec![format!("\n{} {}", colors.info("→"), colors.info("Summary"))];
+  for (label, value) in rows {
+    // Pad before coloring so escape codes do not break the alignment
+    lines.push(format!("  {} {value}", colors.emphasis(format!("{label:<14}"))));
+  }
+  if !summary.stubs.is_empty() {
+    lines.push(format!(
+      "\n{} {}",
+      colors.warning("⚠"),
+      colors.warning("Not exported, written as stubs linking to Confluence")
+    ));
+    for stub in &summary.stubs {
+      lines.push(format!(
+        "  {} {}",
+        colors.dimmed(format!("{:<16}", stub.kind.label())),
+        stub.title
+      ));
+    }
+  }
+  lines
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::cli::ColorOption;
+  use crate::front_matter::FrontMatter;
+  use crate::processed_page::{AssetContent, AssetData, LinkMap};
+
+  #[test]
+  fn test_progress_counts_pages_and_assets() {
+    let colors = ColorScheme::new(ColorOption::Never);
+    let progress = ExportProgress::new(3, false, &colors).with_accessibility_audit(true);
+    progress.record_page(&ProcessedPage {
+      filename: "Page".to_string(),
+      content: "x".repeat(1000),
+      raw_storage: None,
+      images: vec![AssetData {
+        relative_path: "images/a.png".into(),
+        content: AssetContent::Bytes(vec![0; 1048]),
+      }],
+      attachments: Vec::new(),
+      tables: Vec::new(),
+      front_matter: FrontMatter::default(),
+      link_map: LinkMap::default(),
+      record: None,
+    });
+    progress.record_failure();
+    progress.record_accessibility_issues(4);
+    let whiteboard: Page = serde_json::from_value(serde_json::json!({
+      "id": "9",
+      "title": "Architecture sketch",
+      "type": "whiteboard",
+      "status": "current"
+    }))
+    .unwrap();
+    progress.record_stub(&whiteboard);
+
+    let summary = progress.finish();
+    let lines = format_summary_lines(
+      &ExportSummary {
+        elapsed: Duration::from_millis(2500),
+        ..summary
+      },
+      &colors,
+    );
+
+    insta::assert_snapshot!(lines.join("\n"), @r"
+    → Summary
+      Pages          1/3
+      Downloaded     2 KiB
+      Images         1
+      Attachments    0
+      Failures       1
+      Accessibility  4
+      Stubs          1
+      Elapsed        2.5s
+
+    ⚠ Not exported, written as stubs linking to Confluence
+      whiteboard       Architecture sketch
+    ");
+  }
+}
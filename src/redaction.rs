@@ -0,0 +1,119 @@
+//! Redaction of macros that embed secrets.
+//!
+//! Some marketplace macros, such as secrets-manager or credential vault
+//! embeds, store credentials or internal URLs in their parameters. Macros
+//! named with `--redact-macro` are rendered as [`REDACTED`] in converted
+//! output, and [`redact_storage`] blanks their parameters and plain-text
+//! bodies in the storage format before it is converted or saved with
+//! `--save-raw`.
+
+use std::borrow::Cow;
+
+use anyhow::{Result, anyhow};
+use roxmltree::{Document, Node};
+
+use crate::markdown::html_entities::preprocess_html_entities;
+use crate::markdown::utils::{get_attribute, matches_tag, wrap_with_namespaces};
+
+/// Text standing in for a redacted macro or parameter value.
+pub const REDACTED: &str = "[macro redacted]";
+
+/// Check whether `macro_name` is on the deny-list, ignoring case.
+pub fn is_redacted(redacted_macros: &[String], macro_name: &str) -> bool {
+  redacted_macros.iter().any(|name| name.eq_ignore_ascii_case(macro_name))
+}
+
+/// Replace the parameters and plain-text bodies of denied macros with
+/// [`REDACTED`].
+///
+/// Rich text bodies are kept, since they hold page content rather than macro
+/// configuration.
+///
+/// # Arguments
+/// * `storage_content` - Storage format of a page.
+/// * `redacted_macros` - Names of the macros to redact.
+///
+/// # Returns
+/// The storage content unchanged when it contains no denied macro, otherwise
+/// the redacted content with HTML entities replaced by their characters.
+///
+/// # Errors
+/// Returns an error when the storage content cannot be parsed, so a page is
+/// never exported with secrets left in place.
+pub fn redact_storage<'a>(storage_content: &'a str, redacted_macros: &[String]) -> Result<Cow<'a, str>> {
+  if redacted_macros.is_empty() || !storage_content.contains("ac:structured-macro") {
+    return Ok(Cow::Borrowed(storage_content));
+  }
+
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document =
+    Document::parse(&wrapped).map_err(|err| anyhow!("Failed to parse storage content for redaction: {err}"))?;
+  let offset = wrapped.len() - preprocessed.len() - "</cdl-root>".len();
+
+  let mut ranges: Vec<(usize, usize)> = document
+    .descendants()
+    .filter(|node| matches_tag(*node, "ac:structured-macro"))
+    .filter(|node| get_attribute(*node, "ac:name").is_some_and(|name| is_redacted(redacted_macros, &name)))
+    .flat_map(|node| node.descendants())
+    .filter(|node| matches_tag(*node, "ac:parameter") || matches_tag(*node, "ac:plain-text-body"))
+    .filter_map(content_range)
+    .map(|(start, end)| (start - offset, end - offset))
+    .collect();
+  if ranges.is_empty() {
+    return Ok(Cow::Borrowed(storage_content));
+  }
+
+  // Macros nested in a denied macro are visited once per denied ancestor, so
+  // ranges repeat but never partially overlap
+  ranges.sort_unstable();
+  ranges.dedup();
+  let mut result = String::with_capacity(preprocessed.len());
+  let mut position = 0;
+  for (start, end) in ranges {
+    result.push_str(&preprocessed[position..start]);
+    result.push_str(REDACTED);
+    position = end;
+  }
+  result.push_str(&preprocessed[position..]);
+  Ok(Cow::Owned(result))
+}
+
+/// Byte range of an element's content, between its start and end tags.
+fn content_range(element: Node) -> Option<(usize, usize)> {
+  let first = element.first_child()?;
+  let last = element.last_child()?;
+  Some((first.range().start, last.range().end))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_redact_storage() {
+    let storage = concat!(
+      "<p>Intro</p>",
+      r#"<ac:structured-macro ac:name="Vault-Secret">"#,
+      r#"<ac:parameter ac:name="token">s3cr3t</ac:parameter>"#,
+      r#"<ac:parameter ac:name="url"><ri:url ri:value="https://vault.internal" /></ac:parameter>"#,
+      "<ac:plain-text-body><![CDATA[password=hunter2]]></ac:plain-text-body>",
+      "</ac:structured-macro>",
+      r#"<ac:structured-macro ac:name="code"><ac:parameter ac:name="language">sh</ac:parameter></ac:structured-macro>"#,
+    );
+    let redacted_macros = vec!["vault-secret".to_string()];
+
+    insta::assert_snapshot!(redact_storage(storage, &redacted_macros).unwrap(), @r#"<p>Intro</p><ac:structured-macro ac:name="Vault-Secret"><ac:parameter ac:name="token">[macro redacted]</ac:parameter><ac:parameter ac:name="url">[macro redacted]</ac:parameter><ac:plain-text-body>[macro redacted]</ac:plain-text-body></ac:structured-macro><ac:structured-macro ac:name="code"><ac:parameter ac:name="language">sh</ac:parameter></ac:structured-macro>"#);
+  }
+
+  #[test]
+  fn test_redact_storage_without_denied_macros_is_unchanged() {
+    let storage = r#"<p>A&nbsp;B</p><ac:structured-macro ac:name="code"></ac:structured-macro>"#;
+
+    assert!(matches!(
+      redact_storage(storage, &["vault-secret".to_string()]).unwrap(),
+      Cow::Borrowed(_)
+    ));
+    assert!(matches!(redact_storage(storage, &[]).unwrap(), Cow::Borrowed(_)));
+  }
+}
@@ -0,0 +1,186 @@
+//! Content-addressed asset directory shared by the pages of a tree export.
+//!
+//! Pages of a tree are written to nested directories, each with its own
+//! images and attachments folders, so a logo or diagram used across a space
+//! is stored once per page. With `--dedupe-assets`, [`SharedAssets`] moves
+//! every image and attachment into one [`SHARED_ASSETS_DIR`] at the export
+//! root, named after a hash of its content, and rewrites the page's links to
+//! point there. [`SharedAssets::write_index`] records which original files and
+//! pages each stored file came from.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::processed_page::{ProcessedPage, link_path};
+
+/// Directory, relative to the export root, holding the shared assets.
+pub const SHARED_ASSETS_DIR: &str = "assets";
+
+/// File name of the hash index written to [`SHARED_ASSETS_DIR`].
+pub const INDEX_FILE: &str = "index.json";
+
+/// Hex digits of the content hash used in shared file names.
+const NAME_HASH_LEN: usize = 16;
+
+/// A file in the shared assets directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+struct IndexEntry {
+  /// File name in the shared assets directory.
+  file: String,
+  /// Names the file had in the pages it was found in.
+  names: BTreeSet<String>,
+  /// Pages referencing the file.
+  pages: BTreeSet<String>,
+}
+
+/// Shared asset directory of a tree export and its hash index.
+#[derive(Debug)]
+pub struct SharedAssets {
+  root: PathBuf,
+  index: Mutex<BTreeMap<String, IndexEntry>>,
+}
+
+impl SharedAssets {
+  /// Create an empty index for the export rooted at `root`.
+  pub fn new(root: &Path) -> Self {
+    Self {
+      root: root.to_path_buf(),
+      index: Mutex::new(BTreeMap::new()),
+    }
+  }
+
+  /// Move a page's images and attachments into the shared directory.
+  ///
+  /// Asset paths, the links to them in the page content, and the page's link
+  /// map are rewritten relative to `output_dir`. Identical files from any
+  /// page end up at the same path, so they are written once.
+  ///
+  /// # Arguments
+  /// * `processed` - The converted page, not yet written.
+  /// * `output_dir` - Directory the page is written to, below the export root.
+  ///
+  /// # Errors
+  /// Returns an error when `output_dir` is not inside the export root.
+  pub fn share(&self, processed: &mut ProcessedPage, output_dir: &Path) -> Result<()> {
+    let depth = output_dir
+      .strip_prefix(&self.root)
+      .with_context(|| {
+        format!(
+          "Page directory {} is outside the export root {}",
+          output_dir.display(),
+          self.root.display()
+        )
+      })?
+      .components()
+      .count();
+    let shared_dir = std::iter::repeat_n(Path::new(".."), depth)
+      .collect::<PathBuf>()
+      .join(SHARED_ASSETS_DIR);
+
+    let mut index = self.index.lock().expect("shared asset index lock poisoned");
+    for asset in processed.images.iter_mut().chain(processed.attachments.iter_mut()) {
+      let digest = asset.content.sha256();
+      let entry = index.entry(digest.clone()).or_default();
+      if entry.file.is_empty() {
+        entry.file = match asset.relative_path.extension() {
+          Some(extension) => format!(
+            "{}.{}",
+            &digest[..NAME_HASH_LEN],
+            extension.to_string_lossy().to_lowercase()
+          ),
+          None => digest[..NAME_HASH_LEN].to_string(),
+        };
+      }
+      if let Some(name) = asset.relative_path.file_name() {
+        entry.names.insert(name.to_string_lossy().into_owned());
+      }
+      entry.pages.insert(processed.filename.clone());
+
+      let shared_path = shared_dir.join(&entry.file);
+      let from = link_path(&asset.relative_path);
+      let to = link_path(&shared_path);
+      processed.content = processed
+        .content
+        .replace(&format!("]({from})"), &format!("]({to})"))
+        .replace(&format!("src=\"{from}\""), &format!("src=\"{to}\""))
+        .replace(&format!(":{from}["), &format!(":{to}["));
+      for link in processed
+        .link_map
+        .images
+        .values_mut()
+        .chain(processed.link_map.attachments.values_mut())
+      {
+        if *link == from {
+          link.clone_from(&to);
+        }
+      }
+      asset.relative_path = shared_path;
+    }
+    Ok(())
+  }
+
+  /// Write the hash index to `assets/index.json` at the export root.
+  ///
+  /// # Returns
+  /// The path of the index, or `None` when no assets were shared.
+  ///
+  /// # Errors
+  /// Returns an error when the index cannot be written.
+  pub fn write_index(&self) -> Result<Option<PathBuf>> {
+    let index = self.index.lock().expect("shared asset index lock poisoned");
+    if index.is_empty() {
+      return Ok(None);
+    }
+    let dir = self.root.join(SHARED_ASSETS_DIR);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let path = dir.join(INDEX_FILE);
+    let json = serde_json::to_string_pretty(&*index).context("Failed to serialize shared asset index")?;
+    fs::write(&path, format!("{json}\n")).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::integrity::sha256_hex;
+  use crate::test_support::processed_page;
+
+  #[test]
+  fn test_share_rewrites_links_to_one_file_per_content() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    let shared = SharedAssets::new(root);
+    let logo = sha256_hex(b"logo");
+
+    let mut home = processed_page("Home", "![Logo](images/logo.png)\n", &[("images/logo.png", b"logo")]);
+    shared.share(&mut home, root).unwrap();
+    let mut guide = processed_page(
+      "Guide",
+      "![Logo](images/brand.PNG)\n\nimage::images/brand.PNG[Logo]\n",
+      &[("images/brand.PNG", b"logo")],
+    );
+    shared.share(&mut guide, &root.join("Home")).unwrap();
+
+    let file = format!("{}.png", &logo[..NAME_HASH_LEN]);
+    assert_eq!(home.images[0].relative_path, Path::new("assets").join(&file));
+    assert_eq!(home.content, format!("![Logo](assets/{file})\n"));
+    assert_eq!(
+      guide.content,
+      format!("![Logo](../assets/{file})\n\nimage::../assets/{file}[Logo]\n")
+    );
+
+    let index_path = shared.write_index().unwrap().unwrap();
+    assert_eq!(index_path, root.join("assets").join(INDEX_FILE));
+    let index: serde_json::Value = serde_json::from_str(&fs::read_to_string(index_path).unwrap()).unwrap();
+    assert_eq!(
+      index[&logo],
+      serde_json::json!({"file": file, "names": ["brand.PNG", "logo.png"], "pages": ["Guide", "Home"]})
+    );
+  }
+}
@@ -0,0 +1,188 @@
+//! File size limits of the platforms exports are published to.
+//!
+//! Git hosts and wikis stop rendering Markdown files above a certain size, and
+//! users usually only find out after pushing. [`SizeLimit`] describes such a
+//! limit (a platform preset or an explicit byte count), and
+//! [`check_page_size`] reports pages that exceed it along with how much of the
+//! page is tables, which are the usual culprit.
+
+use std::fmt;
+
+/// Maximum size of a generated page file before a warning is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeLimit {
+  /// Never warn about file sizes.
+  Off,
+  /// Limit of a known platform, e.g. GitHub's 1 MiB render limit.
+  Platform(&'static str, u64),
+  /// Explicit limit in bytes.
+  Bytes(u64),
+}
+
+/// Known platform limits, by preset name.
+const PLATFORM_LIMITS: &[(&str, &str, u64)] = &[
+  ("github", "GitHub", 1024 * 1024),
+  ("gitlab", "GitLab wiki", 50 * 1024 * 1024),
+];
+
+impl SizeLimit {
+  /// The limit in bytes, or `None` when checks are disabled.
+  pub fn bytes(self) -> Option<u64> {
+    match self {
+      Self::Off => None,
+      Self::Platform(_, bytes) | Self::Bytes(bytes) => Some(bytes),
+    }
+  }
+}
+
+impl fmt::Display for SizeLimit {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Off => write!(f, "off"),
+      Self::Platform(name, bytes) => write!(f, "{name}'s {} limit", format_size(*bytes)),
+      Self::Bytes(bytes) => write!(f, "the {} limit", format_size(*bytes)),
+    }
+  }
+}
+
+/// Parse a `--size-limit` value.
+///
+/// Accepts `off`, a platform preset (`github`, `gitlab`), or a byte count with
+/// an optional `K`, `M`, or `G` suffix (binary multiples, e.g. `512K`).
+pub fn parse_size_limit(value: &str) -> Result<SizeLimit, String> {
+  let value = value.trim();
+  if value.eq_ignore_ascii_case("off") {
+    return Ok(SizeLimit::Off);
+  }
+  if let Some((_, name, bytes)) = PLATFORM_LIMITS
+    .iter()
+    .find(|(preset, _, _)| preset.eq_ignore_ascii_case(value))
+  {
+    return Ok(SizeLimit::Platform(name, *bytes));
+  }
+
+  parse_size(value)
+    .map(SizeLimit::Bytes)
+    .map_err(|_| format!("invalid size limit '{value}': expected off, github, gitlab, or a size like 512K"))
+}
+
+/// Parse a size in bytes with an optional `K`, `M`, or `G` suffix (binary
+/// multiples, e.g. `512K` or `10MiB`).
+pub fn parse_size(value: &str) -> Result<u64, String> {
+  let value = value.trim();
+  let upper = value.to_ascii_uppercase();
+  let digits = upper.trim_end_matches(['B', 'I']);
+  let (number, multiplier) = match digits.chars().last() {
+    Some('K') => (&digits[..digits.len() - 1], 1024),
+    Some('M') => (&digits[..digits.len() - 1], 1024 * 1024),
+    Some('G') => (&digits[..digits.len() - 1], 1024 * 1024 * 1024),
+    _ => (digits, 1),
+  };
+  match number.trim().parse::<u64>() {
+    Ok(count) if count > 0 => Ok(count * multiplier),
+    _ => Err(format!("invalid size '{value}': expected a size like 512K or 10M")),
+  }
+}
+
+/// A page file larger than the configured limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OversizedPage {
+  /// Size of the written file in bytes.
+  pub size: u64,
+  /// Percentage of the content made up of table rows.
+  pub table_percent: u8,
+}
+
+/// Check a written page against a size limit.
+///
+/// # Arguments
+/// * `size` - Size of the written page file in bytes.
+/// * `content` - Converted page content, used to estimate the share of tables.
+/// * `limit` - Configured size limit.
+///
+/// # Returns
+/// `Some(OversizedPage)` when the file exceeds the limit, otherwise `None`.
+pub fn check_page_size(size: u64, content: &str, limit: SizeLimit) -> Option<OversizedPage> {
+  if size <= limit.bytes()? {
+    return None;
+  }
+
+  // Markdown table rows start with `|`; AsciiDoc tables are fenced with `|===`
+  let mut in_asciidoc_table = false;
+  let table_bytes: usize = content
+    .lines()
+    .filter(|line| {
+      let trimmed = line.trim_start();
+      if trimmed.starts_with("|===") {
+        in_asciidoc_table = !in_asciidoc_table;
+        return true;
+      }
+      in_asciidoc_table || trimmed.starts_with('|')
+    })
+    .map(|line| line.len() + 1)
+    .sum();
+  let table_percent = (table_bytes * 100 / content.len().max(1)).min(100) as u8;
+
+  Some(OversizedPage { size, table_percent })
+}
+
+/// Format a byte count using binary units, e.g. `1.5 MiB`.
+pub fn format_size(bytes: u64) -> String {
+  const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+  if bytes < 1024 {
+    return format!("{bytes} B");
+  }
+
+  let mut value = bytes as f64 / 1024.0;
+  let mut unit = 0;
+  while value >= 1024.0 && unit + 1 < UNITS.len() {
+    value /= 1024.0;
+    unit += 1;
+  }
+  if value.fract() == 0.0 {
+    format!("{value:.0} {}", UNITS[unit])
+  } else {
+    format!("{value:.1} {}", UNITS[unit])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_size_limit() {
+    assert_eq!(parse_size_limit("off"), Ok(SizeLimit::Off));
+    assert_eq!(
+      parse_size_limit("GitHub"),
+      Ok(SizeLimit::Platform("GitHub", 1024 * 1024))
+    );
+    assert_eq!(parse_size_limit("512K"), Ok(SizeLimit::Bytes(512 * 1024)));
+    assert_eq!(parse_size_limit("2MiB"), Ok(SizeLimit::Bytes(2 * 1024 * 1024)));
+    assert_eq!(parse_size_limit("900"), Ok(SizeLimit::Bytes(900)));
+    assert!(parse_size_limit("0").is_err());
+    assert!(parse_size_limit("big").is_err());
+  }
+
+  #[test]
+  fn test_check_page_size_reports_table_share() {
+    let content = "# Inventory\n\n| a | b |\n| - | - |\n| 1 | 2 |\n";
+
+    assert_eq!(check_page_size(10, content, SizeLimit::Bytes(100)), None);
+    assert_eq!(check_page_size(10, content, SizeLimit::Off), None);
+    assert_eq!(
+      check_page_size(200, content, SizeLimit::Bytes(100)),
+      Some(OversizedPage {
+        size: 200,
+        table_percent: 69
+      })
+    );
+  }
+
+  #[test]
+  fn test_format_size() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1024 * 1024), "1 MiB");
+    assert_eq!(format_size(1536 * 1024), "1.5 MiB");
+  }
+}
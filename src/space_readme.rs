@@ -0,0 +1,192 @@
+//! Space landing page for tree exports.
+//!
+//! With `--space-readme`, a `README` at the export root summarizes the space
+//! the exported tree belongs to: its name and key, description and logo, a
+//! link to the space home page, and when and how much was exported. It is
+//! written in the output format, with the extension of the pages, so Git
+//! mirrors of a space open on a useful page instead of a bare directory
+//! listing.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::confluence::Space;
+use crate::format::OutputFormat;
+
+/// File name, without extension, of the generated landing page.
+pub const README_STEM: &str = "README";
+
+/// File name (without extension) the space logo is saved as.
+pub const ICON_STEM: &str = "space-icon";
+
+/// Details of an export shown on the landing page.
+#[derive(Debug, Clone)]
+pub struct SpaceReadme<'a> {
+  /// The exported space.
+  pub space: &'a Space,
+  /// Confluence instance URL, e.g. `https://example.atlassian.net`.
+  pub base_url: &'a str,
+  /// Title of the exported root page.
+  pub root_title: &'a str,
+  /// Path of the root page file, relative to the export root.
+  pub root_path: &'a str,
+  /// Pages written by the export.
+  pub page_count: usize,
+  /// When the export ran.
+  pub exported_at: DateTime<Utc>,
+  /// Path of the saved space logo, relative to the export root.
+  pub icon_path: Option<&'a str>,
+  /// Format the landing page is written in; JSON exports get Markdown.
+  pub format: OutputFormat,
+}
+
+impl SpaceReadme<'_> {
+  /// Render the landing page in its format.
+  pub fn render(&self) -> String {
+    let space = self.space;
+    let format = self.format;
+    let base_url = self.base_url.trim_end_matches('/');
+    let mut result = format!("{}\n\n", format.heading(1, &space.name));
+
+    if let Some(icon) = self.icon_path {
+      let alt = format!("{} logo", space.name);
+      let image = match format.content_format() {
+        OutputFormat::AsciiDoc => format!("image::{}[{alt}]", self.link_target(icon)),
+        _ => format!("![{alt}]({})", self.link_target(icon)),
+      };
+      result.push_str(&format!("{image}\n\n"));
+    }
+    if let Some(description) = space.description_text() {
+      result.push_str(description);
+      result.push_str("\n\n");
+    }
+
+    result.push_str(&format!(
+      "Start reading at {}.\n\n",
+      format.link(self.root_title, &self.link_target(self.root_path))
+    ));
+
+    let mut item = |label: &str, value: String| {
+      result.push_str(&format!("{} {} {value}\n", format.bullet(), format.strong(label)));
+    };
+    item(
+      "Space:",
+      format.link(&space.key, &format!("{base_url}/wiki/spaces/{}", space.key)),
+    );
+    if let Some(homepage) = &space.homepage {
+      item(
+        "Home page:",
+        format.link(
+          &homepage.title,
+          &format!("{base_url}/wiki/pages/viewpage.action?pageId={}", homepage.id),
+        ),
+      );
+    }
+    item("Pages exported:", self.page_count.to_string());
+    item(
+      "Exported at:",
+      self.exported_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+    );
+    result
+  }
+
+  /// A relative path usable as a link target.
+  ///
+  /// Markdown paths with spaces or parentheses are wrapped in angle brackets;
+  /// AsciiDoc paths have their spaces escaped.
+  fn link_target(&self, path: &str) -> String {
+    match self.format.content_format() {
+      OutputFormat::AsciiDoc => path.replace(' ', "%20"),
+      _ if path.contains([' ', '(', ')']) => format!("<{path}>"),
+      _ => path.to_string(),
+    }
+  }
+}
+
+/// File extension for a downloaded logo, detected from its contents.
+///
+/// Space logo URLs usually carry no extension, so the image type is sniffed
+/// from the file signature, falling back to `png`.
+pub fn icon_extension(content: &[u8]) -> &'static str {
+  if content.starts_with(b"\x89PNG") {
+    "png"
+  } else if content.starts_with(b"\xFF\xD8\xFF") {
+    "jpg"
+  } else if content.starts_with(b"GIF8") {
+    "gif"
+  } else if content.starts_with(b"<svg") || content.starts_with(b"<?xml") {
+    "svg"
+  } else {
+    "png"
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_render_space_readme() {
+    let space: Space = serde_json::from_value(serde_json::json!({
+      "key": "DOCS",
+      "name": "Product Docs",
+      "type": "global",
+      "description": {"plain": {"value": "Everything about the product.", "representation": "plain"}},
+      "icon": {"path": "/wiki/download/attachments/1/DOCS", "isDefault": false},
+      "homepage": {"id": "42", "title": "Docs Home", "type": "page", "status": "current"}
+    }))
+    .unwrap();
+    let readme = SpaceReadme {
+      space: &space,
+      base_url: "https://example.atlassian.net/",
+      root_title: "Docs Home",
+      root_path: "Docs Home.md",
+      page_count: 12,
+      exported_at: DateTime::parse_from_rfc3339("2026-10-16T08:30:00Z").unwrap().to_utc(),
+      icon_path: Some("space-icon.png"),
+      format: OutputFormat::Markdown,
+    };
+
+    insta::assert_snapshot!(readme.render(), @r"
+    # Product Docs
+
+    ![Product Docs logo](space-icon.png)
+
+    Everything about the product.
+
+    Start reading at [Docs Home](<Docs Home.md>).
+
+    - **Space:** [DOCS](https://example.atlassian.net/wiki/spaces/DOCS)
+    - **Home page:** [Docs Home](https://example.atlassian.net/wiki/pages/viewpage.action?pageId=42)
+    - **Pages exported:** 12
+    - **Exported at:** 2026-10-16T08:30:00Z
+    ");
+
+    let readme = SpaceReadme {
+      root_path: "Docs Home.adoc",
+      format: OutputFormat::AsciiDoc,
+      ..readme
+    };
+    insta::assert_snapshot!(readme.render(), @r"
+    = Product Docs
+
+    image::space-icon.png[Product Docs logo]
+
+    Everything about the product.
+
+    Start reading at link:Docs%20Home.adoc[Docs Home].
+
+    * *Space:* https://example.atlassian.net/wiki/spaces/DOCS[DOCS]
+    * *Home page:* https://example.atlassian.net/wiki/pages/viewpage.action?pageId=42[Docs Home]
+    * *Pages exported:* 12
+    * *Exported at:* 2026-10-16T08:30:00Z
+    ");
+  }
+
+  #[test]
+  fn test_icon_extension() {
+    assert_eq!(icon_extension(b"\x89PNG\r\n"), "png");
+    assert_eq!(icon_extension(b"\xFF\xD8\xFF\xE0"), "jpg");
+    assert_eq!(icon_extension(b"<svg xmlns="), "svg");
+    assert_eq!(icon_extension(b""), "png");
+  }
+}
@@ -0,0 +1,242 @@
+//! Extraction of page tables to CSV or TSV files.
+//!
+//! With `--extract-tables`, every table on a page is also written to
+//! `tables/<page>-<n>.csv` (or `.tsv`) next to the page, so figures from
+//! status tables can be loaded into a spreadsheet without re-keying them.
+//! Cells hold the plain text of the table cell; cells covered by a `colspan`
+//! or `rowspan` are left empty so values stay in their columns.
+//!
+//! With `--split-table-rows` or `--split-table-columns`, tables over the
+//! [`TableSplit`] limits are cut down to a preview in the converted page, and
+//! [`extract_split_tables`] writes them in full to the same CSV files.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+
+use crate::format::TableFormat;
+use crate::markdown::html_entities::{decode_html_entities, preprocess_html_entities};
+use crate::markdown::table_grid;
+use crate::markdown::utils::{matches_tag, wrap_with_namespaces};
+use crate::processed_page::{AssetContent, AssetData};
+
+/// Subdirectory, relative to the page, that extracted tables are written to.
+pub const TABLES_DIR: &str = "tables";
+
+/// Data rows shown in the preview of a split table when only the column limit
+/// is set.
+pub const DEFAULT_PREVIEW_ROWS: usize = 10;
+
+/// Limits above which a table is shown as a preview linking to a CSV file.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TableSplit {
+  /// Most data rows (below the header row) kept in the page.
+  pub max_rows: Option<usize>,
+  /// Most columns kept in the page.
+  pub max_columns: Option<usize>,
+  /// Sanitized filename of the page being converted, naming the CSV files.
+  pub page_filename: String,
+}
+
+impl TableSplit {
+  /// Whether a table of `rows` rows, including its header row, and
+  /// `columns` columns is split.
+  pub fn exceeded(&self, rows: usize, columns: usize) -> bool {
+    self.max_rows.is_some_and(|max| rows.saturating_sub(1) > max) || self.max_columns.is_some_and(|max| columns > max)
+  }
+
+  /// Data rows shown in the preview of a split table.
+  pub fn preview_rows(&self) -> usize {
+    self.max_rows.unwrap_or(DEFAULT_PREVIEW_ROWS)
+  }
+}
+
+/// Path of the file the `index`th table (counting from 1) of a page is
+/// extracted to.
+pub fn table_path(filename: &str, index: usize, format: TableFormat) -> PathBuf {
+  PathBuf::from(TABLES_DIR).join(format!("{filename}-{index}.{}", format.file_extension()))
+}
+
+/// Elements whose boundaries separate words in a cell's text.
+const BREAKING_TAGS: &[&str] = &["br", "p", "div", "li", "h1", "h2", "h3", "h4", "h5", "h6"];
+
+/// Extract the tables of a page as delimited text files.
+///
+/// # Arguments
+/// * `storage_content` - Confluence storage format of the page.
+/// * `filename` - Sanitized page filename (without extension) the files are named after.
+/// * `format` - File format of the extracted tables.
+///
+/// # Returns
+/// One file per table with at least one cell, in document order, named
+/// `tables/<filename>-<n>.<ext>` with `n` counting from 1.
+///
+/// # Errors
+/// Returns an error when the storage content cannot be parsed.
+pub fn extract_tables(storage_content: &str, filename: &str, format: TableFormat) -> Result<Vec<AssetData>> {
+  let tables = table_grids(storage_content)?
+    .into_iter()
+    .enumerate()
+    .map(|(index, rows)| AssetData {
+      relative_path: table_path(filename, index + 1, format),
+      content: AssetContent::Bytes(render_delimited(&rows, format).into_bytes()),
+    })
+    .collect();
+  Ok(tables)
+}
+
+/// Extract the tables of a page that exceed the split limits as CSV files.
+///
+/// Files are numbered like [`extract_tables`] numbers every table, so the
+/// links in the converted previews point at them.
+///
+/// # Errors
+/// Returns an error when the storage content cannot be parsed.
+pub fn extract_split_tables(storage_content: &str, split: &TableSplit) -> Result<Vec<AssetData>> {
+  let tables = table_grids(storage_content)?
+    .into_iter()
+    .enumerate()
+    .filter(|(_, rows)| split.exceeded(rows.len(), rows.iter().map(Vec::len).max().unwrap_or_default()))
+    .map(|(index, rows)| AssetData {
+      relative_path: table_path(&split.page_filename, index + 1, TableFormat::Csv),
+      content: AssetContent::Bytes(render_delimited(&rows, TableFormat::Csv).into_bytes()),
+    })
+    .collect();
+  Ok(tables)
+}
+
+/// Cell text of every table with at least one cell, in document order.
+fn table_grids(storage_content: &str) -> Result<Vec<Vec<Vec<String>>>> {
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document = Document::parse(&wrapped).context("Failed to parse Confluence storage content to extract tables")?;
+
+  Ok(
+    document
+      .descendants()
+      .filter(|node| matches_tag(*node, "table"))
+      .map(|table| table_grid(table, cell_text))
+      .filter(|rows| !rows.is_empty())
+      .collect(),
+  )
+}
+
+/// Plain text of a table cell with whitespace collapsed.
+///
+/// Line breaks, paragraphs, and list items are separated by a space so their
+/// words do not run together.
+fn cell_text(cell: Node) -> String {
+  let mut text = String::new();
+  collect_text(cell, &mut text);
+  text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn collect_text(node: Node, text: &mut String) {
+  for child in node.children() {
+    if child.is_element() {
+      let breaking = BREAKING_TAGS.iter().any(|tag| matches_tag(child, tag));
+      if breaking {
+        text.push(' ');
+      }
+      collect_text(child, text);
+      if breaking {
+        text.push(' ');
+      }
+    } else if let Some(value) = child.text() {
+      text.push_str(&decode_html_entities(value));
+    }
+  }
+}
+
+/// Render table rows as CSV (RFC 4180 quoting and line endings) or TSV.
+///
+/// Cell text never contains tabs or line breaks, so TSV needs no quoting.
+fn render_delimited(rows: &[Vec<String>], format: TableFormat) -> String {
+  rows
+    .iter()
+    .map(|row| match format {
+      TableFormat::Csv => row.iter().map(|cell| csv_field(cell)).collect::<Vec<_>>().join(",") + "\r\n",
+      TableFormat::Tsv => row.join("\t") + "\n",
+    })
+    .collect()
+}
+
+fn csv_field(cell: &str) -> String {
+  if cell.contains([',', '"', '\r', '\n']) {
+    format!("\"{}\"", cell.replace('"', "\"\""))
+  } else {
+    cell.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn text(table: &AssetData) -> String {
+    match &table.content {
+      AssetContent::Bytes(bytes) => String::from_utf8(bytes.clone()).unwrap(),
+      AssetContent::Staged(staged) => panic!("table staged at {}", staged.path.display()),
+    }
+  }
+
+  #[test]
+  fn test_extract_tables() {
+    let storage = concat!(
+      r#"<p>Intro</p>"#,
+      r#"<table><tbody><tr><th>Team</th><th colspan="2">Budget, "approved"</th></tr>"#,
+      r#"<tr><td><p>Platform</p><p>Core</p></td><td>1,200</td><td>&euro;</td></tr></tbody></table>"#,
+      r#"<table><tbody></tbody></table>"#,
+      r#"<table><tbody><tr><td>a<br/>b</td></tr></tbody></table>"#,
+    );
+
+    let tables = extract_tables(storage, "Status", TableFormat::Csv).unwrap();
+
+    let paths: Vec<_> = tables.iter().map(|table| table.relative_path.clone()).collect();
+    assert_eq!(
+      paths,
+      [
+        PathBuf::from("tables/Status-1.csv"),
+        PathBuf::from("tables/Status-2.csv")
+      ]
+    );
+    assert_eq!(
+      text(&tables[0]),
+      "Team,\"Budget, \"\"approved\"\"\",\r\nPlatform Core,\"1,200\",€\r\n"
+    );
+    assert_eq!(text(&tables[1]), "a b\r\n");
+
+    let tables = extract_tables(storage, "Status", TableFormat::Tsv).unwrap();
+    assert_eq!(
+      text(&tables[0]),
+      "Team\tBudget, \"approved\"\t\nPlatform Core\t1,200\t€\n"
+    );
+  }
+
+  #[test]
+  fn test_extract_split_tables() {
+    let storage = concat!(
+      "<table><tbody><tr><th>A</th></tr><tr><td>1</td></tr></tbody></table>",
+      "<table><tbody><tr><th>B</th></tr><tr><td>1</td></tr><tr><td>2</td></tr><tr><td>3</td></tr></tbody></table>",
+      "<table><tbody><tr><th>C</th><th>D</th><th>E</th></tr></tbody></table>",
+    );
+    let split = TableSplit {
+      max_rows: Some(2),
+      max_columns: Some(2),
+      page_filename: "Status".to_string(),
+    };
+
+    let tables = extract_split_tables(storage, &split).unwrap();
+
+    let paths: Vec<_> = tables.iter().map(|table| table.relative_path.clone()).collect();
+    assert_eq!(
+      paths,
+      [
+        PathBuf::from("tables/Status-2.csv"),
+        PathBuf::from("tables/Status-3.csv")
+      ]
+    );
+    assert_eq!(text(&tables[0]), "B\r\n1\r\n2\r\n3\r\n");
+  }
+}
@@ -0,0 +1,215 @@
+//! Inline tasks of Confluence pages.
+//!
+//! Confluence stores the assignee of a task as a user mention inside its
+//! body and the due date as a `<time>` element. [`InlineTask`] reads both so
+//! the converters can render them after the task text, as `@owner` and
+//! `📅 2024-05-01`. With `--tasks-report`, [`TaskReport`] collects the open
+//! tasks of every exported page into a `TASKS` file at the export root,
+//! written in the output format with the extension of the pages.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+
+use crate::confluence::Page;
+use crate::format::OutputFormat;
+use crate::markdown::html_entities::preprocess_html_entities;
+use crate::markdown::utils::{find_child_by_tag, get_attribute, get_element_text, matches_tag, wrap_with_namespaces};
+
+/// File name, without extension, of the open task summary at the export
+/// root.
+pub const TASKS_STEM: &str = "TASKS";
+
+/// A task of an `<ac:task-list>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InlineTask {
+  /// Whether the task is checked off.
+  pub complete: bool,
+  /// Task text with whitespace collapsed.
+  pub text: String,
+  /// Mention of the assigned user, `@user:<account-id>` or `@<username>`.
+  pub assignee: Option<String>,
+  /// Due date as stored, usually `YYYY-MM-DD`.
+  pub due: Option<String>,
+}
+
+impl InlineTask {
+  /// Read a task from its `<ac:task>` node.
+  pub fn from_node(task: Node) -> Self {
+    let complete =
+      find_child_by_tag(task, "ac:task-status").is_some_and(|status| get_element_text(status).trim() == "complete");
+    let Some(body) = find_child_by_tag(task, "ac:task-body") else {
+      return Self {
+        complete,
+        ..Default::default()
+      };
+    };
+
+    let assignee = body
+      .descendants()
+      .filter(|node| matches_tag(*node, "ri:user"))
+      .find_map(|user| {
+        get_attribute(user, "ri:account-id")
+          .map(|account_id| format!("@user:{account_id}"))
+          .or_else(|| get_attribute(user, "ri:username").map(|username| format!("@{username}")))
+      });
+    let due = body
+      .descendants()
+      .filter(|node| matches_tag(*node, "time"))
+      .find_map(|time| get_attribute(time, "datetime"))
+      .filter(|due| !due.trim().is_empty());
+
+    Self {
+      complete,
+      text: get_element_text(body).split_whitespace().collect::<Vec<_>>().join(" "),
+      assignee,
+      due,
+    }
+  }
+
+  /// The task text followed by its assignee and due date.
+  pub fn label(&self) -> String {
+    let mut label = self.text.clone();
+    for suffix in [self.assignee.clone(), self.due.as_ref().map(|due| format!("📅 {due}"))]
+      .into_iter()
+      .flatten()
+    {
+      if !label.is_empty() {
+        label.push(' ');
+      }
+      label.push_str(&suffix);
+    }
+    label
+  }
+}
+
+/// Find the open tasks of a page's storage content, in document order.
+///
+/// # Errors
+/// Returns an error if the storage content cannot be parsed.
+pub fn open_tasks(storage_content: &str) -> Result<Vec<InlineTask>> {
+  let preprocessed = preprocess_html_entities(storage_content);
+  let wrapped = wrap_with_namespaces(&preprocessed);
+  let document = Document::parse(&wrapped).context("Failed to parse Confluence storage content for tasks")?;
+  Ok(
+    document
+      .descendants()
+      .filter(|node| matches_tag(*node, "ac:task"))
+      .map(InlineTask::from_node)
+      .filter(|task| !task.complete)
+      .collect(),
+  )
+}
+
+/// Open tasks of one exported page.
+#[derive(Debug)]
+struct PageTasks {
+  title: String,
+  tasks: Vec<InlineTask>,
+}
+
+/// Open tasks recorded during an export, keyed by page path.
+#[derive(Debug, Default)]
+pub struct TaskReport {
+  pages: Mutex<BTreeMap<String, PageTasks>>,
+}
+
+impl TaskReport {
+  /// Record the open tasks of an exported page.
+  ///
+  /// # Arguments
+  /// * `page` - The exported page.
+  /// * `path` - Path of the page file relative to the export root.
+  ///
+  /// # Errors
+  /// Returns an error if the page's storage content cannot be parsed.
+  pub fn record(&self, page: &Page, path: &str) -> Result<()> {
+    let tasks = match page.storage_content() {
+      Some(storage) => open_tasks(storage)?,
+      None => Vec::new(),
+    };
+    if !tasks.is_empty() {
+      self.pages.lock().expect("task report lock poisoned").insert(
+        path.replace('\\', "/"),
+        PageTasks {
+          title: page.title.clone(),
+          tasks,
+        },
+      );
+    }
+    Ok(())
+  }
+
+  /// Render the task summary in `format`, listing the open tasks grouped by
+  /// page.
+  pub fn render(&self, format: OutputFormat) -> String {
+    let pages = self.pages.lock().expect("task report lock poisoned");
+    let mut result = format!("{}\n\n", format.heading(1, "Open tasks"));
+    let count: usize = pages.values().map(|page| page.tasks.len()).sum();
+    if count == 0 {
+      result.push_str("No open tasks.\n");
+      return result;
+    }
+
+    result.push_str(&format!(
+      "{count} open {} on {} {}.\n",
+      if count == 1 { "task" } else { "tasks" },
+      pages.len(),
+      if pages.len() == 1 { "page" } else { "pages" }
+    ));
+    for (path, page) in pages.iter() {
+      let link = format.link(&page.title, &path.replace(' ', "%20"));
+      result.push_str(&format!("\n{}\n\n", format.heading(2, &link)));
+      for task in &page.tasks {
+        result.push_str(&format!("{} [ ] {}\n", format.bullet(), task.label()));
+      }
+    }
+    result
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_task_report() {
+    let storage = r#"
+      <ac:task-list>
+        <ac:task>
+          <ac:task-status>incomplete</ac:task-status>
+          <ac:task-body><span class="placeholder-inline-tasks">Review the
+            draft <ac:link><ri:user ri:account-id="abc123" /></ac:link> <time datetime="2024-05-01" /></span></ac:task-body>
+        </ac:task>
+        <ac:task>
+          <ac:task-status>complete</ac:task-status>
+          <ac:task-body>Write the draft</ac:task-body>
+        </ac:task>
+      </ac:task-list>
+    "#;
+    let page: Page = serde_json::from_value(serde_json::json!({
+      "id": "1",
+      "type": "page",
+      "status": "current",
+      "title": "Release Plan",
+      "body": { "storage": { "value": storage, "representation": "storage" } }
+    }))
+    .unwrap();
+
+    let report = TaskReport::default();
+    report.record(&page, "Release Plan.md").unwrap();
+    assert_eq!(
+      report.render(OutputFormat::Markdown),
+      "# Open tasks\n\n1 open task on 1 page.\n\n## [Release Plan](Release%20Plan.md)\n\n- [ ] Review the draft @user:abc123 📅 2024-05-01\n"
+    );
+
+    let report = TaskReport::default();
+    report.record(&page, "Release Plan.adoc").unwrap();
+    assert_eq!(
+      report.render(OutputFormat::AsciiDoc),
+      "= Open tasks\n\n1 open task on 1 page.\n\n== link:Release%20Plan.adoc[Release Plan]\n\n* [ ] Review the draft @user:abc123 📅 2024-05-01\n"
+    );
+  }
+}
@@ -0,0 +1,239 @@
+//! Output templates wrapping each page's converted content.
+//!
+//! Teams publishing an export often need the same header, footer, or
+//! disclaimer on every page, such as a note that the page is a mirror with a
+//! link to edit it in Confluence. With `--template`, each page's content is
+//! placed into a template file at `{{body}}`, and the other placeholders are
+//! filled from the page's metadata and its rewritten links. Unknown placeholders are rejected when the
+//! template is loaded so a typo does not silently end up in every page.
+
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+
+use crate::confluence::Page;
+use crate::content_stubs::content_url;
+use crate::processed_page::LinkMap;
+
+/// Value a placeholder is replaced with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+  Title,
+  Body,
+  Url,
+  Labels,
+  Id,
+  Space,
+  Version,
+  LastModified,
+  Links,
+}
+
+impl Field {
+  const NAMES: &[(&str, Field)] = &[
+    ("title", Field::Title),
+    ("body", Field::Body),
+    ("url", Field::Url),
+    ("labels", Field::Labels),
+    ("id", Field::Id),
+    ("space", Field::Space),
+    ("version", Field::Version),
+    ("last_modified", Field::LastModified),
+    ("links", Field::Links),
+  ];
+
+  fn from_name(name: &str) -> Option<Self> {
+    Self::NAMES
+      .iter()
+      .find(|(candidate, _)| *candidate == name)
+      .map(|(_, field)| *field)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+  Text(String),
+  Field(Field),
+}
+
+/// A parsed template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageTemplate {
+  segments: Vec<Segment>,
+}
+
+impl PageTemplate {
+  /// Load a template file.
+  ///
+  /// # Errors
+  /// Returns an error when the file cannot be read or is not a valid template.
+  pub fn load(path: &Path) -> Result<Self> {
+    let content =
+      std::fs::read_to_string(path).with_context(|| format!("Failed to read template {}", path.display()))?;
+    Self::parse(&content).with_context(|| format!("Invalid template {}", path.display()))
+  }
+
+  /// Parse a template.
+  ///
+  /// Placeholders are written `{{name}}`, optionally with spaces inside the
+  /// braces.
+  ///
+  /// # Errors
+  /// Returns an error when a placeholder is unknown, or when the template has
+  /// no `{{body}}` placeholder and would drop the page's content.
+  pub fn parse(template: &str) -> Result<Self> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+      let Some(end) = rest[start + 2..].find("}}") else {
+        break;
+      };
+      let name = rest[start + 2..start + 2 + end].trim();
+      let Some(field) = Field::from_name(name) else {
+        let known: Vec<&str> = Field::NAMES.iter().map(|(name, _)| *name).collect();
+        bail!(
+          "Unknown placeholder {{{{{name}}}}}; expected one of: {}",
+          known.join(", ")
+        );
+      };
+      if start > 0 {
+        segments.push(Segment::Text(rest[..start].to_string()));
+      }
+      segments.push(Segment::Field(field));
+      rest = &rest[start + 2 + end + 2..];
+    }
+    if !rest.is_empty() {
+      segments.push(Segment::Text(rest.to_string()));
+    }
+
+    if !segments.contains(&Segment::Field(Field::Body)) {
+      bail!("Template has no {{{{body}}}} placeholder for the page content");
+    }
+    Ok(Self { segments })
+  }
+
+  /// Place a page's converted content into the template.
+  ///
+  /// # Arguments
+  /// * `page` - The exported page, whose metadata fills the placeholders.
+  /// * `body` - The page's converted content.
+  /// * `links` - Links rewritten in `body`, written as JSON for `{{links}}`.
+  /// * `instance_url` - Base URL of the Confluence instance, used for `{{url}}`.
+  ///
+  /// # Returns
+  /// The wrapped content, ending with a single newline. Placeholders whose
+  /// value is unknown are left empty.
+  pub fn render(&self, page: &Page, body: &str, links: &LinkMap, instance_url: Option<&str>) -> String {
+    let mut result = String::with_capacity(body.len());
+    for segment in &self.segments {
+      match segment {
+        Segment::Text(text) => result.push_str(text),
+        Segment::Field(Field::Body) => result.push_str(body.trim_end_matches('\n')),
+        Segment::Field(Field::Title) => result.push_str(&page.title),
+        Segment::Field(Field::Url) => result.push_str(&content_url(page, instance_url).unwrap_or_default()),
+        Segment::Field(Field::Labels) => result.push_str(&page.labels().join(", ")),
+        Segment::Field(Field::Id) => result.push_str(&page.id),
+        Segment::Field(Field::Space) => {
+          result.push_str(page.space.as_ref().map_or("", |space| space.key.as_str()));
+        }
+        Segment::Field(Field::Version) => {
+          if let Some(version) = &page.version {
+            result.push_str(&version.number.to_string());
+          }
+        }
+        Segment::Field(Field::LastModified) => result.push_str(
+          page
+            .last_modified_date()
+            .or_else(|| page.version.as_ref().and_then(|version| version.when.as_deref()))
+            .unwrap_or_default(),
+        ),
+        Segment::Field(Field::Links) => {
+          result.push_str(&serde_json::to_string(links).expect("link maps serialize to JSON"));
+        }
+      }
+    }
+    let trimmed_len = result.trim_end_matches('\n').len();
+    result.truncate(trimmed_len);
+    result.push('\n');
+    result
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::page_in_space;
+
+  fn runbook() -> Page {
+    Page {
+      metadata: serde_json::from_value(
+        serde_json::json!({"labels": {"results": [{"name": "oncall"}, {"name": "sre"}]}}),
+      )
+      .unwrap(),
+      version: serde_json::from_value(serde_json::json!({"number": 7, "when": "2024-05-01T10:00:00.000Z"})).unwrap(),
+      links: serde_json::from_value(serde_json::json!({"webui": "/spaces/OPS/pages/42/Runbook"})).unwrap(),
+      ..page_in_space("42", "Runbook", "OPS")
+    }
+  }
+
+  #[test]
+  fn test_render() {
+    let template = PageTemplate::parse(
+      "> Mirrored from Confluence. [Edit {{ title }}]({{url}}) (v{{version}}, {{last_modified}})\n\n{{body}}\n\n---\nSpace {{space}}, page {{id}}. Labels: {{labels}}\n\n",
+    )
+    .unwrap();
+
+    insta::assert_snapshot!(
+      template.render(
+        &runbook(),
+        "# Runbook\n\nRestart the service.\n",
+        &LinkMap::default(),
+        Some("https://example.atlassian.net")
+      ),
+      @r"
+    > Mirrored from Confluence. [Edit Runbook](https://example.atlassian.net/wiki/spaces/OPS/pages/42/Runbook) (v7, 2024-05-01T10:00:00.000Z)
+
+    # Runbook
+
+    Restart the service.
+
+    ---
+    Space OPS, page 42. Labels: oncall, sre
+    "
+    );
+  }
+
+  #[test]
+  fn test_render_links() {
+    let template = PageTemplate::parse("{{body}}\n<!-- links: {{ links }} -->\n").unwrap();
+    let mut links = LinkMap::default();
+    links
+      .images
+      .insert("arch.png".to_string(), "images/arch.png".to_string());
+    links.pages.insert(
+      "https://example.atlassian.net/wiki/display/OPS/Setup".to_string(),
+      "Setup.md".to_string(),
+    );
+
+    insta::assert_snapshot!(template.render(&runbook(), "See [Setup](Setup.md).", &links, None), @r#"
+    See [Setup](Setup.md).
+    <!-- links: {"images":{"arch.png":"images/arch.png"},"attachments":{},"pages":{"https://example.atlassian.net/wiki/display/OPS/Setup":"Setup.md"}} -->
+    "#);
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    let err = PageTemplate::parse("{{body}} {{author}}").unwrap_err();
+    assert!(err.to_string().starts_with("Unknown placeholder {{author}}"), "{err}");
+
+    let err = PageTemplate::parse("# {{title}}\n").unwrap_err();
+    assert!(err.to_string().contains("no {{body}} placeholder"), "{err}");
+
+    // An unterminated placeholder is kept as text
+    let template = PageTemplate::parse("{{body}} {{ not closed").unwrap();
+    assert_eq!(
+      template.render(&runbook(), "Text", &LinkMap::default(), None),
+      "Text {{ not closed\n"
+    );
+  }
+}
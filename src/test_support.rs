@@ -0,0 +1,69 @@
+//! Builders of pages, page trees, and processed pages shared by the unit
+//! tests.
+
+use std::path::PathBuf;
+
+use crate::confluence::{Page, PageSpace, PageTree};
+use crate::processed_page::{AssetContent, AssetData, ProcessedPage};
+
+/// A current page with no body, space, or metadata.
+pub(crate) fn page(id: &str, title: &str) -> Page {
+  Page {
+    id: id.to_string(),
+    title: title.to_string(),
+    page_type: "page".to_string(),
+    status: "current".to_string(),
+    body: None,
+    space: None,
+    links: None,
+    metadata: None,
+    history: None,
+    version: None,
+    ancestors: Vec::new(),
+  }
+}
+
+/// A page of the global space `key`, named after its key.
+pub(crate) fn page_in_space(id: &str, title: &str, key: &str) -> Page {
+  Page {
+    space: Some(PageSpace {
+      key: key.to_string(),
+      name: key.to_string(),
+      space_type: "global".to_string(),
+    }),
+    ..page(id, title)
+  }
+}
+
+/// An exported tree rooted at `page` at depth 0, with `children` moved one
+/// level below it.
+pub(crate) fn tree(page: Page, mut children: Vec<PageTree>) -> PageTree {
+  fn deepen(node: &mut PageTree) {
+    node.depth += 1;
+    node.children.iter_mut().for_each(deepen);
+  }
+  children.iter_mut().for_each(deepen);
+  PageTree {
+    page,
+    children,
+    depth: 0,
+    exported: true,
+  }
+}
+
+/// A converted page with its downloaded images, given as relative path and
+/// content.
+pub(crate) fn processed_page(filename: &str, content: &str, images: &[(&str, &[u8])]) -> ProcessedPage {
+  ProcessedPage {
+    filename: filename.to_string(),
+    content: content.to_string(),
+    images: images
+      .iter()
+      .map(|(path, content)| AssetData {
+        relative_path: PathBuf::from(path),
+        content: AssetContent::Bytes(content.to_vec()),
+      })
+      .collect(),
+    ..Default::default()
+  }
+}
@@ -0,0 +1,110 @@
+//! Display names for user mentions.
+//!
+//! The converters render mentions as `@user:<account-id>` because storage
+//! format only stores the account ID. When enabled, [`UserResolver`] looks up
+//! each mentioned account once through the Confluence API and rewrites the
+//! mentions to `@Display Name`. Lookups are cached for the whole export, and a
+//! failed lookup leaves the mention unchanged.
+
+use std::collections::HashMap;
+
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::confluence::ConfluenceApi;
+
+/// Prefix the converters emit for user mentions.
+const MENTION_PREFIX: &str = "@user:";
+
+/// Resolves and caches display names of mentioned users.
+#[derive(Debug, Default)]
+pub struct UserResolver {
+  cache: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl UserResolver {
+  /// Create a resolver with an empty cache.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Replace `@user:<account-id>` mentions with the users' display names.
+  ///
+  /// # Arguments
+  /// * `client` - API used to look up users not yet in the cache.
+  /// * `content` - Converted page content.
+  ///
+  /// # Returns
+  /// The content with every resolvable mention rewritten to `@Display Name`.
+  pub async fn apply(&self, client: &dyn ConfluenceApi, content: &str) -> String {
+    let mentions = find_mentions(content);
+    if mentions.is_empty() {
+      return content.to_string();
+    }
+
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end, account_id) in mentions {
+      let Some(name) = self.display_name(client, account_id).await else {
+        continue;
+      };
+      result.push_str(&content[last..start]);
+      result.push('@');
+      result.push_str(&name);
+      last = end;
+    }
+    result.push_str(&content[last..]);
+    result
+  }
+
+  async fn display_name(&self, client: &dyn ConfluenceApi, account_id: &str) -> Option<String> {
+    if let Some(cached) = self.cache.lock().await.get(account_id) {
+      return cached.clone();
+    }
+
+    let name = match client.get_user(account_id).await {
+      Ok(user) => Some(user.display_name).filter(|name| !name.trim().is_empty()),
+      Err(err) => {
+        debug!("Failed to resolve user {account_id}: {err:#}");
+        None
+      }
+    };
+    self.cache.lock().await.insert(account_id.to_string(), name.clone());
+    name
+  }
+}
+
+/// Byte ranges and account IDs of the mentions in `content`.
+fn find_mentions(content: &str) -> Vec<(usize, usize, &str)> {
+  let mut mentions = Vec::new();
+  let mut offset = 0;
+
+  while let Some(position) = content[offset..].find(MENTION_PREFIX) {
+    let start = offset + position;
+    let id_start = start + MENTION_PREFIX.len();
+    let id_len = content[id_start..]
+      .bytes()
+      .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b':' | b'-' | b'_'))
+      .count();
+    let end = id_start + id_len;
+    if id_len > 0 {
+      mentions.push((start, end, &content[id_start..end]));
+    }
+    offset = end;
+  }
+  mentions
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_find_mentions() {
+    let content = "Owner: @user:557058:abc-123, reviewer @user:42. Not @user: or email@user.com";
+
+    let mentions: Vec<_> = find_mentions(content).into_iter().map(|(_, _, id)| id).collect();
+
+    assert_eq!(mentions, ["557058:abc-123", "42"]);
+  }
+}
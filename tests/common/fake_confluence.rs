@@ -6,16 +6,20 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use confluence_dl::confluence::{Attachment, ConfluenceApi, Page, UserInfo};
+use confluence_dl::confluence::{Attachment, ConfluenceApi, ContentState, Page, Space, UserInfo};
+use confluence_dl::{Error, Result};
 
 use crate::common::fixtures;
 
 /// A fake Confluence client that returns predefined responses for testing
 pub struct FakeConfluenceClient {
   pages: HashMap<String, Page>,
+  page_versions: HashMap<(String, u32), Page>,
   attachments: HashMap<String, Vec<Attachment>>,
+  content_states: HashMap<String, ContentState>,
+  users: HashMap<String, UserInfo>,
+  spaces: HashMap<String, Space>,
   child_pages: HashMap<String, Vec<String>>,
   auth_should_succeed: bool,
 }
@@ -25,7 +29,11 @@ impl FakeConfluenceClient {
   pub fn new() -> Self {
     Self {
       pages: HashMap::new(),
+      page_versions: HashMap::new(),
       attachments: HashMap::new(),
+      content_states: HashMap::new(),
+      users: HashMap::new(),
+      spaces: HashMap::new(),
       child_pages: HashMap::new(),
       auth_should_succeed: true,
     }
@@ -58,6 +66,12 @@ impl FakeConfluenceClient {
     self.pages.insert(page_id.to_string(), page);
   }
 
+  /// Add a historical version of a page
+  #[allow(dead_code)]
+  pub fn add_page_version(&mut self, page_id: &str, version: u32, page: Page) {
+    self.page_versions.insert((page_id.to_string(), version), page);
+  }
+
   /// Configure whether authentication should succeed
   pub fn set_auth_success(&mut self, should_succeed: bool) {
     self.auth_should_succeed = should_succeed;
@@ -69,6 +83,40 @@ impl FakeConfluenceClient {
     self.attachments.insert(page_id.to_string(), attachments);
   }
 
+  /// Set the content state of a page
+  #[allow(dead_code)]
+  pub fn set_content_state(&mut self, page_id: &str, name: &str) {
+    self.content_states.insert(
+      page_id.to_string(),
+      ContentState {
+        name: name.to_string(),
+        color: None,
+      },
+    );
+  }
+
+  /// Add a user that can be looked up by account ID
+  #[allow(dead_code)]
+  pub fn add_user(&mut self, account_id: &str, display_name: &str) {
+    self.users.insert(
+      account_id.to_string(),
+      UserInfo {
+        account_id: account_id.to_string(),
+        email: None,
+        display_name: display_name.to_string(),
+        public_name: None,
+      },
+    );
+  }
+
+  /// Add a space that can be looked up by key
+  #[allow(dead_code)]
+  pub fn add_space_from_json(&mut self, json: serde_json::Value) {
+    if let Ok(space) = serde_json::from_value::<Space>(json) {
+      self.spaces.insert(space.key.clone(), space);
+    }
+  }
+
   /// Add child pages for a parent page
   #[allow(dead_code)]
   pub fn add_child_pages(&mut self, parent_id: &str, child_ids: Vec<String>) {
@@ -89,7 +137,15 @@ impl ConfluenceApi for FakeConfluenceClient {
       .pages
       .get(page_id)
       .cloned()
-      .ok_or_else(|| anyhow!("No content found with id: {}", page_id))
+      .ok_or_else(|| Error::from_status(404, format!("No content found with id: {}", page_id)))
+  }
+
+  async fn get_page_version(&self, page_id: &str, version: u32) -> Result<Page> {
+    self
+      .page_versions
+      .get(&(page_id.to_string(), version))
+      .cloned()
+      .ok_or_else(|| Error::from_status(404, format!("No version {} found for content id: {}", version, page_id)))
   }
 
   async fn get_child_pages(&self, page_id: &str) -> Result<Vec<Page>> {
@@ -105,6 +161,10 @@ impl ConfluenceApi for FakeConfluenceClient {
     Ok(children)
   }
 
+  async fn get_content_state(&self, page_id: &str) -> Result<Option<ContentState>> {
+    Ok(self.content_states.get(page_id).cloned())
+  }
+
   async fn get_attachments(&self, page_id: &str) -> Result<Vec<Attachment>> {
     Ok(self.attachments.get(page_id).cloned().unwrap_or_default())
   }
@@ -122,6 +182,32 @@ impl ConfluenceApi for FakeConfluenceClient {
     Ok(b"fake image data".to_vec())
   }
 
+  async fn get_user(&self, account_id: &str) -> Result<UserInfo> {
+    self
+      .users
+      .get(account_id)
+      .cloned()
+      .ok_or_else(|| Error::from_status(404, format!("User not found: {account_id}")))
+  }
+
+  async fn get_space(&self, space_key: &str) -> Result<Space> {
+    self
+      .spaces
+      .get(space_key)
+      .cloned()
+      .ok_or_else(|| Error::from_status(404, format!("Space not found: {space_key}")))
+  }
+
+  async fn find_page(&self, space_key: &str, title: &str) -> Result<Option<Page>> {
+    Ok(
+      self
+        .pages
+        .values()
+        .find(|page| page.title == title && page.space.as_ref().is_some_and(|space| space.key == space_key))
+        .cloned(),
+    )
+  }
+
   async fn test_auth(&self) -> Result<UserInfo> {
     if self.auth_should_succeed {
       Ok(UserInfo {
@@ -131,7 +217,7 @@ impl ConfluenceApi for FakeConfluenceClient {
         public_name: Some("Test User".to_string()),
       })
     } else {
-      Err(anyhow!("Authentication failed with status: 401"))
+      Err(Error::from_status(401, "Authentication failed with status: 401"))
     }
   }
 }
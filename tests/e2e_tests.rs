@@ -7,6 +7,7 @@ mod common;
 
 use common::fake_confluence::FakeConfluenceClient;
 use common::fixtures;
+use confluence_dl::Error;
 use confluence_dl::confluence::ConfluenceApi;
 use confluence_dl::markdown::{self, MarkdownOptions};
 use insta::assert_snapshot;
@@ -116,6 +117,13 @@ async fn test_fetch_nonexistent_page() {
   let err = result.unwrap_err();
   assert!(err.to_string().contains("No content found"));
   assert!(err.to_string().contains("999999"));
+  assert_eq!(err.status(), Some(404));
+}
+
+#[test]
+fn test_convert_malformed_storage() {
+  let err = markdown::storage_to_markdown_with_options("<p>Unclosed", &MarkdownOptions::default()).unwrap_err();
+  assert!(matches!(err, Error::Parse { .. }), "{err:?}");
 }
 
 #[tokio::test]
@@ -143,6 +151,7 @@ async fn test_authentication_failure() {
   let err = result.unwrap_err();
   assert!(err.to_string().contains("Authentication failed"));
   assert!(err.to_string().contains("401"));
+  assert!(matches!(err, Error::Auth { status: Some(401), .. }), "{err:?}");
 }
 
 #[tokio::test]
@@ -203,6 +212,39 @@ async fn test_page_space_metadata() {
   assert_eq!(space.space_type, "global");
 }
 
+#[tokio::test]
+async fn test_space_readme_describes_page_space() {
+  use confluence_dl::space_readme::SpaceReadme;
+
+  let mut client = FakeConfluenceClient::with_sample_pages();
+  client.add_space_from_json(serde_json::json!({
+    "key": "DEV",
+    "name": "Developer Portal",
+    "type": "global",
+    "description": {"plain": {"value": "APIs and SDKs.", "representation": "plain"}},
+    "icon": {"path": "/images/logo/default-space-logo.svg", "isDefault": true}
+  }));
+
+  let page = client.get_page("789012").await.unwrap();
+  let space = client.get_space(&page.space.unwrap().key).await.unwrap();
+  assert!(space.icon.as_ref().is_some_and(|icon| icon.is_default));
+
+  let readme = SpaceReadme {
+    space: &space,
+    base_url: "https://example.atlassian.net",
+    root_title: &page.title,
+    root_path: "Root.md",
+    page_count: 3,
+    exported_at: chrono::Utc::now(),
+    icon_path: None,
+    format: confluence_dl::format::OutputFormat::Markdown,
+  }
+  .render();
+  assert!(readme.starts_with("# Developer Portal\n\nAPIs and SDKs.\n"));
+  assert!(readme.contains("- **Pages exported:** 3"));
+  assert!(client.get_space("NOPE").await.is_err());
+}
+
 #[tokio::test]
 async fn test_storage_format_representation() {
   let client = FakeConfluenceClient::with_sample_pages();
@@ -253,7 +295,7 @@ use tempfile::TempDir;
 #[tokio::test]
 async fn test_image_download_workflow() {
   use confluence_dl::confluence::{Attachment, AttachmentLinks};
-  use confluence_dl::images;
+  use confluence_dl::images::{self, ImageQuality};
 
   // Create a temporary directory for the test
   let temp_dir = TempDir::new().unwrap();
@@ -290,9 +332,17 @@ async fn test_image_download_workflow() {
   assert!(!image_refs.is_empty(), "Should find images in the page");
 
   // Download images
-  let filename_map = images::download_images(&client, "456789", &image_refs, output_path, "images", false)
-    .await
-    .unwrap();
+  let filename_map = images::download_images(
+    &client,
+    "456789",
+    &image_refs,
+    output_path,
+    "images",
+    false,
+    ImageQuality::Original,
+  )
+  .await
+  .unwrap();
 
   // Verify images were "downloaded" (fake client creates empty files)
   assert!(!filename_map.is_empty(), "Should have downloaded images");
@@ -313,6 +363,25 @@ async fn test_image_download_workflow() {
     updated_markdown.contains("](images/"),
     "Links should be updated to images directory: {updated_markdown}"
   );
+
+  // Thumbnails are saved next to the originals under their own name
+  let thumbnails = images::download_images(
+    &client,
+    "456789",
+    &image_refs,
+    output_path,
+    "images",
+    false,
+    ImageQuality::Thumbnail,
+  )
+  .await
+  .unwrap();
+  let thumbnail = &thumbnails["architecture.png"];
+  assert_eq!(
+    thumbnail,
+    &std::path::PathBuf::from("images/architecture-thumbnail.png")
+  );
+  assert!(output_path.join(thumbnail).exists());
 }
 
 #[tokio::test]
@@ -383,6 +452,381 @@ async fn test_attachment_download_workflow() {
   );
 }
 
+#[tokio::test]
+async fn test_view_only_images_are_downloaded() {
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use serde_json::json;
+
+  let mut client = FakeConfluenceClient::new();
+  client.add_page_from_json(
+    "777777",
+    json!({
+      "id": "777777",
+      "type": "page",
+      "status": "current",
+      "title": "Legacy Diagram",
+      "body": {
+        "storage": {
+          "value": "<p>See the diagram below.</p>",
+          "representation": "storage"
+        },
+        "view": {
+          "value": r#"<p>See the diagram below.</p><p><img class="confluence-embedded-image" src="/wiki/download/attachments/777777/flow.png?version=1&amp;api=v2" alt="Flow"></p>"#,
+          "representation": "view"
+        }
+      }
+    }),
+  );
+
+  let page = client.get_page("777777").await.unwrap();
+  let options = ProcessOptions {
+    download_images: true,
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+
+  assert_eq!(processed.images.len(), 1);
+  assert_eq!(
+    processed.images[0].relative_path,
+    std::path::PathBuf::from("images/flow.png")
+  );
+  assert!(
+    processed.content.contains("![Flow](images/flow.png)"),
+    "View-only image should be linked: {}",
+    processed.content
+  );
+  assert_eq!(
+    processed
+      .link_map
+      .images
+      .get("/wiki/download/attachments/777777/flow.png?version=1&api=v2"),
+    Some(&"images/flow.png".to_string())
+  );
+  assert!(processed.link_map.attachments.is_empty());
+}
+
+#[tokio::test]
+async fn test_legacy_wiki_page_is_converted() {
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use serde_json::json;
+
+  let mut client = FakeConfluenceClient::new();
+  client.add_page_from_json(
+    "888888",
+    json!({
+      "id": "888888",
+      "type": "page",
+      "status": "current",
+      "title": "Ancient Runbook",
+      "body": {
+        "storage": {
+          "value": "h1. Restart\n# Stop the service\n# Run {{start.sh}}\n\nSee [the wiki|https://example.com].",
+          "representation": "wiki"
+        }
+      }
+    }),
+  );
+
+  let page = client.get_page("888888").await.unwrap();
+  let processed = process_page(&client, &page, &ProcessOptions::default()).await.unwrap();
+
+  assert_eq!(
+    processed.content,
+    "# Restart\n\n1. Stop the service\n2. Run `start.sh`\n\nSee [the wiki](https://example.com).\n"
+  );
+
+  let asciidoc = ProcessOptions {
+    format: confluence_dl::format::OutputFormat::AsciiDoc,
+    ..Default::default()
+  };
+  let error = process_page(&client, &page, &asciidoc).await.unwrap_err();
+  assert!(error.to_string().contains("only has legacy wiki markup"));
+}
+
+#[tokio::test]
+async fn test_page_links_are_recorded_in_link_map() {
+  use std::sync::Arc;
+
+  use confluence_dl::links::{ExportScope, LinkContext};
+  use confluence_dl::markdown::MarkdownOptions;
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use serde_json::json;
+
+  let page_json = |id: &str, title: &str, storage: &str| {
+    json!({
+      "id": id,
+      "type": "page",
+      "status": "current",
+      "title": title,
+      "body": {"storage": {"value": storage, "representation": "storage"}},
+      "space": {"key": "DOCS", "name": "Docs", "type": "global"}
+    })
+  };
+  let mut client = FakeConfluenceClient::new();
+  client.add_page_from_json("1", page_json("1", "Guide", "<p>Guide</p>"));
+  client.add_page_from_json(
+    "2",
+    page_json(
+      "2",
+      "Setup",
+      r#"<p>Back to <ac:link><ri:page ri:content-title="Guide" /></ac:link></p>"#,
+    ),
+  );
+  let guide = client.get_page("1").await.unwrap();
+  let page = client.get_page("2").await.unwrap();
+
+  let mut scope = ExportScope::default();
+  scope.insert_with_path(&guide, "Guide.md");
+  scope.insert_with_path(&page, "Guide/Setup.md");
+  let options = ProcessOptions {
+    markdown_options: MarkdownOptions {
+      links: LinkContext {
+        scope: Some(Arc::new(scope)),
+        ..LinkContext::new("https://example.atlassian.net")
+      },
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+
+  assert!(
+    processed.content.contains("[Guide](../Guide.md)"),
+    "{}",
+    processed.content
+  );
+  assert_eq!(
+    processed
+      .link_map
+      .pages
+      .get("https://example.atlassian.net/wiki/display/DOCS/Guide"),
+    Some(&"../Guide.md".to_string())
+  );
+}
+
+#[tokio::test]
+async fn test_whiteboard_is_written_as_stub() {
+  use confluence_dl::links::LinkContext;
+  use confluence_dl::markdown::MarkdownOptions;
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use serde_json::json;
+
+  let mut client = FakeConfluenceClient::new();
+  client.add_page_from_json(
+    "778899",
+    json!({
+      "id": "778899",
+      "type": "whiteboard",
+      "status": "current",
+      "title": "Architecture sketch",
+      "_links": {"webui": "/spaces/ENG/whiteboard/778899"}
+    }),
+  );
+
+  let page = client.get_page("778899").await.unwrap();
+  let options = ProcessOptions {
+    download_images: true,
+    markdown_options: MarkdownOptions {
+      links: LinkContext::new("https://example.atlassian.net"),
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+
+  assert_eq!(processed.filename, "Architecture sketch");
+  assert!(
+    processed
+      .content
+      .contains("This Confluence whiteboard has no content that can be converted.")
+  );
+  assert!(
+    processed
+      .content
+      .contains("(https://example.atlassian.net/wiki/spaces/ENG/whiteboard/778899)")
+  );
+  assert!(processed.images.is_empty());
+}
+
+#[tokio::test]
+async fn test_content_state_rendered_as_badge_and_front_matter() {
+  use confluence_dl::format::ContentStateDisplay;
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+
+  let mut client = FakeConfluenceClient::with_sample_pages();
+  client.set_content_state("123456", "In review");
+  let page = client.get_page("123456").await.unwrap();
+
+  let options = ProcessOptions {
+    content_state: ContentStateDisplay::Both,
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+
+  assert!(
+    processed.content.starts_with("**Status:** In review\n\n"),
+    "Badge should lead the page body: {}",
+    processed.content
+  );
+  assert_eq!(processed.front_matter.get("content_state"), Some("In review"));
+
+  let options = ProcessOptions::default();
+  let processed = process_page(&client, &page, &options).await.unwrap();
+  assert!(!processed.content.contains("Status:"));
+  assert!(processed.front_matter.get("content_state").is_none());
+}
+
+#[tokio::test]
+async fn test_author_metadata_in_front_matter() {
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use serde_json::json;
+
+  let mut client = FakeConfluenceClient::new();
+  client.add_page_from_json(
+    "888889",
+    json!({
+      "id": "888889",
+      "type": "page",
+      "status": "current",
+      "title": "Runbook",
+      "body": { "storage": { "value": "<p>Steps</p>", "representation": "storage" } },
+      "history": { "createdBy": { "accountId": "557058:ana", "displayName": "Ana Lima" } },
+      "version": {
+        "number": 4,
+        "by": { "accountId": "557058:ben", "displayName": "Ben Ode", "email": "ben@example.com" }
+      }
+    }),
+  );
+  let page = client.get_page("888889").await.unwrap();
+  assert!(page.is_authored_by("BEN@example.com"));
+  assert!(page.is_authored_by("Ana Lima"));
+  assert!(!page.is_authored_by("cara@example.com"));
+
+  let options = ProcessOptions {
+    author_metadata: true,
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+  assert_eq!(processed.front_matter.get("created_by"), Some("Ana Lima"));
+  assert_eq!(processed.front_matter.get("last_modified_by"), Some("Ben Ode"));
+
+  let processed = process_page(&client, &page, &ProcessOptions::default()).await.unwrap();
+  assert!(processed.front_matter.get("created_by").is_none());
+}
+
+#[tokio::test]
+async fn test_resolve_users_rewrites_mentions() {
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use confluence_dl::users::UserResolver;
+  use serde_json::json;
+
+  let mut client = FakeConfluenceClient::new();
+  client.add_user("557058:jane", "Jane Doe");
+  client.add_page_from_json(
+    "888888",
+    json!({
+      "id": "888888",
+      "type": "page",
+      "status": "current",
+      "title": "Owners",
+      "body": {
+        "storage": {
+          "value": r#"<p>Ask <ac:link><ri:user ri:account-id="557058:jane" /></ac:link> or <ac:link><ri:user ri:account-id="unknown" /></ac:link>.</p>"#,
+          "representation": "storage"
+        }
+      }
+    }),
+  );
+
+  let page = client.get_page("888888").await.unwrap();
+  let users = UserResolver::new();
+  let options = ProcessOptions {
+    users: Some(&users),
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+
+  assert_eq!(processed.content.trim(), "Ask @Jane Doe or @user:unknown.");
+}
+
+#[tokio::test]
+async fn test_resolve_includes_inlines_content() {
+  use confluence_dl::includes::IncludeResolver;
+  use confluence_dl::processed_page::{ProcessOptions, process_page};
+  use serde_json::json;
+
+  let mut client = FakeConfluenceClient::new();
+  let mut add_page = |id: &str, title: &str, storage: &str| {
+    client.add_page_from_json(
+      id,
+      json!({
+        "id": id,
+        "type": "page",
+        "status": "current",
+        "title": title,
+        "space": {"key": "DOCS", "name": "Docs", "type": "global"},
+        "body": {"storage": {"value": storage, "representation": "storage"}}
+      }),
+    );
+  };
+  add_page(
+    "1",
+    "Runbook",
+    concat!(
+      r#"<ac:structured-macro ac:name="include"><ac:parameter ac:name=""><ac:link><ri:page ri:content-title="Setup" /></ac:link></ac:parameter></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="excerpt-include"><ac:parameter ac:name=""><ac:link><ri:page ri:content-title="Glossary" /></ac:link></ac:parameter></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="multiexcerpt-include"><ac:parameter ac:name="MultiExcerptName">limits</ac:parameter>"#,
+      r#"<ac:parameter ac:name="PageWithExcerpt"><ac:link><ri:page ri:content-title="Glossary" /></ac:link></ac:parameter></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="include"><ac:parameter ac:name=""><ac:link><ri:page ri:content-title="Missing" /></ac:link></ac:parameter></ac:structured-macro>"#,
+    ),
+  );
+  add_page(
+    "2",
+    "Setup",
+    concat!(
+      "<p>Install the CLI.</p>",
+      r#"<ac:structured-macro ac:name="include"><ac:parameter ac:name=""><ac:link><ri:page ri:content-title="Runbook" /></ac:link></ac:parameter></ac:structured-macro>"#,
+    ),
+  );
+  add_page(
+    "3",
+    "Glossary",
+    concat!(
+      r#"<ac:structured-macro ac:name="excerpt"><ac:rich-text-body><p>Terms used in <strong>runbooks</strong>.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="multiexcerpt"><ac:parameter ac:name="MultiExcerptName">other</ac:parameter><ac:rich-text-body><p>Other.</p></ac:rich-text-body></ac:structured-macro>"#,
+      r#"<ac:structured-macro ac:name="multiexcerpt"><ac:parameter ac:name="MultiExcerptName">limits</ac:parameter><ac:rich-text-body><p>100 requests per minute.</p></ac:rich-text-body></ac:structured-macro>"#,
+    ),
+  );
+
+  let page = client.get_page("1").await.unwrap();
+  let placeholders = process_page(&client, &page, &ProcessOptions::default()).await.unwrap();
+  assert!(placeholders.content.contains("Content included from [[Setup]]."));
+  assert!(
+    placeholders
+      .content
+      .contains("Excerpt `limits` included from [[Glossary]].")
+  );
+
+  let includes = IncludeResolver::new();
+  let options = ProcessOptions {
+    includes: Some(&includes),
+    ..Default::default()
+  };
+  let processed = process_page(&client, &page, &options).await.unwrap();
+
+  insta::assert_snapshot!(processed.content, @r"
+  Install the CLI.
+
+  > **Included content:** Content included from [[Runbook]]. Not expanded because the include is circular.
+
+  Terms used in **runbooks**.
+
+  100 requests per minute.
+
+  > **Included content:** Content included from [[Missing]].
+  ");
+}
+
 #[tokio::test]
 async fn test_get_child_pages_empty() {
   let client = FakeConfluenceClient::with_sample_pages();
@@ -414,12 +858,14 @@ async fn test_get_child_pages_with_children() {
 
 #[tokio::test]
 async fn test_page_tree_single_page() {
-  use confluence_dl::confluence::get_page_tree;
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
 
   let client = FakeConfluenceClient::with_sample_pages();
 
   // Build tree for page with no children
-  let tree = get_page_tree(&client, "123456", None).await.unwrap();
+  let tree = get_page_tree(&client, "123456", None, &PageFilter::default(), 4)
+    .await
+    .unwrap();
 
   assert_eq!(tree.page.id, "123456");
   assert_eq!(tree.page.title, "Getting Started Guide");
@@ -429,7 +875,7 @@ async fn test_page_tree_single_page() {
 
 #[tokio::test]
 async fn test_page_tree_with_children() {
-  use confluence_dl::confluence::get_page_tree;
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
 
   let mut client = FakeConfluenceClient::with_sample_pages();
 
@@ -439,7 +885,9 @@ async fn test_page_tree_with_children() {
   client.add_child_pages("123456", vec!["111111".to_string(), "222222".to_string()]);
 
   // Build tree
-  let tree = get_page_tree(&client, "123456", None).await.unwrap();
+  let tree = get_page_tree(&client, "123456", None, &PageFilter::default(), 4)
+    .await
+    .unwrap();
 
   assert_eq!(tree.page.title, "Getting Started Guide");
   assert_eq!(tree.depth, 0);
@@ -458,7 +906,7 @@ async fn test_page_tree_with_children() {
 
 #[tokio::test]
 async fn test_page_tree_with_grandchildren() {
-  use confluence_dl::confluence::get_page_tree;
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
 
   let mut client = FakeConfluenceClient::with_sample_pages();
 
@@ -469,7 +917,9 @@ async fn test_page_tree_with_grandchildren() {
   client.add_child_pages("111111", vec!["333333".to_string()]);
 
   // Build tree with unlimited depth
-  let tree = get_page_tree(&client, "123456", None).await.unwrap();
+  let tree = get_page_tree(&client, "123456", None, &PageFilter::default(), 4)
+    .await
+    .unwrap();
 
   assert_eq!(tree.depth, 0);
   assert_eq!(tree.children.len(), 1);
@@ -489,7 +939,7 @@ async fn test_page_tree_with_grandchildren() {
 
 #[tokio::test]
 async fn test_page_tree_max_depth_limit() {
-  use confluence_dl::confluence::get_page_tree;
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
 
   let mut client = FakeConfluenceClient::with_sample_pages();
 
@@ -500,7 +950,9 @@ async fn test_page_tree_max_depth_limit() {
   client.add_child_pages("111111", vec!["333333".to_string()]);
 
   // Build tree with max_depth = 1 (should stop at children, not grandchildren)
-  let tree = get_page_tree(&client, "123456", Some(1)).await.unwrap();
+  let tree = get_page_tree(&client, "123456", Some(1), &PageFilter::default(), 4)
+    .await
+    .unwrap();
 
   assert_eq!(tree.depth, 0);
   assert_eq!(tree.children.len(), 1);
@@ -519,7 +971,7 @@ async fn test_page_tree_max_depth_limit() {
 
 #[tokio::test]
 async fn test_page_tree_depth_zero() {
-  use confluence_dl::confluence::get_page_tree;
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
 
   let mut client = FakeConfluenceClient::with_sample_pages();
 
@@ -528,7 +980,9 @@ async fn test_page_tree_depth_zero() {
   client.add_child_pages("123456", vec!["111111".to_string()]);
 
   // Build tree with max_depth = 0 (should include only root page)
-  let tree = get_page_tree(&client, "123456", Some(0)).await.unwrap();
+  let tree = get_page_tree(&client, "123456", Some(0), &PageFilter::default(), 4)
+    .await
+    .unwrap();
 
   assert_eq!(tree.depth, 0);
   assert!(tree.children.is_empty(), "Should not fetch children when max_depth=0");
@@ -536,7 +990,7 @@ async fn test_page_tree_depth_zero() {
 
 #[tokio::test]
 async fn test_page_tree_circular_reference_detection() {
-  use confluence_dl::confluence::get_page_tree;
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
 
   let mut client = FakeConfluenceClient::with_sample_pages();
 
@@ -547,7 +1001,7 @@ async fn test_page_tree_circular_reference_detection() {
 
   // The function should successfully build the tree but skip the circular
   // reference (it logs a warning and continues with other children)
-  let result = get_page_tree(&client, "123456", None).await;
+  let result = get_page_tree(&client, "123456", None, &PageFilter::default(), 4).await;
 
   assert!(result.is_ok(), "Should handle circular reference gracefully");
   let tree = result.unwrap();
@@ -562,6 +1016,48 @@ async fn test_page_tree_circular_reference_detection() {
   assert!(child.children.is_empty(), "Circular reference should be skipped");
 }
 
+#[tokio::test]
+async fn test_crawl_linked_pages() {
+  use confluence_dl::confluence::{PageFilter, get_page_tree};
+  use confluence_dl::link_crawl::crawl_linked_pages;
+
+  fn linking_page(id: &str, title: &str, linked_title: &str) -> serde_json::Value {
+    serde_json::json!({
+      "id": id,
+      "type": "page",
+      "status": "current",
+      "title": title,
+      "body": {
+        "storage": {
+          "value": format!(r#"<p><ac:link><ri:page ri:content-title="{linked_title}" /></ac:link></p>"#),
+          "representation": "storage"
+        }
+      },
+      "space": { "key": "DOCS", "name": "Documentation", "type": "global" }
+    })
+  }
+
+  // Links form a cycle: Alpha -> Beta -> Gamma -> Alpha
+  let mut client = FakeConfluenceClient::new();
+  client.add_page_from_json("1", linking_page("1", "Alpha", "Beta"));
+  client.add_page_from_json("2", linking_page("2", "Beta", "Gamma"));
+  client.add_page_from_json("3", linking_page("3", "Gamma", "Alpha"));
+
+  let tree = get_page_tree(&client, "1", Some(0), &PageFilter::default(), 4)
+    .await
+    .unwrap();
+  let trees = std::slice::from_ref(&tree);
+  let titles = |linked: Vec<confluence_dl::confluence::PageTree>| -> Vec<String> {
+    linked.into_iter().map(|tree| tree.page.title).collect()
+  };
+
+  assert_eq!(titles(crawl_linked_pages(&client, trees, 1, 4).await), ["Beta"]);
+  assert_eq!(
+    titles(crawl_linked_pages(&client, trees, 5, 4).await),
+    ["Beta", "Gamma"]
+  );
+}
+
 #[tokio::test]
 async fn test_convert_comprehensive_features_page_to_markdown() {
   let mut client = FakeConfluenceClient::with_sample_pages();
@@ -779,3 +1275,16 @@ async fn test_markdown_conversion_preserves_structure() {
 
   assert_snapshot!(markdown);
 }
+
+#[test]
+fn test_lint_fix_is_idempotent_on_demo_export() {
+  let options = MarkdownOptions {
+    lint_fix: true,
+    ..Default::default()
+  };
+  let fixed = markdown::storage_to_markdown_with_options(DEMO_RAW_XML, &options).unwrap();
+
+  assert_eq!(markdown::lint::autofix(&fixed), fixed);
+  assert!(!fixed.contains("\n\n\n"));
+  assert!(fixed.lines().all(|line| line == line.trim_end()));
+}
@@ -0,0 +1,104 @@
+//! Golden tests for Markdown and AsciiDoc converter parity
+//!
+//! Every fixture in `tests/resources/parity` is converted with both backends.
+//! The outputs are snapshotted, and each fixture lists the content both
+//! backends must carry over so a construct handled by one converter cannot
+//! silently disappear from the other.
+
+use confluence_dl::asciidoc::{self, AsciiDocOptions};
+use confluence_dl::markdown::{self, MarkdownOptions};
+use insta::assert_snapshot;
+
+/// A storage-format fixture shared by both converters.
+struct Fixture {
+  name: &'static str,
+  storage: &'static str,
+  /// Text that must appear in both outputs.
+  expected: &'static [&'static str],
+}
+
+const FIXTURES: &[Fixture] = &[
+  Fixture {
+    name: "admonitions",
+    storage: include_str!("./resources/parity/admonitions.xml"),
+    expected: &[
+      "Exports run nightly.",
+      "Before you upgrade",
+      "database",
+      "--dry-run",
+      "Tokens expire after 90 days.",
+      "Troubleshooting",
+      "/var/log/app",
+    ],
+  },
+  Fixture {
+    name: "code",
+    storage: include_str!("./resources/parity/code.xml"),
+    expected: &[
+      "bash",
+      "cargo install confluence-dl",
+      "python",
+      "    return f\"Hello, {name}!\"",
+      "plain text block",
+    ],
+  },
+  Fixture {
+    name: "tables",
+    storage: include_str!("./resources/parity/tables.xml"),
+    expected: &["Service", "Owner", "Platform", "routing", "Retries", "Backoff"],
+  },
+  Fixture {
+    name: "task_lists",
+    storage: include_str!("./resources/parity/task_lists.xml"),
+    expected: &["[x] Tag the release", "[ ] Publish release notes"],
+  },
+  Fixture {
+    name: "images_attachments",
+    storage: include_str!("./resources/parity/images_attachments.xml"),
+    expected: &[
+      "architecture.png",
+      "Architecture diagram",
+      "https://example.com/logo.png",
+      "runbook.pdf",
+      "metrics.csv",
+    ],
+  },
+];
+
+fn render_both(fixture: &Fixture) -> (String, String) {
+  let markdown = markdown::storage_to_markdown_with_options(fixture.storage, &MarkdownOptions::default())
+    .unwrap_or_else(|e| panic!("{}: Markdown conversion failed: {e}", fixture.name));
+  let asciidoc = asciidoc::storage_to_asciidoc_with_options(fixture.storage, &AsciiDocOptions::default())
+    .unwrap_or_else(|e| panic!("{}: AsciiDoc conversion failed: {e}", fixture.name));
+  (markdown, asciidoc)
+}
+
+#[test]
+fn test_parity_fixtures_keep_shared_content() {
+  for fixture in FIXTURES {
+    let (markdown, asciidoc) = render_both(fixture);
+    for (backend, output) in [("Markdown", &markdown), ("AsciiDoc", &asciidoc)] {
+      for expected in fixture.expected {
+        assert!(
+          output.contains(expected),
+          "{}: {backend} output is missing {expected:?}:\n{output}",
+          fixture.name
+        );
+      }
+      assert!(
+        !output.contains("ac:") && !output.contains("ri:"),
+        "{}: {backend} output leaks storage markup:\n{output}",
+        fixture.name
+      );
+    }
+  }
+}
+
+#[test]
+fn test_parity_fixture_snapshots() {
+  for fixture in FIXTURES {
+    let (markdown, asciidoc) = render_both(fixture);
+    assert_snapshot!(format!("{}_markdown", fixture.name), markdown);
+    assert_snapshot!(format!("{}_asciidoc", fixture.name), asciidoc);
+  }
+}